@@ -11,8 +11,14 @@
 //! ```
 //!
 //! Based on ReConcile (ACL 2024) confidence-weighted voting.
+//!
+//! When `run_debate` is given a Queen provider, each round is analyzed with
+//! schema-constrained output ([`analyze_round`]) instead of the confidence
+//! heuristic and position counting.
 
 use crate::a2a::types::*;
+use crate::brain::provider::structured::complete_structured;
+use crate::brain::provider::{self as llm, LLMRequest, Provider, ProviderError, ResponseFormat};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -306,7 +312,7 @@ impl DebateSession {
             avg_confidence,
             agreement_points,
             contention_points,
-            blind_spots: vec![], // filled by LLM in production
+            blind_spots: vec![], // only the Queen's analysis finds these
             consensus_reached,
         }
     }
@@ -319,6 +325,18 @@ impl DebateSession {
         responses: Vec<BeeResponse>,
     ) {
         let consensus = Self::analyze_consensus(&responses, self.config.consensus_threshold);
+        self.record_analyzed_round(round_number, prompt, responses, consensus);
+    }
+
+    /// Record a completed round whose consensus was already analyzed
+    /// (see [`analyze_round`]).
+    pub fn record_analyzed_round(
+        &mut self,
+        round_number: usize,
+        prompt: String,
+        responses: Vec<BeeResponse>,
+        consensus: ConsensusAnalysis,
+    ) {
         let concluded = consensus.consensus_reached || round_number >= self.config.max_rounds;
 
         self.rounds.push(DebateRound {
@@ -549,13 +567,117 @@ fn extract_confidence(text: &str) -> f64 {
     0.5
 }
 
+/// Response format for the Queen's round analysis.
+pub fn round_analysis_format() -> ResponseFormat {
+    let points = serde_json::json!({"type": "array", "items": {"type": "string"}});
+    ResponseFormat::json_schema(
+        "debate_round_analysis",
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "bees": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "bee_id": {"type": "string"},
+                            "position": {"type": "string"},
+                            "confidence": {"type": "number", "minimum": 0, "maximum": 1},
+                            "key_points": points
+                        },
+                        "required": ["bee_id", "position", "confidence", "key_points"],
+                        "additionalProperties": false
+                    }
+                },
+                "agreement_points": points,
+                "contention_points": points,
+                "blind_spots": points
+            },
+            "required": ["bees", "agreement_points", "contention_points", "blind_spots"],
+            "additionalProperties": false
+        }),
+    )
+}
+
+/// Reply shape of [`round_analysis_format`].
+#[derive(Deserialize)]
+struct RoundAnalysis {
+    bees: Vec<BeeAnalysis>,
+    agreement_points: Vec<String>,
+    contention_points: Vec<String>,
+    blind_spots: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct BeeAnalysis {
+    bee_id: String,
+    position: String,
+    confidence: f64,
+    key_points: Vec<String>,
+}
+
+/// Have the Queen analyze a round's responses with schema-constrained
+/// output. Fills in each Bee's position, confidence and key points, and
+/// returns the round's consensus analysis.
+pub async fn analyze_round(
+    provider: &dyn Provider,
+    model: &str,
+    topic: &str,
+    responses: &mut [BeeResponse],
+    threshold: f64,
+) -> Result<ConsensusAnalysis, ProviderError> {
+    let mut prompt = format!("## Debate Topic\n\n{}\n\n## Responses\n\n", topic);
+    for resp in responses.iter() {
+        prompt.push_str(&format!("### {}\n{}\n\n", resp.bee_id, resp.content));
+    }
+    prompt.push_str(
+        "For each Bee give its position in a few words (the same words for Bees that \
+         agree), its confidence (0.0-1.0) as stated or implied, and its key points. Then \
+         list the points all Bees agree on, the points of contention, and blind spots — \
+         aspects of the topic no Bee addressed.",
+    );
+    let request = LLMRequest::new(model, vec![llm::Message::user(prompt)])
+        .with_system("You are the Queen moderating a Bee Colony debate.")
+        .with_max_tokens(4096);
+
+    let value = complete_structured(provider, request, round_analysis_format()).await?;
+    let analysis: RoundAnalysis = serde_json::from_value(value)
+        .map_err(|e| ProviderError::StructuredOutput(e.to_string()))?;
+
+    for bee in analysis.bees {
+        if let Some(resp) = responses.iter_mut().find(|r| r.bee_id == bee.bee_id) {
+            resp.position = Some(bee.position);
+            resp.confidence = bee.confidence;
+            resp.key_points = bee.key_points;
+        }
+    }
+    let avg_confidence = if responses.is_empty() {
+        0.0
+    } else {
+        responses.iter().map(|r| r.confidence).sum::<f64>() / responses.len() as f64
+    };
+    let consensus_reached = avg_confidence >= threshold && !analysis.agreement_points.is_empty();
+
+    Ok(ConsensusAnalysis {
+        avg_confidence,
+        agreement_points: analysis.agreement_points,
+        contention_points: analysis.contention_points,
+        blind_spots: analysis.blind_spots,
+        consensus_reached,
+    })
+}
+
 /// Run a full multi-round debate across bee endpoints.
 ///
 /// 1. Loads knowledge context from QMD memory if not pre-populated
 /// 2. Sends round prompts to all bee endpoints concurrently via A2A JSON-RPC
-/// 3. Collects responses, checks consensus
+/// 3. Collects responses, checks consensus — through `queen` when given,
+///    falling back to the heuristic if its analysis fails
 /// 4. Repeats or concludes
-pub async fn run_debate(mut config: DebateConfig) -> Result<DebateSession, DebateError> {
+pub async fn run_debate(
+    mut config: DebateConfig,
+    queen: Option<&dyn Provider>,
+) -> Result<DebateSession, DebateError> {
     // Load knowledge context from QMD if not pre-populated
     if config.knowledge_context.is_empty()
         && let Ok(store) = crate::memory::get_store()
@@ -631,7 +753,26 @@ pub async fn run_debate(mut config: DebateConfig) -> Result<DebateSession, Debat
             ));
         }
 
-        session.record_round(round_num, prompt, responses);
+        let threshold = session.config.consensus_threshold;
+        let analysis = match queen {
+            Some(queen) => analyze_round(
+                queen,
+                queen.default_model(),
+                &session.config.topic,
+                &mut responses,
+                threshold,
+            )
+            .await
+            .inspect_err(|e| tracing::warn!("Queen analysis of round {} failed: {}", round_num, e))
+            .ok(),
+            None => None,
+        };
+        match analysis {
+            Some(consensus) => {
+                session.record_analyzed_round(round_num, prompt, responses, consensus)
+            }
+            None => session.record_round(round_num, prompt, responses),
+        }
 
         if session.state == DebateState::Concluded || session.state == DebateState::Exhausted {
             break;
@@ -3,8 +3,10 @@
 //! Manages conversation context including messages, system brain,
 //! and token tracking.
 
+use super::pruning::{self, PruneReport};
 use crate::brain::provider::{ContentBlock, Message, Role};
 use crate::brain::tokenizer;
+use crate::config::CompactionConfig;
 use crate::db::models::Message as DbMessage;
 use std::path::PathBuf;
 use uuid::Uuid;
@@ -29,6 +31,9 @@ pub struct AgentContext {
 
     /// Maximum context tokens
     pub max_tokens: usize,
+
+    /// Token counter for the session's model (cl100k until set)
+    pub tokenizer: tokenizer::Tokenizer,
 }
 
 /// A file tracked in the conversation
@@ -50,16 +55,31 @@ impl AgentContext {
             tracked_files: Vec::new(),
             token_count: 0,
             max_tokens,
+            tokenizer: tokenizer::Tokenizer::default(),
         }
     }
 
+    /// Count with the tokenizer for the session's model and recalculate the
+    /// token count.
+    pub fn with_tokenizer(mut self, tokenizer: tokenizer::Tokenizer) -> Self {
+        self.tokenizer = tokenizer;
+        self.recount();
+        self
+    }
+
     /// Set the system brain
     pub fn with_system_brain(mut self, prompt: String) -> Self {
-        self.token_count += Self::estimate_tokens(&prompt);
+        self.token_count += self.tokenizer.count(&prompt);
         self.system_brain = Some(prompt);
         self
     }
 
+    /// Swap the system brain mid-session and recalculate the token count
+    pub fn replace_system_brain(&mut self, prompt: Option<String>) {
+        self.system_brain = prompt;
+        self.recount();
+    }
+
     /// Add a message to the context
     pub fn add_message(&mut self, message: Message) {
         // Estimate tokens for the message
@@ -139,32 +159,7 @@ impl AgentContext {
 
     /// Estimate tokens for a message
     fn estimate_message_tokens(&self, message: &Message) -> usize {
-        let mut tokens = 0;
-
-        for content in &message.content {
-            match content {
-                ContentBlock::Text { text } => {
-                    tokens += Self::estimate_tokens(text);
-                }
-                ContentBlock::ToolUse { name, input, .. } => {
-                    tokens += Self::estimate_tokens(name);
-                    tokens += Self::estimate_tokens(&input.to_string());
-                }
-                ContentBlock::ToolResult { content, .. } => {
-                    tokens += Self::estimate_tokens(content);
-                }
-                ContentBlock::Image { .. } => {
-                    // Images use a fixed token count (approximate)
-                    tokens += 1000;
-                }
-                ContentBlock::Thinking { thinking, .. } => {
-                    tokens += Self::estimate_tokens(thinking);
-                }
-            }
-        }
-
-        // Add overhead for message structure
-        tokens + 4
+        Self::message_tokens(&self.tokenizer, message)
     }
 
     /// Token estimation using tiktoken cl100k_base BPE encoding.
@@ -175,24 +170,34 @@ impl AgentContext {
 
     /// Static version of estimate_message_tokens — usable without a &self reference.
     pub fn estimate_tokens_static(message: &Message) -> usize {
+        Self::message_tokens(&tokenizer::Tokenizer::default(), message)
+    }
+
+    /// Tokens for a message counted with `tokenizer`, including ~4 tokens of
+    /// structural overhead.
+    pub fn message_tokens(tokenizer: &tokenizer::Tokenizer, message: &Message) -> usize {
         let mut tokens = 0;
         for content in &message.content {
             match content {
                 ContentBlock::Text { text } => {
-                    tokens += Self::estimate_tokens(text);
+                    tokens += tokenizer.count(text);
                 }
                 ContentBlock::ToolUse { name, input, .. } => {
-                    tokens += Self::estimate_tokens(name);
-                    tokens += Self::estimate_tokens(&input.to_string());
+                    tokens += tokenizer.count(name);
+                    tokens += tokenizer.count(&input.to_string());
                 }
                 ContentBlock::ToolResult { content, .. } => {
-                    tokens += Self::estimate_tokens(content);
+                    tokens += tokenizer.count(content);
                 }
                 ContentBlock::Image { .. } => {
+                    // Images use a fixed token count (approximate)
                     tokens += 1000;
                 }
                 ContentBlock::Thinking { thinking, .. } => {
-                    tokens += Self::estimate_tokens(thinking);
+                    tokens += tokenizer.count(thinking);
+                }
+                ContentBlock::RedactedThinking { data } => {
+                    tokens += tokenizer.count(data);
                 }
             }
         }
@@ -262,7 +267,7 @@ impl AgentContext {
     /// `keep_token_budget` is the max tokens for kept messages (excluding the summary).
     pub fn compact_with_summary(&mut self, summary: String, keep_token_budget: usize) {
         // Walk backwards from end, keeping messages until we hit the budget
-        let summary_tokens = Self::estimate_tokens(&summary) + 50; // +50 for the marker text
+        let summary_tokens = self.tokenizer.count(&summary) + 50; // +50 for the marker text
         let available = keep_token_budget.saturating_sub(summary_tokens);
         let mut running = 0usize;
         let mut keep_count = 0usize;
@@ -308,10 +313,26 @@ impl AgentContext {
         self.messages.extend(kept_messages);
 
         // Recalculate token count
-        self.token_count = 0;
-        if let Some(brain) = &self.system_brain {
-            self.token_count += Self::estimate_tokens(brain);
+        self.recount();
+    }
+
+    /// Prune old tool output (see [`pruning`]) and recount the tokens.
+    pub fn prune_tool_results(&mut self, config: &CompactionConfig) -> PruneReport {
+        let before = self.token_count;
+        let mut report = pruning::prune_tool_results(&mut self.messages, config);
+        if report.pruned() > 0 {
+            self.recount();
+            report.tokens_saved = before.saturating_sub(self.token_count);
         }
+        report
+    }
+
+    /// Recalculate `token_count` from the system brain and messages.
+    fn recount(&mut self) {
+        self.token_count = self
+            .system_brain
+            .as_deref()
+            .map_or(0, |brain| self.tokenizer.count(brain));
         for msg in &self.messages {
             self.token_count += self.estimate_message_tokens(msg);
         }
@@ -42,6 +42,10 @@ pub enum AgentError {
     #[error("Cancelled")]
     Cancelled,
 
+    /// A UserPromptSubmit hook (hooks.toml) refused the message
+    #[error("Blocked by hook: {0}")]
+    HookDenied(String),
+
     /// Internal error
     #[error("Internal error: {0}")]
     Internal(String),
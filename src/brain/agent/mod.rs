@@ -5,6 +5,7 @@
 
 pub mod context;
 pub mod error;
+pub mod pruning;
 pub mod service;
 
 // Re-exports
@@ -0,0 +1,230 @@
+//! Tool-Output Pruning
+//!
+//! The cheap first stage of context compaction: old tool results are cut
+//! down in place instead of summarizing the conversation with an LLM call.
+//! Long output is elided to its head and tail, and a call repeated later
+//! with the same input (re-reading a file) leaves only the newest result.
+//! Thresholds come from [`CompactionConfig`].
+
+use crate::brain::provider::{ContentBlock, Message};
+use crate::config::CompactionConfig;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Pruned results start with this, so later passes leave them alone
+pub const PRUNED_PREFIX: &str = "[pruned: ";
+
+/// Longest line kept from an elided result
+const MAX_LINE_CHARS: usize = 200;
+
+/// Tools whose result is superseded when called again with the same input
+const REREAD_TOOLS: &[&str] = &["read_file", "browser_content"];
+
+/// Input fields that best describe a call, in order of preference
+const TARGET_FIELDS: &[&str] = &[
+    "command",
+    "path",
+    "file_path",
+    "url",
+    "query",
+    "pattern",
+    "selector",
+];
+
+/// What a pruning pass changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Results cut to their head and tail
+    pub elided: usize,
+    /// Results replaced by a pointer to a newer identical call
+    pub deduplicated: usize,
+    /// Context tokens freed
+    pub tokens_saved: usize,
+}
+
+impl PruneReport {
+    /// Number of results pruned
+    pub fn pruned(&self) -> usize {
+        self.elided + self.deduplicated
+    }
+
+    /// What this report adds on top of `earlier`
+    pub fn since(&self, earlier: &PruneReport) -> PruneReport {
+        PruneReport {
+            elided: self.elided.saturating_sub(earlier.elided),
+            deduplicated: self.deduplicated.saturating_sub(earlier.deduplicated),
+            tokens_saved: self.tokens_saved.saturating_sub(earlier.tokens_saved),
+        }
+    }
+
+    /// One line for the chat
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.elided > 0 {
+            parts.push(format!("elided {} old tool output(s)", self.elided));
+        }
+        if self.deduplicated > 0 {
+            parts.push(format!("dropped {} repeated read(s)", self.deduplicated));
+        }
+        format!(
+            "Context pruning: {} (~{} tokens freed)",
+            parts.join(", "),
+            self.tokens_saved
+        )
+    }
+}
+
+impl std::ops::Add for PruneReport {
+    type Output = PruneReport;
+
+    fn add(self, other: PruneReport) -> PruneReport {
+        PruneReport {
+            elided: self.elided + other.elided,
+            deduplicated: self.deduplicated + other.deduplicated,
+            tokens_saved: self.tokens_saved + other.tokens_saved,
+        }
+    }
+}
+
+/// Prune the tool results in `messages`, leaving the newest
+/// `config.keep_recent` untouched. `tokens_saved` is left for the caller
+/// to fill in.
+pub fn prune_tool_results(messages: &mut [Message], config: &CompactionConfig) -> PruneReport {
+    // Name and input of every tool call, by id
+    let calls: HashMap<String, (String, Value)> = messages
+        .iter()
+        .flat_map(|m| &m.content)
+        .filter_map(|block| match block {
+            ContentBlock::ToolUse { id, name, input } => {
+                Some((id.clone(), (name.clone(), input.clone())))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let results: Vec<(usize, usize)> = messages
+        .iter()
+        .enumerate()
+        .flat_map(|(m, message)| {
+            message
+                .content
+                .iter()
+                .enumerate()
+                .filter(|(_, block)| matches!(block, ContentBlock::ToolResult { .. }))
+                .map(move |(b, _)| (m, b))
+        })
+        .collect();
+    let prunable = results.len().saturating_sub(config.keep_recent);
+
+    // Walking newest first, a successful re-read makes every older
+    // identical call stale
+    let mut seen = HashSet::new();
+    let mut superseded = HashSet::new();
+    for (i, &(m, b)) in results.iter().enumerate().rev() {
+        let ContentBlock::ToolResult {
+            tool_use_id,
+            is_error,
+            ..
+        } = &messages[m].content[b]
+        else {
+            continue;
+        };
+        let Some((name, input)) = calls.get(tool_use_id) else {
+            continue;
+        };
+        if !REREAD_TOOLS.contains(&name.as_str()) || *is_error == Some(true) {
+            continue;
+        }
+        if !seen.insert((name.as_str(), input.to_string())) && i < prunable {
+            superseded.insert(i);
+        }
+    }
+
+    let mut report = PruneReport::default();
+    for (i, &(m, b)) in results.iter().enumerate().take(prunable) {
+        let ContentBlock::ToolResult {
+            tool_use_id,
+            content,
+            ..
+        } = &mut messages[m].content[b]
+        else {
+            continue;
+        };
+        if content.starts_with(PRUNED_PREFIX) {
+            continue;
+        }
+        let Some((name, input)) = calls.get(tool_use_id.as_str()) else {
+            continue;
+        };
+        if superseded.contains(&i) {
+            *content = format!(
+                "{}{} was called again later — see the newer result]",
+                PRUNED_PREFIX,
+                describe(name, input)
+            );
+            report.deduplicated += 1;
+        } else if content.len() > config.elide_over_chars && config.tools.contains(name) {
+            *content = elide(content, &describe(name, input), config);
+            report.elided += 1;
+        }
+    }
+    report
+}
+
+/// Tool results in `messages` that a pruning pass has already cut down
+pub fn count_pruned(messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .flat_map(|m| &m.content)
+        .filter(|block| match block {
+            ContentBlock::ToolResult { content, .. } => content.starts_with(PRUNED_PREFIX),
+            _ => false,
+        })
+        .count()
+}
+
+/// `name` plus the input field that best identifies the call
+fn describe(name: &str, input: &Value) -> String {
+    let target = TARGET_FIELDS
+        .iter()
+        .find_map(|field| input.get(*field).and_then(Value::as_str));
+    match target {
+        Some(target) => format!("{} `{}`", name, clip(target, 120)),
+        None => name.to_string(),
+    }
+}
+
+/// Keep the head and tail lines of `content` under a pruning note
+fn elide(content: &str, call: &str, config: &CompactionConfig) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let head = config.head_lines.min(lines.len());
+    let tail = config.tail_lines.min(lines.len() - head);
+
+    let mut out = format!(
+        "{}{} output elided, {} lines / {} chars]",
+        PRUNED_PREFIX,
+        call,
+        lines.len(),
+        content.len()
+    );
+    for line in &lines[..head] {
+        out.push('\n');
+        out.push_str(&clip(line, MAX_LINE_CHARS));
+    }
+    let skipped = lines.len() - head - tail;
+    if skipped > 0 {
+        out.push_str(&format!("\n… {} lines …", skipped));
+    }
+    for line in &lines[lines.len() - tail..] {
+        out.push('\n');
+        out.push_str(&clip(line, MAX_LINE_CHARS));
+    }
+    out
+}
+
+fn clip(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
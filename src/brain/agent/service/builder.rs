@@ -1,5 +1,5 @@
 use super::types::*;
-use crate::brain::provider::Provider;
+use crate::brain::provider::{Provider, ToolChoice};
 use crate::brain::tools::ToolRegistry;
 use crate::services::ServiceContext;
 use std::collections::HashMap;
@@ -34,6 +34,14 @@ pub struct AgentService {
     /// Maximum tool execution iterations (0 = unlimited, relies on loop detection)
     pub(super) max_tool_iterations: usize,
 
+    /// Most read-only tool calls from one response run at once
+    /// (`[agent] max_concurrent`; 1 = one after another)
+    pub(super) max_parallel_tools: usize,
+
+    /// Permission rules for this service; `None` follows the process-wide
+    /// `[permissions]` config
+    pub(super) permissions: Option<crate::utils::permissions::Permissions>,
+
     /// System brain template
     pub(super) default_system_brain: Option<String>,
 
@@ -43,9 +51,41 @@ pub struct AgentService {
     /// Context window limit in tokens from config
     pub(super) context_limit: u32,
 
+    /// Tool-output pruning and summarization settings (`[compaction]`)
+    pub(super) compaction: crate::config::CompactionConfig,
+
+    /// Everything pruned from each session's context as of the last pass.
+    /// Context is rebuilt from the database every turn and re-pruned, so
+    /// only the growth is worth telling the user about.
+    pub(super) pruned_results:
+        std::sync::Mutex<HashMap<Uuid, crate::brain::agent::pruning::PruneReport>>,
+
     /// Max output tokens for API calls from config
     pub(super) max_tokens: u32,
 
+    /// Extended thinking budget applied to every agent request that doesn't
+    /// set its own. `None` defers to the provider's configured budget;
+    /// `Some(0)` forces thinking off. Set per run by cron jobs.
+    pub(super) thinking_budget: std::sync::RwLock<Option<u32>>,
+
+    /// Tool-use constraint for the first LLM call of each turn; later
+    /// iterations fall back to auto so the loop can finish. Set per run by
+    /// cron jobs.
+    pub(super) tool_choice: std::sync::RwLock<Option<ToolChoice>>,
+
+    /// Response cache TTL (seconds) applied to every LLM call of a turn.
+    /// `None` bypasses the cache. Set per run by cron jobs and the A2A
+    /// gateway when `[response_cache]` opts them in.
+    pub(super) response_cache_ttl: std::sync::RwLock<Option<u64>>,
+
+    /// Channel usage is recorded and budgeted under, overriding the channel
+    /// each turn arrives on. Set per run by cron jobs (`cron:<job>`).
+    pub(super) usage_channel: std::sync::RwLock<Option<String>>,
+
+    /// Channel each session's latest turn arrived on (`tui`, `telegram`,
+    /// ...), so LLM calls deep in the loop know which budget they draw on.
+    pub(super) session_channels: std::sync::RwLock<HashMap<Uuid, String>>,
+
     /// Callback for requesting tool approval from user
     pub(super) approval_callback: Option<ApprovalCallback>,
 
@@ -64,6 +104,10 @@ pub struct AgentService {
     /// Brain path (~/.opencrabs/) for loading brain files
     pub(super) brain_path: Option<std::path::PathBuf>,
 
+    /// Project brain files (`AGENTS.md`, `OPENCRABS.md`) in scope for each
+    /// session, rediscovered when the working directory changes
+    pub(super) project_brains: std::sync::RwLock<HashMap<Uuid, crate::brain::ProjectBrain>>,
+
     /// Notification channel — fired after every `run_tool_loop` completion so
     /// the TUI can refresh when a remote channel (Telegram/WhatsApp/…) updates
     /// the shared session.
@@ -89,10 +133,19 @@ impl AgentService {
             context,
             tool_registry: Arc::new(ToolRegistry::new()),
             max_tool_iterations: 0, // 0 = unlimited (loop detection is the safety net)
+            max_parallel_tools: config.agent.max_concurrent.max(1) as usize,
+            permissions: None,
             default_system_brain: None,
             auto_approve_tools: false,
             context_limit: config.agent.context_limit,
+            compaction: config.compaction.clone(),
+            pruned_results: std::sync::Mutex::new(HashMap::new()),
             max_tokens: config.agent.max_tokens,
+            thinking_budget: std::sync::RwLock::new(None),
+            tool_choice: std::sync::RwLock::new(None),
+            response_cache_ttl: std::sync::RwLock::new(None),
+            usage_channel: std::sync::RwLock::new(None),
+            session_channels: std::sync::RwLock::new(HashMap::new()),
             approval_callback: None,
             progress_callback: None,
             message_queue_callback: None,
@@ -101,6 +154,7 @@ impl AgentService {
                 std::env::current_dir().unwrap_or_default(),
             )),
             brain_path: None,
+            project_brains: std::sync::RwLock::new(HashMap::new()),
             session_updated_tx: None,
             fallback_providers: Self::build_fallback_providers(config).await,
         }
@@ -132,6 +186,94 @@ impl AgentService {
         self.max_tokens
     }
 
+    /// Get the extended thinking budget override
+    pub fn thinking_budget(&self) -> Option<u32> {
+        *self
+            .thinking_budget
+            .read()
+            .expect("thinking_budget lock poisoned")
+    }
+
+    /// Override the extended thinking budget at runtime (0 disables thinking,
+    /// `None` restores the provider default)
+    pub fn set_thinking_budget(&self, budget_tokens: Option<u32>) {
+        *self
+            .thinking_budget
+            .write()
+            .expect("thinking_budget lock poisoned") = budget_tokens;
+    }
+
+    /// Get the first-call tool choice override
+    pub fn tool_choice(&self) -> Option<ToolChoice> {
+        self.tool_choice
+            .read()
+            .expect("tool_choice lock poisoned")
+            .clone()
+    }
+
+    /// Constrain tool use on the first LLM call of each turn (`None`
+    /// restores the provider default)
+    pub fn set_tool_choice(&self, choice: Option<ToolChoice>) {
+        *self.tool_choice.write().expect("tool_choice lock poisoned") = choice;
+    }
+
+    /// Get the response cache TTL
+    pub fn response_cache_ttl(&self) -> Option<u64> {
+        *self
+            .response_cache_ttl
+            .read()
+            .expect("response_cache_ttl lock poisoned")
+    }
+
+    /// Serve identical LLM calls from the response cache for `ttl_secs`
+    /// (`None` bypasses the cache)
+    pub fn set_response_cache_ttl(&self, ttl_secs: Option<u64>) {
+        *self
+            .response_cache_ttl
+            .write()
+            .expect("response_cache_ttl lock poisoned") = ttl_secs;
+    }
+
+    /// Get the usage channel override
+    pub fn usage_channel(&self) -> Option<String> {
+        self.usage_channel
+            .read()
+            .expect("usage_channel lock poisoned")
+            .clone()
+    }
+
+    /// Record usage and check budgets under `channel` instead of each
+    /// turn's own channel (`None` restores the default)
+    pub fn set_usage_channel(&self, channel: Option<String>) {
+        *self
+            .usage_channel
+            .write()
+            .expect("usage_channel lock poisoned") = channel;
+    }
+
+    /// Remember the channel `session_id`'s current turn arrived on.
+    pub(super) fn set_session_channel(&self, session_id: Uuid, channel: &str) {
+        self.session_channels
+            .write()
+            .expect("session_channels lock poisoned")
+            .insert(session_id, channel.to_string());
+    }
+
+    /// Channel `session_id`'s usage is budgeted under: the usage channel
+    /// override, else the channel of its latest turn, else `""` (profile
+    /// budget only).
+    pub(super) fn channel_for_session(&self, session_id: Uuid) -> String {
+        if let Some(channel) = self.usage_channel() {
+            return channel;
+        }
+        self.session_channels
+            .read()
+            .expect("session_channels lock poisoned")
+            .get(&session_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     /// Get the tool registry
     pub fn tool_registry(&self) -> &Arc<ToolRegistry> {
         &self.tool_registry
@@ -174,6 +316,24 @@ impl AgentService {
         self
     }
 
+    /// Set how many read-only tool calls from one response run at once
+    pub fn with_max_parallel_tools(mut self, max: usize) -> Self {
+        self.max_parallel_tools = max.max(1);
+        self
+    }
+
+    /// Use these compaction settings instead of the `[compaction]` config
+    pub fn with_compaction(mut self, compaction: crate::config::CompactionConfig) -> Self {
+        self.compaction = compaction;
+        self
+    }
+
+    /// Use these permission rules instead of the `[permissions]` config
+    pub fn with_permissions(mut self, permissions: crate::utils::permissions::Permissions) -> Self {
+        self.permissions = Some(permissions);
+        self
+    }
+
     /// Set the tool registry
     pub fn with_tool_registry(mut self, registry: Arc<ToolRegistry>) -> Self {
         self.tool_registry = registry;
@@ -374,6 +534,15 @@ impl AgentService {
             .remove(&session_id);
     }
 
+    /// Forget what was pruned from a session's context (session deleted or
+    /// switched away). Noop if nothing was.
+    pub fn clear_pruned_results(&self, session_id: Uuid) {
+        self.pruned_results
+            .lock()
+            .expect("pruned_results lock poisoned")
+            .remove(&session_id);
+    }
+
     /// Snapshot of every per-session provider binding. Used by
     /// `rebuild_agent_service` to carry session→provider pins across
     /// the rebuild so live sessions on other panes don't lose their
@@ -43,13 +43,12 @@ impl AgentService {
         let db_messages = Self::messages_from_last_compaction(all_db_messages);
 
         let mut context =
-            AgentContext::from_db_messages(session_id, db_messages, context_window as usize);
+            AgentContext::from_db_messages(session_id, db_messages, context_window as usize)
+                .with_tokenizer(self.tokenizer_for(session_id, &model_name));
 
-        // Add system brain if available (count its tokens for accurate tracking)
-        if let Some(brain) = &self.default_system_brain {
-            context.token_count += AgentContext::estimate_tokens(brain);
-            context.system_brain = Some(brain.clone());
-        }
+        // Add system brain plus project brain files and skills (count their
+        // tokens for accurate tracking)
+        self.refresh_system_brain(session_id, &mut context);
 
         // Add user message
         let user_msg = Message::user(user_message.clone());
@@ -109,6 +108,94 @@ impl AgentService {
         }
     }
 
+    /// Layer the project brain and skill index for the current working
+    /// directory over the default system brain. Runs at the start of each
+    /// turn and after each tool batch, so a `/cd`, a newly touched
+    /// subdirectory or an added skill takes effect on the next LLM call.
+    pub(super) fn refresh_system_brain(&self, session_id: Uuid, context: &mut AgentContext) {
+        let working_directory = self.get_working_directory();
+        let project = {
+            let mut brains = self
+                .project_brains
+                .write()
+                .expect("project_brains lock poisoned");
+            let brain = brains.entry(session_id).or_insert_with(|| {
+                crate::brain::ProjectBrain::new(working_directory.clone(), self.global_brain_path())
+            });
+            if brain.working_directory() != working_directory {
+                tracing::info!(
+                    "Working directory for session {} is now {} — reloading project brain",
+                    session_id,
+                    working_directory.display()
+                );
+                *brain = crate::brain::ProjectBrain::new(
+                    working_directory.clone(),
+                    self.global_brain_path(),
+                );
+            }
+            brain.clone()
+        };
+
+        let skills = crate::brain::Skills::discover(
+            &crate::brain::skills::global_dir(&self.global_brain_path()),
+            &working_directory,
+        );
+
+        let layers: Vec<String> = [project.section(), skills.index_section()]
+            .into_iter()
+            .flatten()
+            .collect();
+        let system_brain = match self.default_system_brain.as_deref() {
+            Some(global) if layers.is_empty() => Some(global.to_string()),
+            Some(global) => Some(format!("{}\n\n{}", global.trim_end(), layers.concat())),
+            None => Some(layers.concat()).filter(|layers| !layers.is_empty()),
+        };
+        if context.system_brain != system_brain {
+            context.replace_system_brain(system_brain);
+        }
+    }
+
+    /// Bring project brain files under the paths `tool_uses` touch into
+    /// scope for later LLM calls
+    pub(super) fn note_touched_files(
+        &self,
+        session_id: Uuid,
+        tool_uses: &[(String, String, serde_json::Value)],
+    ) {
+        let working_directory = self.get_working_directory();
+        let mut brains = self
+            .project_brains
+            .write()
+            .expect("project_brains lock poisoned");
+        let Some(brain) = brains.get_mut(&session_id) else {
+            return;
+        };
+        for (_, tool_name, input) in tool_uses {
+            let Some(path) = ["path", "file_path"]
+                .iter()
+                .find_map(|field| input.get(*field).and_then(serde_json::Value::as_str))
+            else {
+                continue;
+            };
+            let path = crate::brain::tools::error::resolve_tool_path(path, &working_directory);
+            if brain.touch(&path) {
+                tracing::debug!(
+                    "'{}' touched {} — nested project brain files now in scope",
+                    tool_name,
+                    path.display()
+                );
+            }
+        }
+    }
+
+    /// Global brain directory, whose files the project brain skips and
+    /// whose `skills/` holds the global skills
+    fn global_brain_path(&self) -> std::path::PathBuf {
+        self.brain_path
+            .clone()
+            .unwrap_or_else(crate::brain::BrainLoader::resolve_path)
+    }
+
     /// Build a "recovered brain" context string from key brain files.
     ///
     /// After compaction wipes the conversation history, this restores the agent's
@@ -491,6 +578,7 @@ The summary above is NOT sufficient for implementation work.
                             lines.push(format!("{}: [thinking: {}]", role_label, display));
                         }
                     }
+                    ContentBlock::RedactedThinking { .. } => {}
                 }
             }
         }
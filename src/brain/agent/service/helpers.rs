@@ -15,6 +15,62 @@ impl AgentService {
         )
     }
 
+    /// Tokenizer for `model` on the session's provider (the live fallback,
+    /// if one has taken over).
+    pub(super) fn tokenizer_for(
+        &self,
+        session_id: Uuid,
+        model: &str,
+    ) -> crate::brain::tokenizer::Tokenizer {
+        let provider = self.provider_for_session(session_id);
+        crate::brain::tokenizer::for_model(&live_provider_name(provider.as_ref()), model)
+    }
+
+    /// Check the spending budgets before an LLM call on `session_id`: warn
+    /// once a cap passes its soft threshold, and at a cap either refuse the
+    /// call or switch `request` to the configured cheaper model.
+    pub(super) async fn apply_budget(
+        &self,
+        session_id: Uuid,
+        request: &mut LLMRequest,
+        progress: Option<&ProgressCallback>,
+    ) -> std::result::Result<(), crate::brain::provider::ProviderError> {
+        use crate::usage::budget::{self, Verdict};
+
+        let alert = |message: String| {
+            if let Some(cb) = progress {
+                cb(session_id, ProgressEvent::BudgetAlert { message });
+            }
+        };
+        let channel = self.channel_for_session(session_id);
+        match budget::check(&self.context.pool(), &channel).await {
+            Verdict::Allow => {}
+            Verdict::Warn(status) => {
+                if budget::first_notice("warn", &status) {
+                    tracing::warn!("Nearing {}", status);
+                    alert(format!("Nearing {}", status));
+                }
+            }
+            Verdict::Downgrade { status, model } => {
+                if budget::first_notice("downgrade", &status) {
+                    tracing::warn!("Over {} — downgrading to '{}'", status, model);
+                    alert(format!(
+                        "Over {} — using '{}' until it resets",
+                        status, model
+                    ));
+                }
+                request.model = model;
+            }
+            Verdict::Refuse(status) => {
+                tracing::warn!("Refusing LLM call: over {}", status);
+                return Err(crate::brain::provider::ProviderError::BudgetExceeded(
+                    status.to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Stream a request and accumulate into an LLMResponse.
     ///
     /// Sends text deltas to the progress callback as `StreamingChunk` events
@@ -52,6 +108,12 @@ impl AgentService {
 
         let provider = self.provider_for_session(session_id);
 
+        // Spending budgets ([budget]) go first: a downgrade still has to
+        // pass the supported-model check below.
+        let mut request = request;
+        self.apply_budget(session_id, &mut request, effective_cb)
+            .await?;
+
         // Invariant: never send a {provider, model} pair the user didn't
         // configure. If the request's model isn't in this provider's
         // supported list, remap to the provider's own default. This
@@ -63,7 +125,6 @@ impl AgentService {
         // because the session's provider got stuck on a fallback
         // after a cancelled turn. That exact case is now prevented
         // at the stream site regardless of the cached state.
-        let mut request = request;
         let supported = provider.supported_models();
         if !supported.is_empty() && !supported.iter().any(|m| m == &request.model) {
             let remapped = provider.default_model().to_string();
@@ -75,8 +136,73 @@ impl AgentService {
             );
             request.model = remapped;
         }
+        // Agent-level thinking budget (cron `thinking` mode). Compaction
+        // calls (suppress_callback) never think — the summary doesn't need it.
+        if request.thinking_budget.is_none() {
+            request.thinking_budget = if suppress_callback {
+                Some(0)
+            } else {
+                self.thinking_budget()
+            };
+        }
         let request_model = request.model.clone();
 
+        // Configured rate budgets ([providers.<name>.rate_limit]) are shared
+        // by every session, channel, subagent and cron job. Wait for room
+        // here — before the handshake timeout starts — instead of sending
+        // and eating a 429.
+        let budget_provider = live_provider_name(provider.as_ref());
+        let tokenizer = crate::brain::tokenizer::for_model(&budget_provider, &request_model);
+        let raw_estimate = estimate_request_tokens(&request, &tokenizer.uncorrected());
+        let estimated_tokens = tokenizer.correct(raw_estimate).min(u32::MAX as usize) as u32;
+        let rate_wait = crate::brain::provider::rate_limiter::RATE_BUDGETS.reserve(
+            &budget_provider,
+            &request_model,
+            estimated_tokens,
+        );
+        if !rate_wait.is_zero() {
+            tracing::info!(
+                "Rate budget for {}/{} exhausted — waiting {:.1}s",
+                budget_provider,
+                request_model,
+                rate_wait.as_secs_f64()
+            );
+            if let Some(cb) = effective_cb {
+                cb(
+                    session_id,
+                    ProgressEvent::RateLimitWait {
+                        provider: budget_provider.clone(),
+                        model: request_model.clone(),
+                        wait_secs: rate_wait.as_secs().max(1),
+                    },
+                );
+            }
+            let cancelled = tokio::select! {
+                _ = async {
+                    if let Some(token) = cancel_token {
+                        token.cancelled().await;
+                    } else {
+                        std::future::pending::<()>().await;
+                    }
+                } => true,
+                _ = tokio::time::sleep(rate_wait) => false,
+            };
+            if cancelled {
+                // Same shape as a stream cancelled before its first event
+                tracing::info!("Cancelled while waiting for rate limit");
+                return Ok((
+                    LLMResponse {
+                        id: String::new(),
+                        model: request_model,
+                        content: Vec::new(),
+                        stop_reason: None,
+                        usage: TokenUsage::default(),
+                    },
+                    None,
+                ));
+            }
+        }
+
         // Bound the initial stream handshake (HTTP POST + response headers)
         // so a wedged local server — accepts TCP but never replies — can't
         // eat the full 300s reqwest timeout before the retry chain fires.
@@ -99,7 +225,10 @@ impl AgentService {
             match tokio::time::timeout(handshake_timeout, provider.stream(request)).await {
                 Ok(Ok(s)) => s,
                 Ok(Err(e)) => {
-                    crate::config::health::record_failure(provider.name(), &e.to_string());
+                    crate::config::health::record_failure(
+                        &live_provider_name(provider.as_ref()),
+                        &e.to_string(),
+                    );
                     return Err(e);
                 }
                 Err(_elapsed) => {
@@ -110,7 +239,7 @@ impl AgentService {
                         provider.base_url().unwrap_or("<no-base-url>"),
                     );
                     crate::config::health::record_failure(
-                        provider.name(),
+                        &live_provider_name(provider.as_ref()),
                         &format!("handshake timeout after {}s", secs),
                     );
                     return Err(crate::brain::provider::ProviderError::Timeout(secs));
@@ -127,6 +256,7 @@ impl AgentService {
         let mut cache_read_tokens = 0u32;
         let mut billing_cache_creation = 0u32;
         let mut billing_cache_read = 0u32;
+        let mut response_cache_hit = false;
 
         // --- Text repetition detection ---
         // Some providers (e.g. MiniMax) loop the same content indefinitely without
@@ -140,9 +270,11 @@ impl AgentService {
         // Track partial content blocks by index
         // Text blocks: accumulate text deltas
         // ToolUse blocks: accumulate JSON deltas
+        // Thinking blocks: accumulate thinking deltas (kept only when signed)
         struct BlockState {
             block: ContentBlock,
-            json_buf: String, // for tool use JSON accumulation
+            json_buf: String,     // for tool use JSON accumulation
+            thinking_buf: String, // for signed thinking round-trip
         }
         let mut block_states: Vec<BlockState> = Vec::new();
         let mut reasoning_buf = String::new();
@@ -230,6 +362,7 @@ impl AgentService {
                     id = message.id;
                     model = message.model;
                     input_tokens = message.usage.input_tokens;
+                    response_cache_hit |= message.usage.response_cache_hit;
                 }
                 StreamEvent::ContentBlockStart {
                     index,
@@ -242,6 +375,7 @@ impl AgentService {
                                 text: String::new(),
                             },
                             json_buf: String::new(),
+                            thinking_buf: String::new(),
                         });
                     }
                     // Separate thinking blocks from different rounds with a blank line
@@ -262,6 +396,7 @@ impl AgentService {
                     block_states[index] = BlockState {
                         block: content_block,
                         json_buf: String::new(),
+                        thinking_buf: String::new(),
                     };
                 }
                 StreamEvent::ContentBlockDelta { index, delta } => {
@@ -335,6 +470,16 @@ impl AgentService {
                                     );
                                 }
                                 reasoning_buf.push_str(&thinking);
+                                block_states[index].thinking_buf.push_str(&thinking);
+                            }
+                            ContentDelta::SignatureDelta { signature } => {
+                                if let ContentBlock::Thinking {
+                                    signature: ref mut sig,
+                                    ..
+                                } = block_states[index].block
+                                {
+                                    sig.get_or_insert_with(String::new).push_str(&signature);
+                                }
                             }
                         }
                     }
@@ -350,6 +495,20 @@ impl AgentService {
                             {
                                 *input = parsed;
                             }
+                            // Signed thinking (Anthropic extended thinking) must be
+                            // echoed back verbatim on the next tool-use turn, so
+                            // keep the full text in the block. Unsigned thinking
+                            // stays display-only via reasoning_buf.
+                            if let ContentBlock::Thinking {
+                                ref mut thinking,
+                                ref signature,
+                            } = state.block
+                                && signature.as_deref().is_some_and(|s| !s.is_empty())
+                                && !state.thinking_buf.is_empty()
+                            {
+                                thinking.push_str(&state.thinking_buf);
+                                state.thinking_buf.clear();
+                            }
                         }
                         // CLI: flush accumulated text as IntermediateText before
                         // emitting tool events, so TUI shows text→tools sequentially
@@ -446,6 +605,7 @@ impl AgentService {
                     }
                 }
                 StreamEvent::MessageDelta { delta, usage } => {
+                    response_cache_hit |= usage.response_cache_hit;
                     // Only update stop_reason if the delta carries one — deferred
                     // usage chunks send a second MessageDelta with stop_reason=None
                     // that must not overwrite the real stop_reason.
@@ -507,7 +667,10 @@ impl AgentService {
                     }
                 }
                 StreamEvent::Error { error } => {
-                    crate::config::health::record_failure(provider.name(), &error);
+                    crate::config::health::record_failure(
+                        &live_provider_name(provider.as_ref()),
+                        &error,
+                    );
                     return Err(crate::brain::provider::ProviderError::StreamError(error));
                 }
             }
@@ -612,7 +775,45 @@ impl AgentService {
             .collect();
 
         // Track provider health + snapshot config on first success.
-        crate::config::health::record_success(provider.name());
+        crate::config::health::record_success(&live_provider_name(provider.as_ref()));
+        // A response cache hit never reached the provider: refund the
+        // reservation and don't learn from replayed usage.
+        let used_tokens = if response_cache_hit {
+            0
+        } else {
+            input_tokens.saturating_add(output_tokens)
+        };
+        crate::brain::provider::rate_limiter::RATE_BUDGETS.settle(
+            &budget_provider,
+            &request_model,
+            estimated_tokens,
+            used_tokens,
+        );
+        // Count the call against spending budgets until the turn's usage
+        // lands in the ledger.
+        if !response_cache_hit {
+            crate::usage::budget::note_call_cost(
+                session_id,
+                &self.channel_for_session(session_id),
+                provider.calculate_cost_with_cache(
+                    &request_model,
+                    input_tokens,
+                    output_tokens,
+                    cache_creation_tokens,
+                    cache_read_tokens,
+                ),
+            );
+        }
+        // Learn how far local counting is from the provider's. Cached calls
+        // are skipped: providers disagree on whether cache hits are part of
+        // `input_tokens`. CLI providers report their own session totals.
+        if cache_creation_tokens == 0
+            && cache_read_tokens == 0
+            && !response_cache_hit
+            && !provider.cli_handles_tools()
+        {
+            tokenizer.calibrate(raw_estimate, input_tokens);
+        }
         {
             use std::sync::atomic::{AtomicBool, Ordering};
             static SAVED: AtomicBool = AtomicBool::new(false);
@@ -645,6 +846,7 @@ impl AgentService {
                     cache_read_tokens,
                     billing_cache_creation,
                     billing_cache_read,
+                    response_cache_hit,
                 },
             },
             reasoning,
@@ -1071,6 +1273,44 @@ impl AgentService {
     }
 }
 
+/// Name of the provider actually serving requests. A fallback chain reports
+/// the primary as its `name()`; health records and rate budgets go against
+/// whichever sub-provider is live instead, so a fallback's success never
+/// marks a dead primary healthy.
+fn live_provider_name(provider: &dyn crate::brain::provider::Provider) -> String {
+    provider
+        .active_subprovider_name()
+        .unwrap_or_else(|| provider.name().to_string())
+}
+
+/// Prompt size as counted by `tokenizer`: system prompt, messages and tool
+/// schemas. Reserved against tokens-per-minute budgets (settled against the
+/// provider's reported usage afterwards) and used as the calibration sample.
+fn estimate_request_tokens(
+    request: &LLMRequest,
+    tokenizer: &crate::brain::tokenizer::Tokenizer,
+) -> usize {
+    use crate::brain::agent::context::AgentContext;
+
+    let mut tokens = request
+        .system
+        .as_deref()
+        .map(|s| tokenizer.count(s))
+        .unwrap_or(0);
+    tokens += request
+        .messages
+        .iter()
+        .map(|m| AgentContext::message_tokens(tokenizer, m))
+        .sum::<usize>();
+    if let Some(tools) = &request.tools {
+        tokens += tools
+            .iter()
+            .map(|t| tokenizer.count(&t.description) + tokenizer.count(&t.input_schema.to_string()))
+            .sum::<usize>();
+    }
+    tokens
+}
+
 /// Walk a JSON array starting at `s[0] == '['` and return the byte offset
 /// one-past the matching `]`. Tracks string + escape state so braces,
 /// brackets, quotes or `-->` arrows embedded in string values don't fool
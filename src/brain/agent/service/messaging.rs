@@ -1,6 +1,9 @@
 use super::builder::AgentService;
 use super::types::*;
 use crate::brain::agent::error::{AgentError, Result};
+use crate::brain::provider::{ContentDelta, ProviderStream, StreamEvent};
+use futures::StreamExt;
+use std::sync::{Arc, Mutex};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
@@ -19,10 +22,15 @@ impl AgentService {
         user_message: String,
         model: Option<String>,
     ) -> Result<AgentResponse> {
+        let user_message = self.submit_prompt(session_id, user_message).await?;
+
         // Prepare message context (common setup logic)
-        let (_model_name, request, message_service, session_service) = self
+        let (_model_name, mut request, message_service, session_service) = self
             .prepare_message_context(session_id, user_message, model)
             .await?;
+        self.apply_budget(session_id, &mut request, self.progress_callback.as_ref())
+            .await
+            .map_err(AgentError::Provider)?;
 
         // Send to provider — use session's provider so a concurrent
         // foreground swap on another pane can't hijack this turn.
@@ -70,10 +78,17 @@ impl AgentService {
 
         // Update session token usage
         session_service
-            .update_session_usage(session_id, total_tokens as i32, cost)
+            .update_session_usage_for_channel(
+                session_id,
+                total_tokens as i32,
+                cost,
+                &self.channel_for_session(session_id),
+            )
             .await
             .map_err(|e| AgentError::Database(e.to_string()))?;
 
+        self.finish_turn(session_id, &assistant_text).await;
+
         Ok(AgentResponse {
             message_id: assistant_db_msg.id,
             content: assistant_text,
@@ -94,6 +109,8 @@ impl AgentService {
         user_message: String,
         model: Option<String>,
     ) -> Result<AgentStreamResponse> {
+        let user_message = self.submit_prompt(session_id, user_message).await?;
+
         // Prepare message context (common setup logic)
         let (model_name, request, _message_service, _session_service) = self
             .prepare_message_context(session_id, user_message, model)
@@ -113,11 +130,86 @@ impl AgentService {
         Ok(AgentStreamResponse {
             session_id,
             message_id: Uuid::new_v4(),
-            stream,
+            stream: self.finish_turn_after(session_id, stream),
             model: model_name,
         })
     }
 
+    /// Run the SessionStart and UserPromptSubmit hooks (hooks.toml) for an
+    /// incoming user message. Returns the message to send, which a hook may
+    /// have rewritten.
+    pub(super) async fn submit_prompt(
+        &self,
+        session_id: Uuid,
+        user_message: String,
+    ) -> Result<String> {
+        let hooks = crate::brain::hooks::current();
+        if hooks.is_empty() {
+            return Ok(user_message);
+        }
+        let cwd = self
+            .working_directory
+            .read()
+            .expect("working_directory lock poisoned")
+            .clone();
+        hooks.session_start(session_id, &cwd).await;
+        hooks
+            .user_prompt_submit(session_id, &cwd, user_message)
+            .await
+            .map_err(|reason| {
+                tracing::warn!("UserPromptSubmit hook denied message: {}", reason);
+                AgentError::HookDenied(reason)
+            })
+    }
+
+    /// Run the Stop hooks (hooks.toml) with the agent's final reply.
+    pub(super) async fn finish_turn(&self, session_id: Uuid, response: &str) {
+        let hooks = crate::brain::hooks::current();
+        if hooks.is_empty() {
+            return;
+        }
+        let cwd = self
+            .working_directory
+            .read()
+            .expect("working_directory lock poisoned")
+            .clone();
+        hooks.stop(session_id, &cwd, response).await;
+    }
+
+    /// [`Self::finish_turn`] for a streamed reply: the Stop hooks run once
+    /// the caller has drained `stream`, with the text it carried.
+    fn finish_turn_after(&self, session_id: Uuid, stream: ProviderStream) -> ProviderStream {
+        let hooks = crate::brain::hooks::current();
+        if hooks.is_empty() {
+            return stream;
+        }
+        let cwd = self
+            .working_directory
+            .read()
+            .expect("working_directory lock poisoned")
+            .clone();
+        let text = Arc::new(Mutex::new(String::new()));
+        let streamed = text.clone();
+        let events = stream.inspect(move |event| {
+            if let Ok(StreamEvent::ContentBlockDelta {
+                delta: ContentDelta::TextDelta { text },
+                ..
+            }) = event
+            {
+                streamed
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push_str(text);
+            }
+        });
+        let stop = futures::stream::once(async move {
+            let response = std::mem::take(&mut *text.lock().unwrap_or_else(|e| e.into_inner()));
+            hooks.stop(session_id, &cwd, &response).await;
+        })
+        .filter_map(|()| async { None });
+        Box::pin(events.chain(stop))
+    }
+
     /// Send a message with automatic tool execution (TUI channel).
     pub async fn send_message_with_tools(
         &self,
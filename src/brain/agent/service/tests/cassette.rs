@@ -0,0 +1,47 @@
+use super::*;
+use crate::brain::provider::CassetteProvider;
+
+async fn run_session(provider: Arc<dyn Provider>) -> String {
+    let db = Database::connect_in_memory().await.unwrap();
+    db.run_migrations().await.unwrap();
+    let context = ServiceContext::new(db.pool().clone());
+
+    let registry = ToolRegistry::new();
+    registry.register(Arc::new(MockTool));
+
+    let agent_service = AgentService::new_for_test(provider, context.clone())
+        .await
+        .with_tool_registry(Arc::new(registry))
+        .with_auto_approve_tools(true);
+
+    let session = SessionService::new(context)
+        .create_session(Some("Cassette Session".to_string()))
+        .await
+        .unwrap();
+
+    agent_service
+        .send_message_with_tools(session.id, "Use the test tool".to_string(), None)
+        .await
+        .unwrap()
+        .content
+}
+
+#[tokio::test]
+async fn test_tool_loop_replays_from_cassette() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tool_loop.json");
+
+    let recorder = Arc::new(CassetteProvider::record(
+        Arc::new(MockProviderWithTools::new()),
+        &path,
+    ));
+    let recorded = run_session(recorder).await;
+    assert!(recorded.contains("completed successfully"));
+
+    // Fresh service and DB, no live provider: the cassette answers both turns
+    let player = Arc::new(CassetteProvider::replay(&path).unwrap());
+    let replayed = run_session(player.clone()).await;
+
+    assert_eq!(replayed, recorded);
+    assert!(player.unplayed().is_empty());
+}
@@ -1,9 +1,14 @@
 mod approval_policies;
 mod basic;
+mod cassette;
 mod context_tracking;
 mod model_selection;
 mod parallel_sessions;
+mod parallel_tools;
+mod permission_rules;
+mod response_cache;
 mod streaming_usage;
+mod tool_choice;
 mod tool_normalization;
 
 use super::*;
@@ -0,0 +1,305 @@
+use super::*;
+use crate::brain::tools::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Mock provider that asks for `calls` uses of one tool in a single
+/// response, then records the tool results it gets back.
+struct MockProviderWithToolBatch {
+    tool_name: String,
+    calls: usize,
+    call_count: std::sync::Mutex<usize>,
+    results: std::sync::Mutex<Vec<(String, String)>>,
+}
+
+impl MockProviderWithToolBatch {
+    fn new(tool_name: &str, calls: usize) -> Self {
+        Self {
+            tool_name: tool_name.to_string(),
+            calls,
+            call_count: std::sync::Mutex::new(0),
+            results: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// `(tool_use_id, content)` of every tool result in the follow-up request
+    fn results(&self) -> Vec<(String, String)> {
+        self.results.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Provider for MockProviderWithToolBatch {
+    async fn complete(&self, request: LLMRequest) -> crate::brain::provider::Result<LLMResponse> {
+        let call_num = {
+            let mut count = self.call_count.lock().unwrap();
+            *count += 1;
+            *count
+        };
+
+        if call_num == 1 {
+            let mut content = vec![ContentBlock::Text {
+                text: "Reading everything at once".to_string(),
+            }];
+            content.extend((0..self.calls).map(|n| ContentBlock::ToolUse {
+                id: format!("tool-call-{n}"),
+                name: self.tool_name.clone(),
+                input: serde_json::json!({"n": n}),
+            }));
+            return Ok(LLMResponse {
+                id: "test-response-1".to_string(),
+                model: "mock-model".to_string(),
+                content,
+                stop_reason: Some(StopReason::ToolUse),
+                usage: TokenUsage {
+                    input_tokens: 10,
+                    output_tokens: 20,
+                    ..Default::default()
+                },
+            });
+        }
+
+        *self.results.lock().unwrap() = request
+            .messages
+            .iter()
+            .flat_map(|message| &message.content)
+            .filter_map(|block| match block {
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    ..
+                } => Some((tool_use_id.clone(), content.clone())),
+                _ => None,
+            })
+            .collect();
+        Ok(LLMResponse {
+            id: "test-response-2".to_string(),
+            model: "mock-model".to_string(),
+            content: vec![ContentBlock::Text {
+                text: "All read.".to_string(),
+            }],
+            stop_reason: Some(StopReason::EndTurn),
+            usage: TokenUsage {
+                input_tokens: 15,
+                output_tokens: 25,
+                ..Default::default()
+            },
+        })
+    }
+
+    async fn stream(&self, request: LLMRequest) -> crate::brain::provider::Result<ProviderStream> {
+        use crate::brain::provider::{ContentDelta, MessageDelta, StreamEvent, StreamMessage};
+
+        let response = self.complete(request).await?;
+        let mut events = vec![Ok(StreamEvent::MessageStart {
+            message: StreamMessage {
+                id: response.id.clone(),
+                model: response.model.clone(),
+                role: Role::Assistant,
+                usage: response.usage,
+            },
+        })];
+        for (i, block) in response.content.iter().enumerate() {
+            match block {
+                ContentBlock::Text { text } => {
+                    events.push(Ok(StreamEvent::ContentBlockStart {
+                        index: i,
+                        content_block: ContentBlock::Text {
+                            text: String::new(),
+                        },
+                    }));
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index: i,
+                        delta: ContentDelta::TextDelta { text: text.clone() },
+                    }));
+                }
+                ContentBlock::ToolUse { id, name, input } => {
+                    events.push(Ok(StreamEvent::ContentBlockStart {
+                        index: i,
+                        content_block: ContentBlock::ToolUse {
+                            id: id.clone(),
+                            name: name.clone(),
+                            input: serde_json::Value::Object(Default::default()),
+                        },
+                    }));
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index: i,
+                        delta: ContentDelta::InputJsonDelta {
+                            partial_json: serde_json::to_string(input).unwrap_or_default(),
+                        },
+                    }));
+                }
+                _ => {
+                    events.push(Ok(StreamEvent::ContentBlockStart {
+                        index: i,
+                        content_block: block.clone(),
+                    }));
+                }
+            }
+            events.push(Ok(StreamEvent::ContentBlockStop { index: i }));
+        }
+        events.push(Ok(StreamEvent::MessageDelta {
+            delta: MessageDelta {
+                stop_reason: response.stop_reason,
+                stop_sequence: None,
+            },
+            usage: response.usage,
+        }));
+        events.push(Ok(StreamEvent::MessageStop));
+        Ok(Box::pin(futures::stream::iter(events)))
+    }
+
+    fn name(&self) -> &str {
+        "mock-tool-batch"
+    }
+
+    fn default_model(&self) -> &str {
+        "mock-model"
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        vec!["mock-model".to_string()]
+    }
+
+    fn context_window(&self, _model: &str) -> Option<u32> {
+        Some(4096)
+    }
+
+    fn calculate_cost(&self, _model: &str, _input: u32, _output: u32) -> f64 {
+        0.001
+    }
+}
+
+/// Mock tool that takes a while and tracks how many calls overlap. Later
+/// calls finish first, so out-of-order completion would show in results.
+struct MockSlowTool {
+    name: &'static str,
+    capabilities: Vec<ToolCapability>,
+    running: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl MockSlowTool {
+    fn new(name: &'static str, capabilities: Vec<ToolCapability>) -> Arc<Self> {
+        Arc::new(Self {
+            name,
+            capabilities,
+            running: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        })
+    }
+}
+
+#[async_trait]
+impl Tool for MockSlowTool {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn description(&self) -> &str {
+        "A slow test tool"
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "n": {"type": "integer"}
+            }
+        })
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        self.capabilities.clone()
+    }
+
+    fn requires_approval(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        input: serde_json::Value,
+        _context: &ToolExecutionContext,
+    ) -> crate::brain::tools::Result<ToolResult> {
+        let n = input["n"].as_u64().unwrap_or(0);
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(running, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(60 - 10 * n)).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(ToolResult::success(format!("result {n}")))
+    }
+}
+
+async fn run_batch(
+    tool: Arc<MockSlowTool>,
+    calls: usize,
+    max_parallel: usize,
+) -> Vec<(String, String)> {
+    let db = Database::connect_in_memory().await.unwrap();
+    db.run_migrations().await.unwrap();
+    let context = ServiceContext::new(db.pool().clone());
+
+    let provider = Arc::new(MockProviderWithToolBatch::new(tool.name, calls));
+    let registry = ToolRegistry::new();
+    registry.register(tool);
+
+    let agent_service = AgentService::new_for_test(provider.clone(), context.clone())
+        .await
+        .with_tool_registry(Arc::new(registry))
+        .with_auto_approve_tools(true)
+        .with_max_parallel_tools(max_parallel);
+
+    let session = SessionService::new(context)
+        .create_session(Some("Parallel Tools Test".to_string()))
+        .await
+        .unwrap();
+    agent_service
+        .send_message_with_tools(session.id, "Read them all".to_string(), None)
+        .await
+        .unwrap();
+
+    provider.results()
+}
+
+fn expected(calls: usize) -> Vec<(String, String)> {
+    (0..calls)
+        .map(|n| (format!("tool-call-{n}"), format!("result {n}")))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_read_only_batch_runs_concurrently_in_order() {
+    let tool = MockSlowTool::new("slow_read", vec![ToolCapability::ReadFiles]);
+
+    let results = run_batch(tool.clone(), 5, 3).await;
+
+    assert_eq!(results, expected(5), "results keep the model's call order");
+    assert_eq!(
+        tool.peak.load(Ordering::SeqCst),
+        3,
+        "at most max_parallel_tools calls run at once"
+    );
+}
+
+#[tokio::test]
+async fn test_mutating_batch_runs_sequentially() {
+    let tool = MockSlowTool::new(
+        "slow_write",
+        vec![ToolCapability::ReadFiles, ToolCapability::WriteFiles],
+    );
+
+    let results = run_batch(tool.clone(), 3, 4).await;
+
+    assert_eq!(results, expected(3));
+    assert_eq!(tool.peak.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_parallel_limit_of_one_runs_sequentially() {
+    let tool = MockSlowTool::new("slow_grep", vec![ToolCapability::ReadFiles]);
+
+    let results = run_batch(tool.clone(), 3, 1).await;
+
+    assert_eq!(results, expected(3));
+    assert_eq!(tool.peak.load(Ordering::SeqCst), 1);
+}
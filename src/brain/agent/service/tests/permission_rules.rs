@@ -0,0 +1,133 @@
+use super::*;
+use crate::brain::tools::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use crate::utils::permissions::Permissions;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Mock tool that requires approval and counts how often it runs
+struct CountingTool {
+    runs: AtomicUsize,
+}
+
+#[async_trait]
+impl Tool for CountingTool {
+    fn name(&self) -> &str {
+        "rule_tool"
+    }
+
+    fn description(&self) -> &str {
+        "A tool guarded by permission rules"
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {"type": "string"}
+            }
+        })
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::ExecuteShell]
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        _input: serde_json::Value,
+        _context: &ToolExecutionContext,
+    ) -> crate::brain::tools::Result<ToolResult> {
+        self.runs.fetch_add(1, Ordering::SeqCst);
+        Ok(ToolResult::success("Rule tool executed".to_string()))
+    }
+}
+
+/// Run one turn calling `rule_tool` under `rules`. Returns the approval
+/// prompts shown and how often the tool ran.
+async fn run_with_rules(rules: &[&str], auto_approve: bool) -> (Vec<ToolApprovalInfo>, usize) {
+    let db = Database::connect_in_memory().await.unwrap();
+    db.run_migrations().await.unwrap();
+    let context = ServiceContext::new(db.pool().clone());
+
+    let tool = Arc::new(CountingTool {
+        runs: AtomicUsize::new(0),
+    });
+    let registry = ToolRegistry::new();
+    registry.register(tool.clone());
+
+    let prompts = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let prompts_clone = Arc::clone(&prompts);
+    let approval_cb: ApprovalCallback = Arc::new(move |info| {
+        prompts_clone.lock().unwrap().push(info);
+        Box::pin(async move { Ok((true, false)) })
+    });
+
+    let rules: Vec<String> = rules.iter().map(|r| r.to_string()).collect();
+    let agent_service = AgentService::new_for_test(
+        Arc::new(MockProviderWithNamedTool::new("rule_tool")),
+        context.clone(),
+    )
+    .await
+    .with_tool_registry(Arc::new(registry))
+    .with_auto_approve_tools(auto_approve)
+    .with_approval_callback(Some(approval_cb))
+    .with_permissions(Permissions::new(&rules));
+
+    let session = SessionService::new(context)
+        .create_session(Some("Permission Rules Test".to_string()))
+        .await
+        .unwrap();
+    agent_service
+        .send_message_with_tools(session.id, "Use the rule tool".to_string(), None)
+        .await
+        .unwrap();
+
+    let prompts = prompts.lock().unwrap().clone();
+    (prompts, tool.runs.load(Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn test_deny_rule_blocks_without_prompting() {
+    let (prompts, runs) = run_with_rules(&["deny rule_tool", "allow rule_tool"], false).await;
+
+    assert!(prompts.is_empty(), "denied calls never reach the prompt");
+    assert_eq!(runs, 0, "denied calls never run");
+}
+
+#[tokio::test]
+async fn test_allow_rule_skips_prompt() {
+    let (prompts, runs) = run_with_rules(&["allow rule_*"], false).await;
+
+    assert!(prompts.is_empty(), "allowed calls skip the prompt");
+    assert_eq!(runs, 1);
+}
+
+#[tokio::test]
+async fn test_ask_rule_prompts_despite_auto_approve() {
+    let (prompts, runs) = run_with_rules(&["ask rule_tool", "allow rule_tool"], true).await;
+
+    assert_eq!(
+        prompts.len(),
+        1,
+        "ask rules prompt even when auto-approving"
+    );
+    assert_eq!(prompts[0].ask_rule.as_deref(), Some("ask rule_tool"));
+    assert_eq!(prompts[0].suggested_rule, "rule_tool");
+    assert_eq!(runs, 1);
+}
+
+#[tokio::test]
+async fn test_unmatched_calls_follow_approval_policy() {
+    let (prompts, runs) = run_with_rules(&["deny other_tool"], false).await;
+
+    assert_eq!(
+        prompts.len(),
+        1,
+        "no rule matched — the tool's own policy asks"
+    );
+    assert_eq!(prompts[0].ask_rule, None);
+    assert_eq!(runs, 1);
+}
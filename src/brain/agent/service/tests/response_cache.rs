@@ -0,0 +1,56 @@
+use super::*;
+use crate::brain::provider::response_cache::CacheLimits;
+use crate::brain::provider::{CachingProvider, ResponseCache};
+use crate::db::repository::UsageLedgerRepository;
+
+#[tokio::test]
+async fn test_cached_turn_is_recorded_as_saving() {
+    let db = Database::connect_in_memory().await.unwrap();
+    db.run_migrations().await.unwrap();
+    let context = ServiceContext::new(db.pool().clone());
+
+    let cache = Arc::new(ResponseCache::new(
+        db.pool().clone(),
+        CacheLimits::default(),
+    ));
+    let provider = Arc::new(CachingProvider::new(Arc::new(MockProvider), cache));
+    let agent_service = AgentService::new_for_test(provider, context.clone()).await;
+    agent_service.set_response_cache_ttl(Some(3600));
+
+    let session_service = SessionService::new(context);
+    let first = session_service
+        .create_session(Some("First".to_string()))
+        .await
+        .unwrap();
+    let second = session_service
+        .create_session(Some("Second".to_string()))
+        .await
+        .unwrap();
+
+    let billed = agent_service
+        .send_message_with_tools(first.id, "Hello".to_string(), None)
+        .await
+        .unwrap();
+    let cached = agent_service
+        .send_message_with_tools(second.id, "Hello".to_string(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(cached.content, billed.content);
+    assert_eq!(billed.usage.input_tokens, 10);
+    assert_eq!(cached.usage.input_tokens, 0);
+    assert_eq!(cached.cost, 0.0);
+
+    let second = session_service
+        .get_session_required(second.id)
+        .await
+        .unwrap();
+    assert_eq!(second.token_count, 0);
+
+    let (hits, saved) = UsageLedgerRepository::new(db.pool().clone())
+        .cache_savings()
+        .await
+        .unwrap();
+    assert_eq!(hits, 1);
+    assert!(saved > 0.0);
+}
@@ -0,0 +1,239 @@
+//! A tool result can name the model's next step: the loop sends it as the
+//! next request's `tool_choice`. The plan tool uses this to force `start_task`
+//! after approval and tool use while a task runs.
+
+use super::*;
+use crate::brain::provider::ToolChoice;
+use crate::brain::tools::plan_tool::PlanTool;
+
+/// Mock provider that makes one scripted tool call per response, then
+/// answers in text, recording each request's `tool_choice`.
+struct MockProviderWithScript {
+    calls: Vec<(String, serde_json::Value)>,
+    choices: std::sync::Mutex<Vec<Option<ToolChoice>>>,
+}
+
+impl MockProviderWithScript {
+    fn new(calls: Vec<(&str, serde_json::Value)>) -> Self {
+        Self {
+            calls: calls
+                .into_iter()
+                .map(|(name, input)| (name.to_string(), input))
+                .collect(),
+            choices: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    fn choices(&self) -> Vec<Option<ToolChoice>> {
+        self.choices.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Provider for MockProviderWithScript {
+    async fn complete(&self, request: LLMRequest) -> crate::brain::provider::Result<LLMResponse> {
+        let call_num = {
+            let mut choices = self.choices.lock().unwrap();
+            choices.push(request.tool_choice.clone());
+            choices.len() - 1
+        };
+
+        let (content, stop_reason) = match self.calls.get(call_num) {
+            Some((name, input)) => (
+                vec![ContentBlock::ToolUse {
+                    id: format!("tool-call-{call_num}"),
+                    name: name.clone(),
+                    input: input.clone(),
+                }],
+                StopReason::ToolUse,
+            ),
+            None => (
+                vec![ContentBlock::Text {
+                    text: "Done.".to_string(),
+                }],
+                StopReason::EndTurn,
+            ),
+        };
+        Ok(LLMResponse {
+            id: format!("test-response-{call_num}"),
+            model: "mock-model".to_string(),
+            content,
+            stop_reason: Some(stop_reason),
+            usage: TokenUsage {
+                input_tokens: 10,
+                output_tokens: 20,
+                ..Default::default()
+            },
+        })
+    }
+
+    async fn stream(&self, request: LLMRequest) -> crate::brain::provider::Result<ProviderStream> {
+        use crate::brain::provider::{ContentDelta, MessageDelta, StreamEvent, StreamMessage};
+
+        let response = self.complete(request).await?;
+        let mut events = vec![Ok(StreamEvent::MessageStart {
+            message: StreamMessage {
+                id: response.id.clone(),
+                model: response.model.clone(),
+                role: Role::Assistant,
+                usage: response.usage,
+            },
+        })];
+        for (i, block) in response.content.iter().enumerate() {
+            match block {
+                ContentBlock::Text { text } => {
+                    events.push(Ok(StreamEvent::ContentBlockStart {
+                        index: i,
+                        content_block: ContentBlock::Text {
+                            text: String::new(),
+                        },
+                    }));
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index: i,
+                        delta: ContentDelta::TextDelta { text: text.clone() },
+                    }));
+                }
+                ContentBlock::ToolUse { id, name, input } => {
+                    events.push(Ok(StreamEvent::ContentBlockStart {
+                        index: i,
+                        content_block: ContentBlock::ToolUse {
+                            id: id.clone(),
+                            name: name.clone(),
+                            input: serde_json::Value::Object(Default::default()),
+                        },
+                    }));
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index: i,
+                        delta: ContentDelta::InputJsonDelta {
+                            partial_json: serde_json::to_string(input).unwrap_or_default(),
+                        },
+                    }));
+                }
+                _ => {
+                    events.push(Ok(StreamEvent::ContentBlockStart {
+                        index: i,
+                        content_block: block.clone(),
+                    }));
+                }
+            }
+            events.push(Ok(StreamEvent::ContentBlockStop { index: i }));
+        }
+        events.push(Ok(StreamEvent::MessageDelta {
+            delta: MessageDelta {
+                stop_reason: response.stop_reason,
+                stop_sequence: None,
+            },
+            usage: response.usage,
+        }));
+        events.push(Ok(StreamEvent::MessageStop));
+        Ok(Box::pin(futures::stream::iter(events)))
+    }
+
+    fn name(&self) -> &str {
+        "mock-script"
+    }
+
+    fn default_model(&self) -> &str {
+        "mock-model"
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        vec!["mock-model".to_string()]
+    }
+
+    fn context_window(&self, _model: &str) -> Option<u32> {
+        Some(4096)
+    }
+
+    fn calculate_cost(&self, _model: &str, _input: u32, _output: u32) -> f64 {
+        0.001
+    }
+}
+
+#[tokio::test]
+async fn test_plan_flow_forces_its_next_steps() {
+    let db = Database::connect_in_memory().await.unwrap();
+    db.run_migrations().await.unwrap();
+    let context = ServiceContext::new(db.pool().clone());
+
+    let provider = Arc::new(MockProviderWithScript::new(vec![
+        (
+            "plan",
+            serde_json::json!({
+                "operation": "create",
+                "title": "Add a greeting",
+                "description": "Print hello on startup"
+            }),
+        ),
+        (
+            "plan",
+            serde_json::json!({
+                "operation": "add_task",
+                "title": "Write the greeting",
+                "description": "Print hello in main",
+                "task_type": "edit"
+            }),
+        ),
+        ("plan", serde_json::json!({"operation": "finalize"})),
+        (
+            "plan",
+            serde_json::json!({"operation": "start_task", "task_order": 1}),
+        ),
+        ("test_tool", serde_json::json!({"message": "hello"})),
+    ]));
+    let registry = ToolRegistry::new();
+    registry.register(Arc::new(PlanTool));
+    registry.register(Arc::new(MockTool));
+
+    let agent_service = AgentService::new_for_test(provider.clone(), context.clone())
+        .await
+        .with_tool_registry(Arc::new(registry))
+        .with_auto_approve_tools(true);
+
+    let session = SessionService::new(context)
+        .create_session(Some("Plan Flow Test".to_string()))
+        .await
+        .unwrap();
+    let result = agent_service
+        .send_message_with_tools(session.id, "Plan and do it".to_string(), None)
+        .await;
+    let _ = std::fs::remove_file(
+        crate::config::opencrabs_home()
+            .join("agents")
+            .join("session")
+            .join(format!(".opencrabs_plan_{}.json", session.id)),
+    );
+    result.unwrap();
+
+    assert_eq!(
+        provider.choices(),
+        vec![
+            None,
+            None,
+            None,
+            Some(ToolChoice::Tool("plan".to_string())),
+            Some(ToolChoice::Required),
+            None,
+        ],
+        "approval forces start_task, a started task forces a tool call"
+    );
+}
+
+#[test]
+fn test_tool_result_next_tool_choice() {
+    use crate::brain::tools::ToolResult;
+
+    assert_eq!(ToolResult::success("ok".into()).next_tool_choice(), None);
+    assert_eq!(
+        ToolResult::success("ok".into())
+            .with_next_tool_choice("required")
+            .next_tool_choice(),
+        Some(ToolChoice::Required)
+    );
+    assert_eq!(
+        ToolResult::success("ok".into())
+            .with_next_tool_choice("plan")
+            .next_tool_choice(),
+        Some(ToolChoice::Tool("plan".to_string()))
+    );
+}
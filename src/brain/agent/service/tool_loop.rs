@@ -2,10 +2,13 @@ use super::builder::AgentService;
 use super::types::*;
 use crate::brain::agent::context::AgentContext;
 use crate::brain::agent::error::{AgentError, Result};
-use crate::brain::provider::{ContentBlock, LLMRequest, LLMResponse, Message};
-use crate::brain::tools::ToolExecutionContext;
+use crate::brain::provider::{ContentBlock, LLMRequest, LLMResponse, Message, ToolChoice};
+use crate::brain::tools::{ToolExecutionContext, ToolResult};
+use crate::config::CompactionStrategy;
 use crate::services::{MessageService, SessionService};
+use crate::utils::permissions::{PermissionAction, Permissions};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
@@ -100,6 +103,9 @@ pub fn is_user_correction(msg: &str) -> bool {
 impl AgentService {
     /// Enforce context budget with two-tier enforcement.
     ///
+    /// Tier 0 — past `[compaction] prune_at`: prune old tool output in
+    /// place. Cheap, no LLM call; often enough to stay under Tier 1.
+    ///
     /// Tier 1 — soft trigger at 65%: try LLM compaction (up to 3 retries),
     /// then re-compact if still over. Preserves context via summaries.
     ///
@@ -127,7 +133,7 @@ impl AgentService {
         // ~50k tokens and triggered emergency truncation at 140k/200k (70%
         // real usage) instead of letting the conversation use the full window.
         let effective_max = context.max_tokens;
-        let usage_pct = if effective_max > 0 {
+        let mut usage_pct = if effective_max > 0 {
             (context.token_count as f64 / effective_max as f64) * 100.0
         } else {
             100.0
@@ -140,6 +146,60 @@ impl AgentService {
             usage_pct,
         );
 
+        // ── Tier 0: prune old tool output before anything lossier ──
+        if self.compaction.strategy != CompactionStrategy::Summarize
+            && usage_pct > self.compaction.prune_at * 100.0
+        {
+            let report = context.prune_tool_results(&self.compaction);
+            // A pass skips results already pruned in memory (earlier in this
+            // turn), which the stored totals cover; a freshly loaded context
+            // is pruned from scratch.
+            let carried_over =
+                crate::brain::agent::pruning::count_pruned(&context.messages) > report.pruned();
+            let (previously, total) = {
+                let mut pruned_results = self
+                    .pruned_results
+                    .lock()
+                    .expect("pruned_results lock poisoned");
+                let previously = pruned_results.get(&session_id).copied().unwrap_or_default();
+                let total = if carried_over {
+                    previously + report
+                } else {
+                    report
+                };
+                pruned_results.insert(session_id, total);
+                (previously, total)
+            };
+            if report.pruned() > 0 {
+                usage_pct = if effective_max > 0 {
+                    (context.token_count as f64 / effective_max as f64) * 100.0
+                } else {
+                    100.0
+                };
+                tracing::debug!(
+                    "Pruned {} tool results ({} elided, {} deduplicated), freed {} tokens — now at {:.0}%",
+                    report.pruned(),
+                    report.elided,
+                    report.deduplicated,
+                    report.tokens_saved,
+                    usage_pct,
+                );
+                if let Some(cb) = progress_callback {
+                    // Context is reloaded and re-pruned every turn; only
+                    // results pruned for the first time get a notice
+                    if total.pruned() > previously.pruned() {
+                        cb(
+                            session_id,
+                            ProgressEvent::ContextPruned {
+                                message: total.since(&previously).summary(),
+                            },
+                        );
+                    }
+                    cb(session_id, ProgressEvent::TokenCount(context.token_count));
+                }
+            }
+        }
+
         // ── Tier 2: 90% hard floor — truncate to 80%, then fall through to Tier 1 compaction ──
         if usage_pct >= 90.0 {
             tracing::warn!(
@@ -185,7 +245,7 @@ impl AgentService {
         } else {
             100.0
         };
-        if usage_pct <= 65.0 {
+        if usage_pct <= 65.0 || self.compaction.strategy == CompactionStrategy::Prune {
             return None;
         }
 
@@ -328,6 +388,74 @@ impl AgentService {
         });
     }
 
+    /// Permission rules in effect for this service's tool calls.
+    fn permissions(&self) -> Permissions {
+        self.permissions
+            .clone()
+            .unwrap_or_else(crate::utils::permissions::current)
+    }
+
+    /// Whether a response's tool calls can run concurrently: more than one
+    /// call, each to a registered read-only tool that won't prompt for
+    /// approval or be denied by a permission rule. Anything else runs one
+    /// call at a time so approval prompts stay serialized.
+    fn runs_in_parallel(
+        &self,
+        tool_uses: &[(String, String, Value)],
+        tool_context: &ToolExecutionContext,
+        has_override_approval: bool,
+    ) -> bool {
+        let permissions = self.permissions();
+        self.max_parallel_tools > 1
+            && tool_uses.len() > 1
+            && tool_uses.iter().all(|(_, tool_name, tool_input)| {
+                let rule =
+                    permissions.evaluate(tool_name, tool_input, &tool_context.working_directory);
+                self.tool_registry.get(tool_name).is_some_and(|tool| {
+                    tool.is_read_only()
+                        && match rule.map(|r| r.action) {
+                            Some(PermissionAction::Allow) => true,
+                            Some(PermissionAction::Ask | PermissionAction::Deny) => false,
+                            None => {
+                                !(tool.requires_approval_for_input(tool_input)
+                                    && (!self.auto_approve_tools || has_override_approval)
+                                    && !tool_context.auto_approve)
+                            }
+                        }
+                })
+            })
+    }
+
+    /// Execute read-only tool calls at most `max_parallel_tools` at a time.
+    /// Results come back in call order; `None` if the turn was cancelled.
+    async fn execute_tools_concurrently(
+        &self,
+        tool_uses: &[(String, String, Value)],
+        tool_context: &ToolExecutionContext,
+        cancel_token: Option<&CancellationToken>,
+    ) -> Option<Vec<crate::brain::tools::Result<ToolResult>>> {
+        use futures::StreamExt;
+
+        let mut approved_context = tool_context.clone();
+        approved_context.auto_approve = true;
+        let approved_context = &approved_context;
+        let batch = futures::stream::iter(tool_uses.iter().cloned())
+            .map(|(_, tool_name, tool_input)| async move {
+                self.tool_registry
+                    .execute(&tool_name, tool_input, approved_context)
+                    .await
+            })
+            .buffered(self.max_parallel_tools)
+            .collect::<Vec<_>>();
+        tokio::select! {
+            biased;
+            _ = async {
+                if let Some(t) = cancel_token { t.cancelled().await } else { std::future::pending().await }
+            } => None,
+            results = batch => Some(results),
+        }
+    }
+
     /// Core tool-execution loop — called by all public shims.
     /// `override_approval_callback` and `override_progress_callback` take
     /// precedence over the service-level callbacks (used by Telegram, etc.)
@@ -343,6 +471,9 @@ impl AgentService {
         channel: &str,
         channel_chat_id: Option<&str>,
     ) -> Result<AgentResponse> {
+        self.set_session_channel(session_id, channel);
+        let user_message = self.submit_prompt(session_id, user_message).await?;
+
         // Track this request for restart recovery
         let pending_repo = crate::db::PendingRequestRepository::new(self.context.pool());
         let request_id = Uuid::new_v4();
@@ -396,6 +527,13 @@ impl AgentService {
                 tx.send(crate::brain::agent::ChannelSessionEvent::ProcessingFinished(session_id));
         }
 
+        // The turn's usage is in the ledger now (or never will be)
+        crate::usage::budget::clear_unrecorded(session_id);
+
+        if let Ok(ref response) = result {
+            self.finish_turn(session_id, &response.content).await;
+        }
+
         // Request finished — delete the tracking row. Only PROCESSING rows
         // survive (meaning the process crashed/restarted mid-request).
         if let Err(e) = pending_repo.delete(request_id).await {
@@ -503,15 +641,14 @@ impl AgentService {
         }
 
         let mut context =
-            AgentContext::from_db_messages(session_id, db_messages, context_window as usize);
+            AgentContext::from_db_messages(session_id, db_messages, context_window as usize)
+                .with_tokenizer(self.tokenizer_for(session_id, &model_name));
 
         // Add system brain if available (count its tokens so context.token_count
         // reflects the full API input from the start — prevents gross undercount
-        // that causes the TUI context counter to jump wildly on first calibration)
-        if let Some(brain) = &self.default_system_brain {
-            context.token_count += AgentContext::estimate_tokens(brain);
-            context.system_brain = Some(brain.clone());
-        }
+        // that causes the TUI context counter to jump wildly on first calibration).
+        // Project brain files and skills for the working directory are layered on top.
+        self.refresh_system_brain(session_id, &mut context);
 
         // Emit token count immediately after DB reload so the TUI reflects the
         // real post-compaction value. Without this, the TUI shows the stale
@@ -709,6 +846,11 @@ impl AgentService {
         let mut total_output_tokens = 0u32;
         let mut total_cache_creation = 0u32;
         let mut total_cache_read = 0u32;
+        // Calls answered by the response cache: not billed, but their
+        // would-be cost is recorded in the usage ledger as savings.
+        let mut cached_calls = 0u32;
+        let mut cached_input_tokens = 0u32;
+        let mut cached_output_tokens = 0u32;
         // Last iteration's prompt size, used for the "current context
         // usage" indicator. Distinct from `total_input_tokens` which
         // sums across every iteration for cost/billing. The UI ctx
@@ -728,6 +870,13 @@ impl AgentService {
         // letting pathological cases chew quota forever.
         let mut phantom_retries_used: u32 = 0;
         const MAX_PHANTOM_RETRIES: u32 = 3;
+        // Tool choice for the NEXT request only. Seeded from the agent's
+        // override (cron jobs forcing a specific tool), set by a tool result
+        // that names the next step (the plan tool after approval and while a
+        // task runs), and re-armed with `Required` after a phantom tool call,
+        // so the retry can't answer in prose again. Consumed on use so the
+        // loop can still finish.
+        let mut next_tool_choice: Option<ToolChoice> = self.tool_choice();
         // Bounded retry for the "reasoning-only, no answer" failure mode:
         // MLX Qwen models periodically emit finish_reason=stop after only
         // reasoning_content chunks — zero text, zero tool calls — so the
@@ -877,6 +1026,7 @@ impl AgentService {
             request.working_directory =
                 Some(self.get_working_directory().to_string_lossy().to_string());
             request.session_id = Some(session_id);
+            request.cache_ttl = self.response_cache_ttl();
 
             if let Some(system) = &context.system_brain {
                 request = request.with_system(system.clone());
@@ -889,6 +1039,10 @@ impl AgentService {
                 let tool_defs = self.tool_registry.get_tool_definitions();
                 tracing::debug!("Adding {} tool definitions to request", tool_defs.len());
                 request = request.with_tools(tool_defs);
+                if let Some(choice) = next_tool_choice.take() {
+                    tracing::debug!("Constraining tool use this iteration: {:?}", choice);
+                    request = request.with_tool_choice(choice);
+                }
             } else {
                 tracing::warn!("No tools registered in tool registry!");
             }
@@ -1078,6 +1232,9 @@ impl AgentService {
                                             ContentBlock::Thinking { thinking, .. } => {
                                                 crate::brain::tokenizer::count_tokens(thinking)
                                             }
+                                            ContentBlock::RedactedThinking { data } => {
+                                                crate::brain::tokenizer::count_tokens(data)
+                                            }
                                             ContentBlock::Image { .. } => 1000,
                                         })
                                         .sum::<usize>()
@@ -1381,6 +1538,8 @@ impl AgentService {
                             // (otherwise the next tool-loop iteration
                             // would send the primary's model name to the
                             // fallback provider → 400).
+                            context.tokenizer =
+                                crate::brain::tokenizer::for_model(&fb_name, &fb_model);
                             model_name = fb_model;
                             resp
                         }
@@ -1927,20 +2086,26 @@ impl AgentService {
                 );
                 estimate
             };
-            total_input_tokens += call_input_tokens;
             last_iter_input_tokens = call_input_tokens;
-            total_output_tokens += response.usage.output_tokens;
-            // Use billing fields (cumulative across CLI rounds) when available
-            total_cache_creation += if response.usage.billing_cache_creation > 0 {
-                response.usage.billing_cache_creation
-            } else {
-                response.usage.cache_creation_tokens
-            };
-            total_cache_read += if response.usage.billing_cache_read > 0 {
-                response.usage.billing_cache_read
+            if response.usage.response_cache_hit {
+                cached_calls += 1;
+                cached_input_tokens += call_input_tokens;
+                cached_output_tokens += response.usage.output_tokens;
             } else {
-                response.usage.cache_read_tokens
-            };
+                total_input_tokens += call_input_tokens;
+                total_output_tokens += response.usage.output_tokens;
+                // Use billing fields (cumulative across CLI rounds) when available
+                total_cache_creation += if response.usage.billing_cache_creation > 0 {
+                    response.usage.billing_cache_creation
+                } else {
+                    response.usage.cache_creation_tokens
+                };
+                total_cache_read += if response.usage.billing_cache_read > 0 {
+                    response.usage.billing_cache_read
+                } else {
+                    response.usage.cache_read_tokens
+                };
+            }
 
             // Calibrate context token count from the provider's reported usage.
             //
@@ -2673,6 +2838,7 @@ impl AgentService {
                          call the tools.]"
                     };
                     context.add_message(Message::user(nudge.to_string()));
+                    next_tool_choice = Some(ToolChoice::Required);
                     continue;
                 }
 
@@ -2949,11 +3115,63 @@ impl AgentService {
                 }
             }
 
+            // PreToolUse hooks run before permission rules and approval, so
+            // both judge the input a hook rewrote; denied calls never reach them.
+            let mut hook_denials = HashMap::new();
+            for (tool_id, tool_name, tool_input) in &mut tool_uses {
+                match self
+                    .tool_registry
+                    .pre_tool_use(tool_name, tool_input.clone(), &tool_context)
+                    .await
+                {
+                    Ok(input) => *tool_input = input,
+                    Err(reason) => {
+                        hook_denials.insert(tool_id.clone(), reason);
+                    }
+                }
+            }
+            // Every call below has been through them, so execute skips them
+            tool_context.pre_tool_use_done = true;
+
+            // Files touched below the working directory bring their
+            // project brain files into scope for the next call
+            self.note_touched_files(session_id, &tool_uses);
+
             // Execute tools and build response message
             let mut tool_results = Vec::new();
             let mut tool_descriptions: Vec<String> = Vec::new(); // For DB persistence
             let mut tool_outputs: Vec<(bool, String)> = Vec::new(); // (success, output) parallel to descriptions
 
+            // Read-only batches run up front, concurrently; the loop below
+            // then takes their results in call order, so the follow-up
+            // message and progress events keep the model's ordering.
+            let mut prefetched = None;
+            if hook_denials.is_empty()
+                && self.runs_in_parallel(&tool_uses, &tool_context, has_override_approval)
+            {
+                tracing::info!(
+                    "Executing {} read-only tools concurrently (max {}, iteration {})",
+                    tool_uses.len(),
+                    self.max_parallel_tools,
+                    iteration,
+                );
+                if let Some(ref cb) = progress_callback {
+                    for (_, tool_name, tool_input) in &tool_uses {
+                        cb(
+                            session_id,
+                            ProgressEvent::ToolStarted {
+                                tool_name: tool_name.clone(),
+                                tool_input: tool_input.clone(),
+                            },
+                        );
+                    }
+                }
+                prefetched = self
+                    .execute_tools_concurrently(&tool_uses, &tool_context, cancel_token.as_ref())
+                    .await
+                    .map(Vec::into_iter);
+            }
+
             for (tool_id, tool_name, tool_input) in tool_uses {
                 // Check for cancellation before each tool
                 if let Some(ref token) = cancel_token
@@ -2975,8 +3193,10 @@ impl AgentService {
                 // Build short description for DB persistence
                 tool_descriptions.push(Self::format_tool_summary(&tool_name, &tool_input));
 
-                // Emit tool started progress
-                if let Some(ref cb) = progress_callback {
+                // Emit tool started progress (already sent for a concurrent batch)
+                if prefetched.is_none()
+                    && let Some(ref cb) = progress_callback
+                {
                     cb(
                         session_id,
                         ProgressEvent::ToolStarted {
@@ -2986,16 +3206,55 @@ impl AgentService {
                     );
                 }
 
+                if let Some(reason) = hook_denials.remove(&tool_id) {
+                    self.record_tool_feedback(session_id, &tool_name, false, Some("hook_denied"));
+                    let err = format!("Blocked by hook: {}", reason);
+                    tool_outputs.push((false, err.clone()));
+                    tool_results.push(ContentBlock::ToolResult {
+                        tool_use_id: tool_id,
+                        content: err,
+                        is_error: Some(true),
+                    });
+                    continue;
+                }
+
+                // Permission rules come first: the first matching rule blocks
+                // the call, skips the prompt, or forces one.
+                let rule = self
+                    .permissions()
+                    .evaluate(&tool_name, &tool_input, &tool_context.working_directory)
+                    .cloned();
+                if let Some(rule) = rule.as_ref().filter(|r| r.action == PermissionAction::Deny) {
+                    tracing::warn!("Tool '{}' blocked by permission rule '{}'", tool_name, rule);
+                    self.record_tool_feedback(
+                        session_id,
+                        &tool_name,
+                        false,
+                        Some("permission_rule_denied"),
+                    );
+                    let err = format!("Denied by permission rule `{}`", rule);
+                    tool_outputs.push((false, err.clone()));
+                    tool_results.push(ContentBlock::ToolResult {
+                        tool_use_id: tool_id,
+                        content: err,
+                        is_error: Some(true),
+                    });
+                    continue;
+                }
+
                 // Check if approval is needed.
                 // Each channel's make_approval_callback() already checks
                 // check_approval_policy() from config — the tool loop only
-                // respects the auto_approve_tools flag and tool-level policy.
-                let needs_approval = if let Some(tool) = self.tool_registry.get(&tool_name) {
-                    tool.requires_approval_for_input(&tool_input)
-                        && (!self.auto_approve_tools || has_override_approval)
-                        && !tool_context.auto_approve
-                } else {
-                    false
+                // respects permission rules, the auto_approve_tools flag and
+                // tool-level policy.
+                let needs_approval = match rule.as_ref().map(|r| r.action) {
+                    Some(PermissionAction::Allow) => false,
+                    Some(PermissionAction::Ask) => true,
+                    _ => self.tool_registry.get(&tool_name).is_some_and(|tool| {
+                        tool.requires_approval_for_input(&tool_input)
+                            && (!self.auto_approve_tools || has_override_approval)
+                            && !tool_context.auto_approve
+                    }),
                 };
 
                 // Request approval if needed
@@ -3013,6 +3272,15 @@ impl AgentService {
                                     .iter()
                                     .map(|c| format!("{:?}", c))
                                     .collect(),
+                                ask_rule: rule
+                                    .as_ref()
+                                    .filter(|r| r.action == PermissionAction::Ask)
+                                    .map(ToString::to_string),
+                                suggested_rule: crate::utils::permissions::suggest_rule(
+                                    &tool_name,
+                                    &tool_input,
+                                    &tool_context.working_directory,
+                                ),
                             }
                         } else {
                             // Tool not found, skip approval
@@ -3068,6 +3336,7 @@ impl AgentService {
                                         .shared_working_directory
                                         .clone(),
                                     service_context: tool_context.service_context.clone(),
+                                    pre_tool_use_done: tool_context.pre_tool_use_done,
                                 };
 
                                 // Execute the tool with approved context, racing against cancel
@@ -3083,6 +3352,9 @@ impl AgentService {
                                 };
                                 match exec_result {
                                     Ok(result) => {
+                                        if let Some(choice) = result.next_tool_choice() {
+                                            next_tool_choice = Some(choice);
+                                        }
                                         let success = result.success;
                                         let images = result.images;
                                         let content = if result.success {
@@ -3265,18 +3537,25 @@ impl AgentService {
                 // so the registry's own approval check doesn't block it)
                 let mut approved_context = tool_context.clone();
                 approved_context.auto_approve = true;
-                let exec_result = tokio::select! {
-                    biased;
-                    _ = async {
-                        if let Some(ref t) = cancel_token { t.cancelled().await } else { std::future::pending().await }
-                    } => {
-                        tracing::warn!("🛑 Tool '{}' cancelled mid-execution", tool_name);
-                        break;
+                let exec_result = if let Some(r) = prefetched.as_mut().and_then(Iterator::next) {
+                    r
+                } else {
+                    tokio::select! {
+                        biased;
+                        _ = async {
+                            if let Some(ref t) = cancel_token { t.cancelled().await } else { std::future::pending().await }
+                        } => {
+                            tracing::warn!("🛑 Tool '{}' cancelled mid-execution", tool_name);
+                            break;
+                        }
+                        r = self.tool_registry.execute(&tool_name, tool_input, &approved_context) => r,
                     }
-                    r = self.tool_registry.execute(&tool_name, tool_input, &approved_context) => r,
                 };
                 match exec_result {
                     Ok(result) => {
+                        if let Some(choice) = result.next_tool_choice() {
+                            next_tool_choice = Some(choice);
+                        }
                         let success = result.success;
                         let images = result.images;
                         let content = if result.success {
@@ -3487,6 +3766,10 @@ impl AgentService {
             };
             context.add_message(tool_result_msg);
 
+            // Pick up project brain files and skills from a `/cd`, newly
+            // touched subdirectories or added skills before the next LLM call
+            self.refresh_system_brain(session_id, &mut context);
+
             // Fire token count update after tool results are added — keeps TUI in sync.
             if let Some(ref cb) = progress_callback {
                 cb(session_id, ProgressEvent::TokenCount(context.token_count));
@@ -3668,11 +3951,36 @@ impl AgentService {
             .await
             .map_err(|e| AgentError::Database(e.to_string()))?;
 
-        // Update session token usage
-        session_service
-            .update_session_usage(session_id, total_tokens as i32, cost)
-            .await
-            .map_err(|e| AgentError::Database(e.to_string()))?;
+        // Update session token usage. A turn answered entirely from the
+        // response cache billed nothing and only leaves cache-hit rows.
+        if total_tokens > 0 || cost > 0.0 || cached_calls == 0 {
+            session_service
+                .update_session_usage_for_channel(
+                    session_id,
+                    total_tokens as i32,
+                    cost,
+                    &self.channel_for_session(session_id),
+                )
+                .await
+                .map_err(|e| AgentError::Database(e.to_string()))?;
+        }
+        if cached_calls > 0 {
+            let saved_cost = self
+                .provider_for_session(session_id)
+                .calculate_cost_with_cache(
+                    &response.model,
+                    cached_input_tokens,
+                    cached_output_tokens,
+                    0,
+                    0,
+                );
+            if let Err(e) = session_service
+                .record_cache_hits(session_id, &response.model, cached_calls, saved_cost)
+                .await
+            {
+                tracing::warn!("Failed to record response cache hits: {}", e);
+            }
+        }
 
         // Notify the TUI that this session was updated (enables live refresh when
         // a remote channel — Telegram, WhatsApp, Discord, Slack — processes a message).
@@ -23,6 +23,11 @@ pub struct ToolApprovalInfo {
     pub tool_input: Value,
     /// Tool capabilities
     pub capabilities: Vec<String>,
+    /// `ask` permission rule that asked for this prompt. Such prompts are
+    /// shown even when the approval policy would auto-approve.
+    pub ask_rule: Option<String>,
+    /// Rule offered as "always allow this pattern", e.g. `bash(cargo test:*)`
+    pub suggested_rule: String,
 }
 
 /// Type alias for approval callback function.
@@ -67,6 +72,10 @@ pub enum ProgressEvent {
     CompactionSummary {
         summary: String,
     },
+    /// Old tool output was pruned from the context (`[compaction]`)
+    ContextPruned {
+        message: String,
+    },
     /// A single build-output line (e.g. "Compiling foo v1.0"). The TUI keeps a
     /// rolling window of the last few lines and clears them on RestartReady.
     BuildLine(String),
@@ -97,6 +106,18 @@ pub enum ProgressEvent {
         bytes: usize,
         reason: String,
     },
+    /// The call is queued behind a configured rate budget
+    /// (`[providers.<name>.rate_limit]`) and will be sent in `wait_secs`.
+    RateLimitWait {
+        provider: String,
+        model: String,
+        wait_secs: u64,
+    },
+    /// A spending budget (`[budget]`) passed its warning threshold, or was
+    /// spent and calls moved to the downgrade model.
+    BudgetAlert {
+        message: String,
+    },
     /// Sticky fallback promoted a new provider/model. Carries structured data
     /// so UIs can update the session + footer without parsing text.
     ProviderSwitched {
@@ -0,0 +1,439 @@
+//! Lifecycle Hooks
+//!
+//! `~/.opencrabs/hooks.toml` runs shell commands at points in the agent
+//! loop, so local policy (format after every edit, block writes to a
+//! directory, audit every bash command) doesn't need a patched binary:
+//!
+//! ```toml
+//! [[hooks]]
+//! event = "PreToolUse"
+//! matcher = "write_file|edit_file"
+//! command = "~/.opencrabs/hooks/no-migrations.sh"
+//! ```
+//!
+//! Each hook gets the event as JSON on stdin. Exit code 2 denies (stderr is
+//! the reason); exit 0 may print `{"decision": "deny", "reason": "..."}`, or
+//! for PreToolUse a replacement `{"tool_input": {...}}` and for
+//! UserPromptSubmit a replacement `{"prompt": "..."}`. Anything else —
+//! other exit codes, timeouts, commands that fail to start — is logged and
+//! lets the action through.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+/// Exit code a hook uses to deny the action.
+pub const DENY_EXIT_CODE: i32 = 2;
+
+/// Point in the agent loop a hook runs at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HookEvent {
+    /// Before a tool runs — may deny the call or rewrite its input
+    PreToolUse,
+    /// After a tool runs — may deny, turning the result into an error the
+    /// model sees along with the reason
+    PostToolUse,
+    /// Before a user message reaches the model — may deny or rewrite it
+    UserPromptSubmit,
+    /// First message of a session in this process
+    SessionStart,
+    /// After the agent finishes its reply
+    Stop,
+}
+
+/// One hook as written in hooks.toml.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookDef {
+    pub event: HookEvent,
+
+    /// Regex the tool name must match (PreToolUse/PostToolUse only).
+    /// Unset matches every tool.
+    #[serde(default)]
+    pub matcher: Option<String>,
+
+    /// Shell command, run with `sh -c` in the session's working directory
+    pub command: String,
+
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// TOML wrapper: `[[hooks]]` array
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HooksFile {
+    #[serde(default)]
+    hooks: Vec<HookDef>,
+}
+
+/// What a hook (or a chain of them) decided.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookOutcome {
+    Allow,
+    Deny(String),
+    /// Carry on with this tool input or prompt instead
+    Rewrite(Value),
+}
+
+/// Hooks loaded from hooks.toml, with their matchers compiled.
+#[derive(Default)]
+pub struct Hooks {
+    hooks: Vec<(HookDef, Option<Regex>)>,
+}
+
+impl Hooks {
+    /// Build from definitions. Disabled hooks and hooks with an invalid
+    /// matcher are dropped (the latter with a warning).
+    pub fn new(defs: Vec<HookDef>) -> Self {
+        let hooks = defs
+            .into_iter()
+            .filter(|def| def.enabled)
+            .filter_map(|def| match def.matcher.as_deref() {
+                None | Some("") | Some("*") => Some((def, None)),
+                Some(pattern) => match Regex::new(&format!("^(?:{pattern})$")) {
+                    Ok(re) => Some((def, Some(re))),
+                    Err(e) => {
+                        tracing::warn!(
+                            "hooks.toml: skipping hook with bad matcher '{pattern}': {e}"
+                        );
+                        None
+                    }
+                },
+            })
+            .collect();
+        Self { hooks }
+    }
+
+    /// Load hooks.toml. A missing file means no hooks; a malformed one is
+    /// logged and ignored.
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        match toml::from_str::<HooksFile>(&content) {
+            Ok(file) => {
+                let hooks = Self::new(file.hooks);
+                if !hooks.is_empty() {
+                    tracing::info!("Loaded {} hook(s) from {}", hooks.len(), path.display());
+                }
+                hooks
+            }
+            Err(e) => {
+                tracing::warn!("Failed to parse {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    fn matching<'a>(
+        &'a self,
+        event: HookEvent,
+        tool_name: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a HookDef> + 'a {
+        self.hooks
+            .iter()
+            .filter(move |(def, matcher)| {
+                def.event == event
+                    && match (matcher, tool_name) {
+                        (Some(re), Some(name)) => re.is_match(name),
+                        _ => true,
+                    }
+            })
+            .map(|(def, _)| def)
+    }
+
+    /// Run every hook for `event` in file order. The first deny wins; a
+    /// rewrite replaces `payload[field]` for the hooks after it.
+    async fn run_chain(
+        &self,
+        event: HookEvent,
+        tool_name: Option<&str>,
+        cwd: &Path,
+        mut payload: Value,
+        field: &str,
+    ) -> HookOutcome {
+        let mut rewritten = false;
+        for def in self.matching(event, tool_name) {
+            match run_hook(def, cwd, &payload, field).await {
+                HookOutcome::Allow => {}
+                HookOutcome::Deny(reason) => return HookOutcome::Deny(reason),
+                HookOutcome::Rewrite(value) => {
+                    payload[field] = value;
+                    rewritten = true;
+                }
+            }
+        }
+        if rewritten {
+            HookOutcome::Rewrite(payload[field].take())
+        } else {
+            HookOutcome::Allow
+        }
+    }
+
+    /// PreToolUse: `Rewrite` carries the tool input to use instead.
+    pub async fn pre_tool_use(
+        &self,
+        session_id: Uuid,
+        cwd: &Path,
+        tool_name: &str,
+        tool_input: &Value,
+    ) -> HookOutcome {
+        let payload = serde_json::json!({
+            "event": HookEvent::PreToolUse,
+            "session_id": session_id,
+            "cwd": cwd,
+            "tool_name": tool_name,
+            "tool_input": tool_input,
+        });
+        self.run_chain(
+            HookEvent::PreToolUse,
+            Some(tool_name),
+            cwd,
+            payload,
+            "tool_input",
+        )
+        .await
+    }
+
+    /// PostToolUse: a deny reason to hand back to the model, if any.
+    pub async fn post_tool_use(
+        &self,
+        session_id: Uuid,
+        cwd: &Path,
+        tool_name: &str,
+        tool_input: &Value,
+        success: bool,
+        output: &str,
+    ) -> Option<String> {
+        let payload = serde_json::json!({
+            "event": HookEvent::PostToolUse,
+            "session_id": session_id,
+            "cwd": cwd,
+            "tool_name": tool_name,
+            "tool_input": tool_input,
+            "success": success,
+            "tool_output": output,
+        });
+        match self
+            .run_chain(HookEvent::PostToolUse, Some(tool_name), cwd, payload, "")
+            .await
+        {
+            HookOutcome::Deny(reason) => Some(reason),
+            _ => None,
+        }
+    }
+
+    /// UserPromptSubmit: the prompt to send, or the deny reason.
+    pub async fn user_prompt_submit(
+        &self,
+        session_id: Uuid,
+        cwd: &Path,
+        prompt: String,
+    ) -> Result<String, String> {
+        let payload = serde_json::json!({
+            "event": HookEvent::UserPromptSubmit,
+            "session_id": session_id,
+            "cwd": cwd,
+            "prompt": prompt,
+        });
+        match self
+            .run_chain(HookEvent::UserPromptSubmit, None, cwd, payload, "prompt")
+            .await
+        {
+            HookOutcome::Allow => Ok(prompt),
+            HookOutcome::Deny(reason) => Err(reason),
+            HookOutcome::Rewrite(Value::String(rewritten)) => Ok(rewritten),
+            HookOutcome::Rewrite(other) => {
+                tracing::warn!("UserPromptSubmit hook returned a non-string prompt: {other}");
+                Ok(prompt)
+            }
+        }
+    }
+
+    /// SessionStart, once per session per process. Decisions are ignored.
+    pub async fn session_start(&self, session_id: Uuid, cwd: &Path) {
+        if self
+            .matching(HookEvent::SessionStart, None)
+            .next()
+            .is_none()
+            || !STARTED
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(session_id)
+        {
+            return;
+        }
+        let payload = serde_json::json!({
+            "event": HookEvent::SessionStart,
+            "session_id": session_id,
+            "cwd": cwd,
+        });
+        self.run_chain(HookEvent::SessionStart, None, cwd, payload, "")
+            .await;
+    }
+
+    /// Stop, with the agent's final reply. Decisions are ignored.
+    pub async fn stop(&self, session_id: Uuid, cwd: &Path, response: &str) {
+        let payload = serde_json::json!({
+            "event": HookEvent::Stop,
+            "session_id": session_id,
+            "cwd": cwd,
+            "response": response,
+        });
+        self.run_chain(HookEvent::Stop, None, cwd, payload, "")
+            .await;
+    }
+}
+
+/// Hooks in effect for the process.
+static HOOKS: LazyLock<RwLock<Arc<Hooks>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(Hooks::load(&default_path()))));
+
+/// Sessions SessionStart has already fired for.
+static STARTED: LazyLock<Mutex<HashSet<Uuid>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// `~/.opencrabs/hooks.toml` (profile-aware)
+pub fn default_path() -> PathBuf {
+    crate::config::opencrabs_home().join("hooks.toml")
+}
+
+/// Hooks currently in effect (loaded from hooks.toml on first use).
+pub fn current() -> Arc<Hooks> {
+    HOOKS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Replace the hooks in effect.
+pub fn install(hooks: Hooks) {
+    *HOOKS.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(hooks);
+}
+
+/// Re-read hooks.toml. Called by the config watcher.
+pub fn reload() {
+    install(Hooks::load(&default_path()));
+}
+
+/// Run one hook and read its decision. `field` names what a rewrite
+/// replaces (`tool_input` or `prompt`; empty when rewrites don't apply).
+async fn run_hook(def: &HookDef, cwd: &Path, payload: &Value, field: &str) -> HookOutcome {
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.arg("-c")
+        .arg(&def.command)
+        .env("OPENCRABS_HOOK_EVENT", format!("{:?}", def.event))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if cwd.is_dir() {
+        cmd.current_dir(cwd);
+    }
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::warn!(
+                "{:?} hook '{}' failed to start: {}",
+                def.event,
+                def.command,
+                e
+            );
+            return HookOutcome::Allow;
+        }
+    };
+    // Feed stdin alongside reading the output so a hook that never reads it
+    // (or fills its stdout pipe first) can't stall us past the timeout.
+    if let Some(mut stdin) = child.stdin.take() {
+        let payload = payload.to_string();
+        tokio::spawn(async move {
+            // A hook that never reads stdin closes the pipe early; that's fine.
+            let _ = stdin.write_all(payload.as_bytes()).await;
+        });
+    }
+    let output = match tokio::time::timeout(
+        Duration::from_secs(def.timeout_secs),
+        child.wait_with_output(),
+    )
+    .await
+    {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            tracing::warn!("{:?} hook '{}' failed: {}", def.event, def.command, e);
+            return HookOutcome::Allow;
+        }
+        Err(_) => {
+            tracing::warn!(
+                "{:?} hook '{}' timed out after {}s",
+                def.event,
+                def.command,
+                def.timeout_secs
+            );
+            return HookOutcome::Allow;
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match output.status.code() {
+        Some(0) => parse_decision(stdout.trim(), field),
+        Some(DENY_EXIT_CODE) => {
+            let reason = stderr.trim();
+            HookOutcome::Deny(if reason.is_empty() {
+                format!("denied by hook '{}'", def.command)
+            } else {
+                reason.to_string()
+            })
+        }
+        code => {
+            tracing::warn!(
+                "{:?} hook '{}' exited with {:?}: {}",
+                def.event,
+                def.command,
+                code,
+                stderr.trim()
+            );
+            HookOutcome::Allow
+        }
+    }
+}
+
+/// Read a hook's stdout. Plain text (or nothing) allows.
+fn parse_decision(stdout: &str, field: &str) -> HookOutcome {
+    let Ok(Value::Object(reply)) = serde_json::from_str::<Value>(stdout) else {
+        return HookOutcome::Allow;
+    };
+    let decision = reply.get("decision").and_then(Value::as_str);
+    if matches!(decision, Some("deny" | "block")) {
+        let reason = reply
+            .get("reason")
+            .and_then(Value::as_str)
+            .unwrap_or("denied by hook");
+        return HookOutcome::Deny(reason.to_string());
+    }
+    match reply.get(field) {
+        Some(value) if !field.is_empty() => HookOutcome::Rewrite(value.clone()),
+        _ => HookOutcome::Allow,
+    }
+}
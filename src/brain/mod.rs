@@ -1,21 +1,27 @@
 //! Brain Module
 //!
 //! The core intelligence layer — LLM providers, agent services, tools, tokenizer,
-//! dynamic system prompt assembly, user-defined slash commands, and self-update.
+//! dynamic system prompt assembly, project brain files, skills, user-defined
+//! slash commands, lifecycle hooks, and self-update.
 
 pub mod agent;
 pub mod commands;
+pub mod hooks;
+pub mod project_brain;
 pub mod prompt_builder;
 pub mod provider;
 pub mod rsi;
 pub mod self_update;
+pub mod skills;
 pub mod tokenizer;
 pub mod tools;
 
 // Brain re-exports
 pub use commands::{CommandLoader, UserCommand};
+pub use project_brain::ProjectBrain;
 pub use prompt_builder::BrainLoader;
 pub use self_update::SelfUpdater;
+pub use skills::Skills;
 
 // LLM re-exports
 pub use agent::{AgentContext, AgentError, AgentService};
@@ -0,0 +1,133 @@
+//! Project Brain
+//!
+//! Brain files that live with a project instead of in `~/.opencrabs/`:
+//! `AGENTS.md` and `OPENCRABS.md` in the working directory and each of its
+//! ancestors, plus those in subdirectories once the agent touches a file
+//! under them. They're layered after the global brain, each under a header
+//! naming the file it came from, and re-read every turn like the global
+//! brain files.
+
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+/// File names picked up as project brain files, in load order per directory
+pub const PROJECT_BRAIN_FILES: &[&str] = &["AGENTS.md", "OPENCRABS.md"];
+
+/// Longest project brain file injected in full
+const MAX_FILE_CHARS: usize = 20_000;
+
+/// Explains the layering to the model, ahead of the files themselves
+const SECTION_INTRO: &str = "Instructions from the project being worked on, found in the \
+working directory, its parent directories and subdirectories you've worked in. They add to \
+the brain files above; where they conflict, the file closest to the code being changed wins.";
+
+/// Project brain files in scope for one session
+#[derive(Debug, Clone)]
+pub struct ProjectBrain {
+    working_directory: PathBuf,
+    /// The global brain directory — its files are already in the system brain
+    global_path: PathBuf,
+    /// Directories below the working directory the agent has touched
+    nested: BTreeSet<PathBuf>,
+}
+
+impl ProjectBrain {
+    /// Project brain for `working_directory`, skipping files in
+    /// `global_path`
+    pub fn new(working_directory: PathBuf, global_path: PathBuf) -> Self {
+        Self {
+            working_directory: normalize(&working_directory),
+            global_path: normalize(&global_path),
+            nested: BTreeSet::new(),
+        }
+    }
+
+    /// Directory the files were discovered from
+    pub fn working_directory(&self) -> &Path {
+        &self.working_directory
+    }
+
+    /// Bring every directory between the working directory and `path` into
+    /// scope. Relative paths resolve against the working directory; paths
+    /// outside it are ignored. Returns whether a new directory was added.
+    pub fn touch(&mut self, path: &Path) -> bool {
+        let path = normalize(&self.working_directory.join(path));
+        let dir = if path.is_dir() {
+            path.as_path()
+        } else {
+            match path.parent() {
+                Some(parent) => parent,
+                None => return false,
+            }
+        };
+        let Ok(relative) = dir.strip_prefix(&self.working_directory) else {
+            return false;
+        };
+
+        let mut added = false;
+        let mut current = self.working_directory.clone();
+        for component in relative.components() {
+            current.push(component);
+            added |= self.nested.insert(current.clone());
+        }
+        added
+    }
+
+    /// Project brain files on disk, outermost directory first
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<&Path> = self.working_directory.ancestors().collect();
+        dirs.reverse();
+        dirs.extend(self.nested.iter().map(PathBuf::as_path));
+
+        dirs.into_iter()
+            .filter(|dir| *dir != self.global_path)
+            .flat_map(|dir| PROJECT_BRAIN_FILES.iter().map(move |name| dir.join(name)))
+            .filter(|path| path.is_file())
+            .collect()
+    }
+
+    /// The system brain section for the files in scope, or `None` when
+    /// there are none (or they're all empty)
+    pub fn section(&self) -> Option<String> {
+        let mut files = String::new();
+        for path in self.files() {
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let trimmed = content.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            files.push_str(&format!("--- {} (project) ---\n", path.display()));
+            match trimmed.char_indices().nth(MAX_FILE_CHARS) {
+                Some((end, _)) => files.push_str(&format!(
+                    "{}\n[… truncated — read the file for the rest]\n\n",
+                    &trimmed[..end]
+                )),
+                None => files.push_str(&format!("{}\n\n", trimmed)),
+            }
+        }
+        if files.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "--- Project Brain ---\n{}\n\n{}",
+            SECTION_INTRO, files
+        ))
+    }
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
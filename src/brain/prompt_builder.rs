@@ -2,6 +2,8 @@
 //!
 //! Reads workspace markdown files and assembles the system brain dynamically
 //! each turn, so edits to brain files take effect immediately.
+//! Project brain files from the working directory are layered on top per
+//! session by the agent service (see [`crate::brain::project_brain`]).
 
 use crate::db::repository::feedback_ledger::FeedbackLedgerRepository;
 use std::path::PathBuf;
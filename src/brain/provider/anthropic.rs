@@ -12,7 +12,26 @@
 //! - claude-3-opus-20240229 (legacy)
 //! - claude-3-sonnet-20240229 (legacy)
 //! - claude-3-haiku-20240307 (legacy)
+//!
+//! ## Extended Thinking
+//! When a thinking budget is set (per provider via `thinking_budget` in
+//! config, or per request via `LLMRequest::thinking_budget`), the request
+//! carries a `thinking` block. Streamed `thinking_delta`s surface as
+//! reasoning, and signed thinking blocks are echoed back on tool-use turns.
+//!
+//! ## Vertex AI
+//! `with_vertex` points the same Messages request at Claude on Vertex AI
+//! (`{base}/publishers/anthropic/models/{model}:rawPredict`), authenticated
+//! with a bearer token. `model` moves into the URL and `anthropic_version`
+//! into the body; everything else — including the SSE stream — is identical.
+//!
+//! ## Message Batches
+//! `submit_batch` posts Messages params to `/v1/messages/batches` (half
+//! price, results within 24h); results are downloaded as JSONL from
+//! `/v1/messages/batches/{id}/results`. Not available through Vertex.
 
+use super::batch::{BatchRequest, BatchResult, BatchState, BatchStatus};
+use super::custom_openai_compatible::TokenFn;
 use super::error::{ProviderError, Result};
 use super::r#trait::{Provider, ProviderStream};
 use super::types::*;
@@ -23,11 +42,22 @@ use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
+const ANTHROPIC_BATCHES_URL: &str = "https://api.anthropic.com/v1/messages/batches";
 const ANTHROPIC_MODELS_URL: &str = "https://api.anthropic.com/v1/models";
 const ANTHROPIC_VERSION: &str = "2023-06-01";
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300); // Total request timeout
 const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10); // Connection timeout
 const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90); // Keep connections alive
+const DEFAULT_MAX_TOKENS: u32 = 16384;
+const PROMPT_CACHING_BETA: &str = "prompt-caching-2024-07-31";
+const INTERLEAVED_THINKING_BETA: &str = "interleaved-thinking-2025-05-14";
+const VERTEX_ANTHROPIC_VERSION: &str = "vertex-2023-10-16";
+
+/// Thinking budget used when extended thinking is switched "on" without an
+/// explicit token count (e.g. cron jobs with `thinking = "on"`).
+pub const DEFAULT_THINKING_BUDGET: u32 = 10_000;
+/// Smallest budget the API accepts — used for `thinking = "budget"`.
+pub const MIN_THINKING_BUDGET: u32 = 1_024;
 
 /// Anthropic provider for Claude models
 #[derive(Clone)]
@@ -35,6 +65,16 @@ pub struct AnthropicProvider {
     api_key: String,
     client: Client,
     custom_default_model: Option<String>,
+    thinking_budget: Option<u32>,
+    vertex: Option<VertexRoute>,
+}
+
+/// Claude served through Vertex AI: project/location base URL plus a
+/// callback returning the current OAuth access token.
+#[derive(Clone)]
+struct VertexRoute {
+    base_url: String,
+    token_fn: TokenFn,
 }
 
 impl AnthropicProvider {
@@ -52,6 +92,8 @@ impl AnthropicProvider {
             api_key,
             client,
             custom_default_model: None,
+            thinking_budget: None,
+            vertex: None,
         }
     }
 
@@ -61,6 +103,8 @@ impl AnthropicProvider {
             api_key,
             client,
             custom_default_model: None,
+            thinking_budget: None,
+            vertex: None,
         }
     }
 
@@ -70,24 +114,95 @@ impl AnthropicProvider {
         self
     }
 
+    /// Enable extended thinking with the given token budget by default.
+    /// Clamped up to the API minimum; 0 leaves thinking off.
+    pub fn with_thinking_budget(mut self, budget_tokens: u32) -> Self {
+        self.thinking_budget = if budget_tokens == 0 {
+            None
+        } else {
+            Some(budget_tokens.max(MIN_THINKING_BUDGET))
+        };
+        self
+    }
+
+    /// Send requests to Claude on Vertex AI instead of api.anthropic.com.
+    /// `base_url` is the `.../projects/{project}/locations/{region}` root;
+    /// `token_fn` returns the access token for the `Authorization` header.
+    pub fn with_vertex(mut self, base_url: String, token_fn: TokenFn) -> Self {
+        self.vertex = Some(VertexRoute {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token_fn,
+        });
+        self
+    }
+
+    /// Messages endpoint for a model — the public API, or the Vertex
+    /// `rawPredict` / `streamRawPredict` method for that publisher model.
+    fn messages_url(&self, model: &str, stream: bool) -> String {
+        match &self.vertex {
+            Some(vertex) => format!(
+                "{}/publishers/anthropic/models/{}:{}",
+                vertex.base_url,
+                model,
+                if stream {
+                    "streamRawPredict"
+                } else {
+                    "rawPredict"
+                }
+            ),
+            None => ANTHROPIC_API_URL.to_string(),
+        }
+    }
+
+    /// Effective thinking budget for a request. The request-level value wins
+    /// over the provider default; `Some(0)` on the request turns thinking off.
+    /// Models that predate extended thinking (Claude 3.x before 3.7) never
+    /// get a budget — the API rejects the parameter for them. Thinking
+    /// also can't be combined with a forced tool call (structured output,
+    /// `tool_choice` any/tool).
+    fn thinking_budget_for(&self, request: &LLMRequest) -> Option<u32> {
+        if request.response_format.is_some()
+            || matches!(
+                request.tool_choice,
+                Some(ToolChoice::Required | ToolChoice::Tool(_))
+            )
+        {
+            return None;
+        }
+        let budget = request.thinking_budget.or(self.thinking_budget)?;
+        if budget == 0 || !supports_thinking(&request.model) {
+            return None;
+        }
+        Some(budget.max(MIN_THINKING_BUDGET))
+    }
+
     /// Build request headers with prompt-caching beta header.
     fn headers(&self) -> reqwest::header::HeaderMap {
         let mut headers = reqwest::header::HeaderMap::new();
 
-        headers.insert(
-            "x-api-key",
-            self.api_key.parse().expect("Invalid API key format"),
-        );
-        headers.insert(
-            "anthropic-beta",
-            "prompt-caching-2024-07-31"
-                .parse()
-                .expect("Invalid beta header"),
-        );
-        headers.insert(
-            "anthropic-version",
-            ANTHROPIC_VERSION.parse().expect("Invalid version"),
-        );
+        if let Some(vertex) = &self.vertex {
+            // Vertex takes the version in the body and caches prompts
+            // without the beta flag.
+            headers.insert(
+                reqwest::header::AUTHORIZATION,
+                format!("Bearer {}", (vertex.token_fn)())
+                    .parse()
+                    .expect("Invalid access token format"),
+            );
+        } else {
+            headers.insert(
+                "x-api-key",
+                self.api_key.parse().expect("Invalid API key format"),
+            );
+            headers.insert(
+                "anthropic-beta",
+                PROMPT_CACHING_BETA.parse().expect("Invalid beta header"),
+            );
+            headers.insert(
+                "anthropic-version",
+                ANTHROPIC_VERSION.parse().expect("Invalid version"),
+            );
+        }
         headers.insert(
             reqwest::header::CONTENT_TYPE,
             "application/json".parse().expect("valid content-type"),
@@ -95,9 +210,22 @@ impl AnthropicProvider {
         headers
     }
 
-    /// Build request headers.
-    fn request_headers(&self, _request: &LLMRequest) -> reqwest::header::HeaderMap {
-        self.headers()
+    /// Build request headers. Thinking requests opt into interleaved
+    /// thinking so the model can reason between tool calls.
+    fn request_headers(&self, request: &LLMRequest) -> reqwest::header::HeaderMap {
+        let mut headers = self.headers();
+        if self.thinking_budget_for(request).is_some() {
+            let betas = if self.vertex.is_some() {
+                INTERLEAVED_THINKING_BETA.to_string()
+            } else {
+                format!("{},{}", PROMPT_CACHING_BETA, INTERLEAVED_THINKING_BETA)
+            };
+            headers.insert(
+                "anthropic-beta",
+                betas.parse().expect("Invalid beta header"),
+            );
+        }
+        headers
     }
 
     /// Convert our generic request to Anthropic format with prompt caching.
@@ -107,6 +235,24 @@ impl AnthropicProvider {
         let cache = AnthropicCacheControl {
             cache_type: "ephemeral".to_string(),
         };
+        let thinking_budget = self.thinking_budget_for(&request);
+
+        // Structured output rides on a single forced tool call
+        let response_tool = request
+            .response_format
+            .as_ref()
+            .map(super::structured::response_tool);
+        let tool_choice = match (&response_tool, &request.tool_choice) {
+            (Some(tool), _) => Some(serde_json::json!({"type": "tool", "name": tool.name})),
+            (None, Some(choice)) if request.tools.as_ref().is_some_and(|t| !t.is_empty()) => {
+                Some(anthropic_tool_choice(choice))
+            }
+            _ => None,
+        };
+        let request_tools = match response_tool {
+            Some(tool) => Some(vec![tool]),
+            None => request.tools,
+        };
 
         // Convert system to cacheable blocks
         let system = request.system.map(|s| {
@@ -118,7 +264,7 @@ impl AnthropicProvider {
         });
 
         // Convert tools with cache_control on the last tool
-        let tools = request.tools.map(|tools| {
+        let tools = request_tools.map(|tools| {
             let len = tools.len();
             tools
                 .into_iter()
@@ -136,15 +282,40 @@ impl AnthropicProvider {
                 .collect()
         });
 
+        let mut max_tokens = request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+        let mut temperature = request.temperature;
+        let thinking = thinking_budget.map(|budget_tokens| {
+            // max_tokens must exceed the budget, and thinking only runs at
+            // the default temperature — the API 400s on anything else.
+            if max_tokens <= budget_tokens {
+                max_tokens = budget_tokens + DEFAULT_MAX_TOKENS;
+            }
+            temperature = None;
+            AnthropicThinking {
+                thinking_type: "enabled".to_string(),
+                budget_tokens,
+            }
+        });
+
+        // Vertex addresses the model in the URL and rejects it in the body.
+        let (model, anthropic_version) = if self.vertex.is_some() {
+            (String::new(), Some(VERTEX_ANTHROPIC_VERSION))
+        } else {
+            (request.model, None)
+        };
+
         AnthropicRequest {
-            model: request.model,
-            messages: request.messages,
+            model,
+            anthropic_version,
+            messages: strip_unsigned_thinking(request.messages),
             system,
-            max_tokens: request.max_tokens.unwrap_or(16384),
-            temperature: request.temperature,
+            max_tokens,
+            temperature,
             tools,
+            tool_choice,
             stream: Some(request.stream),
             metadata: request.metadata,
+            thinking,
         }
     }
 
@@ -160,6 +331,54 @@ impl AnthropicProvider {
         }
     }
 
+    /// Send a Message Batches API request, mapping error responses.
+    /// Not retried: a duplicate submit would create (and bill) a second
+    /// batch, and a failed poll is simply repeated on the next tick.
+    async fn send_batch_request(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let response = request.headers(self.headers()).send().await?;
+        if !response.status().is_success() {
+            return Err(self.handle_error(response).await);
+        }
+        Ok(response)
+    }
+
+    /// Messages params for one batched request. Batches never stream.
+    fn to_batch_item(&self, request: BatchRequest) -> AnthropicBatchItem {
+        let mut params = self.to_anthropic_request(request.request);
+        params.stream = None;
+        AnthropicBatchItem {
+            custom_id: request.custom_id,
+            params,
+        }
+    }
+
+    /// Map one line of a batch results file to a [`BatchResult`].
+    fn batch_result(&self, line: AnthropicBatchLine) -> BatchResult {
+        match line.result {
+            AnthropicBatchOutcome::Succeeded { message } => {
+                BatchResult::succeeded(line.custom_id, self.from_anthropic_response(message))
+            }
+            AnthropicBatchOutcome::Errored { error } => {
+                // `{"type": "error", "error": {"type": ..., "message": ...}}`
+                let message = error
+                    .pointer("/error/message")
+                    .or_else(|| error.get("message"))
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("request errored");
+                BatchResult::errored(line.custom_id, message)
+            }
+            AnthropicBatchOutcome::Canceled => {
+                BatchResult::errored(line.custom_id, "batch was cancelled")
+            }
+            AnthropicBatchOutcome::Expired => {
+                BatchResult::errored(line.custom_id, "batch expired before the request ran")
+            }
+        }
+    }
+
     /// Handle API error response
     async fn handle_error(&self, response: reqwest::Response) -> ProviderError {
         let status = response.status().as_u16();
@@ -238,13 +457,16 @@ impl Provider for AnthropicProvider {
         let model = request.model.clone();
         let message_count = request.messages.len();
         tracing::info!(
-            "Anthropic API request: model={}, messages={}, max_tokens={}",
+            "Anthropic API request: model={}, messages={}, max_tokens={}, thinking_budget={:?}",
             model,
             message_count,
-            request.max_tokens.unwrap_or(16384)
+            request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            self.thinking_budget_for(&request)
         );
 
         let req_headers = self.request_headers(&request);
+        let url = self.messages_url(&model, false);
+        let response_format = request.response_format.clone();
         let anthropic_request = self.to_anthropic_request(request);
         let retry_config = RetryConfig::default();
 
@@ -254,7 +476,7 @@ impl Provider for AnthropicProvider {
                 tracing::debug!("Sending request to Anthropic API");
                 let response = self
                     .client
-                    .post(ANTHROPIC_API_URL)
+                    .post(&url)
                     .headers(req_headers.clone())
                     .json(&anthropic_request)
                     .send()
@@ -287,7 +509,12 @@ impl Provider for AnthropicProvider {
             tracing::error!("Anthropic API request failed: {}", e);
         }
 
-        result
+        result.map(|mut response| {
+            if let Some(format) = &response_format {
+                super::structured::unwrap_response_tool(&mut response, format);
+            }
+            response
+        })
     }
 
     async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
@@ -296,12 +523,14 @@ impl Provider for AnthropicProvider {
         let model = request.model.clone();
         let message_count = request.messages.len();
         tracing::info!(
-            "Anthropic streaming request: model={}, messages={}",
+            "Anthropic streaming request: model={}, messages={}, thinking_budget={:?}",
             model,
-            message_count
+            message_count,
+            self.thinking_budget_for(&request)
         );
 
         let req_headers = self.request_headers(&request);
+        let url = self.messages_url(&model, true);
         let mut anthropic_request = self.to_anthropic_request(request);
         anthropic_request.stream = Some(true);
         let retry_config = RetryConfig::default();
@@ -311,7 +540,7 @@ impl Provider for AnthropicProvider {
             || async {
                 let response = self
                     .client
-                    .post(ANTHROPIC_API_URL)
+                    .post(&url)
                     .headers(req_headers.clone())
                     .json(&anthropic_request)
                     .send()
@@ -393,6 +622,58 @@ impl Provider for AnthropicProvider {
         true
     }
 
+    fn supports_batch(&self) -> bool {
+        // Vertex runs batches as batch prediction jobs over GCS files
+        self.vertex.is_none()
+    }
+
+    async fn submit_batch(&self, requests: Vec<BatchRequest>) -> Result<String> {
+        if !self.supports_batch() {
+            return Err(ProviderError::BatchNotSupported);
+        }
+        let count = requests.len();
+        let body = AnthropicBatchCreate {
+            requests: requests
+                .into_iter()
+                .map(|r| self.to_batch_item(r))
+                .collect(),
+        };
+        let response = self
+            .send_batch_request(self.client.post(ANTHROPIC_BATCHES_URL).json(&body))
+            .await?;
+        let batch: AnthropicBatch = response.json().await?;
+        tracing::info!(
+            "Anthropic batch submitted: id={}, requests={}",
+            batch.id,
+            count
+        );
+        Ok(batch.id)
+    }
+
+    async fn poll_batch(&self, batch_id: &str) -> Result<BatchStatus> {
+        let url = format!("{}/{}", ANTHROPIC_BATCHES_URL, batch_id);
+        let response = self.send_batch_request(self.client.get(url)).await?;
+        let batch: AnthropicBatch = response.json().await?;
+        Ok(batch.status())
+    }
+
+    async fn fetch_batch_results(&self, batch_id: &str) -> Result<Vec<BatchResult>> {
+        let url = format!("{}/{}/results", ANTHROPIC_BATCHES_URL, batch_id);
+        let body = self
+            .send_batch_request(self.client.get(url))
+            .await?
+            .text()
+            .await?;
+        Ok(super::batch::parse_jsonl::<AnthropicBatchLine>(&body)
+            .into_iter()
+            .map(|line| self.batch_result(line))
+            .collect())
+    }
+
+    fn supports_response_format(&self) -> bool {
+        true
+    }
+
     fn name(&self) -> &str {
         "anthropic"
     }
@@ -435,7 +716,7 @@ impl Provider for AnthropicProvider {
             .get(ANTHROPIC_MODELS_URL)
             .header("anthropic-version", ANTHROPIC_VERSION)
             .header("x-api-key", &self.api_key)
-            .header("anthropic-beta", "prompt-caching-2024-07-31");
+            .header("anthropic-beta", PROMPT_CACHING_BETA);
 
         match req.send().await {
             Ok(resp) if resp.status().is_success() => match resp.json::<ModelsResponse>().await {
@@ -475,10 +756,43 @@ impl Provider for AnthropicProvider {
     }
 }
 
+/// Whether a model accepts the `thinking` request parameter. Extended
+/// thinking arrived with Claude 3.7; every 3.0/3.5 model rejects it.
+fn supports_thinking(model: &str) -> bool {
+    !model.starts_with("claude-3-") || model.starts_with("claude-3-7")
+}
+
+/// Drop thinking blocks the API can't verify. Anthropic only accepts
+/// thinking blocks it signed itself — reasoning rehydrated from the DB or
+/// produced by another provider (kimi, qwen, claude-cli) has no signature
+/// and would 400 the request. Messages left empty are dropped too.
+fn strip_unsigned_thinking(messages: Vec<Message>) -> Vec<Message> {
+    messages
+        .into_iter()
+        .filter_map(|mut msg| {
+            let before = msg.content.len();
+            msg.content.retain(|b| match b {
+                ContentBlock::Thinking { signature, .. } => {
+                    signature.as_deref().is_some_and(|s| !s.is_empty())
+                }
+                _ => true,
+            });
+            if msg.content.is_empty() && before > 0 {
+                None
+            } else {
+                Some(msg)
+            }
+        })
+        .collect()
+}
+
 // Anthropic-specific request format with prompt caching support
 #[derive(Debug, Serialize)]
 struct AnthropicRequest {
+    #[serde(skip_serializing_if = "String::is_empty")]
     model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anthropic_version: Option<&'static str>,
     messages: Vec<Message>,
     /// System can be a string or an array of content blocks (for caching).
     /// We use a custom serializer to decide based on whether caching is enabled.
@@ -490,9 +804,31 @@ struct AnthropicRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<AnthropicTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     metadata: Option<std::collections::HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<AnthropicThinking>,
+}
+
+/// Messages API `tool_choice` value for a [`ToolChoice`].
+fn anthropic_tool_choice(choice: &ToolChoice) -> serde_json::Value {
+    match choice {
+        ToolChoice::Auto => serde_json::json!({"type": "auto"}),
+        ToolChoice::Required => serde_json::json!({"type": "any"}),
+        ToolChoice::None => serde_json::json!({"type": "none"}),
+        ToolChoice::Tool(name) => serde_json::json!({"type": "tool", "name": name}),
+    }
+}
+
+/// Extended thinking configuration (`{"type": "enabled", "budget_tokens": N}`).
+#[derive(Debug, Serialize)]
+struct AnthropicThinking {
+    #[serde(rename = "type")]
+    thinking_type: String,
+    budget_tokens: u32,
 }
 
 /// System prompt — either a plain string or a list of content blocks for caching.
@@ -541,6 +877,71 @@ struct AnthropicResponse {
     usage: TokenUsage,
 }
 
+/// `POST /v1/messages/batches` body
+#[derive(Debug, Serialize)]
+struct AnthropicBatchCreate {
+    requests: Vec<AnthropicBatchItem>,
+}
+
+#[derive(Debug, Serialize)]
+struct AnthropicBatchItem {
+    custom_id: String,
+    params: AnthropicRequest,
+}
+
+/// Message Batch object (create / retrieve response)
+#[derive(Debug, Deserialize)]
+struct AnthropicBatch {
+    id: String,
+    /// "in_progress", "canceling" or "ended"
+    processing_status: String,
+    #[serde(default)]
+    request_counts: AnthropicBatchCounts,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AnthropicBatchCounts {
+    processing: u32,
+    succeeded: u32,
+    errored: u32,
+    canceled: u32,
+    expired: u32,
+}
+
+impl AnthropicBatch {
+    fn status(&self) -> BatchStatus {
+        let counts = &self.request_counts;
+        BatchStatus {
+            state: if self.processing_status == "ended" {
+                BatchState::Ended
+            } else {
+                BatchState::InProgress
+            },
+            succeeded: counts.succeeded,
+            errored: counts.errored + counts.canceled + counts.expired,
+            pending: counts.processing,
+            error: None,
+        }
+    }
+}
+
+/// One line of a batch results file
+#[derive(Debug, Deserialize)]
+struct AnthropicBatchLine {
+    custom_id: String,
+    result: AnthropicBatchOutcome,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicBatchOutcome {
+    Succeeded { message: AnthropicResponse },
+    Errored { error: serde_json::Value },
+    Canceled,
+    Expired,
+}
+
 // Anthropic error format
 #[derive(Debug, Deserialize)]
 struct AnthropicError {
@@ -632,11 +1033,240 @@ mod tests {
         assert!(headers.contains_key("anthropic-beta"));
     }
 
+    #[test]
+    fn test_thinking_budget_in_request() {
+        let provider = AnthropicProvider::new("test-key".to_string()).with_thinking_budget(8000);
+        let request = LLMRequest::new("claude-sonnet-4-5", vec![Message::user("hi")])
+            .with_temperature(0.3)
+            .with_max_tokens(4096);
+        let body = serde_json::to_value(provider.to_anthropic_request(request)).unwrap();
+        assert_eq!(body["thinking"]["type"], "enabled");
+        assert_eq!(body["thinking"]["budget_tokens"], 8000);
+        assert!(body["max_tokens"].as_u64().unwrap() > 8000);
+        assert!(body.get("temperature").is_none());
+    }
+
+    #[test]
+    fn test_thinking_disabled_per_request_and_for_legacy_models() {
+        let provider = AnthropicProvider::new("test-key".to_string()).with_thinking_budget(8000);
+        let off =
+            LLMRequest::new("claude-sonnet-4-5", vec![Message::user("hi")]).with_thinking_budget(0);
+        assert!(provider.to_anthropic_request(off).thinking.is_none());
+
+        let legacy = LLMRequest::new("claude-3-5-haiku-20241022", vec![Message::user("hi")]);
+        assert!(provider.to_anthropic_request(legacy).thinking.is_none());
+
+        let plain = AnthropicProvider::new("test-key".to_string());
+        let request = LLMRequest::new("claude-sonnet-4-5", vec![Message::user("hi")]);
+        assert!(plain.to_anthropic_request(request).thinking.is_none());
+    }
+
+    #[test]
+    fn test_tool_choice_mapping_and_thinking() {
+        let provider = AnthropicProvider::new("test-key".to_string()).with_thinking_budget(8000);
+        let tool = Tool {
+            name: "ls".into(),
+            description: "list".into(),
+            input_schema: serde_json::json!({"type": "object"}),
+        };
+        let request = |choice| {
+            LLMRequest::new("claude-sonnet-4-5", vec![Message::user("hi")])
+                .with_tools(vec![tool.clone()])
+                .with_tool_choice(choice)
+        };
+
+        let forced = provider.to_anthropic_request(request(ToolChoice::Tool("ls".into())));
+        assert_eq!(
+            forced.tool_choice,
+            Some(serde_json::json!({"type": "tool", "name": "ls"}))
+        );
+        assert!(forced.thinking.is_none());
+
+        let any = provider.to_anthropic_request(request(ToolChoice::Required));
+        assert_eq!(any.tool_choice, Some(serde_json::json!({"type": "any"})));
+
+        let none = provider.to_anthropic_request(request(ToolChoice::None));
+        assert_eq!(none.tool_choice, Some(serde_json::json!({"type": "none"})));
+        assert!(none.thinking.is_some());
+    }
+
+    #[test]
+    fn test_vertex_route_moves_model_into_url() {
+        let provider = AnthropicProvider::new(String::new()).with_vertex(
+            "https://us-east5-aiplatform.googleapis.com/v1/projects/p/locations/us-east5/"
+                .to_string(),
+            std::sync::Arc::new(|| "ya29.token".to_string()),
+        );
+        assert_eq!(
+            provider.messages_url("claude-sonnet-4-5@20250929", true),
+            "https://us-east5-aiplatform.googleapis.com/v1/projects/p/locations/us-east5\
+             /publishers/anthropic/models/claude-sonnet-4-5@20250929:streamRawPredict"
+        );
+
+        let headers = provider.headers();
+        assert_eq!(headers["authorization"], "Bearer ya29.token");
+        assert!(headers.get("x-api-key").is_none());
+
+        let request = LLMRequest::new("claude-sonnet-4-5@20250929", vec![Message::user("hi")]);
+        let body = serde_json::to_value(provider.to_anthropic_request(request)).unwrap();
+        assert!(body.get("model").is_none());
+        assert_eq!(body["anthropic_version"], VERTEX_ANTHROPIC_VERSION);
+    }
+
+    #[test]
+    fn test_unsigned_thinking_is_stripped() {
+        let provider = AnthropicProvider::new("test-key".to_string());
+        let messages = vec![
+            Message::user("hi"),
+            Message {
+                role: Role::Assistant,
+                content: vec![
+                    ContentBlock::Thinking {
+                        thinking: "signed".to_string(),
+                        signature: Some("sig".to_string()),
+                    },
+                    ContentBlock::Thinking {
+                        thinking: "from the db".to_string(),
+                        signature: None,
+                    },
+                    ContentBlock::RedactedThinking {
+                        data: "opaque".to_string(),
+                    },
+                    ContentBlock::Text {
+                        text: "hello".to_string(),
+                    },
+                ],
+            },
+            Message {
+                role: Role::Assistant,
+                content: vec![ContentBlock::Thinking {
+                    thinking: "orphan".to_string(),
+                    signature: None,
+                }],
+            },
+        ];
+        let request = provider.to_anthropic_request(LLMRequest::new("claude-sonnet-4-5", messages));
+        assert_eq!(request.messages.len(), 2);
+        let body = serde_json::to_value(&request.messages[1]).unwrap();
+        let types: Vec<&str> = body["content"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(types, vec!["thinking", "redacted_thinking", "text"]);
+        assert_eq!(body["content"][0]["signature"], "sig");
+    }
+
+    #[test]
+    fn test_parse_thinking_stream_events() {
+        let start: StreamEvent = serde_json::from_str(
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"redacted_thinking","data":"abc"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            start,
+            StreamEvent::ContentBlockStart {
+                content_block: ContentBlock::RedactedThinking { .. },
+                ..
+            }
+        ));
+        let sig: StreamEvent = serde_json::from_str(
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"EqQB"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            sig,
+            StreamEvent::ContentBlockDelta {
+                delta: ContentDelta::SignatureDelta { ref signature },
+                ..
+            } if signature == "EqQB"
+        ));
+    }
+
     #[test]
     fn test_capabilities() {
         let provider = AnthropicProvider::new("test-key".to_string());
         assert!(provider.supports_streaming());
         assert!(provider.supports_tools());
         assert!(provider.supports_vision());
+        assert!(provider.supports_batch());
+    }
+
+    #[test]
+    fn test_batch_status_mapping() {
+        let batch: AnthropicBatch = serde_json::from_value(serde_json::json!({
+            "id": "msgbatch_01",
+            "type": "message_batch",
+            "processing_status": "in_progress",
+            "request_counts": {"processing": 2, "succeeded": 1, "errored": 0, "canceled": 0, "expired": 0}
+        }))
+        .unwrap();
+        let status = batch.status();
+        assert_eq!(status.state, BatchState::InProgress);
+        assert_eq!(
+            (status.succeeded, status.errored, status.pending),
+            (1, 0, 2)
+        );
+
+        let batch: AnthropicBatch = serde_json::from_value(serde_json::json!({
+            "id": "msgbatch_01",
+            "processing_status": "ended",
+            "request_counts": {"processing": 0, "succeeded": 2, "errored": 0, "canceled": 0, "expired": 1}
+        }))
+        .unwrap();
+        let status = batch.status();
+        assert_eq!(status.state, BatchState::Ended);
+        assert_eq!(
+            (status.succeeded, status.errored, status.pending),
+            (2, 1, 0)
+        );
+    }
+
+    #[test]
+    fn test_batch_results_parsing() {
+        let provider = AnthropicProvider::new("test-key".to_string());
+        let body = [
+            r#"{"custom_id":"a","result":{"type":"succeeded","message":{"id":"msg_1","type":"message","role":"assistant","model":"claude-haiku-4-5","content":[{"type":"text","text":"done"}],"stop_reason":"end_turn","usage":{"input_tokens":12,"output_tokens":3}}}}"#,
+            "",
+            r#"{"custom_id":"b","result":{"type":"errored","error":{"type":"error","error":{"type":"invalid_request_error","message":"max_tokens too large"}}}}"#,
+            r#"{"custom_id":"c","result":{"type":"expired"}}"#,
+        ]
+        .join("\n");
+        let results: Vec<BatchResult> =
+            super::super::batch::parse_jsonl::<AnthropicBatchLine>(&body)
+                .into_iter()
+                .map(|line| provider.batch_result(line))
+                .collect();
+
+        assert_eq!(results.len(), 3);
+        let reply = results[0].outcome.as_ref().unwrap();
+        assert_eq!(reply.usage.input_tokens, 12);
+        assert!(matches!(&reply.content[0], ContentBlock::Text { text } if text == "done"));
+        assert_eq!(
+            results[1].outcome.as_ref().unwrap_err(),
+            "max_tokens too large"
+        );
+        assert_eq!(results[2].custom_id, "c");
+        assert!(results[2].outcome.is_err());
+    }
+
+    #[test]
+    fn test_batch_params_never_stream() {
+        let provider = AnthropicProvider::new("test-key".to_string());
+        let request =
+            LLMRequest::new("claude-haiku-4-5", vec![Message::user("hi")]).with_streaming();
+        let item =
+            serde_json::to_value(provider.to_batch_item(BatchRequest::new("run-1", request)))
+                .unwrap();
+        assert_eq!(item["custom_id"], "run-1");
+        assert_eq!(item["params"]["model"], "claude-haiku-4-5");
+        assert!(item["params"].get("stream").is_none());
+
+        let vertex = AnthropicProvider::new(String::new()).with_vertex(
+            "https://aiplatform.googleapis.com/v1".to_string(),
+            std::sync::Arc::new(String::new),
+        );
+        assert!(!vertex.supports_batch());
     }
 }
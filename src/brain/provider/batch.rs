@@ -0,0 +1,122 @@
+//! Message Batches
+//!
+//! Anthropic Message Batches and OpenAI's Batch API take a list of requests,
+//! process them asynchronously (within 24h, usually minutes) and bill them
+//! at half the synchronous price. Non-interactive work — cron summaries,
+//! session categorization, the RSI digest analysis — goes through the batch
+//! methods on `Provider`:
+//!
+//! 1. `submit_batch` uploads the requests and returns the provider's batch id
+//! 2. `poll_batch` reports progress until the batch leaves `InProgress`
+//! 3. `fetch_batch_results` downloads one [`BatchResult`] per request
+//!
+//! Callers persist the returned id in `llm_batches` (see
+//! `db::repository::llm_batch`) so polling resumes after a restart.
+
+use super::types::{LLMRequest, LLMResponse};
+use serde::de::DeserializeOwned;
+
+/// One request inside a batch. The `custom_id` is echoed back on its result
+/// and must be 1–64 characters of `[A-Za-z0-9_-]`.
+#[derive(Debug, Clone)]
+pub struct BatchRequest {
+    pub custom_id: String,
+    pub request: LLMRequest,
+}
+
+impl BatchRequest {
+    pub fn new(custom_id: impl Into<String>, request: LLMRequest) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            request,
+        }
+    }
+}
+
+/// Lifecycle of a submitted batch, normalized across providers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchState {
+    /// Validating, queued or still processing
+    InProgress,
+    /// Finished — results are ready to fetch. Expired or cancelled batches
+    /// also land here when some requests completed; the rest come back as
+    /// errored results.
+    Ended,
+    /// The batch as a whole was rejected or produced no results
+    Failed,
+}
+
+/// Progress snapshot returned by `Provider::poll_batch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchStatus {
+    pub state: BatchState,
+    pub succeeded: u32,
+    pub errored: u32,
+    pub pending: u32,
+    /// Why the batch failed, when `state` is `Failed`
+    pub error: Option<String>,
+}
+
+impl BatchStatus {
+    pub fn in_progress(pending: u32) -> Self {
+        Self {
+            state: BatchState::InProgress,
+            succeeded: 0,
+            errored: 0,
+            pending,
+            error: None,
+        }
+    }
+}
+
+/// Outcome of a single request in a finished batch.
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    pub custom_id: String,
+    /// The model's reply, or the per-request error message
+    pub outcome: std::result::Result<LLMResponse, String>,
+}
+
+impl BatchResult {
+    pub fn succeeded(custom_id: impl Into<String>, response: LLMResponse) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            outcome: Ok(response),
+        }
+    }
+
+    pub fn errored(custom_id: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            outcome: Err(message.into()),
+        }
+    }
+
+    /// Concatenated text of a successful reply
+    pub fn text(&self) -> Option<String> {
+        self.outcome
+            .as_ref()
+            .ok()
+            .map(super::structured::response_text)
+    }
+}
+
+/// Parse a JSONL results file. Blank lines are skipped; malformed lines
+/// are logged and dropped so one bad record can't hide the rest.
+pub(crate) fn parse_jsonl<T: DeserializeOwned>(body: &str) -> Vec<T> {
+    body.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                tracing::warn!(
+                    "Skipping malformed batch result line: {}. Data: {}",
+                    e,
+                    line.chars().take(200).collect::<String>()
+                );
+                None
+            }
+        })
+        .collect()
+}
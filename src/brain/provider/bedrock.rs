@@ -0,0 +1,1582 @@
+//! AWS Bedrock Provider Implementation
+//!
+//! Implements the Provider trait on top of Bedrock's Converse API, which
+//! exposes one request shape for every model family hosted on Bedrock
+//! (Claude, Nova, Llama, Mistral, ...).
+//!
+//! ## API Format
+//! - Base URL: `https://bedrock-runtime.{region}.amazonaws.com`
+//! - Auth: SigV4 (`AWS4-HMAC-SHA256`, service `bedrock`), or a Bedrock API
+//!   key sent as `Authorization: Bearer`
+//! - Chat: `POST /model/{modelId}/converse`
+//! - Stream: `POST /model/{modelId}/converse-stream` — the response body is
+//!   binary `application/vnd.amazon.eventstream` frames, not SSE
+//!
+//! ## Credentials
+//! Resolved in order:
+//! 1. keys.toml `[providers.bedrock]` — `aws_access_key_id` +
+//!    `aws_secret_access_key` (+ `aws_session_token`), or `api_key`
+//! 2. `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN`,
+//!    then `AWS_BEARER_TOKEN_BEDROCK`
+//! 3. The shared credentials file (`~/.aws/credentials`, profile from
+//!    `AWS_PROFILE`, default `default`)
+//!
+//! The region comes from `region` in config.toml, then `AWS_REGION` /
+//! `AWS_DEFAULT_REGION`, then `~/.aws/config`, then `us-east-1`.
+
+use super::error::{ProviderError, Result};
+use super::r#trait::{Provider, ProviderStream};
+use super::types::*;
+use crate::config::ProviderConfig;
+use async_trait::async_trait;
+use futures::stream::StreamExt;
+use reqwest::{Client, RequestBuilder};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const DEFAULT_REGION: &str = "us-east-1";
+const DEFAULT_MODEL: &str = "us.anthropic.claude-sonnet-4-5-20250929-v1:0";
+const DEFAULT_MAX_TOKENS: u32 = 8192;
+const SIGNING_SERVICE: &str = "bedrock";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Static IAM credentials used for SigV4 signing.
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// How requests to Bedrock are authenticated.
+#[derive(Clone)]
+pub enum BedrockAuth {
+    /// IAM credentials — every request is SigV4-signed.
+    SigV4(AwsCredentials),
+    /// Bedrock API key, sent as a bearer token.
+    ApiKey(String),
+}
+
+impl BedrockAuth {
+    /// Resolve credentials for `[providers.bedrock]`: keys.toml first, then
+    /// the `AWS_*` environment, then the shared credentials file.
+    pub fn resolve(config: &ProviderConfig) -> Option<Self> {
+        let non_empty = |v: &Option<String>| v.clone().filter(|s| !s.is_empty());
+        if let (Some(access_key_id), Some(secret_access_key)) = (
+            non_empty(&config.aws_access_key_id),
+            non_empty(&config.aws_secret_access_key),
+        ) {
+            return Some(Self::SigV4(AwsCredentials {
+                access_key_id,
+                secret_access_key,
+                session_token: non_empty(&config.aws_session_token),
+            }));
+        }
+        if let Some(key) = non_empty(&config.api_key) {
+            return Some(Self::ApiKey(key));
+        }
+
+        if let (Some(access_key_id), Some(secret_access_key)) = (
+            env_var("AWS_ACCESS_KEY_ID"),
+            env_var("AWS_SECRET_ACCESS_KEY"),
+        ) {
+            return Some(Self::SigV4(AwsCredentials {
+                access_key_id,
+                secret_access_key,
+                session_token: env_var("AWS_SESSION_TOKEN"),
+            }));
+        }
+        if let Some(token) = env_var("AWS_BEARER_TOKEN_BEDROCK") {
+            return Some(Self::ApiKey(token));
+        }
+
+        let path = env_var("AWS_SHARED_CREDENTIALS_FILE")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|h| h.join(".aws").join("credentials")))?;
+        let contents = std::fs::read_to_string(path).ok()?;
+        let section = ini_section(&contents, &aws_profile())?;
+        Some(Self::SigV4(AwsCredentials {
+            access_key_id: section.get("aws_access_key_id")?.clone(),
+            secret_access_key: section.get("aws_secret_access_key")?.clone(),
+            session_token: section.get("aws_session_token").cloned(),
+        }))
+    }
+}
+
+/// Resolve the Bedrock region: config, `AWS_REGION` / `AWS_DEFAULT_REGION`,
+/// the active profile in `~/.aws/config`, then `us-east-1`.
+pub fn resolve_region(configured: Option<&str>) -> String {
+    if let Some(region) = configured.filter(|r| !r.is_empty()) {
+        return region.to_string();
+    }
+    if let Some(region) = env_var("AWS_REGION").or_else(|| env_var("AWS_DEFAULT_REGION")) {
+        return region;
+    }
+    let from_profile = env_var("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".aws").join("config")))
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| {
+            // ~/.aws/config names non-default sections `[profile <name>]`.
+            let profile = aws_profile();
+            ini_section(&contents, &format!("profile {}", profile))
+                .or_else(|| ini_section(&contents, &profile))
+        })
+        .and_then(|section| section.get("region").cloned());
+    from_profile.unwrap_or_else(|| DEFAULT_REGION.to_string())
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn aws_profile() -> String {
+    env_var("AWS_PROFILE").unwrap_or_else(|| "default".to_string())
+}
+
+/// Key/value pairs of `[section]` in an AWS-style INI file.
+fn ini_section(contents: &str, section: &str) -> Option<HashMap<String, String>> {
+    let mut current: Option<&str> = None;
+    let mut values = HashMap::new();
+    let mut found = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = Some(name.trim());
+            found |= current == Some(section);
+            continue;
+        }
+        if current == Some(section)
+            && let Some((key, value)) = line.split_once('=')
+        {
+            values.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    found.then_some(values)
+}
+
+/// AWS Bedrock provider (Converse API)
+#[derive(Clone)]
+pub struct BedrockProvider {
+    auth: BedrockAuth,
+    region: String,
+    base_url: Option<String>,
+    client: Client,
+    model: String,
+    models: Vec<String>,
+}
+
+impl BedrockProvider {
+    /// Create a new Bedrock provider for `region`
+    pub fn new(auth: BedrockAuth, region: impl Into<String>) -> Self {
+        let client = Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+            .pool_idle_timeout(DEFAULT_POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(2)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            auth,
+            region: region.into(),
+            base_url: None,
+            client,
+            model: DEFAULT_MODEL.to_string(),
+            models: Vec::new(),
+        }
+    }
+
+    /// Override the runtime endpoint (VPC endpoints, local mocks)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Set the default model (model ID, inference profile ID or ARN)
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Extra model IDs the user configured under `models = [...]`
+    pub fn with_models(mut self, models: Vec<String>) -> Self {
+        self.models = models;
+        self
+    }
+
+    fn runtime_url(&self, model: &str, stream: bool) -> String {
+        let base = self
+            .base_url
+            .clone()
+            .unwrap_or_else(|| format!("https://bedrock-runtime.{}.amazonaws.com", self.region));
+        let action = if stream {
+            "converse-stream"
+        } else {
+            "converse"
+        };
+        format!("{}/model/{}/{}", base, urlencoding::encode(model), action)
+    }
+
+    /// Attach authentication to a request. SigV4 signatures embed the
+    /// timestamp, so the request is signed right before it is sent.
+    fn authorize(
+        &self,
+        builder: RequestBuilder,
+        method: &str,
+        url: &str,
+        body: &[u8],
+    ) -> Result<RequestBuilder> {
+        match &self.auth {
+            BedrockAuth::ApiKey(key) => Ok(builder.bearer_auth(key)),
+            BedrockAuth::SigV4(creds) => {
+                let parsed = reqwest::Url::parse(url).map_err(|e| {
+                    ProviderError::InvalidRequest(format!("invalid Bedrock URL {}: {}", url, e))
+                })?;
+                let headers = sigv4_headers(
+                    creds,
+                    &self.region,
+                    SIGNING_SERVICE,
+                    method,
+                    &parsed,
+                    body,
+                    chrono::Utc::now(),
+                );
+                Ok(headers
+                    .into_iter()
+                    .fold(builder, |b, (name, value)| b.header(name, value)))
+            }
+        }
+    }
+
+    async fn post(&self, url: &str, body: &[u8]) -> Result<reqwest::Response> {
+        let builder = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json");
+        let response = self
+            .authorize(builder, "POST", url, body)?
+            .body(body.to_vec())
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(self.handle_error(response).await);
+        }
+        Ok(response)
+    }
+
+    /// Handle API error response. Bedrock puts the exception name in the
+    /// `x-amzn-ErrorType` header and the text in `message`.
+    async fn handle_error(&self, response: reqwest::Response) -> ProviderError {
+        let status = response.status().as_u16();
+        let error_type = response
+            .headers()
+            .get("x-amzn-ErrorType")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.split(':').next().unwrap_or(v).to_string());
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|v| {
+                v["message"]
+                    .as_str()
+                    .or_else(|| v["Message"].as_str())
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| {
+                if body.is_empty() {
+                    "Unknown error".to_string()
+                } else {
+                    body
+                }
+            });
+
+        if status == 429 {
+            return ProviderError::RateLimitExceeded(message);
+        }
+        ProviderError::ApiError {
+            status,
+            message,
+            error_type,
+        }
+    }
+}
+
+/// Convert our LLMRequest to a Converse request body.
+pub(crate) fn build_converse_body(request: &LLMRequest) -> Value {
+    // Thinking can't be combined with a forced tool call (structured
+    // output, `tool_choice` any/tool)
+    let forces_tool = request.response_format.is_some()
+        || matches!(
+            request.tool_choice,
+            Some(ToolChoice::Required | ToolChoice::Tool(_))
+        );
+    let thinking = request
+        .thinking_budget
+        .filter(|_| !forces_tool)
+        .filter(|b| *b > 0 && supports_thinking(&request.model))
+        .map(|b| b.max(super::anthropic::MIN_THINKING_BUDGET));
+
+    let mut system: Vec<Value> = request
+        .system
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| json!({"text": s}))
+        .collect();
+    let mut messages: Vec<Value> = Vec::new();
+
+    for msg in &request.messages {
+        let role = match msg.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => {
+                for block in &msg.content {
+                    if let ContentBlock::Text { text } = block
+                        && !text.is_empty()
+                    {
+                        system.push(json!({"text": text}));
+                    }
+                }
+                continue;
+            }
+        };
+
+        let content: Vec<Value> = msg
+            .content
+            .iter()
+            .filter_map(|block| converse_block(block, thinking.is_some()))
+            .collect();
+        if content.is_empty() {
+            continue;
+        }
+
+        // Converse requires strictly alternating roles — fold consecutive
+        // same-role turns (tool results followed by a user nudge) together.
+        if let Some(last) = messages.last_mut()
+            && last["role"] == role
+            && let Some(existing) = last["content"].as_array_mut()
+        {
+            existing.extend(content);
+            continue;
+        }
+        messages.push(json!({"role": role, "content": content}));
+    }
+
+    let mut max_tokens = request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+    let mut inference = json!({});
+    if let Some(budget) = thinking {
+        // maxTokens covers thinking + answer, so it must exceed the budget.
+        if max_tokens <= budget {
+            max_tokens = budget + DEFAULT_MAX_TOKENS;
+        }
+    } else if let Some(temperature) = request.temperature {
+        inference["temperature"] = json!(temperature);
+    }
+    inference["maxTokens"] = json!(max_tokens);
+
+    let mut body = json!({
+        "messages": messages,
+        "inferenceConfig": inference,
+    });
+    if !system.is_empty() {
+        body["system"] = Value::Array(system);
+    }
+
+    if let Some(ref tools) = request.tools
+        && !tools.is_empty()
+    {
+        let specs: Vec<Value> = tools
+            .iter()
+            .map(|t| {
+                json!({
+                    "toolSpec": {
+                        "name": t.name,
+                        "description": t.description,
+                        "inputSchema": {"json": t.input_schema}
+                    }
+                })
+            })
+            .collect();
+        body["toolConfig"] = json!({"tools": specs});
+        // Converse has no "none" choice; leaving `toolChoice` unset is auto.
+        // Tools can only be withheld when the history has no tool blocks —
+        // Bedrock requires `toolConfig` whenever those are present.
+        match &request.tool_choice {
+            Some(ToolChoice::Required) => body["toolConfig"]["toolChoice"] = json!({"any": {}}),
+            Some(ToolChoice::Tool(name)) => {
+                body["toolConfig"]["toolChoice"] = json!({"tool": {"name": name}})
+            }
+            Some(ToolChoice::None) if !has_tool_blocks(&request.messages) => {
+                if let Some(map) = body.as_object_mut() {
+                    map.remove("toolConfig");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(ref format) = request.response_format {
+        let tool = super::structured::response_tool(format);
+        body["toolConfig"] = json!({
+            "tools": [{
+                "toolSpec": {
+                    "name": tool.name,
+                    "description": tool.description,
+                    "inputSchema": {"json": tool.input_schema}
+                }
+            }],
+            "toolChoice": {"tool": {"name": tool.name}}
+        });
+    }
+
+    if let Some(budget) = thinking {
+        body["additionalModelRequestFields"] = json!({
+            "thinking": {"type": "enabled", "budget_tokens": budget}
+        });
+    }
+
+    body
+}
+
+/// Whether any message carries a tool call or tool result
+fn has_tool_blocks(messages: &[Message]) -> bool {
+    messages.iter().flat_map(|m| &m.content).any(|block| {
+        matches!(
+            block,
+            ContentBlock::ToolUse { .. } | ContentBlock::ToolResult { .. }
+        )
+    })
+}
+
+/// Map one content block to its Converse form. Reasoning blocks are only
+/// echoed back while thinking is on, and only when Bedrock signed them.
+fn converse_block(block: &ContentBlock, keep_reasoning: bool) -> Option<Value> {
+    match block {
+        ContentBlock::Text { text } if text.trim().is_empty() => None,
+        ContentBlock::Text { text } => Some(json!({"text": text})),
+        ContentBlock::Image {
+            source: ImageSource::Base64 { media_type, data },
+        } => Some(json!({
+            "image": {
+                "format": image_format(media_type),
+                "source": {"bytes": data}
+            }
+        })),
+        // Converse only accepts inline bytes (or S3 locations); keep the
+        // reference visible to the model instead of failing the request.
+        ContentBlock::Image {
+            source: ImageSource::Url { url },
+        } => Some(json!({"text": format!("[image: {}]", url)})),
+        ContentBlock::ToolUse { id, name, input } => {
+            let input = if input.is_object() {
+                input.clone()
+            } else {
+                json!({})
+            };
+            Some(json!({
+                "toolUse": {"toolUseId": id, "name": name, "input": input}
+            }))
+        }
+        ContentBlock::ToolResult {
+            tool_use_id,
+            content,
+            is_error,
+        } => {
+            // Blank text blocks are rejected with a ValidationException.
+            let text = if content.trim().is_empty() {
+                "(no output)"
+            } else {
+                content.as_str()
+            };
+            let mut result = json!({
+                "toolUseId": tool_use_id,
+                "content": [{"text": text}]
+            });
+            if *is_error == Some(true) {
+                result["status"] = json!("error");
+            }
+            Some(json!({"toolResult": result}))
+        }
+        ContentBlock::Thinking {
+            thinking,
+            signature: Some(signature),
+        } if keep_reasoning && !signature.is_empty() => Some(json!({
+            "reasoningContent": {
+                "reasoningText": {"text": thinking, "signature": signature}
+            }
+        })),
+        ContentBlock::Thinking { .. } => None,
+        ContentBlock::RedactedThinking { data } if keep_reasoning => Some(json!({
+            "reasoningContent": {"redactedContent": data}
+        })),
+        ContentBlock::RedactedThinking { .. } => None,
+    }
+}
+
+fn image_format(media_type: &str) -> &str {
+    match media_type {
+        "image/jpeg" | "image/jpg" => "jpeg",
+        other => other.strip_prefix("image/").unwrap_or(other),
+    }
+}
+
+/// Claude 3.7+ on Bedrock accepts the Anthropic `thinking` field through
+/// `additionalModelRequestFields`. Matches plain model IDs, cross-region
+/// inference profiles (`us.anthropic.…`) and ARNs.
+fn supports_thinking(model: &str) -> bool {
+    if !model.contains("anthropic.claude") || model.contains("claude-v2") {
+        return false;
+    }
+    !model.contains("claude-3-") || model.contains("claude-3-7")
+}
+
+fn map_stop_reason(reason: &str) -> StopReason {
+    match reason {
+        "tool_use" => StopReason::ToolUse,
+        "max_tokens" | "model_context_window_exceeded" => StopReason::MaxTokens,
+        "stop_sequence" => StopReason::StopSequence,
+        _ => StopReason::EndTurn,
+    }
+}
+
+fn parse_usage(usage: &Value) -> TokenUsage {
+    let count = |key: &str| usage[key].as_u64().unwrap_or(0) as u32;
+    TokenUsage {
+        input_tokens: count("inputTokens"),
+        output_tokens: count("outputTokens"),
+        cache_read_tokens: count("cacheReadInputTokens"),
+        cache_creation_tokens: count("cacheWriteInputTokens"),
+        ..Default::default()
+    }
+}
+
+/// Parse a Converse response JSON into an LLMResponse
+pub(crate) fn parse_converse_response(model: &str, json: &Value) -> LLMResponse {
+    let mut content = Vec::new();
+    let empty = vec![];
+    let blocks = json["output"]["message"]["content"]
+        .as_array()
+        .unwrap_or(&empty);
+
+    for block in blocks {
+        if let Some(text) = block["text"].as_str() {
+            if !text.is_empty() {
+                content.push(ContentBlock::Text {
+                    text: text.to_string(),
+                });
+            }
+        } else if block["toolUse"].is_object() {
+            let tool = &block["toolUse"];
+            content.push(ContentBlock::ToolUse {
+                id: tool["toolUseId"].as_str().unwrap_or_default().to_string(),
+                name: tool["name"].as_str().unwrap_or("unknown").to_string(),
+                input: tool["input"].clone(),
+            });
+        } else if block["reasoningContent"].is_object() {
+            let reasoning = &block["reasoningContent"];
+            if let Some(data) = reasoning["redactedContent"].as_str() {
+                content.push(ContentBlock::RedactedThinking {
+                    data: data.to_string(),
+                });
+            } else {
+                let text = &reasoning["reasoningText"];
+                content.push(ContentBlock::Thinking {
+                    thinking: text["text"].as_str().unwrap_or_default().to_string(),
+                    signature: text["signature"].as_str().map(str::to_string),
+                });
+            }
+        }
+    }
+
+    LLMResponse {
+        id: format!("bedrock-{}", uuid::Uuid::new_v4().simple()),
+        model: model.to_string(),
+        content,
+        stop_reason: Some(map_stop_reason(
+            json["stopReason"].as_str().unwrap_or_default(),
+        )),
+        usage: parse_usage(&json["usage"]),
+    }
+}
+
+// ── SigV4 ───────────────────────────────────────────────────────────────
+
+/// Compute the SigV4 headers (`x-amz-date`, optional
+/// `x-amz-security-token`, `authorization`) for a request.
+pub(crate) fn sigv4_headers(
+    creds: &AwsCredentials,
+    region: &str,
+    service: &str,
+    method: &str,
+    url: &reqwest::Url,
+    body: &[u8],
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<(&'static str, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = &amz_date[..8];
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+
+    // Non-S3 services encode each path segment twice: once on the wire,
+    // once more in the canonical request.
+    let canonical_uri = url
+        .path()
+        .split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/");
+    let mut query: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| (uri_encode(&k), uri_encode(&v)))
+        .collect();
+    query.sort();
+    let canonical_query = query
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut canonical_headers = format!("host:{}\nx-amz-date:{}\n", host, amz_date);
+    let mut signed_headers = "host;x-amz-date".to_string();
+    if let Some(token) = &creds.session_token {
+        let _ = writeln!(canonical_headers, "x-amz-security-token:{}", token);
+        signed_headers.push_str(";x-amz-security-token");
+    }
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        canonical_uri,
+        canonical_query,
+        canonical_headers,
+        signed_headers,
+        hex(&Sha256::digest(body))
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let k_date = hmac_sha256(
+        format!("AWS4{}", creds.secret_access_key).as_bytes(),
+        date.as_bytes(),
+    );
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    let k_signing = hmac_sha256(&k_service, b"aws4_request");
+    let signature = hex(&hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+    let mut headers = vec![("x-amz-date", amz_date.clone())];
+    if let Some(token) = &creds.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            creds.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    headers
+}
+
+/// RFC 3986 encoding as SigV4 defines it: everything but unreserved
+/// characters becomes `%XX` (uppercase hex).
+fn uri_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            out.push(byte as char);
+        } else {
+            let _ = write!(out, "%{:02X}", byte);
+        }
+    }
+    out
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let ipad: Vec<u8> = block.iter().map(|b| b ^ 0x36).collect();
+    let opad: Vec<u8> = block.iter().map(|b| b ^ 0x5c).collect();
+    let inner = Sha256::new()
+        .chain_update(&ipad)
+        .chain_update(data)
+        .finalize();
+    let outer = Sha256::new()
+        .chain_update(&opad)
+        .chain_update(inner)
+        .finalize();
+    let mut mac = [0u8; 32];
+    mac.copy_from_slice(&outer);
+    mac
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
+            let _ = write!(s, "{:02x}", b);
+            s
+        })
+}
+
+// ── Event stream ────────────────────────────────────────────────────────
+
+/// One decoded `application/vnd.amazon.eventstream` frame.
+struct EventFrame {
+    headers: HashMap<String, String>,
+    payload: Vec<u8>,
+}
+
+/// Pull every complete frame off the front of `buf`, leaving a trailing
+/// partial frame for the next chunk.
+///
+/// Frame layout: total length (u32 BE), headers length (u32 BE), prelude
+/// CRC, headers, payload, message CRC. The CRCs aren't checked — TLS
+/// already guarantees integrity.
+fn decode_frames(buf: &mut Vec<u8>) -> std::result::Result<Vec<EventFrame>, String> {
+    let mut frames = Vec::new();
+    while buf.len() >= 12 {
+        let total = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+        let headers_len = u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]) as usize;
+        if total < 16 + headers_len {
+            return Err(format!("malformed event-stream frame (length {})", total));
+        }
+        if buf.len() < total {
+            break;
+        }
+        let frame: Vec<u8> = buf.drain(..total).collect();
+        let headers = parse_frame_headers(&frame[12..12 + headers_len])
+            .ok_or_else(|| "malformed event-stream headers".to_string())?;
+        frames.push(EventFrame {
+            headers,
+            payload: frame[12 + headers_len..total - 4].to_vec(),
+        });
+    }
+    Ok(frames)
+}
+
+/// Parse frame headers, keeping only string-typed values (the only kind
+/// Bedrock sends: `:event-type`, `:message-type`, `:exception-type`, ...).
+fn parse_frame_headers(data: &[u8]) -> Option<HashMap<String, String>> {
+    let mut headers = HashMap::new();
+    let mut i = 0;
+    while i < data.len() {
+        let name_len = *data.get(i)? as usize;
+        let name = String::from_utf8_lossy(data.get(i + 1..i + 1 + name_len)?).to_string();
+        i += 1 + name_len;
+        let value_type = *data.get(i)?;
+        i += 1;
+        let value_len = match value_type {
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 | 8 => 8,
+            9 => 16,
+            6 | 7 => {
+                let len = u16::from_be_bytes([*data.get(i)?, *data.get(i + 1)?]) as usize;
+                i += 2;
+                len
+            }
+            _ => return None,
+        };
+        let value = data.get(i..i + value_len)?;
+        i += value_len;
+        if value_type == 7 {
+            headers.insert(name, String::from_utf8_lossy(value).to_string());
+        }
+    }
+    Some(headers)
+}
+
+/// Streaming state persisted across ConverseStream chunks
+struct ConverseStreamState {
+    model: String,
+    buffer: Vec<u8>,
+    /// Block indices that already had a ContentBlockStart emitted. Text and
+    /// reasoning blocks have no start event in Converse, so we synthesize
+    /// one on their first delta.
+    open_blocks: HashSet<usize>,
+    /// `messageStop` carries the stop reason, `metadata` (sent after it)
+    /// carries usage — both go out together as one MessageDelta.
+    stop_reason: Option<StopReason>,
+}
+
+impl ConverseStreamState {
+    fn new(model: String) -> Self {
+        Self {
+            model,
+            buffer: Vec::new(),
+            open_blocks: HashSet::new(),
+            stop_reason: None,
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) -> Vec<Result<StreamEvent>> {
+        self.buffer.extend_from_slice(chunk);
+        let frames = match decode_frames(&mut self.buffer) {
+            Ok(frames) => frames,
+            Err(e) => return vec![Err(ProviderError::StreamError(e))],
+        };
+
+        let mut events = Vec::new();
+        for frame in frames {
+            events.extend(self.handle_frame(frame));
+        }
+        events
+    }
+
+    fn handle_frame(&mut self, frame: EventFrame) -> Vec<Result<StreamEvent>> {
+        let message_type = frame
+            .headers
+            .get(":message-type")
+            .map(String::as_str)
+            .unwrap_or("event");
+        if message_type != "event" {
+            let kind = frame
+                .headers
+                .get(":exception-type")
+                .or_else(|| frame.headers.get(":error-code"))
+                .cloned()
+                .unwrap_or_else(|| "unknownException".to_string());
+            let message = serde_json::from_slice::<Value>(&frame.payload)
+                .ok()
+                .and_then(|v| v["message"].as_str().map(str::to_string))
+                .or_else(|| frame.headers.get(":error-message").cloned())
+                .unwrap_or_default();
+            let error = if kind == "throttlingException" {
+                ProviderError::RateLimitExceeded(message)
+            } else {
+                ProviderError::StreamError(format!("Bedrock {}: {}", kind, message))
+            };
+            return vec![Err(error)];
+        }
+
+        let event_type = frame
+            .headers
+            .get(":event-type")
+            .cloned()
+            .unwrap_or_default();
+        let payload: Value = match serde_json::from_slice(&frame.payload) {
+            Ok(v) => v,
+            Err(e) => {
+                tracing::warn!(
+                    "Bedrock: failed to parse {} event payload: {}",
+                    event_type,
+                    e
+                );
+                return Vec::new();
+            }
+        };
+        self.translate(&event_type, &payload)
+    }
+
+    fn translate(&mut self, event_type: &str, payload: &Value) -> Vec<Result<StreamEvent>> {
+        let index = payload["contentBlockIndex"].as_u64().unwrap_or(0) as usize;
+        let mut events = Vec::new();
+
+        match event_type {
+            "messageStart" => events.push(Ok(StreamEvent::MessageStart {
+                message: StreamMessage {
+                    id: format!("bedrock-{}", uuid::Uuid::new_v4().simple()),
+                    model: self.model.clone(),
+                    role: Role::Assistant,
+                    usage: TokenUsage::default(),
+                },
+            })),
+            "contentBlockStart" => {
+                let tool = &payload["start"]["toolUse"];
+                if tool.is_object() {
+                    self.open_blocks.insert(index);
+                    events.push(Ok(StreamEvent::ContentBlockStart {
+                        index,
+                        content_block: ContentBlock::ToolUse {
+                            id: tool["toolUseId"].as_str().unwrap_or_default().to_string(),
+                            name: tool["name"].as_str().unwrap_or_default().to_string(),
+                            input: json!({}),
+                        },
+                    }));
+                }
+            }
+            "contentBlockDelta" => {
+                let delta = &payload["delta"];
+                let reasoning = &delta["reasoningContent"];
+                if let Some(text) = delta["text"].as_str() {
+                    self.open(
+                        index,
+                        ContentBlock::Text {
+                            text: String::new(),
+                        },
+                        &mut events,
+                    );
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index,
+                        delta: ContentDelta::TextDelta {
+                            text: text.to_string(),
+                        },
+                    }));
+                } else if let Some(input) = delta["toolUse"]["input"].as_str() {
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index,
+                        delta: ContentDelta::InputJsonDelta {
+                            partial_json: input.to_string(),
+                        },
+                    }));
+                } else if let Some(text) = reasoning["text"].as_str() {
+                    self.open(index, empty_thinking(), &mut events);
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index,
+                        delta: ContentDelta::ThinkingDelta {
+                            thinking: text.to_string(),
+                        },
+                    }));
+                } else if let Some(signature) = reasoning["signature"].as_str() {
+                    self.open(index, empty_thinking(), &mut events);
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index,
+                        delta: ContentDelta::SignatureDelta {
+                            signature: signature.to_string(),
+                        },
+                    }));
+                } else if let Some(data) = reasoning["redactedContent"].as_str() {
+                    self.open(
+                        index,
+                        ContentBlock::RedactedThinking {
+                            data: data.to_string(),
+                        },
+                        &mut events,
+                    );
+                }
+            }
+            "contentBlockStop" => {
+                if self.open_blocks.remove(&index) {
+                    events.push(Ok(StreamEvent::ContentBlockStop { index }));
+                }
+            }
+            "messageStop" => {
+                self.stop_reason = Some(map_stop_reason(
+                    payload["stopReason"].as_str().unwrap_or_default(),
+                ));
+            }
+            "metadata" => {
+                events.push(Ok(StreamEvent::MessageDelta {
+                    delta: MessageDelta {
+                        stop_reason: Some(self.stop_reason.take().unwrap_or(StopReason::EndTurn)),
+                        stop_sequence: None,
+                    },
+                    usage: parse_usage(&payload["usage"]),
+                }));
+                events.push(Ok(StreamEvent::MessageStop));
+            }
+            other => tracing::debug!("Bedrock: ignoring stream event {}", other),
+        }
+
+        events
+    }
+
+    fn open(&mut self, index: usize, block: ContentBlock, events: &mut Vec<Result<StreamEvent>>) {
+        if self.open_blocks.insert(index) {
+            events.push(Ok(StreamEvent::ContentBlockStart {
+                index,
+                content_block: block,
+            }));
+        }
+    }
+}
+
+fn empty_thinking() -> ContentBlock {
+    ContentBlock::Thinking {
+        thinking: String::new(),
+        signature: None,
+    }
+}
+
+#[async_trait]
+impl Provider for BedrockProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        use super::retry::{RetryConfig, retry_with_backoff};
+
+        let model = request.model.clone();
+        tracing::info!(
+            "Bedrock API request: model={}, region={}, messages={}",
+            model,
+            self.region,
+            request.messages.len()
+        );
+
+        let body = serde_json::to_vec(&build_converse_body(&request))?;
+        let url = self.runtime_url(&model, false);
+        let retry_config = RetryConfig::default();
+
+        let result = retry_with_backoff(
+            || async {
+                let response = self.post(&url, &body).await?;
+                let json: Value = response.json().await?;
+                let mut llm_response = parse_converse_response(&model, &json);
+                if let Some(format) = &request.response_format {
+                    super::structured::unwrap_response_tool(&mut llm_response, format);
+                }
+
+                tracing::info!(
+                    "Bedrock API response: input_tokens={}, output_tokens={}, stop_reason={:?}",
+                    llm_response.usage.input_tokens,
+                    llm_response.usage.output_tokens,
+                    llm_response.stop_reason
+                );
+
+                Ok(llm_response)
+            },
+            &retry_config,
+        )
+        .await;
+
+        if let Err(ref e) = result {
+            tracing::error!("Bedrock API request failed: {}", e);
+        }
+
+        result
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        use super::retry::{RetryConfig, retry_with_backoff};
+
+        let model = request.model.clone();
+        tracing::info!(
+            "Bedrock streaming request: model={}, region={}, messages={}",
+            model,
+            self.region,
+            request.messages.len()
+        );
+
+        let body = serde_json::to_vec(&build_converse_body(&request))?;
+        let url = self.runtime_url(&model, true);
+        let retry_config = RetryConfig::default();
+
+        let response =
+            retry_with_backoff(|| async { self.post(&url, &body).await }, &retry_config).await?;
+
+        let state = Arc::new(Mutex::new(ConverseStreamState::new(model)));
+        let event_stream = response
+            .bytes_stream()
+            .map(move |chunk_result| -> Vec<Result<StreamEvent>> {
+                match chunk_result {
+                    Err(e) => vec![Err(ProviderError::StreamError(e.to_string()))],
+                    Ok(chunk) => {
+                        let mut st = state.lock().expect("event-stream state lock");
+                        let events = st.feed(&chunk);
+                        if events.is_empty() {
+                            vec![Ok(StreamEvent::Ping)]
+                        } else {
+                            events
+                        }
+                    }
+                }
+            })
+            .flat_map(futures::stream::iter);
+
+        Ok(Box::pin(event_stream))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn supports_response_format(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        "bedrock"
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        let mut models = vec![self.model.clone()];
+        let builtin = [
+            DEFAULT_MODEL,
+            "us.anthropic.claude-opus-4-1-20250805-v1:0",
+            "us.anthropic.claude-haiku-4-5-20251001-v1:0",
+            "us.amazon.nova-pro-v1:0",
+            "us.amazon.nova-lite-v1:0",
+            "us.meta.llama3-3-70b-instruct-v1:0",
+            "mistral.mistral-large-2407-v1:0",
+        ];
+        for model in self
+            .models
+            .iter()
+            .map(String::as_str)
+            .chain(builtin.iter().copied())
+        {
+            if !models.iter().any(|m| m == model) {
+                models.push(model.to_string());
+            }
+        }
+        models
+    }
+
+    async fn fetch_models(&self) -> Vec<String> {
+        // The model catalogue lives on the control-plane host, not the
+        // runtime one — nothing to list behind a custom endpoint.
+        if self.base_url.is_some() {
+            return self.supported_models();
+        }
+        let url = format!(
+            "https://bedrock.{}.amazonaws.com/foundation-models?byOutputModality=TEXT",
+            self.region
+        );
+        let Ok(builder) = self.authorize(self.client.get(&url), "GET", &url, b"") else {
+            return self.supported_models();
+        };
+
+        match builder.send().await {
+            Ok(resp) if resp.status().is_success() => match resp.json::<Value>().await {
+                Ok(body) => {
+                    let mut models = self.supported_models();
+                    for summary in body["modelSummaries"].as_array().into_iter().flatten() {
+                        if summary["responseStreamingSupported"] == false {
+                            continue;
+                        }
+                        if let Some(id) = summary["modelId"].as_str()
+                            && !models.iter().any(|m| m == id)
+                        {
+                            models.push(id.to_string());
+                        }
+                    }
+                    models
+                }
+                Err(_) => self.supported_models(),
+            },
+            _ => self.supported_models(),
+        }
+    }
+
+    fn context_window(&self, model: &str) -> Option<u32> {
+        match model {
+            m if m.contains("anthropic.claude") => Some(200_000),
+            m if m.contains("nova-pro") || m.contains("nova-lite") => Some(300_000),
+            m if m.contains("nova-micro") => Some(128_000),
+            m if m.contains("llama3") || m.contains("llama4") => Some(128_000),
+            m if m.contains("mistral-large") => Some(128_000),
+            _ => None,
+        }
+    }
+
+    fn calculate_cost(&self, model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        crate::usage::pricing::PricingConfig::load()
+            .map(|cfg| cfg.calculate_cost(model, input_tokens, output_tokens))
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode one event-stream frame (CRCs zeroed — the decoder ignores them).
+    fn frame(headers: &[(&str, &str)], payload: &[u8]) -> Vec<u8> {
+        let mut header_bytes = Vec::new();
+        for (name, value) in headers {
+            header_bytes.push(name.len() as u8);
+            header_bytes.extend_from_slice(name.as_bytes());
+            header_bytes.push(7);
+            header_bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            header_bytes.extend_from_slice(value.as_bytes());
+        }
+        let total = 16 + header_bytes.len() + payload.len();
+        let mut out = Vec::with_capacity(total);
+        out.extend_from_slice(&(total as u32).to_be_bytes());
+        out.extend_from_slice(&(header_bytes.len() as u32).to_be_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&header_bytes);
+        out.extend_from_slice(payload);
+        out.extend_from_slice(&[0; 4]);
+        out
+    }
+
+    fn event(event_type: &str, payload: Value) -> Vec<u8> {
+        frame(
+            &[(":message-type", "event"), (":event-type", event_type)],
+            payload.to_string().as_bytes(),
+        )
+    }
+
+    #[test]
+    fn test_sigv4_matches_aws_vanilla_vector() {
+        // aws-sig-v4-test-suite "get-vanilla"
+        let creds = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let url = reqwest::Url::parse("https://example.amazonaws.com/").unwrap();
+        let now = chrono::DateTime::parse_from_rfc3339("2015-08-30T12:36:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let headers = sigv4_headers(&creds, "us-east-1", "service", "GET", &url, b"", now);
+
+        let auth = &headers
+            .iter()
+            .find(|(k, _)| *k == "authorization")
+            .unwrap()
+            .1;
+        assert_eq!(
+            auth,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+        assert!(headers.contains(&("x-amz-date", "20150830T123600Z".to_string())));
+    }
+
+    #[test]
+    fn test_sigv4_signs_session_token() {
+        let creds = AwsCredentials {
+            access_key_id: "AKID".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: Some("token".to_string()),
+        };
+        let url = reqwest::Url::parse("http://127.0.0.1:9000/model/a%3A0/converse").unwrap();
+        let headers = sigv4_headers(
+            &creds,
+            "us-west-2",
+            "bedrock",
+            "POST",
+            &url,
+            b"{}",
+            chrono::Utc::now(),
+        );
+        assert!(headers.contains(&("x-amz-security-token", "token".to_string())));
+        let auth = &headers
+            .iter()
+            .find(|(k, _)| *k == "authorization")
+            .unwrap()
+            .1;
+        assert!(auth.contains("SignedHeaders=host;x-amz-date;x-amz-security-token"));
+    }
+
+    #[test]
+    fn test_uri_encode_double_encodes_path() {
+        assert_eq!(uri_encode("a%3A0"), "a%253A0");
+        assert_eq!(uri_encode("us.model-v1_~"), "us.model-v1_~");
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231_case2() {
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            hex(&mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_ini_section_lookup() {
+        let ini = "[default]\naws_access_key_id = AKID\n\n[profile work]\nregion=eu-west-1\n";
+        let default = ini_section(ini, "default").unwrap();
+        assert_eq!(default.get("aws_access_key_id").unwrap(), "AKID");
+        let work = ini_section(ini, "profile work").unwrap();
+        assert_eq!(work.get("region").unwrap(), "eu-west-1");
+        assert!(ini_section(ini, "missing").is_none());
+    }
+
+    #[test]
+    fn test_converse_body_maps_tools_images_and_results() {
+        let request = LLMRequest::new(
+            "us.amazon.nova-pro-v1:0",
+            vec![
+                Message {
+                    role: Role::User,
+                    content: vec![
+                        ContentBlock::Text {
+                            text: "what is this?".to_string(),
+                        },
+                        ContentBlock::Image {
+                            source: ImageSource::Base64 {
+                                media_type: "image/jpeg".to_string(),
+                                data: "AAAA".to_string(),
+                            },
+                        },
+                    ],
+                },
+                Message {
+                    role: Role::Assistant,
+                    content: vec![ContentBlock::ToolUse {
+                        id: "tu_1".to_string(),
+                        name: "read_file".to_string(),
+                        input: json!({"path": "a.txt"}),
+                    }],
+                },
+                Message {
+                    role: Role::User,
+                    content: vec![ContentBlock::ToolResult {
+                        tool_use_id: "tu_1".to_string(),
+                        content: String::new(),
+                        is_error: Some(true),
+                    }],
+                },
+                Message::user("continue"),
+            ],
+        )
+        .with_system("be brief")
+        .with_temperature(0.2)
+        .with_tools(vec![Tool {
+            name: "read_file".to_string(),
+            description: "Read a file".to_string(),
+            input_schema: json!({"type": "object"}),
+        }]);
+
+        let body = build_converse_body(&request);
+        assert_eq!(body["system"][0]["text"], "be brief");
+        assert_eq!(body["inferenceConfig"]["maxTokens"], DEFAULT_MAX_TOKENS);
+        assert!((body["inferenceConfig"]["temperature"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+
+        let messages = body["messages"].as_array().unwrap();
+        // Tool result and the follow-up user text fold into one user turn.
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["content"][1]["image"]["format"], "jpeg");
+        assert_eq!(
+            messages[0]["content"][1]["image"]["source"]["bytes"],
+            "AAAA"
+        );
+        assert_eq!(messages[1]["content"][0]["toolUse"]["toolUseId"], "tu_1");
+        let result = &messages[2]["content"][0]["toolResult"];
+        assert_eq!(result["status"], "error");
+        assert_eq!(result["content"][0]["text"], "(no output)");
+        assert_eq!(messages[2]["content"][1]["text"], "continue");
+
+        let spec = &body["toolConfig"]["tools"][0]["toolSpec"];
+        assert_eq!(spec["name"], "read_file");
+        assert_eq!(spec["inputSchema"]["json"]["type"], "object");
+        assert!(body.get("additionalModelRequestFields").is_none());
+    }
+
+    #[test]
+    fn test_converse_body_thinking_only_for_claude() {
+        let messages = vec![Message::user("hi")];
+        let claude = LLMRequest::new(DEFAULT_MODEL, messages.clone())
+            .with_max_tokens(4096)
+            .with_temperature(0.5)
+            .with_thinking_budget(8000);
+        let body = build_converse_body(&claude);
+        assert_eq!(
+            body["additionalModelRequestFields"]["thinking"]["budget_tokens"],
+            8000
+        );
+        assert_eq!(
+            body["inferenceConfig"]["maxTokens"],
+            8000 + DEFAULT_MAX_TOKENS
+        );
+        assert!(body["inferenceConfig"].get("temperature").is_none());
+
+        let nova = LLMRequest::new("us.amazon.nova-pro-v1:0", messages).with_thinking_budget(8000);
+        assert!(
+            build_converse_body(&nova)
+                .get("additionalModelRequestFields")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_converse_body_tool_choice() {
+        let tools = vec![Tool {
+            name: "ls".into(),
+            description: "list".into(),
+            input_schema: json!({"type": "object"}),
+        }];
+        let request = |choice| {
+            LLMRequest::new(DEFAULT_MODEL, vec![Message::user("hi")])
+                .with_tools(tools.clone())
+                .with_thinking_budget(8000)
+                .with_tool_choice(choice)
+        };
+
+        let forced = build_converse_body(&request(ToolChoice::Tool("ls".into())));
+        assert_eq!(
+            forced["toolConfig"]["toolChoice"],
+            json!({"tool": {"name": "ls"}})
+        );
+        assert!(forced.get("additionalModelRequestFields").is_none());
+
+        let any = build_converse_body(&request(ToolChoice::Required));
+        assert_eq!(any["toolConfig"]["toolChoice"], json!({"any": {}}));
+
+        // No tool history: "none" withholds the tools entirely
+        let none = build_converse_body(&request(ToolChoice::None));
+        assert!(none.get("toolConfig").is_none());
+        assert!(none.get("additionalModelRequestFields").is_some());
+    }
+
+    #[test]
+    fn test_parse_converse_response() {
+        let json = json!({
+            "output": {"message": {"role": "assistant", "content": [
+                {"text": "Let me check."},
+                {"toolUse": {"toolUseId": "tu_9", "name": "ls", "input": {"path": "."}}}
+            ]}},
+            "stopReason": "tool_use",
+            "usage": {"inputTokens": 12, "outputTokens": 7, "cacheReadInputTokens": 3}
+        });
+        let response = parse_converse_response("m", &json);
+        assert!(matches!(response.stop_reason, Some(StopReason::ToolUse)));
+        assert_eq!(response.usage.input_tokens, 12);
+        assert_eq!(response.usage.output_tokens, 7);
+        assert_eq!(response.usage.cache_read_tokens, 3);
+        assert!(matches!(
+            &response.content[1],
+            ContentBlock::ToolUse { id, name, .. } if id == "tu_9" && name == "ls"
+        ));
+    }
+
+    #[test]
+    fn test_decode_frames_keeps_partial_tail() {
+        let mut bytes = event("messageStart", json!({"role": "assistant"}));
+        let second = event("messageStop", json!({"stopReason": "end_turn"}));
+        bytes.extend_from_slice(&second[..10]);
+
+        let frames = decode_frames(&mut bytes).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].headers[":event-type"], "messageStart");
+        assert_eq!(bytes.len(), 10);
+
+        bytes.extend_from_slice(&second[10..]);
+        let frames = decode_frames(&mut bytes).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_stream_translates_text_and_tool_use() {
+        let mut state = ConverseStreamState::new("m".to_string());
+        let mut bytes = Vec::new();
+        for (ty, payload) in [
+            ("messageStart", json!({"role": "assistant"})),
+            (
+                "contentBlockDelta",
+                json!({"contentBlockIndex": 0, "delta": {"text": "Hi"}}),
+            ),
+            ("contentBlockStop", json!({"contentBlockIndex": 0})),
+            (
+                "contentBlockStart",
+                json!({"contentBlockIndex": 1, "start": {"toolUse": {"toolUseId": "t1", "name": "ls"}}}),
+            ),
+            (
+                "contentBlockDelta",
+                json!({"contentBlockIndex": 1, "delta": {"toolUse": {"input": "{\"a\":1}"}}}),
+            ),
+            ("contentBlockStop", json!({"contentBlockIndex": 1})),
+            ("messageStop", json!({"stopReason": "tool_use"})),
+            (
+                "metadata",
+                json!({"usage": {"inputTokens": 5, "outputTokens": 2}}),
+            ),
+        ] {
+            bytes.extend(event(ty, payload));
+        }
+
+        let events: Vec<StreamEvent> = state.feed(&bytes).into_iter().map(|e| e.unwrap()).collect();
+        assert!(matches!(events[0], StreamEvent::MessageStart { .. }));
+        assert!(matches!(
+            events[1],
+            StreamEvent::ContentBlockStart {
+                index: 0,
+                content_block: ContentBlock::Text { .. }
+            }
+        ));
+        assert!(matches!(
+            &events[2],
+            StreamEvent::ContentBlockDelta { delta: ContentDelta::TextDelta { text }, .. } if text == "Hi"
+        ));
+        assert!(matches!(
+            &events[4],
+            StreamEvent::ContentBlockStart { index: 1, content_block: ContentBlock::ToolUse { id, .. } } if id == "t1"
+        ));
+        assert!(matches!(
+            &events[5],
+            StreamEvent::ContentBlockDelta { delta: ContentDelta::InputJsonDelta { partial_json }, .. } if partial_json == "{\"a\":1}"
+        ));
+        assert!(matches!(
+            &events[7],
+            StreamEvent::MessageDelta { delta, usage }
+                if matches!(delta.stop_reason, Some(StopReason::ToolUse)) && usage.input_tokens == 5
+        ));
+        assert!(matches!(events[8], StreamEvent::MessageStop));
+    }
+
+    #[test]
+    fn test_stream_reasoning_deltas() {
+        let mut state = ConverseStreamState::new("m".to_string());
+        let mut bytes = event(
+            "contentBlockDelta",
+            json!({"contentBlockIndex": 0, "delta": {"reasoningContent": {"text": "hmm"}}}),
+        );
+        bytes.extend(event(
+            "contentBlockDelta",
+            json!({"contentBlockIndex": 0, "delta": {"reasoningContent": {"signature": "sig"}}}),
+        ));
+
+        let events: Vec<StreamEvent> = state.feed(&bytes).into_iter().map(|e| e.unwrap()).collect();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[0],
+            StreamEvent::ContentBlockStart {
+                content_block: ContentBlock::Thinking { .. },
+                ..
+            }
+        ));
+        assert!(matches!(
+            &events[2],
+            StreamEvent::ContentBlockDelta { delta: ContentDelta::SignatureDelta { signature }, .. } if signature == "sig"
+        ));
+    }
+
+    #[test]
+    fn test_stream_exception_frame() {
+        let mut state = ConverseStreamState::new("m".to_string());
+        let bytes = frame(
+            &[
+                (":message-type", "exception"),
+                (":exception-type", "throttlingException"),
+            ],
+            br#"{"message":"slow down"}"#,
+        );
+        let events = state.feed(&bytes);
+        assert!(matches!(
+            &events[0],
+            Err(ProviderError::RateLimitExceeded(msg)) if msg == "slow down"
+        ));
+    }
+}
@@ -0,0 +1,546 @@
+//! Cassette Provider
+//!
+//! Record-and-replay wrapper for deterministic tests. In record mode every
+//! call is forwarded to a real provider and the request plus the full
+//! response (the `ProviderStream` event sequence, the `complete()` reply or
+//! the error) is appended to a JSON cassette. In replay mode the cassette
+//! answers instead — no network, no keys — so a multi-turn tool-loop session
+//! captured once runs offline as a regression test.
+//!
+//! Replay matches each request on its model, last user message and trailing
+//! tool results. The first unused interaction that matches is played back;
+//! when none does, the call fails with a diff against the next unused one.
+
+use super::error::{ProviderError, Result};
+use super::r#trait::{Provider, ProviderStream};
+use super::types::{ContentBlock, LLMRequest, LLMResponse, Role, StreamEvent};
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// What a replayed request must agree on with the recorded one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestMatch {
+    pub model: String,
+    /// Text of the most recent user message that carries text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_user_message: Option<String>,
+    /// Tool results in the final message, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_results: Vec<RecordedToolResult>,
+}
+
+/// A tool result as seen by the matcher. Tool-use IDs are left out: on
+/// replay they come from the cassette anyway.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedToolResult {
+    pub content: String,
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+impl RequestMatch {
+    pub fn from_request(request: &LLMRequest) -> Self {
+        let last_user_message = request
+            .messages
+            .iter()
+            .rev()
+            .filter(|m| m.role == Role::User)
+            .find_map(|m| {
+                let text: Vec<&str> = m
+                    .content
+                    .iter()
+                    .filter_map(|b| match b {
+                        ContentBlock::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                (!text.is_empty()).then(|| text.join("\n"))
+            });
+        let tool_results = request
+            .messages
+            .last()
+            .map(|m| {
+                m.content
+                    .iter()
+                    .filter_map(|b| match b {
+                        ContentBlock::ToolResult {
+                            content, is_error, ..
+                        } => Some(RecordedToolResult {
+                            content: content.clone(),
+                            is_error: is_error.unwrap_or(false),
+                        }),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            model: request.model.clone(),
+            last_user_message,
+            tool_results,
+        }
+    }
+
+    /// Human-readable differences, one `-expected`/`+actual` pair per field.
+    pub fn diff(&self, actual: &Self) -> String {
+        let mut out = String::new();
+        if self.model != actual.model {
+            out.push_str(&format!(
+                "  model:\n  - {}\n  + {}\n",
+                self.model, actual.model
+            ));
+        }
+        if self.last_user_message != actual.last_user_message {
+            out.push_str("  last user message:\n");
+            push_lines(&mut out, '-', self.last_user_message.as_deref());
+            push_lines(&mut out, '+', actual.last_user_message.as_deref());
+        }
+        let n = self.tool_results.len().max(actual.tool_results.len());
+        for i in 0..n {
+            let expected = self.tool_results.get(i);
+            let got = actual.tool_results.get(i);
+            if expected == got {
+                continue;
+            }
+            out.push_str(&format!("  tool result #{}:\n", i + 1));
+            push_lines(&mut out, '-', expected.map(describe_tool_result).as_deref());
+            push_lines(&mut out, '+', got.map(describe_tool_result).as_deref());
+        }
+        out
+    }
+
+    /// The request fields as `+` lines, for requests with nothing to diff against.
+    pub fn describe(&self) -> String {
+        let mut out = format!("  model:\n  + {}\n  last user message:\n", self.model);
+        push_lines(&mut out, '+', self.last_user_message.as_deref());
+        for (i, result) in self.tool_results.iter().enumerate() {
+            out.push_str(&format!("  tool result #{}:\n", i + 1));
+            push_lines(&mut out, '+', Some(&describe_tool_result(result)));
+        }
+        out
+    }
+}
+
+fn describe_tool_result(result: &RecordedToolResult) -> String {
+    if result.is_error {
+        format!("[error] {}", result.content)
+    } else {
+        result.content.clone()
+    }
+}
+
+fn push_lines(out: &mut String, sign: char, text: Option<&str>) {
+    match text {
+        None => out.push_str(&format!("  {} <none>\n", sign)),
+        Some(text) => {
+            for line in text.lines() {
+                out.push_str(&format!("  {} {}\n", sign, line));
+            }
+        }
+    }
+}
+
+/// An error as recorded. API and rate-limit errors keep their shape so
+/// retry and fallback logic behaves the same on replay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordedError {
+    Api {
+        status: u16,
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_type: Option<String>,
+    },
+    RateLimit {
+        message: String,
+    },
+    Other {
+        message: String,
+    },
+}
+
+impl From<&ProviderError> for RecordedError {
+    fn from(err: &ProviderError) -> Self {
+        match err {
+            ProviderError::ApiError {
+                status,
+                message,
+                error_type,
+            } => Self::Api {
+                status: *status,
+                message: message.clone(),
+                error_type: error_type.clone(),
+            },
+            ProviderError::RateLimitExceeded(message) => Self::RateLimit {
+                message: message.clone(),
+            },
+            other => Self::Other {
+                message: other.to_string(),
+            },
+        }
+    }
+}
+
+impl From<&RecordedError> for ProviderError {
+    fn from(err: &RecordedError) -> Self {
+        match err {
+            RecordedError::Api {
+                status,
+                message,
+                error_type,
+            } => ProviderError::ApiError {
+                status: *status,
+                message: message.clone(),
+                error_type: error_type.clone(),
+            },
+            RecordedError::RateLimit { message } => {
+                ProviderError::RateLimitExceeded(message.clone())
+            }
+            RecordedError::Other { message } => ProviderError::Internal(message.clone()),
+        }
+    }
+}
+
+/// One item of a recorded stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RecordedEvent {
+    Event(StreamEvent),
+    Error { error: RecordedError },
+}
+
+/// How the provider answered a recorded call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum RecordedReply {
+    Stream { events: Vec<RecordedEvent> },
+    Complete { response: LLMResponse },
+    Error { error: RecordedError },
+}
+
+/// One request/response exchange.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    #[serde(rename = "match")]
+    pub request_match: RequestMatch,
+    /// Full request as sent, for reading the cassette — not matched on
+    pub request: LLMRequest,
+    pub reply: RecordedReply,
+}
+
+/// On-disk cassette.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    /// `name()` of the recorded provider
+    pub provider: String,
+    /// `default_model()` of the recorded provider
+    pub default_model: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u32>,
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| ProviderError::Internal(format!("cassette {}: {}", path.display(), e)))?;
+        Ok(serde_json::from_str(&raw)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| {
+                ProviderError::Internal(format!("cassette {}: {}", path.display(), e))
+            })?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| ProviderError::Internal(format!("cassette {}: {}", path.display(), e)))
+    }
+}
+
+struct ReplayState {
+    cassette: Cassette,
+    used: Vec<bool>,
+}
+
+enum Mode {
+    Record {
+        inner: Arc<dyn Provider>,
+        cassette: Arc<Mutex<Cassette>>,
+    },
+    Replay(Mutex<ReplayState>),
+}
+
+/// Provider that records to, or replays from, a [`Cassette`] file.
+pub struct CassetteProvider {
+    mode: Mode,
+    path: PathBuf,
+    name: String,
+    default_model: String,
+    context_window: Option<u32>,
+}
+
+impl CassetteProvider {
+    /// Forward every call to `inner` and write the exchanges to `path`
+    /// (rewritten after each call, so a crashed run still leaves a cassette).
+    pub fn record(inner: Arc<dyn Provider>, path: impl Into<PathBuf>) -> Self {
+        let name = inner.name().to_string();
+        let default_model = inner.default_model().to_string();
+        let context_window = inner.context_window(&default_model);
+        let cassette = Cassette {
+            provider: name.clone(),
+            default_model: default_model.clone(),
+            context_window,
+            interactions: Vec::new(),
+        };
+        Self {
+            mode: Mode::Record {
+                inner,
+                cassette: Arc::new(Mutex::new(cassette)),
+            },
+            path: path.into(),
+            name,
+            default_model,
+            context_window,
+        }
+    }
+
+    /// Answer from the cassette at `path` without touching `inner`.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let cassette = Cassette::load(&path)?;
+        Ok(Self {
+            name: cassette.provider.clone(),
+            default_model: cassette.default_model.clone(),
+            context_window: cassette.context_window,
+            mode: Mode::Replay(Mutex::new(ReplayState {
+                used: vec![false; cassette.interactions.len()],
+                cassette,
+            })),
+            path,
+        })
+    }
+
+    /// Replay `path` if it exists, otherwise record it from `inner`.
+    /// Setting `OPENCRABS_CASSETTE=record` re-records an existing cassette.
+    pub fn auto(inner: Arc<dyn Provider>, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let rerecord = std::env::var("OPENCRABS_CASSETTE").is_ok_and(|v| v == "record");
+        if path.exists() && !rerecord {
+            Self::replay(path)
+        } else {
+            Ok(Self::record(inner, path))
+        }
+    }
+
+    /// Whether this provider is replaying rather than recording.
+    pub fn is_replay(&self) -> bool {
+        matches!(self.mode, Mode::Replay(_))
+    }
+
+    /// Interactions not yet played back. Empty once a replayed session
+    /// made every recorded call — handy as a final test assertion.
+    pub fn unplayed(&self) -> Vec<RequestMatch> {
+        match &self.mode {
+            Mode::Record { .. } => Vec::new(),
+            Mode::Replay(state) => {
+                let state = state.lock().unwrap_or_else(|e| e.into_inner());
+                state
+                    .cassette
+                    .interactions
+                    .iter()
+                    .zip(&state.used)
+                    .filter(|(_, used)| !**used)
+                    .map(|(i, _)| i.request_match.clone())
+                    .collect()
+            }
+        }
+    }
+
+    fn append(cassette: &Mutex<Cassette>, path: &Path, request: LLMRequest, reply: RecordedReply) {
+        let mut cassette = cassette.lock().unwrap_or_else(|e| e.into_inner());
+        cassette.interactions.push(Interaction {
+            request_match: RequestMatch::from_request(&request),
+            request,
+            reply,
+        });
+        if let Err(e) = cassette.save(path) {
+            tracing::warn!("Failed to write cassette: {}", e);
+        }
+    }
+
+    /// Take the first unused interaction matching `request`.
+    fn next_reply(
+        &self,
+        state: &Mutex<ReplayState>,
+        request: &LLMRequest,
+    ) -> Result<RecordedReply> {
+        let actual = RequestMatch::from_request(request);
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        let ReplayState { cassette, used } = &mut *state;
+        let found = cassette
+            .interactions
+            .iter()
+            .zip(used.iter())
+            .position(|(i, used)| !used && i.request_match == actual);
+        if let Some(idx) = found {
+            used[idx] = true;
+            return Ok(cassette.interactions[idx].reply.clone());
+        }
+        let total = cassette.interactions.len();
+        let message = match used.iter().position(|u| !u) {
+            Some(idx) => format!(
+                "cassette {}: no recorded request matches (next unplayed is #{} of {}):\n{}",
+                self.path.display(),
+                idx + 1,
+                total,
+                cassette.interactions[idx].request_match.diff(&actual)
+            ),
+            None => format!(
+                "cassette {}: all {} recorded requests already played; unexpected extra request:\n{}",
+                self.path.display(),
+                total,
+                actual.describe()
+            ),
+        };
+        Err(ProviderError::Internal(message))
+    }
+
+    fn mismatched_method(&self, recorded: &str, called: &str) -> ProviderError {
+        ProviderError::Internal(format!(
+            "cassette {}: request was recorded with {}() but replayed with {}()",
+            self.path.display(),
+            recorded,
+            called
+        ))
+    }
+}
+
+#[async_trait]
+impl Provider for CassetteProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        match &self.mode {
+            Mode::Record { inner, cassette } => {
+                let result = inner.complete(request.clone()).await;
+                let reply = match &result {
+                    Ok(response) => RecordedReply::Complete {
+                        response: response.clone(),
+                    },
+                    Err(e) => RecordedReply::Error { error: e.into() },
+                };
+                Self::append(cassette, &self.path, request, reply);
+                result
+            }
+            Mode::Replay(state) => match self.next_reply(state, &request)? {
+                RecordedReply::Complete { response } => Ok(response),
+                RecordedReply::Error { error } => Err((&error).into()),
+                RecordedReply::Stream { .. } => Err(self.mismatched_method("stream", "complete")),
+            },
+        }
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        match &self.mode {
+            Mode::Record { inner, cassette } => {
+                let stream = match inner.stream(request.clone()).await {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        let reply = RecordedReply::Error { error: (&e).into() };
+                        Self::append(cassette, &self.path, request, reply);
+                        return Err(e);
+                    }
+                };
+                // Tee events into the cassette; the interaction is written
+                // once the stream is drained.
+                let events = Arc::new(Mutex::new(Vec::new()));
+                let sink = events.clone();
+                let tee = stream.map(move |item| {
+                    let recorded = match &item {
+                        Ok(event) => RecordedEvent::Event(event.clone()),
+                        Err(e) => RecordedEvent::Error { error: e.into() },
+                    };
+                    sink.lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(recorded);
+                    item
+                });
+                let cassette = cassette.clone();
+                let path = self.path.clone();
+                let finish = futures::stream::once(async move {
+                    let events =
+                        std::mem::take(&mut *events.lock().unwrap_or_else(|e| e.into_inner()));
+                    Self::append(&cassette, &path, request, RecordedReply::Stream { events });
+                })
+                .filter_map(|_| async { None });
+                Ok(Box::pin(tee.chain(finish)))
+            }
+            Mode::Replay(state) => match self.next_reply(state, &request)? {
+                RecordedReply::Stream { events } => {
+                    let items: Vec<Result<StreamEvent>> = events
+                        .into_iter()
+                        .map(|e| match e {
+                            RecordedEvent::Event(event) => Ok(event),
+                            RecordedEvent::Error { error } => Err((&error).into()),
+                        })
+                        .collect();
+                    Ok(Box::pin(futures::stream::iter(items)))
+                }
+                RecordedReply::Error { error } => Err((&error).into()),
+                RecordedReply::Complete { .. } => Err(self.mismatched_method("complete", "stream")),
+            },
+        }
+    }
+
+    fn supports_streaming(&self) -> bool {
+        match &self.mode {
+            Mode::Record { inner, .. } => inner.supports_streaming(),
+            Mode::Replay(_) => true,
+        }
+    }
+
+    fn supports_tools(&self) -> bool {
+        match &self.mode {
+            Mode::Record { inner, .. } => inner.supports_tools(),
+            Mode::Replay(_) => true,
+        }
+    }
+
+    fn supports_vision(&self) -> bool {
+        match &self.mode {
+            Mode::Record { inner, .. } => inner.supports_vision(),
+            Mode::Replay(_) => true,
+        }
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn default_model(&self) -> &str {
+        &self.default_model
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        match &self.mode {
+            Mode::Record { inner, .. } => inner.supported_models(),
+            Mode::Replay(_) => vec![self.default_model.clone()],
+        }
+    }
+
+    fn context_window(&self, model: &str) -> Option<u32> {
+        match &self.mode {
+            Mode::Record { inner, .. } => inner.context_window(model),
+            Mode::Replay(_) => self.context_window,
+        }
+    }
+
+    fn calculate_cost(&self, model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        match &self.mode {
+            Mode::Record { inner, .. } => inner.calculate_cost(model, input_tokens, output_tokens),
+            Mode::Replay(_) => 0.0,
+        }
+    }
+}
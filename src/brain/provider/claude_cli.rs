@@ -150,6 +150,7 @@ impl ClaudeCliProvider {
                             Some(format!("<thinking>{}</thinking>", thinking))
                         }
                     }
+                    ContentBlock::RedactedThinking { .. } => None,
                     ContentBlock::Image { source } => {
                         // CLI -p mode cannot process images inline.
                         // Keep the file path reference so the agent can use
@@ -1043,6 +1044,7 @@ impl Provider for ClaudeCliProvider {
                                     // Billing tokens (cumulative across rounds)
                                     billing_cache_creation,
                                     billing_cache_read,
+                                    ..Default::default()
                                 },
                             }))
                             .await;
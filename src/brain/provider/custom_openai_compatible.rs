@@ -7,8 +7,17 @@
 //! - Minimax
 //! - Local LLMs via LM Studio, Ollama, LocalAI
 //! - Any endpoint that speaks the OpenAI chat completions protocol
+//!
+//! Official OpenAI also gets the Batch API: requests are uploaded as a JSONL
+//! file, run against `/v1/chat/completions` within 24h at half price, and
+//! collected from the batch's output/error files.
 
+use super::batch::{BatchRequest, BatchResult, BatchState, BatchStatus, parse_jsonl};
 use super::error::{ProviderError, Result};
+use super::openai_responses::{
+    PendingLink, ResponseChain, ResponseObject, ResponsesStream, build_request_body,
+    is_stale_chain_error, parse_response, responses_url,
+};
 use super::rate_limiter::RateLimiter;
 use super::r#trait::{Provider, ProviderStream};
 use super::types::*;
@@ -1021,6 +1030,9 @@ pub struct OpenAIProvider {
     /// `retry_config()`. Used by `RotatingQwenProvider` to disable
     /// retry-on-rate-limit for sub-providers (rotation handles 429).
     retry_config_override: Option<super::retry::RetryConfig>,
+    /// Set when speaking the Responses API (`endpoint_type = "responses"`).
+    /// Holds the per-session `previous_response_id` chain, shared across clones.
+    responses_chain: Option<Arc<ResponseChain>>,
 }
 
 impl OpenAIProvider {
@@ -1085,6 +1097,7 @@ impl OpenAIProvider {
             auth_refresh_fn: None,
             auth_invalidate_fn: None,
             retry_config_override: None,
+            responses_chain: None,
         }
     }
 
@@ -1114,6 +1127,7 @@ impl OpenAIProvider {
             auth_refresh_fn: None,
             auth_invalidate_fn: None,
             retry_config_override: None,
+            responses_chain: None,
         }
     }
 
@@ -1143,6 +1157,7 @@ impl OpenAIProvider {
             auth_refresh_fn: None,
             auth_invalidate_fn: None,
             retry_config_override: None,
+            responses_chain: None,
         }
     }
 
@@ -1233,6 +1248,14 @@ impl OpenAIProvider {
         self
     }
 
+    /// Speak the Responses API (`/v1/responses`) instead of chat completions.
+    /// Follow-up turns in a session chain on `previous_response_id`, so only
+    /// the new input items are sent.
+    pub fn with_responses_api(mut self) -> Self {
+        self.responses_chain = Some(Arc::new(ResponseChain::default()));
+        self
+    }
+
     /// Serialize a request body to JSON, applying the optional body_transform
     /// hook. Returns a `serde_json::Value` ready to pass to `.json(&value)`.
     fn encode_body<T: Serialize>(&self, body: &T) -> Result<serde_json::Value> {
@@ -1351,6 +1374,10 @@ impl OpenAIProvider {
     /// Convert our generic request to OpenAI-specific format
     pub(crate) fn to_openai_request(&self, request: LLMRequest) -> OpenAIRequest {
         let mut messages = Vec::new();
+        let response_format = request
+            .response_format
+            .as_ref()
+            .map(super::structured::openai_response_format);
 
         // Debug: log system brain
         if let Some(ref system) = request.system {
@@ -1419,6 +1446,7 @@ impl OpenAIProvider {
                             thinking_parts.push(thinking);
                         }
                     }
+                    ContentBlock::RedactedThinking { .. } => {}
                     ContentBlock::Image { source } => {
                         let url = match source {
                             ImageSource::Base64 { media_type, data } => {
@@ -1555,10 +1583,12 @@ impl OpenAIProvider {
 
         // Set tool_choice to "auto" when tools are present so the model
         // knows it is allowed to call them (MiniMax requires this explicitly).
-        let tool_choice = tools
-            .as_ref()
-            .filter(|t| !t.is_empty())
-            .map(|_| serde_json::json!("auto"));
+        let tool_choice = tools.as_ref().filter(|t| !t.is_empty()).map(|_| {
+            request
+                .tool_choice
+                .as_ref()
+                .map_or_else(|| serde_json::json!("auto"), openai_tool_choice)
+        });
 
         // Enable reasoning/thinking for OpenRouter and compatible endpoints.
         // Detection is intentionally broad — models that don't support the field ignore it.
@@ -1583,6 +1613,7 @@ impl OpenAIProvider {
             tools,
             tool_choice,
             include_reasoning,
+            response_format,
         }
     }
 
@@ -1633,7 +1664,9 @@ impl OpenAIProvider {
                             tool_use_id,
                             content,
                         }),
-                        ContentBlock::Thinking { .. } | ContentBlock::Image { .. } => None,
+                        ContentBlock::Thinking { .. }
+                        | ContentBlock::RedactedThinking { .. }
+                        | ContentBlock::Image { .. } => None,
                     })
                     .collect();
 
@@ -2140,6 +2173,189 @@ impl OpenAIProvider {
         self.parse_openai_stream(response, 0)
     }
 
+    /// POST a Responses API body, retrying transient failures. When the
+    /// server has forgotten the chained `previous_response_id`, the chain is
+    /// dropped and the full history is resent once.
+    /// API root for the Files and Batches endpoints: the configured
+    /// chat-completions URL without its `/chat/completions` suffix.
+    fn api_root(&self) -> String {
+        let url = self.send_url();
+        let base = url.trim_end_matches('/');
+        base.strip_suffix("/chat/completions")
+            .unwrap_or(base)
+            .to_string()
+    }
+
+    /// Send a Files/Batches API request, mapping error responses. Not
+    /// retried: a duplicate submit would create (and bill) a second batch,
+    /// and a failed poll is simply repeated on the next tick.
+    async fn send_batch_request(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(self.handle_error(response).await);
+        }
+        Ok(response)
+    }
+
+    async fn retrieve_batch(&self, batch_id: &str) -> Result<OpenAIBatch> {
+        let url = format!("{}/batches/{}", self.api_root(), batch_id);
+        let response = self
+            .send_batch_request(self.client.get(url).headers(self.headers()?))
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    /// One line of a Batch API input file. Batched requests never stream.
+    fn to_batch_line(&self, request: BatchRequest) -> Result<serde_json::Value> {
+        let mut body = self.to_openai_request(request.request);
+        body.stream = None;
+        body.stream_options = None;
+        Ok(serde_json::json!({
+            "custom_id": request.custom_id,
+            "method": "POST",
+            "url": "/v1/chat/completions",
+            "body": self.encode_body(&body)?,
+        }))
+    }
+
+    /// Map one line of a batch output or error file to a [`BatchResult`].
+    fn batch_result(&self, line: OpenAIBatchLine) -> BatchResult {
+        match line.response {
+            Some(response) if response.status_code == 200 => {
+                match serde_json::from_value::<OpenAIResponse>(response.body) {
+                    Ok(parsed) => {
+                        BatchResult::succeeded(line.custom_id, self.from_openai_response(parsed))
+                    }
+                    Err(e) => BatchResult::errored(
+                        line.custom_id,
+                        format!("unparseable batch response: {}", e),
+                    ),
+                }
+            }
+            Some(response) => {
+                let message = response
+                    .body
+                    .pointer("/error/message")
+                    .and_then(|m| m.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("HTTP {}", response.status_code));
+                BatchResult::errored(line.custom_id, message)
+            }
+            None => {
+                let message = line
+                    .error
+                    .and_then(|e| e.message)
+                    .unwrap_or_else(|| "request errored".to_string());
+                BatchResult::errored(line.custom_id, message)
+            }
+        }
+    }
+
+    async fn send_responses_request(
+        &self,
+        request: &LLMRequest,
+        chain: &ResponseChain,
+        stream: bool,
+    ) -> Result<reqwest::Response> {
+        use super::retry::retry_with_backoff;
+
+        // Proactive pacing
+        if let Some(ref limiter) = self.rate_limiter {
+            let waited = limiter.wait().await;
+            if !waited.is_zero() {
+                tracing::debug!("Rate limiter: waited {:?} before request", waited);
+            }
+        }
+
+        let url = responses_url(&self.send_url());
+        let retry_config = self.retry_config(&request.model);
+        let mut resume = request
+            .session_id
+            .and_then(|session| chain.resume(session, &request.model, &request.messages));
+
+        loop {
+            let body = build_request_body(request, resume.as_ref(), stream);
+            tracing::info!(
+                "{} Responses API request: model={}, input_items={}, previous_response_id={:?}",
+                self.name,
+                request.model,
+                body["input"].as_array().map(|a| a.len()).unwrap_or(0),
+                resume.as_ref().map(|(id, _)| id),
+            );
+
+            let result = retry_with_backoff(
+                || async {
+                    let response = self
+                        .client
+                        .post(&url)
+                        .headers(self.headers()?)
+                        .json(&body)
+                        .send()
+                        .await?;
+                    if !response.status().is_success() {
+                        return Err(self.handle_error(response).await);
+                    }
+                    Ok(response)
+                },
+                &retry_config,
+            )
+            .await;
+
+            match result {
+                Err(ref e) if resume.is_some() && is_stale_chain_error(e) => {
+                    tracing::warn!("Stored response expired — resending full history");
+                    if let Some(session) = request.session_id {
+                        chain.forget(session);
+                    }
+                    resume = None;
+                }
+                other => return other,
+            }
+        }
+    }
+
+    /// Execute a request against the Responses API.
+    async fn complete_with_responses_api(
+        &self,
+        request: LLMRequest,
+        chain: &Arc<ResponseChain>,
+    ) -> Result<LLMResponse> {
+        let response = self.send_responses_request(&request, chain, false).await?;
+        let response: ResponseObject = response.json().await?;
+        if let Some(link) = PendingLink::new(chain, &request) {
+            link.commit(&response.id);
+        }
+        let llm_response = parse_response(response);
+        tracing::info!(
+            "Responses API response: input_tokens={}, output_tokens={}, stop_reason={:?}",
+            llm_response.usage.input_tokens,
+            llm_response.usage.output_tokens,
+            llm_response.stop_reason
+        );
+        Ok(llm_response)
+    }
+
+    /// Execute a streaming request against the Responses API.
+    async fn stream_with_responses_api(
+        &self,
+        request: LLMRequest,
+        chain: &Arc<ResponseChain>,
+    ) -> Result<ProviderStream> {
+        let response = self.send_responses_request(&request, chain, true).await?;
+        let mut translator = ResponsesStream::new(PendingLink::new(chain, &request));
+        let event_stream = response
+            .bytes_stream()
+            .map(move |chunk| match chunk {
+                Ok(bytes) => translator.feed(&bytes),
+                Err(e) => vec![Err(ProviderError::StreamError(e.to_string()))],
+            })
+            .flat_map(futures::stream::iter);
+        Ok(Box::pin(event_stream))
+    }
+
     /// Parse an OpenAI-compatible SSE stream into a ProviderStream.
     /// `total_input_tokens` is used as fallback usage on stream end if no real usage arrives.
     fn parse_openai_stream(
@@ -2365,6 +2581,10 @@ impl Provider for OpenAIProvider {
     async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
         use super::retry::retry_with_backoff;
 
+        if let Some(ref chain) = self.responses_chain {
+            return self.complete_with_responses_api(request, chain).await;
+        }
+
         let model = request.model.clone();
         let message_count = request.messages.len();
         let retry_config = self.retry_config(&model);
@@ -2524,6 +2744,10 @@ impl Provider for OpenAIProvider {
     async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
         use super::retry::retry_with_backoff;
 
+        if let Some(ref chain) = self.responses_chain {
+            return self.stream_with_responses_api(request, chain).await;
+        }
+
         let model = request.model.clone();
         let message_count = request.messages.len();
 
@@ -3572,6 +3796,97 @@ impl Provider for OpenAIProvider {
         self.vision_model.is_some()
     }
 
+    fn supports_response_format(&self) -> bool {
+        true
+    }
+
+    fn supports_batch(&self) -> bool {
+        // Compatible servers rarely implement /batches; the methods below
+        // still work against any that do.
+        self.base_url.contains("api.openai.com")
+    }
+
+    async fn submit_batch(&self, requests: Vec<BatchRequest>) -> Result<String> {
+        let root = self.api_root();
+        let count = requests.len();
+        let mut jsonl = String::new();
+        for request in requests {
+            jsonl.push_str(&serde_json::to_string(&self.to_batch_line(request)?)?);
+            jsonl.push('\n');
+        }
+
+        // Upload the input file — multipart, so drop the JSON content type
+        let mut upload_headers = self.headers()?;
+        upload_headers.remove(reqwest::header::CONTENT_TYPE);
+        let form = reqwest::multipart::Form::new()
+            .text("purpose", "batch")
+            .part(
+                "file",
+                reqwest::multipart::Part::bytes(jsonl.into_bytes()).file_name("batch.jsonl"),
+            );
+        let file: OpenAIFile = self
+            .send_batch_request(
+                self.client
+                    .post(format!("{}/files", root))
+                    .headers(upload_headers)
+                    .multipart(form),
+            )
+            .await?
+            .json()
+            .await?;
+
+        let batch: OpenAIBatch = self
+            .send_batch_request(
+                self.client
+                    .post(format!("{}/batches", root))
+                    .headers(self.headers()?)
+                    .json(&serde_json::json!({
+                        "input_file_id": file.id,
+                        "endpoint": "/v1/chat/completions",
+                        "completion_window": "24h",
+                    })),
+            )
+            .await?
+            .json()
+            .await?;
+        tracing::info!(
+            "{} batch submitted: id={}, requests={}",
+            self.name,
+            batch.id,
+            count
+        );
+        Ok(batch.id)
+    }
+
+    async fn poll_batch(&self, batch_id: &str) -> Result<BatchStatus> {
+        Ok(self.retrieve_batch(batch_id).await?.status())
+    }
+
+    async fn fetch_batch_results(&self, batch_id: &str) -> Result<Vec<BatchResult>> {
+        let batch = self.retrieve_batch(batch_id).await?;
+        let root = self.api_root();
+        let mut results = Vec::new();
+        // Successes land in the output file, per-request failures in the
+        // error file; either may be absent.
+        for file_id in [batch.output_file_id, batch.error_file_id]
+            .into_iter()
+            .flatten()
+        {
+            let url = format!("{}/files/{}/content", root, file_id);
+            let body = self
+                .send_batch_request(self.client.get(url).headers(self.headers()?))
+                .await?
+                .text()
+                .await?;
+            results.extend(
+                parse_jsonl::<OpenAIBatchLine>(&body)
+                    .into_iter()
+                    .map(|line| self.batch_result(line)),
+            );
+        }
+        Ok(results)
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -3797,6 +4112,9 @@ pub(crate) struct OpenAIRequest {
     /// OpenRouter: request reasoning/thinking tokens in the response.
     #[serde(skip_serializing_if = "Option::is_none")]
     include_reasoning: Option<bool>,
+    /// Structured output: `json_object` or `json_schema`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 impl OpenAIRequest {
@@ -3809,6 +4127,107 @@ impl OpenAIRequest {
     }
 }
 
+/// Files API upload response
+#[derive(Debug, Deserialize)]
+struct OpenAIFile {
+    id: String,
+}
+
+/// Batch object (create / retrieve response)
+#[derive(Debug, Deserialize)]
+struct OpenAIBatch {
+    id: String,
+    /// validating, failed, in_progress, finalizing, completed, expired,
+    /// cancelling or cancelled
+    status: String,
+    #[serde(default)]
+    output_file_id: Option<String>,
+    #[serde(default)]
+    error_file_id: Option<String>,
+    #[serde(default)]
+    request_counts: OpenAIBatchCounts,
+    #[serde(default)]
+    errors: Option<OpenAIBatchErrors>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OpenAIBatchCounts {
+    total: u32,
+    completed: u32,
+    failed: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIBatchErrors {
+    #[serde(default)]
+    data: Vec<OpenAIBatchErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIBatchErrorDetail {
+    #[serde(default)]
+    message: Option<String>,
+}
+
+impl OpenAIBatch {
+    fn status(&self) -> BatchStatus {
+        // Expired and cancelled batches still return whatever finished
+        let has_results = self.output_file_id.is_some() || self.error_file_id.is_some();
+        let state = match self.status.as_str() {
+            "completed" => BatchState::Ended,
+            "expired" | "cancelled" if has_results => BatchState::Ended,
+            "failed" | "expired" | "cancelled" => BatchState::Failed,
+            _ => BatchState::InProgress,
+        };
+        let error = (state == BatchState::Failed).then(|| {
+            self.errors
+                .as_ref()
+                .and_then(|e| e.data.iter().find_map(|d| d.message.clone()))
+                .unwrap_or_else(|| format!("batch {}", self.status))
+        });
+        let counts = &self.request_counts;
+        BatchStatus {
+            state,
+            succeeded: counts.completed,
+            errored: counts.failed,
+            pending: counts
+                .total
+                .saturating_sub(counts.completed + counts.failed),
+            error,
+        }
+    }
+}
+
+/// One line of a batch output or error file
+#[derive(Debug, Deserialize)]
+struct OpenAIBatchLine {
+    custom_id: String,
+    #[serde(default)]
+    response: Option<OpenAIBatchResponse>,
+    #[serde(default)]
+    error: Option<OpenAIBatchErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIBatchResponse {
+    status_code: u16,
+    #[serde(default)]
+    body: serde_json::Value,
+}
+
+/// Chat-completions `tool_choice` value for a [`ToolChoice`].
+pub(crate) fn openai_tool_choice(choice: &ToolChoice) -> serde_json::Value {
+    match choice {
+        ToolChoice::Auto => serde_json::json!("auto"),
+        ToolChoice::Required => serde_json::json!("required"),
+        ToolChoice::None => serde_json::json!("none"),
+        ToolChoice::Tool(name) => {
+            serde_json::json!({"type": "function", "function": {"name": name}})
+        }
+    }
+}
+
 /// Returns true if the error message indicates a max_tokens / max_completion_tokens mismatch.
 pub(crate) fn is_token_field_mismatch(msg: &str) -> bool {
     let m = msg.to_lowercase();
@@ -52,6 +52,10 @@ pub enum ProviderError {
     #[error("Tools not supported by this provider")]
     ToolsNotSupported,
 
+    /// Batch API not supported
+    #[error("Batch requests not supported by this provider")]
+    BatchNotSupported,
+
     /// JSON parsing error
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
@@ -60,6 +64,14 @@ pub enum ProviderError {
     #[error("Streaming error: {0}")]
     StreamError(String),
 
+    /// Reply never matched the requested `response_format`
+    #[error("Structured output invalid: {0}")]
+    StructuredOutput(String),
+
+    /// A configured spending budget (`[budget]`) is spent
+    #[error("Spending budget exceeded — {0}")]
+    BudgetExceeded(String),
+
     /// Timeout
     #[error("Request timed out after {0}s")]
     Timeout(u64),
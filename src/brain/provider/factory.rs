@@ -6,10 +6,14 @@ use super::qwen::{qwen_body_transform, qwen_extra_headers};
 use super::{
     Provider,
     anthropic::AnthropicProvider,
+    bedrock::{BedrockAuth, BedrockProvider},
     claude_cli::ClaudeCliProvider,
     custom_openai_compatible::{BodyTransformFn, OpenAIProvider},
     gemini::GeminiProvider,
+    local::LocalProvider,
+    ollama::OllamaProvider,
     opencode_cli::OpenCodeCliProvider,
+    vertex::{ServiceAccountKey, VertexProvider, VertexTokenManager},
 };
 use crate::config::{Config, ProviderConfig};
 use anyhow::Result;
@@ -107,6 +111,10 @@ async fn try_create_by_name(config: &Config, name: &str) -> Result<Option<Arc<dy
         "OpenRouter" => try_create_openrouter(config),
         "Minimax" => try_create_minimax(config),
         "z.ai GLM" => try_create_zhipu(config),
+        "AWS Bedrock" => try_create_bedrock(config),
+        "Vertex AI" => try_create_vertex(config),
+        "Local GGUF" => try_create_local(config),
+        "Ollama" => try_create_ollama(config),
         "Custom" => try_create_custom(config),
         _ => Ok(None),
     }
@@ -124,6 +132,10 @@ const PROVIDER_NAMES: &[&str] = &[
     "OpenRouter",
     "Minimax",
     "z.ai GLM",
+    "AWS Bedrock",
+    "Vertex AI",
+    "Local GGUF",
+    "Ollama",
     "Custom",
 ];
 
@@ -156,11 +168,83 @@ fn provider_enabled(config: &Config, idx: usize) -> bool {
             .is_some_and(|p| p.enabled),
         8 => config.providers.minimax.as_ref().is_some_and(|p| p.enabled),
         9 => config.providers.zhipu.as_ref().is_some_and(|p| p.enabled),
-        10 => config.providers.active_custom().is_some(),
+        10 => config.providers.bedrock.as_ref().is_some_and(|p| p.enabled),
+        11 => config.providers.vertex.as_ref().is_some_and(|p| p.enabled),
+        12 => config.providers.local.as_ref().is_some_and(|p| p.enabled),
+        13 => config.providers.ollama.as_ref().is_some_and(|p| p.enabled),
+        14 => config.providers.active_custom().is_some(),
         _ => false,
     }
 }
 
+/// Collect `rate_limit` sections as `(provider, model, budget)` entries,
+/// keyed by the name each provider reports at runtime (`Provider::name`).
+pub(crate) fn rate_budgets(
+    config: &Config,
+) -> Vec<(String, Option<String>, super::rate_limiter::RateBudget)> {
+    use super::rate_limiter::RateBudget;
+
+    let p = &config.providers;
+    let mut named: Vec<(String, &ProviderConfig)> = [
+        ("claude-cli", p.claude_cli.as_ref()),
+        ("opencode", p.opencode_cli.as_ref()),
+        ("qwen", p.qwen.as_ref()),
+        ("anthropic", p.anthropic.as_ref()),
+        ("openai", p.openai.as_ref()),
+        ("GitHub Copilot", p.github.as_ref()),
+        ("gemini", p.gemini.as_ref()),
+        ("openrouter", p.openrouter.as_ref()),
+        ("minimax", p.minimax.as_ref()),
+        ("zhipu", p.zhipu.as_ref()),
+        ("bedrock", p.bedrock.as_ref()),
+        ("vertex", p.vertex.as_ref()),
+        ("local", p.local.as_ref()),
+        ("ollama", p.ollama.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(name, cfg)| cfg.map(|c| (name.to_string(), c)))
+    .collect();
+    if let Some(custom) = p.custom.as_ref() {
+        named.extend(custom.iter().map(|(name, cfg)| (name.clone(), cfg)));
+    }
+
+    let mut entries = Vec::new();
+    for (name, cfg) in named {
+        let Some(limit) = cfg.rate_limit.as_ref() else {
+            continue;
+        };
+        entries.push((
+            name.clone(),
+            None,
+            RateBudget {
+                rpm: limit.rpm,
+                tpm: limit.tpm,
+            },
+        ));
+        for (model, m) in &limit.models {
+            entries.push((
+                name.clone(),
+                Some(model.clone()),
+                RateBudget {
+                    rpm: m.rpm,
+                    tpm: m.tpm,
+                },
+            ));
+        }
+    }
+    entries
+}
+
+/// Load the configured rate budgets, spending budgets, tokenizer settings
+/// and permission rules into their process-wide registries. Called
+/// whenever providers are (re)built so config reloads take effect.
+fn configure_process_limits(config: &Config) {
+    super::rate_limiter::RATE_BUDGETS.configure(rate_budgets(config));
+    crate::usage::budget::configure(&config.budget);
+    crate::brain::tokenizer::configure(&config.tokenizer);
+    crate::utils::permissions::configure(&config.permissions);
+}
+
 /// Create a provider based on config.toml
 /// No hardcoded priority - providers are enabled/disabled in config
 pub async fn create_provider(config: &Config) -> Result<Arc<dyn Provider>> {
@@ -176,6 +260,7 @@ pub async fn create_provider(config: &Config) -> Result<Arc<dyn Provider>> {
 pub async fn create_provider_with_warning(
     config: &Config,
 ) -> Result<(Arc<dyn Provider>, Option<String>)> {
+    configure_process_limits(config);
     let mut primary: Option<Arc<dyn Provider>> = None;
     let mut failed_name: Option<&str> = None;
     let mut warning: Option<String> = None;
@@ -219,6 +304,12 @@ pub async fn create_provider_with_warning(
     }
 
     // Build fallback chain if configured (user-defined in config.toml)
+    let breaker = config
+        .providers
+        .fallback
+        .as_ref()
+        .map(circuit_breaker)
+        .unwrap_or_default();
     let fallback_providers = if let Some(fallback) = &config.providers.fallback
         && fallback.enabled
     {
@@ -243,23 +334,24 @@ pub async fn create_provider_with_warning(
     match primary {
         Some(provider) => {
             if fallback_providers.is_empty() {
-                Ok((provider, warning))
+                Ok((with_response_cache(config, provider), warning))
             } else {
                 tracing::info!(
                     "Wrapping primary provider with {} fallback(s)",
                     fallback_providers.len()
                 );
-                Ok((
-                    Arc::new(super::FallbackProvider::new(provider, fallback_providers)),
-                    warning,
-                ))
+                let chain: Arc<dyn Provider> = Arc::new(
+                    super::FallbackProvider::new(provider, fallback_providers)
+                        .with_circuit_breaker(breaker),
+                );
+                Ok((with_response_cache(config, chain), warning))
             }
         }
         None => {
             // No primary — try fallbacks as primary candidates
             if let Some(first) = fallback_providers.into_iter().next() {
                 tracing::warn!("No primary provider enabled, using first fallback");
-                Ok((first, warning))
+                Ok((with_response_cache(config, first), warning))
             } else {
                 tracing::info!("No provider configured, using placeholder provider");
                 Ok((Arc::new(super::PlaceholderProvider), warning))
@@ -268,10 +360,31 @@ pub async fn create_provider_with_warning(
     }
 }
 
+/// Wrap `provider` in the response cache when `[response_cache]` is enabled
+/// and the database is open. Requests still opt in individually via
+/// `LLMRequest::cache_ttl`.
+fn with_response_cache(config: &Config, provider: Arc<dyn Provider>) -> Arc<dyn Provider> {
+    if !config.response_cache.enabled {
+        return provider;
+    }
+    let Some(pool) = crate::db::global_pool() else {
+        tracing::debug!("Response cache enabled but no database is open — not caching");
+        return provider;
+    };
+    let cache = super::ResponseCache::new(pool.clone(), (&config.response_cache).into());
+    Arc::new(super::CachingProvider::new(provider, Arc::new(cache)))
+}
+
 /// Create a provider by name, ignoring the `enabled` flag.
 /// Used for per-session provider restoration without toggling disk config.
 /// Accepts names like "anthropic", "openai", "minimax", "openrouter", or "custom:<name>".
 pub async fn create_provider_by_name(config: &Config, name: &str) -> Result<Arc<dyn Provider>> {
+    configure_process_limits(config);
+    let provider = build_provider_by_name(config, name).await?;
+    Ok(with_response_cache(config, provider))
+}
+
+async fn build_provider_by_name(config: &Config, name: &str) -> Result<Arc<dyn Provider>> {
     // Custom entries take precedence over built-in names. If the user
     // created a custom provider literally named "opencode" / "anthropic"
     // / anything that collides with a built-in id, the custom entry wins.
@@ -341,6 +454,15 @@ pub async fn create_provider_by_name(config: &Config, name: &str) -> Result<Arc<
             .ok_or_else(|| anyhow::anyhow!("GitHub not configured (missing token)")),
         "gemini" => try_create_gemini(config)?
             .ok_or_else(|| anyhow::anyhow!("Gemini not configured (missing API key)")),
+        "bedrock" | "aws-bedrock" => try_create_bedrock(config)?
+            .ok_or_else(|| anyhow::anyhow!("AWS Bedrock not configured (missing credentials)")),
+        "vertex" | "vertexai" => try_create_vertex(config)?.ok_or_else(|| {
+            anyhow::anyhow!("Vertex AI not configured (missing service account key or project)")
+        }),
+        "local" => try_create_local(config)?
+            .ok_or_else(|| anyhow::anyhow!("Local GGUF not configured (missing model_path)")),
+        "ollama" => try_create_ollama(config)?
+            .ok_or_else(|| anyhow::anyhow!("Ollama not configured (run /onboard:provider)")),
         "qwen" => try_create_qwen(config)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Qwen not configured (run /onboard:provider)")),
@@ -357,6 +479,14 @@ pub async fn create_provider_by_name(config: &Config, name: &str) -> Result<Arc<
 
 /// Try to create a specific named custom provider (ignores enabled flag).
 fn try_create_custom_by_name(config: &Config, name: &str) -> Result<Option<Arc<dyn Provider>>> {
+    with_key_pool(
+        config,
+        |c| c.providers.custom.as_mut()?.get_mut(name),
+        |c| build_custom_by_name(c, name),
+    )
+}
+
+fn build_custom_by_name(config: &Config, name: &str) -> Result<Option<Arc<dyn Provider>>> {
     let customs = match &config.providers.custom {
         Some(map) => map,
         None => return Ok(None),
@@ -441,6 +571,18 @@ pub(crate) fn fallback_chain(fallback: &crate::config::FallbackProviderConfig) -
     chain
 }
 
+/// Map `[providers.fallback]` recovery settings onto the chain's circuit breaker.
+pub(crate) fn circuit_breaker(
+    fallback: &crate::config::FallbackProviderConfig,
+) -> super::CircuitBreaker {
+    super::CircuitBreaker {
+        max_failures: fallback.max_consecutive_failures.max(1),
+        cooldown: (fallback.cooldown_secs > 0)
+            .then(|| std::time::Duration::from_secs(fallback.cooldown_secs)),
+        probe_interval: std::time::Duration::from_secs(fallback.probe_interval_secs),
+    }
+}
+
 /// Create fallback provider
 async fn create_fallback(config: &Config, fallback_type: &str) -> Result<Arc<dyn Provider>> {
     match fallback_type {
@@ -483,6 +625,22 @@ async fn create_fallback(config: &Config, fallback_type: &str) -> Result<Arc<dyn
             tracing::info!("Using fallback: Gemini");
             try_create_gemini(config)?.ok_or_else(|| anyhow::anyhow!("Gemini not configured"))
         }
+        "bedrock" | "aws-bedrock" => {
+            tracing::info!("Using fallback: AWS Bedrock");
+            try_create_bedrock(config)?.ok_or_else(|| anyhow::anyhow!("AWS Bedrock not configured"))
+        }
+        "vertex" | "vertexai" => {
+            tracing::info!("Using fallback: Vertex AI");
+            try_create_vertex(config)?.ok_or_else(|| anyhow::anyhow!("Vertex AI not configured"))
+        }
+        "local" => {
+            tracing::info!("Using fallback: Local GGUF");
+            try_create_local(config)?.ok_or_else(|| anyhow::anyhow!("Local GGUF not configured"))
+        }
+        "ollama" => {
+            tracing::info!("Using fallback: Ollama");
+            try_create_ollama(config)?.ok_or_else(|| anyhow::anyhow!("Ollama not configured"))
+        }
         "qwen" => {
             tracing::info!("Using fallback: Qwen native");
             try_create_qwen(config)
@@ -567,6 +725,10 @@ const QWEN_DEFAULT_DASHSCOPE_URL: &str =
 /// key-based provider (zhipu, openai, …): `api_key` + `base_url` +
 /// `default_model` from `[providers.qwen]`.
 async fn try_create_qwen(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    with_key_pool(config, |c| c.providers.qwen.as_mut(), build_qwen)
+}
+
+fn build_qwen(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     let qwen_config = match &config.providers.qwen {
         Some(cfg) => cfg,
         None => return Ok(None),
@@ -616,6 +778,14 @@ async fn try_create_qwen(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
 
 /// Try to create OpenRouter provider if configured
 fn try_create_openrouter(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    with_key_pool(
+        config,
+        |c| c.providers.openrouter.as_mut(),
+        build_openrouter,
+    )
+}
+
+fn build_openrouter(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     let openrouter_config = match &config.providers.openrouter {
         Some(cfg) => cfg,
         None => return Ok(None),
@@ -672,6 +842,10 @@ fn try_create_openrouter(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
 
 /// Try to create Minimax provider if configured
 fn try_create_minimax(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    with_key_pool(config, |c| c.providers.minimax.as_mut(), build_minimax)
+}
+
+fn build_minimax(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     let minimax_config = match &config.providers.minimax {
         Some(cfg) => {
             tracing::debug!(
@@ -721,6 +895,10 @@ fn try_create_minimax(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
 /// Try to create z.ai GLM provider if configured
 /// Supports two endpoint types: "api" (general) or "coding" (coding-specific)
 fn try_create_zhipu(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    with_key_pool(config, |c| c.providers.zhipu.as_mut(), build_zhipu)
+}
+
+fn build_zhipu(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     let zhipu_config = match &config.providers.zhipu {
         Some(cfg) => cfg,
         None => return Ok(None),
@@ -754,6 +932,17 @@ fn try_create_zhipu(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
 /// Try to create Custom OpenAI-compatible provider if configured.
 /// Picks the first enabled named custom provider from the map.
 fn try_create_custom(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    let Some(name) = config.providers.active_custom().map(|(n, _)| n.to_string()) else {
+        return build_custom(config);
+    };
+    with_key_pool(
+        config,
+        |c| c.providers.custom.as_mut()?.get_mut(&name),
+        build_custom,
+    )
+}
+
+fn build_custom(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     let (name, custom_config) = match config.providers.active_custom() {
         Some((n, c)) => (n.to_string(), c.clone()),
         None => {
@@ -796,6 +985,46 @@ fn try_create_custom(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     Ok(Some(Arc::new(provider)))
 }
 
+/// Build a provider from its config section, one instance per API key when
+/// the section lists several (`api_keys` in keys.toml), rotating between
+/// them. `section` locates the provider's config so each instance can be
+/// built with its own key; `build` is the single-key constructor.
+fn with_key_pool(
+    config: &Config,
+    section: impl Fn(&mut Config) -> Option<&mut ProviderConfig>,
+    build: impl Fn(&Config) -> Result<Option<Arc<dyn Provider>>>,
+) -> Result<Option<Arc<dyn Provider>>> {
+    let (keys, rotation) = match section(&mut config.clone()) {
+        Some(cfg) => (cfg.all_api_keys(), cfg.key_rotation.unwrap_or_default()),
+        None => (Vec::new(), Default::default()),
+    };
+    if keys.len() < 2 {
+        let provider = build(config)?;
+        if let Some(p) = &provider {
+            crate::config::health::forget_key_pool(p.name());
+        }
+        return Ok(provider);
+    }
+
+    let mut providers = Vec::with_capacity(keys.len());
+    for key in keys {
+        let mut keyed = config.clone();
+        if let Some(cfg) = section(&mut keyed) {
+            cfg.api_key = Some(key);
+            cfg.api_keys.clear();
+        }
+        if let Some(p) = build(&keyed)? {
+            providers.push(p);
+        }
+    }
+    if providers.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Arc::new(super::KeyPoolProvider::new(
+        providers, rotation,
+    ))))
+}
+
 /// Configure OpenAI-compatible provider with custom model
 fn configure_openai_compatible(
     mut provider: OpenAIProvider,
@@ -817,11 +1046,19 @@ fn configure_openai_compatible(
         tracing::info!("Context window configured: {} tokens", cw);
         provider = provider.with_context_window(cw);
     }
+    if config.endpoint_type.as_deref() == Some("responses") {
+        tracing::info!("Using the OpenAI Responses API");
+        provider = provider.with_responses_api();
+    }
     provider
 }
 
 /// Try to create OpenAI provider if configured
 fn try_create_openai(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    with_key_pool(config, |c| c.providers.openai.as_mut(), build_openai)
+}
+
+fn build_openai(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     let openai_config = match &config.providers.openai {
         Some(cfg) => cfg,
         None => return Ok(None),
@@ -857,6 +1094,10 @@ fn try_create_openai(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
 
 /// Try to create Gemini provider if configured
 fn try_create_gemini(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    with_key_pool(config, |c| c.providers.gemini.as_mut(), build_gemini)
+}
+
+fn build_gemini(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     let gemini_config = match &config.providers.gemini {
         Some(cfg) => cfg,
         None => return Ok(None),
@@ -881,6 +1122,171 @@ fn try_create_gemini(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     )))
 }
 
+/// Try to create AWS Bedrock provider if configured and credentials resolve
+/// (keys.toml, the `AWS_*` environment, or `~/.aws/credentials`).
+fn try_create_bedrock(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    let bedrock_config = match &config.providers.bedrock {
+        Some(cfg) => cfg,
+        None => return Ok(None),
+    };
+
+    let Some(auth) = BedrockAuth::resolve(bedrock_config) else {
+        tracing::warn!(
+            "Bedrock enabled but no AWS credentials found — set them in keys.toml, \
+             the AWS_* environment, or ~/.aws/credentials"
+        );
+        return Ok(None);
+    };
+
+    let region = super::bedrock::resolve_region(bedrock_config.region.as_deref());
+    let mut provider =
+        BedrockProvider::new(auth, region.clone()).with_models(bedrock_config.models.clone());
+    if let Some(model) = &bedrock_config.default_model {
+        provider = provider.with_model(model.clone());
+    }
+    if let Some(base_url) = &bedrock_config.base_url {
+        provider = provider.with_base_url(base_url.clone());
+    }
+
+    tracing::info!(
+        "Using AWS Bedrock provider in {} with model: {}",
+        region,
+        provider.default_model()
+    );
+    Ok(Some(Arc::new(provider)))
+}
+
+/// Try to create Vertex AI provider if a service-account key is configured
+/// (`credentials_file` or `GOOGLE_APPLICATION_CREDENTIALS`).
+fn try_create_vertex(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    let vertex_config = match &config.providers.vertex {
+        Some(cfg) => cfg,
+        None => return Ok(None),
+    };
+
+    let Some(path) = vertex_config
+        .credentials_file
+        .clone()
+        .or_else(|| std::env::var("GOOGLE_APPLICATION_CREDENTIALS").ok())
+        .filter(|p| !p.is_empty())
+    else {
+        tracing::warn!(
+            "Vertex AI enabled but no service account key — set credentials_file \
+             or GOOGLE_APPLICATION_CREDENTIALS"
+        );
+        return Ok(None);
+    };
+    let key = match ServiceAccountKey::load(&path) {
+        Ok(key) => key,
+        Err(e) => {
+            tracing::warn!("Vertex AI service account key unusable: {}", e);
+            return Ok(None);
+        }
+    };
+
+    let Some(project_id) = vertex_config
+        .project_id
+        .clone()
+        .or_else(|| key.project_id.clone())
+    else {
+        tracing::warn!("Vertex AI enabled but no project_id configured");
+        return Ok(None);
+    };
+    let region = vertex_config
+        .region
+        .clone()
+        .unwrap_or_else(|| super::vertex::DEFAULT_REGION.to_string());
+
+    let tokens = Arc::new(VertexTokenManager::new(
+        key,
+        vertex_config.token_url.clone(),
+    ));
+    let mut provider = VertexProvider::new(tokens, project_id.clone(), region.clone())
+        .with_models(vertex_config.models.clone());
+    if let Some(model) = &vertex_config.default_model {
+        provider = provider.with_model(model.clone());
+    }
+    if let Some(base_url) = &vertex_config.base_url {
+        provider = provider.with_base_url(base_url.clone());
+    }
+    if let Some(budget) = vertex_config.thinking_budget {
+        provider = provider.with_thinking_budget(budget);
+    }
+
+    tracing::info!(
+        "Using Vertex AI provider ({} in {}) with model: {}",
+        project_id,
+        region,
+        provider.default_model()
+    );
+    Ok(Some(Arc::new(provider)))
+}
+
+/// Try to create the in-process GGUF provider if a `model_path` is set.
+/// Loads the model synchronously (cached per path for later re-creation).
+fn try_create_local(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    let local_config = match &config.providers.local {
+        Some(cfg) => cfg,
+        None => return Ok(None),
+    };
+    let Some(spec) = local_config.model_path.as_deref().filter(|p| !p.is_empty()) else {
+        tracing::warn!("Local GGUF provider enabled but no model_path configured");
+        return Ok(None);
+    };
+
+    let path = super::local::resolve_model_path(spec)?;
+    let mut provider = LocalProvider::load(&path, local_config.gpu_layers.unwrap_or(0))?;
+    if let Some(name) = &local_config.default_model {
+        provider = provider.with_model_name(name.clone());
+    }
+    if let Some(cw) = local_config.context_window {
+        provider = provider.with_context_window(cw);
+    }
+
+    tracing::info!(
+        "Using Local GGUF provider: {} (context {})",
+        path.display(),
+        provider
+            .context_window(provider.default_model())
+            .unwrap_or_default()
+    );
+    Ok(Some(Arc::new(provider)))
+}
+
+/// Try to create the native Ollama provider. No key needed; the server
+/// address defaults to `localhost:11434`.
+fn try_create_ollama(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    let ollama_config = match &config.providers.ollama {
+        Some(cfg) => cfg,
+        None => return Ok(None),
+    };
+
+    let base_url = ollama_config
+        .base_url
+        .clone()
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| super::ollama::DEFAULT_BASE_URL.to_string());
+    let mut provider =
+        OllamaProvider::new(base_url.clone()).with_models(ollama_config.models.clone());
+    if let Some(model) = &ollama_config.default_model {
+        provider = provider.with_model(model.clone());
+    }
+    if let Some(cw) = ollama_config.context_window {
+        provider = provider.with_context_window(cw);
+    }
+    if let Some(keep_alive) = &ollama_config.keep_alive {
+        provider = provider.with_keep_alive(keep_alive.clone());
+    }
+    provider.prefetch_model_info();
+
+    tracing::info!(
+        "Using Ollama at {} with model: {}",
+        base_url,
+        provider.default_model()
+    );
+    Ok(Some(Arc::new(provider)))
+}
+
 /// Try to create Claude CLI provider if configured and binary is available.
 fn try_create_claude_cli(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     let cli_config = match &config.providers.claude_cli {
@@ -927,6 +1333,10 @@ fn try_create_opencode_cli(config: &Config) -> Result<Option<Arc<dyn Provider>>>
 
 /// Try to create Anthropic provider if configured
 fn try_create_anthropic(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    with_key_pool(config, |c| c.providers.anthropic.as_mut(), build_anthropic)
+}
+
+fn build_anthropic(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     let anthropic_config = match &config.providers.anthropic {
         Some(cfg) => cfg,
         None => return Ok(None),
@@ -947,6 +1357,11 @@ fn try_create_anthropic(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
         provider = provider.with_default_model(model.clone());
     }
 
+    if let Some(budget) = anthropic_config.thinking_budget {
+        tracing::info!("Extended thinking budget configured: {} tokens", budget);
+        provider = provider.with_thinking_budget(budget);
+    }
+
     tracing::info!("Using Anthropic provider");
 
     Ok(Some(Arc::new(provider)))
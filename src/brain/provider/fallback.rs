@@ -4,16 +4,30 @@
 //! When a provider returns a rate-limit (or other retryable) error, the
 //! next provider in the chain is tried. After a successful fallback the
 //! chosen provider becomes **sticky** — subsequent calls skip the dead
-//! primary entirely until the process exits, so a single 429 doesn't
-//! cost 60s of retries on every following turn.
-
+//! primary entirely, so a single 429 doesn't cost 60s of retries on every
+//! following turn.
+//!
+//! Stickiness is governed by a [`CircuitBreaker`]: the chain only swaps
+//! away after `max_failures` consecutive primary failures, and once the
+//! cool-down has passed a call probes the primary in the background with a
+//! one-token health request while the fallback answers it, so nobody waits
+//! on a primary that is still failing. The call after a successful probe
+//! swaps back and records a [`SwapEvent`] like any other swap. Probes are
+//! spaced by `probe_interval`, measured from the primary's last failure in
+//! `config::health`.
+
+use super::batch::{BatchRequest, BatchResult, BatchStatus};
 use super::error::{ProviderError, Result};
 use super::r#trait::{Provider, ProviderStream};
-use super::types::{LLMRequest, LLMResponse};
+use super::types::{LLMRequest, LLMResponse, Message};
 use async_trait::async_trait;
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long a health probe may take before the primary counts as still down.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Description of a swap that just occurred — consumed once by the
 /// caller (typically the agent service) so it can surface a UI alert.
@@ -26,17 +40,62 @@ pub struct SwapEvent {
     pub reason: String,
 }
 
+/// When the chain swaps away from the primary and when it tries to come back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    /// Consecutive primary failures before a fallback becomes sticky.
+    /// Below the threshold the fallback answers the call but the next one
+    /// goes to the primary again.
+    pub max_failures: u32,
+    /// How long to stay on a fallback before probing the primary.
+    /// `None` keeps the fallback for the rest of the process.
+    pub cooldown: Option<Duration>,
+    /// Minimum gap between the primary's last failure and the next probe.
+    pub probe_interval: Duration,
+}
+
+impl Default for CircuitBreaker {
+    /// Swap on the first failure and never come back (the original behaviour).
+    fn default() -> Self {
+        Self {
+            max_failures: 1,
+            cooldown: None,
+            probe_interval: Duration::from_secs(60),
+        }
+    }
+}
+
 /// A provider that tries a chain of providers in order on failure.
 ///
 /// `active` indexes into the chain: 0 = primary, 1..=fallbacks.len() = the
 /// (n-1)-th fallback. After a successful swap, `active` advances and stays
-/// there for the rest of the process — there is no automatic recovery
-/// back to the original primary.
+/// there until the [`CircuitBreaker`] cool-down lets a probe of the primary
+/// through and it succeeds.
 pub struct FallbackProvider {
     primary: Arc<dyn Provider>,
     fallbacks: Vec<Arc<dyn Provider>>,
     active: AtomicUsize,
     pending_swap: Mutex<Option<SwapEvent>>,
+    breaker: CircuitBreaker,
+    /// Consecutive primary failures while the primary is active.
+    primary_failures: AtomicU32,
+    /// When the chain last swapped away from the primary.
+    tripped_at: Mutex<Option<Instant>>,
+    /// Set while a probe is in flight so concurrent calls don't all probe.
+    probing: Arc<AtomicBool>,
+    /// Set by a successful probe; the next call swaps back to the primary.
+    primary_recovered: Arc<AtomicBool>,
+}
+
+/// Holds the probe slot and hands it back when dropped, so a probe that
+/// times out, panics or is cancelled with its runtime can't block every
+/// later probe.
+struct ProbeGuard(Arc<AtomicBool>);
+
+impl Drop for ProbeGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
 }
 
 impl FallbackProvider {
@@ -46,9 +105,20 @@ impl FallbackProvider {
             fallbacks,
             active: AtomicUsize::new(0),
             pending_swap: Mutex::new(None),
+            breaker: CircuitBreaker::default(),
+            primary_failures: AtomicU32::new(0),
+            tripped_at: Mutex::new(None),
+            probing: Arc::new(AtomicBool::new(false)),
+            primary_recovered: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Set when to swap away from the primary and when to probe it again.
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
     /// Get the currently-active provider (primary or a sticky fallback).
     fn active_provider(&self) -> Arc<dyn Provider> {
         let idx = self.active.load(Ordering::Acquire);
@@ -66,6 +136,14 @@ impl FallbackProvider {
         if old_idx == new_idx {
             return;
         }
+        if let Ok(mut tripped) = self.tripped_at.lock() {
+            if new_idx == 0 {
+                *tripped = None;
+            } else if old_idx == 0 {
+                *tripped = Some(Instant::now());
+            }
+        }
+        self.primary_failures.store(0, Ordering::Release);
         let from = if old_idx == 0 {
             &self.primary
         } else {
@@ -83,19 +161,131 @@ impl FallbackProvider {
             to_model: to.default_model().to_string(),
             reason: reason.to_string(),
         };
-        tracing::warn!(
-            "Sticky fallback: '{}/{}' → '{}/{}' (reason: {})",
-            event.from_name,
-            event.from_model,
-            event.to_name,
-            event.to_model,
-            event.reason
-        );
+        if new_idx == 0 {
+            tracing::info!(
+                "Fallback recovered: '{}/{}' → '{}/{}' (reason: {})",
+                event.from_name,
+                event.from_model,
+                event.to_name,
+                event.to_model,
+                event.reason
+            );
+        } else {
+            tracing::warn!(
+                "Sticky fallback: '{}/{}' → '{}/{}' (reason: {})",
+                event.from_name,
+                event.from_model,
+                event.to_name,
+                event.to_model,
+                event.reason
+            );
+        }
         if let Ok(mut slot) = self.pending_swap.lock() {
             *slot = Some(event);
         }
     }
 
+    /// Count a failed primary call and report whether the breaker should
+    /// trip, i.e. whether a successful fallback should become sticky.
+    fn record_primary_failure(&self, err: &ProviderError) -> bool {
+        crate::config::health::record_failure(self.primary.name(), &err.to_string());
+        let failures = self.primary_failures.fetch_add(1, Ordering::AcqRel) + 1;
+        failures >= self.breaker.max_failures.max(1)
+    }
+
+    /// Run before every call: swap back if a probe found the primary
+    /// healthy, otherwise start a probe when one is due.
+    fn check_primary(&self) {
+        if self.primary_recovered.swap(false, Ordering::AcqRel) {
+            self.promote(0, "primary recovered");
+        } else if let Some(guard) = self.begin_probe() {
+            self.spawn_probe(guard);
+        }
+    }
+
+    /// Whether the primary should be probed now. Claims the probe slot when
+    /// it is; the returned guard releases it.
+    fn begin_probe(&self) -> Option<ProbeGuard> {
+        if self.active.load(Ordering::Acquire) == 0 {
+            return None;
+        }
+        let cooldown = self.breaker.cooldown?;
+        let cooled_down = self
+            .tripped_at
+            .lock()
+            .ok()
+            .and_then(|t| *t)
+            .is_none_or(|t| t.elapsed() >= cooldown);
+        if !cooled_down {
+            return None;
+        }
+        // Health records are shared with every other user of the primary
+        // (other sessions, cron jobs), so a success elsewhere since its last
+        // failure means it's worth probing right away.
+        if let Some(health) = crate::config::health::get_health(self.primary.name())
+            && let Some(last_failure) = health.last_failure
+            && health.last_success.is_none_or(|s| s < last_failure)
+        {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            if now.saturating_sub(last_failure) < self.breaker.probe_interval.as_secs() {
+                return None;
+            }
+        }
+        self.probing
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| ProbeGuard(self.probing.clone()))
+    }
+
+    /// Send the primary a one-token health request in the background. The
+    /// call that started it goes on to the active fallback without waiting.
+    fn spawn_probe(&self, guard: ProbeGuard) {
+        let primary = self.primary.clone();
+        let recovered = self.primary_recovered.clone();
+        let probe = LLMRequest::new(primary.default_model(), vec![Message::user("ping")])
+            .with_max_tokens(1);
+        tokio::spawn(async move {
+            let _guard = guard;
+            let err = match tokio::time::timeout(PROBE_TIMEOUT, primary.complete(probe)).await {
+                Ok(Ok(_)) => {
+                    crate::config::health::record_success(primary.name());
+                    recovered.store(true, Ordering::Release);
+                    return;
+                }
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => format!("no answer within {}s", PROBE_TIMEOUT.as_secs()),
+            };
+            tracing::info!(
+                "Primary '{}' still unavailable: {} — staying on fallback",
+                primary.name(),
+                err
+            );
+            crate::config::health::record_failure(primary.name(), &err);
+        });
+    }
+
+    /// A fallback at `idx` just answered. Make it sticky if the breaker
+    /// tripped; otherwise leave the primary active for the next call.
+    fn settle_on(&self, idx: usize, trip: bool, err: Option<&ProviderError>) {
+        let reason = err
+            .map(|e| format!("{}", e))
+            .unwrap_or_else(|| "unknown".into());
+        if trip {
+            self.promote(idx, &reason);
+        } else {
+            tracing::info!(
+                "Fallback '{}' answered for primary ({} of {} failures before swapping): {}",
+                self.fallbacks[idx - 1].name(),
+                self.primary_failures.load(Ordering::Acquire),
+                self.breaker.max_failures,
+                reason
+            );
+        }
+    }
+
     /// Build a request for a fallback provider, remapping the model if needed.
     fn remap_request_for_fallback(fb: &dyn Provider, request: &LLMRequest) -> LLMRequest {
         let mut fb_request = request.clone();
@@ -148,8 +338,11 @@ impl FallbackProvider {
 #[async_trait]
 impl Provider for FallbackProvider {
     async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        self.check_primary();
+
         let start_idx = self.active.load(Ordering::Acquire);
         let mut last_err: Option<ProviderError>;
+        let mut trip = start_idx > 0;
 
         // Try the currently-active provider first.
         // Always remap — after a restart the sticky index resets to 0 but
@@ -158,9 +351,17 @@ impl Provider for FallbackProvider {
         let active = self.active_provider();
         let active_request = Self::remap_request_for_fallback(active.as_ref(), &request);
         match active.complete(active_request).await {
-            Ok(resp) => return Ok(resp),
+            Ok(resp) => {
+                if start_idx == 0 {
+                    self.primary_failures.store(0, Ordering::Release);
+                }
+                return Ok(resp);
+            }
             Err(e) if !Self::should_try_next(&e) => return Err(e),
             Err(e) => {
+                if start_idx == 0 {
+                    trip = self.record_primary_failure(&e);
+                }
                 tracing::warn!(
                     "Active provider '{}' failed: {} — trying next in chain",
                     active.name(),
@@ -177,14 +378,7 @@ impl Provider for FallbackProvider {
             let fb_request = Self::remap_request_for_fallback(fb.as_ref(), &request);
             match fb.complete(fb_request).await {
                 Ok(resp) => {
-                    self.promote(
-                        offset + 1,
-                        last_err
-                            .as_ref()
-                            .map(|e| format!("{}", e))
-                            .unwrap_or_else(|| "unknown".into())
-                            .as_str(),
-                    );
+                    self.settle_on(offset + 1, trip, last_err.as_ref());
                     return Ok(resp);
                 }
                 Err(e) => {
@@ -200,17 +394,28 @@ impl Provider for FallbackProvider {
     }
 
     async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        self.check_primary();
+
         let start_idx = self.active.load(Ordering::Acquire);
         let mut last_err: Option<ProviderError>;
+        let mut trip = start_idx > 0;
 
         // Try the currently-active provider first.
         // Always remap — see complete() comment.
         let active = self.active_provider();
         let active_request = Self::remap_request_for_fallback(active.as_ref(), &request);
         match active.stream(active_request).await {
-            Ok(stream) => return Ok(stream),
+            Ok(stream) => {
+                if start_idx == 0 {
+                    self.primary_failures.store(0, Ordering::Release);
+                }
+                return Ok(stream);
+            }
             Err(e) if !Self::should_try_next(&e) => return Err(e),
             Err(e) => {
+                if start_idx == 0 {
+                    trip = self.record_primary_failure(&e);
+                }
                 tracing::warn!(
                     "Active provider '{}' stream failed: {} — trying next in chain",
                     active.name(),
@@ -226,14 +431,7 @@ impl Provider for FallbackProvider {
             let fb_request = Self::remap_request_for_fallback(fb.as_ref(), &request);
             match fb.stream(fb_request).await {
                 Ok(stream) => {
-                    self.promote(
-                        offset + 1,
-                        last_err
-                            .as_ref()
-                            .map(|e| format!("{}", e))
-                            .unwrap_or_else(|| "unknown".into())
-                            .as_str(),
-                    );
+                    self.settle_on(offset + 1, trip, last_err.as_ref());
                     return Ok(stream);
                 }
                 Err(e) => {
@@ -260,10 +458,33 @@ impl Provider for FallbackProvider {
         self.primary.supports_vision()
     }
 
+    fn supports_response_format(&self) -> bool {
+        self.primary.supports_response_format()
+    }
+
     fn cli_handles_tools(&self) -> bool {
         self.primary.cli_handles_tools()
     }
 
+    // Batches run on the primary only: the id is only meaningful to the
+    // provider that accepted it, and a deferred job has no user waiting on
+    // a fallback.
+    fn supports_batch(&self) -> bool {
+        self.primary.supports_batch()
+    }
+
+    async fn submit_batch(&self, requests: Vec<BatchRequest>) -> Result<String> {
+        self.primary.submit_batch(requests).await
+    }
+
+    async fn poll_batch(&self, batch_id: &str) -> Result<BatchStatus> {
+        self.primary.poll_batch(batch_id).await
+    }
+
+    async fn fetch_batch_results(&self, batch_id: &str) -> Result<Vec<BatchResult>> {
+        self.primary.fetch_batch_results(batch_id).await
+    }
+
     fn cli_manages_context(&self) -> bool {
         self.primary.cli_manages_context()
     }
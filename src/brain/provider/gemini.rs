@@ -9,6 +9,12 @@
 //! - Chat: `POST /models/{model}:generateContent`
 //! - Stream: `POST /models/{model}:streamGenerateContent?alt=sse`
 //!
+//! ## Vertex AI
+//! `with_vertex` sends the same requests to Gemini publisher models on
+//! Vertex AI (`{base}/publishers/google/models/{model}:generateContent`)
+//! with a bearer token instead of the API key. Context caching is skipped
+//! there — `cachedContents` are project resources on Vertex.
+//!
 //! ## Role Mapping
 //! Gemini uses `"user"` and `"model"` (not `"assistant"`)
 //!
@@ -18,12 +24,13 @@
 //! - gemini-1.5-pro
 //! - gemini-1.5-flash
 
+use super::custom_openai_compatible::TokenFn;
 use super::error::{ProviderError, Result};
 use super::r#trait::{Provider, ProviderStream};
 use super::types::*;
 use async_trait::async_trait;
 use futures::stream::StreamExt;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::time::Duration;
@@ -42,6 +49,9 @@ pub struct GeminiProvider {
     client: Client,
     model: String,
     cached_content_name: Arc<std::sync::Mutex<Option<String>>>,
+    base_url: String,
+    /// Bearer token source for Vertex AI; `None` uses `api_key`.
+    token_fn: Option<TokenFn>,
 }
 
 impl GeminiProvider {
@@ -60,6 +70,8 @@ impl GeminiProvider {
             client,
             model: "gemini-2.0-flash".to_string(),
             cached_content_name: Arc::new(std::sync::Mutex::new(None)),
+            base_url: GEMINI_BASE_URL.to_string(),
+            token_fn: None,
         }
     }
 
@@ -69,15 +81,32 @@ impl GeminiProvider {
         self
     }
 
+    /// Send requests to Gemini on Vertex AI. `base_url` is the
+    /// `.../locations/{region}/publishers/google` root; `token_fn` returns
+    /// the access token for the `Authorization` header.
+    pub fn with_vertex(mut self, base_url: String, token_fn: TokenFn) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self.token_fn = Some(token_fn);
+        self
+    }
+
+    /// Attach credentials: API key header, or bearer token on Vertex.
+    fn authorize(&self, builder: RequestBuilder) -> RequestBuilder {
+        match &self.token_fn {
+            Some(token_fn) => builder.bearer_auth(token_fn()),
+            None => builder.header("x-goog-api-key", &self.api_key),
+        }
+    }
+
     /// Build the generate content URL for a given model
     fn generate_url(&self, model: &str, stream: bool) -> String {
         if stream {
             format!(
                 "{}/models/{}:streamGenerateContent?alt=sse",
-                GEMINI_BASE_URL, model
+                self.base_url, model
             )
         } else {
-            format!("{}/models/{}:generateContent", GEMINI_BASE_URL, model)
+            format!("{}/models/{}:generateContent", self.base_url, model)
         }
     }
 
@@ -103,7 +132,7 @@ impl GeminiProvider {
                     ContentBlock::Text { text } => {
                         text_parts.push(serde_json::json!({"text": text}));
                     }
-                    ContentBlock::Thinking { .. } => {
+                    ContentBlock::Thinking { .. } | ContentBlock::RedactedThinking { .. } => {
                         // Gemini doesn't use Anthropic-style thinking blocks; skip.
                     }
                     ContentBlock::Image { source } => {
@@ -223,9 +252,24 @@ impl GeminiProvider {
             body["tools"] = serde_json::json!([{
                 "functionDeclarations": function_declarations
             }]);
-            body["toolConfig"] = serde_json::json!({
-                "functionCallingConfig": {"mode": "AUTO"}
-            });
+            let calling_config = match &request.tool_choice {
+                None | Some(ToolChoice::Auto) => serde_json::json!({"mode": "AUTO"}),
+                Some(ToolChoice::Required) => serde_json::json!({"mode": "ANY"}),
+                Some(ToolChoice::None) => serde_json::json!({"mode": "NONE"}),
+                Some(ToolChoice::Tool(name)) => {
+                    serde_json::json!({"mode": "ANY", "allowedFunctionNames": [name]})
+                }
+            };
+            body["toolConfig"] = serde_json::json!({"functionCallingConfig": calling_config});
+        }
+
+        // Structured output. `responseJsonSchema` takes standard JSON Schema,
+        // unlike the OpenAPI-subset `responseSchema`.
+        if let Some(ref format) = request.response_format {
+            body["generationConfig"]["responseMimeType"] = "application/json".into();
+            if let ResponseFormat::JsonSchema { schema, .. } = format {
+                body["generationConfig"]["responseJsonSchema"] = schema.clone();
+            }
         }
 
         body
@@ -323,6 +367,10 @@ impl GeminiProvider {
     /// Gemini prompt caching works by creating a named cachedContents resource
     /// via a separate API call, then referencing it in subsequent generateContent requests.
     async fn ensure_cached_content(&self, system: &str, tools: &[Tool]) -> Result<()> {
+        if self.token_fn.is_some() {
+            return Ok(());
+        }
+
         // Check if we already have a cached content name
         {
             let guard = self.cached_content_name.lock().unwrap();
@@ -426,10 +474,8 @@ impl Provider for GeminiProvider {
         let result = retry_with_backoff(
             || async {
                 let response = self
-                    .client
-                    .post(&url)
+                    .authorize(self.client.post(&url))
                     .header("Content-Type", "application/json")
-                    .header("x-goog-api-key", &self.api_key)
                     .json(&body)
                     .send()
                     .await?;
@@ -487,10 +533,8 @@ impl Provider for GeminiProvider {
         let response = retry_with_backoff(
             || async {
                 let response = self
-                    .client
-                    .post(&url)
+                    .authorize(self.client.post(&url))
                     .header("Content-Type", "application/json")
-                    .header("x-goog-api-key", &self.api_key)
                     .json(&body)
                     .send()
                     .await?;
@@ -684,6 +728,10 @@ impl Provider for GeminiProvider {
         true
     }
 
+    fn supports_response_format(&self) -> bool {
+        true
+    }
+
     fn name(&self) -> &str {
         "gemini"
     }
@@ -0,0 +1,326 @@
+//! API Key Rotation
+//!
+//! A provider section in keys.toml can list several keys
+//! (`api_keys = ["sk-1", "sk-2"]`). The factory builds one provider per key
+//! and wraps them in a [`KeyPoolProvider`], which picks a key for every
+//! request according to [`KeyRotation`]. A key that answers 401 or 429 is
+//! quarantined and the request is retried on the next key straight away;
+//! only when every key fails does the error reach the caller (and the
+//! fallback chain).
+//!
+//! Quarantines are recorded in `config::health`, so they survive restarts
+//! and show up in `opencrabs status`. Keys are only ever identified by
+//! position (`key 2/3`) — the secret never reaches logs or the health file.
+
+use super::batch::{BatchRequest, BatchResult, BatchStatus};
+use super::error::{ProviderError, Result};
+use super::r#trait::{Provider, ProviderStream};
+use super::types::{LLMRequest, LLMResponse};
+use crate::config::KeyRotation;
+use crate::config::health::{self, KeyHealth};
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// How long a rate-limited (429) key sits out.
+pub const RATE_LIMIT_QUARANTINE: Duration = Duration::from_secs(60);
+
+/// How long a rejected (401) key sits out. Long enough to stop burning
+/// requests on a revoked key, short enough to pick up a re-enabled one.
+pub const AUTH_QUARANTINE: Duration = Duration::from_secs(60 * 60);
+
+/// One provider per API key, rotated per request.
+pub struct KeyPoolProvider {
+    /// Name the pool is recorded under in `config::health`
+    name: String,
+    keys: Vec<Arc<dyn Provider>>,
+    rotation: KeyRotation,
+    state: Mutex<Vec<KeyHealth>>,
+    /// Round-robin position
+    cursor: AtomicUsize,
+    /// Key that served the most recent request
+    active: AtomicUsize,
+}
+
+impl KeyPoolProvider {
+    /// Wrap one provider per key, in keys.toml order. Quarantines recorded
+    /// by an earlier run are restored from `config::health`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keys` is empty.
+    pub fn new(keys: Vec<Arc<dyn Provider>>, rotation: KeyRotation) -> Self {
+        assert!(!keys.is_empty(), "KeyPoolProvider needs at least one key");
+        let name = keys[0].name().to_string();
+        let pool = health::register_key_pool(&name, keys.len());
+        tracing::info!(
+            "{}: rotating {} API keys ({:?})",
+            name,
+            keys.len(),
+            rotation
+        );
+        Self {
+            name,
+            keys,
+            rotation,
+            state: Mutex::new(pool.keys),
+            cursor: AtomicUsize::new(pool.active),
+            active: AtomicUsize::new(pool.active),
+        }
+    }
+
+    /// Number of keys in the pool
+    pub fn key_count(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Index of the key that served the most recent request
+    pub fn active_key(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, Vec<KeyHealth>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Keys to try for the next request, best first. Keys in quarantine go
+    /// last, the one released soonest first.
+    fn attempt_order(&self) -> Vec<usize> {
+        let n = self.keys.len();
+        let now = health::now_epoch();
+        let state = self.state();
+        let mut order: Vec<usize> = match self.rotation {
+            KeyRotation::RoundRobin => {
+                let start = self.cursor.fetch_add(1, Ordering::Relaxed) % n;
+                (0..n).map(|i| (start + i) % n).collect()
+            }
+            KeyRotation::LeastLimited => {
+                let mut order: Vec<usize> = (0..n).collect();
+                order.sort_by_key(|&i| state[i].last_limited.unwrap_or(0));
+                order
+            }
+        };
+        order.sort_by_key(|&i| state[i].quarantined_until.filter(|&t| t > now));
+        order
+    }
+
+    fn is_quarantined(&self, index: usize) -> bool {
+        self.state()[index]
+            .quarantine_left(health::now_epoch())
+            .is_some()
+    }
+
+    fn activate(&self, index: usize) {
+        if self.active.swap(index, Ordering::Relaxed) != index {
+            tracing::info!(
+                "{}: switched to API key {}/{}",
+                self.name,
+                index + 1,
+                self.keys.len()
+            );
+            health::record_active_key(&self.name, index);
+        }
+    }
+
+    fn quarantine(&self, index: usize, status: u16) {
+        let duration = if status == 429 {
+            RATE_LIMIT_QUARANTINE
+        } else {
+            AUTH_QUARANTINE
+        };
+        let now = health::now_epoch();
+        let until = now + duration.as_secs();
+        {
+            let mut state = self.state();
+            let key = &mut state[index];
+            key.quarantined_until = Some(until);
+            key.last_limited = Some(now);
+            key.last_status = Some(status);
+        }
+        tracing::warn!(
+            "{}: API key {}/{} got HTTP {} — quarantined for {}s",
+            self.name,
+            index + 1,
+            self.keys.len(),
+            status,
+            duration.as_secs()
+        );
+        health::record_key_quarantine(&self.name, index, status, until);
+    }
+
+    /// Run `call` on the best key, moving on to the next one while keys
+    /// come back 401/429. Quarantined keys are only tried when every key
+    /// is quarantined, and then only the one released soonest.
+    async fn with_key<T, F, Fut>(&self, call: F) -> Result<T>
+    where
+        F: Fn(Arc<dyn Provider>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut last_err = None;
+        for (attempt, index) in self.attempt_order().into_iter().enumerate() {
+            if attempt > 0 && self.is_quarantined(index) {
+                break;
+            }
+            self.activate(index);
+            tracing::debug!(
+                "{}: request on API key {}/{}",
+                self.name,
+                index + 1,
+                self.keys.len()
+            );
+            match call(Arc::clone(&self.keys[index])).await {
+                Err(err) => match key_failure(&err) {
+                    Some(status) => {
+                        self.quarantine(index, status);
+                        last_err = Some(err);
+                    }
+                    None => return Err(err),
+                },
+                ok => return ok,
+            }
+        }
+        Err(last_err.unwrap_or_else(|| ProviderError::Internal("no API key available".into())))
+    }
+
+    fn current(&self) -> &Arc<dyn Provider> {
+        &self.keys[self.active_key()]
+    }
+}
+
+/// HTTP status when `err` means this particular key is unusable for now.
+fn key_failure(err: &ProviderError) -> Option<u16> {
+    match err {
+        ProviderError::InvalidApiKey => Some(401),
+        ProviderError::RateLimitExceeded(_) => Some(429),
+        // Some proxies answer 401 for "model not allowed" — that follows
+        // the request, not the key.
+        ProviderError::ApiError { status, .. }
+            if matches!(status, 401 | 429) && !err.is_model_unsupported() =>
+        {
+            Some(*status)
+        }
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl Provider for KeyPoolProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        self.with_key(|provider| {
+            let request = request.clone();
+            async move { provider.complete(request).await }
+        })
+        .await
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        // Only errors before the first event rotate; a stream that fails
+        // midway is the caller's to retry.
+        self.with_key(|provider| {
+            let request = request.clone();
+            async move { provider.stream(request).await }
+        })
+        .await
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.keys[0].supports_streaming()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.keys[0].supports_tools()
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.keys[0].supports_vision()
+    }
+
+    fn supports_response_format(&self) -> bool {
+        self.keys[0].supports_response_format()
+    }
+
+    fn cli_handles_tools(&self) -> bool {
+        self.keys[0].cli_handles_tools()
+    }
+
+    fn supports_batch(&self) -> bool {
+        self.keys[0].supports_batch()
+    }
+
+    async fn submit_batch(&self, requests: Vec<BatchRequest>) -> Result<String> {
+        self.with_key(|provider| {
+            let requests = requests.clone();
+            async move { provider.submit_batch(requests).await }
+        })
+        .await
+    }
+
+    // Batches belong to the account, not the key, so any key can follow up.
+    async fn poll_batch(&self, batch_id: &str) -> Result<BatchStatus> {
+        self.current().poll_batch(batch_id).await
+    }
+
+    async fn fetch_batch_results(&self, batch_id: &str) -> Result<Vec<BatchResult>> {
+        self.current().fetch_batch_results(batch_id).await
+    }
+
+    fn cli_manages_context(&self) -> bool {
+        self.keys[0].cli_manages_context()
+    }
+
+    fn name(&self) -> &str {
+        self.keys[0].name()
+    }
+
+    fn base_url(&self) -> Option<&str> {
+        self.keys[0].base_url()
+    }
+
+    fn default_model(&self) -> &str {
+        self.keys[0].default_model()
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        self.keys[0].supported_models()
+    }
+
+    async fn fetch_models(&self) -> Vec<String> {
+        self.current().fetch_models().await
+    }
+
+    fn validate_model(&self, model: &str) -> bool {
+        self.keys[0].validate_model(model)
+    }
+
+    fn context_window(&self, model: &str) -> Option<u32> {
+        self.keys[0].context_window(model)
+    }
+
+    fn configured_context_window(&self) -> Option<u32> {
+        self.keys[0].configured_context_window()
+    }
+
+    fn calculate_cost(&self, model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        self.keys[0].calculate_cost(model, input_tokens, output_tokens)
+    }
+
+    fn calculate_cost_with_cache(
+        &self,
+        model: &str,
+        input_tokens: u32,
+        output_tokens: u32,
+        cache_creation_tokens: u32,
+        cache_read_tokens: u32,
+    ) -> f64 {
+        self.keys[0].calculate_cost_with_cache(
+            model,
+            input_tokens,
+            output_tokens,
+            cache_creation_tokens,
+            cache_read_tokens,
+        )
+    }
+}
@@ -0,0 +1,853 @@
+//! Local GGUF Provider — in-process llama.cpp inference
+//!
+//! Loads a GGUF chat model with `llama-cpp-2` (the same bindings the memory
+//! embedding engine uses) and runs generation on a blocking thread, so the
+//! agent works fully offline — air-gapped machines, CPU-only CI.
+//!
+//! ## Prompting
+//! The conversation is rendered through the model's built-in chat template
+//! (ChatML when the GGUF has none). Tool calls are not a native concept for
+//! most templates, so when tools are offered the system prompt lists them
+//! and a GBNF grammar constrains the reply to either plain text or exactly
+//! one `{"name": ..., "arguments": {...}}` object naming a known tool.
+//!
+//! ## Model source
+//! `model_path` is a GGUF file on disk (`~/` is expanded) or an `hf:` URI
+//! that `qmd::pull_model` downloads and caches, like the embedding model.
+
+use super::error::{ProviderError, Result};
+use super::r#trait::{Provider, ProviderStream};
+use super::types::*;
+use async_trait::async_trait;
+use futures::stream::StreamExt;
+use llama_cpp_2::LlamaCppError;
+use llama_cpp_2::context::params::LlamaContextParams;
+use llama_cpp_2::llama_backend::LlamaBackend;
+use llama_cpp_2::llama_batch::LlamaBatch;
+use llama_cpp_2::model::params::LlamaModelParams;
+use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaChatTemplate, LlamaModel, Special};
+use llama_cpp_2::sampling::LlamaSampler;
+use serde_json::json;
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, Once};
+
+/// Prompt tokens decoded per llama.cpp batch.
+const BATCH_SIZE: usize = 512;
+/// Context size when neither config nor the model metadata gives one.
+const DEFAULT_CONTEXT: u32 = 8_192;
+/// Sampling temperature when the request doesn't set one.
+const DEFAULT_TEMPERATURE: f32 = 0.7;
+
+/// Proof that llama.cpp is initialized, shared by every local model. Never
+/// dropped, so it never frees the backend out from under anyone.
+static BACKEND: LlamaBackend = LlamaBackend {};
+static BACKEND_INIT: Once = Once::new();
+
+/// Loaded models keyed by path, so re-creating the provider (model switch,
+/// fallback chain) doesn't reload gigabytes of weights.
+static MODELS: LazyLock<Mutex<HashMap<PathBuf, Arc<LocalModel>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Initialize llama.cpp once without keeping `LlamaBackend::init`'s
+/// one-per-process claim: qmd's `EmbeddingEngine::new` makes that claim
+/// itself and fails with `BackendAlreadyInitialized` if we hold it.
+fn backend() -> &'static LlamaBackend {
+    BACKEND_INIT.call_once(|| {
+        crate::memory::silence_llama_logs();
+        match LlamaBackend::init() {
+            // llama.cpp is set up; hand the claim back for the embedding engine
+            Ok(claimed) => drop(claimed),
+            // The embedding engine already set it up and holds the claim
+            Err(LlamaCppError::BackendAlreadyInitialized) => {}
+            Err(e) => tracing::warn!("llama.cpp backend init failed: {e}"),
+        }
+    });
+    &BACKEND
+}
+
+/// Resolve `model_path` to a GGUF file: `hf:` URIs are pulled through the
+/// qmd model cache, plain paths have a leading `~/` expanded.
+pub fn resolve_model_path(spec: &str) -> Result<PathBuf> {
+    if spec.starts_with("hf:") {
+        let pull = qmd::pull_model(spec, false)
+            .map_err(|e| ProviderError::Internal(format!("Failed to pull {spec}: {e}")))?;
+        return Ok(PathBuf::from(&pull.path));
+    }
+    let path = match spec.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|h| h.join(rest))
+            .unwrap_or_else(|| PathBuf::from(spec)),
+        None => PathBuf::from(spec),
+    };
+    if !path.is_file() {
+        return Err(ProviderError::ModelNotFound(path.display().to_string()));
+    }
+    Ok(path)
+}
+
+/// A loaded GGUF model plus what we read from its metadata.
+struct LocalModel {
+    model: LlamaModel,
+    template: LlamaChatTemplate,
+    n_ctx_train: u32,
+    /// One generation at a time — each holds a full KV cache.
+    generation: Mutex<()>,
+}
+
+impl LocalModel {
+    fn load(path: &Path, gpu_layers: u32) -> Result<Arc<Self>> {
+        let mut models = MODELS
+            .lock()
+            .map_err(|e| ProviderError::Internal(format!("model cache poisoned: {e}")))?;
+        if let Some(model) = models.get(path) {
+            return Ok(model.clone());
+        }
+
+        check_cpu_features()?;
+        let params = LlamaModelParams::default().with_n_gpu_layers(gpu_layers);
+        let model = LlamaModel::load_from_file(backend(), path, &params).map_err(|e| {
+            ProviderError::Internal(format!("Failed to load {}: {e}", path.display()))
+        })?;
+        let template = match model.chat_template(None) {
+            Ok(template) => template,
+            Err(_) => LlamaChatTemplate::new("chatml")
+                .map_err(|e| ProviderError::Internal(format!("chatml template: {e}")))?,
+        };
+        let loaded = Arc::new(Self {
+            n_ctx_train: model.n_ctx_train(),
+            model,
+            template,
+            generation: Mutex::new(()),
+        });
+        models.insert(path.to_path_buf(), loaded.clone());
+        Ok(loaded)
+    }
+}
+
+/// llama.cpp's x86 kernels need AVX; without it the process dies on SIGILL.
+fn check_cpu_features() -> Result<()> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if !std::arch::is_x86_feature_detected!("avx") {
+            return Err(ProviderError::Internal(
+                "CPU lacks AVX — llama.cpp GGUF inference requires AVX (Sandy Bridge 2011+)"
+                    .to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// In-process GGUF chat provider
+#[derive(Clone)]
+pub struct LocalProvider {
+    model: Arc<LocalModel>,
+    model_name: String,
+    context_window: Option<u32>,
+}
+
+impl LocalProvider {
+    /// Load (or reuse) the GGUF model at `path`. Blocking — weights are
+    /// memory-mapped and the first load of a large model takes seconds.
+    pub fn load(path: &Path, gpu_layers: u32) -> Result<Self> {
+        let model = LocalModel::load(path, gpu_layers)?;
+        let model_name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "local".to_string());
+        Ok(Self {
+            model,
+            model_name,
+            context_window: None,
+        })
+    }
+
+    /// Name reported as the model (defaults to the file stem)
+    pub fn with_model_name(mut self, name: impl Into<String>) -> Self {
+        self.model_name = name.into();
+        self
+    }
+
+    /// Context size to allocate; capped at the model's training context
+    pub fn with_context_window(mut self, size: u32) -> Self {
+        self.context_window = Some(size);
+        self
+    }
+
+    fn n_ctx(&self) -> u32 {
+        let trained = match self.model.n_ctx_train {
+            0 => DEFAULT_CONTEXT,
+            n => n,
+        };
+        self.context_window
+            .map_or(trained, |size| size.min(trained))
+    }
+
+    /// Render the request through the model's chat template.
+    fn build_prompt(&self, request: &LLMRequest) -> Result<String> {
+        let chat = chat_messages(request)
+            .into_iter()
+            .map(|(role, content)| LlamaChatMessage::new(role.to_string(), content))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| ProviderError::InvalidRequest(format!("chat message: {e}")))?;
+        self.model
+            .model
+            .apply_chat_template(&self.model.template, &chat, true)
+            .map_err(|e| ProviderError::Internal(format!("chat template failed: {e}")))
+    }
+}
+
+/// Flatten the conversation to `(role, text)` pairs for the chat template.
+/// Tool calls are shown in the same JSON shape the grammar makes the model
+/// emit, and tool results come back as user turns — few GGUF templates
+/// understand a `tool` role.
+fn chat_messages(request: &LLMRequest) -> Vec<(&'static str, String)> {
+    let tools = request.tools.as_deref().unwrap_or_default();
+    let mut system = request.system.clone().unwrap_or_default();
+    if !tools.is_empty() {
+        if !system.is_empty() {
+            system.push_str("\n\n");
+        }
+        system.push_str(&tool_instructions(tools));
+    }
+
+    let mut out = Vec::new();
+    if !system.is_empty() {
+        out.push(("system", system));
+    }
+
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
+    for msg in &request.messages {
+        let role = match msg.role {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        let mut parts = Vec::new();
+        for block in &msg.content {
+            match block {
+                ContentBlock::Text { text } => parts.push(text.clone()),
+                ContentBlock::ToolUse { id, name, input } => {
+                    tool_names.insert(id.as_str(), name.as_str());
+                    parts.push(json!({"name": name, "arguments": input}).to_string());
+                }
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    is_error,
+                } => {
+                    let name = tool_names.get(tool_use_id.as_str()).unwrap_or(&"tool");
+                    let label = if is_error.unwrap_or(false) {
+                        "error"
+                    } else {
+                        "result"
+                    };
+                    parts.push(format!("[{name} {label}]\n{content}"));
+                }
+                ContentBlock::Image { .. } => parts.push("[image omitted]".to_string()),
+                ContentBlock::Thinking { .. } | ContentBlock::RedactedThinking { .. } => {}
+            }
+        }
+        if !parts.is_empty() {
+            out.push((role, parts.join("\n\n")));
+        }
+    }
+    out
+}
+
+/// System-prompt section describing the available tools.
+fn tool_instructions(tools: &[Tool]) -> String {
+    let mut out = String::from(
+        "You can call tools. To call one, reply with ONLY a JSON object of the form \
+         {\"name\": \"<tool name>\", \"arguments\": {...}} and nothing else. \
+         Otherwise reply in plain text. Available tools:\n",
+    );
+    for tool in tools {
+        out.push_str(&format!(
+            "\n- {}: {}\n  arguments schema: {}",
+            tool.name, tool.description, tool.input_schema
+        ));
+    }
+    out
+}
+
+/// GBNF grammar: plain text that doesn't start with `{`, or one tool call
+/// whose name is one of `tools` and whose arguments are a JSON object.
+fn tool_grammar(tools: &[Tool]) -> String {
+    let names = tools
+        .iter()
+        .map(|t| {
+            // The JSON-quoted name, written as a GBNF string literal.
+            let quoted = serde_json::to_string(&t.name).unwrap_or_default();
+            format!("{:?}", quoted)
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+    format!(
+        r#"root   ::= call | answer
+answer ::= [^{{] [^\x00]*
+call   ::= "{{" ws "\"name\"" ws ":" ws name ws "," ws "\"arguments\"" ws ":" ws object ws "}}"
+name   ::= {names}
+value  ::= object | array | string | number | "true" | "false" | "null"
+object ::= "{{" ws ( string ws ":" ws value ( ws "," ws string ws ":" ws value )* )? ws "}}"
+array  ::= "[" ws ( value ( ws "," ws value )* )? ws "]"
+string ::= "\"" ( [^"\\\x00-\x1F] | "\\" ( ["\\/bfnrt] | "u" hex hex hex hex ) )* "\""
+hex    ::= [0-9a-fA-F]
+number ::= "-"? ( "0" | [1-9] [0-9]* ) ( "." [0-9]+ )? ( [eE] [-+]? [0-9]+ )?
+ws     ::= [ \t\n]*
+"#
+    )
+}
+
+/// Parse a grammar-constrained tool call back into `(name, arguments)`.
+fn parse_tool_call(text: &str) -> Option<(String, serde_json::Value)> {
+    let value: serde_json::Value = serde_json::from_str(text.trim()).ok()?;
+    let name = value["name"].as_str()?.to_string();
+    let arguments = match &value["arguments"] {
+        serde_json::Value::Object(_) => value["arguments"].clone(),
+        _ => json!({}),
+    };
+    Some((name, arguments))
+}
+
+/// Reassembles UTF-8 across token boundaries — a multi-byte character is
+/// often split over two tokens.
+#[derive(Default)]
+struct Utf8Buffer {
+    pending: Vec<u8>,
+}
+
+impl Utf8Buffer {
+    fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        match std::str::from_utf8(&self.pending) {
+            Ok(text) => {
+                let text = text.to_string();
+                self.pending.clear();
+                text
+            }
+            Err(e) if e.error_len().is_none() => {
+                // Incomplete trailing sequence — keep it for the next token.
+                let valid = e.valid_up_to();
+                let text = String::from_utf8_lossy(&self.pending[..valid]).into_owned();
+                self.pending.drain(..valid);
+                text
+            }
+            Err(_) => {
+                let text = String::from_utf8_lossy(&self.pending).into_owned();
+                self.pending.clear();
+                text
+            }
+        }
+    }
+}
+
+/// Whether the reply turned out to be text or a tool call. Decided on the
+/// first generated character, which the grammar makes unambiguous.
+#[derive(Debug, PartialEq)]
+enum ReplyMode {
+    Pending,
+    Text,
+    ToolCall,
+}
+
+/// Turns generated text into `StreamEvent`s. Text is streamed as it
+/// arrives; a tool call is buffered and emitted whole once it parses.
+struct GenerationState {
+    tools_enabled: bool,
+    mode: ReplyMode,
+    call_buffer: String,
+}
+
+impl GenerationState {
+    fn new(tools_enabled: bool) -> Self {
+        Self {
+            tools_enabled,
+            mode: ReplyMode::Pending,
+            call_buffer: String::new(),
+        }
+    }
+
+    fn push(&mut self, piece: &str) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        if piece.is_empty() {
+            return events;
+        }
+        if self.mode == ReplyMode::Pending {
+            if self.tools_enabled && piece.starts_with('{') {
+                self.mode = ReplyMode::ToolCall;
+            } else {
+                self.mode = ReplyMode::Text;
+                events.push(StreamEvent::ContentBlockStart {
+                    index: 0,
+                    content_block: ContentBlock::Text {
+                        text: String::new(),
+                    },
+                });
+            }
+        }
+        match self.mode {
+            ReplyMode::ToolCall => self.call_buffer.push_str(piece),
+            _ => events.push(StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: ContentDelta::TextDelta {
+                    text: piece.to_string(),
+                },
+            }),
+        }
+        events
+    }
+
+    /// Close the open block; returns the events and the stop reason
+    /// (`ToolUse` when a tool call parsed).
+    fn finish(self, stop_reason: StopReason) -> (Vec<StreamEvent>, StopReason) {
+        match self.mode {
+            ReplyMode::Pending => (Vec::new(), stop_reason),
+            ReplyMode::Text => (
+                vec![StreamEvent::ContentBlockStop { index: 0 }],
+                stop_reason,
+            ),
+            ReplyMode::ToolCall => match parse_tool_call(&self.call_buffer) {
+                Some((name, arguments)) => (
+                    vec![
+                        StreamEvent::ContentBlockStart {
+                            index: 0,
+                            content_block: ContentBlock::ToolUse {
+                                id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+                                name,
+                                input: json!({}),
+                            },
+                        },
+                        StreamEvent::ContentBlockDelta {
+                            index: 0,
+                            delta: ContentDelta::InputJsonDelta {
+                                partial_json: arguments.to_string(),
+                            },
+                        },
+                        StreamEvent::ContentBlockStop { index: 0 },
+                    ],
+                    StopReason::ToolUse,
+                ),
+                // Truncated by max_tokens — surface what we have as text.
+                None => (
+                    vec![
+                        StreamEvent::ContentBlockStart {
+                            index: 0,
+                            content_block: ContentBlock::Text {
+                                text: String::new(),
+                            },
+                        },
+                        StreamEvent::ContentBlockDelta {
+                            index: 0,
+                            delta: ContentDelta::TextDelta {
+                                text: self.call_buffer,
+                            },
+                        },
+                        StreamEvent::ContentBlockStop { index: 0 },
+                    ],
+                    stop_reason,
+                ),
+            },
+        }
+    }
+}
+
+/// Everything the blocking generation thread needs.
+struct GenerationJob {
+    model: Arc<LocalModel>,
+    model_name: String,
+    n_ctx: u32,
+    prompt: String,
+    grammar: Option<String>,
+    temperature: f32,
+    max_tokens: Option<u32>,
+}
+
+type EventSender = tokio::sync::mpsc::Sender<Result<StreamEvent>>;
+
+impl GenerationJob {
+    /// Run generation, sending events as tokens are produced. Returns early
+    /// (without error) if the receiver is dropped.
+    fn run(self, tx: &EventSender) -> Result<()> {
+        let local = &self.model;
+        let _turn = local
+            .generation
+            .lock()
+            .map_err(|e| ProviderError::Internal(format!("generation lock poisoned: {e}")))?;
+        let model = &local.model;
+
+        let tokens = model
+            .str_to_token(&self.prompt, AddBos::Always)
+            .map_err(|e| ProviderError::Internal(format!("tokenize failed: {e}")))?;
+        let prompt_len = tokens.len() as u32;
+        if prompt_len >= self.n_ctx {
+            return Err(ProviderError::ContextLengthExceeded(prompt_len));
+        }
+        let room = self.n_ctx - prompt_len;
+        let max_tokens = self.max_tokens.map_or(room, |m| m.min(room));
+
+        let send = |event: StreamEvent| tx.blocking_send(Ok(event)).is_ok();
+        if !send(StreamEvent::MessageStart {
+            message: StreamMessage {
+                id: format!("local-{}", uuid::Uuid::new_v4()),
+                model: self.model_name.clone(),
+                role: Role::Assistant,
+                usage: TokenUsage {
+                    input_tokens: prompt_len,
+                    ..Default::default()
+                },
+            },
+        }) {
+            return Ok(());
+        }
+
+        let ctx_params = LlamaContextParams::default()
+            .with_n_ctx(NonZeroU32::new(self.n_ctx))
+            .with_n_batch(BATCH_SIZE as u32);
+        let mut ctx = model
+            .new_context(backend(), ctx_params)
+            .map_err(|e| ProviderError::Internal(format!("context init failed: {e}")))?;
+
+        let decode_err = |e| ProviderError::Internal(format!("decode failed: {e}"));
+        let batch_err = |e| ProviderError::Internal(format!("batch failed: {e}"));
+        let mut batch = LlamaBatch::new(BATCH_SIZE, 1);
+        let last = tokens.len() - 1;
+        for (chunk_index, chunk) in tokens.chunks(BATCH_SIZE).enumerate() {
+            batch.clear();
+            for (offset, token) in chunk.iter().enumerate() {
+                let pos = chunk_index * BATCH_SIZE + offset;
+                batch
+                    .add(*token, pos as i32, &[0], pos == last)
+                    .map_err(batch_err)?;
+            }
+            ctx.decode(&mut batch).map_err(decode_err)?;
+        }
+
+        let mut samplers = Vec::new();
+        if let Some(grammar) = &self.grammar {
+            let sampler = LlamaSampler::grammar(model, grammar, "root")
+                .map_err(|e| ProviderError::Internal(format!("tool grammar rejected: {e}")))?;
+            samplers.push(sampler);
+        }
+        if self.temperature <= 0.0 {
+            samplers.push(LlamaSampler::greedy());
+        } else {
+            samplers.push(LlamaSampler::temp(self.temperature));
+            samplers.push(LlamaSampler::dist(rand::random::<u32>()));
+        }
+        let mut sampler = LlamaSampler::chain_simple(samplers);
+
+        let mut state = GenerationState::new(self.grammar.is_some());
+        let mut utf8 = Utf8Buffer::default();
+        let mut generated = 0u32;
+        let mut stop_reason = StopReason::MaxTokens;
+        let mut pos = prompt_len as i32;
+        while generated < max_tokens {
+            let token = sampler.sample(&ctx, batch.n_tokens() - 1);
+            if model.is_eog_token(token) {
+                stop_reason = StopReason::EndTurn;
+                break;
+            }
+            generated += 1;
+
+            let bytes = model
+                .token_to_bytes(token, Special::Plaintext)
+                .map_err(|e| ProviderError::Internal(format!("detokenize failed: {e}")))?;
+            for event in state.push(&utf8.push(&bytes)) {
+                if !send(event) {
+                    return Ok(());
+                }
+            }
+
+            batch.clear();
+            batch.add(token, pos, &[0], true).map_err(batch_err)?;
+            pos += 1;
+            ctx.decode(&mut batch).map_err(decode_err)?;
+        }
+
+        let (events, stop_reason) = state.finish(stop_reason);
+        for event in events {
+            send(event);
+        }
+        send(StreamEvent::MessageDelta {
+            delta: MessageDelta {
+                stop_reason: Some(stop_reason),
+                stop_sequence: None,
+            },
+            usage: TokenUsage {
+                input_tokens: prompt_len,
+                output_tokens: generated,
+                ..Default::default()
+            },
+        });
+        send(StreamEvent::MessageStop);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Provider for LocalProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let mut stream = self.stream(request).await?;
+
+        let mut id = String::new();
+        let mut content = Vec::new();
+        let mut text_buf = String::new();
+        let mut stop_reason = None;
+        let mut usage = TokenUsage::default();
+
+        while let Some(event) = stream.next().await {
+            match event? {
+                StreamEvent::MessageStart { message } => id = message.id,
+                StreamEvent::ContentBlockStart {
+                    content_block: ContentBlock::ToolUse { id, name, .. },
+                    ..
+                } => content.push(ContentBlock::ToolUse {
+                    id,
+                    name,
+                    input: json!({}),
+                }),
+                StreamEvent::ContentBlockDelta { delta, .. } => match delta {
+                    ContentDelta::TextDelta { text } => text_buf.push_str(&text),
+                    ContentDelta::InputJsonDelta { partial_json } => {
+                        if let Some(ContentBlock::ToolUse { input, .. }) = content.last_mut() {
+                            *input = serde_json::from_str(&partial_json)?;
+                        }
+                    }
+                    _ => {}
+                },
+                StreamEvent::MessageDelta { delta, usage: u } => {
+                    stop_reason = delta.stop_reason;
+                    usage = u;
+                }
+                StreamEvent::MessageStop => break,
+                _ => {}
+            }
+        }
+
+        if !text_buf.is_empty() {
+            content.insert(0, ContentBlock::Text { text: text_buf });
+        }
+        Ok(LLMResponse {
+            id,
+            model: self.model_name.clone(),
+            content,
+            stop_reason,
+            usage,
+        })
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        let tools = request.tools.as_deref().unwrap_or_default();
+        let job = GenerationJob {
+            model: self.model.clone(),
+            model_name: self.model_name.clone(),
+            n_ctx: self.n_ctx(),
+            prompt: self.build_prompt(&request)?,
+            grammar: (!tools.is_empty()).then(|| tool_grammar(tools)),
+            temperature: request.temperature.unwrap_or(DEFAULT_TEMPERATURE),
+            max_tokens: request.max_tokens,
+        };
+
+        let (tx, rx) = tokio::sync::mpsc::channel::<Result<StreamEvent>>(64);
+        tokio::task::spawn_blocking(move || {
+            // catch_unwind guards against panics from the llama-cpp bindings.
+            let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| job.run(&tx)))
+                .unwrap_or_else(|_| {
+                    Err(ProviderError::Internal(
+                        "llama.cpp panicked during generation".to_string(),
+                    ))
+                });
+            if let Err(e) = outcome {
+                let _ = tx.blocking_send(Err(e));
+            }
+        });
+
+        let stream = futures::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        });
+        Ok(Box::pin(stream))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model_name
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        vec![self.model_name.clone()]
+    }
+
+    fn context_window(&self, _model: &str) -> Option<u32> {
+        Some(self.n_ctx())
+    }
+
+    fn calculate_cost(&self, _model: &str, _input_tokens: u32, _output_tokens: u32) -> f64 {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str) -> Tool {
+        Tool {
+            name: name.to_string(),
+            description: format!("{name} tool"),
+            input_schema: json!({"type": "object", "properties": {"path": {"type": "string"}}}),
+        }
+    }
+
+    fn texts(events: &[StreamEvent]) -> String {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ContentBlockDelta {
+                    delta: ContentDelta::TextDelta { text },
+                    ..
+                } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chat_messages_render_tool_turns() {
+        let mut request = LLMRequest::new(
+            "local",
+            vec![
+                Message::user("read a.txt"),
+                Message {
+                    role: Role::Assistant,
+                    content: vec![ContentBlock::ToolUse {
+                        id: "call_1".to_string(),
+                        name: "read_file".to_string(),
+                        input: json!({"path": "a.txt"}),
+                    }],
+                },
+                Message {
+                    role: Role::User,
+                    content: vec![ContentBlock::ToolResult {
+                        tool_use_id: "call_1".to_string(),
+                        content: "hello".to_string(),
+                        is_error: None,
+                    }],
+                },
+            ],
+        )
+        .with_system("be brief");
+        request.tools = Some(vec![tool("read_file")]);
+
+        let chat = chat_messages(&request);
+        assert_eq!(chat.len(), 4);
+        assert_eq!(chat[0].0, "system");
+        assert!(chat[0].1.starts_with("be brief\n\n"));
+        assert!(chat[0].1.contains("- read_file: read_file tool"));
+        assert_eq!(chat[2].0, "assistant");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&chat[2].1).unwrap(),
+            json!({"name": "read_file", "arguments": {"path": "a.txt"}})
+        );
+        assert_eq!(chat[3], ("user", "[read_file result]\nhello".to_string()));
+    }
+
+    #[test]
+    fn test_tool_grammar_lists_tool_names() {
+        let grammar = tool_grammar(&[tool("read_file"), tool("bash")]);
+        assert!(grammar.starts_with("root   ::= call | answer\n"));
+        assert!(grammar.contains(r#"name   ::= "\"read_file\"" | "\"bash\"""#));
+        assert!(grammar.contains(r#"answer ::= [^{] [^\x00]*"#));
+    }
+
+    #[test]
+    fn test_utf8_buffer_joins_split_characters() {
+        let bytes = "héllo".as_bytes();
+        let mut buf = Utf8Buffer::default();
+        assert_eq!(buf.push(&bytes[..2]), "h");
+        assert_eq!(buf.push(&bytes[2..]), "éllo");
+    }
+
+    #[test]
+    fn test_generation_state_streams_text() {
+        let mut state = GenerationState::new(true);
+        let mut events = state.push("Hi");
+        events.extend(state.push(" there"));
+        assert!(matches!(
+            events[0],
+            StreamEvent::ContentBlockStart {
+                content_block: ContentBlock::Text { .. },
+                ..
+            }
+        ));
+        assert_eq!(texts(&events), "Hi there");
+
+        let (end, stop) = state.finish(StopReason::EndTurn);
+        assert!(matches!(
+            end[..],
+            [StreamEvent::ContentBlockStop { index: 0 }]
+        ));
+        assert_eq!(stop, StopReason::EndTurn);
+    }
+
+    #[test]
+    fn test_generation_state_buffers_tool_call() {
+        let mut state = GenerationState::new(true);
+        assert!(state.push("{\"name\": \"read_file\", ").is_empty());
+        assert!(
+            state
+                .push("\"arguments\": {\"path\": \"a.txt\"}}")
+                .is_empty()
+        );
+
+        let (events, stop) = state.finish(StopReason::EndTurn);
+        assert_eq!(stop, StopReason::ToolUse);
+        assert!(matches!(
+            &events[0],
+            StreamEvent::ContentBlockStart {
+                content_block: ContentBlock::ToolUse { name, .. },
+                ..
+            } if name == "read_file"
+        ));
+        assert!(matches!(
+            &events[1],
+            StreamEvent::ContentBlockDelta {
+                delta: ContentDelta::InputJsonDelta { partial_json },
+                ..
+            } if partial_json == r#"{"path":"a.txt"}"#
+        ));
+    }
+
+    #[test]
+    fn test_truncated_tool_call_falls_back_to_text() {
+        let mut state = GenerationState::new(true);
+        state.push("{\"name\": \"read_");
+        let (events, stop) = state.finish(StopReason::MaxTokens);
+        assert_eq!(stop, StopReason::MaxTokens);
+        assert_eq!(texts(&events), "{\"name\": \"read_");
+    }
+
+    #[test]
+    fn test_brace_is_text_without_tools() {
+        let mut state = GenerationState::new(false);
+        let events = state.push("{not a call}");
+        assert_eq!(texts(&events), "{not a call}");
+    }
+}
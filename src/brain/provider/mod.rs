@@ -2,15 +2,18 @@
 //!
 //! Provides a unified interface for interacting with different LLM providers.
 
+pub mod batch;
 pub mod error;
 pub mod placeholder;
 pub mod rate_limiter;
 pub mod retry;
+pub mod structured;
 #[allow(clippy::module_inception)]
 mod r#trait;
 pub mod types;
 
 // Re-exports
+pub use batch::{BatchRequest, BatchResult, BatchState, BatchStatus};
 pub use error::{ProviderError, Result};
 pub use placeholder::PlaceholderProvider;
 pub use r#trait::{Provider, ProviderCapabilities, ProviderStream};
@@ -18,23 +21,38 @@ pub use types::*;
 
 // Provider implementations
 pub mod anthropic;
+pub mod bedrock;
+pub mod cassette;
 pub mod claude_cli;
 pub mod copilot;
 pub mod custom_openai_compatible;
 pub mod factory;
 pub mod fallback;
 pub mod gemini;
+pub mod key_pool;
+pub mod local;
 pub(crate) mod nonstream_compat;
+pub mod ollama;
+pub(crate) mod openai_responses;
 pub mod opencode_cli;
 pub mod qwen;
+pub mod response_cache;
+pub mod vertex;
 
 pub use anthropic::AnthropicProvider;
+pub use bedrock::BedrockProvider;
+pub use cassette::CassetteProvider;
 pub use claude_cli::ClaudeCliProvider;
 pub use custom_openai_compatible::OpenAIProvider;
 pub use factory::{create_provider, create_provider_by_name, create_provider_with_warning};
-pub use fallback::{FallbackProvider, SwapEvent};
+pub use fallback::{CircuitBreaker, FallbackProvider, SwapEvent};
 pub use gemini::GeminiProvider;
+pub use key_pool::KeyPoolProvider;
+pub use local::LocalProvider;
+pub use ollama::OllamaProvider;
 pub use opencode_cli::OpenCodeCliProvider;
+pub use response_cache::{CachingProvider, ResponseCache};
+pub use vertex::VertexProvider;
 
 /// Cross-platform binary lookup. Uses `where.exe` on Windows, `which` elsewhere.
 /// Returns the resolved path if found on PATH, or None.
@@ -0,0 +1,1020 @@
+//! Ollama Provider Implementation
+//!
+//! Talks to Ollama's native API rather than its OpenAI-compatible shim, so
+//! per-request runtime options (`num_ctx`, `keep_alive`) and native tool
+//! calling survive the round trip, and the model list reflects what is
+//! actually installed on the server.
+//!
+//! ## API Format
+//! - Base URL: `http://localhost:11434` (no auth)
+//! - Chat: `POST /api/chat` — streaming bodies are NDJSON, one partial
+//!   message per line; the final line carries `done: true` and token counts
+//! - Installed models: `GET /api/tags`
+//! - Model metadata: `POST /api/show` — trained context length lives in
+//!   `model_info["<arch>.context_length"]`
+//! - Pull: `POST /api/pull` — NDJSON progress (`status`, `total`, `completed`)
+
+use super::error::{ProviderError, Result};
+use super::r#trait::{Provider, ProviderStream};
+use super::types::*;
+use async_trait::async_trait;
+use futures::stream::StreamExt;
+use reqwest::Client;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+/// Default Ollama server address
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+const DEFAULT_MODEL: &str = "llama3.2";
+/// Context Ollama allocates when neither the request nor the Modelfile
+/// sets `num_ctx`.
+const DEFAULT_NUM_CTX: u32 = 4096;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What `/api/show` tells us about a model's context
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModelInfo {
+    /// Context length the model was trained with
+    pub trained_context: Option<u32>,
+    /// `num_ctx` baked into the Modelfile, if any
+    pub modelfile_num_ctx: Option<u32>,
+}
+
+/// One progress line from `/api/pull`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PullProgress {
+    /// Phase reported by Ollama ("pulling manifest", "pulling <digest>", "success", ...)
+    pub status: String,
+    /// Bytes downloaded for the current layer
+    pub completed: u64,
+    /// Size of the current layer in bytes (0 while not downloading)
+    pub total: u64,
+}
+
+impl PullProgress {
+    /// Download progress of the current layer in `0.0..=1.0`, if known
+    pub fn fraction(&self) -> Option<f64> {
+        (self.total > 0).then(|| (self.completed as f64 / self.total as f64).min(1.0))
+    }
+}
+
+/// Native Ollama provider
+#[derive(Clone)]
+pub struct OllamaProvider {
+    base_url: String,
+    client: Client,
+    model: String,
+    models: Vec<String>,
+    num_ctx: Option<u32>,
+    keep_alive: Option<String>,
+    model_info: Arc<RwLock<HashMap<String, ModelInfo>>>,
+}
+
+impl OllamaProvider {
+    /// Create a provider for the Ollama server at `base_url`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let client = Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            base_url: normalize_base_url(&base_url.into()),
+            client,
+            model: DEFAULT_MODEL.to_string(),
+            models: Vec::new(),
+            num_ctx: None,
+            keep_alive: None,
+            model_info: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Set the default model (e.g. `"qwen3:8b"`)
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Suggested models from `models = [...]`, offered for pulling
+    pub fn with_models(mut self, models: Vec<String>) -> Self {
+        self.models = models;
+        self
+    }
+
+    /// Context window sent as `options.num_ctx` on every request
+    pub fn with_context_window(mut self, num_ctx: u32) -> Self {
+        self.num_ctx = Some(num_ctx);
+        self
+    }
+
+    /// How long Ollama keeps the model loaded after a request (`"5m"`, `"-1"`, ...)
+    pub fn with_keep_alive(mut self, keep_alive: impl Into<String>) -> Self {
+        self.keep_alive = Some(keep_alive.into());
+        self
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Models installed on the server, from `/api/tags`
+    pub async fn installed_models(&self) -> Result<Vec<String>> {
+        let response = self.client.get(self.url("/api/tags")).send().await?;
+        if !response.status().is_success() {
+            return Err(handle_error(response).await);
+        }
+        let body: Value = response.json().await?;
+        Ok(body["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m["name"].as_str().or_else(|| m["model"].as_str()))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Whether `model` is already installed (an untagged name means `:latest`)
+    pub async fn is_installed(&self, model: &str) -> Result<bool> {
+        let wanted = with_default_tag(model);
+        Ok(self
+            .installed_models()
+            .await?
+            .iter()
+            .any(|m| with_default_tag(m) == wanted))
+    }
+
+    /// Pull `model` from the Ollama registry, reporting each progress line.
+    /// Returns once Ollama reports `success`.
+    pub async fn pull_model(
+        &self,
+        model: &str,
+        mut on_progress: impl FnMut(PullProgress) + Send,
+    ) -> Result<()> {
+        tracing::info!("Ollama pull: {}", model);
+        let response = self
+            .client
+            .post(self.url("/api/pull"))
+            .json(&json!({"model": model, "stream": true}))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(handle_error(response).await);
+        }
+
+        let mut lines = NdjsonBuffer::default();
+        let mut body = response.bytes_stream();
+        let mut succeeded = false;
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(|e| ProviderError::StreamError(e.to_string()))?;
+            for line in lines.feed(&chunk) {
+                if let Some(err) = line["error"].as_str() {
+                    return Err(ProviderError::ApiError {
+                        status: 500,
+                        message: err.to_string(),
+                        error_type: Some("pull".to_string()),
+                    });
+                }
+                let progress = PullProgress {
+                    status: line["status"].as_str().unwrap_or_default().to_string(),
+                    completed: line["completed"].as_u64().unwrap_or(0),
+                    total: line["total"].as_u64().unwrap_or(0),
+                };
+                succeeded |= progress.status == "success";
+                on_progress(progress);
+            }
+        }
+
+        if succeeded {
+            self.model_info
+                .write()
+                .expect("model info lock")
+                .remove(model);
+            Ok(())
+        } else {
+            Err(ProviderError::StreamError(format!(
+                "pull of {} ended before Ollama reported success",
+                model
+            )))
+        }
+    }
+
+    /// Context metadata for `model` from `/api/show`, cached per model
+    pub async fn model_info(&self, model: &str) -> Result<ModelInfo> {
+        if let Some(info) = self.cached_info(model) {
+            return Ok(info);
+        }
+        let response = self
+            .client
+            .post(self.url("/api/show"))
+            .json(&json!({"model": model}))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(handle_error(response).await);
+        }
+        let info = parse_show_response(&response.json().await?);
+        self.model_info
+            .write()
+            .expect("model info lock")
+            .insert(model.to_string(), info);
+        Ok(info)
+    }
+
+    fn cached_info(&self, model: &str) -> Option<ModelInfo> {
+        self.model_info
+            .read()
+            .expect("model info lock")
+            .get(model)
+            .copied()
+    }
+
+    /// Warm the `/api/show` cache for the default model in the background so
+    /// `context_window()` has a real value before the first request.
+    pub fn prefetch_model_info(&self) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let provider = self.clone();
+        handle.spawn(async move {
+            let model = provider.model.clone();
+            if let Err(e) = provider.model_info(&model).await {
+                tracing::debug!("Ollama /api/show for {} failed: {}", model, e);
+            }
+        });
+    }
+
+    async fn post_chat(&self, body: &Value) -> Result<reqwest::Response> {
+        let response = self
+            .client
+            .post(self.url("/api/chat"))
+            .json(body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(handle_error(response).await);
+        }
+        Ok(response)
+    }
+
+    fn chat_body(&self, request: &LLMRequest, stream: bool) -> Value {
+        build_chat_body(request, self.num_ctx, self.keep_alive.as_deref(), stream)
+    }
+}
+
+/// Strip a trailing `/` and an OpenAI-shim `/v1` suffix so configs copied
+/// from `[providers.custom.ollama]` keep working.
+fn normalize_base_url(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    url.strip_suffix("/v1").unwrap_or(url).to_string()
+}
+
+pub(crate) fn with_default_tag(model: &str) -> String {
+    if model.contains(':') {
+        model.to_string()
+    } else {
+        format!("{}:latest", model)
+    }
+}
+
+/// Ollama errors are `{"error": "..."}`; a missing model is a 404.
+async fn handle_error(response: reqwest::Response) -> ProviderError {
+    let status = response.status().as_u16();
+    let body = response.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<Value>(&body)
+        .ok()
+        .and_then(|v| v["error"].as_str().map(str::to_string))
+        .unwrap_or_else(|| {
+            if body.is_empty() {
+                "Unknown error".to_string()
+            } else {
+                body
+            }
+        });
+    if status == 404 {
+        return ProviderError::ModelNotFound(message);
+    }
+    ProviderError::ApiError {
+        status,
+        message,
+        error_type: None,
+    }
+}
+
+pub(crate) fn parse_show_response(body: &Value) -> ModelInfo {
+    let trained_context = body["model_info"].as_object().and_then(|info| {
+        let arch = info.get("general.architecture").and_then(Value::as_str);
+        arch.and_then(|a| info.get(&format!("{}.context_length", a)))
+            .or_else(|| {
+                info.iter()
+                    .find(|(k, _)| k.ends_with(".context_length"))
+                    .map(|(_, v)| v)
+            })
+            .and_then(Value::as_u64)
+            .map(|n| n.min(u32::MAX as u64) as u32)
+    });
+    let modelfile_num_ctx = body["parameters"].as_str().and_then(|params| {
+        params.lines().find_map(|line| {
+            let mut parts = line.split_whitespace();
+            (parts.next() == Some("num_ctx"))
+                .then(|| parts.next()?.parse().ok())
+                .flatten()
+        })
+    });
+    ModelInfo {
+        trained_context,
+        modelfile_num_ctx,
+    }
+}
+
+/// Convert our LLMRequest to an `/api/chat` body.
+pub(crate) fn build_chat_body(
+    request: &LLMRequest,
+    num_ctx: Option<u32>,
+    keep_alive: Option<&str>,
+    stream: bool,
+) -> Value {
+    let mut messages = Vec::new();
+    if let Some(system) = &request.system {
+        messages.push(json!({"role": "system", "content": system}));
+    }
+
+    // Ollama keys tool results by function name, not call id.
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
+    for message in &request.messages {
+        let mut text = String::new();
+        let mut images = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in &message.content {
+            match block {
+                ContentBlock::Text { text: t } => {
+                    if !text.is_empty() {
+                        text.push('\n');
+                    }
+                    text.push_str(t);
+                }
+                ContentBlock::Image {
+                    source: ImageSource::Base64 { data, .. },
+                } => images.push(json!(data)),
+                ContentBlock::Image {
+                    source: ImageSource::Url { url },
+                } => {
+                    tracing::warn!("Ollama only accepts inline images; dropping {}", url);
+                }
+                ContentBlock::ToolUse { id, name, input } => {
+                    tool_names.insert(id.as_str(), name.as_str());
+                    tool_calls.push(json!({"function": {"name": name, "arguments": input}}));
+                }
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    ..
+                } => {
+                    let mut result = json!({"role": "tool", "content": content});
+                    if let Some(name) = tool_names.get(tool_use_id.as_str()) {
+                        result["tool_name"] = json!(name);
+                    }
+                    messages.push(result);
+                }
+                ContentBlock::Thinking { .. } | ContentBlock::RedactedThinking { .. } => {}
+            }
+        }
+        if text.is_empty() && images.is_empty() && tool_calls.is_empty() {
+            continue;
+        }
+        let role = match message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+        };
+        let mut entry = json!({"role": role, "content": text});
+        if !images.is_empty() {
+            entry["images"] = Value::Array(images);
+        }
+        if !tool_calls.is_empty() {
+            entry["tool_calls"] = Value::Array(tool_calls);
+        }
+        messages.push(entry);
+    }
+
+    let mut body = json!({
+        "model": request.model,
+        "messages": messages,
+        "stream": stream,
+    });
+    // No `tool_choice` in the chat API: "none" withholds the tools and a
+    // named tool is offered alone. "required" can't be expressed.
+    let offered = request
+        .tools
+        .as_ref()
+        .filter(|t| !t.is_empty() && request.tool_choice != Some(ToolChoice::None));
+    if let Some(tools) = offered {
+        body["tools"] = tools
+            .iter()
+            .filter(|t| match &request.tool_choice {
+                Some(ToolChoice::Tool(name)) => &t.name == name,
+                _ => true,
+            })
+            .map(|t| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": t.name,
+                        "description": t.description,
+                        "parameters": t.input_schema,
+                    }
+                })
+            })
+            .collect();
+    }
+
+    let mut options = serde_json::Map::new();
+    if let Some(n) = num_ctx {
+        options.insert("num_ctx".to_string(), json!(n));
+    }
+    if let Some(t) = request.temperature {
+        options.insert("temperature".to_string(), json!(t));
+    }
+    if let Some(m) = request.max_tokens {
+        options.insert("num_predict".to_string(), json!(m));
+    }
+    if !options.is_empty() {
+        body["options"] = Value::Object(options);
+    }
+    if let Some(keep_alive) = keep_alive {
+        // Plain integers are seconds; anything else is a duration string.
+        body["keep_alive"] = keep_alive
+            .parse::<i64>()
+            .map(Value::from)
+            .unwrap_or_else(|_| json!(keep_alive));
+    }
+    match &request.response_format {
+        Some(ResponseFormat::JsonObject) => body["format"] = json!("json"),
+        Some(ResponseFormat::JsonSchema { schema, .. }) => body["format"] = schema.clone(),
+        None => {}
+    }
+    body
+}
+
+fn tool_call_block(call: &Value) -> Option<ContentBlock> {
+    let function = &call["function"];
+    let name = function["name"].as_str()?.to_string();
+    let input = match &function["arguments"] {
+        // Some templates hand arguments back as a JSON string
+        Value::String(s) => serde_json::from_str(s).unwrap_or_else(|_| json!({})),
+        Value::Null => json!({}),
+        other => other.clone(),
+    };
+    let id = call["id"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple()));
+    Some(ContentBlock::ToolUse { id, name, input })
+}
+
+fn parse_usage(body: &Value) -> TokenUsage {
+    TokenUsage {
+        input_tokens: body["prompt_eval_count"].as_u64().unwrap_or(0) as u32,
+        output_tokens: body["eval_count"].as_u64().unwrap_or(0) as u32,
+        ..Default::default()
+    }
+}
+
+fn stop_reason(body: &Value, saw_tool_call: bool) -> StopReason {
+    if saw_tool_call {
+        StopReason::ToolUse
+    } else if body["done_reason"] == "length" {
+        StopReason::MaxTokens
+    } else {
+        StopReason::EndTurn
+    }
+}
+
+pub(crate) fn parse_chat_response(model: &str, body: &Value) -> LLMResponse {
+    let message = &body["message"];
+    let mut content = Vec::new();
+    if let Some(text) = message["content"].as_str().filter(|t| !t.is_empty()) {
+        content.push(ContentBlock::Text {
+            text: text.to_string(),
+        });
+    }
+    content.extend(
+        message["tool_calls"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(tool_call_block),
+    );
+    let saw_tool_call = content
+        .iter()
+        .any(|b| matches!(b, ContentBlock::ToolUse { .. }));
+
+    LLMResponse {
+        id: format!("ollama-{}", uuid::Uuid::new_v4()),
+        model: body["model"].as_str().unwrap_or(model).to_string(),
+        content,
+        stop_reason: Some(stop_reason(body, saw_tool_call)),
+        usage: parse_usage(body),
+    }
+}
+
+/// Splits a byte stream into parsed NDJSON lines, holding back a partial
+/// trailing line until the rest arrives.
+#[derive(Default)]
+struct NdjsonBuffer {
+    buf: Vec<u8>,
+}
+
+impl NdjsonBuffer {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<Value> {
+        self.buf.extend_from_slice(chunk);
+        let mut lines = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(value) => lines.push(value),
+                Err(e) => tracing::warn!("Skipping malformed Ollama line: {} ({})", line, e),
+            }
+        }
+        lines
+    }
+}
+
+/// Translates `/api/chat` NDJSON chunks into our stream events. Text and
+/// thinking share the text block at index 0; each tool call arrives whole
+/// and gets its own block after it.
+struct ChatStreamState {
+    model: String,
+    lines: NdjsonBuffer,
+    started: bool,
+    text_open: bool,
+    next_index: usize,
+    saw_tool_call: bool,
+}
+
+impl ChatStreamState {
+    fn new(model: String) -> Self {
+        Self {
+            model,
+            lines: NdjsonBuffer::default(),
+            started: false,
+            text_open: false,
+            next_index: 1,
+            saw_tool_call: false,
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) -> Vec<Result<StreamEvent>> {
+        let mut events = Vec::new();
+        for line in self.lines.feed(chunk) {
+            self.translate(&line, &mut events);
+        }
+        events
+    }
+
+    fn translate(&mut self, line: &Value, events: &mut Vec<Result<StreamEvent>>) {
+        if let Some(err) = line["error"].as_str() {
+            events.push(Err(ProviderError::StreamError(err.to_string())));
+            return;
+        }
+        if !self.started {
+            self.started = true;
+            events.push(Ok(StreamEvent::MessageStart {
+                message: StreamMessage {
+                    id: format!("ollama-{}", uuid::Uuid::new_v4()),
+                    model: self.model.clone(),
+                    role: Role::Assistant,
+                    usage: TokenUsage::default(),
+                },
+            }));
+        }
+
+        let message = &line["message"];
+        if let Some(thinking) = message["thinking"].as_str().filter(|t| !t.is_empty()) {
+            self.open_text(events);
+            events.push(Ok(StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: ContentDelta::ReasoningDelta {
+                    text: thinking.to_string(),
+                },
+            }));
+        }
+        if let Some(text) = message["content"].as_str().filter(|t| !t.is_empty()) {
+            self.open_text(events);
+            events.push(Ok(StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: ContentDelta::TextDelta {
+                    text: text.to_string(),
+                },
+            }));
+        }
+        for call in message["tool_calls"].as_array().into_iter().flatten() {
+            let Some(ContentBlock::ToolUse { id, name, input }) = tool_call_block(call) else {
+                continue;
+            };
+            let index = self.next_index;
+            self.next_index += 1;
+            self.saw_tool_call = true;
+            events.push(Ok(StreamEvent::ContentBlockStart {
+                index,
+                content_block: ContentBlock::ToolUse {
+                    id,
+                    name,
+                    input: json!({}),
+                },
+            }));
+            events.push(Ok(StreamEvent::ContentBlockDelta {
+                index,
+                delta: ContentDelta::InputJsonDelta {
+                    partial_json: input.to_string(),
+                },
+            }));
+            events.push(Ok(StreamEvent::ContentBlockStop { index }));
+        }
+
+        if line["done"] == true {
+            if self.text_open {
+                self.text_open = false;
+                events.push(Ok(StreamEvent::ContentBlockStop { index: 0 }));
+            }
+            events.push(Ok(StreamEvent::MessageDelta {
+                delta: MessageDelta {
+                    stop_reason: Some(stop_reason(line, self.saw_tool_call)),
+                    stop_sequence: None,
+                },
+                usage: parse_usage(line),
+            }));
+            events.push(Ok(StreamEvent::MessageStop));
+        }
+    }
+
+    fn open_text(&mut self, events: &mut Vec<Result<StreamEvent>>) {
+        if !self.text_open {
+            self.text_open = true;
+            events.push(Ok(StreamEvent::ContentBlockStart {
+                index: 0,
+                content_block: ContentBlock::Text {
+                    text: String::new(),
+                },
+            }));
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for OllamaProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        use super::retry::{RetryConfig, retry_with_backoff};
+
+        let model = request.model.clone();
+        tracing::info!(
+            "Ollama API request: model={}, messages={}, num_ctx={:?}",
+            model,
+            request.messages.len(),
+            self.num_ctx
+        );
+        if let Err(e) = self.model_info(&model).await {
+            tracing::debug!("Ollama /api/show for {} failed: {}", model, e);
+        }
+
+        let body = self.chat_body(&request, false);
+        let retry_config = RetryConfig::default();
+
+        let result = retry_with_backoff(
+            || async {
+                let response = self.post_chat(&body).await?;
+                let json: Value = response.json().await?;
+                let llm_response = parse_chat_response(&model, &json);
+
+                tracing::info!(
+                    "Ollama API response: input_tokens={}, output_tokens={}, stop_reason={:?}",
+                    llm_response.usage.input_tokens,
+                    llm_response.usage.output_tokens,
+                    llm_response.stop_reason
+                );
+
+                Ok(llm_response)
+            },
+            &retry_config,
+        )
+        .await;
+
+        if let Err(ref e) = result {
+            tracing::error!("Ollama API request failed: {}", e);
+        }
+
+        result
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        use super::retry::{RetryConfig, retry_with_backoff};
+
+        let model = request.model.clone();
+        tracing::info!(
+            "Ollama streaming request: model={}, messages={}, num_ctx={:?}",
+            model,
+            request.messages.len(),
+            self.num_ctx
+        );
+        if let Err(e) = self.model_info(&model).await {
+            tracing::debug!("Ollama /api/show for {} failed: {}", model, e);
+        }
+
+        let body = self.chat_body(&request, true);
+        let retry_config = RetryConfig::default();
+
+        let response =
+            retry_with_backoff(|| async { self.post_chat(&body).await }, &retry_config).await?;
+
+        let state = Arc::new(Mutex::new(ChatStreamState::new(model)));
+        let event_stream = response
+            .bytes_stream()
+            .map(move |chunk_result| -> Vec<Result<StreamEvent>> {
+                match chunk_result {
+                    Err(e) => vec![Err(ProviderError::StreamError(e.to_string()))],
+                    Ok(chunk) => {
+                        let mut st = state.lock().expect("ndjson state lock");
+                        let events = st.feed(&chunk);
+                        if events.is_empty() {
+                            vec![Ok(StreamEvent::Ping)]
+                        } else {
+                            events
+                        }
+                    }
+                }
+            })
+            .flat_map(futures::stream::iter);
+
+        Ok(Box::pin(event_stream))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn supports_response_format(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    fn base_url(&self) -> Option<&str> {
+        Some(&self.base_url)
+    }
+
+    fn default_model(&self) -> &str {
+        &self.model
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        let mut models = vec![self.model.clone()];
+        for model in &self.models {
+            if !models.contains(model) {
+                models.push(model.clone());
+            }
+        }
+        models
+    }
+
+    /// Installed models only — suggestions from config are pullable, not
+    /// runnable, so they stay out of this list.
+    async fn fetch_models(&self) -> Vec<String> {
+        match self.installed_models().await {
+            Ok(models) if !models.is_empty() => models,
+            Ok(_) => self.supported_models(),
+            Err(e) => {
+                tracing::warn!("Ollama /api/tags failed: {}", e);
+                self.supported_models()
+            }
+        }
+    }
+
+    /// The window the server actually allocates: the configured `num_ctx`
+    /// (or the Modelfile's, or Ollama's default), capped at the trained
+    /// length reported by `/api/show`.
+    fn context_window(&self, model: &str) -> Option<u32> {
+        let info = self.cached_info(model);
+        let allocated = self
+            .num_ctx
+            .or_else(|| info.map(|i| i.modelfile_num_ctx.unwrap_or(DEFAULT_NUM_CTX)))?;
+        Some(match info.and_then(|i| i.trained_context) {
+            Some(trained) => allocated.min(trained),
+            None => allocated,
+        })
+    }
+
+    fn configured_context_window(&self) -> Option<u32> {
+        self.num_ctx
+    }
+
+    fn calculate_cost(&self, _model: &str, _input_tokens: u32, _output_tokens: u32) -> f64 {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chat_body_maps_tools_images_and_options() {
+        let request = LLMRequest::new(
+            "qwen3:8b",
+            vec![
+                Message {
+                    role: Role::User,
+                    content: vec![
+                        ContentBlock::Text {
+                            text: "what is this?".into(),
+                        },
+                        ContentBlock::Image {
+                            source: ImageSource::Base64 {
+                                media_type: "image/png".into(),
+                                data: "aGk=".into(),
+                            },
+                        },
+                    ],
+                },
+                Message {
+                    role: Role::Assistant,
+                    content: vec![ContentBlock::ToolUse {
+                        id: "call_1".into(),
+                        name: "read_file".into(),
+                        input: json!({"path": "a.txt"}),
+                    }],
+                },
+                Message {
+                    role: Role::User,
+                    content: vec![ContentBlock::ToolResult {
+                        tool_use_id: "call_1".into(),
+                        content: "hello".into(),
+                        is_error: None,
+                    }],
+                },
+            ],
+        )
+        .with_system("sys")
+        .with_max_tokens(256)
+        .with_tools(vec![Tool {
+            name: "read_file".into(),
+            description: "Read a file".into(),
+            input_schema: json!({"type": "object"}),
+        }]);
+
+        let body = build_chat_body(&request, Some(16384), Some("10m"), true);
+        assert_eq!(
+            body["messages"][0],
+            json!({"role": "system", "content": "sys"})
+        );
+        assert_eq!(body["messages"][1]["images"], json!(["aGk="]));
+        assert_eq!(
+            body["messages"][2]["tool_calls"][0]["function"],
+            json!({"name": "read_file", "arguments": {"path": "a.txt"}})
+        );
+        assert_eq!(
+            body["messages"][3],
+            json!({"role": "tool", "content": "hello", "tool_name": "read_file"})
+        );
+        assert_eq!(body["tools"][0]["function"]["name"], "read_file");
+        assert_eq!(
+            body["options"],
+            json!({"num_ctx": 16384, "num_predict": 256})
+        );
+        assert_eq!(body["keep_alive"], "10m");
+        assert_eq!(body["stream"], true);
+    }
+
+    #[test]
+    fn test_keep_alive_seconds_sent_as_number() {
+        let request = LLMRequest::new("llama3.2", vec![Message::user("hi")]);
+        let body = build_chat_body(&request, None, Some("-1"), false);
+        assert_eq!(body["keep_alive"], -1);
+        assert!(body.get("options").is_none());
+    }
+
+    #[test]
+    fn test_parse_show_response() {
+        let info = parse_show_response(&json!({
+            "parameters": "stop \"<|im_end|>\"\nnum_ctx 8192",
+            "model_info": {
+                "general.architecture": "qwen3",
+                "qwen3.context_length": 40960
+            }
+        }));
+        assert_eq!(
+            info,
+            ModelInfo {
+                trained_context: Some(40960),
+                modelfile_num_ctx: Some(8192),
+            }
+        );
+    }
+
+    #[test]
+    fn test_context_window_caps_at_trained_length() {
+        let provider = OllamaProvider::new(DEFAULT_BASE_URL).with_context_window(65536);
+        assert_eq!(provider.context_window("qwen3:8b"), Some(65536));
+
+        provider.model_info.write().unwrap().insert(
+            "qwen3:8b".into(),
+            ModelInfo {
+                trained_context: Some(40960),
+                modelfile_num_ctx: None,
+            },
+        );
+        assert_eq!(provider.context_window("qwen3:8b"), Some(40960));
+
+        let unconfigured = OllamaProvider::new(DEFAULT_BASE_URL);
+        assert_eq!(unconfigured.context_window("qwen3:8b"), None);
+        unconfigured.model_info.write().unwrap().insert(
+            "qwen3:8b".into(),
+            ModelInfo {
+                trained_context: Some(40960),
+                modelfile_num_ctx: None,
+            },
+        );
+        assert_eq!(
+            unconfigured.context_window("qwen3:8b"),
+            Some(DEFAULT_NUM_CTX)
+        );
+    }
+
+    #[test]
+    fn test_base_url_normalization() {
+        assert_eq!(
+            normalize_base_url("http://localhost:11434/v1/"),
+            "http://localhost:11434"
+        );
+        assert_eq!(with_default_tag("llama3.2"), "llama3.2:latest");
+        assert_eq!(with_default_tag("qwen3:8b"), "qwen3:8b");
+    }
+
+    #[test]
+    fn test_stream_splits_lines_across_chunks() {
+        let mut state = ChatStreamState::new("llama3.2".into());
+        let first = state.feed(
+            br#"{"message":{"role":"assistant","content":"Hel"},"done":false}
+{"message":{"role":"assis"#,
+        );
+        assert!(matches!(first[0], Ok(StreamEvent::MessageStart { .. })));
+        assert!(matches!(
+            first[1],
+            Ok(StreamEvent::ContentBlockStart { index: 0, .. })
+        ));
+        assert_eq!(first.len(), 3);
+
+        let rest = state.feed(
+            br#"tant","content":"","tool_calls":[{"function":{"name":"ls","arguments":{"path":"."}}}]},"done":false}
+{"message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":12,"eval_count":5}
+"#,
+        );
+        assert!(matches!(
+            &rest[0],
+            Ok(StreamEvent::ContentBlockStart {
+                index: 1,
+                content_block: ContentBlock::ToolUse { name, .. }
+            }) if name == "ls"
+        ));
+        assert!(matches!(
+            &rest[1],
+            Ok(StreamEvent::ContentBlockDelta {
+                delta: ContentDelta::InputJsonDelta { partial_json },
+                ..
+            }) if partial_json == r#"{"path":"."}"#
+        ));
+        assert!(matches!(
+            &rest[4],
+            Ok(StreamEvent::MessageDelta { delta, usage })
+                if delta.stop_reason == Some(StopReason::ToolUse) && usage.input_tokens == 12
+        ));
+        assert!(matches!(rest[5], Ok(StreamEvent::MessageStop)));
+    }
+
+    #[test]
+    fn test_stream_error_line() {
+        let mut state = ChatStreamState::new("llama3.2".into());
+        let events = state.feed(b"{\"error\":\"model requires more system memory\"}\n");
+        assert!(matches!(
+            &events[0],
+            Err(ProviderError::StreamError(msg)) if msg.contains("system memory")
+        ));
+    }
+}
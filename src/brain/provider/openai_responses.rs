@@ -0,0 +1,946 @@
+//! OpenAI Responses API
+//!
+//! With `endpoint_type = "responses"`, `OpenAIProvider` talks to
+//! `/v1/responses` instead of `/v1/chat/completions`. Newer reasoning models
+//! only expose reasoning summaries and built-in tools through this API.
+//!
+//! ## API Format
+//! - Request: `input` is a list of items (`message`, `function_call`,
+//!   `function_call_output`); the system brain goes in `instructions`
+//! - Chaining: responses are stored server-side, so a follow-up turn sends
+//!   only the items added since the last response plus its
+//!   `previous_response_id` (tracked per session by [`ResponseChain`])
+//! - Output: `reasoning` items carry summaries (→ thinking), `function_call`
+//!   items map to `ToolUse`, built-in tool items (`web_search_call`,
+//!   `code_interpreter_call`, ...) are surfaced as thinking so the user sees
+//!   what ran server-side
+//! - Streaming: typed SSE events (`response.output_text.delta`,
+//!   `response.function_call_arguments.delta`, `response.completed`, ...)
+
+use super::error::{ProviderError, Result};
+use super::types::*;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Map a chat-completions URL to the matching `/responses` endpoint
+pub(crate) fn responses_url(chat_url: &str) -> String {
+    let base = chat_url.trim_end_matches('/');
+    if base.ends_with("/responses") {
+        base.to_string()
+    } else if let Some(root) = base.strip_suffix("/chat/completions") {
+        format!("{}/responses", root)
+    } else {
+        format!("{}/responses", base)
+    }
+}
+
+/// Reasoning models reject `temperature` and accept a `reasoning` block;
+/// everything else is the other way round.
+pub(crate) fn is_reasoning_model(model: &str) -> bool {
+    let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    ["o1", "o3", "o4", "gpt-5", "codex"]
+        .iter()
+        .any(|p| name.starts_with(p))
+        && !name.contains("-chat")
+}
+
+/// True when the server no longer knows the `previous_response_id` we sent
+/// (expired, deleted, or created under another key). The caller drops the
+/// chain and resends the full history.
+pub(crate) fn is_stale_chain_error(err: &ProviderError) -> bool {
+    match err {
+        ProviderError::ApiError {
+            status: 400 | 404,
+            message,
+            error_type,
+        } => {
+            let msg = message.to_lowercase();
+            msg.contains("previous response")
+                || msg.contains("previous_response")
+                || error_type
+                    .as_deref()
+                    .is_some_and(|t| t.contains("previous_response"))
+        }
+        _ => false,
+    }
+}
+
+// ── Chaining ────────────────────────────────────────────────────
+
+/// Last stored response per session, so follow-up turns can send only the
+/// new input items alongside `previous_response_id`.
+#[derive(Default)]
+pub(crate) struct ResponseChain {
+    links: Mutex<HashMap<Uuid, ChainLink>>,
+}
+
+struct ChainLink {
+    response_id: String,
+    model: String,
+    /// Number of request messages the response was generated from
+    sent: usize,
+    /// Hash of those messages — a mismatch means history was rewritten
+    /// (compaction, rewind, edit) and the chain can't be trusted
+    fingerprint: u64,
+}
+
+impl ResponseChain {
+    /// Previous response id and the index of the first message still to be
+    /// sent, when `messages` extends the last exchange of this session.
+    pub(crate) fn resume(
+        &self,
+        session: Uuid,
+        model: &str,
+        messages: &[Message],
+    ) -> Option<(String, usize)> {
+        let links = self.links.lock().expect("response chain lock");
+        let link = links.get(&session)?;
+        // messages[sent] is the assistant turn the server already has
+        let skip = link.sent + 1;
+        if link.model != model
+            || messages.len() <= skip
+            || messages[link.sent].role != Role::Assistant
+            || fingerprint(&messages[..link.sent]) != link.fingerprint
+        {
+            return None;
+        }
+        Some((link.response_id.clone(), skip))
+    }
+
+    pub(crate) fn forget(&self, session: Uuid) {
+        self.links
+            .lock()
+            .expect("response chain lock")
+            .remove(&session);
+    }
+}
+
+/// Chain entry waiting on the response id — captured before the request is
+/// sent so the stream can commit it once `response.completed` arrives.
+pub(crate) struct PendingLink {
+    chain: Arc<ResponseChain>,
+    session: Uuid,
+    link: ChainLink,
+}
+
+impl PendingLink {
+    /// `None` for requests without a session — there is nothing to chain on
+    pub(crate) fn new(chain: &Arc<ResponseChain>, request: &LLMRequest) -> Option<Self> {
+        Some(Self {
+            chain: Arc::clone(chain),
+            session: request.session_id?,
+            link: ChainLink {
+                response_id: String::new(),
+                model: request.model.clone(),
+                sent: request.messages.len(),
+                fingerprint: fingerprint(&request.messages),
+            },
+        })
+    }
+
+    pub(crate) fn commit(mut self, response_id: &str) {
+        if response_id.is_empty() {
+            return;
+        }
+        self.link.response_id = response_id.to_string();
+        self.chain
+            .links
+            .lock()
+            .expect("response chain lock")
+            .insert(self.session, self.link);
+    }
+}
+
+fn fingerprint(messages: &[Message]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(messages)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+// ── Request ─────────────────────────────────────────────────────
+
+/// Build a `/responses` body. With `resume`, only the messages after the
+/// chained assistant turn are sent.
+pub(crate) fn build_request_body(
+    request: &LLMRequest,
+    resume: Option<&(String, usize)>,
+    stream: bool,
+) -> Value {
+    let skip = resume.map(|(_, skip)| *skip).unwrap_or(0);
+    let mut body = json!({
+        "model": request.model,
+        "input": input_items(&request.messages[skip.min(request.messages.len())..]),
+        "stream": stream,
+    });
+
+    if let Some(ref system) = request.system {
+        body["instructions"] = json!(system);
+    }
+    if let Some((id, _)) = resume {
+        body["previous_response_id"] = json!(id);
+    }
+    if let Some(tools) = request.tools.as_ref().filter(|t| !t.is_empty()) {
+        // Function tools default to strict mode here, which rejects any
+        // schema with optional fields — ours are plain JSON Schema.
+        body["tools"] = tools
+            .iter()
+            .map(|t| {
+                json!({
+                    "type": "function",
+                    "name": t.name,
+                    "description": t.description,
+                    "parameters": t.input_schema,
+                    "strict": false,
+                })
+            })
+            .collect();
+        // Same values as chat completions, but a named function is flat
+        body["tool_choice"] = match &request.tool_choice {
+            None | Some(ToolChoice::Auto) => json!("auto"),
+            Some(ToolChoice::Required) => json!("required"),
+            Some(ToolChoice::None) => json!("none"),
+            Some(ToolChoice::Tool(name)) => json!({"type": "function", "name": name}),
+        };
+    }
+    if let Some(max) = request.max_tokens {
+        body["max_output_tokens"] = json!(max);
+    }
+    if is_reasoning_model(&request.model) {
+        body["reasoning"] = json!({"summary": "auto"});
+    } else if let Some(t) = request.temperature {
+        body["temperature"] = json!(t);
+    }
+    if let Some(format) = &request.response_format {
+        body["text"] = json!({"format": super::structured::responses_text_format(format)});
+    }
+    body
+}
+
+fn input_items(messages: &[Message]) -> Vec<Value> {
+    let mut items = Vec::new();
+    for msg in messages {
+        let role = match msg.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+        };
+        let mut parts: Vec<Value> = Vec::new();
+        for block in &msg.content {
+            match block {
+                ContentBlock::Text { text } if !text.is_empty() => {
+                    let kind = if msg.role == Role::Assistant {
+                        "output_text"
+                    } else {
+                        "input_text"
+                    };
+                    parts.push(json!({"type": kind, "text": text}));
+                }
+                ContentBlock::Image { source } if msg.role != Role::Assistant => {
+                    let url = match source {
+                        ImageSource::Base64 { media_type, data } => {
+                            format!("data:{};base64,{}", media_type, data)
+                        }
+                        ImageSource::Url { url } => url.clone(),
+                    };
+                    parts.push(json!({"type": "input_image", "image_url": url}));
+                }
+                ContentBlock::ToolUse { id, name, input } => {
+                    flush_message(&mut items, role, &mut parts);
+                    items.push(json!({
+                        "type": "function_call",
+                        "call_id": id,
+                        "name": name,
+                        "arguments": input.to_string(),
+                    }));
+                }
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    ..
+                } => {
+                    flush_message(&mut items, role, &mut parts);
+                    items.push(json!({
+                        "type": "function_call_output",
+                        "call_id": tool_use_id,
+                        "output": content,
+                    }));
+                }
+                // Reasoning items can't be replayed without the server's
+                // ids; the chain carries them when history is unchanged.
+                _ => {}
+            }
+        }
+        flush_message(&mut items, role, &mut parts);
+    }
+    items
+}
+
+fn flush_message(items: &mut Vec<Value>, role: &str, parts: &mut Vec<Value>) {
+    if !parts.is_empty() {
+        items.push(json!({
+            "type": "message",
+            "role": role,
+            "content": std::mem::take(parts),
+        }));
+    }
+}
+
+// ── Response ────────────────────────────────────────────────────
+
+/// A response object, as returned by `POST /responses` and carried by the
+/// terminal stream events
+#[derive(Debug, Deserialize)]
+pub(crate) struct ResponseObject {
+    pub id: String,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    output: Vec<Value>,
+    #[serde(default)]
+    usage: Option<ResponseUsage>,
+    #[serde(default)]
+    incomplete_details: Option<IncompleteDetails>,
+    #[serde(default)]
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ResponseUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+    #[serde(default)]
+    input_tokens_details: Option<InputTokensDetails>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct InputTokensDetails {
+    #[serde(default)]
+    cached_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct IncompleteDetails {
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+    #[serde(default)]
+    message: String,
+}
+
+impl ResponseObject {
+    fn token_usage(&self) -> TokenUsage {
+        let Some(ref usage) = self.usage else {
+            return TokenUsage::default();
+        };
+        let cached = usage
+            .input_tokens_details
+            .as_ref()
+            .map(|d| d.cached_tokens)
+            .unwrap_or(0);
+        TokenUsage {
+            input_tokens: usage.input_tokens.saturating_sub(cached),
+            output_tokens: usage.output_tokens,
+            cache_read_tokens: cached,
+            ..Default::default()
+        }
+    }
+
+    fn stop_reason(&self) -> StopReason {
+        if self
+            .output
+            .iter()
+            .any(|item| item["type"] == "function_call")
+        {
+            StopReason::ToolUse
+        } else if self.status.as_deref() == Some("incomplete")
+            && self
+                .incomplete_details
+                .as_ref()
+                .and_then(|d| d.reason.as_deref())
+                == Some("max_output_tokens")
+        {
+            StopReason::MaxTokens
+        } else {
+            StopReason::EndTurn
+        }
+    }
+}
+
+/// Convert a complete response into an `LLMResponse`
+pub(crate) fn parse_response(response: ResponseObject) -> LLMResponse {
+    let content = response
+        .output
+        .iter()
+        .flat_map(output_item_blocks)
+        .collect();
+    LLMResponse {
+        id: response.id.clone(),
+        model: response.model.clone(),
+        content,
+        stop_reason: Some(response.stop_reason()),
+        usage: response.token_usage(),
+    }
+}
+
+fn output_item_blocks(item: &Value) -> Vec<ContentBlock> {
+    match item["type"].as_str().unwrap_or_default() {
+        "message" => {
+            let mut text = message_parts(item)
+                .filter_map(|part| part["text"].as_str().or(part["refusal"].as_str()))
+                .collect::<String>();
+            text.push_str(&message_sources(item));
+            if text.is_empty() {
+                vec![]
+            } else {
+                vec![ContentBlock::Text { text }]
+            }
+        }
+        "reasoning" => {
+            let summary = item["summary"]
+                .as_array()
+                .map(|parts| {
+                    parts
+                        .iter()
+                        .filter_map(|p| p["text"].as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n\n")
+                })
+                .unwrap_or_default();
+            if summary.is_empty() {
+                vec![]
+            } else {
+                vec![ContentBlock::Thinking {
+                    thinking: summary,
+                    signature: None,
+                }]
+            }
+        }
+        "function_call" => vec![ContentBlock::ToolUse {
+            id: item["call_id"].as_str().unwrap_or_default().to_string(),
+            name: item["name"].as_str().unwrap_or_default().to_string(),
+            input: parse_arguments(item["arguments"].as_str().unwrap_or_default()),
+        }],
+        "image_generation_call" if item["result"].is_string() => vec![generated_image(item)],
+        _ => describe_builtin_item(item)
+            .map(|thinking| ContentBlock::Thinking {
+                thinking,
+                signature: None,
+            })
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn message_parts(item: &Value) -> impl Iterator<Item = &Value> {
+    item["content"].as_array().into_iter().flatten()
+}
+
+/// `url_citation` annotations rendered as a markdown source list
+fn message_sources(item: &Value) -> String {
+    let mut seen = HashSet::new();
+    let sources: Vec<String> = message_parts(item)
+        .filter_map(|part| part["annotations"].as_array())
+        .flatten()
+        .filter(|a| a["type"] == "url_citation")
+        .filter_map(|a| {
+            let url = a["url"].as_str()?;
+            seen.insert(url).then(|| {
+                let title = a["title"].as_str().filter(|t| !t.is_empty()).unwrap_or(url);
+                format!("- [{}]({})", title, url)
+            })
+        })
+        .collect();
+    if sources.is_empty() {
+        String::new()
+    } else {
+        format!("\n\nSources:\n{}", sources.join("\n"))
+    }
+}
+
+fn generated_image(item: &Value) -> ContentBlock {
+    let format = item["output_format"].as_str().unwrap_or("png");
+    ContentBlock::Image {
+        source: ImageSource::Base64 {
+            media_type: format!("image/{}", format),
+            data: item["result"].as_str().unwrap_or_default().to_string(),
+        },
+    }
+}
+
+fn parse_arguments(arguments: &str) -> Value {
+    serde_json::from_str(arguments).unwrap_or_else(|_| json!({}))
+}
+
+/// One-line trace of a built-in tool the server ran on our behalf
+fn describe_builtin_item(item: &Value) -> Option<String> {
+    let kind = item["type"].as_str()?;
+    let line = match kind {
+        "web_search_call" => {
+            let action = &item["action"];
+            match action["type"].as_str() {
+                Some("open_page") => {
+                    format!("Opened {}", action["url"].as_str().unwrap_or("a page"))
+                }
+                Some("find") => format!(
+                    "Searched {} for \"{}\"",
+                    action["url"].as_str().unwrap_or("a page"),
+                    action["pattern"].as_str().unwrap_or_default()
+                ),
+                _ => match action["query"].as_str() {
+                    Some(q) => format!("Searched the web: {}", q),
+                    None => "Searched the web".to_string(),
+                },
+            }
+        }
+        "file_search_call" => {
+            let queries: Vec<&str> = item["queries"]
+                .as_array()
+                .map(|q| q.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            format!("Searched files: {}", queries.join(", "))
+        }
+        "code_interpreter_call" => {
+            let mut line = format!(
+                "Ran code:\n```python\n{}\n```",
+                item["code"].as_str().unwrap_or_default().trim_end()
+            );
+            for output in item["outputs"].as_array().into_iter().flatten() {
+                if let Some(logs) = output["logs"].as_str().filter(|l| !l.is_empty()) {
+                    line.push('\n');
+                    line.push_str(logs.trim_end());
+                }
+            }
+            line
+        }
+        "image_generation_call" => "Generated an image".to_string(),
+        "mcp_call" => {
+            let mut line = format!(
+                "Called {}/{}",
+                item["server_label"].as_str().unwrap_or("mcp"),
+                item["name"].as_str().unwrap_or_default()
+            );
+            if let Some(err) = item["error"].as_str() {
+                line.push_str(&format!(" (failed: {})", err));
+            }
+            line
+        }
+        "mcp_list_tools" => format!(
+            "Listed tools on {}",
+            item["server_label"].as_str().unwrap_or("mcp")
+        ),
+        other => format!("Ran {}", other.trim_end_matches("_call").replace('_', " ")),
+    };
+    Some(line)
+}
+
+// ── Streaming ───────────────────────────────────────────────────
+
+/// Translates the Responses API SSE grammar into `StreamEvent`s. Each output
+/// item keeps its `output_index` as the content block index.
+pub(crate) struct ResponsesStream {
+    buf: Vec<u8>,
+    started: HashSet<usize>,
+    /// Function calls whose arguments arrived as deltas
+    streamed_args: HashSet<usize>,
+    link: Option<PendingLink>,
+}
+
+impl ResponsesStream {
+    pub(crate) fn new(link: Option<PendingLink>) -> Self {
+        Self {
+            buf: Vec::new(),
+            started: HashSet::new(),
+            streamed_args: HashSet::new(),
+            link,
+        }
+    }
+
+    /// Feed raw bytes; returns the events for every complete `data:` line
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Vec<Result<StreamEvent>> {
+        self.buf.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+            match serde_json::from_str::<Value>(data.trim()) {
+                Ok(event) => events.extend(self.handle(&event)),
+                Err(e) => tracing::debug!("[RESPONSES_STREAM] unparseable event: {}", e),
+            }
+        }
+        events
+    }
+
+    fn start(&mut self, index: usize, content_block: ContentBlock) -> Vec<Result<StreamEvent>> {
+        if self.started.insert(index) {
+            vec![Ok(StreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            })]
+        } else {
+            vec![]
+        }
+    }
+
+    fn delta(&mut self, index: usize, delta: ContentDelta) -> Vec<Result<StreamEvent>> {
+        // Deltas on an unopened index are dropped downstream
+        let mut events = self.start(
+            index,
+            ContentBlock::Text {
+                text: String::new(),
+            },
+        );
+        events.push(Ok(StreamEvent::ContentBlockDelta { index, delta }));
+        events
+    }
+
+    fn stop(&mut self, index: usize) -> Vec<Result<StreamEvent>> {
+        if self.started.contains(&index) {
+            vec![Ok(StreamEvent::ContentBlockStop { index })]
+        } else {
+            vec![]
+        }
+    }
+
+    fn handle(&mut self, event: &Value) -> Vec<Result<StreamEvent>> {
+        let index = event["output_index"].as_u64().unwrap_or(0) as usize;
+        let text = || event["delta"].as_str().unwrap_or_default().to_string();
+        match event["type"].as_str().unwrap_or_default() {
+            "response.created" => {
+                let response = &event["response"];
+                vec![Ok(StreamEvent::MessageStart {
+                    message: StreamMessage {
+                        id: response["id"].as_str().unwrap_or_default().to_string(),
+                        model: response["model"].as_str().unwrap_or_default().to_string(),
+                        role: Role::Assistant,
+                        usage: TokenUsage::default(),
+                    },
+                })]
+            }
+            "response.output_item.added" => {
+                let item = &event["item"];
+                match item["type"].as_str().unwrap_or_default() {
+                    "function_call" => self.start(
+                        index,
+                        ContentBlock::ToolUse {
+                            id: item["call_id"].as_str().unwrap_or_default().to_string(),
+                            name: item["name"].as_str().unwrap_or_default().to_string(),
+                            input: json!({}),
+                        },
+                    ),
+                    // Reasoning rides on a text block as display-only deltas,
+                    // same as the chat-completions stream
+                    "message" | "reasoning" => self.start(
+                        index,
+                        ContentBlock::Text {
+                            text: String::new(),
+                        },
+                    ),
+                    _ => vec![],
+                }
+            }
+            "response.output_text.delta" | "response.refusal.delta" => {
+                self.delta(index, ContentDelta::TextDelta { text: text() })
+            }
+            "response.reasoning_summary_text.delta" => {
+                self.delta(index, ContentDelta::ReasoningDelta { text: text() })
+            }
+            "response.reasoning_summary_part.added"
+                if event["summary_index"].as_u64() > Some(0) =>
+            {
+                self.delta(
+                    index,
+                    ContentDelta::ReasoningDelta {
+                        text: "\n\n".to_string(),
+                    },
+                )
+            }
+            "response.function_call_arguments.delta" => {
+                self.streamed_args.insert(index);
+                vec![Ok(StreamEvent::ContentBlockDelta {
+                    index,
+                    delta: ContentDelta::InputJsonDelta {
+                        partial_json: text(),
+                    },
+                })]
+            }
+            "response.output_item.done" => self.item_done(index, &event["item"]),
+            "response.completed" | "response.incomplete" => self.finish(&event["response"]),
+            "response.failed" => {
+                let message = event["response"]["error"]["message"]
+                    .as_str()
+                    .unwrap_or("response failed");
+                vec![Ok(StreamEvent::Error {
+                    error: message.to_string(),
+                })]
+            }
+            "error" => vec![Ok(StreamEvent::Error {
+                error: event["message"]
+                    .as_str()
+                    .unwrap_or("stream error")
+                    .to_string(),
+            })],
+            _ => vec![],
+        }
+    }
+
+    fn item_done(&mut self, index: usize, item: &Value) -> Vec<Result<StreamEvent>> {
+        let mut events = Vec::new();
+        match item["type"].as_str().unwrap_or_default() {
+            "message" => {
+                let sources = message_sources(item);
+                if !sources.is_empty() {
+                    events.extend(self.delta(index, ContentDelta::TextDelta { text: sources }));
+                }
+            }
+            "reasoning" => {}
+            "function_call" => {
+                events.extend(self.start(
+                    index,
+                    ContentBlock::ToolUse {
+                        id: item["call_id"].as_str().unwrap_or_default().to_string(),
+                        name: item["name"].as_str().unwrap_or_default().to_string(),
+                        input: json!({}),
+                    },
+                ));
+                if !self.streamed_args.contains(&index) {
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index,
+                        delta: ContentDelta::InputJsonDelta {
+                            partial_json: item["arguments"].as_str().unwrap_or("{}").to_string(),
+                        },
+                    }));
+                }
+            }
+            "image_generation_call" if item["result"].is_string() => {
+                events.extend(self.start(index, generated_image(item)));
+            }
+            _ => {
+                if let Some(line) = describe_builtin_item(item) {
+                    events.extend(self.delta(
+                        index,
+                        ContentDelta::ReasoningDelta {
+                            text: format!("{}\n", line),
+                        },
+                    ));
+                }
+            }
+        }
+        events.extend(self.stop(index));
+        events
+    }
+
+    fn finish(&mut self, response: &Value) -> Vec<Result<StreamEvent>> {
+        let response = match ResponseObject::deserialize(response) {
+            Ok(r) => r,
+            Err(e) => {
+                return vec![Err(ProviderError::StreamError(format!(
+                    "Invalid terminal response event: {}",
+                    e
+                )))];
+            }
+        };
+        if let Some(link) = self.link.take() {
+            link.commit(&response.id);
+        }
+        if let Some(ref err) = response.error
+            && !err.message.is_empty()
+        {
+            tracing::warn!("Responses API reported an error: {}", err.message);
+        }
+        vec![
+            Ok(StreamEvent::MessageDelta {
+                delta: MessageDelta {
+                    stop_reason: Some(response.stop_reason()),
+                    stop_sequence: None,
+                },
+                usage: response.token_usage(),
+            }),
+            Ok(StreamEvent::MessageStop),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_request(messages: Vec<Message>, session: Uuid) -> LLMRequest {
+        let mut request = LLMRequest::new("gpt-5", messages);
+        request.session_id = Some(session);
+        request
+    }
+
+    #[test]
+    fn test_responses_url() {
+        assert_eq!(
+            responses_url("https://api.openai.com/v1/chat/completions"),
+            "https://api.openai.com/v1/responses"
+        );
+        assert_eq!(
+            responses_url("http://localhost:8000/v1/"),
+            "http://localhost:8000/v1/responses"
+        );
+        assert_eq!(
+            responses_url("https://gw.example/v1/responses"),
+            "https://gw.example/v1/responses"
+        );
+    }
+
+    #[test]
+    fn test_reasoning_model_detection() {
+        assert!(is_reasoning_model("gpt-5-mini"));
+        assert!(is_reasoning_model("o4-mini"));
+        assert!(is_reasoning_model("openai/o3"));
+        assert!(!is_reasoning_model("gpt-5-chat-latest"));
+        assert!(!is_reasoning_model("gpt-4.1"));
+    }
+
+    #[test]
+    fn test_body_maps_items_and_tools() {
+        let request = LLMRequest::new(
+            "gpt-4.1",
+            vec![
+                Message::user("read a.txt"),
+                Message {
+                    role: Role::Assistant,
+                    content: vec![
+                        ContentBlock::Thinking {
+                            thinking: "plan".into(),
+                            signature: None,
+                        },
+                        ContentBlock::Text {
+                            text: "Reading".into(),
+                        },
+                        ContentBlock::ToolUse {
+                            id: "call_1".into(),
+                            name: "read_file".into(),
+                            input: json!({"path": "a.txt"}),
+                        },
+                    ],
+                },
+                Message {
+                    role: Role::User,
+                    content: vec![ContentBlock::ToolResult {
+                        tool_use_id: "call_1".into(),
+                        content: "hello".into(),
+                        is_error: None,
+                    }],
+                },
+            ],
+        )
+        .with_system("be brief")
+        .with_temperature(0.2)
+        .with_tools(vec![Tool {
+            name: "read_file".into(),
+            description: "Read a file".into(),
+            input_schema: json!({"type": "object"}),
+        }]);
+
+        let body = build_request_body(&request, None, true);
+        assert_eq!(body["instructions"], "be brief");
+        assert_eq!(body["temperature"], json!(0.2_f32));
+        assert!(body.get("reasoning").is_none());
+        assert_eq!(body["tools"][0]["strict"], false);
+        assert_eq!(
+            body["input"],
+            json!([
+                {"type": "message", "role": "user", "content": [{"type": "input_text", "text": "read a.txt"}]},
+                {"type": "message", "role": "assistant", "content": [{"type": "output_text", "text": "Reading"}]},
+                {"type": "function_call", "call_id": "call_1", "name": "read_file", "arguments": "{\"path\":\"a.txt\"}"},
+                {"type": "function_call_output", "call_id": "call_1", "output": "hello"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_chain_resumes_only_on_unchanged_history() {
+        let chain = Arc::new(ResponseChain::default());
+        let session = Uuid::new_v4();
+        let first = session_request(vec![Message::user("hi")], session);
+        PendingLink::new(&chain, &first).unwrap().commit("resp_1");
+
+        let mut history = vec![
+            Message::user("hi"),
+            Message::assistant("hello"),
+            Message::user("again"),
+        ];
+        assert_eq!(
+            chain.resume(session, "gpt-5", &history),
+            Some(("resp_1".to_string(), 2))
+        );
+        // Different model or rewritten history breaks the chain
+        assert_eq!(chain.resume(session, "gpt-5-mini", &history), None);
+        history[0] = Message::user("edited");
+        assert_eq!(chain.resume(session, "gpt-5", &history), None);
+    }
+
+    #[test]
+    fn test_parse_response_maps_builtin_items() {
+        let response: ResponseObject = serde_json::from_value(json!({
+            "id": "resp_1",
+            "model": "gpt-5",
+            "status": "completed",
+            "output": [
+                {"type": "reasoning", "summary": [{"type": "summary_text", "text": "Look it up"}]},
+                {"type": "web_search_call", "status": "completed", "action": {"type": "search", "query": "rust 2024"}},
+                {"type": "message", "role": "assistant", "content": [{
+                    "type": "output_text",
+                    "text": "Rust 2024 shipped.",
+                    "annotations": [{"type": "url_citation", "url": "https://blog.rust-lang.org", "title": "Rust Blog"}]
+                }]}
+            ],
+            "usage": {"input_tokens": 100, "output_tokens": 20, "input_tokens_details": {"cached_tokens": 60}}
+        }))
+        .unwrap();
+
+        let parsed = parse_response(response);
+        assert!(matches!(
+            &parsed.content[0],
+            ContentBlock::Thinking { thinking, .. } if thinking == "Look it up"
+        ));
+        assert!(matches!(
+            &parsed.content[1],
+            ContentBlock::Thinking { thinking, .. } if thinking == "Searched the web: rust 2024"
+        ));
+        assert!(matches!(
+            &parsed.content[2],
+            ContentBlock::Text { text } if text.ends_with("- [Rust Blog](https://blog.rust-lang.org)")
+        ));
+        assert_eq!(parsed.stop_reason, Some(StopReason::EndTurn));
+        assert_eq!(parsed.usage.input_tokens, 40);
+        assert_eq!(parsed.usage.cache_read_tokens, 60);
+    }
+
+    #[test]
+    fn test_stale_chain_error_detection() {
+        let stale = ProviderError::ApiError {
+            status: 400,
+            message: "Previous response with id 'resp_x' not found.".into(),
+            error_type: Some("invalid_request_error".into()),
+        };
+        assert!(is_stale_chain_error(&stale));
+        let other = ProviderError::ApiError {
+            status: 400,
+            message: "Invalid 'input'".into(),
+            error_type: None,
+        };
+        assert!(!is_stale_chain_error(&other));
+    }
+}
@@ -73,6 +73,7 @@ impl OpenCodeCliProvider {
                             Some(format!("<thinking>{}</thinking>", thinking))
                         }
                     }
+                    ContentBlock::RedactedThinking { .. } => None,
                     ContentBlock::Image { source } => {
                         // CLI mode cannot process images inline.
                         // Save to temp file and tell agent to use analyze_image.
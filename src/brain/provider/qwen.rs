@@ -156,6 +156,8 @@ fn add_cache_control_to_content(content: &serde_json::Value) -> serde_json::Valu
 ///   4. No field stripping. `temperature`, `top_p`, `tool_choice`, etc.
 ///      pass through. DashScope's fingerprint expects these to be present
 ///      when the client supplies them. `max_tokens` is never synthesized.
+///   5. **tool_choice** — DashScope only accepts `auto`, `none` or a named
+///      function; `"required"` is downgraded to `"auto"`.
 pub fn qwen_body_transform(mut body: serde_json::Value) -> serde_json::Value {
     let obj = match body.as_object_mut() {
         Some(o) => o,
@@ -227,6 +229,11 @@ pub fn qwen_body_transform(mut body: serde_json::Value) -> serde_json::Value {
         );
     }
 
+    // ── 5. tool_choice dialect ──────────────────────────────────────────
+    if obj.get("tool_choice").and_then(|v| v.as_str()) == Some("required") {
+        obj.insert("tool_choice".to_string(), serde_json::json!("auto"));
+    }
+
     body
 }
 
@@ -292,6 +299,19 @@ mod tests {
         })
     }
 
+    #[test]
+    fn body_transform_downgrades_required_tool_choice() {
+        let mut body = sample_body();
+        body["tool_choice"] = serde_json::json!("required");
+        let out = qwen_body_transform(body);
+        assert_eq!(out["tool_choice"], serde_json::json!("auto"));
+
+        let mut body = sample_body();
+        let named = serde_json::json!({"type": "function", "function": {"name": "last_tool"}});
+        body["tool_choice"] = named.clone();
+        assert_eq!(qwen_body_transform(body)["tool_choice"], named);
+    }
+
     #[test]
     fn body_transform_cache_control_streaming_system_and_last_message() {
         let out = qwen_body_transform(sample_body());
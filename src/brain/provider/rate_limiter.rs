@@ -9,11 +9,19 @@
 //! Each `:free` model gets its own independent rate limiter bucket, shared
 //! across all provider instances and sessions using that exact model.
 //! This prevents 429s from concurrent sessions hammering the same endpoint.
+//!
+//! ## Configured Budgets
+//!
+//! `[providers.<name>.rate_limit]` declares requests- and tokens-per-minute
+//! budgets, optionally per model. [`RATE_BUDGETS`] keeps one pair of
+//! [`TokenBucket`]s per provider and per model for the whole process; the
+//! agent reserves against them before every LLM call and settles the token
+//! count once the real usage is known.
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
 
 /// Fixed reference point for nanosecond timestamps.
@@ -149,3 +157,169 @@ impl GlobalRateLimiter {
         limiter
     }
 }
+
+/// Continuously-refilling bucket holding up to `capacity` units, refilled at
+/// `capacity` per minute. Reservations may overdraw it: the caller waits
+/// until the debt is repaid, which queues concurrent callers in order.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket for a per-minute budget.
+    pub fn per_minute(capacity: u32) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        Self {
+            capacity,
+            available: capacity,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.capacity / 60.0).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Take `cost` units and return how long until the bucket is back out
+    /// of debt. A single reservation never costs more than the capacity, so
+    /// an oversized prompt waits at most one minute instead of forever.
+    pub fn reserve(&mut self, cost: f64, now: Instant) -> Duration {
+        self.refill(now);
+        self.available -= cost.min(self.capacity);
+        if self.available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available * 60.0 / self.capacity)
+        }
+    }
+
+    /// Charge (`delta < 0`) or refund (`delta > 0`) units after the fact.
+    pub fn adjust(&mut self, delta: f64) {
+        self.refill(Instant::now());
+        self.available = (self.available + delta).min(self.capacity);
+    }
+}
+
+/// Requests/tokens per minute for one provider or provider/model pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RateBudget {
+    pub rpm: Option<u32>,
+    pub tpm: Option<u32>,
+}
+
+struct BudgetState {
+    budget: RateBudget,
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+}
+
+impl BudgetState {
+    fn new(budget: RateBudget) -> Self {
+        Self {
+            budget,
+            requests: budget.rpm.map(TokenBucket::per_minute),
+            tokens: budget.tpm.map(TokenBucket::per_minute),
+        }
+    }
+}
+
+/// Process-wide configured budgets, keyed by provider name and by
+/// `provider/model`. Names are matched case-insensitively.
+pub static RATE_BUDGETS: std::sync::LazyLock<RateBudgets> =
+    std::sync::LazyLock::new(RateBudgets::new);
+
+/// Registry of configured rate budgets. See [`RATE_BUDGETS`].
+pub struct RateBudgets {
+    budgets: Mutex<HashMap<String, BudgetState>>,
+}
+
+impl RateBudgets {
+    pub(crate) fn new() -> Self {
+        Self {
+            budgets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn provider_key(provider: &str) -> String {
+        provider.to_lowercase()
+    }
+
+    fn model_key(provider: &str, model: &str) -> String {
+        format!("{}/{}", provider.to_lowercase(), model.to_lowercase())
+    }
+
+    /// Replace the configured budgets with `(provider, model, budget)`
+    /// entries (`model = None` for a provider-wide budget). Budgets that are
+    /// unchanged keep their bucket state, so a config reload doesn't hand
+    /// every caller a fresh minute.
+    pub fn configure(
+        &self,
+        entries: impl IntoIterator<Item = (String, Option<String>, RateBudget)>,
+    ) {
+        let mut budgets = self.budgets.lock().unwrap_or_else(|e| e.into_inner());
+        let mut next = HashMap::new();
+        for (provider, model, budget) in entries {
+            if budget.rpm.is_none() && budget.tpm.is_none() {
+                continue;
+            }
+            let key = match model {
+                Some(model) => Self::model_key(&provider, &model),
+                None => Self::provider_key(&provider),
+            };
+            let state = match budgets.remove(&key) {
+                Some(state) if state.budget == budget => state,
+                _ => BudgetState::new(budget),
+            };
+            next.insert(key, state);
+        }
+        *budgets = next;
+    }
+
+    /// Reserve one request and `tokens` tokens against every budget that
+    /// applies, returning how long to wait before sending. Zero when the
+    /// call fits (or no budget is configured).
+    pub fn reserve(&self, provider: &str, model: &str, tokens: u32) -> Duration {
+        let now = Instant::now();
+        let mut budgets = self.budgets.lock().unwrap_or_else(|e| e.into_inner());
+        let mut wait = Duration::ZERO;
+        for key in [
+            Self::provider_key(provider),
+            Self::model_key(provider, model),
+        ] {
+            let Some(state) = budgets.get_mut(&key) else {
+                continue;
+            };
+            if let Some(bucket) = state.requests.as_mut() {
+                wait = wait.max(bucket.reserve(1.0, now));
+            }
+            if let Some(bucket) = state.tokens.as_mut() {
+                wait = wait.max(bucket.reserve(f64::from(tokens), now));
+            }
+        }
+        wait
+    }
+
+    /// Correct a reservation once the provider reported real usage:
+    /// charges the difference when `actual` exceeded the estimate and
+    /// refunds it otherwise.
+    pub fn settle(&self, provider: &str, model: &str, estimated: u32, actual: u32) {
+        let delta = f64::from(estimated) - f64::from(actual);
+        if delta == 0.0 {
+            return;
+        }
+        let mut budgets = self.budgets.lock().unwrap_or_else(|e| e.into_inner());
+        for key in [
+            Self::provider_key(provider),
+            Self::model_key(provider, model),
+        ] {
+            if let Some(bucket) = budgets.get_mut(&key).and_then(|s| s.tokens.as_mut()) {
+                bucket.adjust(delta);
+            }
+        }
+    }
+}
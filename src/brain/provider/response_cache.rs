@@ -0,0 +1,496 @@
+//! Response Cache
+//!
+//! Exact-match cache for provider calls. Call sites opt in per request with
+//! `LLMRequest::with_cache_ttl`; `CachingProvider` hashes the normalized
+//! request (provider, host, model, system, messages, tools and sampling
+//! settings) and, while a stored reply is younger than the TTL, answers
+//! `complete()` with the stored `LLMResponse` or replays the stored
+//! `stream()` events from SQLite.
+//! Requests without a TTL go straight to the wrapped provider.
+//!
+//! Replayed usage carries `TokenUsage::response_cache_hit` so the agent can
+//! keep it out of billed totals and record the saving in the usage ledger.
+
+use super::batch::{BatchRequest, BatchResult, BatchStatus};
+use super::error::Result;
+use super::r#trait::{Provider, ProviderStream};
+use super::types::{LLMRequest, LLMResponse, StreamEvent, TokenUsage};
+use crate::db::{Pool, interact_err};
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::StreamExt;
+use rusqlite::{OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Size limits applied when storing replies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheLimits {
+    /// Maximum number of stored replies
+    pub max_entries: usize,
+    /// Replies whose payload exceeds this are not stored
+    pub max_entry_bytes: usize,
+    /// Least recently used replies are evicted past this total
+    pub max_total_bytes: usize,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 1000,
+            max_entry_bytes: 256 * 1024,
+            max_total_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl From<&crate::config::ResponseCacheConfig> for CacheLimits {
+    fn from(config: &crate::config::ResponseCacheConfig) -> Self {
+        Self {
+            max_entries: config.max_entries,
+            max_entry_bytes: config.max_entry_kb.saturating_mul(1024),
+            max_total_bytes: config.max_total_mb.saturating_mul(1024 * 1024),
+        }
+    }
+}
+
+/// A stored reply, keyed by the method that produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+enum CachedReply {
+    Complete { response: LLMResponse },
+    Stream { events: Vec<StreamEvent> },
+}
+
+impl CachedReply {
+    /// Usage reported by the original call (for the entry's bookkeeping).
+    fn usage(&self) -> TokenUsage {
+        match self {
+            Self::Complete { response } => response.usage,
+            Self::Stream { events } => {
+                let mut usage = TokenUsage::default();
+                for event in events {
+                    match event {
+                        StreamEvent::MessageStart { message } => {
+                            usage.input_tokens = usage.input_tokens.max(message.usage.input_tokens);
+                        }
+                        StreamEvent::MessageDelta { usage: delta, .. } => {
+                            usage.input_tokens = usage.input_tokens.max(delta.input_tokens);
+                            usage.output_tokens = usage.output_tokens.max(delta.output_tokens);
+                        }
+                        _ => {}
+                    }
+                }
+                usage
+            }
+        }
+    }
+}
+
+/// Cache key for `request` answered by `provider` through `method`
+/// ("complete" or "stream"). The provider's name and host are part of the
+/// key, so two endpoints serving the same model name never share replies.
+/// Fields that only route the call (session, working directory, metadata)
+/// are left out.
+pub fn cache_key(provider: &dyn Provider, request: &LLMRequest, method: &str) -> String {
+    let normalized = serde_json::json!({
+        "provider": provider.name(),
+        "base_url": provider.base_url(),
+        "method": method,
+        "model": request.model,
+        "system": request.system,
+        "messages": request.messages,
+        "tools": request.tools,
+        "tool_choice": request.tool_choice,
+        "temperature": request.temperature,
+        "max_tokens": request.max_tokens,
+        "thinking_budget": request.thinking_budget,
+        "response_format": request.response_format,
+    });
+    let digest = Sha256::digest(normalized.to_string().as_bytes());
+    digest.iter().fold(String::with_capacity(64), |mut s, b| {
+        let _ = write!(s, "{:02x}", b);
+        s
+    })
+}
+
+/// SQLite-backed store for cached replies
+pub struct ResponseCache {
+    pool: Pool,
+    limits: CacheLimits,
+}
+
+impl ResponseCache {
+    pub fn new(pool: Pool, limits: CacheLimits) -> Self {
+        Self { pool, limits }
+    }
+
+    /// Stored reply for `key` if it is at most `ttl_secs` old. Bumps the
+    /// entry's hit count and recency on success.
+    async fn get(&self, key: &str, ttl_secs: u64) -> anyhow::Result<Option<CachedReply>> {
+        let key = key.to_string();
+        let now = chrono::Utc::now().timestamp();
+        let oldest = now.saturating_sub(ttl_secs.min(i64::MAX as u64) as i64);
+        let payload = self
+            .pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| -> rusqlite::Result<Option<String>> {
+                let payload: Option<String> = conn
+                    .query_row(
+                        "SELECT payload FROM response_cache WHERE key = ?1 AND created_at >= ?2",
+                        params![key, oldest],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                if payload.is_some() {
+                    conn.execute(
+                        "UPDATE response_cache SET hits = hits + 1, last_hit_at = ?2 WHERE key = ?1",
+                        params![key, now],
+                    )?;
+                }
+                Ok(payload)
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to read response cache")?;
+
+        match payload {
+            Some(payload) => Ok(Some(
+                serde_json::from_str(&payload).context("Corrupt response cache entry")?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Store `reply` under `key`, replacing any older entry, then evict
+    /// down to the configured limits. Oversized replies are skipped.
+    async fn put(
+        &self,
+        key: &str,
+        provider: &str,
+        model: &str,
+        reply: &CachedReply,
+    ) -> anyhow::Result<()> {
+        let payload = serde_json::to_string(reply).context("Failed to serialize reply")?;
+        if payload.len() > self.limits.max_entry_bytes {
+            tracing::debug!(
+                "Response cache: reply of {} bytes exceeds the {} byte entry limit — not stored",
+                payload.len(),
+                self.limits.max_entry_bytes
+            );
+            return Ok(());
+        }
+        let usage = reply.usage();
+        let key = key.to_string();
+        let provider = provider.to_string();
+        let model = model.to_string();
+        let limits = self.limits;
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| -> rusqlite::Result<()> {
+                let size = payload.len() as i64;
+                conn.execute(
+                    "INSERT OR REPLACE INTO response_cache \
+                     (key, provider, model, payload, size_bytes, input_tokens, output_tokens) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        key,
+                        provider,
+                        model,
+                        payload,
+                        size,
+                        usage.input_tokens,
+                        usage.output_tokens
+                    ],
+                )?;
+                // Most recently used first; rowid breaks ties within a second
+                // (INSERT OR REPLACE assigns a fresh one).
+                conn.execute(
+                    "DELETE FROM response_cache WHERE key IN ( \
+                       SELECT key FROM ( \
+                         SELECT key, \
+                           ROW_NUMBER() OVER w AS n, \
+                           SUM(size_bytes) OVER w AS running \
+                         FROM response_cache \
+                         WINDOW w AS (ORDER BY COALESCE(last_hit_at, created_at) DESC, rowid DESC) \
+                       ) WHERE n > ?1 OR running > ?2 \
+                     )",
+                    params![
+                        limits.max_entries as i64,
+                        limits.max_total_bytes.min(i64::MAX as usize) as i64
+                    ],
+                )?;
+                Ok(())
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to write response cache")
+    }
+
+    /// Number of stored replies and their total payload size in bytes
+    pub async fn stats(&self) -> anyhow::Result<(i64, i64)> {
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(|conn| {
+                conn.query_row(
+                    "SELECT COUNT(*), COALESCE(SUM(size_bytes), 0) FROM response_cache",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to query response cache")
+    }
+
+    /// Lookup that never fails the call: errors are logged and count as a miss.
+    async fn lookup(&self, key: &str, ttl_secs: u64) -> Option<CachedReply> {
+        match self.get(key, ttl_secs).await {
+            Ok(reply) => reply,
+            Err(e) => {
+                tracing::warn!("Response cache lookup failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Store that never fails the call: errors are logged.
+    async fn store(&self, key: &str, provider: &str, model: &str, reply: &CachedReply) {
+        if let Err(e) = self.put(key, provider, model, reply).await {
+            tracing::warn!("Response cache store failed: {}", e);
+        }
+    }
+}
+
+/// Flag replayed usage as served from the cache.
+fn mark_hit(event: StreamEvent) -> StreamEvent {
+    match event {
+        StreamEvent::MessageStart { mut message } => {
+            message.usage.response_cache_hit = true;
+            StreamEvent::MessageStart { message }
+        }
+        StreamEvent::MessageDelta { delta, mut usage } => {
+            usage.response_cache_hit = true;
+            StreamEvent::MessageDelta { delta, usage }
+        }
+        other => other,
+    }
+}
+
+/// Provider wrapper that answers opted-in requests from a `ResponseCache`.
+pub struct CachingProvider {
+    inner: Arc<dyn Provider>,
+    cache: Arc<ResponseCache>,
+}
+
+impl CachingProvider {
+    pub fn new(inner: Arc<dyn Provider>, cache: Arc<ResponseCache>) -> Self {
+        Self { inner, cache }
+    }
+
+    /// TTL if this request should go through the cache. CLI providers run
+    /// tools themselves, so replaying them would skip real side effects.
+    fn ttl(&self, request: &LLMRequest) -> Option<u64> {
+        request
+            .cache_ttl
+            .filter(|_| !self.inner.cli_handles_tools())
+    }
+}
+
+#[async_trait]
+impl Provider for CachingProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        let Some(ttl) = self.ttl(&request) else {
+            return self.inner.complete(request).await;
+        };
+        let key = cache_key(&*self.inner, &request, "complete");
+        if let Some(CachedReply::Complete { mut response }) = self.cache.lookup(&key, ttl).await {
+            tracing::debug!("Response cache hit for {} ({})", request.model, &key[..12]);
+            response.usage.response_cache_hit = true;
+            return Ok(response);
+        }
+
+        let model = request.model.clone();
+        let response = self.inner.complete(request).await?;
+        let reply = CachedReply::Complete {
+            response: response.clone(),
+        };
+        self.cache
+            .store(&key, self.inner.name(), &model, &reply)
+            .await;
+        Ok(response)
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        let Some(ttl) = self.ttl(&request) else {
+            return self.inner.stream(request).await;
+        };
+        let key = cache_key(&*self.inner, &request, "stream");
+        if let Some(CachedReply::Stream { events }) = self.cache.lookup(&key, ttl).await {
+            tracing::debug!("Response cache hit for {} ({})", request.model, &key[..12]);
+            let items: Vec<Result<StreamEvent>> =
+                events.into_iter().map(mark_hit).map(Ok).collect();
+            return Ok(Box::pin(futures::stream::iter(items)));
+        }
+
+        let model = request.model.clone();
+        let stream = self.inner.stream(request).await?;
+        // Tee events and store the reply when MessageStop passes through —
+        // consumers stop reading there. A stream that errors is not stored.
+        let events = Arc::new(Mutex::new(Some(Vec::new())));
+        let cache = self.cache.clone();
+        let provider = self.inner.name().to_string();
+        let tee = stream.then(move |item| {
+            let recorded = {
+                let mut guard = events.lock().unwrap_or_else(|e| e.into_inner());
+                match &item {
+                    Ok(event) => {
+                        if let Some(buf) = guard.as_mut() {
+                            buf.push(event.clone());
+                        }
+                        if matches!(event, StreamEvent::MessageStop) {
+                            guard.take()
+                        } else {
+                            None
+                        }
+                    }
+                    Err(_) => {
+                        *guard = None;
+                        None
+                    }
+                }
+            };
+            let cache = cache.clone();
+            let key = key.clone();
+            let provider = provider.clone();
+            let model = model.clone();
+            async move {
+                if let Some(events) = recorded {
+                    cache
+                        .store(&key, &provider, &model, &CachedReply::Stream { events })
+                        .await;
+                }
+                item
+            }
+        });
+        Ok(Box::pin(tee))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.inner.supports_tools()
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.inner.supports_vision()
+    }
+
+    fn supports_response_format(&self) -> bool {
+        self.inner.supports_response_format()
+    }
+
+    fn cli_handles_tools(&self) -> bool {
+        self.inner.cli_handles_tools()
+    }
+
+    fn supports_batch(&self) -> bool {
+        self.inner.supports_batch()
+    }
+
+    async fn submit_batch(&self, requests: Vec<BatchRequest>) -> Result<String> {
+        self.inner.submit_batch(requests).await
+    }
+
+    async fn poll_batch(&self, batch_id: &str) -> Result<BatchStatus> {
+        self.inner.poll_batch(batch_id).await
+    }
+
+    async fn fetch_batch_results(&self, batch_id: &str) -> Result<Vec<BatchResult>> {
+        self.inner.fetch_batch_results(batch_id).await
+    }
+
+    fn cli_manages_context(&self) -> bool {
+        self.inner.cli_manages_context()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn base_url(&self) -> Option<&str> {
+        self.inner.base_url()
+    }
+
+    fn default_model(&self) -> &str {
+        self.inner.default_model()
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        self.inner.supported_models()
+    }
+
+    async fn fetch_models(&self) -> Vec<String> {
+        self.inner.fetch_models().await
+    }
+
+    fn validate_model(&self, model: &str) -> bool {
+        self.inner.validate_model(model)
+    }
+
+    fn context_window(&self, model: &str) -> Option<u32> {
+        self.inner.context_window(model)
+    }
+
+    fn configured_context_window(&self) -> Option<u32> {
+        self.inner.configured_context_window()
+    }
+
+    fn force_next_fallback(&self, reason: &str) -> bool {
+        self.inner.force_next_fallback(reason)
+    }
+
+    fn take_swap_event(&self) -> Option<super::fallback::SwapEvent> {
+        self.inner.take_swap_event()
+    }
+
+    fn active_subprovider_name(&self) -> Option<String> {
+        self.inner.active_subprovider_name()
+    }
+
+    fn active_subprovider_model(&self) -> Option<String> {
+        self.inner.active_subprovider_model()
+    }
+
+    fn calculate_cost(&self, model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        self.inner
+            .calculate_cost(model, input_tokens, output_tokens)
+    }
+
+    fn calculate_cost_with_cache(
+        &self,
+        model: &str,
+        input_tokens: u32,
+        output_tokens: u32,
+        cache_creation_tokens: u32,
+        cache_read_tokens: u32,
+    ) -> f64 {
+        self.inner.calculate_cost_with_cache(
+            model,
+            input_tokens,
+            output_tokens,
+            cache_creation_tokens,
+            cache_read_tokens,
+        )
+    }
+}
@@ -0,0 +1,463 @@
+//! Structured (JSON) output.
+//!
+//! `LLMRequest::response_format` asks for a reply that is JSON, optionally
+//! matching a JSON Schema. Each provider maps it onto its own mechanism:
+//!
+//! - OpenAI-compatible chat: `response_format` (`json_object` / `json_schema`)
+//! - OpenAI Responses API: `text.format`
+//! - Gemini (and Vertex Gemini): `responseMimeType` + `responseJsonSchema`
+//! - Ollama: `format` (`"json"` or the schema itself)
+//! - Anthropic / Bedrock (and Vertex Claude): a single tool whose input is
+//!   the schema, forced with `tool_choice`; the tool call is unwrapped back
+//!   into a text block holding the JSON
+//!
+//! Native modes still can't be trusted everywhere (proxies drop the field,
+//! non-strict schemas drift), so [`complete_structured`] validates every
+//! reply, feeds validation errors back to the model, and retries. Providers
+//! without a native mode get the schema as a system-prompt instruction.
+
+use super::custom_openai_compatible::extract_balanced_json;
+use super::error::{ProviderError, Result};
+use super::r#trait::Provider;
+use super::types::*;
+use serde_json::{Value, json};
+
+/// Retries after the first reply when it fails to parse or validate
+const MAX_REPAIR_ATTEMPTS: usize = 2;
+
+/// Key the schema is wrapped under when its root isn't an object — tool
+/// inputs must be objects.
+const WRAPPED_VALUE_KEY: &str = "value";
+
+/// Request a reply matching `format` and return the parsed, validated JSON.
+///
+/// Uses the provider's native mechanism when it has one and always states
+/// the schema in the system prompt. Invalid replies are sent back with the
+/// validation error up to [`MAX_REPAIR_ATTEMPTS`] times. A 400 from the
+/// native mode (endpoint doesn't know the field) drops to prompt-only.
+pub async fn complete_structured(
+    provider: &dyn Provider,
+    mut request: LLMRequest,
+    format: ResponseFormat,
+) -> Result<Value> {
+    let schema = format.schema();
+    let instruction = schema_instruction(&schema);
+    request.system = Some(match request.system.take() {
+        Some(system) => format!("{}\n\n{}", system, instruction),
+        None => instruction,
+    });
+    request.stream = false;
+    if provider.supports_response_format() {
+        request.response_format = Some(format);
+    }
+
+    let mut last_error = String::new();
+    let mut attempt = 0;
+    while attempt <= MAX_REPAIR_ATTEMPTS {
+        let response = match provider.complete(request.clone()).await {
+            Ok(response) => response,
+            Err(ProviderError::ApiError { status: 400, .. })
+                if request.response_format.is_some() =>
+            {
+                tracing::warn!(
+                    "{} rejected response_format, retrying with prompt-only JSON",
+                    provider.name()
+                );
+                request.response_format = None;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        attempt += 1;
+
+        let text = response_text(&response);
+        match parse_reply(&text, &schema) {
+            Ok(value) => return Ok(value),
+            Err(e) => last_error = e,
+        }
+        tracing::debug!(
+            "Structured output attempt {} rejected: {}",
+            attempt,
+            last_error
+        );
+
+        let echoed = if text.trim().is_empty() {
+            "(empty reply)".to_string()
+        } else {
+            text
+        };
+        request.messages.push(Message::assistant(echoed));
+        request.messages.push(Message::user(format!(
+            "That reply was rejected: {}. Respond again with only the corrected JSON.",
+            last_error
+        )));
+    }
+
+    Err(ProviderError::StructuredOutput(last_error))
+}
+
+/// System-prompt instruction stating the reply shape.
+pub fn schema_instruction(schema: &Value) -> String {
+    format!(
+        "Respond with a single JSON value and nothing else — no prose, no code fences. \
+         It must match this JSON Schema:\n{}",
+        schema
+    )
+}
+
+/// Extract and validate the JSON in a reply. For replies that can't be sent
+/// back for repair, such as batch results.
+pub fn parse_reply(text: &str, schema: &Value) -> std::result::Result<Value, String> {
+    let value = extract_json(text).ok_or_else(|| "reply is not valid JSON".to_string())?;
+    validate(&value, schema)?;
+    Ok(value)
+}
+
+/// Concatenated text blocks of a response
+pub(crate) fn response_text(response: &LLMResponse) -> String {
+    response
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Pull a JSON value out of a reply: bare JSON, a fenced ```json block, or
+/// the first balanced object embedded in prose.
+pub fn extract_json(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"));
+    if let Some(inner) = unfenced
+        && let Ok(value) = serde_json::from_str(inner.trim())
+    {
+        return Some(value);
+    }
+
+    let start = trimmed.find('{')?;
+    let len = extract_balanced_json(&trimmed[start..])?;
+    serde_json::from_str(&trimmed[start..start + len]).ok()
+}
+
+/// Check `value` against the subset of JSON Schema the providers accept:
+/// `type`, `enum`, `const`, `anyOf`/`oneOf`, `properties`, `required`,
+/// `additionalProperties`, `items`, `minItems`/`maxItems` and
+/// `minimum`/`maximum`. Unknown keywords are ignored. The error names the
+/// offending path so it can be fed back to the model.
+pub fn validate(value: &Value, schema: &Value) -> std::result::Result<(), String> {
+    validate_at(value, schema, "$")
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str) -> std::result::Result<(), String> {
+    if let Some(options) = schema["anyOf"].as_array().or(schema["oneOf"].as_array()) {
+        return if options.iter().any(|s| validate_at(value, s, path).is_ok()) {
+            Ok(())
+        } else {
+            Err(format!("{}: matches none of the allowed shapes", path))
+        };
+    }
+
+    if let Some(allowed) = schema["enum"].as_array()
+        && !allowed.contains(value)
+    {
+        return Err(format!(
+            "{}: {} is not one of {}",
+            path,
+            value,
+            Value::Array(allowed.clone())
+        ));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        return Err(format!("{}: expected {}", path, expected));
+    }
+
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|t| type_matches(value, t)) {
+        return Err(format!(
+            "{}: expected {}, got {}",
+            path,
+            types.join(" or "),
+            type_name(value)
+        ));
+    }
+
+    match value {
+        Value::Object(map) => {
+            for key in schema["required"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !map.contains_key(key) {
+                    return Err(format!("{}: missing required property \"{}\"", path, key));
+                }
+            }
+            let properties = schema["properties"].as_object();
+            for (key, child) in map {
+                let child_path = format!("{}.{}", path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(child_schema) => validate_at(child, child_schema, &child_path)?,
+                    None => match &schema["additionalProperties"] {
+                        Value::Bool(false) => {
+                            return Err(format!("{}: unexpected property \"{}\"", path, key));
+                        }
+                        extra @ Value::Object(_) => validate_at(child, extra, &child_path)?,
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema["minItems"].as_u64()
+                && (items.len() as u64) < min
+            {
+                return Err(format!("{}: expected at least {} items", path, min));
+            }
+            if let Some(max) = schema["maxItems"].as_u64()
+                && (items.len() as u64) > max
+            {
+                return Err(format!("{}: expected at most {} items", path, max));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_at(item, item_schema, &format!("{}[{}]", path, i))?;
+                }
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(min) = schema["minimum"].as_f64()
+                && n < min
+            {
+                return Err(format!("{}: {} is below the minimum {}", path, n, min));
+            }
+            if let Some(max) = schema["maximum"].as_f64()
+                && n > max
+            {
+                return Err(format!("{}: {} is above the maximum {}", path, n, max));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn type_matches(value: &Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// OpenAI chat-completions `response_format` value
+pub(crate) fn openai_response_format(format: &ResponseFormat) -> Value {
+    match format {
+        ResponseFormat::JsonObject => json!({"type": "json_object"}),
+        ResponseFormat::JsonSchema {
+            name,
+            schema,
+            strict,
+        } => json!({
+            "type": "json_schema",
+            "json_schema": {"name": name, "schema": schema, "strict": strict}
+        }),
+    }
+}
+
+/// Responses API `text.format` value (the chat shape, flattened)
+pub(crate) fn responses_text_format(format: &ResponseFormat) -> Value {
+    match format {
+        ResponseFormat::JsonObject => json!({"type": "json_object"}),
+        ResponseFormat::JsonSchema {
+            name,
+            schema,
+            strict,
+        } => json!({"type": "json_schema", "name": name, "schema": schema, "strict": strict}),
+    }
+}
+
+/// Tool that carries the reply on providers that force a tool call instead
+/// of offering a JSON mode
+pub(crate) fn response_tool(format: &ResponseFormat) -> Tool {
+    let schema = format.schema();
+    let input_schema = if schema["type"] == "object" {
+        schema
+    } else {
+        json!({
+            "type": "object",
+            "properties": {WRAPPED_VALUE_KEY: schema},
+            "required": [WRAPPED_VALUE_KEY]
+        })
+    };
+    Tool {
+        name: format.name().to_string(),
+        description: "Return the final answer. Call this exactly once with the complete result."
+            .to_string(),
+        input_schema,
+    }
+}
+
+/// Replace the forced [`response_tool`] call with a text block holding its
+/// input as JSON, so callers read the reply the same way on every provider.
+pub(crate) fn unwrap_response_tool(response: &mut LLMResponse, format: &ResponseFormat) {
+    let wrapped = format.schema()["type"] != "object";
+    let Some(index) = response.content.iter().position(
+        |block| matches!(block, ContentBlock::ToolUse { name, .. } if name == format.name()),
+    ) else {
+        return;
+    };
+    let ContentBlock::ToolUse { input, .. } = response.content.remove(index) else {
+        return;
+    };
+    let value = if wrapped {
+        input.get(WRAPPED_VALUE_KEY).cloned().unwrap_or(input)
+    } else {
+        input
+    };
+    response
+        .content
+        .retain(|block| !matches!(block, ContentBlock::Text { .. }));
+    response.content.push(ContentBlock::Text {
+        text: value.to_string(),
+    });
+    response.stop_reason = Some(StopReason::EndTurn);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "tags": {"type": "array", "items": {"type": "string", "enum": ["a", "b"]}},
+                "score": {"type": "integer", "minimum": 0}
+            },
+            "required": ["name", "tags"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_validate_accepts_matching_value() {
+        let value = json!({"name": "x", "tags": ["a", "b"], "score": 3});
+        assert!(validate(&value, &schema()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_reports_path() {
+        let err = validate(&json!({"name": "x", "tags": ["a", "c"]}), &schema()).unwrap_err();
+        assert!(err.starts_with("$.tags[1]"), "{}", err);
+
+        let err = validate(&json!({"name": "x"}), &schema()).unwrap_err();
+        assert!(err.contains("missing required property \"tags\""));
+
+        let err = validate(&json!({"name": "x", "tags": [], "extra": 1}), &schema()).unwrap_err();
+        assert!(err.contains("unexpected property \"extra\""));
+
+        let err = validate(&json!({"name": 1, "tags": []}), &schema()).unwrap_err();
+        assert_eq!(err, "$.name: expected string, got number");
+
+        let err = validate(&json!({"name": "x", "tags": [], "score": -1}), &schema()).unwrap_err();
+        assert!(err.contains("below the minimum"));
+    }
+
+    #[test]
+    fn test_extract_json_variants() {
+        assert_eq!(extract_json(r#"{"a":1}"#), Some(json!({"a": 1})));
+        assert_eq!(extract_json("[1, 2]"), Some(json!([1, 2])));
+        assert_eq!(
+            extract_json("```json\n{\"a\": \"}\"}\n```"),
+            Some(json!({"a": "}"}))
+        );
+        assert_eq!(
+            extract_json("Sure! Here it is: {\"a\": {\"b\": 2}} hope that helps"),
+            Some(json!({"a": {"b": 2}}))
+        );
+        assert_eq!(extract_json("no json here"), None);
+    }
+
+    #[test]
+    fn test_response_tool_wraps_non_object_schema() {
+        let format = ResponseFormat::json_schema("ids", json!({"type": "array"}));
+        let tool = response_tool(&format);
+        assert_eq!(tool.name, "ids");
+        assert_eq!(tool.input_schema["properties"]["value"]["type"], "array");
+
+        let mut response = LLMResponse {
+            id: "msg_1".into(),
+            model: "m".into(),
+            content: vec![
+                ContentBlock::Text {
+                    text: "Calling the tool".into(),
+                },
+                ContentBlock::ToolUse {
+                    id: "toolu_1".into(),
+                    name: "ids".into(),
+                    input: json!({"value": [1, 2]}),
+                },
+            ],
+            stop_reason: Some(StopReason::ToolUse),
+            usage: TokenUsage::default(),
+        };
+        unwrap_response_tool(&mut response, &format);
+        assert_eq!(response.content.len(), 1);
+        assert!(matches!(&response.content[0], ContentBlock::Text { text } if text == "[1,2]"));
+        assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+    }
+
+    #[test]
+    fn test_openai_response_format_shapes() {
+        assert_eq!(
+            openai_response_format(&ResponseFormat::JsonObject),
+            json!({"type": "json_object"})
+        );
+        let strict = ResponseFormat::JsonSchema {
+            name: "r".into(),
+            schema: json!({"type": "object"}),
+            strict: true,
+        };
+        assert_eq!(
+            openai_response_format(&strict)["json_schema"]["strict"],
+            true
+        );
+        assert_eq!(responses_text_format(&strict)["name"], "r");
+    }
+}
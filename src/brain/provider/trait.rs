@@ -2,7 +2,8 @@
 //!
 //! Defines the interface that all LLM providers must implement.
 
-use super::error::Result;
+use super::batch::{BatchRequest, BatchResult, BatchStatus};
+use super::error::{ProviderError, Result};
 use super::types::{LLMRequest, LLMResponse, StreamEvent};
 use async_trait::async_trait;
 use futures::Stream;
@@ -43,6 +44,35 @@ pub trait Provider: Send + Sync {
         false // Not all providers support vision
     }
 
+    /// Whether `LLMRequest::response_format` is honoured natively (JSON mode,
+    /// response schema or forced tool). Callers fall back to prompting and
+    /// validating when false; see `provider::structured`.
+    fn supports_response_format(&self) -> bool {
+        false
+    }
+
+    /// Whether this provider exposes a discounted asynchronous batch API.
+    /// See `provider::batch` for the submit → poll → fetch lifecycle.
+    fn supports_batch(&self) -> bool {
+        false
+    }
+
+    /// Submit requests as one batch. Returns the provider's batch id, to be
+    /// persisted and handed back to `poll_batch` / `fetch_batch_results`.
+    async fn submit_batch(&self, _requests: Vec<BatchRequest>) -> Result<String> {
+        Err(ProviderError::BatchNotSupported)
+    }
+
+    /// Check the progress of a submitted batch.
+    async fn poll_batch(&self, _batch_id: &str) -> Result<BatchStatus> {
+        Err(ProviderError::BatchNotSupported)
+    }
+
+    /// Download the per-request results of an ended batch.
+    async fn fetch_batch_results(&self, _batch_id: &str) -> Result<Vec<BatchResult>> {
+        Err(ProviderError::BatchNotSupported)
+    }
+
     /// Whether the CLI subprocess handles tool execution internally.
     /// When true, the tool_loop emits ToolStarted/ToolCompleted progress
     /// events for display but does NOT execute tools itself.
@@ -188,7 +218,7 @@ impl ProviderCapabilities {
             streaming: provider.supports_streaming(),
             tools: provider.supports_tools(),
             vision: provider.supports_vision(),
-            json_mode: false, // Provider-specific
+            json_mode: provider.supports_response_format(),
         }
     }
 }
@@ -246,5 +276,6 @@ mod tests {
         assert!(caps.streaming);
         assert!(caps.tools);
         assert!(!caps.vision);
+        assert!(!caps.json_mode);
     }
 }
@@ -80,6 +80,9 @@ pub enum ContentBlock {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         signature: Option<String>,
     },
+    /// Redacted thinking (Anthropic extended thinking flagged by safety
+    /// systems). Opaque encrypted payload that must be echoed back verbatim.
+    RedactedThinking { data: String },
 }
 
 /// Image source for image content blocks
@@ -105,6 +108,10 @@ pub struct LLMRequest {
     /// Available tools
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<Tool>>,
+    /// How the model may use `tools`. `None` leaves it to the provider
+    /// default (auto).
+    #[serde(skip)]
+    pub tool_choice: Option<ToolChoice>,
     /// Temperature (0.0-1.0)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
@@ -123,6 +130,18 @@ pub struct LLMRequest {
     /// Session ID — used by CLI providers to isolate sessions via --session-id
     #[serde(skip)]
     pub session_id: Option<Uuid>,
+    /// Extended thinking budget in tokens (Anthropic). `None` defers to the
+    /// provider's configured budget, `Some(0)` disables thinking explicitly.
+    #[serde(skip)]
+    pub thinking_budget: Option<u32>,
+    /// Constrain the reply to JSON (optionally schema-shaped). Providers map
+    /// this to their native mechanism; see `provider::structured`.
+    #[serde(skip)]
+    pub response_format: Option<ResponseFormat>,
+    /// Serve an identical earlier reply from the response cache if it is
+    /// younger than this many seconds. `None` bypasses the cache.
+    #[serde(skip)]
+    pub cache_ttl: Option<u64>,
 }
 
 impl LLMRequest {
@@ -133,12 +152,16 @@ impl LLMRequest {
             messages,
             system: None,
             tools: None,
+            tool_choice: None,
             temperature: None,
             max_tokens: None,
             stream: false,
             metadata: None,
             working_directory: None,
             session_id: None,
+            thinking_budget: None,
+            response_format: None,
+            cache_ttl: None,
         }
     }
 
@@ -171,6 +194,104 @@ impl LLMRequest {
         self.stream = true;
         self
     }
+
+    /// Set extended thinking budget (0 disables thinking)
+    pub fn with_thinking_budget(mut self, budget_tokens: u32) -> Self {
+        self.thinking_budget = Some(budget_tokens);
+        self
+    }
+
+    /// Constrain tool use for this call
+    pub fn with_tool_choice(mut self, choice: ToolChoice) -> Self {
+        self.tool_choice = Some(choice);
+        self
+    }
+
+    /// Request structured (JSON) output
+    pub fn with_response_format(mut self, format: ResponseFormat) -> Self {
+        self.response_format = Some(format);
+        self
+    }
+
+    /// Opt in to the response cache with a TTL in seconds
+    pub fn with_cache_ttl(mut self, ttl_secs: u64) -> Self {
+        self.cache_ttl = Some(ttl_secs);
+        self
+    }
+}
+
+/// Tool-use constraint for one request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides
+    Auto,
+    /// The model must call at least one tool
+    Required,
+    /// The model must answer in text
+    None,
+    /// The model must call this tool
+    Tool(String),
+}
+
+impl ToolChoice {
+    /// Parse a config/CLI value: `auto`, `required` (or `any`), `none`, or
+    /// anything else as a tool name
+    pub fn from_setting(value: &str) -> Self {
+        match value.trim() {
+            "auto" => Self::Auto,
+            "required" | "any" => Self::Required,
+            "none" => Self::None,
+            name => Self::Tool(name.to_string()),
+        }
+    }
+}
+
+/// Requested shape of the model's reply
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Any JSON object
+    JsonObject,
+    /// JSON matching a JSON Schema
+    JsonSchema {
+        /// Schema name (`[a-zA-Z0-9_-]`); doubles as the forced tool name on
+        /// providers without a native JSON mode
+        name: String,
+        schema: serde_json::Value,
+        /// Ask for strict decoding where supported (OpenAI). Strict schemas
+        /// must list every property in `required` and set
+        /// `additionalProperties: false`.
+        #[serde(default)]
+        strict: bool,
+    },
+}
+
+impl ResponseFormat {
+    /// Non-strict schema-constrained output
+    pub fn json_schema(name: impl Into<String>, schema: serde_json::Value) -> Self {
+        Self::JsonSchema {
+            name: name.into(),
+            schema,
+            strict: false,
+        }
+    }
+
+    /// Schema name, or a generic one for `JsonObject`
+    pub fn name(&self) -> &str {
+        match self {
+            Self::JsonObject => "json_response",
+            Self::JsonSchema { name, .. } => name,
+        }
+    }
+
+    /// The schema replies are validated against
+    pub fn schema(&self) -> serde_json::Value {
+        match self {
+            Self::JsonObject => serde_json::json!({"type": "object"}),
+            Self::JsonSchema { schema, .. } => schema.clone(),
+        }
+    }
 }
 
 /// Tool definition for LLM
@@ -234,6 +355,9 @@ pub struct TokenUsage {
     /// Billing: cumulative cache read across all CLI tool rounds.
     #[serde(default)]
     pub billing_cache_read: u32,
+    /// Served from the local response cache — nothing was billed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub response_cache_hit: bool,
 }
 
 impl TokenUsage {
@@ -320,6 +444,9 @@ pub enum ContentDelta {
     ReasoningDelta { text: String },
     /// Anthropic native thinking delta (extended thinking — same as reasoning but uses `thinking` field)
     ThinkingDelta { thinking: String },
+    /// Anthropic thinking block signature, sent just before the block stops.
+    /// Required to echo the block back on the next tool-use turn.
+    SignatureDelta { signature: String },
 }
 
 /// Message delta for final updates
@@ -349,12 +476,14 @@ mod tests {
             .with_system("You are helpful")
             .with_temperature(0.7)
             .with_max_tokens(1000)
+            .with_thinking_budget(2048)
             .with_streaming();
 
         assert_eq!(request.model, "claude-3-sonnet-20240229");
         assert!(request.system.is_some());
         assert_eq!(request.temperature, Some(0.7));
         assert_eq!(request.max_tokens, Some(1000));
+        assert_eq!(request.thinking_budget, Some(2048));
         assert!(request.stream);
     }
 
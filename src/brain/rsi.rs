@@ -7,9 +7,22 @@
 //!
 //! Uses the provider/model configured in `[agent].self_improvement_provider`
 //! and `[agent].self_improvement_model`, falling back to the active provider.
-
+//!
+//! Each cycle first triages the detected opportunities with schema-constrained
+//! output ([`analyze_opportunities`]) and only starts the agent when something
+//! is actionable. The same triage runs over the digest once a day through the
+//! provider's batch API ([`submit_digest_batch`]); its findings are added to
+//! the digest.
+
+use crate::brain::provider::structured::{complete_structured, parse_reply, schema_instruction};
+use crate::brain::provider::{
+    BatchRequest, BatchResult, LLMRequest, Message, Provider, ProviderError, ResponseFormat,
+};
 use crate::config::Config;
+use crate::db::LlmBatchRepository;
+use crate::db::models::LlmBatch;
 use crate::db::repository::FeedbackLedgerRepository;
+use serde::Deserialize;
 use std::io::Write;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -24,6 +37,23 @@ const RSI_MIN_ENTRIES: i64 = 50;
 /// Max tool iterations for the RSI agent (keep it focused).
 const RSI_MAX_TOOL_ITERATIONS: usize = 10;
 
+/// `llm_batches.purpose` for the daily digest analysis.
+pub const DIGEST_BATCH_PURPOSE: &str = "rsi_digest";
+
+/// Findings of the last digest batch, added to every digest written after it.
+const DIGEST_FINDINGS_FILE: &str = "digest_findings.md";
+
+/// Brain files an improvement can target (see the taxonomy in `RSI_AGENT_PROMPT`).
+const RSI_TARGET_FILES: &[&str] = &[
+    "SOUL.md",
+    "TOOLS.md",
+    "USER.md",
+    "MEMORY.md",
+    "AGENTS.md",
+    "CODE.md",
+    "SECURITY.md",
+];
+
 /// Ensure `~/.opencrabs/rsi/` and `~/.opencrabs/rsi/history/` exist.
 fn ensure_rsi_dirs() -> std::io::Result<PathBuf> {
     let home = crate::config::opencrabs_home();
@@ -145,6 +175,11 @@ pub async fn write_startup_digest(pool: crate::db::Pool) {
         out.push('\n');
     }
 
+    // Findings from the last batched analysis of the digest
+    if let Ok(findings) = std::fs::read_to_string(rsi_dir.join(DIGEST_FINDINGS_FILE)) {
+        out.push_str(&findings);
+    }
+
     let digest_path = rsi_dir.join("digest.md");
     match std::fs::File::create(&digest_path) {
         Ok(mut f) => {
@@ -260,6 +295,156 @@ Never duplicate an existing instruction in a brain file — you have the 'read'
 If an improvement was already applied (check self_improve action='list'), skip it. \
 Use 'update' over 'apply' when an existing instruction needs rewording, not a new one added.";
 
+/// An actionable improvement found by [`analyze_opportunities`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RsiFinding {
+    /// Brain file the fix belongs in, one of `RSI_TARGET_FILES`
+    pub target_file: String,
+    /// The recurring problem the feedback shows
+    pub problem: String,
+    /// The instruction to add or refine
+    pub improvement: String,
+}
+
+/// Response format for the opportunity triage: `{"findings": [RsiFinding]}`.
+pub fn analysis_format() -> ResponseFormat {
+    ResponseFormat::json_schema(
+        "rsi_findings",
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "findings": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "target_file": {"type": "string", "enum": RSI_TARGET_FILES},
+                            "problem": {"type": "string"},
+                            "improvement": {"type": "string"}
+                        },
+                        "required": ["target_file", "problem", "improvement"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["findings"],
+            "additionalProperties": false
+        }),
+    )
+}
+
+/// Triage detected opportunities with schema-constrained output: which are
+/// actionable and which brain file each fix belongs in. An empty list means
+/// nothing is worth changing this cycle.
+pub async fn analyze_opportunities(
+    provider: &dyn Provider,
+    model: &str,
+    opportunities: &[String],
+) -> Result<Vec<RsiFinding>, ProviderError> {
+    let mut evidence = "Detected opportunities:\n".to_string();
+    for opp in opportunities {
+        evidence.push_str(&format!("- {opp}\n"));
+    }
+    let value =
+        complete_structured(provider, triage_request(model, evidence), analysis_format()).await?;
+    serde_json::from_value(value["findings"].clone())
+        .map_err(|e| ProviderError::StructuredOutput(e.to_string()))
+}
+
+/// Triage request over `evidence` (detected opportunities or the digest).
+fn triage_request(model: &str, mut evidence: String) -> LLMRequest {
+    evidence.push_str(
+        "\nList only the improvements the evidence clearly supports, each routed to the \
+         brain file that controls that behavior. Return an empty list if none are.",
+    );
+    LLMRequest::new(model, vec![Message::user(evidence)])
+        .with_system(
+            "You triage runtime feedback for OpenCrabs' self-improvement engine. \
+             SOUL.md: behavior and style. TOOLS.md: tool usage. USER.md: this user's \
+             preferences. MEMORY.md: retained knowledge. AGENTS.md: agent rules. \
+             CODE.md: coding standards. SECURITY.md: security policy.",
+        )
+        .with_max_tokens(2048)
+}
+
+/// Submit the triage of the current digest as a provider batch and record
+/// it in `llm_batches`. A batch reply can't be sent back for repair, so the
+/// schema goes in the system prompt and [`apply_digest_batch`] drops a reply
+/// that doesn't match it. Returns the batch id, or `None` without a digest.
+pub async fn submit_digest_batch(
+    pool: &crate::db::Pool,
+    provider: &dyn Provider,
+    model: &str,
+) -> anyhow::Result<Option<String>> {
+    let rsi_dir = ensure_rsi_dirs()?;
+    let Ok(digest) = std::fs::read_to_string(rsi_dir.join("digest.md")) else {
+        return Ok(None);
+    };
+
+    let mut request = triage_request(model, format!("{digest}\n"));
+    let instruction = schema_instruction(&analysis_format().schema());
+    request.system = Some(match request.system.take() {
+        Some(system) => format!("{system}\n\n{instruction}"),
+        None => instruction,
+    });
+    let batch_id = provider
+        .submit_batch(vec![BatchRequest::new(DIGEST_BATCH_PURPOSE, request)])
+        .await?;
+
+    LlmBatchRepository::new(pool.clone())
+        .insert(&LlmBatch::new_pending(
+            batch_id.clone(),
+            provider.name().to_string(),
+            DIGEST_BATCH_PURPOSE,
+            None,
+            1,
+        ))
+        .await?;
+    Ok(Some(batch_id))
+}
+
+/// Findings in a finished digest batch; replies that fail the schema are
+/// dropped.
+pub fn parse_digest_batch(results: &[BatchResult]) -> Vec<RsiFinding> {
+    let schema = analysis_format().schema();
+    let mut findings = Vec::new();
+    for result in results {
+        let parsed = match &result.outcome {
+            Ok(_) => parse_reply(&result.text().unwrap_or_default(), &schema),
+            Err(e) => Err(e.clone()),
+        };
+        match parsed.and_then(|value| {
+            serde_json::from_value::<Vec<RsiFinding>>(value["findings"].clone())
+                .map_err(|e| e.to_string())
+        }) {
+            Ok(batch_findings) => findings.extend(batch_findings),
+            Err(e) => tracing::warn!("RSI digest batch reply dropped: {e}"),
+        }
+    }
+    findings
+}
+
+/// Save the findings of a finished digest batch for the next digest to
+/// include. Returns the number of findings.
+pub fn apply_digest_batch(results: &[BatchResult]) -> anyhow::Result<usize> {
+    let findings = parse_digest_batch(results);
+    let mut out = format!(
+        "## Analysis\n\n**Analyzed:** {}\n\n",
+        chrono::Utc::now().format("%Y-%m-%d %H:%M UTC")
+    );
+    if findings.is_empty() {
+        out.push_str("Nothing actionable.\n");
+    }
+    for f in &findings {
+        out.push_str(&format!(
+            "- **{}** — {} → {}\n",
+            f.target_file, f.problem, f.improvement
+        ));
+    }
+    std::fs::write(ensure_rsi_dirs()?.join(DIGEST_FINDINGS_FILE), out)?;
+    Ok(findings.len())
+}
+
 /// Run a single autonomous RSI agent cycle.
 ///
 /// Creates a lightweight AgentService with only RSI tools, sends the improvement
@@ -282,6 +467,23 @@ async fn run_rsi_agent_cycle(
 
     let provider =
         crate::brain::provider::factory::create_provider_by_name(config, provider_name).await?;
+    let model = config.agent.self_improvement_model.clone();
+
+    // Triage first; fall back to the raw opportunities if the analysis fails
+    let analysis_model = model
+        .clone()
+        .unwrap_or_else(|| provider.default_model().to_string());
+    let findings =
+        match analyze_opportunities(provider.as_ref(), &analysis_model, opportunities).await {
+            Ok(findings) if findings.is_empty() => {
+                return Ok("Analysis found nothing actionable this cycle.".to_string());
+            }
+            Ok(findings) => findings,
+            Err(e) => {
+                tracing::warn!("RSI analysis failed, using raw opportunities: {e}");
+                Vec::new()
+            }
+        };
 
     let service_ctx = ServiceContext::new(pool);
     let tool_registry = build_rsi_tool_registry();
@@ -323,12 +525,20 @@ async fn run_rsi_agent_cycle(
         }
         prompt.push('\n');
     }
+    if !findings.is_empty() {
+        prompt.push_str("Proposed improvements:\n");
+        for f in &findings {
+            prompt.push_str(&format!(
+                "- [{}] {} → {}\n",
+                f.target_file, f.problem, f.improvement
+            ));
+        }
+        prompt.push('\n');
+    }
     prompt.push_str(
         "Analyze the feedback data, identify the highest-impact issues, and apply improvements.",
     );
 
-    let model = config.agent.self_improvement_model.clone();
-
     let response = agent
         .send_message_with_tools(session.id, prompt, model)
         .await?;
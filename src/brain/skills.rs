@@ -0,0 +1,314 @@
+//! Skills
+//!
+//! Instruction bundles the agent loads only when a task calls for them. A
+//! skill is a directory holding `SKILL.md` — optional frontmatter with a
+//! `name` and one-line `description`, then the instructions — plus any
+//! scripts or reference files the instructions point to. Skills live in
+//! `~/.opencrabs/skills/` and in a project's `.opencrabs/skills/`; a project
+//! skill wins over a global one of the same name.
+//!
+//! Only names and descriptions enter the system brain; the `load_skill` tool
+//! fetches the rest. Skill directories are rescanned every turn, so new or
+//! edited skills take effect without a restart.
+
+use anyhow::{Context, Result, bail};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The instructions file every skill directory has
+pub const SKILL_FILE: &str = "SKILL.md";
+
+/// Skills directory under `~/.opencrabs/` and a project's `.opencrabs/`
+const SKILLS_DIR: &str = "skills";
+
+/// Longest description shown in the skill index
+const MAX_DESCRIPTION_CHARS: usize = 200;
+
+/// Most resource files listed for one skill
+const MAX_RESOURCES: usize = 100;
+
+/// Global skills directory of a brain path: `~/.opencrabs/skills/` by default
+pub fn global_dir(brain_path: &Path) -> PathBuf {
+    brain_path.join(SKILLS_DIR)
+}
+
+/// Where a skill was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkillScope {
+    /// `~/.opencrabs/skills/`
+    Global,
+    /// `.opencrabs/skills/` in the working directory or one of its parents
+    Project,
+}
+
+impl std::fmt::Display for SkillScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Global => write!(f, "global"),
+            Self::Project => write!(f, "project"),
+        }
+    }
+}
+
+/// One skill directory
+#[derive(Debug, Clone)]
+pub struct Skill {
+    /// Frontmatter `name`, or the directory name
+    pub name: String,
+    /// Frontmatter `description`, or the first line of the instructions
+    pub description: String,
+    /// Directory holding `SKILL.md` and the skill's resources
+    pub dir: PathBuf,
+    /// Whether it came from `~/.opencrabs/` or the project
+    pub scope: SkillScope,
+}
+
+impl Skill {
+    /// Read the skill in `dir`. `None` when it has no readable `SKILL.md`.
+    pub fn load(dir: &Path, scope: SkillScope) -> Option<Self> {
+        let content = std::fs::read_to_string(dir.join(SKILL_FILE)).ok()?;
+        let (fields, body) = split_frontmatter(&content);
+
+        let name = fields
+            .get("name")
+            .cloned()
+            .filter(|name| !name.is_empty())
+            .or_else(|| Some(dir.file_name()?.to_string_lossy().to_string()))?;
+        let description = fields
+            .get("description")
+            .cloned()
+            .filter(|d| !d.is_empty())
+            .or_else(|| {
+                body.lines()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| "(no description)".to_string());
+
+        Some(Self {
+            name,
+            description: clip(&description, MAX_DESCRIPTION_CHARS),
+            dir: dir.to_path_buf(),
+            scope,
+        })
+    }
+
+    /// The full instructions, followed by the resource files that come
+    /// with them
+    pub fn instructions(&self) -> Result<String> {
+        let path = self.dir.join(SKILL_FILE);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let (_, body) = split_frontmatter(&content);
+
+        let mut out = format!(
+            "--- Skill: {} ({}, {}) ---\n{}\n",
+            self.name,
+            self.scope,
+            self.dir.display(),
+            body.trim()
+        );
+        let resources = self.resources();
+        if !resources.is_empty() {
+            out.push_str(&format!(
+                "\nResources in {} (load one with `load_skill` and `file`, run scripts \
+                 with `bash` from that directory):\n",
+                self.dir.display()
+            ));
+            for resource in &resources {
+                out.push_str(&format!("- {}\n", resource.display()));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Files that come with the skill besides `SKILL.md`, relative to its
+    /// directory and sorted. Hidden files are skipped.
+    pub fn resources(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        if let Ok(real_dir) = self.dir.canonicalize() {
+            collect_files(&self.dir, &real_dir, &self.dir, &mut files);
+        }
+        files.retain(|path| path != Path::new(SKILL_FILE));
+        files.sort();
+        files.truncate(MAX_RESOURCES);
+        files
+    }
+
+    /// Content of the resource `file`, relative to the skill directory
+    pub fn resource(&self, file: &str) -> Result<String> {
+        let relative = Path::new(file);
+        if relative.is_absolute()
+            || relative
+                .components()
+                .any(|c| matches!(c, std::path::Component::ParentDir))
+        {
+            bail!(
+                "'{}' must be a path inside the skill directory, e.g. scripts/run.sh",
+                file
+            );
+        }
+        let path = self.dir.join(relative);
+        // Symlinks are followed, so check where the path really leads
+        let real_path = path
+            .canonicalize()
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let real_dir = self
+            .dir
+            .canonicalize()
+            .with_context(|| format!("Failed to read {}", self.dir.display()))?;
+        if !real_path.starts_with(&real_dir) {
+            bail!("'{}' links outside the skill directory", file);
+        }
+        std::fs::read_to_string(&real_path)
+            .with_context(|| format!("Failed to read {}", path.display()))
+    }
+}
+
+/// The skills available from a working directory, by name
+#[derive(Debug, Clone, Default)]
+pub struct Skills {
+    skills: BTreeMap<String, Skill>,
+}
+
+impl Skills {
+    /// Skills in `global_dir`, plus those in `.opencrabs/skills/` in
+    /// `working_directory` and each of its parents. The nearest directory
+    /// wins when two skills share a name.
+    pub fn discover(global_dir: &Path, working_directory: &Path) -> Self {
+        let mut skills = Self::default();
+        for dir in working_directory.ancestors() {
+            let project_dir = dir.join(".opencrabs").join(SKILLS_DIR);
+            if project_dir != global_dir {
+                skills.add_dir(&project_dir, SkillScope::Project);
+            }
+        }
+        skills.add_dir(global_dir, SkillScope::Global);
+        skills
+    }
+
+    /// Add the skills in `dir` whose names aren't taken yet
+    fn add_dir(&mut self, dir: &Path, scope: SkillScope) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let mut dirs: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        dirs.sort();
+        for skill_dir in dirs {
+            if let Some(skill) = Skill::load(&skill_dir, scope) {
+                self.skills
+                    .entry(skill.name.to_lowercase())
+                    .or_insert(skill);
+            }
+        }
+    }
+
+    /// The skill called `name` (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&Skill> {
+        self.skills.get(&name.trim().to_lowercase())
+    }
+
+    /// All skills, sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = &Skill> {
+        self.skills.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.skills.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skills.is_empty()
+    }
+
+    /// The skill index for the system brain, or `None` without skills
+    pub fn index_section(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut out = String::from("--- Available Skills ---\n");
+        out.push_str(
+            "Skills are instruction bundles for specific tasks. When a request matches a \
+             skill's description, call `load_skill` with its name before starting and follow \
+             the instructions it returns. Don't load skills the request doesn't need.\n\n",
+        );
+        for skill in self.iter() {
+            let scope = match skill.scope {
+                SkillScope::Project => " (project)",
+                SkillScope::Global => "",
+            };
+            out.push_str(&format!(
+                "- **{}**{}: {}\n",
+                skill.name, scope, skill.description
+            ));
+        }
+        out.push('\n');
+        Some(out)
+    }
+}
+
+/// Split `---`-fenced `key: value` frontmatter from the body. Content
+/// without frontmatter is all body.
+fn split_frontmatter(content: &str) -> (BTreeMap<String, String>, &str) {
+    let mut fields = BTreeMap::new();
+    let content = content.trim_start_matches('\u{feff}');
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (fields, content);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (fields, content);
+    };
+
+    for line in rest[..end].lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            fields.insert(key.trim().to_lowercase(), value.to_string());
+        }
+    }
+    let body = &rest[end + "\n---".len()..];
+    (fields, body.split_once('\n').map_or("", |(_, body)| body))
+}
+
+/// Files under `dir`, relative to `root`. `real_root` is `root` with symlinks
+/// resolved: a symlinked file is listed only if it resolves to a file inside it.
+fn collect_files(root: &Path, real_root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        // Symlinked directories aren't followed, so a link cycle can't recurse forever
+        if file_type.is_dir() {
+            collect_files(root, real_root, &path, files);
+        } else if file_type.is_symlink()
+            && !path
+                .canonicalize()
+                .is_ok_and(|real| real.starts_with(real_root) && real.is_file())
+        {
+            continue;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+}
+
+fn clip(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
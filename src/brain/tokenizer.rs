@@ -1,12 +1,25 @@
-//! Token counting using tiktoken (cl100k_base encoding).
+//! Token counting using tiktoken and per-model tokenizers.
 //!
-//! Uses OpenAI's cl100k_base BPE tokenizer for accurate token estimation.
-//! While Anthropic uses their own tokenizer internally, cl100k_base provides
-//! a much closer approximation than chars/N heuristics (~5-10% variance vs ~30-50%).
+//! `count_tokens` uses OpenAI's cl100k_base BPE tokenizer — the model-agnostic
+//! default and a much closer approximation than chars/N heuristics (~5-10%
+//! variance vs ~30-50%).
 //!
-//! The tokenizer is initialized lazily via `once_cell` and reused across all calls.
+//! Where the model is known, `for_model` returns a [`Tokenizer`] for its
+//! family instead: o200k_base for newer OpenAI models, a HuggingFace
+//! `tokenizer.json` from disk for families that publish one (Qwen, GLM,
+//! Kimi, ...) and cl100k_base otherwise. Each handle carries a correction
+//! factor learned from the `input_tokens` the provider actually reports
+//! (see [`Tokenizer::calibrate`]), which absorbs what local counting can't
+//! see — chat templates, hidden system text, or a missing tokenizer file.
+//!
+//! Tokenizers are initialized lazily via `once_cell` and reused across all calls.
 
 use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use tiktoken_rs::CoreBPE;
 
 /// Global tokenizer instance — initialized once, reused everywhere.
@@ -15,10 +28,14 @@ use tiktoken_rs::CoreBPE;
 static TOKENIZER: Lazy<CoreBPE> =
     Lazy::new(|| tiktoken_rs::cl100k_base().expect("Failed to initialize cl100k_base tokenizer"));
 
+/// o200k_base — GPT-4o, GPT-4.1, GPT-5 and the o-series.
+static O200K: Lazy<CoreBPE> =
+    Lazy::new(|| tiktoken_rs::o200k_base().expect("Failed to initialize o200k_base tokenizer"));
+
 /// Count tokens in a string using cl100k_base BPE encoding.
 ///
-/// This is the single source of truth for token estimation across the entire
-/// codebase. No more chars/3, chars/4, or any other heuristic.
+/// This is the model-agnostic estimate. Prefer [`for_model`] when the
+/// model is known. No more chars/3, chars/4, or any other heuristic.
 ///
 /// # Returns
 /// Actual BPE token count (minimum 1 for non-empty strings, 0 for empty).
@@ -37,6 +54,367 @@ pub fn count_message_tokens(text: &str) -> usize {
     count_tokens(text) + 4
 }
 
+/// Model family, used to pick a tokenizer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    /// GPT-4o, GPT-4.1, GPT-5, o1/o3/o4, gpt-oss — o200k_base
+    OpenAi,
+    /// GPT-4, GPT-3.5 and older OpenAI models — cl100k_base
+    OpenAiLegacy,
+    Claude,
+    Gemini,
+    Qwen,
+    Glm,
+    Kimi,
+    DeepSeek,
+    MiniMax,
+    Llama,
+    Mistral,
+    Other,
+}
+
+impl Family {
+    /// Family of a model ID. Vendor prefixes (`openai/gpt-4o`,
+    /// `qwen/qwen3-coder`) are ignored.
+    pub fn of(model: &str) -> Self {
+        let model = model.to_lowercase();
+        let base = model.rsplit('/').next().unwrap_or(&model);
+        const O200K_PREFIXES: &[&str] = &[
+            "gpt-4o", "gpt-4.1", "gpt-4.5", "gpt-5", "gpt-oss", "chatgpt-", "codex", "o1", "o3",
+            "o4",
+        ];
+        if O200K_PREFIXES.iter().any(|p| base.starts_with(p)) {
+            return Self::OpenAi;
+        }
+        if base.starts_with("gpt-") || base.starts_with("text-embedding") {
+            return Self::OpenAiLegacy;
+        }
+        let has = |needles: &[&str]| needles.iter().any(|n| model.contains(n));
+        if has(&["claude"]) {
+            Self::Claude
+        } else if has(&["gemini", "gemma"]) {
+            Self::Gemini
+        } else if has(&["qwen", "qwq"]) {
+            Self::Qwen
+        } else if has(&["glm", "zhipu"]) {
+            Self::Glm
+        } else if has(&["kimi", "moonshot"]) {
+            Self::Kimi
+        } else if has(&["deepseek"]) {
+            Self::DeepSeek
+        } else if has(&["minimax"]) {
+            Self::MiniMax
+        } else if has(&["llama"]) {
+            Self::Llama
+        } else if has(&["mistral", "mixtral", "codestral", "devstral"]) {
+            Self::Mistral
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Lowercase name, also used for tokenizer file lookup.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::OpenAi => "openai",
+            Self::OpenAiLegacy => "openai-legacy",
+            Self::Claude => "claude",
+            Self::Gemini => "gemini",
+            Self::Qwen => "qwen",
+            Self::Glm => "glm",
+            Self::Kimi => "kimi",
+            Self::DeepSeek => "deepseek",
+            Self::MiniMax => "minimax",
+            Self::Llama => "llama",
+            Self::Mistral => "mistral",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Encoding {
+    Cl100k,
+    O200k,
+    HuggingFace(Arc<tokenizers::Tokenizer>),
+}
+
+/// Learned ratio between reported and locally counted input tokens.
+struct Correction {
+    /// `f64` bits; 0 means no samples yet
+    factor: AtomicU64,
+    samples: AtomicU32,
+    /// The owning registry's `[tokenizer] calibrate` switch
+    enabled: Arc<AtomicBool>,
+}
+
+impl Correction {
+    fn new(enabled: Arc<AtomicBool>) -> Self {
+        Self {
+            factor: AtomicU64::new(0),
+            samples: AtomicU32::new(0),
+            enabled,
+        }
+    }
+
+    fn factor(&self) -> f64 {
+        match self.factor.load(Ordering::Relaxed) {
+            0 => 1.0,
+            bits => f64::from_bits(bits),
+        }
+    }
+}
+
+/// Smallest local estimate worth learning from. Below this, fixed
+/// per-request overhead dominates the ratio.
+const MIN_CALIBRATION_TOKENS: usize = 256;
+/// Plausible reported/estimated ratios; samples outside are ignored.
+const CORRECTION_RANGE: (f64, f64) = (0.5, 2.0);
+/// Weight of each new sample in the running factor.
+const CORRECTION_SMOOTHING: f64 = 0.3;
+
+/// Token counter for one provider/model. Cheap to clone.
+#[derive(Clone)]
+pub struct Tokenizer {
+    encoding: Encoding,
+    correction: Option<Arc<Correction>>,
+}
+
+impl Default for Tokenizer {
+    /// cl100k_base without correction — same as [`count_tokens`].
+    fn default() -> Self {
+        Self {
+            encoding: Encoding::Cl100k,
+            correction: None,
+        }
+    }
+}
+
+impl fmt::Debug for Tokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tokenizer")
+            .field("encoding", &self.encoding_name())
+            .field("correction", &self.correction())
+            .finish()
+    }
+}
+
+impl Tokenizer {
+    /// Encoding in use: `cl100k_base`, `o200k_base` or `tokenizer.json`.
+    pub fn encoding_name(&self) -> &'static str {
+        match self.encoding {
+            Encoding::Cl100k => "cl100k_base",
+            Encoding::O200k => "o200k_base",
+            Encoding::HuggingFace(_) => "tokenizer.json",
+        }
+    }
+
+    /// Tokens in `text` as the encoding sees it, without correction.
+    pub fn count_raw(&self, text: &str) -> usize {
+        if text.is_empty() {
+            return 0;
+        }
+        let n = match &self.encoding {
+            Encoding::Cl100k => TOKENIZER.encode_ordinary(text).len(),
+            Encoding::O200k => O200K.encode_ordinary(text).len(),
+            Encoding::HuggingFace(tok) => match tok.encode(text, false) {
+                Ok(encoding) => encoding.len(),
+                Err(_) => TOKENIZER.encode_ordinary(text).len(),
+            },
+        };
+        n.max(1)
+    }
+
+    /// Tokens in `text` with the learned correction applied.
+    pub fn count(&self, text: &str) -> usize {
+        self.correct(self.count_raw(text))
+    }
+
+    /// Apply the learned correction to a raw count.
+    pub fn correct(&self, raw: usize) -> usize {
+        let factor = self.correction();
+        if raw == 0 || factor == 1.0 {
+            return raw;
+        }
+        ((raw as f64 * factor).round() as usize).max(1)
+    }
+
+    /// Current correction factor (1.0 until calibrated).
+    pub fn correction(&self) -> f64 {
+        self.correction.as_ref().map_or(1.0, |c| c.factor())
+    }
+
+    /// Same encoding, no correction — for producing calibration samples.
+    pub fn uncorrected(&self) -> Self {
+        Self {
+            encoding: self.encoding.clone(),
+            correction: None,
+        }
+    }
+
+    /// Fold one observation into the correction factor: `raw_estimate` is
+    /// the uncorrected count of a whole request, `reported` the provider's
+    /// `input_tokens` for it. No-op when calibration is off in config or
+    /// the request is too small to be a useful sample.
+    pub fn calibrate(&self, raw_estimate: usize, reported: u32) {
+        let Some(correction) = &self.correction else {
+            return;
+        };
+        if !correction.enabled.load(Ordering::Relaxed)
+            || raw_estimate < MIN_CALIBRATION_TOKENS
+            || reported == 0
+        {
+            return;
+        }
+        // Far outside the plausible range means the report isn't comparable
+        // (cumulative usage, a proxy rewriting the prompt), not a tokenizer gap.
+        let ratio = reported as f64 / raw_estimate as f64;
+        if !(CORRECTION_RANGE.0..=CORRECTION_RANGE.1).contains(&ratio) {
+            tracing::debug!(
+                "Tokenizer calibration: ignoring estimate {} vs reported {}",
+                raw_estimate,
+                reported,
+            );
+            return;
+        }
+        let samples = correction.samples.fetch_add(1, Ordering::Relaxed);
+        let factor = if samples == 0 {
+            ratio
+        } else {
+            let prev = correction.factor();
+            prev + (ratio - prev) * CORRECTION_SMOOTHING
+        };
+        correction.factor.store(factor.to_bits(), Ordering::Relaxed);
+        tracing::debug!(
+            "Tokenizer calibration: estimate {} vs reported {} → factor {:.3} ({} samples)",
+            raw_estimate,
+            reported,
+            factor,
+            samples + 1,
+        );
+    }
+}
+
+/// Tokenizer settings, loaded files and learned corrections.
+struct Registry {
+    calibrate: Arc<AtomicBool>,
+    dir: RwLock<Option<PathBuf>>,
+    files: RwLock<BTreeMap<String, PathBuf>>,
+    /// Parsed tokenizer files; `None` marks a file that failed to load
+    loaded: Mutex<HashMap<PathBuf, Option<Arc<tokenizers::Tokenizer>>>>,
+    /// Keyed by lowercase `provider/model`
+    corrections: Mutex<HashMap<String, Arc<Correction>>>,
+}
+
+/// The process-wide registry behind [`configure`] and [`for_model`].
+static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+impl Registry {
+    fn new() -> Self {
+        Self {
+            calibrate: Arc::new(AtomicBool::new(true)),
+            dir: RwLock::new(None),
+            files: RwLock::new(BTreeMap::new()),
+            loaded: Mutex::new(HashMap::new()),
+            corrections: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn configure(&self, config: &crate::config::TokenizerConfig) {
+        self.calibrate.store(config.calibrate, Ordering::Relaxed);
+        *self.dir.write().unwrap_or_else(|e| e.into_inner()) = Some(config.resolved_dir());
+        *self.files.write().unwrap_or_else(|e| e.into_inner()) = config.resolved_files();
+    }
+
+    fn for_model(&self, provider: &str, model: &str) -> Tokenizer {
+        let key = format!("{}/{}", provider.to_lowercase(), model.to_lowercase());
+        let correction = self
+            .corrections
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key)
+            .or_insert_with(|| Arc::new(Correction::new(self.calibrate.clone())))
+            .clone();
+        Tokenizer {
+            encoding: self.encoding_for(model),
+            correction: Some(correction),
+        }
+    }
+
+    fn encoding_for(&self, model: &str) -> Encoding {
+        let family = Family::of(model);
+        if let Some(path) = self.tokenizer_file(model, family)
+            && let Some(tok) = self.load_file(&path)
+        {
+            return Encoding::HuggingFace(tok);
+        }
+        match family {
+            Family::OpenAi => Encoding::O200k,
+            _ => Encoding::Cl100k,
+        }
+    }
+
+    /// Configured file for the model or its family, else `<dir>/<family>/tokenizer.json`
+    /// or `<dir>/<family>.json`. Built-in OpenAI encodings skip the directory scan.
+    fn tokenizer_file(&self, model: &str, family: Family) -> Option<PathBuf> {
+        let files = self.files.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(path) = files
+            .get(&model.to_lowercase())
+            .or_else(|| files.get(family.name()))
+        {
+            return Some(path.clone());
+        }
+        if matches!(family, Family::OpenAi | Family::OpenAiLegacy) {
+            return None;
+        }
+        let dir = self
+            .dir
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .unwrap_or_else(|| crate::config::opencrabs_home().join("tokenizers"));
+        [
+            dir.join(family.name()).join("tokenizer.json"),
+            dir.join(format!("{}.json", family.name())),
+        ]
+        .into_iter()
+        .find(|p| p.is_file())
+    }
+
+    fn load_file(&self, path: &Path) -> Option<Arc<tokenizers::Tokenizer>> {
+        let mut loaded = self.loaded.lock().unwrap_or_else(|e| e.into_inner());
+        loaded
+            .entry(path.to_path_buf())
+            .or_insert_with(|| match tokenizers::Tokenizer::from_file(path) {
+                Ok(tok) => {
+                    tracing::info!("Loaded tokenizer {}", path.display());
+                    Some(Arc::new(tok))
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to load tokenizer {}: {} — using cl100k_base",
+                        path.display(),
+                        e
+                    );
+                    None
+                }
+            })
+            .clone()
+    }
+}
+
+/// Apply `[tokenizer]` from config.toml. Called whenever providers are
+/// (re)built so config reloads take effect. Learned corrections are kept.
+pub fn configure(config: &crate::config::TokenizerConfig) {
+    REGISTRY.configure(config);
+}
+
+/// Tokenizer for `model` served by `provider`. Corrections are learned per
+/// provider and model, since proxies add their own prompt overhead.
+pub fn for_model(provider: &str, model: &str) -> Tokenizer {
+    REGISTRY.for_model(provider, model)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,4 +498,109 @@ mod tests {
         let base = count_tokens("Hello");
         assert_eq!(count, base + 4);
     }
+
+    #[test]
+    fn test_family_detection() {
+        assert_eq!(Family::of("gpt-4o-mini"), Family::OpenAi);
+        assert_eq!(Family::of("openai/o3-mini"), Family::OpenAi);
+        assert_eq!(Family::of("gpt-5-codex"), Family::OpenAi);
+        assert_eq!(Family::of("gpt-4-turbo"), Family::OpenAiLegacy);
+        assert_eq!(Family::of("qwen/qwen3-coder-plus"), Family::Qwen);
+        assert_eq!(Family::of("GLM-4.6"), Family::Glm);
+        assert_eq!(Family::of("kimi-k2-0905-preview"), Family::Kimi);
+        assert_eq!(Family::of("gemini-2.5-pro"), Family::Gemini);
+        assert_eq!(Family::of("claude-sonnet-4-5"), Family::Claude);
+        assert_eq!(Family::of("my-finetune"), Family::Other);
+    }
+
+    #[test]
+    fn test_newer_openai_models_use_o200k() {
+        let o200k = for_model("family-test", "gpt-4o");
+        assert_eq!(o200k.encoding_name(), "o200k_base");
+        assert_eq!(
+            for_model("family-test", "gpt-4").encoding_name(),
+            "cl100k_base"
+        );
+
+        // o200k's larger vocabulary packs non-English text tighter
+        let text = "Привет! Как дела? Сегодня отличная погода для прогулки.";
+        assert!(o200k.count_raw(text) < count_tokens(text));
+    }
+
+    #[test]
+    fn test_calibration_learns_reported_ratio() {
+        let tok = for_model("calibration-test", "qwen3-coder-plus");
+        assert_eq!(tok.correction(), 1.0);
+        let text = "word ".repeat(400);
+        let raw = tok.count_raw(&text);
+
+        // Too small to learn from
+        tok.calibrate(100, 1000);
+        assert_eq!(tok.correction(), 1.0);
+
+        tok.calibrate(raw, (raw as f64 * 1.2) as u32);
+        assert!((tok.correction() - 1.2).abs() < 0.01);
+
+        // Shared by every handle for the same provider/model
+        let again = for_model("calibration-test", "qwen3-coder-plus");
+        assert_eq!(
+            again.count(&text),
+            (raw as f64 * tok.correction()).round() as usize
+        );
+        assert_eq!(again.uncorrected().count(&text), raw);
+        assert_eq!(for_model("calibration-test", "glm-4.6").correction(), 1.0);
+
+        // Implausible reports are ignored
+        tok.calibrate(raw, 10);
+        tok.calibrate(raw, u32::MAX);
+        assert!((tok.correction() - 1.2).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_loads_tokenizer_json_from_disk() {
+        use tokenizers::models::wordlevel::WordLevel;
+        use tokenizers::pre_tokenizers::whitespace::Whitespace;
+
+        let dir = tempfile::tempdir().unwrap();
+        let vocab = [("[UNK]", 0), ("hello", 1), ("world", 2)]
+            .into_iter()
+            .map(|(w, i)| (w.to_string(), i))
+            .collect();
+        let model = WordLevel::builder()
+            .vocab(vocab)
+            .unk_token("[UNK]".to_string())
+            .build()
+            .unwrap();
+        let mut tok = tokenizers::Tokenizer::new(model);
+        tok.with_pre_tokenizer(Some(Whitespace {}));
+        std::fs::create_dir_all(dir.path().join("glm")).unwrap();
+        tok.save(dir.path().join("glm").join("tokenizer.json"), false)
+            .unwrap();
+        let pinned = dir.path().join("pinned.json");
+        tok.save(&pinned, false).unwrap();
+
+        let mut config = crate::config::TokenizerConfig {
+            dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        config.files.insert("My-Finetune".to_string(), pinned);
+        // A local registry, so the process-wide one stays unconfigured
+        let registry = Registry::new();
+        registry.configure(&config);
+
+        let glm = registry.for_model("hf-test", "glm-4.6");
+        assert_eq!(glm.encoding_name(), "tokenizer.json");
+        assert_eq!(glm.count_raw("hello world hello"), 3);
+        assert_eq!(
+            registry.for_model("hf-test", "my-finetune").encoding_name(),
+            "tokenizer.json"
+        );
+        // No file for this family: cl100k
+        assert_eq!(
+            registry
+                .for_model("hf-test", "deepseek-chat")
+                .encoding_name(),
+            "cl100k_base"
+        );
+    }
 }
@@ -68,8 +68,17 @@ impl Tool for CronManageTool {
                 },
                 "thinking": {
                     "type": "string",
-                    "enum": ["off", "on", "budget"],
-                    "description": "Thinking mode (default: off)"
+                    "enum": ["off", "on", "budget", "disabled"],
+                    "description": "Thinking mode (default: off = the provider's configured thinking). 'on' = extended thinking with a 10k token budget, 'budget' = minimal 1k token budget, 'disabled' = no thinking even if the provider enables it"
+                },
+                "tool_choice": {
+                    "type": "string",
+                    "description": "Constrain the job's first LLM call: 'auto', 'required' (must call some tool), 'none', or the name of a tool the job must call (e.g. 'telegram_send'). Omit for provider default"
+                },
+                "delivery_mode": {
+                    "type": "string",
+                    "enum": ["immediate", "batch"],
+                    "description": "How the job runs (default: immediate). 'batch' sends the prompt as a single tool-less request through the provider's half-price batch API (Anthropic/OpenAI); results are saved and delivered when the batch completes, usually within minutes and at most 24h. Use for summaries and digests that need no tools"
                 },
                 "auto_approve": {
                     "type": "boolean",
@@ -186,6 +195,21 @@ impl CronManageTool {
             .and_then(|v| v.as_str())
             .unwrap_or("off")
             .to_string();
+        let tool_choice = input
+            .get("tool_choice")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty())
+            .map(String::from);
+        let delivery_mode = input
+            .get("delivery_mode")
+            .and_then(|v| v.as_str())
+            .unwrap_or("immediate")
+            .to_string();
+        if !matches!(delivery_mode.as_str(), "immediate" | "batch") {
+            return Ok(ToolResult::error(format!(
+                "Invalid delivery_mode '{delivery_mode}'. Valid: immediate, batch"
+            )));
+        }
         let auto_approve = input
             .get("auto_approve")
             .and_then(|v| v.as_bool())
@@ -195,7 +219,7 @@ impl CronManageTool {
             .and_then(|v| v.as_str())
             .map(String::from);
 
-        let job = CronJob::new(
+        let mut job = CronJob::new(
             name.to_string(),
             cron_expr.to_string(),
             tz,
@@ -206,6 +230,8 @@ impl CronManageTool {
             auto_approve,
             deliver_to.clone(),
         );
+        job.tool_choice = tool_choice;
+        job.delivery_mode = delivery_mode;
 
         let job_id = job.id.to_string();
 
@@ -219,8 +245,8 @@ impl CronManageTool {
             .unwrap_or("none (results logged only)");
 
         Ok(ToolResult::success(format!(
-            "Cron job created:\n  ID: {job_id}\n  Name: {name}\n  Schedule: {cron_expr}\n  Timezone: {}\n  Deliver to: {delivery}\n  Enabled: true",
-            job.timezone
+            "Cron job created:\n  ID: {job_id}\n  Name: {name}\n  Schedule: {cron_expr}\n  Timezone: {}\n  Mode: {}\n  Deliver to: {delivery}\n  Enabled: true",
+            job.timezone, job.delivery_mode
         )))
     }
 
@@ -0,0 +1,102 @@
+//! Load Skill Tool
+//!
+//! Fetches a skill's full instructions, or one of its resource files, when
+//! the model decides the skill is relevant. The system brain only lists
+//! skill names and descriptions (see [`crate::brain::skills`]).
+
+use super::error::Result;
+use super::r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use crate::brain::skills::Skills;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::path::PathBuf;
+
+pub struct LoadSkillTool {
+    /// Global skills directory, the one the system brain's index lists
+    global_dir: PathBuf,
+}
+
+impl LoadSkillTool {
+    pub fn new(global_dir: PathBuf) -> Self {
+        Self { global_dir }
+    }
+}
+
+#[async_trait]
+impl Tool for LoadSkillTool {
+    fn name(&self) -> &str {
+        "load_skill"
+    }
+
+    fn description(&self) -> &str {
+        "Load a skill listed under \"Available Skills\" in the system prompt: its full \
+         instructions plus the scripts and reference files that come with it. Call it when a \
+         request matches a skill's description, before starting the work. Pass `file` to read \
+         one of the skill's resource files."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Skill name, as listed under Available Skills"
+                },
+                "file": {
+                    "type": "string",
+                    "description": "Optional resource file to read, relative to the skill directory (e.g. \"reference.md\", \"scripts/fill.py\")"
+                }
+            },
+            "required": ["name"]
+        })
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::ReadFiles]
+    }
+
+    fn requires_approval(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, input: Value, ctx: &ToolExecutionContext) -> Result<ToolResult> {
+        let name = input
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .trim();
+        if name.is_empty() {
+            return Ok(ToolResult::error("name parameter is required".to_string()));
+        }
+
+        let skills = Skills::discover(&self.global_dir, &ctx.working_directory);
+        let Some(skill) = skills.get(name) else {
+            let available: Vec<&str> = skills.iter().map(|s| s.name.as_str()).collect();
+            return Ok(ToolResult::error(if available.is_empty() {
+                format!(
+                    "No skill named '{}'. No skills are installed — add them under \
+                     ~/.opencrabs/skills/<name>/SKILL.md or .opencrabs/skills/ in the project.",
+                    name
+                )
+            } else {
+                format!(
+                    "No skill named '{}'. Available: {}",
+                    name,
+                    available.join(", ")
+                )
+            }));
+        };
+
+        let loaded = match input.get("file").and_then(|v| v.as_str()) {
+            Some(file) if !file.trim().is_empty() => skill
+                .resource(file.trim())
+                .map(|content| format!("--- {} / {} ---\n{}", skill.name, file.trim(), content)),
+            _ => skill.instructions(),
+        };
+        Ok(match loaded {
+            Ok(content) => ToolResult::success(content),
+            Err(e) => ToolResult::error(format!("{:#}", e)),
+        })
+    }
+}
@@ -39,6 +39,7 @@ pub mod feedback_record;
 pub mod generate_image;
 pub mod http;
 pub mod load_brain_file;
+pub mod load_skill;
 pub mod memory_search;
 pub mod plan_tool;
 pub mod provider_vision;
@@ -362,6 +362,10 @@ impl Tool for PlanTool {
             None
         };
 
+        // Tool choice for the model's next request: after approval it must
+        // start a task, while a task runs it must act through tools.
+        let mut next_step: Option<&str> = None;
+
         let result = match operation {
             PlanOperation::Create {
                 title,
@@ -583,6 +587,7 @@ impl Tool for PlanTool {
                     .map(|(i, t)| format!("  {}. {} — {}", i + 1, t.title, t.description))
                     .collect::<Vec<_>>()
                     .join("\n");
+                next_step = Some(self.name());
 
                 format!(
                     "✓ Plan approved! Proceed to execute tasks in order using 'start_task' and 'complete_task'.\n\n\
@@ -709,6 +714,7 @@ impl Tool for PlanTool {
                 let task_title = task.title.clone();
 
                 current_plan.status = PlanStatus::InProgress;
+                next_step = Some("required");
 
                 format!(
                     "▶️ Started Task #{}: {}\n\n\
@@ -801,6 +807,8 @@ impl Tool for PlanTool {
                 // Check if all tasks are complete
                 if current_plan.is_complete() {
                     current_plan.complete();
+                } else {
+                    next_step = Some(self.name());
                 }
 
                 status_msg
@@ -1011,7 +1019,11 @@ impl Tool for PlanTool {
             }
         }
 
-        Ok(ToolResult::success(result))
+        let result = ToolResult::success(result);
+        Ok(match next_step {
+            Some(choice) => result.with_next_tool_choice(choice),
+            None => result,
+        })
     }
 }
 
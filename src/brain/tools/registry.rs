@@ -3,7 +3,8 @@
 //! Manages the collection of available tools that can be invoked by agents.
 
 use super::error::{Result, ToolError};
-use super::r#trait::{Tool, ToolExecutionContext, ToolResult};
+use super::r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use crate::brain::hooks::{HookOutcome, Hooks};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
@@ -71,6 +72,8 @@ fn normalize_tool_input(tool_name: &str, mut input: Value) -> Value {
 /// runtime registration/removal through a shared `Arc<ToolRegistry>`.
 pub struct ToolRegistry {
     tools: RwLock<HashMap<String, Arc<dyn Tool>>>,
+    /// Hooks run around tool calls; `None` follows hooks.toml
+    hooks: Option<Arc<Hooks>>,
 }
 
 impl ToolRegistry {
@@ -78,9 +81,23 @@ impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: RwLock::new(HashMap::new()),
+            hooks: None,
         }
     }
 
+    /// Use these hooks instead of hooks.toml
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = Some(Arc::new(hooks));
+        self
+    }
+
+    /// Hooks in effect for this registry's tool calls.
+    fn hooks(&self) -> Arc<Hooks> {
+        self.hooks
+            .clone()
+            .unwrap_or_else(crate::brain::hooks::current)
+    }
+
     /// Register a tool (takes `&self` — safe through shared `Arc`)
     pub fn register(&self, tool: Arc<dyn Tool>) {
         let name = tool.name().to_string();
@@ -122,7 +139,37 @@ impl ToolRegistry {
             .collect()
     }
 
-    /// Execute a tool by name
+    /// Run PreToolUse hooks (hooks.toml) on a call. The tool loop calls this
+    /// before permission rules and approval, so both judge the input that
+    /// will actually run, and then sets `pre_tool_use_done` on the context.
+    /// Returns that input — normalized and possibly rewritten by a hook — or
+    /// the reason a hook denied the call.
+    pub async fn pre_tool_use(
+        &self,
+        name: &str,
+        input: Value,
+        context: &ToolExecutionContext,
+    ) -> std::result::Result<Value, String> {
+        let input = normalize_tool_input(name, input);
+        match self
+            .hooks()
+            .pre_tool_use(context.session_id, &context.working_directory, name, &input)
+            .await
+        {
+            HookOutcome::Allow => Ok(input),
+            HookOutcome::Rewrite(rewritten) => {
+                tracing::info!("PreToolUse hook rewrote input for '{}'", name);
+                Ok(rewritten)
+            }
+            HookOutcome::Deny(reason) => {
+                tracing::warn!("PreToolUse hook denied '{}': {}", name, reason);
+                Err(reason)
+            }
+        }
+    }
+
+    /// Execute a tool by name. PreToolUse hooks run first unless the
+    /// context says the caller already ran them ([`Self::pre_tool_use`]).
     pub async fn execute(
         &self,
         name: &str,
@@ -133,6 +180,15 @@ impl ToolRegistry {
             .get(name)
             .ok_or_else(|| ToolError::NotFound(name.to_string()))?;
 
+        let input = if context.pre_tool_use_done {
+            input
+        } else {
+            match self.pre_tool_use(name, input, context).await {
+                Ok(input) => input,
+                Err(reason) => return Ok(ToolResult::error(format!("Blocked by hook: {reason}"))),
+            }
+        };
+
         // Normalize LLM parameter name mistakes before validation
         let input = normalize_tool_input(name, input);
 
@@ -147,9 +203,50 @@ impl ToolRegistry {
             )));
         }
 
+        // Checkpoint files before they change so /undo and /rewind can restore them
+        if let Some(service_context) = &context.service_context {
+            let runs_shell = tool.capabilities().contains(&ToolCapability::ExecuteShell);
+            crate::services::CheckpointService::new(service_context.clone())
+                .before_tool_call(
+                    context.session_id,
+                    name,
+                    &input,
+                    &context.working_directory,
+                    runs_shell,
+                )
+                .await;
+        }
+
         // Execute the tool
         tracing::info!("Executing tool: {}", name);
-        let result = tool.execute(input, context).await?;
+        let hooks = self.hooks();
+        let hook_input = (!hooks.is_empty()).then(|| input.clone());
+        let mut result = tool.execute(input, context).await?;
+
+        // PostToolUse hooks may reject the result; the model gets the reason
+        if let Some(input) = hook_input {
+            let output = if result.success {
+                result.output.as_str()
+            } else {
+                result.error.as_deref().unwrap_or_default()
+            };
+            if let Some(reason) = hooks
+                .post_tool_use(
+                    context.session_id,
+                    &context.working_directory,
+                    name,
+                    &input,
+                    result.success,
+                    output,
+                )
+                .await
+            {
+                tracing::warn!("PostToolUse hook rejected '{}': {}", name, reason);
+                let output = output.to_string();
+                result.success = false;
+                result.error = Some(format!("{output}\n\nBlocked by hook: {reason}"));
+            }
+        }
 
         if result.success {
             tracing::info!("Tool '{}' executed successfully", name);
@@ -179,7 +276,6 @@ impl Default for ToolRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::brain::tools::r#trait::ToolCapability;
     use async_trait::async_trait;
     use uuid::Uuid;
 
@@ -344,4 +440,98 @@ mod tests {
             .unwrap();
         assert!(result.success);
     }
+
+    #[tokio::test]
+    async fn test_registry_runs_hooks() {
+        use crate::brain::hooks::{HookDef, HookEvent};
+
+        let hook = |event, command: &str| HookDef {
+            event,
+            matcher: Some("hooked_tool".to_string()),
+            command: command.to_string(),
+            timeout_secs: 10,
+            enabled: true,
+        };
+        let registry = ToolRegistry::new().with_hooks(Hooks::new(vec![
+            hook(
+                HookEvent::PreToolUse,
+                r#"grep -q '"deny"' && { echo 'not allowed' >&2; exit 2; }; true"#,
+            ),
+            hook(
+                HookEvent::PreToolUse,
+                r#"grep -q '"rewrite"' && echo '{"tool_input": {"message": "rewritten"}}'; true"#,
+            ),
+            hook(
+                HookEvent::PostToolUse,
+                r#"echo '{"decision": "block", "reason": "needs review"}'"#,
+            ),
+        ]));
+        registry.register(Arc::new(MockTool {
+            name: "hooked_tool".to_string(),
+            requires_approval: false,
+        }));
+        let context = ToolExecutionContext::new(Uuid::new_v4());
+
+        let denied = registry
+            .pre_tool_use(
+                "hooked_tool",
+                serde_json::json!({ "message": "deny" }),
+                &context,
+            )
+            .await;
+        assert_eq!(denied, Err("not allowed".to_string()));
+        let rewritten = registry
+            .pre_tool_use(
+                "hooked_tool",
+                serde_json::json!({ "message": "rewrite" }),
+                &context,
+            )
+            .await;
+        assert_eq!(rewritten, Ok(serde_json::json!({ "message": "rewritten" })));
+
+        let rejected = registry
+            .execute(
+                "hooked_tool",
+                serde_json::json!({ "message": "ok" }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert!(!rejected.success);
+        assert_eq!(
+            rejected.error.as_deref(),
+            Some("Mock execution successful\n\nBlocked by hook: needs review")
+        );
+
+        // Callers that skip pre_tool_use still get PreToolUse from execute
+        let denied = registry
+            .execute(
+                "hooked_tool",
+                serde_json::json!({ "message": "deny" }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert!(!denied.success);
+        assert_eq!(
+            denied.error.as_deref(),
+            Some("Blocked by hook: not allowed")
+        );
+
+        // ...but the tool loop, having run it already, doesn't get it twice
+        let mut checked = context.clone();
+        checked.pre_tool_use_done = true;
+        let ran = registry
+            .execute(
+                "hooked_tool",
+                serde_json::json!({ "message": "deny" }),
+                &checked,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            ran.error.as_deref(),
+            Some("Mock execution successful\n\nBlocked by hook: needs review")
+        );
+    }
 }
@@ -549,6 +549,19 @@ impl SlashCommandTool {
             }
         }
 
+        // Remaining spending budget
+        let budget = crate::usage::budget::current();
+        if budget.is_enabled()
+            && let Ok(statuses) =
+                crate::usage::budget::all_statuses(&session_svc.pool(), &budget).await
+        {
+            lines.push(String::new());
+            lines.push("Budget:".to_string());
+            for status in &statuses {
+                lines.push(format!("  {}", status));
+            }
+        }
+
         Ok(ToolResult::success(lines.join("\n")))
     }
 
@@ -1,6 +1,7 @@
 //! Tool trait definition
 
 use super::error::Result;
+use crate::brain::provider::ToolChoice;
 use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -34,6 +35,11 @@ pub struct ToolExecutionContext {
 
     /// Service context — tools use this to create SessionService for /usage stats.
     pub service_context: Option<crate::services::ServiceContext>,
+
+    /// PreToolUse hooks already ran on the input passed to `execute`. The
+    /// tool loop runs them early, before permission rules and approval;
+    /// every other caller leaves this off and `ToolRegistry::execute` runs them.
+    pub pre_tool_use_done: bool,
 }
 
 impl std::fmt::Debug for ToolExecutionContext {
@@ -60,6 +66,7 @@ impl ToolExecutionContext {
             sudo_callback: None,
             shared_working_directory: None,
             service_context: None,
+            pre_tool_use_done: false,
         }
     }
 
@@ -103,6 +110,9 @@ pub struct ToolResult {
     pub images: Vec<(String, String)>,
 }
 
+/// Metadata key for [`ToolResult::with_next_tool_choice`].
+pub const NEXT_TOOL_CHOICE_KEY: &str = "next_tool_choice";
+
 impl ToolResult {
     /// Create a successful result
     pub fn success(output: String) -> Self {
@@ -137,6 +147,19 @@ impl ToolResult {
         self.metadata.insert(key, value);
         self
     }
+
+    /// Constrain the model's next request, e.g. to force the next step of a
+    /// multi-step flow. Stored in metadata as a `ToolChoice::from_setting` value.
+    pub fn with_next_tool_choice(self, choice: &str) -> Self {
+        self.with_metadata(NEXT_TOOL_CHOICE_KEY.to_string(), choice.to_string())
+    }
+
+    /// The tool choice this result asks the tool loop to use next, if any.
+    pub fn next_tool_choice(&self) -> Option<ToolChoice> {
+        self.metadata
+            .get(NEXT_TOOL_CHOICE_KEY)
+            .map(|choice| ToolChoice::from_setting(choice))
+    }
 }
 
 /// Tool capability flags
@@ -185,6 +208,20 @@ pub trait Tool: Send + Sync {
             .any(|cap| dangerous_capabilities.contains(cap))
     }
 
+    /// Whether the tool only observes state — no WriteFiles, ExecuteShell or
+    /// SystemModification capability. Read-only calls in one response may
+    /// run concurrently.
+    fn is_read_only(&self) -> bool {
+        !self.capabilities().iter().any(|cap| {
+            matches!(
+                cap,
+                ToolCapability::WriteFiles
+                    | ToolCapability::ExecuteShell
+                    | ToolCapability::SystemModification
+            )
+        })
+    }
+
     /// Check if this specific invocation requires approval.
     /// Override for tools where only certain operations need approval (e.g. plan finalize).
     fn requires_approval_for_input(&self, _input: &Value) -> bool {
@@ -171,6 +171,8 @@ pub enum ChannelCommand {
     Doctor,
     /// `/evolve` — check for updates and install directly (no LLM needed)
     Evolve,
+    /// `/undo`, `/rewind` and `/fork` errors — plain-text reply
+    Notice(String),
     /// User-defined command with action "prompt" — forward prompt text to the agent
     UserPrompt(String),
     /// User-defined command with action "system" — display text directly
@@ -229,14 +231,25 @@ pub async fn handle_command(
         "/new" => ChannelCommand::NewSession,
         "/sessions" => ChannelCommand::Sessions(format_sessions(session_id, session_svc).await),
         "/stop" => ChannelCommand::Stop,
+        "/undo" => ChannelCommand::Notice(undo_file_changes(session_id, agent).await),
         "/usage" => ChannelCommand::Usage(format_usage(session_id, agent, session_svc).await),
+        _ if trimmed.split_whitespace().next() == Some("/rewind") => {
+            let arg = trimmed.split_whitespace().nth(1);
+            ChannelCommand::Notice(rewind_to_message(session_id, agent, arg).await)
+        }
+        _ if trimmed.split_whitespace().next() == Some("/fork") => {
+            let arg = trimmed.split_whitespace().nth(1);
+            fork_session(session_id, agent, session_svc, arg).await
+        }
         _ if trimmed.starts_with('/') => match_user_command(trimmed),
         _ => ChannelCommand::NotACommand,
     };
 
     // Persist command + response to session history
     let response_text = match &result {
-        ChannelCommand::Help(body) | ChannelCommand::Usage(body) => Some(body.clone()),
+        ChannelCommand::Help(body) | ChannelCommand::Usage(body) | ChannelCommand::Notice(body) => {
+            Some(body.clone())
+        }
         ChannelCommand::Models(resp) => Some(resp.text.clone()),
         ChannelCommand::Sessions(resp) => Some(resp.text.clone()),
         ChannelCommand::NewSession => Some("New session started.".to_string()),
@@ -330,11 +343,14 @@ fn format_help() -> String {
         String::new(),
         "`/compact`  — Compact context (summarize & trim)".to_string(),
         "`/evolve`   — Download latest release & restart".to_string(),
+        "`/fork`     — Branch this chat into a new session".to_string(),
         "`/help`     — Show this message".to_string(),
         "`/models`   — Switch AI model".to_string(),
         "`/new`      — Start a new session".to_string(),
+        "`/rewind`   — Restore files & chat to an earlier message".to_string(),
         "`/sessions` — Switch between sessions".to_string(),
         "`/stop`     — Abort current operation".to_string(),
+        "`/undo`     — Revert the last turn's file changes".to_string(),
         "`/usage`    — Session token & cost stats".to_string(),
     ];
 
@@ -356,6 +372,110 @@ fn format_help() -> String {
     lines.join("\n")
 }
 
+// ── /undo, /rewind ──────────────────────────────────────────────────────────
+
+async fn undo_file_changes(session_id: Uuid, agent: &AgentService) -> String {
+    let checkpoints = crate::services::CheckpointService::new(agent.context().clone());
+    match checkpoints.undo(session_id).await {
+        Ok(Some(report)) => format!(
+            "↩️ Undid the last turn's file changes.\n{}",
+            report.summary()
+        ),
+        Ok(None) => "Nothing to undo — no file changes recorded in this session.".to_string(),
+        Err(e) => format!("⚠️ Undo failed: {}", e),
+    }
+}
+
+/// `/rewind` lists recent messages; `/rewind <n>` restores files and
+/// conversation to just before the `n`th most recent one.
+async fn rewind_to_message(session_id: Uuid, agent: &AgentService, arg: Option<&str>) -> String {
+    use crate::services::checkpoint;
+
+    let checkpoints = crate::services::CheckpointService::new(agent.context().clone());
+    let points = match checkpoints.rewind_points(session_id).await {
+        Ok(points) => points,
+        Err(e) => return format!("⚠️ Rewind failed: {}", e),
+    };
+    let Some(n) = arg.and_then(|a| a.parse::<usize>().ok()) else {
+        return checkpoint::rewind_menu(&points);
+    };
+    let Some(message) = checkpoint::rewind_point(&points, n) else {
+        return format!("⚠️ No message #{} to rewind to.", n);
+    };
+    match checkpoints.rewind(session_id, message).await {
+        Ok(report) => format!(
+            "⏪ Rewound to before:\n> {}\n{}",
+            message.content.chars().take(200).collect::<String>(),
+            report.summary()
+        ),
+        Err(e) => format!("⚠️ Rewind failed: {}", e),
+    }
+}
+
+// ── /fork ───────────────────────────────────────────────────────────────────
+
+/// `/fork` branches the whole conversation into a new session; `/fork <n>`
+/// only what came before the `n`th most recent message (numbered as in
+/// `/rewind`). Replies with a button to switch to the fork.
+async fn fork_session(
+    session_id: Uuid,
+    agent: &AgentService,
+    session_svc: &SessionService,
+    arg: Option<&str>,
+) -> ChannelCommand {
+    use crate::services::checkpoint;
+
+    let before = match arg {
+        None => None,
+        Some(arg) => {
+            let checkpoints = crate::services::CheckpointService::new(agent.context().clone());
+            let points = checkpoints
+                .rewind_points(session_id)
+                .await
+                .unwrap_or_default();
+            match arg
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| checkpoint::rewind_point(&points, n))
+            {
+                Some(message) => Some(message.clone()),
+                None => {
+                    return ChannelCommand::Notice(format!("⚠️ No message #{} to fork at.", arg));
+                }
+            }
+        }
+    };
+
+    let fork = match session_svc.fork_point(session_id, before.as_ref()).await {
+        Ok(Some(at)) => session_svc.fork_session(session_id, at.id).await,
+        Ok(None) => {
+            return ChannelCommand::Notice("Nothing to fork yet — use /new to start over.".into());
+        }
+        Err(e) => Err(e),
+    };
+    match fork {
+        Ok(fork) => {
+            let title = fork.title.clone().unwrap_or_default();
+            let mut text = format!(
+                "🌿 Forked into *{}*. Switch to it to continue there.",
+                title
+            );
+            if let Some(message) = before {
+                text.push_str(&format!(
+                    "\nIt ends just before:\n> {}",
+                    message.content.chars().take(200).collect::<String>()
+                ));
+            }
+            ChannelCommand::Sessions(SessionsResponse {
+                current_session_id: session_id,
+                sessions: vec![(fork.id, title)],
+                text,
+            })
+        }
+        Err(e) => ChannelCommand::Notice(format!("⚠️ Fork failed: {}", e)),
+    }
+}
+
 // ── /usage ──────────────────────────────────────────────────────────────────
 
 async fn format_usage(
@@ -399,6 +519,18 @@ async fn format_usage(
     }
     lines.push(String::new());
 
+    // ── Budgets ([budget] caps, current day/month) ───────────────────
+    let budget = crate::usage::budget::current();
+    if budget.is_enabled()
+        && let Ok(statuses) = crate::usage::budget::all_statuses(&session_svc.pool(), &budget).await
+    {
+        lines.push("*Budget:*".to_string());
+        for status in &statuses {
+            lines.push(format!("  {}", status));
+        }
+        lines.push(String::new());
+    }
+
     // ── Period cards (Today + All-Time, both rendered inline) ────────
     // TUI /usage lets the user cycle T/W/M/A — for the channel dump
     // we show Today and All-Time since those are the two most useful
@@ -813,6 +945,7 @@ pub async fn try_execute_text_command(cmd: &ChannelCommand) -> Option<String> {
     match cmd {
         ChannelCommand::Help(body)
         | ChannelCommand::Usage(body)
+        | ChannelCommand::Notice(body)
         | ChannelCommand::UserSystem(body) => Some(body.clone()),
         ChannelCommand::Doctor => Some(run_doctor()),
         ChannelCommand::Evolve => Some(run_evolve().await),
@@ -861,11 +994,14 @@ mod tests {
         let help = format_help();
         for cmd in [
             "/evolve",
+            "/fork",
             "/help",
             "/models",
             "/new",
+            "/rewind",
             "/sessions",
             "/stop",
+            "/undo",
             "/usage",
         ] {
             assert!(help.contains(cmd), "help text missing {}", cmd);
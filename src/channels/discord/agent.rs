@@ -435,6 +435,8 @@ impl EventHandler for Handler {
             let (approved, always, yolo, approval_id) =
                 if let Some(id) = custom_id.strip_prefix("approve:") {
                     (true, false, false, id.to_string())
+                } else if let Some(id) = custom_id.strip_prefix("pattern:") {
+                    (true, false, false, id.to_string())
                 } else if let Some(id) = custom_id.strip_prefix("always:") {
                     (true, true, false, id.to_string())
                 } else if let Some(id) = custom_id.strip_prefix("yolo:") {
@@ -455,6 +457,11 @@ impl EventHandler for Handler {
             if yolo {
                 crate::utils::persist_auto_always_policy();
             }
+            let allowed_rule = if custom_id.starts_with("pattern:") {
+                crate::utils::accept_allow_rule(&approval_id)
+            } else {
+                None
+            };
 
             let resolved = self
                 .discord_state
@@ -475,12 +482,15 @@ impl EventHandler for Handler {
             }
 
             // Ack the interaction so Discord doesn't show "interaction failed"
-            let _ = comp
-                .create_response(
-                    &ctx.http,
-                    serenity::builder::CreateInteractionResponse::Acknowledge,
-                )
-                .await;
+            let response = match allowed_rule {
+                Some(rule) => serenity::builder::CreateInteractionResponse::Message(
+                    serenity::builder::CreateInteractionResponseMessage::new()
+                        .content(format!("📌 Always allowed `{}`", rule))
+                        .ephemeral(true),
+                ),
+                None => serenity::builder::CreateInteractionResponse::Acknowledge,
+            };
+            let _ = comp.create_response(&ctx.http, response).await;
         }
     }
 }
@@ -625,6 +625,12 @@ pub(crate) async fn handle_message(
                         let _ = channel.say(&http, &text).await;
                     });
                 }
+                ProgressEvent::BudgetAlert { message } => {
+                    tokio::spawn(async move {
+                        let text = format!("💸 {}", message);
+                        let _ = channel.say(&http, &text).await;
+                    });
+                }
                 _ => {}
             }
         })
@@ -742,13 +748,15 @@ pub(crate) async fn handle_message(
     }
 }
 
-/// Build an `ApprovalCallback` that sends a Discord message with 3 buttons
-/// (Yes / Always / No) and waits up to 5 min for a click.
+/// Build an `ApprovalCallback` that sends a Discord message with 5 buttons
+/// (Yes / Always / Allow pattern / YOLO / No) and waits up to 5 min for a click.
 pub(crate) fn make_approval_callback(
     state: Arc<super::DiscordState>,
 ) -> crate::brain::agent::ApprovalCallback {
     use crate::brain::agent::ToolApprovalInfo;
-    use crate::utils::{check_approval_policy, persist_auto_session_policy};
+    use crate::utils::{
+        check_approval_policy, offer_allow_rule, persist_auto_session_policy, withdraw_allow_rule,
+    };
     use serenity::builder::{CreateActionRow, CreateButton, CreateMessage, EditMessage};
     use serenity::model::application::ButtonStyle;
     use serenity::model::id::ChannelId;
@@ -757,7 +765,7 @@ pub(crate) fn make_approval_callback(
     Arc::new(move |info: ToolApprovalInfo| {
         let state = state.clone();
         Box::pin(async move {
-            if let Some(result) = check_approval_policy() {
+            if let Some(result) = check_approval_policy(&info) {
                 return Ok(result);
             }
 
@@ -787,11 +795,15 @@ pub(crate) fn make_approval_callback(
             let safe_input = crate::utils::redact_tool_input(&info.tool_input);
             let input_pretty = serde_json::to_string_pretty(&safe_input)
                 .unwrap_or_else(|_| safe_input.to_string());
-            let text = format!(
-                "🔐 **Tool Approval Required**\n\nTool: `{}`\nInput:\n```json\n{}\n```",
+            let mut text = format!(
+                "🔐 **Tool Approval Required**\n\nTool: `{}`\nInput:\n```json\n{}\n```\nPattern: `{}`",
                 info.tool_name,
                 truncate_str(&input_pretty, 1800),
+                info.suggested_rule,
             );
+            if let Some(rule) = &info.ask_rule {
+                text.push_str(&format!("\nAsked by rule: `{}`", rule));
+            }
 
             let row = CreateActionRow::Buttons(vec![
                 CreateButton::new(format!("approve:{}", approval_id))
@@ -800,6 +812,9 @@ pub(crate) fn make_approval_callback(
                 CreateButton::new(format!("always:{}", approval_id))
                     .label("🔁 Always (session)")
                     .style(ButtonStyle::Primary),
+                CreateButton::new(format!("pattern:{}", approval_id))
+                    .label("📌 Allow pattern")
+                    .style(ButtonStyle::Primary),
                 CreateButton::new(format!("yolo:{}", approval_id))
                     .label("🔥 YOLO")
                     .style(ButtonStyle::Secondary),
@@ -813,6 +828,7 @@ pub(crate) fn make_approval_callback(
             state
                 .register_pending_approval(approval_id.clone(), tx)
                 .await;
+            offer_allow_rule(&approval_id, &info);
             tracing::info!(
                 "Discord approval: registered pending id={}, sending to channel={}",
                 approval_id,
@@ -829,6 +845,7 @@ pub(crate) fn make_approval_callback(
                 Ok(m) => m,
                 Err(e) => {
                     tracing::error!("Discord approval: failed to send message: {}", e);
+                    withdraw_allow_rule(&approval_id);
                     return Ok((false, false));
                 }
             };
@@ -838,7 +855,9 @@ pub(crate) fn make_approval_callback(
                 approval_id
             );
 
-            match tokio::time::timeout(std::time::Duration::from_secs(300), rx).await {
+            let answer = tokio::time::timeout(std::time::Duration::from_secs(300), rx).await;
+            withdraw_allow_rule(&approval_id);
+            match answer {
                 Ok(Ok((approved, always))) => {
                     tracing::info!(
                         "Discord approval: user responded id={}, approved={}, always={}",
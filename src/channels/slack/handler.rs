@@ -289,6 +289,8 @@ pub async fn on_interaction(
                 let (approved, always, yolo, id) =
                     if let Some(id) = action_id.strip_prefix("approve:") {
                         (true, false, false, id.to_string())
+                    } else if let Some(id) = action_id.strip_prefix("pattern:") {
+                        (true, false, false, id.to_string())
                     } else if let Some(id) = action_id.strip_prefix("always:") {
                         (true, true, false, id.to_string())
                     } else if let Some(id) = action_id.strip_prefix("yolo:") {
@@ -302,6 +304,9 @@ pub async fn on_interaction(
                 if yolo {
                     crate::utils::persist_auto_always_policy();
                 }
+                if action_id.starts_with("pattern:") {
+                    crate::utils::accept_allow_rule(&id);
+                }
                 let resolved = state
                     .slack_state
                     .resolve_pending_approval(&id, approved, always)
@@ -1329,6 +1334,21 @@ async fn handle_message(
                         let _ = session.chat_post_message(&req).await;
                     });
                 }
+                ProgressEvent::BudgetAlert { message } => {
+                    let thread_ts_budget = thread_ts_inner.clone();
+                    tokio::spawn(async move {
+                        let session = client.open_session(&token);
+                        let text = format!("💸 {}", message);
+                        let mut req = SlackApiChatPostMessageRequest::new(
+                            channel,
+                            SlackMessageContent::new().with_text(text),
+                        );
+                        if let Some(ref ts) = thread_ts_budget {
+                            req = req.with_thread_ts(ts.clone());
+                        }
+                        let _ = session.chat_post_message(&req).await;
+                    });
+                }
                 ProgressEvent::IntermediateText { text, .. } => {
                     let thread_ts_resp = thread_ts_inner.clone();
                     let ts_ref = sent_intermediate_ts.clone();
@@ -1548,19 +1568,21 @@ async fn handle_message(
     }
 }
 
-/// Build an `ApprovalCallback` that sends a Slack Block Kit message with 3 buttons
-/// (Yes / Always / No) and waits up to 5 min for a click.
+/// Build an `ApprovalCallback` that sends a Slack Block Kit message with 5 buttons
+/// (Yes / Always / Allow pattern / YOLO / No) and waits up to 5 min for a click.
 pub(crate) fn make_approval_callback(
     state: Arc<super::SlackState>,
 ) -> crate::brain::agent::ApprovalCallback {
     use crate::brain::agent::ToolApprovalInfo;
-    use crate::utils::{check_approval_policy, persist_auto_session_policy};
+    use crate::utils::{
+        check_approval_policy, offer_allow_rule, persist_auto_session_policy, withdraw_allow_rule,
+    };
     use tokio::sync::oneshot;
 
     Arc::new(move |info: ToolApprovalInfo| {
         let state = state.clone();
         Box::pin(async move {
-            if let Some(result) = check_approval_policy() {
+            if let Some(result) = check_approval_policy(&info) {
                 return Ok(result);
             }
 
@@ -1598,11 +1620,15 @@ pub(crate) fn make_approval_callback(
             let safe_input = crate::utils::redact_tool_input(&info.tool_input);
             let input_pretty = serde_json::to_string_pretty(&safe_input)
                 .unwrap_or_else(|_| safe_input.to_string());
-            let text = format!(
-                "🔐 *Tool Approval Required*\n\nTool: `{}`\nInput:\n```\n{}\n```",
+            let mut text = format!(
+                "🔐 *Tool Approval Required*\n\nTool: `{}`\nInput:\n```\n{}\n```\nPattern: `{}`",
                 info.tool_name,
                 truncate_str(&input_pretty, 1800),
+                info.suggested_rule,
             );
+            if let Some(rule) = &info.ask_rule {
+                text.push_str(&format!("\nAsked by rule: `{}`", rule));
+            }
 
             let section = SlackBlock::Section(SlackSectionBlock::new().with_text(
                 SlackBlockText::MarkDown(SlackBlockMarkDownText::new(text.clone())),
@@ -1618,6 +1644,12 @@ pub(crate) fn make_approval_callback(
                     "🔁 Always (session)".to_string(),
                 )),
             );
+            let pattern_btn = SlackBlockButtonElement::new(
+                SlackActionId::new(format!("pattern:{}", approval_id)),
+                SlackBlockPlainTextOnly::from(SlackBlockPlainText::new(
+                    "📌 Allow pattern".to_string(),
+                )),
+            );
             let yolo_btn = SlackBlockButtonElement::new(
                 SlackActionId::new(format!("yolo:{}", approval_id)),
                 SlackBlockPlainTextOnly::from(SlackBlockPlainText::new("🔥 YOLO".to_string())),
@@ -1630,6 +1662,7 @@ pub(crate) fn make_approval_callback(
             let actions = SlackBlock::Actions(SlackActionsBlock::new(vec![
                 SlackActionBlockElement::Button(approve_btn),
                 SlackActionBlockElement::Button(always_btn),
+                SlackActionBlockElement::Button(pattern_btn),
                 SlackActionBlockElement::Button(yolo_btn),
                 SlackActionBlockElement::Button(deny_btn),
             ]));
@@ -1649,6 +1682,7 @@ pub(crate) fn make_approval_callback(
             state
                 .register_pending_approval(approval_id.clone(), tx)
                 .await;
+            offer_allow_rule(&approval_id, &info);
             tracing::info!(
                 "Slack approval: registered pending id={}, sending to channel={}",
                 approval_id,
@@ -1659,6 +1693,7 @@ pub(crate) fn make_approval_callback(
                 Ok(r) => r,
                 Err(e) => {
                     tracing::error!("Slack approval: failed to send message: {}", e);
+                    withdraw_allow_rule(&approval_id);
                     return Ok((false, false));
                 }
             };
@@ -1669,7 +1704,9 @@ pub(crate) fn make_approval_callback(
                 approval_id
             );
 
-            match tokio::time::timeout(std::time::Duration::from_secs(300), rx).await {
+            let answer = tokio::time::timeout(std::time::Duration::from_secs(300), rx).await;
+            withdraw_allow_rule(&approval_id);
+            match answer {
                 Ok(Ok((approved, always))) => {
                     tracing::info!(
                         "Slack approval: user responded id={}, approved={}, always={}",
@@ -414,6 +414,8 @@ impl TelegramAgent {
                             let (approved, always, yolo, id) =
                                 if let Some(id) = data.strip_prefix("approve:") {
                                     (true, false, false, id.to_string())
+                                } else if let Some(id) = data.strip_prefix("pattern:") {
+                                    (true, false, false, id.to_string())
                                 } else if let Some(id) = data.strip_prefix("always:") {
                                     (true, true, false, id.to_string())
                                 } else if let Some(id) = data.strip_prefix("yolo:") {
@@ -426,10 +428,15 @@ impl TelegramAgent {
                                     return ResponseResult::Ok(());
                                 };
 
-                            // Persist YOLO (permanent) directly from callback
+                            // Persist YOLO (permanent) and pattern rules directly from callback
                             if yolo {
                                 crate::utils::persist_auto_always_policy();
                             }
+                            let allowed_rule = if data.starts_with("pattern:") {
+                                crate::utils::accept_allow_rule(&id)
+                            } else {
+                                None
+                            };
 
                             let resolved = state.resolve_pending_approval(&id, approved, always).await;
                             tracing::info!(
@@ -447,13 +454,15 @@ impl TelegramAgent {
                             // Edit the approval message: keep original context, append outcome, remove buttons
                             if let Some(msg) = &query.message {
                                 let label = if yolo {
-                                    "\n\n🔥 YOLO — always approved"
+                                    "\n\n🔥 YOLO — always approved".to_string()
                                 } else if always {
-                                    "\n\n🔁 Always approved (session)"
+                                    "\n\n🔁 Always approved (session)".to_string()
+                                } else if let Some(rule) = &allowed_rule {
+                                    format!("\n\n📌 Always allowed: {}", rule)
                                 } else if approved {
-                                    "\n\n✅ Approved"
+                                    "\n\n✅ Approved".to_string()
                                 } else {
-                                    "\n\n❌ Denied"
+                                    "\n\n❌ Denied".to_string()
                                 };
                                 let original_text = match msg {
                                     teloxide::types::MaybeInaccessibleMessage::Regular(m) => {
@@ -1369,6 +1369,12 @@ pub(crate) async fn handle_message(
                             .push(DisplayItem::Intermediate(format!("🔧 {}", message)));
                     }
                 }
+                ProgressEvent::BudgetAlert { message } => {
+                    if let Ok(mut s) = st.lock() {
+                        s.display_queue
+                            .push(DisplayItem::Intermediate(format!("💸 {}", message)));
+                    }
+                }
                 _ => {}
             }
         })
@@ -2606,12 +2612,14 @@ pub(crate) fn split_message(text: &str, max_len: usize) -> Vec<&str> {
 }
 
 /// Build an `ApprovalCallback` that sends an inline-keyboard message to Telegram
-/// and waits (up to 5 min) for the user to tap Yes, Always, or No.
+/// and waits (up to 5 min) for the user to tap Yes, Always, Allow pattern, YOLO or No.
 pub(crate) fn make_approval_callback(
     state: Arc<super::TelegramState>,
 ) -> crate::brain::agent::ApprovalCallback {
     use crate::brain::agent::ToolApprovalInfo;
-    use crate::utils::{check_approval_policy, persist_auto_session_policy};
+    use crate::utils::{
+        check_approval_policy, offer_allow_rule, persist_auto_session_policy, withdraw_allow_rule,
+    };
     use teloxide::payloads::SendMessageSetters;
     use teloxide::prelude::Requester;
     use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, ParseMode};
@@ -2621,7 +2629,7 @@ pub(crate) fn make_approval_callback(
         let state = state.clone();
         Box::pin(async move {
             // Respect config-level approval policy (single source of truth)
-            if let Some(result) = check_approval_policy() {
+            if let Some(result) = check_approval_policy(&info) {
                 return Ok(result);
             }
 
@@ -2651,7 +2659,8 @@ pub(crate) fn make_approval_callback(
             // Build unique approval id
             let approval_id = uuid::Uuid::new_v4().to_string();
 
-            // Build inline keyboard — Yes / Always (session) / YOLO (permanent) / No
+            // Build inline keyboard — Yes / Always (session) / Allow pattern /
+            // YOLO (permanent) / No
             let keyboard = InlineKeyboardMarkup::new(vec![
                 vec![
                     InlineKeyboardButton::callback("✅ Yes", format!("approve:{}", approval_id)),
@@ -2660,6 +2669,10 @@ pub(crate) fn make_approval_callback(
                         format!("always:{}", approval_id),
                     ),
                 ],
+                vec![InlineKeyboardButton::callback(
+                    "📌 Always allow pattern",
+                    format!("pattern:{}", approval_id),
+                )],
                 vec![
                     InlineKeyboardButton::callback(
                         "🔥 YOLO (permanent)",
@@ -2677,11 +2690,18 @@ pub(crate) fn make_approval_callback(
                 input_pretty.truncate(3500);
                 input_pretty.push_str("\n... [truncated]");
             }
-            let text = format!(
-                "🔐 <b>Tool Approval Required</b>\n\nTool: <code>{}</code>\nInput:\n<pre>{}</pre>",
+            let mut text = format!(
+                "🔐 <b>Tool Approval Required</b>\n\nTool: <code>{}</code>\nInput:\n<pre>{}</pre>\nPattern: <code>{}</code>",
                 info.tool_name,
                 escape_html(&input_pretty),
+                escape_html(&info.suggested_rule),
             );
+            if let Some(rule) = &info.ask_rule {
+                text.push_str(&format!(
+                    "\nAsked by rule: <code>{}</code>",
+                    escape_html(rule)
+                ));
+            }
 
             // Register oneshot channel BEFORE sending the message to prevent
             // race condition where user clicks before registration completes
@@ -2689,6 +2709,7 @@ pub(crate) fn make_approval_callback(
             state
                 .register_pending_approval(approval_id.clone(), tx)
                 .await;
+            offer_allow_rule(&approval_id, &info);
             tracing::info!(
                 "Telegram approval: registered pending id={}, sending to chat={}",
                 approval_id,
@@ -2709,12 +2730,15 @@ pub(crate) fn make_approval_callback(
                 }
                 Err(e) => {
                     tracing::error!("Telegram approval: failed to send message: {}", e);
+                    withdraw_allow_rule(&approval_id);
                     return Ok((false, false));
                 }
             }
 
             // Wait up to 5 minutes
-            match tokio::time::timeout(std::time::Duration::from_secs(300), rx).await {
+            let answer = tokio::time::timeout(std::time::Duration::from_secs(300), rx).await;
+            withdraw_allow_rule(&approval_id);
+            match answer {
                 Ok(Ok((approved, always))) => {
                     tracing::info!(
                         "Telegram approval: user responded id={}, approved={}, always={}",
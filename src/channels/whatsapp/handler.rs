@@ -371,6 +371,7 @@ pub(crate) async fn handle_message(
             match id {
                 "wa_approve_yes" => Some(WaApproval::Yes),
                 "wa_approve_always" => Some(WaApproval::Always),
+                "wa_approve_pattern" => Some(WaApproval::Pattern),
                 "wa_approve_yolo" => Some(WaApproval::Yolo),
                 "wa_approve_no" => Some(WaApproval::No),
                 _ => None,
@@ -381,6 +382,8 @@ pub(crate) async fn handle_message(
                 Some(WaApproval::Yes)
             } else if matches!(answer.as_str(), "always" | "sempre") {
                 Some(WaApproval::Always)
+            } else if matches!(answer.as_str(), "pattern" | "padrao" | "padrão") {
+                Some(WaApproval::Pattern)
             } else if matches!(answer.as_str(), "yolo") {
                 Some(WaApproval::Yolo)
             } else if matches!(answer.as_str(), "no" | "n" | "nao" | "não") {
@@ -788,16 +791,32 @@ pub(crate) async fn handle_message(
                     }
                 });
             }
+            ProgressEvent::BudgetAlert { message } => {
+                let client = client_cb.clone();
+                let jid = jid_cb.clone();
+                let alert = format!("{}\n\n💸 {}", MSG_HEADER, message);
+                tokio::spawn(async move {
+                    let msg = waproto::whatsapp::Message {
+                        conversation: Some(alert),
+                        ..Default::default()
+                    };
+                    if let Err(e) = client.send_message(jid, msg).await {
+                        tracing::error!("WhatsApp: budget alert send failed: {}", e);
+                    }
+                });
+            }
             _ => {}
         })
     };
 
     // Build per-call approval callback.
     // If the user previously chose "Always (session)", auto-approve without asking.
-    // Otherwise send a 3-button message (Yes / Always / No) and wait up to 5 min.
+    // Otherwise ask for yes / always / pattern / yolo / no and wait up to 5 min.
     let approval_cb: ApprovalCallback = {
         use crate::channels::whatsapp::WaApproval;
-        use crate::utils::{check_approval_policy, persist_auto_session_policy};
+        use crate::utils::{
+            check_approval_policy, persist_allow_rule, persist_auto_session_policy,
+        };
 
         let client = client.clone();
         let chat_jid = info.source.chat.clone();
@@ -810,24 +829,28 @@ pub(crate) async fn handle_message(
             let wa_state = wa_state.clone();
             Box::pin(async move {
                 // Respect config-level approval policy (single source of truth)
-                if let Some(result) = check_approval_policy() {
+                if let Some(result) = check_approval_policy(&tool_info) {
                     return Ok(result);
                 }
 
                 // Redact secrets before display
                 let safe_input = crate::utils::redact_tool_input(&tool_info.tool_input);
                 let input_preview = serde_json::to_string_pretty(&safe_input).unwrap_or_default();
-                let body = format!(
-                    "🔐 *Tool Approval Required*\n\nTool: `{}`\n```\n{}\n```",
+                let mut body = format!(
+                    "🔐 *Tool Approval Required*\n\nTool: `{}`\n```\n{}\n```\nPattern: `{}`",
                     tool_info.tool_name,
                     truncate_str(&input_preview, 600),
+                    tool_info.suggested_rule,
                 );
+                if let Some(rule) = &tool_info.ask_rule {
+                    body.push_str(&format!("\nAsked by rule: `{}`", rule));
+                }
 
                 // Send plain text approval request (ButtonsMessage is deprecated
                 // by WhatsApp and silently never renders — use text only)
                 let text_msg = waproto::whatsapp::Message {
                     conversation: Some(format!(
-                        "{}\n\n{}\n\nReply *yes*, *always* (session), *yolo* (permanent), or *no* (5 min timeout).",
+                        "{}\n\n{}\n\nReply *yes*, *always* (session), *pattern* (always allow this pattern), *yolo* (permanent), or *no* (5 min timeout).",
                         MSG_HEADER, body
                     )),
                     ..Default::default()
@@ -864,6 +887,18 @@ pub(crate) async fn handle_message(
                         persist_auto_session_policy();
                         Ok((true, true))
                     }
+                    Ok(Ok(WaApproval::Pattern)) => {
+                        tracing::info!(
+                            "WhatsApp approval: user allowed pattern '{}' (phone={})",
+                            tool_info.suggested_rule,
+                            phone_key
+                        );
+                        persist_allow_rule(
+                            &tool_info.suggested_rule,
+                            tool_info.ask_rule.as_deref(),
+                        );
+                        Ok((true, false))
+                    }
                     Ok(Ok(WaApproval::Yolo)) => {
                         tracing::info!("WhatsApp approval: user chose YOLO (phone={})", phone_key);
                         crate::utils::persist_auto_always_policy();
@@ -23,6 +23,8 @@ pub enum WaApproval {
     Yes,
     /// Approve this and all future tool calls for the rest of the session.
     Always,
+    /// Approve and add an `allow` permission rule for the call's pattern.
+    Pattern,
     /// Approve permanently (survives restarts).
     Yolo,
     /// Deny this tool call.
@@ -221,10 +221,22 @@ pub enum CronCommands {
         #[arg(long)]
         model: Option<String>,
 
-        /// Thinking mode: off, on, budget
+        /// Thinking mode: off (provider default), on (10k token budget), budget
+        /// (1k token budget), disabled (no thinking even if the provider
+        /// enables it)
         #[arg(long, default_value = "off")]
         thinking: String,
 
+        /// Constrain the first LLM call: auto, required, none, or a tool name
+        /// the job must call (e.g. telegram_send)
+        #[arg(long)]
+        tool_choice: Option<String>,
+
+        /// Run mode: immediate (agent turn with tools) or batch (one
+        /// tool-less request via the provider's half-price batch API)
+        #[arg(long, default_value = "immediate", value_parser = ["immediate", "batch"])]
+        delivery_mode: String,
+
         /// Auto-approve tool executions
         #[arg(long, default_value = "true")]
         auto_approve: bool,
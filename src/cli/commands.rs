@@ -26,6 +26,32 @@ pub(crate) async fn cmd_status(config: &crate::config::Config) -> Result<()> {
                 provider.name(),
                 provider.default_model()
             );
+            if let Some(pool) = crate::config::health::get_key_pool(provider.name()) {
+                let now = crate::config::health::now_epoch();
+                let quarantined: Vec<String> = pool
+                    .keys
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, key)| {
+                        let left = key.quarantine_left(now)?;
+                        let status = key.last_status.unwrap_or_default();
+                        Some(format!(
+                            "key {} quarantined ({status}, {left}s left)",
+                            i + 1
+                        ))
+                    })
+                    .collect();
+                let mut line = format!(
+                    "  API keys:  {} — active key {}/{}",
+                    pool.keys.len(),
+                    pool.active + 1,
+                    pool.keys.len()
+                );
+                if !quarantined.is_empty() {
+                    line.push_str(&format!(", {}", quarantined.join(", ")));
+                }
+                println!("{line}");
+            }
         }
         Err(_) => println!("  Provider:  not configured"),
     }
@@ -93,6 +119,17 @@ pub(crate) async fn cmd_status(config: &crate::config::Config) -> Result<()> {
                 } else {
                     println!("  Database:  {} ({})", db_path.display(), size_str);
                 }
+                if config.budget.is_enabled() {
+                    match crate::usage::budget::all_statuses(db.pool(), &config.budget).await {
+                        Ok(statuses) => {
+                            for (i, status) in statuses.iter().enumerate() {
+                                let label = if i == 0 { "Budget:" } else { "" };
+                                println!("  {label:<10} {status}");
+                            }
+                        }
+                        Err(e) => println!("  Budget:    unavailable ({e})"),
+                    }
+                }
             }
             Err(_) => println!("  Database:  {} ({})", db_path.display(), size_str),
         }
@@ -25,6 +25,8 @@ pub(crate) async fn cmd_cron(
             provider,
             model,
             thinking,
+            tool_choice,
+            delivery_mode,
             auto_approve,
             deliver_to,
         } => {
@@ -37,6 +39,8 @@ pub(crate) async fn cmd_cron(
                 provider,
                 model,
                 thinking,
+                tool_choice,
+                delivery_mode,
                 auto_approve,
                 deliver_to,
             )
@@ -60,6 +64,8 @@ async fn cmd_add(
     provider: Option<String>,
     model: Option<String>,
     thinking: String,
+    tool_choice: Option<String>,
+    delivery_mode: String,
     auto_approve: bool,
     deliver_to: Option<String>,
 ) -> Result<()> {
@@ -73,7 +79,7 @@ async fn cmd_add(
         anyhow::bail!("A cron job named '{name}' already exists");
     }
 
-    let job = CronJob::new(
+    let mut job = CronJob::new(
         name.clone(),
         cron.clone(),
         tz.clone(),
@@ -84,6 +90,8 @@ async fn cmd_add(
         auto_approve,
         deliver_to.clone(),
     );
+    job.tool_choice = tool_choice;
+    job.delivery_mode = delivery_mode;
 
     let id = job.id.to_string();
     repo.insert(&job).await?;
@@ -92,6 +100,9 @@ async fn cmd_add(
     println!("   ID: {id}");
     println!("   Name: {name}");
     println!("   Schedule: {cron} ({tz})");
+    if job.delivery_mode == "batch" {
+        println!("   Mode: batch (delivered when the provider batch completes)");
+    }
     if let Some(ref d) = deliver_to {
         println!("   Deliver to: {d}");
     }
@@ -125,7 +136,11 @@ async fn cmd_list(repo: &CronJobRepository) -> Result<()> {
 
         println!("{status} {} ({})", job.name, job.id);
         println!("   Schedule: {} ({})", job.cron_expr, job.timezone);
-        println!("   Deliver: {deliver}");
+        if job.delivery_mode == "batch" {
+            println!("   Deliver: {deliver} (batch)");
+        } else {
+            println!("   Deliver: {deliver}");
+        }
         println!("   Last run: {last}");
         println!("   Prompt: {prompt_preview}");
         println!();
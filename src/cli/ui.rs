@@ -33,8 +33,9 @@ async fn cmd_chat_inner(
                 code_exec::CodeExecTool, config_tool::ConfigTool, context::ContextTool,
                 doc_parser::DocParserTool, edit::EditTool, exa_search::ExaSearchTool,
                 generate_image::GenerateImageTool, glob::GlobTool, grep::GrepTool,
-                http::HttpClientTool, load_brain_file::LoadBrainFileTool, ls::LsTool,
-                memory_search::MemorySearchTool, notebook::NotebookEditTool, plan_tool::PlanTool,
+                http::HttpClientTool, load_brain_file::LoadBrainFileTool,
+                load_skill::LoadSkillTool, ls::LsTool, memory_search::MemorySearchTool,
+                notebook::NotebookEditTool, plan_tool::PlanTool,
                 provider_vision::ProviderVisionTool, read::ReadTool, registry::ToolRegistry,
                 session_search::SessionSearchTool, slash_command::SlashCommandTool, task::TaskTool,
                 web_search::WebSearchTool, write::WriteTool,
@@ -83,6 +84,9 @@ async fn cmd_chat_inner(
         }
     };
 
+    // Brain workspace (~/.opencrabs/) — brain files, user commands and skills
+    let brain_path = BrainLoader::resolve_path();
+
     // Create tool registry (Arc-wrapped early so SpawnAgentTool can reference it)
     tracing::debug!("Setting up tool registry");
     let tool_registry = Arc::new(ToolRegistry::new());
@@ -108,6 +112,10 @@ async fn cmd_chat_inner(
     tool_registry.register(Arc::new(MemorySearchTool));
     // On-demand brain file loader — agent fetches USER.md, MEMORY.md etc. only when needed
     tool_registry.register(Arc::new(LoadBrainFileTool));
+    // Skill loader — full skill instructions are fetched only when a skill is relevant
+    tool_registry.register(Arc::new(LoadSkillTool::new(
+        crate::brain::skills::global_dir(&brain_path),
+    )));
     // OpenCrabs file writer — agent can edit/append/overwrite any file in ~/.opencrabs/
     tool_registry.register(Arc::new(WriteOpenCrabsFileTool));
     // Session search — hybrid QMD search across all session message history
@@ -294,7 +302,6 @@ async fn cmd_chat_inner(
     let working_directory = std::env::current_dir().unwrap_or_default();
 
     // Build dynamic system brain from workspace files
-    let brain_path = BrainLoader::resolve_path();
     let brain_loader = BrainLoader::new(brain_path.clone());
     let command_loader = CommandLoader::from_brain_path(&brain_path);
     let user_commands = command_loader.load();
@@ -400,6 +407,8 @@ async fn cmd_chat_inner(
                 tool_description: tool_info.tool_description,
                 tool_input: tool_info.tool_input,
                 capabilities: tool_info.capabilities,
+                ask_rule: tool_info.ask_rule,
+                suggested_rule: tool_info.suggested_rule,
                 response_tx,
                 requested_at: std::time::Instant::now(),
             };
@@ -488,6 +497,9 @@ async fn cmd_chat_inner(
                 // Compaction is now fully silent — summary goes to memory log only
                 ProgressEvent::Compacting => return,
                 ProgressEvent::CompactionSummary { .. } => return,
+                ProgressEvent::ContextPruned { message } => {
+                    progress_sender.send(TuiEvent::SystemMessage(format!("✂️ {}", message)))
+                }
                 ProgressEvent::BuildLine(line) => progress_sender.send(TuiEvent::BuildLine(line)),
                 ProgressEvent::RestartReady { status } => {
                     progress_sender.send(TuiEvent::RestartReady(status))
@@ -506,6 +518,9 @@ async fn cmd_chat_inner(
                 ProgressEvent::SelfHealingAlert { message } => {
                     progress_sender.send(TuiEvent::SystemMessage(format!("🔧 {}", message)))
                 }
+                ProgressEvent::BudgetAlert { message } => {
+                    progress_sender.send(TuiEvent::SystemMessage(format!("💸 {}", message)))
+                }
                 ProgressEvent::StripStreamedContent { bytes, reason } => {
                     progress_sender.send(TuiEvent::StripStreamedContent {
                         session_id,
@@ -513,6 +528,16 @@ async fn cmd_chat_inner(
                         reason,
                     })
                 }
+                ProgressEvent::RateLimitWait {
+                    provider,
+                    model,
+                    wait_secs,
+                } => progress_sender.send(TuiEvent::RateLimitWait {
+                    session_id,
+                    provider,
+                    model,
+                    wait_secs,
+                }),
                 ProgressEvent::ProviderSwitched {
                     to_name,
                     to_model,
@@ -1120,6 +1145,8 @@ async fn cmd_chat_inner(
     // Spawn A2A gateway if configured
     if config.a2a.enabled {
         let a2a_agent = channel_factory.create_agent_service().await;
+        // Debate rounds re-send identical prompts; opt in to the response cache
+        a2a_agent.set_response_cache_ttl(config.response_cache.ttl_for("a2a"));
         let a2a_ctx = service_context.clone();
         let a2a_config = config.a2a.clone();
         tokio::spawn(async move {
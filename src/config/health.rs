@@ -3,6 +3,10 @@
 //! Persisted to `~/.opencrabs/provider_health.json`. Used for auto-fallback:
 //! when the current provider fails, the system can suggest or switch to the
 //! last provider that successfully returned a response.
+//!
+//! Providers with several API keys also keep a per-key record here (by
+//! position, never the secret): which key is active and which ones are
+//! quarantined after a 401/429.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -22,10 +26,42 @@ pub struct ProviderHealth {
     pub consecutive_failures: u32,
 }
 
+/// Health of one API key in a provider's key pool.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyHealth {
+    /// The key is skipped until this time (epoch seconds).
+    pub quarantined_until: Option<u64>,
+    /// Last time the key got a 401/429 (epoch seconds).
+    pub last_limited: Option<u64>,
+    /// HTTP status behind the last quarantine.
+    pub last_status: Option<u16>,
+}
+
+impl KeyHealth {
+    /// Seconds of quarantine left at `now`, if any.
+    pub fn quarantine_left(&self, now: u64) -> Option<u64> {
+        self.quarantined_until
+            .filter(|&until| until > now)
+            .map(|until| until - now)
+    }
+}
+
+/// Key rotation state for a provider with several API keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyPoolHealth {
+    /// Index of the key that served the most recent request.
+    pub active: usize,
+    /// One entry per configured key, in keys.toml order.
+    pub keys: Vec<KeyHealth>,
+}
+
 /// Health state for all providers.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct HealthState {
     pub providers: HashMap<String, ProviderHealth>,
+    /// Key pools by provider name (only providers with several keys).
+    #[serde(default)]
+    pub key_pools: HashMap<String, KeyPoolHealth>,
 }
 
 /// Global in-memory health state (flushed to disk periodically).
@@ -35,62 +71,67 @@ fn health_path() -> PathBuf {
     super::opencrabs_home().join("provider_health.json")
 }
 
+fn load_from_disk() -> HealthState {
+    std::fs::read_to_string(health_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
 /// Load health state from disk (or initialize empty).
 fn ensure_loaded() -> HealthState {
     let mut guard = HEALTH.lock().unwrap_or_else(|e| e.into_inner());
-    if let Some(ref state) = *guard {
-        return state.clone();
-    }
-    let state: HealthState = std::fs::read_to_string(health_path())
-        .ok()
-        .and_then(|s| serde_json::from_str(&s).ok())
-        .unwrap_or_default();
-    *guard = Some(state.clone());
-    state
+    guard.get_or_insert_with(load_from_disk).clone()
 }
 
-/// Persist health state to disk. Silently ignores errors.
-fn flush(state: &HealthState) {
+/// Modify the health state and persist it, holding the lock throughout so
+/// concurrent updates can't overwrite each other. `f` returns whether it
+/// changed anything. Write errors are silently ignored.
+fn update<R>(f: impl FnOnce(&mut HealthState) -> (R, bool)) -> R {
     let mut guard = HEALTH.lock().unwrap_or_else(|e| e.into_inner());
-    *guard = Some(state.clone());
-    if let Ok(json) = serde_json::to_string_pretty(state) {
+    let state = guard.get_or_insert_with(load_from_disk);
+    let (result, changed) = f(state);
+    if changed && let Ok(json) = serde_json::to_string_pretty(state) {
         let _ = std::fs::write(health_path(), json);
     }
+    result
 }
 
 /// Record a successful provider response.
 pub fn record_success(provider_name: &str) {
-    let mut state = ensure_loaded();
-    let entry = state
-        .providers
-        .entry(provider_name.to_string())
-        .or_insert(ProviderHealth {
-            last_success: None,
-            last_failure: None,
-            last_error: None,
-            consecutive_failures: 0,
-        });
-    entry.last_success = Some(now_epoch());
-    entry.consecutive_failures = 0;
-    flush(&state);
+    update(|state| {
+        let entry = state
+            .providers
+            .entry(provider_name.to_string())
+            .or_insert(ProviderHealth {
+                last_success: None,
+                last_failure: None,
+                last_error: None,
+                consecutive_failures: 0,
+            });
+        entry.last_success = Some(now_epoch());
+        entry.consecutive_failures = 0;
+        ((), true)
+    })
 }
 
 /// Record a provider failure.
 pub fn record_failure(provider_name: &str, error: &str) {
-    let mut state = ensure_loaded();
-    let entry = state
-        .providers
-        .entry(provider_name.to_string())
-        .or_insert(ProviderHealth {
-            last_success: None,
-            last_failure: None,
-            last_error: None,
-            consecutive_failures: 0,
-        });
-    entry.last_failure = Some(now_epoch());
-    entry.last_error = Some(error.chars().take(200).collect());
-    entry.consecutive_failures += 1;
-    flush(&state);
+    update(|state| {
+        let entry = state
+            .providers
+            .entry(provider_name.to_string())
+            .or_insert(ProviderHealth {
+                last_success: None,
+                last_failure: None,
+                last_error: None,
+                consecutive_failures: 0,
+            });
+        entry.last_failure = Some(now_epoch());
+        entry.last_error = Some(error.chars().take(200).collect());
+        entry.consecutive_failures += 1;
+        ((), true)
+    })
 }
 
 /// Get the name of the last provider that succeeded (most recent `last_success`).
@@ -111,7 +152,62 @@ pub fn get_health(provider_name: &str) -> Option<ProviderHealth> {
     state.providers.get(provider_name).cloned()
 }
 
-fn now_epoch() -> u64 {
+/// Register a provider's key pool with `key_count` keys, keeping what is
+/// known about existing positions. Returns the (possibly restored) state.
+pub fn register_key_pool(provider_name: &str, key_count: usize) -> KeyPoolHealth {
+    update(|state| {
+        let pool = state
+            .key_pools
+            .entry(provider_name.to_string())
+            .or_default();
+        pool.keys.resize(key_count, KeyHealth::default());
+        if pool.active >= key_count {
+            pool.active = 0;
+        }
+        (pool.clone(), true)
+    })
+}
+
+/// Drop a provider's key pool record (back to a single key).
+pub fn forget_key_pool(provider_name: &str) {
+    update(|state| ((), state.key_pools.remove(provider_name).is_some()))
+}
+
+/// Record which key is serving requests.
+pub fn record_active_key(provider_name: &str, index: usize) {
+    update(|state| match state.key_pools.get_mut(provider_name) {
+        Some(pool) if pool.active != index => {
+            pool.active = index;
+            ((), true)
+        }
+        _ => ((), false),
+    })
+}
+
+/// Quarantine a key until `until` (epoch seconds) after it got `status`.
+pub fn record_key_quarantine(provider_name: &str, index: usize, status: u16, until: u64) {
+    update(|state| {
+        let Some(key) = state
+            .key_pools
+            .get_mut(provider_name)
+            .and_then(|pool| pool.keys.get_mut(index))
+        else {
+            return ((), false);
+        };
+        key.quarantined_until = Some(until);
+        key.last_limited = Some(now_epoch());
+        key.last_status = Some(status);
+        ((), true)
+    })
+}
+
+/// Get the key pool record for a provider with several keys.
+pub fn get_key_pool(provider_name: &str) -> Option<KeyPoolHealth> {
+    let state = ensure_loaded();
+    state.key_pools.get(provider_name).cloned()
+}
+
+pub(crate) fn now_epoch() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
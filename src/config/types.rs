@@ -65,6 +65,26 @@ pub struct Config {
     /// Cron job defaults
     #[serde(default)]
     pub cron: CronConfig,
+
+    /// Token counting (tokenizer files and calibration)
+    #[serde(default)]
+    pub tokenizer: TokenizerConfig,
+
+    /// Exact-match response cache for repeated provider calls
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
+
+    /// Daily/monthly spending caps
+    #[serde(default)]
+    pub budget: BudgetConfig,
+
+    /// Allow/ask/deny rules for tool calls
+    #[serde(default)]
+    pub permissions: PermissionsConfig,
+
+    /// How the context is shrunk when it fills up
+    #[serde(default)]
+    pub compaction: CompactionConfig,
 }
 
 /// Daemon mode configuration (systemd / launchd service).
@@ -478,7 +498,8 @@ pub struct AgentConfig {
     #[serde(default = "default_approval_policy")]
     pub approval_policy: String,
 
-    /// Maximum concurrent tool calls
+    /// Maximum read-only tool calls from one model response run
+    /// concurrently (default: 4, 1 = one after another)
     #[serde(default = "default_max_concurrent")]
     pub max_concurrent: u32,
 
@@ -576,6 +597,395 @@ pub struct CronConfig {
     pub default_model: Option<String>,
 }
 
+/// Token counting settings.
+///
+/// Newer OpenAI models are counted with o200k and everything else with
+/// cl100k, unless a HuggingFace `tokenizer.json` is available for the model
+/// family (Qwen, GLM, Kimi, ...). Files are looked up as
+/// `<dir>/<family>/tokenizer.json` or `<dir>/<family>.json`; `files` pins a
+/// specific file to a model or family name.
+///
+/// Example in config.toml:
+/// ```toml
+/// [tokenizer]
+/// calibrate = true
+///
+/// [tokenizer.files]
+/// "qwen3-coder-plus" = "~/models/qwen3-coder/tokenizer.json"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenizerConfig {
+    /// Learn a per-model correction factor from the input tokens providers
+    /// report, so compaction triggers on real usage (default: true)
+    #[serde(default = "default_tokenizer_calibrate")]
+    pub calibrate: bool,
+
+    /// Directory searched for tokenizer files (default: ~/.opencrabs/tokenizers)
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+
+    /// Model or family name -> tokenizer.json path
+    #[serde(default)]
+    pub files: BTreeMap<String, PathBuf>,
+}
+
+fn default_tokenizer_calibrate() -> bool {
+    true
+}
+
+impl Default for TokenizerConfig {
+    fn default() -> Self {
+        Self {
+            calibrate: default_tokenizer_calibrate(),
+            dir: None,
+            files: BTreeMap::new(),
+        }
+    }
+}
+
+impl TokenizerConfig {
+    /// Tokenizer directory with `~` expanded.
+    pub fn resolved_dir(&self) -> PathBuf {
+        self.dir
+            .as_deref()
+            .map(expand_tilde)
+            .unwrap_or_else(|| opencrabs_home().join("tokenizers"))
+    }
+
+    /// `files` with `~` expanded and keys lowercased.
+    pub fn resolved_files(&self) -> BTreeMap<String, PathBuf> {
+        self.files
+            .iter()
+            .map(|(k, v)| (k.to_lowercase(), expand_tilde(v)))
+            .collect()
+    }
+}
+
+/// Response cache settings.
+///
+/// Byte-identical requests (same model, system prompt, messages, tools and
+/// sampling settings) from opted-in call sites are answered from SQLite
+/// instead of the provider. Each call site opts in with its own TTL; sites
+/// not listed are never cached.
+///
+/// Example in config.toml:
+/// ```toml
+/// [response_cache]
+/// enabled = true
+///
+/// [response_cache.sites]
+/// cron = 3600
+/// a2a = 600
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCacheConfig {
+    /// Master switch (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Call site -> TTL in seconds. Known sites: `cron`, `a2a`
+    #[serde(default)]
+    pub sites: BTreeMap<String, u64>,
+
+    /// Maximum number of stored responses (default: 1000)
+    #[serde(default = "default_response_cache_max_entries")]
+    pub max_entries: usize,
+
+    /// Responses larger than this are not stored, in KB (default: 256)
+    #[serde(default = "default_response_cache_max_entry_kb")]
+    pub max_entry_kb: usize,
+
+    /// Total cache size; least recently used entries are evicted past it,
+    /// in MB (default: 64)
+    #[serde(default = "default_response_cache_max_total_mb")]
+    pub max_total_mb: usize,
+}
+
+fn default_response_cache_max_entries() -> usize {
+    1000
+}
+
+fn default_response_cache_max_entry_kb() -> usize {
+    256
+}
+
+fn default_response_cache_max_total_mb() -> usize {
+    64
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sites: BTreeMap::new(),
+            max_entries: default_response_cache_max_entries(),
+            max_entry_kb: default_response_cache_max_entry_kb(),
+            max_total_mb: default_response_cache_max_total_mb(),
+        }
+    }
+}
+
+impl ResponseCacheConfig {
+    /// TTL for a call site, or `None` when the cache is off or the site
+    /// has not opted in.
+    pub fn ttl_for(&self, site: &str) -> Option<u64> {
+        if !self.enabled {
+            return None;
+        }
+        self.sites.get(site).copied().filter(|&ttl| ttl > 0)
+    }
+}
+
+/// Spending budgets.
+///
+/// Caps are in USD against the usage ledger, per calendar day and month
+/// (local time). The top-level caps cover the whole profile; channels and
+/// cron jobs can have their own caps on top. Every LLM call is checked
+/// first: past `warn_at` of a cap the user is warned once, and at the cap
+/// the call is refused or — with `on_exceed = "downgrade"` — sent to
+/// `downgrade_model` instead.
+///
+/// Example in config.toml:
+/// ```toml
+/// [budget]
+/// daily_usd = 5.0
+/// monthly_usd = 100.0
+/// on_exceed = "downgrade"
+/// downgrade_model = "claude-haiku-4-5"
+///
+/// [budget.channels.telegram]
+/// daily_usd = 1.0
+///
+/// [budget.cron."nightly-report"]
+/// monthly_usd = 10.0
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Profile-wide daily cap
+    #[serde(default)]
+    pub daily_usd: Option<f64>,
+
+    /// Profile-wide monthly cap
+    #[serde(default)]
+    pub monthly_usd: Option<f64>,
+
+    /// Fraction of a cap at which to warn (default: 0.8)
+    #[serde(default = "default_budget_warn_at")]
+    pub warn_at: f64,
+
+    /// What happens at a cap (default: refuse)
+    #[serde(default)]
+    pub on_exceed: BudgetAction,
+
+    /// Model used once a cap is hit with `on_exceed = "downgrade"`. Must be
+    /// served by the same provider; unknown models fall back to the
+    /// provider's default.
+    #[serde(default)]
+    pub downgrade_model: Option<String>,
+
+    /// Caps per channel (`tui`, `telegram`, `discord`, `slack`, `whatsapp`,
+    /// `trello`, `cron`)
+    #[serde(default)]
+    pub channels: BTreeMap<String, BudgetLimits>,
+
+    /// Caps per cron job, keyed by job name
+    #[serde(default)]
+    pub cron: BTreeMap<String, BudgetLimits>,
+}
+
+/// Daily and monthly caps for one channel or cron job.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetLimits {
+    #[serde(default)]
+    pub daily_usd: Option<f64>,
+    #[serde(default)]
+    pub monthly_usd: Option<f64>,
+}
+
+/// What to do with a call once a budget is spent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    /// Fail the call with a budget error
+    #[default]
+    Refuse,
+    /// Send the call to `downgrade_model` instead
+    Downgrade,
+}
+
+fn default_budget_warn_at() -> f64 {
+    0.8
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            daily_usd: None,
+            monthly_usd: None,
+            warn_at: default_budget_warn_at(),
+            on_exceed: BudgetAction::default(),
+            downgrade_model: None,
+            channels: BTreeMap::new(),
+            cron: BTreeMap::new(),
+        }
+    }
+}
+
+impl BudgetConfig {
+    /// True when any cap is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.daily_usd.is_some()
+            || self.monthly_usd.is_some()
+            || !self.channels.is_empty()
+            || !self.cron.is_empty()
+    }
+}
+
+/// Tool permission rules.
+///
+/// Each rule is `"<allow|ask|deny> <tool>(<pattern>)"`. Rules are checked
+/// in order before any approval prompt and the first match wins: `allow`
+/// runs the call without asking, `ask` prompts even when
+/// `agent.approval_policy` would auto-approve, and `deny` blocks it. Calls
+/// no rule matches fall back to `agent.approval_policy`. Picking "always
+/// allow this pattern" at an approval prompt adds an `allow` rule here.
+///
+/// Example in config.toml:
+/// ```toml
+/// [permissions]
+/// rules = [
+///     "deny bash(rm -rf:*)",
+///     "allow bash(cargo test:*)",
+///     "allow write_file(src/**)",
+///     "ask http_request(https://*)",
+/// ]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PermissionsConfig {
+    /// Ordered rules, first match wins
+    #[serde(default)]
+    pub rules: Vec<String>,
+}
+
+/// Context compaction.
+///
+/// Once the context passes `prune_at` of the window, old tool output is
+/// pruned first — no LLM call needed. Results of the listed `tools` longer
+/// than `elide_over_chars` are cut to the tool name, its input and the
+/// first `head_lines` / last `tail_lines` lines, and earlier reads of a file
+/// that was read again later are dropped. The newest `keep_recent` results
+/// are never touched. The LLM summary (at 65% of the window) only runs
+/// when pruning didn't free enough.
+///
+/// Example in config.toml:
+/// ```toml
+/// [compaction]
+/// strategy = "prune_then_summarize"  # or "summarize", "prune"
+/// prune_at = 0.5
+/// keep_recent = 6
+/// elide_over_chars = 2000
+/// head_lines = 5
+/// tail_lines = 5
+/// tools = ["read_file", "bash", "grep", "browser_content"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionConfig {
+    /// Which stages run (default: prune, then summarize)
+    #[serde(default)]
+    pub strategy: CompactionStrategy,
+
+    /// Fraction of the context window at which pruning starts (default: 0.5)
+    #[serde(default = "default_prune_at")]
+    pub prune_at: f64,
+
+    /// Newest tool results left whole (default: 6)
+    #[serde(default = "default_prune_keep_recent")]
+    pub keep_recent: usize,
+
+    /// Tool results longer than this are elided (default: 2000)
+    #[serde(default = "default_elide_over_chars")]
+    pub elide_over_chars: usize,
+
+    /// Lines kept from the start of an elided result (default: 5)
+    #[serde(default = "default_elide_lines")]
+    pub head_lines: usize,
+
+    /// Lines kept from the end of an elided result (default: 5)
+    #[serde(default = "default_elide_lines")]
+    pub tail_lines: usize,
+
+    /// Tools whose output may be elided
+    #[serde(default = "default_prunable_tools")]
+    pub tools: Vec<String>,
+}
+
+/// Stages of context compaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactionStrategy {
+    /// Prune old tool output, summarize with the LLM if still too full
+    #[default]
+    PruneThenSummarize,
+    /// Always summarize with the LLM
+    Summarize,
+    /// Only prune; never summarize (the context is truncated at 90%)
+    Prune,
+}
+
+fn default_prune_at() -> f64 {
+    0.5
+}
+
+fn default_prune_keep_recent() -> usize {
+    6
+}
+
+fn default_elide_over_chars() -> usize {
+    2000
+}
+
+fn default_elide_lines() -> usize {
+    5
+}
+
+fn default_prunable_tools() -> Vec<String> {
+    [
+        "read_file",
+        "bash",
+        "grep",
+        "glob",
+        "ls",
+        "execute_code",
+        "http_request",
+        "web_search",
+        "exa_search",
+        "brave_search",
+        "browser_content",
+        "browser_eval",
+        "browser_find",
+        "parse_document",
+        "session_search",
+        "memory_search",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            strategy: CompactionStrategy::default(),
+            prune_at: default_prune_at(),
+            keep_recent: default_prune_keep_recent(),
+            elide_over_chars: default_elide_over_chars(),
+            head_lines: default_elide_lines(),
+            tail_lines: default_elide_lines(),
+            tools: default_prunable_tools(),
+        }
+    }
+}
+
 /// Debug configuration options
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DebugConfig {
@@ -643,6 +1053,14 @@ pub struct ProviderConfigs {
     #[serde(default)]
     pub vertex: Option<ProviderConfig>,
 
+    /// In-process GGUF model (llama.cpp) — no network endpoint
+    #[serde(default)]
+    pub local: Option<ProviderConfig>,
+
+    /// Ollama server via its native API (`/api/chat`) — no API key
+    #[serde(default)]
+    pub ollama: Option<ProviderConfig>,
+
     /// STT (Speech-to-Text) provider configurations
     #[serde(default)]
     pub stt: Option<SttProviders>,
@@ -724,6 +1142,45 @@ impl ProviderConfigs {
                 return (name.to_string(), model);
             }
         }
+        // Bedrock can authenticate from the AWS environment, so no key check
+        if let Some(c) = self.bedrock.as_ref()
+            && c.enabled
+        {
+            let model = c
+                .default_model
+                .clone()
+                .unwrap_or_else(|| "(default)".to_string());
+            return ("bedrock".to_string(), model);
+        }
+        // Vertex authenticates with a service-account key file, not an API key
+        if let Some(c) = self.vertex.as_ref()
+            && c.enabled
+        {
+            let model = c
+                .default_model
+                .clone()
+                .unwrap_or_else(|| "(default)".to_string());
+            return ("vertex".to_string(), model);
+        }
+        if let Some(c) = self.local.as_ref()
+            && c.enabled
+            && c.model_path.is_some()
+        {
+            let model = c
+                .default_model
+                .clone()
+                .unwrap_or_else(|| "(default)".to_string());
+            return ("local".to_string(), model);
+        }
+        if let Some(c) = self.ollama.as_ref()
+            && c.enabled
+        {
+            let model = c
+                .default_model
+                .clone()
+                .unwrap_or_else(|| "(default)".to_string());
+            return ("ollama".to_string(), model);
+        }
         // Check custom providers
         if let Some((name, cfg)) = self.active_custom() {
             let model = cfg
@@ -808,7 +1265,7 @@ where
 }
 
 /// Fallback provider configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FallbackProviderConfig {
     /// Enable fallback
     #[serde(default)]
@@ -822,6 +1279,44 @@ pub struct FallbackProviderConfig {
     /// Each name must match a configured provider (e.g. "anthropic", "openrouter").
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub providers: Vec<String>,
+
+    /// Consecutive primary failures before the chain sticks to a fallback.
+    #[serde(default = "default_fallback_max_failures")]
+    pub max_consecutive_failures: u32,
+
+    /// Seconds to stay on a fallback before probing the primary again.
+    /// 0 keeps the fallback until restart.
+    #[serde(default = "default_fallback_cooldown_secs")]
+    pub cooldown_secs: u64,
+
+    /// Minimum seconds between the primary's last failure and the next probe.
+    #[serde(default = "default_fallback_probe_interval_secs")]
+    pub probe_interval_secs: u64,
+}
+
+fn default_fallback_max_failures() -> u32 {
+    1
+}
+
+fn default_fallback_cooldown_secs() -> u64 {
+    300
+}
+
+fn default_fallback_probe_interval_secs() -> u64 {
+    60
+}
+
+impl Default for FallbackProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: None,
+            providers: Vec::new(),
+            max_consecutive_failures: default_fallback_max_failures(),
+            cooldown_secs: default_fallback_cooldown_secs(),
+            probe_interval_secs: default_fallback_probe_interval_secs(),
+        }
+    }
 }
 
 /// STT (Speech-to-Text) provider configurations
@@ -1022,6 +1517,17 @@ pub struct ProviderConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
 
+    /// Extra API keys for the same provider (keys.toml `api_keys = [...]`).
+    /// With more than one key, requests rotate across them and a key that
+    /// gets a 401/429 sits out for a while — see `key_rotation`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+
+    /// How to pick among several API keys: `"round_robin"` (default) or
+    /// `"least_limited"` (stay on one key until it gets rate limited).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_rotation: Option<KeyRotation>,
+
     /// API base URL override
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_url: Option<String>,
@@ -1046,7 +1552,9 @@ pub struct ProviderConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub context_window: Option<u32>,
 
-    /// Endpoint type for providers with multiple API modes (e.g. zhipu: "api" or "coding")
+    /// Endpoint type for providers with multiple API modes (e.g. zhipu: "api" or
+    /// "coding"). `"responses"` switches OpenAI-compatible providers to the
+    /// `/v1/responses` API.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub endpoint_type: Option<String>,
 
@@ -1077,12 +1585,134 @@ pub struct ProviderConfig {
     /// Cloud providers that aren't Qwen ignore this flag entirely.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enable_thinking: Option<bool>,
+
+    /// Extended thinking budget in tokens (`[providers.anthropic]` only).
+    /// When set, every request carries `thinking: {type: "enabled",
+    /// budget_tokens: N}` and the streamed reasoning shows in the TUI
+    /// thinking pane. Values below 1024 are raised to the API minimum;
+    /// unset or 0 keeps thinking off. Cron jobs override this per run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<u32>,
+
+    /// Cloud region for providers that are regional (`[providers.bedrock]`
+    /// e.g. `"us-east-1"`, `[providers.vertex]` e.g. `"us-east5"` or
+    /// `"global"`). Unset falls back to the provider's own discovery
+    /// (for Bedrock: `AWS_REGION`, then `~/.aws/config`; for Vertex:
+    /// `us-central1`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+
+    /// AWS access key ID for SigV4 signing (`[providers.bedrock]` in
+    /// keys.toml). Unset falls back to `AWS_ACCESS_KEY_ID` and
+    /// `~/.aws/credentials`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aws_access_key_id: Option<String>,
+
+    /// AWS secret access key paired with `aws_access_key_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aws_secret_access_key: Option<String>,
+
+    /// Optional AWS session token for temporary (STS) credentials.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aws_session_token: Option<String>,
+
+    /// Google Cloud project ID (`[providers.vertex]`). Unset falls back to
+    /// the `project_id` in the service-account key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+
+    /// Path to a service-account JSON key (`[providers.vertex]`). Unset
+    /// falls back to `GOOGLE_APPLICATION_CREDENTIALS`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials_file: Option<String>,
+
+    /// OAuth token endpoint override (`[providers.vertex]`). Defaults to the
+    /// key's `token_uri`; point it at a local stand-in for testing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_url: Option<String>,
+
+    /// GGUF model to load in-process (`[providers.local]`): a file path or
+    /// an `hf:` URI downloaded into the qmd model cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_path: Option<String>,
+
+    /// Layers to offload to the GPU (`[providers.local]`). Unset keeps
+    /// inference on the CPU.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_layers: Option<u32>,
+
+    /// How long Ollama keeps the model loaded after a request
+    /// (`[providers.ollama]`): a duration like `"10m"`, or seconds, with
+    /// `"-1"` keeping it resident. Unset uses the server default (5m).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+
+    /// Requests/tokens per minute allowed against this provider
+    /// (`[providers.<name>.rate_limit]`). Enforced process-wide, so every
+    /// session, channel, subagent and cron job draws from the same budget.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitConfig>,
 }
 
 fn default_enabled() -> bool {
     true
 }
 
+impl ProviderConfig {
+    /// Every configured API key in rotation order: `api_key` first, then
+    /// `api_keys`, with blanks and duplicates dropped.
+    pub fn all_api_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::new();
+        for key in self.api_key.iter().chain(&self.api_keys) {
+            if !key.is_empty() && key != "__EXISTING_KEY__" && !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+        keys
+    }
+}
+
+/// Key selection strategy for providers with several API keys
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyRotation {
+    /// Spread requests evenly across the keys
+    #[default]
+    RoundRobin,
+    /// Use the key that was rate limited longest ago (or never)
+    LeastLimited,
+}
+
+/// Per-provider rate budget. Requests beyond it wait for the bucket to
+/// refill instead of being sent and bounced with a 429.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct RateLimitConfig {
+    /// Requests per minute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm: Option<u32>,
+
+    /// Tokens per minute (prompt + completion)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tpm: Option<u32>,
+
+    /// Budgets for individual models, applied on top of the provider-wide
+    /// one (`[providers.<name>.rate_limit.models."<model>"]`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, ModelRateLimit>,
+}
+
+/// Rate budget for a single model
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ModelRateLimit {
+    /// Requests per minute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpm: Option<u32>,
+
+    /// Tokens per minute (prompt + completion)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tpm: Option<u32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
     /// Path to SQLite database file
@@ -1424,27 +2054,27 @@ fn merge_provider_keys(mut base: ProviderConfigs, keys: ProviderConfigs) -> Prov
     // Guard: never merge the sentinel placeholder that /models uses internally
     let is_real_key = |k: &str| !k.is_empty() && k != "__EXISTING_KEY__";
 
-    // Merge each provider's api_key if present in keys
+    // Merge each provider's api_key (and extra api_keys) if present in keys
     if let Some(k) = keys.anthropic
-        && let Some(key) = k.api_key
-        && is_real_key(&key)
+        && let Some((key, extra)) = split_api_keys(&k)
     {
         let entry = base.anthropic.get_or_insert_with(ProviderConfig::default);
         entry.api_key = Some(key);
+        entry.api_keys = extra;
     }
     if let Some(k) = keys.openai
-        && let Some(key) = k.api_key
-        && is_real_key(&key)
+        && let Some((key, extra)) = split_api_keys(&k)
     {
         let entry = base.openai.get_or_insert_with(ProviderConfig::default);
         entry.api_key = Some(key);
+        entry.api_keys = extra;
     }
     if let Some(k) = keys.openrouter
-        && let Some(key) = k.api_key
-        && is_real_key(&key)
+        && let Some((key, extra)) = split_api_keys(&k)
     {
         let entry = base.openrouter.get_or_insert_with(ProviderConfig::default);
         entry.api_key = Some(key);
+        entry.api_keys = extra;
     }
     tracing::debug!(
         "merge_provider_keys: minimax keys present={}, base present={}",
@@ -1452,46 +2082,63 @@ fn merge_provider_keys(mut base: ProviderConfigs, keys: ProviderConfigs) -> Prov
         base.minimax.is_some()
     );
     if let Some(k) = keys.minimax
-        && let Some(key) = k.api_key
-        && is_real_key(&key)
+        && let Some((key, extra)) = split_api_keys(&k)
     {
         let entry = base.minimax.get_or_insert_with(ProviderConfig::default);
         entry.api_key = Some(key);
+        entry.api_keys = extra;
     }
     if let Some(k) = keys.gemini
-        && let Some(key) = k.api_key
-        && is_real_key(&key)
+        && let Some((key, extra)) = split_api_keys(&k)
     {
         let entry = base.gemini.get_or_insert_with(ProviderConfig::default);
         entry.api_key = Some(key);
+        entry.api_keys = extra;
     }
     if let Some(k) = keys.github
-        && let Some(key) = k.api_key
-        && is_real_key(&key)
+        && let Some((key, extra)) = split_api_keys(&k)
     {
         let entry = base.github.get_or_insert_with(ProviderConfig::default);
         entry.api_key = Some(key);
+        entry.api_keys = extra;
     }
     // Merge zhipu
     if let Some(k) = keys.zhipu
-        && let Some(key) = k.api_key
-        && is_real_key(&key)
+        && let Some((key, extra)) = split_api_keys(&k)
     {
         let entry = base.zhipu.get_or_insert_with(ProviderConfig::default);
         entry.api_key = Some(key);
+        entry.api_keys = extra;
+    }
+    // Merge bedrock — either a Bedrock API key or an IAM key pair.
+    if let Some(k) = keys.bedrock {
+        let key = k.api_key.filter(|key| is_real_key(key));
+        let has_iam = k.aws_access_key_id.as_deref().is_some_and(is_real_key)
+            && k.aws_secret_access_key.as_deref().is_some_and(is_real_key);
+        if key.is_some() || has_iam {
+            let entry = base.bedrock.get_or_insert_with(ProviderConfig::default);
+            if key.is_some() {
+                entry.api_key = key;
+            }
+            if has_iam {
+                entry.aws_access_key_id = k.aws_access_key_id;
+                entry.aws_secret_access_key = k.aws_secret_access_key;
+                entry.aws_session_token = k.aws_session_token.filter(|t| !t.is_empty());
+            }
+        }
     }
     // Merge qwen (DashScope API key). Auto-enable + create the entry if
     // keys.toml has a key but config.toml doesn't — the user authenticated
     // through onboarding and wants Qwen on.
     if let Some(k) = keys.qwen
-        && let Some(key) = k.api_key
-        && is_real_key(&key)
+        && let Some((key, extra)) = split_api_keys(&k)
     {
         let entry = base.qwen.get_or_insert_with(|| ProviderConfig {
             enabled: true,
             ..Default::default()
         });
         entry.api_key = Some(key);
+        entry.api_keys = extra;
         if entry.default_model.is_none() && k.default_model.is_some() {
             entry.default_model = k.default_model;
         }
@@ -1505,9 +2152,7 @@ fn merge_provider_keys(mut base: ProviderConfigs, keys: ProviderConfigs) -> Prov
     if let Some(custom_keys) = keys.custom {
         let base_customs = base.custom.get_or_insert_with(BTreeMap::default);
         for (name, key_cfg) in custom_keys {
-            if let Some(key) = key_cfg.api_key
-                && is_real_key(&key)
-            {
+            if let Some((key, extra)) = split_api_keys(&key_cfg) {
                 use std::collections::btree_map::Entry;
                 match base_customs.entry(name.clone()) {
                     Entry::Occupied(mut occupied) => {
@@ -1515,7 +2160,9 @@ fn merge_provider_keys(mut base: ProviderConfigs, keys: ProviderConfigs) -> Prov
                             "merge_provider_keys: merging api_key for custom '{}'",
                             name
                         );
-                        occupied.get_mut().api_key = Some(key);
+                        let entry = occupied.get_mut();
+                        entry.api_key = Some(key);
+                        entry.api_keys = extra;
                     }
                     Entry::Vacant(vacant) => {
                         // Key exists in keys.toml but not in config.toml.
@@ -1526,6 +2173,7 @@ fn merge_provider_keys(mut base: ProviderConfigs, keys: ProviderConfigs) -> Prov
                         );
                         vacant.insert(ProviderConfig {
                             api_key: Some(key),
+                            api_keys: extra,
                             base_url: key_cfg.base_url,
                             default_model: key_cfg.default_model,
                             ..Default::default()
@@ -1615,6 +2263,17 @@ fn merge_provider_keys(mut base: ProviderConfigs, keys: ProviderConfigs) -> Prov
     base
 }
 
+/// The first real key of a keys.toml entry plus the remaining ones, or
+/// `None` when it has no usable key.
+fn split_api_keys(k: &ProviderConfig) -> Option<(String, Vec<String>)> {
+    let mut keys = k.all_api_keys();
+    if keys.is_empty() {
+        return None;
+    }
+    let first = keys.remove(0);
+    Some((first, keys))
+}
+
 /// Merge channel tokens from keys.toml into existing channels config
 /// Tokens from keys.toml override values in config.toml
 fn merge_channel_keys(mut base: ChannelsConfig, keys: ChannelsConfig) -> ChannelsConfig {
@@ -1704,6 +2363,11 @@ impl Default for Config {
             a2a: A2aConfig::default(),
             image: ImageConfig::default(),
             cron: CronConfig::default(),
+            tokenizer: TokenizerConfig::default(),
+            response_cache: ResponseCacheConfig::default(),
+            budget: BudgetConfig::default(),
+            permissions: PermissionsConfig::default(),
+            compaction: CompactionConfig::default(),
         }
     }
 }
@@ -2015,6 +2679,10 @@ impl Config {
         "gateway",
         "image",
         "cron",
+        "tokenizer",
+        "response_cache",
+        "budget",
+        "permissions",
     ];
 
     /// Check for unknown top-level keys and log warnings.
@@ -2308,6 +2976,10 @@ impl Config {
             a2a: overlay.a2a,
             image: overlay.image,
             cron: overlay.cron,
+            tokenizer: overlay.tokenizer,
+            response_cache: overlay.response_cache,
+            budget: overlay.budget,
+            permissions: overlay.permissions,
         }
     }
 
@@ -3169,6 +3841,15 @@ pub fn resolve_provider_from_config(config: &Config) -> (&str, &str) {
             .unwrap_or("qwen3.6-plus");
         return ("Qwen", model);
     }
+    if config.providers.ollama.as_ref().is_some_and(|p| p.enabled) {
+        let model = config
+            .providers
+            .ollama
+            .as_ref()
+            .and_then(|p| p.default_model.as_deref())
+            .unwrap_or("default");
+        return ("Ollama", model);
+    }
     if let Some((name, cfg)) = config.providers.active_custom() {
         let model = cfg.default_model.as_deref().unwrap_or("default");
         return (name, model);
@@ -122,6 +122,8 @@ impl ProviderUpdater {
             "gemini" | "google" => &mut config.providers.gemini,
             "bedrock" | "aws-bedrock" => &mut config.providers.bedrock,
             "vertex" | "vertexai" => &mut config.providers.vertex,
+            "local" => &mut config.providers.local,
+            "ollama" => &mut config.providers.ollama,
             _ => {
                 debug!("Unknown provider: {}, skipping", provider.id);
                 return false;
@@ -0,0 +1,285 @@
+//! Batch delivery
+//!
+//! Jobs with `delivery_mode = "batch"` skip the agent loop: the prompt goes
+//! out as a single tool-less request through the provider's batch API
+//! (half price, results within 24h). The submitted batch is recorded in
+//! `llm_batches`, and every scheduler tick polls the pending rows — so a
+//! restart only resumes polling. When a batch ends its results go to the
+//! owner named by `purpose`: the cron run (saved + delivered), the session
+//! categorizer, or the RSI digest.
+//!
+//! The scheduler also submits the non-interactive background work itself —
+//! session categorization and the RSI digest analysis — once a day each,
+//! when the cron provider has a batch API (see [`submit_background`]).
+
+use super::scheduler::{deliver_all, thinking_budget_for_mode};
+use crate::brain::agent::AgentService;
+use crate::brain::provider::structured::response_text;
+use crate::brain::provider::{
+    BatchRequest, BatchResult, BatchState, LLMRequest, Message, Provider,
+};
+use crate::brain::rsi;
+use crate::config::Config;
+use crate::db::models::{CronJob, LlmBatch};
+use crate::db::{
+    CronJobRepository, CronJobRunRepository, LlmBatchRepository, Pool, UsageLedgerRepository,
+};
+use crate::usage::categorizer;
+use uuid::Uuid;
+
+/// Minimum gap between two background batches of the same purpose.
+const BACKGROUND_BATCH_INTERVAL_HOURS: i64 = 24;
+
+/// `llm_batches.purpose` for batch-mode cron runs.
+pub(super) const CRON_PURPOSE: &str = "cron";
+
+/// Batch requests are billed at half the synchronous rate.
+const BATCH_PRICE_FACTOR: f64 = 0.5;
+
+/// Submit a batch-mode job's prompt (with the agent's system brain) and
+/// record the batch against `run_id`. The run stays "running" until
+/// [`poll_pending`] sees the batch end.
+pub(super) async fn submit_job(
+    job: &CronJob,
+    agent: &AgentService,
+    model: Option<String>,
+    run_id: &str,
+    batch_repo: &LlmBatchRepository,
+) -> anyhow::Result<String> {
+    let provider = agent.provider();
+    let model = model.unwrap_or_else(|| agent.provider_model());
+    let mut request = LLMRequest::new(model, vec![Message::user(job.prompt.clone())])
+        .with_max_tokens(agent.max_tokens());
+    if let Some(system) = agent.system_brain() {
+        request = request.with_system(system.clone());
+    }
+    request.thinking_budget = thinking_budget_for_mode(&job.thinking);
+
+    // The run id doubles as the request's custom_id
+    let batch_id = provider
+        .submit_batch(vec![BatchRequest::new(run_id, request)])
+        .await?;
+    batch_repo
+        .insert(&LlmBatch::new_pending(
+            batch_id.clone(),
+            provider.name().to_string(),
+            CRON_PURPOSE,
+            Some(run_id.to_string()),
+            1,
+        ))
+        .await?;
+    Ok(batch_id)
+}
+
+/// Whether a background batch for `purpose` is due: none submitted within
+/// the last [`BACKGROUND_BATCH_INTERVAL_HOURS`].
+pub(super) async fn background_due(
+    batches: &LlmBatchRepository,
+    purpose: &str,
+) -> anyhow::Result<bool> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::hours(BACKGROUND_BATCH_INTERVAL_HOURS);
+    Ok(batches
+        .last_submitted(purpose)
+        .await?
+        .is_none_or(|last| last < cutoff))
+}
+
+/// Submit the daily background batches — classification of sessions the
+/// startup heuristic hasn't seen, and the RSI digest analysis — on the cron
+/// provider. Does nothing when that provider has no batch API.
+pub(super) async fn submit_background(
+    pool: &Pool,
+    batches: &LlmBatchRepository,
+) -> anyhow::Result<()> {
+    let categorize_due = background_due(batches, categorizer::BATCH_PURPOSE).await?;
+    let digest_due = background_due(batches, rsi::DIGEST_BATCH_PURPOSE).await?;
+    if !categorize_due && !digest_due {
+        return Ok(());
+    }
+
+    let config = Config::load()?;
+    let provider_name = config
+        .cron
+        .default_provider
+        .clone()
+        .unwrap_or_else(|| config.providers.active_provider_and_model().0);
+    let provider = crate::brain::provider::create_provider_by_name(&config, &provider_name).await?;
+    if !provider.supports_batch() {
+        return Ok(());
+    }
+    let model = config
+        .cron
+        .default_model
+        .clone()
+        .unwrap_or_else(|| provider.default_model().to_string());
+
+    if categorize_due
+        && let Some(id) = categorizer::submit_categorize_batch(pool, &*provider, &model).await?
+    {
+        tracing::info!("Submitted session classification batch {id}");
+    }
+    if digest_due && let Some(id) = rsi::submit_digest_batch(pool, &*provider, &model).await? {
+        tracing::info!("Submitted RSI digest batch {id}");
+    }
+    Ok(())
+}
+
+/// Repositories the poller hands results to.
+pub(super) struct BatchOwners<'a> {
+    pub pool: &'a Pool,
+    pub batches: &'a LlmBatchRepository,
+    pub jobs: &'a CronJobRepository,
+    pub runs: &'a CronJobRunRepository,
+    /// Session batch-mode cron usage is recorded against
+    pub cron_session_id: Option<Uuid>,
+}
+
+/// Poll every pending batch once, finishing the ones that ended.
+pub(super) async fn poll_pending(owners: &BatchOwners<'_>) -> anyhow::Result<()> {
+    let pending = owners.batches.list_pending().await?;
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let config = Config::load()?;
+    for batch in &pending {
+        if let Err(e) = poll_one(&config, owners, batch).await {
+            tracing::warn!("Batch {} ({}) poll failed: {e}", batch.id, batch.purpose);
+        }
+    }
+    Ok(())
+}
+
+async fn poll_one(
+    config: &Config,
+    owners: &BatchOwners<'_>,
+    batch: &LlmBatch,
+) -> anyhow::Result<()> {
+    let provider = crate::brain::provider::create_provider_by_name(config, &batch.provider).await?;
+    let status = provider.poll_batch(&batch.id).await?;
+
+    match status.state {
+        BatchState::InProgress => {
+            tracing::debug!(
+                "Batch {} still running — {} pending, {} done",
+                batch.id,
+                status.pending,
+                status.succeeded + status.errored
+            );
+        }
+        BatchState::Failed => {
+            let error = status.error.unwrap_or_else(|| "batch failed".to_string());
+            tracing::warn!("Batch {} failed: {error}", batch.id);
+            if batch.purpose == CRON_PURPOSE
+                && let Some(ref run_id) = batch.reference_id
+            {
+                finish_cron_run(owners, &*provider, run_id, Err(error.clone())).await?;
+            }
+            owners.batches.mark_failed(&batch.id, &error).await?;
+        }
+        BatchState::Ended => {
+            let results = provider.fetch_batch_results(&batch.id).await?;
+            tracing::info!("Batch {} ended with {} result(s)", batch.id, results.len());
+            // Hand off before marking complete: a crash in between re-polls
+            // the batch rather than dropping its results.
+            match batch.purpose.as_str() {
+                CRON_PURPOSE => {
+                    if let Some(ref run_id) = batch.reference_id {
+                        let outcome = take_result(results, run_id);
+                        finish_cron_run(owners, &*provider, run_id, outcome).await?;
+                    }
+                }
+                categorizer::BATCH_PURPOSE => {
+                    let saved = categorizer::apply_categorize_batch(owners.pool, &results).await?;
+                    tracing::info!("Categorized {saved} session(s) from batch {}", batch.id);
+                }
+                rsi::DIGEST_BATCH_PURPOSE => {
+                    let found = rsi::apply_digest_batch(&results)?;
+                    tracing::info!("RSI digest batch {} found {found} improvement(s)", batch.id);
+                }
+                other => tracing::warn!("Batch {} has unknown purpose '{other}'", batch.id),
+            }
+            owners.batches.mark_completed(&batch.id).await?;
+        }
+    }
+    Ok(())
+}
+
+/// The outcome for one `custom_id`, or an error when the batch dropped it.
+fn take_result(
+    results: Vec<BatchResult>,
+    custom_id: &str,
+) -> std::result::Result<crate::brain::provider::LLMResponse, String> {
+    results
+        .into_iter()
+        .find(|r| r.custom_id == custom_id)
+        .map(|r| r.outcome)
+        .unwrap_or_else(|| Err("batch returned no result for this run".to_string()))
+}
+
+/// Save a batch-mode run's outcome, record its usage and deliver it like an
+/// immediate run would.
+async fn finish_cron_run(
+    owners: &BatchOwners<'_>,
+    provider: &dyn Provider,
+    run_id: &str,
+    outcome: std::result::Result<crate::brain::provider::LLMResponse, String>,
+) -> anyhow::Result<()> {
+    let Some(run) = owners.runs.find_by_id(run_id).await? else {
+        tracing::warn!("Batch finished for unknown cron run {run_id}");
+        return Ok(());
+    };
+    let job = owners.jobs.find_by_id(&run.job_id.to_string()).await?;
+    let deliver_to = job.as_ref().and_then(|j| j.deliver_to.as_deref());
+
+    match outcome {
+        Ok(response) => {
+            let clean = crate::utils::sanitize::strip_llm_artifacts(&response_text(&response));
+            let usage = response.usage;
+            let cost = provider.calculate_cost_with_cache(
+                &response.model,
+                usage.input_tokens,
+                usage.output_tokens,
+                usage.cache_creation_tokens,
+                usage.cache_read_tokens,
+            ) * BATCH_PRICE_FACTOR;
+            tracing::info!(
+                "Cron job '{}' batch completed — {} tokens, ${:.6}",
+                run.job_name,
+                usage.input_tokens + usage.output_tokens,
+                cost
+            );
+
+            owners
+                .runs
+                .complete_success(
+                    run_id,
+                    &clean,
+                    usage.input_tokens as i64,
+                    usage.output_tokens as i64,
+                    cost,
+                )
+                .await?;
+            if let Some(session_id) = owners.cron_session_id
+                && let Err(e) = UsageLedgerRepository::new(owners.pool.clone())
+                    .record_for_channel(
+                        &session_id.to_string(),
+                        &response.model,
+                        usage.billable_total() as i32,
+                        cost,
+                        &format!("cron:{}", run.job_name),
+                    )
+                    .await
+            {
+                tracing::warn!("Failed to record batch usage: {e}");
+            }
+            deliver_all(deliver_to, &run.job_name, &clean).await;
+        }
+        Err(error) => {
+            tracing::error!("Cron job '{}' batch error: {error}", run.job_name);
+            owners.runs.complete_error(run_id, &error).await?;
+            let msg = format!("Cron job '{}' failed: {error}", run.job_name);
+            deliver_all(deliver_to, &run.job_name, &msg).await;
+        }
+    }
+    Ok(())
+}
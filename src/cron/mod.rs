@@ -5,6 +5,8 @@
 //! follows the user, falls back to initial session. Results are optionally
 //! delivered to a configured channel (Telegram, Discord, Slack).
 
+mod batches;
 mod scheduler;
 
 pub use scheduler::CronScheduler;
+pub(crate) use scheduler::thinking_budget_for_mode;
@@ -3,12 +3,18 @@
 //! Background task that checks the `cron_jobs` table every 60 seconds,
 //! executes due jobs in a dedicated "Cron" session, and delivers results
 //! to the configured channel. Cron jobs are fully isolated from the TUI —
-//! they never share or mutate the user's active session.
+//! they never share or mutate the user's active session. Jobs in batch
+//! delivery mode are submitted to the provider's batch API instead and
+//! finished by the same tick once the batch ends, which also submits the
+//! daily categorizer and RSI digest batches (see `batches`).
 
+use super::batches::{self, BatchOwners};
+use crate::brain::provider::ToolChoice;
 use crate::channels::ChannelFactory;
 use crate::config::Config;
 use crate::db::CronJobRepository;
 use crate::db::CronJobRunRepository;
+use crate::db::LlmBatchRepository;
 use crate::db::models::{CronJob, CronJobRun};
 use crate::services::{ServiceContext, SessionService};
 use chrono::Utc;
@@ -21,14 +27,20 @@ use uuid::Uuid;
 /// Name used for the shared cron session.
 const CRON_SESSION_NAME: &str = "Cron";
 
+/// How often ticks look for due background batches (each is submitted daily).
+const BACKGROUND_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
 /// Background cron scheduler that polls the database and executes due jobs.
 pub struct CronScheduler {
     repo: CronJobRepository,
     run_repo: CronJobRunRepository,
+    batch_repo: LlmBatchRepository,
     factory: Arc<ChannelFactory>,
     service_context: ServiceContext,
     /// Dedicated session for all cron jobs — isolated from TUI sessions.
     cron_session_id: Option<Uuid>,
+    /// When the last tick looked for due background batches.
+    background_checked_at: std::sync::Mutex<Option<std::time::Instant>>,
     /// Kept for API compatibility but no longer used for session resolution.
     #[allow(dead_code)]
     shared_session_id: Arc<Mutex<Option<Uuid>>>,
@@ -45,9 +57,11 @@ impl CronScheduler {
         Self {
             repo,
             run_repo,
+            batch_repo: LlmBatchRepository::new(service_context.pool()),
             factory,
             service_context,
             cron_session_id: None,
+            background_checked_at: std::sync::Mutex::new(None),
             shared_session_id,
         }
     }
@@ -106,7 +120,8 @@ impl CronScheduler {
         Ok(session.id)
     }
 
-    /// One scheduler tick: check all enabled jobs and execute any that are due.
+    /// One scheduler tick: check all enabled jobs and execute any that are due,
+    /// then poll outstanding provider batches and submit due background ones.
     async fn tick(&self) -> anyhow::Result<()> {
         let jobs = self.repo.list_enabled().await?;
         let now = Utc::now();
@@ -134,17 +149,50 @@ impl CronScheduler {
                 let factory = self.factory.clone();
                 let ctx = self.service_context.clone();
                 let run_repo = self.run_repo.clone();
+                let batch_repo = self.batch_repo.clone();
                 tokio::spawn(async move {
-                    if let Err(e) = execute_job(&job, &factory, &ctx, cron_sid, &run_repo).await {
+                    if let Err(e) =
+                        execute_job(&job, &factory, &ctx, cron_sid, &run_repo, &batch_repo).await
+                    {
                         tracing::error!("Cron job '{}' failed: {e}", job.name);
                     }
                 });
             }
         }
 
+        let pool = self.service_context.pool();
+        let owners = BatchOwners {
+            pool: &pool,
+            batches: &self.batch_repo,
+            jobs: &self.repo,
+            runs: &self.run_repo,
+            cron_session_id: self.cron_session_id,
+        };
+        if let Err(e) = batches::poll_pending(&owners).await {
+            tracing::warn!("Cron batch poll error: {e}");
+        }
+        if self.background_check_due()
+            && let Err(e) = batches::submit_background(&pool, &self.batch_repo).await
+        {
+            tracing::warn!("Background batch submission error: {e}");
+        }
+
         Ok(())
     }
 
+    /// Whether this tick should look for due background batches; stamps the
+    /// check when it should.
+    fn background_check_due(&self) -> bool {
+        let Ok(mut checked_at) = self.background_checked_at.lock() else {
+            return false;
+        };
+        if checked_at.is_some_and(|t| t.elapsed() < BACKGROUND_CHECK_INTERVAL) {
+            return false;
+        }
+        *checked_at = Some(std::time::Instant::now());
+        true
+    }
+
     /// Check if a job is due to run.
     fn is_due(&self, job: &CronJob, now: chrono::DateTime<Utc>) -> bool {
         match &job.next_run_at {
@@ -196,6 +244,7 @@ async fn execute_job(
     _ctx: &ServiceContext,
     cron_session_id: Uuid,
     run_repo: &CronJobRunRepository,
+    batch_repo: &LlmBatchRepository,
 ) -> anyhow::Result<()> {
     // Resolve effective provider/model: job override > config default > system default
     let config = Config::load()?;
@@ -229,6 +278,10 @@ async fn execute_job(
 
     // Spawn agent service (inherits tools, brain, working dir from factory)
     let agent = factory.create_agent_service().await;
+    agent.set_thinking_budget(thinking_budget_for_mode(&job.thinking));
+    agent.set_tool_choice(job.tool_choice.as_deref().map(ToolChoice::from_setting));
+    agent.set_response_cache_ttl(config.response_cache.ttl_for("cron"));
+    agent.set_usage_channel(Some(format!("cron:{}", job.name)));
 
     // Swap to cron-specific provider if configured
     if let Some(ref provider_name) = effective_provider {
@@ -251,6 +304,31 @@ async fn execute_job(
         }
     }
 
+    // Batch mode: hand the prompt to the provider's batch API; the tick's
+    // batch poller saves and delivers the result when the batch ends.
+    if job.delivery_mode == "batch" {
+        if agent.provider().supports_batch() {
+            match batches::submit_job(job, &agent, effective_model.clone(), &run_id, batch_repo)
+                .await
+            {
+                Ok(batch_id) => {
+                    tracing::info!("Cron job '{}' — submitted as batch {batch_id}", job.name);
+                    return Ok(());
+                }
+                Err(e) => tracing::warn!(
+                    "Cron job '{}' — batch submission failed: {e}, running immediately",
+                    job.name
+                ),
+            }
+        } else {
+            tracing::warn!(
+                "Cron job '{}' — provider '{}' has no batch API, running immediately",
+                job.name,
+                agent.provider_name()
+            );
+        }
+    }
+
     // Execute with auto-approved tools (no interactive user)
     let result = agent
         .send_message_with_tools_and_callback(
@@ -294,15 +372,7 @@ async fn execute_job(
             }
 
             // Optionally deliver to configured channels too
-            if let Some(ref deliver_to) = job.deliver_to {
-                for target in deliver_to
-                    .split(',')
-                    .map(str::trim)
-                    .filter(|s| !s.is_empty())
-                {
-                    deliver_result(target, &job.name, &clean).await;
-                }
-            }
+            deliver_all(job.deliver_to.as_deref(), &job.name, &clean).await;
         }
         Err(e) => {
             tracing::error!("Cron job '{}' agent error: {e}", job.name);
@@ -314,22 +384,44 @@ async fn execute_job(
             }
 
             // Optionally deliver error to configured channels too
-            if let Some(ref deliver_to) = job.deliver_to {
-                let msg = format!("Cron job '{}' failed: {e}", job.name);
-                for target in deliver_to
-                    .split(',')
-                    .map(str::trim)
-                    .filter(|s| !s.is_empty())
-                {
-                    deliver_result(target, &job.name, &msg).await;
-                }
-            }
+            let msg = format!("Cron job '{}' failed: {e}", job.name);
+            deliver_all(job.deliver_to.as_deref(), &job.name, &msg).await;
         }
     }
 
     Ok(())
 }
 
+/// Map a job's `thinking` column to an extended thinking budget.
+/// "off" (the column default) and unset leave the provider's configured
+/// budget in charge; "disabled" forces thinking off even when the provider
+/// has a default budget. Unknown values fall back to the provider default.
+pub(crate) fn thinking_budget_for_mode(mode: &str) -> Option<u32> {
+    use crate::brain::provider::anthropic::{DEFAULT_THINKING_BUDGET, MIN_THINKING_BUDGET};
+    match mode.trim().to_ascii_lowercase().as_str() {
+        "" | "off" => None,
+        "disabled" => Some(0),
+        "on" => Some(DEFAULT_THINKING_BUDGET),
+        "budget" => Some(MIN_THINKING_BUDGET),
+        other => {
+            tracing::warn!("Unknown cron thinking mode '{other}' — using provider default");
+            None
+        }
+    }
+}
+
+/// Deliver a result to every comma-separated target in a job's `deliver_to`.
+pub(super) async fn deliver_all(deliver_to: Option<&str>, job_name: &str, content: &str) {
+    for target in deliver_to
+        .into_iter()
+        .flat_map(|d| d.split(','))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        deliver_result(target, job_name, content).await;
+    }
+}
+
 /// Deliver a cron job result to the specified channel.
 /// Format: "telegram:chat_id", "discord:channel_id", "slack:channel_id",
 /// or an HTTP(S) URL for generic webhook delivery.
@@ -141,7 +141,7 @@ impl Database {
     }
 
     /// Total number of migrations defined below — keep in sync when adding new ones.
-    const MIGRATION_COUNT: usize = 19;
+    const MIGRATION_COUNT: usize = 26;
 
     /// Run database migrations
     pub async fn run_migrations(&self) -> Result<()> {
@@ -201,6 +201,27 @@ impl Database {
             M::up(include_str!(
                 "../migrations/20260421000001_add_message_thinking.sql"
             )),
+            M::up(include_str!(
+                "../migrations/20260501000001_add_cron_job_tool_choice.sql"
+            )),
+            M::up(include_str!(
+                "../migrations/20260502000001_add_response_cache.sql"
+            )),
+            M::up(include_str!(
+                "../migrations/20260503000001_add_llm_batches.sql"
+            )),
+            M::up(include_str!(
+                "../migrations/20260503000002_add_cron_job_delivery_mode.sql"
+            )),
+            M::up(include_str!(
+                "../migrations/20260504000001_add_usage_ledger_channel.sql"
+            )),
+            M::up(include_str!(
+                "../migrations/20260505000001_add_file_checkpoints.sql"
+            )),
+            M::up(include_str!(
+                "../migrations/20260506000001_add_session_lineage.sql"
+            )),
         ]);
 
         self.pool
@@ -16,6 +16,17 @@ pub fn uuid_col(row: &rusqlite::Row, col: &str) -> rusqlite::Result<Uuid> {
     })
 }
 
+/// Parse an optional UUID column.
+pub fn opt_uuid_col(row: &rusqlite::Row, col: &str) -> rusqlite::Result<Option<Uuid>> {
+    let s: Option<String> = row.get(col)?;
+    s.map(|s| {
+        Uuid::parse_str(&s).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })
+    })
+    .transpose()
+}
+
 /// Parse a Unix-timestamp column into `DateTime<Utc>`.
 pub fn timestamp_col(row: &rusqlite::Row, col: &str) -> rusqlite::Result<DateTime<Utc>> {
     let ts: i64 = row.get(col)?;
@@ -72,6 +83,10 @@ pub struct Session {
     pub token_count: i32,
     pub total_cost: f64,
     pub working_directory: Option<String>,
+    /// Session this one was forked from
+    pub parent_session_id: Option<Uuid>,
+    /// Last parent message copied into the fork
+    pub forked_from_message_id: Option<Uuid>,
 }
 
 impl Session {
@@ -87,6 +102,8 @@ impl Session {
             token_count: row.get("token_count")?,
             total_cost: row.get("total_cost")?,
             working_directory: row.get("working_directory")?,
+            parent_session_id: opt_uuid_col(row, "parent_session_id")?,
+            forked_from_message_id: opt_uuid_col(row, "forked_from_message_id")?,
         })
     }
 
@@ -108,6 +125,8 @@ impl Session {
             token_count: 0,
             total_cost: 0.0,
             working_directory: None,
+            parent_session_id: None,
+            forked_from_message_id: None,
         }
     }
 
@@ -440,6 +459,12 @@ pub struct CronJob {
     pub provider: Option<String>,
     pub model: Option<String>,
     pub thinking: String,
+    /// Tool-use constraint for the run's first LLM call: `auto`,
+    /// `required`, `none` or a tool name. `None` = provider default.
+    pub tool_choice: Option<String>,
+    /// `immediate` (agent turn with tools) or `batch` (one tool-less request
+    /// through the provider's batch API, delivered when the batch ends).
+    pub delivery_mode: String,
     pub auto_approve: bool,
     pub deliver_to: Option<String>,
     pub enabled: bool,
@@ -460,6 +485,10 @@ impl CronJob {
             provider: row.get("provider")?,
             model: row.get("model")?,
             thinking: row.get("thinking")?,
+            tool_choice: row.get("tool_choice").ok().flatten(),
+            delivery_mode: row
+                .get("delivery_mode")
+                .unwrap_or_else(|_| "immediate".to_string()),
             auto_approve: row.get::<_, i32>("auto_approve")? != 0,
             deliver_to: row.get("deliver_to")?,
             enabled: row.get::<_, i32>("enabled")? != 0,
@@ -492,6 +521,8 @@ impl CronJob {
             provider,
             model,
             thinking,
+            tool_choice: None,
+            delivery_mode: "immediate".to_string(),
             auto_approve,
             deliver_to,
             enabled: true,
@@ -570,6 +601,93 @@ impl CronJobRun {
     }
 }
 
+// ─── LlmBatch ─────────────────────────────────────────────────────────────────
+
+/// A batch submitted to a provider's batch API, tracked until it ends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmBatch {
+    /// Batch id assigned by the provider
+    pub id: String,
+    /// Provider name the batch was submitted to (and must be polled on)
+    pub provider: String,
+    /// What consumes the results: "cron", "categorize", "rsi_digest"
+    pub purpose: String,
+    /// Purpose-specific handle, e.g. the cron run id
+    pub reference_id: Option<String>,
+    pub status: String, // "pending", "completed", "failed"
+    pub request_count: i64,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl LlmBatch {
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(LlmBatch {
+            id: row.get("id")?,
+            provider: row.get("provider")?,
+            purpose: row.get("purpose")?,
+            reference_id: row.get("reference_id")?,
+            status: row.get("status")?,
+            request_count: row.get("request_count")?,
+            error: row.get("error")?,
+            created_at: rfc3339_col(row, "created_at")?,
+            completed_at: opt_rfc3339_col(row, "completed_at")?,
+        })
+    }
+
+    pub fn new_pending(
+        id: String,
+        provider: String,
+        purpose: &str,
+        reference_id: Option<String>,
+        request_count: i64,
+    ) -> Self {
+        Self {
+            id,
+            provider,
+            purpose: purpose.to_string(),
+            reference_id,
+            status: "pending".to_string(),
+            request_count,
+            error: None,
+            created_at: Utc::now(),
+            completed_at: None,
+        }
+    }
+}
+
+// ─── FileCheckpoint ─────────────────────────────────────────────────────────
+
+/// What a file held before a tool changed it, for `/undo` and `/rewind`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileCheckpoint {
+    pub id: i64,
+    pub session_id: Uuid,
+    /// `messages.sequence` of the user message whose turn made the change
+    pub turn_sequence: i32,
+    pub tool_name: String,
+    /// Absolute path; `None` for a shell command, whose changes aren't tracked
+    pub path: Option<String>,
+    /// Prior content in `checkpoint_blobs`; `None` if the file didn't exist
+    pub content_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FileCheckpoint {
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(FileCheckpoint {
+            id: row.get("id")?,
+            session_id: uuid_col(row, "session_id")?,
+            turn_sequence: row.get("turn_sequence")?,
+            tool_name: row.get("tool_name")?,
+            path: row.get("path")?,
+            content_hash: row.get("content_hash")?,
+            created_at: rfc3339_col(row, "created_at")?,
+        })
+    }
+}
+
 // ─── FeedbackEntry ──────────────────────────────────────────────────────────
 
 /// Feedback ledger entry — append-only observations for recursive self-improvement.
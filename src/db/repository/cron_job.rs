@@ -37,8 +37,8 @@ impl CronJobRepository {
             .context("Failed to get connection")?
             .interact(move |conn| {
                 conn.execute(
-                    "INSERT INTO cron_jobs (id, name, cron_expr, timezone, prompt, provider, model, thinking, auto_approve, deliver_to, enabled, next_run_at, created_at, updated_at)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                    "INSERT INTO cron_jobs (id, name, cron_expr, timezone, prompt, provider, model, thinking, tool_choice, delivery_mode, auto_approve, deliver_to, enabled, next_run_at, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
                     params![
                         j.id.to_string(),
                         j.name,
@@ -48,6 +48,8 @@ impl CronJobRepository {
                         j.provider,
                         j.model,
                         j.thinking,
+                        j.tool_choice,
+                        j.delivery_mode,
                         j.auto_approve as i32,
                         j.deliver_to,
                         j.enabled as i32,
@@ -96,6 +96,23 @@ impl CronJobRunRepository {
         Ok(())
     }
 
+    /// Look up a single run by id.
+    pub async fn find_by_id(&self, run_id: &str) -> Result<Option<CronJobRun>> {
+        let id = run_id.to_string();
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| -> rusqlite::Result<Option<CronJobRun>> {
+                let mut stmt = conn.prepare_cached("SELECT * FROM cron_job_runs WHERE id = ?1")?;
+                let mut rows = stmt.query_map(params![id], CronJobRun::from_row)?;
+                rows.next().transpose()
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to find cron job run")
+    }
+
     /// List recent runs for a specific job (most recent first).
     pub async fn list_by_job(&self, job_id: &str, limit: i64) -> Result<Vec<CronJobRun>> {
         let job_id = job_id.to_string();
@@ -0,0 +1,187 @@
+use crate::db::Pool;
+use crate::db::database::interact_err;
+use crate::db::models::FileCheckpoint;
+use anyhow::{Context, Result};
+use rusqlite::{OptionalExtension, params};
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
+use uuid::Uuid;
+
+/// Hex sha256 of file content — the key in `checkpoint_blobs`.
+pub fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .fold(String::with_capacity(64), |mut s, b| {
+            let _ = write!(s, "{:02x}", b);
+            s
+        })
+}
+
+/// Persistence for file checkpoints and their content-addressed blobs.
+#[derive(Clone)]
+pub struct FileCheckpointRepository {
+    pool: Pool,
+}
+
+impl FileCheckpointRepository {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a file's content before `tool_name` changes it (`None` = the
+    /// file doesn't exist yet), or with `path = None` that a shell command
+    /// ran. The checkpoint belongs to the session's latest user message.
+    pub async fn record(
+        &self,
+        session_id: Uuid,
+        tool_name: &str,
+        path: Option<&str>,
+        content: Option<Vec<u8>>,
+    ) -> Result<()> {
+        let sid = session_id.to_string();
+        let tool_name = tool_name.to_string();
+        let path = path.map(str::to_string);
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| -> rusqlite::Result<()> {
+                let tx = conn.transaction()?;
+                let hash = match content {
+                    Some(content) => {
+                        let hash = content_hash(&content);
+                        tx.execute(
+                            "INSERT OR IGNORE INTO checkpoint_blobs (hash, content) VALUES (?1, ?2)",
+                            params![hash, content],
+                        )?;
+                        Some(hash)
+                    }
+                    None => None,
+                };
+                tx.execute(
+                    "INSERT INTO file_checkpoints (session_id, turn_sequence, tool_name, path, content_hash)
+                     VALUES (?1, COALESCE((SELECT MAX(sequence) FROM messages WHERE session_id = ?1 AND role = 'user'), 0), ?2, ?3, ?4)",
+                    params![sid, tool_name, path, hash],
+                )?;
+                tx.commit()
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to record file checkpoint")
+    }
+
+    /// Turn of the session's most recent checkpoint, if any.
+    pub async fn latest_turn(&self, session_id: Uuid) -> Result<Option<i32>> {
+        let sid = session_id.to_string();
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| {
+                conn.query_row(
+                    "SELECT MAX(turn_sequence) FROM file_checkpoints WHERE session_id = ?1",
+                    params![sid],
+                    |r| r.get::<_, Option<i32>>(0),
+                )
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to query latest checkpoint turn")
+    }
+
+    /// Checkpoints from `turn_sequence` on, oldest first.
+    pub async fn list_since(
+        &self,
+        session_id: Uuid,
+        turn_sequence: i32,
+    ) -> Result<Vec<FileCheckpoint>> {
+        let sid = session_id.to_string();
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| -> rusqlite::Result<Vec<FileCheckpoint>> {
+                let mut stmt = conn.prepare_cached(
+                    "SELECT * FROM file_checkpoints
+                     WHERE session_id = ?1 AND turn_sequence >= ?2 ORDER BY id",
+                )?;
+                let rows = stmt.query_map(params![sid, turn_sequence], FileCheckpoint::from_row)?;
+                rows.collect()
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to list file checkpoints")
+    }
+
+    /// Content stored under `hash`.
+    pub async fn blob(&self, hash: &str) -> Result<Option<Vec<u8>>> {
+        let hash = hash.to_string();
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| {
+                conn.query_row(
+                    "SELECT content FROM checkpoint_blobs WHERE hash = ?1",
+                    params![hash],
+                    |r| r.get(0),
+                )
+                .optional()
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to load checkpoint content")
+    }
+
+    /// Move checkpoints from `turn_sequence` on to `to_session_id`, whose
+    /// messages share the session's sequence numbers (a fork of it).
+    pub async fn move_since(
+        &self,
+        session_id: Uuid,
+        turn_sequence: i32,
+        to_session_id: Uuid,
+    ) -> Result<usize> {
+        let sid = session_id.to_string();
+        let to = to_session_id.to_string();
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| {
+                conn.execute(
+                    "UPDATE file_checkpoints SET session_id = ?3
+                     WHERE session_id = ?1 AND turn_sequence >= ?2",
+                    params![sid, turn_sequence, to],
+                )
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to move file checkpoints")
+    }
+
+    /// Drop checkpoints from `turn_sequence` on, and any content no
+    /// checkpoint refers to anymore.
+    pub async fn delete_since(&self, session_id: Uuid, turn_sequence: i32) -> Result<()> {
+        let sid = session_id.to_string();
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| -> rusqlite::Result<()> {
+                let tx = conn.transaction()?;
+                tx.execute(
+                    "DELETE FROM file_checkpoints WHERE session_id = ?1 AND turn_sequence >= ?2",
+                    params![sid, turn_sequence],
+                )?;
+                tx.execute(
+                    "DELETE FROM checkpoint_blobs WHERE hash NOT IN
+                     (SELECT content_hash FROM file_checkpoints WHERE content_hash IS NOT NULL)",
+                    [],
+                )?;
+                tx.commit()
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to delete file checkpoints")
+    }
+}
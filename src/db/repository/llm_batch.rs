@@ -0,0 +1,128 @@
+use crate::db::Pool;
+use crate::db::database::interact_err;
+use crate::db::models::LlmBatch;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+/// Persistence for batches submitted through `Provider::submit_batch`.
+#[derive(Clone)]
+pub struct LlmBatchRepository {
+    pool: Pool,
+}
+
+impl LlmBatchRepository {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a newly submitted batch (status = "pending").
+    pub async fn insert(&self, batch: &LlmBatch) -> Result<()> {
+        let b = batch.clone();
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| {
+                conn.execute(
+                    "INSERT INTO llm_batches (id, provider, purpose, reference_id, status, request_count, error, created_at, completed_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        b.id,
+                        b.provider,
+                        b.purpose,
+                        b.reference_id,
+                        b.status,
+                        b.request_count,
+                        b.error,
+                        b.created_at.to_rfc3339(),
+                        b.completed_at.map(|d| d.to_rfc3339()),
+                    ],
+                )
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to insert llm batch")?;
+        Ok(())
+    }
+
+    /// Batches still waiting on the provider, oldest first.
+    pub async fn list_pending(&self) -> Result<Vec<LlmBatch>> {
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(|conn| -> rusqlite::Result<Vec<LlmBatch>> {
+                let mut stmt = conn.prepare_cached(
+                    "SELECT * FROM llm_batches WHERE status = 'pending' ORDER BY created_at",
+                )?;
+                let rows = stmt.query_map([], LlmBatch::from_row)?;
+                rows.collect()
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to list pending llm batches")
+    }
+
+    /// When a batch for `purpose` was last submitted, whatever its outcome.
+    pub async fn last_submitted(&self, purpose: &str) -> Result<Option<DateTime<Utc>>> {
+        let purpose = purpose.to_string();
+        let created: Option<String> = self
+            .pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| {
+                conn.query_row(
+                    "SELECT MAX(created_at) FROM llm_batches WHERE purpose = ?1",
+                    params![purpose],
+                    |row| row.get(0),
+                )
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to query last llm batch")?;
+        Ok(created
+            .and_then(|c| DateTime::parse_from_rfc3339(&c).ok())
+            .map(|c| c.with_timezone(&Utc)))
+    }
+
+    /// Mark a batch as finished and its results handed off.
+    pub async fn mark_completed(&self, id: &str) -> Result<()> {
+        let id = id.to_string();
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| {
+                conn.execute(
+                    "UPDATE llm_batches SET status = 'completed', completed_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE id = ?1",
+                    params![id],
+                )
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to complete llm batch")?;
+        Ok(())
+    }
+
+    /// Mark a batch as failed.
+    pub async fn mark_failed(&self, id: &str, error: &str) -> Result<()> {
+        let id = id.to_string();
+        let error = error.to_string();
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| {
+                conn.execute(
+                    "UPDATE llm_batches SET status = 'failed', error = ?1, completed_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE id = ?2",
+                    params![error, id],
+                )
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to update llm batch error")?;
+        Ok(())
+    }
+}
@@ -310,6 +310,33 @@ impl MessageRepository {
         tracing::debug!("Deleted all messages for session: {}", session_id);
         Ok(())
     }
+
+    /// Delete a session's messages from `sequence` on (rewinding the conversation)
+    pub async fn delete_from_sequence(&self, session_id: Uuid, sequence: i32) -> Result<usize> {
+        let sid = session_id.to_string();
+        let deleted = self
+            .pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| {
+                conn.execute(
+                    "DELETE FROM messages WHERE session_id = ?1 AND sequence >= ?2",
+                    params![sid, sequence],
+                )
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to delete messages")?;
+
+        tracing::debug!(
+            "Deleted {} messages from seq {} in session: {}",
+            deleted,
+            sequence,
+            session_id
+        );
+        Ok(deleted)
+    }
 }
 
 /// Extension trait for rusqlite to add `.optional()` to query results
@@ -7,6 +7,8 @@ pub mod cron_job;
 pub mod cron_job_run;
 pub mod feedback_ledger;
 pub mod file;
+pub mod file_checkpoint;
+pub mod llm_batch;
 pub mod message;
 pub mod pending_request;
 pub mod plan;
@@ -19,6 +21,8 @@ pub use cron_job::CronJobRepository;
 pub use cron_job_run::CronJobRunRepository;
 pub use feedback_ledger::FeedbackLedgerRepository;
 pub use file::FileRepository;
+pub use file_checkpoint::FileCheckpointRepository;
+pub use llm_batch::LlmBatchRepository;
 pub use message::MessageRepository;
 pub use pending_request::PendingRequestRepository;
 pub use plan::PlanRepository;
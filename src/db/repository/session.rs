@@ -21,6 +21,29 @@ pub struct SessionListOptions {
     pub offset: usize,
 }
 
+fn insert_session(conn: &rusqlite::Connection, s: &Session) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT INTO sessions (id, title, model, provider_name, created_at, updated_at,
+                              archived_at, token_count, total_cost, working_directory,
+                              parent_session_id, forked_from_message_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            s.id.to_string(),
+            s.title,
+            s.model,
+            s.provider_name,
+            s.created_at.timestamp(),
+            s.updated_at.timestamp(),
+            s.archived_at.map(|dt| dt.timestamp()),
+            s.token_count,
+            s.total_cost,
+            s.working_directory,
+            s.parent_session_id.map(|id| id.to_string()),
+            s.forked_from_message_id.map(|id| id.to_string()),
+        ],
+    )
+}
+
 /// Repository for session operations
 #[derive(Clone)]
 pub struct SessionRepository {
@@ -76,25 +99,7 @@ impl SessionRepository {
             .get()
             .await
             .context("Failed to get connection")?
-            .interact(move |conn| {
-                conn.execute(
-                    "INSERT INTO sessions (id, title, model, provider_name, created_at, updated_at,
-                                          archived_at, token_count, total_cost, working_directory)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-                    params![
-                        s.id.to_string(),
-                        s.title,
-                        s.model,
-                        s.provider_name,
-                        s.created_at.timestamp(),
-                        s.updated_at.timestamp(),
-                        s.archived_at.map(|dt| dt.timestamp()),
-                        s.token_count,
-                        s.total_cost,
-                        s.working_directory,
-                    ],
-                )
-            })
+            .interact(move |conn| insert_session(conn, &s))
             .await
             .map_err(interact_err)?
             .context("Failed to create session")?;
@@ -103,6 +108,57 @@ impl SessionRepository {
         Ok(())
     }
 
+    /// Create `fork` holding copies (with new ids) of `source_id`'s messages
+    /// up to and including `up_to_sequence`. Returns how many were copied.
+    pub async fn create_fork(
+        &self,
+        fork: &Session,
+        source_id: Uuid,
+        up_to_sequence: i32,
+    ) -> Result<usize> {
+        let s = fork.clone();
+        let source = source_id.to_string();
+        let copied = self
+            .pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| -> rusqlite::Result<usize> {
+                let tx = conn.transaction()?;
+                insert_session(&tx, &s)?;
+                let ids: Vec<String> = tx
+                    .prepare(
+                        "SELECT id FROM messages WHERE session_id = ?1 AND sequence <= ?2
+                         ORDER BY sequence",
+                    )?
+                    .query_map(params![source, up_to_sequence], |r| r.get(0))?
+                    .collect::<rusqlite::Result<_>>()?;
+                for id in &ids {
+                    tx.execute(
+                        "INSERT INTO messages (id, session_id, role, content, sequence, created_at,
+                                               token_count, cost, input_tokens, thinking)
+                         SELECT ?1, ?2, role, content, sequence, created_at,
+                                token_count, cost, input_tokens, thinking
+                         FROM messages WHERE id = ?3",
+                        params![Uuid::new_v4().to_string(), s.id.to_string(), id],
+                    )?;
+                }
+                tx.commit()?;
+                Ok(ids.len())
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to fork session")?;
+
+        tracing::debug!(
+            "Forked session {} into {} ({} messages)",
+            source_id,
+            fork.id,
+            copied
+        );
+        Ok(copied)
+    }
+
     /// Update an existing session
     pub async fn update(&self, session: &Session) -> Result<()> {
         let s = session.clone();
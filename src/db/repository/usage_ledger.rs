@@ -86,17 +86,32 @@ impl UsageLedgerRepository {
         model: &str,
         token_count: i32,
         cost: f64,
+    ) -> Result<()> {
+        self.record_for_channel(session_id, model, token_count, cost, "")
+            .await
+    }
+
+    /// Record a usage event against the channel it's budgeted under
+    /// (`tui`, `telegram`, `cron:<job>`, ...).
+    pub async fn record_for_channel(
+        &self,
+        session_id: &str,
+        model: &str,
+        token_count: i32,
+        cost: f64,
+        channel: &str,
     ) -> Result<()> {
         let sid = session_id.to_string();
         let mdl = normalize_model_name(model);
+        let chan = channel.to_string();
         self.pool
             .get()
             .await
             .context("Failed to get connection")?
             .interact(move |conn| {
                 conn.execute(
-                    "INSERT INTO usage_ledger (session_id, model, token_count, cost) VALUES (?1, ?2, ?3, ?4)",
-                    params![sid, mdl, token_count, cost],
+                    "INSERT INTO usage_ledger (session_id, model, token_count, cost, channel) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![sid, mdl, token_count, cost, chan],
                 )
             })
             .await
@@ -106,6 +121,77 @@ impl UsageLedgerRepository {
         Ok(())
     }
 
+    /// Record a call served from the response cache. No tokens or cost are
+    /// added; `saved_cost` is what the call would have cost uncached.
+    pub async fn record_cache_hit(
+        &self,
+        session_id: &str,
+        model: &str,
+        saved_cost: f64,
+    ) -> Result<()> {
+        let sid = session_id.to_string();
+        let mdl = normalize_model_name(model);
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| {
+                conn.execute(
+                    "INSERT INTO usage_ledger (session_id, model, cache_hit, saved_cost) VALUES (?1, ?2, 1, ?3)",
+                    params![sid, mdl, saved_cost],
+                )
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to record cache hit")?;
+
+        Ok(())
+    }
+
+    /// Get all-time response cache savings (hit count + saved cost)
+    pub async fn cache_savings(&self) -> Result<(i64, f64)> {
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(|conn| {
+                conn.query_row(
+                    "SELECT COUNT(*), COALESCE(SUM(saved_cost), 0.0) FROM usage_ledger WHERE cache_hit = 1",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to query cache savings")
+    }
+
+    /// Cost recorded since `since` (unix seconds). With a channel, only
+    /// that channel counts — `cron` also covers every `cron:<job>`.
+    pub async fn spent_since(&self, since: i64, channel: Option<&str>) -> Result<f64> {
+        let chan = channel.map(str::to_string);
+        self.pool
+            .get()
+            .await
+            .context("Failed to get connection")?
+            .interact(move |conn| match chan {
+                Some(chan) => conn.query_row(
+                    "SELECT COALESCE(SUM(cost), 0.0) FROM usage_ledger \
+                     WHERE created_at >= ?1 AND (channel = ?2 OR channel LIKE ?2 || ':%')",
+                    params![since, chan],
+                    |row| row.get(0),
+                ),
+                None => conn.query_row(
+                    "SELECT COALESCE(SUM(cost), 0.0) FROM usage_ledger WHERE created_at >= ?1",
+                    params![since],
+                    |row| row.get(0),
+                ),
+            })
+            .await
+            .map_err(interact_err)?
+            .context("Failed to query spend")
+    }
+
     /// Get all-time totals (tokens + cost)
     pub async fn totals(&self) -> Result<(i64, f64)> {
         self.pool
@@ -143,7 +229,7 @@ impl UsageLedgerRepository {
                            THEN SUBSTR(model, INSTR(model, '/') + 1) \
                            ELSE model \
                          END) AS m1 \
-                       FROM usage_ledger WHERE model != '' \
+                       FROM usage_ledger WHERE model != '' AND cache_hit = 0 \
                      ), \
                      cleaned AS ( \
                        SELECT *, \
@@ -8,12 +8,13 @@ static ENGINE: OnceCell<Mutex<EmbeddingEngine>> = OnceCell::new();
 
 /// Disable llama.cpp's C-level logging globally.
 ///
-/// Must be called once before creating any EmbeddingEngine.
+/// Must be called before creating any EmbeddingEngine or local chat model.
 /// Routes all llama.cpp log output through the tracing framework
-/// with logging disabled — zero stderr pollution.
-fn silence_llama_logs() {
+/// with logging disabled — zero stderr pollution. Idempotent.
+pub(crate) fn silence_llama_logs() {
     use llama_cpp_2::{LogOptions, send_logs_to_tracing};
-    send_logs_to_tracing(LogOptions::default().with_logs_enabled(false));
+    static ONCE: std::sync::Once = std::sync::Once::new();
+    ONCE.call_once(|| send_logs_to_tracing(LogOptions::default().with_logs_enabled(false)));
 }
 
 /// Get (or create) the shared embedding engine.
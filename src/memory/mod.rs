@@ -9,6 +9,7 @@ mod index;
 mod search;
 mod store;
 
+pub(crate) use embedding::silence_llama_logs;
 pub use embedding::{embed_content, engine_if_ready, get_engine};
 pub use index::{BRAIN_FILES, index_file, reindex};
 pub use search::search;
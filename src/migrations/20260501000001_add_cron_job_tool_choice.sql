@@ -0,0 +1,5 @@
+-- Optional tool-use constraint for a cron job's first LLM call:
+-- 'auto', 'required', 'none', or the name of a tool the job must call.
+-- NULL leaves the provider default.
+
+ALTER TABLE cron_jobs ADD COLUMN tool_choice TEXT;
@@ -0,0 +1,24 @@
+-- Exact-match response cache for provider calls that opt in with a TTL.
+-- `key` is a SHA-256 over the normalized request; `payload` holds either a
+-- full LLMResponse or the recorded stream events (JSON).
+
+CREATE TABLE IF NOT EXISTS response_cache (
+    key TEXT PRIMARY KEY,
+    provider TEXT NOT NULL DEFAULT '',
+    model TEXT NOT NULL DEFAULT '',
+    payload TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL DEFAULT 0,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    hits INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    last_hit_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_response_cache_lru
+    ON response_cache (COALESCE(last_hit_at, created_at));
+
+-- Ledger rows for calls served from the cache: token_count/cost stay 0,
+-- saved_cost records what the call would have cost.
+ALTER TABLE usage_ledger ADD COLUMN cache_hit INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE usage_ledger ADD COLUMN saved_cost REAL NOT NULL DEFAULT 0.0;
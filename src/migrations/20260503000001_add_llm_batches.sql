@@ -0,0 +1,16 @@
+-- Provider-side message batches (Anthropic Message Batches, OpenAI Batch API).
+-- A row is written as soon as the provider accepts a batch so the scheduler
+-- can resume polling after a restart and hand results back to `purpose`.
+CREATE TABLE IF NOT EXISTS llm_batches (
+    id              TEXT PRIMARY KEY NOT NULL,              -- provider batch id
+    provider        TEXT NOT NULL,                          -- provider name to poll with
+    purpose         TEXT NOT NULL,                          -- cron, categorize, rsi_digest
+    reference_id    TEXT,                                   -- e.g. cron_job_runs.id
+    status          TEXT NOT NULL DEFAULT 'pending',        -- pending, completed, failed
+    request_count   INTEGER NOT NULL DEFAULT 0,
+    error           TEXT,
+    created_at      TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    completed_at    TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_llm_batches_status ON llm_batches(status);
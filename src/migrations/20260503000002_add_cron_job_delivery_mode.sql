@@ -0,0 +1,5 @@
+-- How a cron job runs: 'immediate' (agent turn with tools, the default) or
+-- 'batch' (a single tool-less request through the provider's half-price
+-- batch API; the run completes and delivers when the batch ends).
+
+ALTER TABLE cron_jobs ADD COLUMN delivery_mode TEXT NOT NULL DEFAULT 'immediate';
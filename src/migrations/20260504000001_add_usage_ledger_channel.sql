@@ -0,0 +1,7 @@
+-- Channel each usage entry came in on ('tui', 'telegram', 'cron:<job>', ...)
+-- so spending budgets can be enforced per channel and per cron job.
+-- Older rows keep '' and only count toward the profile-wide budget.
+
+ALTER TABLE usage_ledger ADD COLUMN channel TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX IF NOT EXISTS idx_usage_ledger_created_at ON usage_ledger(created_at);
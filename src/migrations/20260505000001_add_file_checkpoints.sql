@@ -0,0 +1,21 @@
+-- File checkpoints for /undo and /rewind: the content a file had before a
+-- tool changed it, stored once per distinct content (content-addressed).
+CREATE TABLE IF NOT EXISTS checkpoint_blobs (
+    hash            TEXT PRIMARY KEY NOT NULL,              -- sha256 of content
+    content         BLOB NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS file_checkpoints (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id      TEXT NOT NULL,
+    turn_sequence   INTEGER NOT NULL,                       -- messages.sequence of the turn's user message
+    tool_name       TEXT NOT NULL,
+    path            TEXT,                                   -- NULL for shell commands (not tracked)
+    content_hash    TEXT,                                   -- NULL when the file did not exist yet
+    created_at      TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_file_checkpoints_session
+    ON file_checkpoints(session_id, turn_sequence);
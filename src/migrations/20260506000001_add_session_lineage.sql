@@ -0,0 +1,5 @@
+-- Session lineage: a fork copies its parent's history up to one message.
+ALTER TABLE sessions ADD COLUMN parent_session_id TEXT;
+ALTER TABLE sessions ADD COLUMN forked_from_message_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_sessions_parent ON sessions(parent_session_id);
@@ -0,0 +1,262 @@
+//! Checkpoint Service
+//!
+//! Snapshots files before `edit_file`, `write_file` and `notebook_edit`
+//! change them, and restores the snapshots for `/undo` (the last turn that
+//! changed files) and `/rewind` (files and conversation back to before a
+//! message). Shell commands can change files too; those changes aren't
+//! tracked, but restores report that such commands ran.
+
+use crate::db::models::{FileCheckpoint, Message};
+use crate::db::repository::{FileCheckpointRepository, MessageRepository};
+use crate::services::ServiceContext;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Tools whose file changes are checkpointed
+pub const CHECKPOINTED_TOOLS: &[&str] = &["edit_file", "write_file", "notebook_edit"];
+
+/// Outcome of an `/undo` or `/rewind`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RestoreReport {
+    /// Files put back to their earlier content
+    pub restored: Vec<PathBuf>,
+    /// Files created in the reverted turns, now deleted again
+    pub removed: Vec<PathBuf>,
+    /// Shell commands that ran in the reverted turns — their changes stay
+    pub shell_commands: usize,
+    /// Messages dropped from the conversation (`/rewind` only)
+    pub messages_removed: usize,
+}
+
+impl RestoreReport {
+    /// One-paragraph summary for the chat
+    pub fn summary(&self) -> String {
+        let list = |paths: &[PathBuf]| {
+            paths
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut lines = Vec::new();
+        if self.messages_removed > 0 {
+            lines.push(format!(
+                "Removed {} message(s) from the conversation.",
+                self.messages_removed
+            ));
+        }
+        if !self.restored.is_empty() {
+            lines.push(format!(
+                "Restored {} file(s): {}",
+                self.restored.len(),
+                list(&self.restored)
+            ));
+        }
+        if !self.removed.is_empty() {
+            lines.push(format!(
+                "Deleted {} new file(s): {}",
+                self.removed.len(),
+                list(&self.removed)
+            ));
+        }
+        if self.restored.is_empty() && self.removed.is_empty() {
+            lines.push("No file changes to revert.".to_string());
+        }
+        if self.shell_commands > 0 {
+            lines.push(format!(
+                "⚠️ {} shell command(s) ran in these turns — any files they changed were not reverted.",
+                self.shell_commands
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// How many rewind points `/rewind` lists
+const REWIND_MENU_LEN: usize = 10;
+
+/// The `/rewind` menu: the latest user messages, newest first, numbered
+/// the way `/rewind <n>` expects
+pub fn rewind_menu(points: &[Message]) -> String {
+    if points.is_empty() {
+        return "Nothing to rewind to yet.".to_string();
+    }
+    let mut lines = vec!["Rewind to before which message? `/rewind <n>`".to_string()];
+    for (i, message) in points.iter().rev().take(REWIND_MENU_LEN).enumerate() {
+        let preview: String = message.content.chars().take(70).collect();
+        lines.push(format!("{}. {}", i + 1, preview.replace('\n', " ")));
+    }
+    lines.join("\n")
+}
+
+/// The `n`th most recent rewind point (1-based, as listed by [`rewind_menu`])
+pub fn rewind_point(points: &[Message], n: usize) -> Option<&Message> {
+    points.iter().rev().nth(n.checked_sub(1)?)
+}
+
+/// Service for file checkpoints
+#[derive(Clone)]
+pub struct CheckpointService {
+    context: ServiceContext,
+}
+
+impl CheckpointService {
+    /// Create a new checkpoint service
+    pub fn new(context: ServiceContext) -> Self {
+        Self { context }
+    }
+
+    fn repo(&self) -> FileCheckpointRepository {
+        FileCheckpointRepository::new(self.context.pool())
+    }
+
+    /// Checkpoint whatever a tool call is about to change: the target file
+    /// of a checkpointed tool, or a note that a shell command (`runs_shell`)
+    /// ran. Failures are logged — they never block the call.
+    pub async fn before_tool_call(
+        &self,
+        session_id: Uuid,
+        tool_name: &str,
+        input: &Value,
+        working_directory: &Path,
+        runs_shell: bool,
+    ) {
+        let result = if CHECKPOINTED_TOOLS.contains(&tool_name) {
+            let Some(path) = input.get("path").and_then(Value::as_str) else {
+                return;
+            };
+            let path = crate::brain::tools::error::resolve_tool_path(path, working_directory);
+            self.snapshot(session_id, tool_name, &path).await
+        } else if runs_shell {
+            self.repo().record(session_id, tool_name, None, None).await
+        } else {
+            return;
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to checkpoint before '{}': {}", tool_name, e);
+        }
+    }
+
+    /// Record `path`'s current content (or that it doesn't exist)
+    pub async fn snapshot(&self, session_id: Uuid, tool_name: &str, path: &Path) -> Result<()> {
+        let content = match tokio::fs::read(path).await {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).context(format!("Failed to read {}", path.display())),
+        };
+        self.repo()
+            .record(
+                session_id,
+                tool_name,
+                Some(&path.to_string_lossy()),
+                content,
+            )
+            .await
+    }
+
+    /// Revert the file changes of the session's last turn that made any.
+    /// `None` when there is nothing left to undo.
+    pub async fn undo(&self, session_id: Uuid) -> Result<Option<RestoreReport>> {
+        let Some(turn) = self.repo().latest_turn(session_id).await? else {
+            return Ok(None);
+        };
+        self.restore_since(session_id, turn).await.map(Some)
+    }
+
+    /// Restore files and conversation to just before `message`: file
+    /// changes from its turn on are reverted and it and every later message
+    /// are removed.
+    pub async fn rewind(&self, session_id: Uuid, message: &Message) -> Result<RestoreReport> {
+        let mut report = self.restore_since(session_id, message.sequence).await?;
+        report.messages_removed = MessageRepository::new(self.context.pool())
+            .delete_from_sequence(session_id, message.sequence)
+            .await?;
+        Ok(report)
+    }
+
+    /// User messages `/rewind` can go back to, oldest first. Slash commands
+    /// that channels save to the history are not turns of their own.
+    pub async fn rewind_points(&self, session_id: Uuid) -> Result<Vec<Message>> {
+        let messages = MessageRepository::new(self.context.pool())
+            .find_by_session(session_id)
+            .await?;
+        Ok(messages
+            .into_iter()
+            .filter(|m| {
+                m.role == "user"
+                    && !m.content.starts_with("[CONTEXT ")
+                    && !m.content.starts_with('/')
+            })
+            .collect())
+    }
+
+    /// Put every file back to how it was before `turn_sequence` and drop
+    /// the checkpoints used.
+    async fn restore_since(&self, session_id: Uuid, turn_sequence: i32) -> Result<RestoreReport> {
+        let repo = self.repo();
+        let checkpoints = repo.list_since(session_id, turn_sequence).await?;
+
+        let mut report = RestoreReport::default();
+        let mut seen = HashSet::new();
+        for checkpoint in &checkpoints {
+            let Some(path) = &checkpoint.path else {
+                report.shell_commands += 1;
+                continue;
+            };
+            // The oldest checkpoint of a path holds its content before them all
+            if seen.insert(path.clone()) {
+                self.restore(checkpoint, Path::new(path), &mut report)
+                    .await?;
+            }
+        }
+
+        repo.delete_since(session_id, turn_sequence).await?;
+        tracing::info!(
+            "Restored checkpoints from seq {} in session {}: {} restored, {} removed",
+            turn_sequence,
+            session_id,
+            report.restored.len(),
+            report.removed.len()
+        );
+        Ok(report)
+    }
+
+    async fn restore(
+        &self,
+        checkpoint: &FileCheckpoint,
+        path: &Path,
+        report: &mut RestoreReport,
+    ) -> Result<()> {
+        let current = tokio::fs::read(path).await.ok();
+        match &checkpoint.content_hash {
+            Some(hash) => {
+                let content = self.repo().blob(hash).await?.with_context(|| {
+                    format!("Checkpoint content missing for {}", path.display())
+                })?;
+                if current.as_ref() == Some(&content) {
+                    return Ok(());
+                }
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(path, content)
+                    .await
+                    .with_context(|| format!("Failed to restore {}", path.display()))?;
+                report.restored.push(path.to_path_buf());
+            }
+            None => {
+                if current.is_none() {
+                    return Ok(());
+                }
+                tokio::fs::remove_file(path)
+                    .await
+                    .with_context(|| format!("Failed to delete {}", path.display()))?;
+                report.removed.push(path.to_path_buf());
+            }
+        }
+        Ok(())
+    }
+}
@@ -182,6 +182,14 @@ impl MessageService {
         Ok(())
     }
 
+    /// Delete a session's messages from `sequence` on
+    pub async fn delete_messages_from(&self, session_id: Uuid, sequence: i32) -> Result<usize> {
+        let repo = MessageRepository::new(self.context.pool());
+        repo.delete_from_sequence(session_id, sequence)
+            .await
+            .context("Failed to delete messages")
+    }
+
     /// Count messages in a session
     pub async fn count_messages_in_session(&self, session_id: Uuid) -> Result<i64> {
         let repo = MessageRepository::new(self.context.pool());
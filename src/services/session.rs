@@ -3,14 +3,43 @@
 //! Provides business logic for session management operations.
 
 use crate::db::{
-    models::Session,
-    repository::{SessionListOptions, SessionRepository, UsageLedgerRepository},
+    models::{Message, Session},
+    repository::{
+        FileCheckpointRepository, MessageRepository, SessionListOptions, SessionRepository,
+        UsageLedgerRepository,
+    },
 };
 use crate::services::ServiceContext;
 use anyhow::{Context, Result};
 use chrono::Utc;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+/// Order sessions as a fork tree: every session is followed by its forks,
+/// one level deeper, keeping the given order among siblings. Forks whose
+/// parent isn't in the list are roots.
+pub fn fork_tree(sessions: Vec<Session>) -> Vec<(Session, usize)> {
+    let ids: HashSet<Uuid> = sessions.iter().map(|s| s.id).collect();
+    let mut roots = Vec::new();
+    let mut forks: HashMap<Uuid, Vec<Session>> = HashMap::new();
+    for session in sessions {
+        match session.parent_session_id.filter(|p| ids.contains(p)) {
+            Some(parent) => forks.entry(parent).or_default().push(session),
+            None => roots.push(session),
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(ids.len());
+    let mut stack: Vec<(Session, usize)> = roots.into_iter().rev().map(|s| (s, 0)).collect();
+    while let Some((session, depth)) = stack.pop() {
+        if let Some(children) = forks.remove(&session.id) {
+            stack.extend(children.into_iter().rev().map(|s| (s, depth + 1)));
+        }
+        ordered.push((session, depth));
+    }
+    ordered
+}
+
 /// Service for managing sessions
 #[derive(Clone)]
 pub struct SessionService {
@@ -53,6 +82,8 @@ impl SessionService {
             token_count: 0,
             total_cost: 0.0,
             working_directory: None,
+            parent_session_id: None,
+            forked_from_message_id: None,
         };
 
         repo.create(&session)
@@ -63,6 +94,106 @@ impl SessionService {
         Ok(session)
     }
 
+    /// Fork a session: a new session on the same provider, model and
+    /// working directory, holding a copy of the history up to and including
+    /// `at_message_id`, with its lineage recorded.
+    pub async fn fork_session(&self, session_id: Uuid, at_message_id: Uuid) -> Result<Session> {
+        self.fork_as(session_id, at_message_id, "fork").await
+    }
+
+    /// Before an earlier user message is edited and re-sent: keep the
+    /// conversation as it stands in a branch titled "… (before edit)", then
+    /// drop `message` and everything after it from the session. File
+    /// checkpoints of the dropped turns move to the branch, so `/undo` and
+    /// `/rewind` there still restore them. Returns the branch.
+    pub async fn branch_before_edit(&self, session_id: Uuid, message: &Message) -> Result<Session> {
+        let last = self
+            .fork_point(session_id, None)
+            .await?
+            .context("Session has no messages")?;
+        let branch = self.fork_as(session_id, last.id, "before edit").await?;
+
+        let removed = MessageRepository::new(self.context.pool())
+            .delete_from_sequence(session_id, message.sequence)
+            .await?;
+        FileCheckpointRepository::new(self.context.pool())
+            .move_since(session_id, message.sequence, branch.id)
+            .await?;
+
+        tracing::info!(
+            "Editing message {} of session {}: {} messages moved to branch {}",
+            message.id,
+            session_id,
+            removed,
+            branch.id
+        );
+        Ok(branch)
+    }
+
+    /// Fork titled "{source title} ({label})"
+    async fn fork_as(&self, session_id: Uuid, at_message_id: Uuid, label: &str) -> Result<Session> {
+        let source = self.get_session_required(session_id).await?;
+        let at = MessageRepository::new(self.context.pool())
+            .find_by_id(at_message_id)
+            .await?
+            .filter(|m| m.session_id == session_id)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Message {} not found in session {}",
+                    at_message_id,
+                    session_id
+                )
+            })?;
+
+        let now = Utc::now();
+        let fork = Session {
+            id: Uuid::new_v4(),
+            title: Some(format!(
+                "{} ({})",
+                source.title.as_deref().unwrap_or("Untitled"),
+                label
+            )),
+            model: source.model,
+            provider_name: source.provider_name,
+            created_at: now,
+            updated_at: now,
+            archived_at: None,
+            token_count: 0,
+            total_cost: 0.0,
+            working_directory: source.working_directory,
+            parent_session_id: Some(session_id),
+            forked_from_message_id: Some(at_message_id),
+        };
+
+        let repo = SessionRepository::new(self.context.pool());
+        let copied = repo.create_fork(&fork, session_id, at.sequence).await?;
+
+        tracing::info!(
+            "Forked session {} at message {} into {} ({} messages)",
+            session_id,
+            at_message_id,
+            fork.id,
+            copied
+        );
+        Ok(fork)
+    }
+
+    /// Message a fork branches at: the session's last message, or with
+    /// `before`, the message right before it. `None` if there is none.
+    pub async fn fork_point(
+        &self,
+        session_id: Uuid,
+        before: Option<&Message>,
+    ) -> Result<Option<Message>> {
+        let messages = MessageRepository::new(self.context.pool())
+            .find_by_session(session_id)
+            .await?;
+        Ok(messages
+            .into_iter()
+            .rev()
+            .find(|m| before.is_none_or(|b| m.sequence < b.sequence)))
+    }
+
     /// Get a session by ID
     pub async fn get_session(&self, id: Uuid) -> Result<Option<Session>> {
         let repo = SessionRepository::new(self.context.pool());
@@ -116,6 +247,19 @@ impl SessionService {
     /// Update session usage statistics and record to the cumulative usage ledger.
     /// The ledger persists even when sessions are deleted.
     pub async fn update_session_usage(&self, id: Uuid, token_count: i32, cost: f64) -> Result<()> {
+        self.update_session_usage_for_channel(id, token_count, cost, "")
+            .await
+    }
+
+    /// [`Self::update_session_usage`], with the ledger entry budgeted under
+    /// `channel`.
+    pub async fn update_session_usage_for_channel(
+        &self,
+        id: Uuid,
+        token_count: i32,
+        cost: f64,
+        channel: &str,
+    ) -> Result<()> {
         let mut session = self.get_session_required(id).await?;
         session.token_count += token_count;
         session.total_cost += cost;
@@ -131,7 +275,7 @@ impl SessionService {
         // Append to cumulative usage ledger (never deleted)
         let ledger = UsageLedgerRepository::new(self.context.pool());
         if let Err(e) = ledger
-            .record(&id.to_string(), &model, token_count, cost)
+            .record_for_channel(&id.to_string(), &model, token_count, cost, channel)
             .await
         {
             tracing::warn!("Failed to record usage to ledger: {}", e);
@@ -146,6 +290,31 @@ impl SessionService {
         Ok(())
     }
 
+    /// Record calls served from the response cache to the usage ledger.
+    /// The session's own token/cost totals are untouched — nothing was billed.
+    pub async fn record_cache_hits(
+        &self,
+        id: Uuid,
+        model: &str,
+        hits: u32,
+        saved_cost: f64,
+    ) -> Result<()> {
+        let ledger = UsageLedgerRepository::new(self.context.pool());
+        let per_hit = saved_cost / hits.max(1) as f64;
+        for _ in 0..hits {
+            ledger
+                .record_cache_hit(&id.to_string(), model, per_hit)
+                .await?;
+        }
+        tracing::debug!(
+            "Recorded {} response cache hit(s) for {} (saved ${:.4})",
+            hits,
+            id,
+            saved_cost
+        );
+        Ok(())
+    }
+
     /// Update session working directory
     pub async fn update_session_working_directory(
         &self,
@@ -0,0 +1,199 @@
+//! Tests for the OpenAI Batch API path of `OpenAIProvider`.
+//!
+//! Covers the JSONL upload + batch creation handshake, status mapping while
+//! polling, and merging the output and error files into per-request results.
+
+use crate::brain::provider::{
+    BatchRequest, BatchState, ContentBlock, LLMRequest, Message, OpenAIProvider, Provider,
+};
+use mockito::Matcher;
+use serde_json::json;
+
+fn provider(server: &mockito::Server) -> OpenAIProvider {
+    OpenAIProvider::with_base_url(
+        "sk-test".to_string(),
+        format!("{}/v1/chat/completions", server.url()),
+    )
+}
+
+#[tokio::test]
+async fn submit_uploads_jsonl_then_creates_batch() {
+    let mut server = mockito::Server::new_async().await;
+    let upload = server
+        .mock("POST", "/v1/files")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex(r#"name="purpose"\s+batch"#.into()),
+            Matcher::Regex(r#""custom_id":"job-1""#.into()),
+            Matcher::Regex(r#""url":"/v1/chat/completions""#.into()),
+            Matcher::Regex(r#""model":"gpt-4o-mini""#.into()),
+        ]))
+        .with_status(200)
+        .with_body(json!({"id": "file-abc", "object": "file", "purpose": "batch"}).to_string())
+        .create_async()
+        .await;
+    let create = server
+        .mock("POST", "/v1/batches")
+        .match_body(Matcher::Json(json!({
+            "input_file_id": "file-abc",
+            "endpoint": "/v1/chat/completions",
+            "completion_window": "24h",
+        })))
+        .with_status(200)
+        .with_body(json!({"id": "batch_1", "status": "validating"}).to_string())
+        .create_async()
+        .await;
+
+    let request = LLMRequest::new("gpt-4o-mini", vec![Message::user("summarize")])
+        .with_system("be brief")
+        .with_streaming();
+    let id = provider(&server)
+        .submit_batch(vec![BatchRequest::new("job-1", request)])
+        .await
+        .unwrap();
+
+    assert_eq!(id, "batch_1");
+    upload.assert_async().await;
+    create.assert_async().await;
+}
+
+#[tokio::test]
+async fn poll_maps_batch_status() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/v1/batches/batch_1")
+        .with_status(200)
+        .with_body(
+            json!({
+                "id": "batch_1",
+                "status": "in_progress",
+                "request_counts": {"total": 3, "completed": 1, "failed": 0}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/v1/batches/batch_2")
+        .with_status(200)
+        .with_body(
+            json!({
+                "id": "batch_2",
+                "status": "failed",
+                "errors": {"data": [{"code": "invalid_model", "message": "Model gpt-x not found"}]}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/v1/batches/batch_3")
+        .with_status(200)
+        .with_body(
+            json!({
+                "id": "batch_3",
+                "status": "expired",
+                "output_file_id": "file-out",
+                "request_counts": {"total": 2, "completed": 1, "failed": 0}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+
+    let provider = provider(&server);
+    let status = provider.poll_batch("batch_1").await.unwrap();
+    assert_eq!(status.state, BatchState::InProgress);
+    assert_eq!(
+        (status.succeeded, status.errored, status.pending),
+        (1, 0, 2)
+    );
+
+    let status = provider.poll_batch("batch_2").await.unwrap();
+    assert_eq!(status.state, BatchState::Failed);
+    assert_eq!(status.error.as_deref(), Some("Model gpt-x not found"));
+
+    // Expired with partial output still has results to collect
+    let status = provider.poll_batch("batch_3").await.unwrap();
+    assert_eq!(status.state, BatchState::Ended);
+}
+
+#[tokio::test]
+async fn fetch_merges_output_and_error_files() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/v1/batches/batch_1")
+        .with_status(200)
+        .with_body(
+            json!({
+                "id": "batch_1",
+                "status": "completed",
+                "output_file_id": "file-out",
+                "error_file_id": "file-err"
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    let output = [
+        json!({
+            "id": "batch_req_1",
+            "custom_id": "a",
+            "response": {
+                "status_code": 200,
+                "body": {
+                    "id": "chatcmpl-1",
+                    "model": "gpt-4o-mini",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": "done"}, "finish_reason": "stop"}],
+                    "usage": {"prompt_tokens": 9, "completion_tokens": 1, "total_tokens": 10}
+                }
+            },
+            "error": null
+        }),
+        json!({
+            "id": "batch_req_2",
+            "custom_id": "b",
+            "response": {"status_code": 400, "body": {"error": {"message": "context too long"}}},
+            "error": null
+        }),
+    ]
+    .map(|line| line.to_string())
+    .join("\n");
+    server
+        .mock("GET", "/v1/files/file-out/content")
+        .with_status(200)
+        .with_body(output)
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/v1/files/file-err/content")
+        .with_status(200)
+        .with_body(
+            json!({"custom_id": "c", "response": null, "error": {"code": "batch_expired", "message": "expired"}})
+                .to_string(),
+        )
+        .create_async()
+        .await;
+
+    let results = provider(&server)
+        .fetch_batch_results("batch_1")
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 3);
+    let reply = results[0].outcome.as_ref().unwrap();
+    assert_eq!(results[0].custom_id, "a");
+    assert_eq!(reply.usage.input_tokens, 9);
+    assert!(matches!(&reply.content[0], ContentBlock::Text { text } if text == "done"));
+    assert_eq!(results[1].outcome.as_ref().unwrap_err(), "context too long");
+    assert_eq!(results[2].custom_id, "c");
+    assert_eq!(results[2].outcome.as_ref().unwrap_err(), "expired");
+}
+
+#[test]
+fn batch_support_is_limited_to_official_endpoints() {
+    assert!(OpenAIProvider::new("sk-test".to_string()).supports_batch());
+    assert!(
+        !OpenAIProvider::local("http://localhost:1234/v1/chat/completions".to_string())
+            .supports_batch()
+    );
+}
@@ -0,0 +1,212 @@
+//! Tests for `BedrockProvider` against a local mock of the Bedrock runtime.
+//!
+//! Uses mockito with `with_base_url` so Converse and ConverseStream run
+//! end-to-end — SigV4 headers, request shape, binary event-stream decoding —
+//! without AWS credentials or network access.
+
+use crate::brain::provider::bedrock::{AwsCredentials, BedrockAuth, BedrockProvider};
+use crate::brain::provider::{
+    ContentBlock, ContentDelta, LLMRequest, Message, Provider, ProviderError, StopReason,
+    StreamEvent,
+};
+use futures::StreamExt;
+use mockito::Matcher;
+use serde_json::json;
+
+const MODEL: &str = "us.amazon.nova-pro-v1:0";
+
+fn provider(base_url: &str) -> BedrockProvider {
+    let auth = BedrockAuth::SigV4(AwsCredentials {
+        access_key_id: "AKIDTEST".to_string(),
+        secret_access_key: "secret".to_string(),
+        session_token: None,
+    });
+    BedrockProvider::new(auth, "us-west-2")
+        .with_base_url(base_url)
+        .with_model(MODEL)
+}
+
+/// Encode one `application/vnd.amazon.eventstream` frame (CRCs zeroed).
+fn frame(event_type: &str, payload: serde_json::Value) -> Vec<u8> {
+    let payload = payload.to_string().into_bytes();
+    let mut headers = Vec::new();
+    for (name, value) in [(":message-type", "event"), (":event-type", event_type)] {
+        headers.push(name.len() as u8);
+        headers.extend_from_slice(name.as_bytes());
+        headers.push(7);
+        headers.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        headers.extend_from_slice(value.as_bytes());
+    }
+    let total = 16 + headers.len() + payload.len();
+    let mut out = Vec::with_capacity(total);
+    out.extend_from_slice(&(total as u32).to_be_bytes());
+    out.extend_from_slice(&(headers.len() as u32).to_be_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&headers);
+    out.extend_from_slice(&payload);
+    out.extend_from_slice(&[0; 4]);
+    out
+}
+
+#[tokio::test]
+async fn complete_sends_signed_converse_request() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", Matcher::Regex(r"^/model/.+/converse$".to_string()))
+        .match_header(
+            "authorization",
+            Matcher::Regex(
+                r"^AWS4-HMAC-SHA256 Credential=AKIDTEST/\d{8}/us-west-2/bedrock/aws4_request, SignedHeaders=host;x-amz-date, Signature=[0-9a-f]{64}$"
+                    .to_string(),
+            ),
+        )
+        .match_header("x-amz-date", Matcher::Regex(r"^\d{8}T\d{6}Z$".to_string()))
+        .match_body(Matcher::PartialJson(json!({
+            "messages": [{"role": "user", "content": [{"text": "hello"}]}],
+            "system": [{"text": "be brief"}]
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "output": {"message": {"role": "assistant", "content": [{"text": "hi there"}]}},
+                "stopReason": "end_turn",
+                "usage": {"inputTokens": 9, "outputTokens": 3, "totalTokens": 12}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+
+    let request = LLMRequest::new(MODEL, vec![Message::user("hello")]).with_system("be brief");
+    let response = provider(&server.url()).complete(request).await.unwrap();
+
+    mock.assert_async().await;
+    assert!(matches!(
+        &response.content[0],
+        ContentBlock::Text { text } if text == "hi there"
+    ));
+    assert!(matches!(response.stop_reason, Some(StopReason::EndTurn)));
+    assert_eq!(response.usage.input_tokens, 9);
+    assert_eq!(response.usage.output_tokens, 3);
+}
+
+#[tokio::test]
+async fn api_key_auth_uses_bearer_token() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", Matcher::Regex(r"^/model/.+/converse$".to_string()))
+        .match_header("authorization", "Bearer bedrock-api-key")
+        .with_status(200)
+        .with_body(r#"{"output":{"message":{"content":[{"text":"ok"}]}},"stopReason":"end_turn","usage":{}}"#)
+        .create_async()
+        .await;
+
+    let provider = BedrockProvider::new(
+        BedrockAuth::ApiKey("bedrock-api-key".to_string()),
+        "us-east-1",
+    )
+    .with_base_url(server.url());
+    provider
+        .complete(LLMRequest::new(MODEL, vec![Message::user("ping")]))
+        .await
+        .unwrap();
+
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn validation_error_surfaces_exception_type() {
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("POST", Matcher::Any)
+        .with_status(400)
+        .with_header(
+            "x-amzn-ErrorType",
+            "ValidationException:http://internal.amazon.com/",
+        )
+        .with_body(r#"{"message":"The model returned the following errors: bad input"}"#)
+        .create_async()
+        .await;
+
+    let err = provider(&server.url())
+        .complete(LLMRequest::new(MODEL, vec![Message::user("hi")]))
+        .await
+        .unwrap_err();
+
+    match err {
+        ProviderError::ApiError {
+            status,
+            message,
+            error_type,
+        } => {
+            assert_eq!(status, 400);
+            assert!(message.contains("bad input"));
+            assert_eq!(error_type.as_deref(), Some("ValidationException"));
+        }
+        other => panic!("expected ApiError, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn stream_decodes_event_stream_body() {
+    let mut body = Vec::new();
+    for (event_type, payload) in [
+        ("messageStart", json!({"role": "assistant"})),
+        (
+            "contentBlockDelta",
+            json!({"contentBlockIndex": 0, "delta": {"text": "Hel"}}),
+        ),
+        (
+            "contentBlockDelta",
+            json!({"contentBlockIndex": 0, "delta": {"text": "lo"}}),
+        ),
+        ("contentBlockStop", json!({"contentBlockIndex": 0})),
+        ("messageStop", json!({"stopReason": "end_turn"})),
+        (
+            "metadata",
+            json!({"usage": {"inputTokens": 4, "outputTokens": 2}, "metrics": {"latencyMs": 10}}),
+        ),
+    ] {
+        body.extend(frame(event_type, payload));
+    }
+
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock(
+            "POST",
+            Matcher::Regex(r"^/model/.+/converse-stream$".to_string()),
+        )
+        .with_status(200)
+        .with_header("content-type", "application/vnd.amazon.eventstream")
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let mut stream = provider(&server.url())
+        .stream(LLMRequest::new(MODEL, vec![Message::user("hi")]).with_streaming())
+        .await
+        .unwrap();
+
+    let mut text = String::new();
+    let mut stop_reason = None;
+    let mut saw_stop = false;
+    while let Some(event) = stream.next().await {
+        match event.unwrap() {
+            StreamEvent::ContentBlockDelta {
+                delta: ContentDelta::TextDelta { text: t },
+                ..
+            } => text.push_str(&t),
+            StreamEvent::MessageDelta { delta, usage } => {
+                stop_reason = delta.stop_reason;
+                assert_eq!(usage.input_tokens, 4);
+            }
+            StreamEvent::MessageStop => saw_stop = true,
+            _ => {}
+        }
+    }
+
+    assert_eq!(text, "Hello");
+    assert!(matches!(stop_reason, Some(StopReason::EndTurn)));
+    assert!(saw_stop);
+}
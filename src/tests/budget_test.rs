@@ -0,0 +1,211 @@
+//! Tests for spending budgets (`usage::budget`).
+//!
+//! Spend is summed from the usage ledger per period and scope (profile,
+//! channel, cron job), plus calls in turns not yet recorded. The verdict
+//! warns past `warn_at`, and at a cap refuses or downgrades.
+
+use crate::config::{BudgetAction, BudgetConfig, BudgetLimits};
+use crate::db::{Database, UsageLedgerRepository};
+use crate::usage::budget::{self, BudgetStatus, Period, Verdict};
+use chrono::Local;
+
+async fn ledger() -> (Database, UsageLedgerRepository) {
+    let db = Database::connect_in_memory()
+        .await
+        .expect("Failed to create database");
+    db.run_migrations().await.expect("Failed to run migrations");
+    let repo = UsageLedgerRepository::new(db.pool().clone());
+    (db, repo)
+}
+
+fn daily(usd: f64) -> BudgetLimits {
+    BudgetLimits {
+        daily_usd: Some(usd),
+        monthly_usd: None,
+    }
+}
+
+fn status(spent: f64, limit: f64) -> BudgetStatus {
+    BudgetStatus {
+        scope: budget::PROFILE_SCOPE.to_string(),
+        period: Period::Daily,
+        spent,
+        limit,
+        since: 0,
+    }
+}
+
+#[tokio::test]
+async fn statuses_cover_profile_channel_and_cron_caps() {
+    let (db, repo) = ledger().await;
+    repo.record_for_channel("s1", "sonnet", 100, 0.40, "telegram")
+        .await
+        .unwrap();
+    repo.record_for_channel("s2", "sonnet", 100, 0.25, "cron:digest")
+        .await
+        .unwrap();
+    repo.record_for_channel("s2", "sonnet", 100, 0.10, "cron:backup")
+        .await
+        .unwrap();
+    repo.record("s3", "sonnet", 100, 0.05).await.unwrap();
+
+    let mut config = BudgetConfig {
+        daily_usd: Some(2.0),
+        monthly_usd: Some(10.0),
+        ..Default::default()
+    };
+    config.channels.insert("cron".into(), daily(1.0));
+    config.cron.insert("digest".into(), daily(0.5));
+
+    let now = Local::now();
+    let spent = |statuses: &[BudgetStatus], scope: &str, period: Period| {
+        statuses
+            .iter()
+            .find(|s| s.scope == scope && s.period == period)
+            .map(|s| s.spent)
+            .unwrap()
+    };
+
+    let statuses = budget::statuses(db.pool(), &config, "cron:digest", now)
+        .await
+        .unwrap();
+    assert_eq!(statuses.len(), 4);
+    assert!((spent(&statuses, "profile", Period::Daily) - 0.80).abs() < 1e-9);
+    assert!((spent(&statuses, "profile", Period::Monthly) - 0.80).abs() < 1e-9);
+    assert!((spent(&statuses, "cron", Period::Daily) - 0.35).abs() < 1e-9);
+    assert!((spent(&statuses, "cron:digest", Period::Daily) - 0.25).abs() < 1e-9);
+
+    // Telegram has no cap of its own: only the profile caps apply
+    let statuses = budget::statuses(db.pool(), &config, "telegram", now)
+        .await
+        .unwrap();
+    assert!(statuses.iter().all(|s| s.scope == "profile"));
+
+    // A running turn's calls count before the ledger has them
+    let session = uuid::Uuid::new_v4();
+    budget::note_call_cost(session, "cron:digest", 0.30);
+    let statuses = budget::statuses(db.pool(), &config, "cron:digest", now)
+        .await
+        .unwrap();
+    assert!((spent(&statuses, "cron:digest", Period::Daily) - 0.55).abs() < 1e-9);
+    assert!(statuses.iter().any(|s| s.is_exceeded()));
+    budget::clear_unrecorded(session);
+    let statuses = budget::statuses(db.pool(), &config, "cron:digest", now)
+        .await
+        .unwrap();
+    assert!(!statuses.iter().any(|s| s.is_exceeded()));
+
+    let all = budget::all_statuses(db.pool(), &config).await.unwrap();
+    let scopes: Vec<_> = all.iter().map(|s| s.scope.as_str()).collect();
+    assert_eq!(scopes, vec!["profile", "profile", "cron", "cron:digest"]);
+}
+
+#[tokio::test]
+async fn spend_before_the_period_is_ignored() {
+    let (db, repo) = ledger().await;
+    repo.record_for_channel("s1", "sonnet", 100, 0.20, "slack")
+        .await
+        .unwrap();
+    let yesterday = Period::Daily.start(Local::now()) - 60;
+    db.pool()
+        .get()
+        .await
+        .unwrap()
+        .interact(move |conn| {
+            conn.execute(
+                "INSERT INTO usage_ledger (session_id, model, cost, channel, created_at) \
+                 VALUES ('s0', 'sonnet', 5.0, 'slack', ?1)",
+                [yesterday],
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut config = BudgetConfig::default();
+    config.channels.insert("slack".into(), daily(1.0));
+    let statuses = budget::statuses(db.pool(), &config, "slack", Local::now())
+        .await
+        .unwrap();
+
+    assert_eq!(statuses.len(), 1);
+    assert!((statuses[0].spent - 0.20).abs() < 1e-9);
+    assert!((statuses[0].remaining() - 0.80).abs() < 1e-9);
+}
+
+#[test]
+fn verdict_warns_then_refuses() {
+    let config = BudgetConfig::default();
+
+    assert_eq!(
+        budget::evaluate(&config, &[status(0.5, 1.0)]),
+        Verdict::Allow
+    );
+    assert_eq!(
+        budget::evaluate(&config, &[status(0.5, 1.0), status(0.85, 1.0)]),
+        Verdict::Warn(status(0.85, 1.0))
+    );
+    assert_eq!(
+        budget::evaluate(&config, &[status(0.9, 1.0), status(1.2, 1.0)]),
+        Verdict::Refuse(status(1.2, 1.0))
+    );
+}
+
+#[test]
+fn verdict_downgrades_when_configured() {
+    let mut config = BudgetConfig {
+        on_exceed: BudgetAction::Downgrade,
+        ..Default::default()
+    };
+    // No model to downgrade to: refuse rather than keep spending
+    assert_eq!(
+        budget::evaluate(&config, &[status(1.0, 1.0)]),
+        Verdict::Refuse(status(1.0, 1.0))
+    );
+
+    config.downgrade_model = Some("claude-haiku-4-5".into());
+    assert_eq!(
+        budget::evaluate(&config, &[status(1.0, 1.0)]),
+        Verdict::Downgrade {
+            status: status(1.0, 1.0),
+            model: "claude-haiku-4-5".into(),
+        }
+    );
+}
+
+#[test]
+fn each_notice_fires_once_per_period() {
+    let mut s = status(0.9, 1.0);
+    s.scope = "budget-test-notice".into();
+    assert!(budget::first_notice("warn", &s));
+    assert!(!budget::first_notice("warn", &s));
+    assert!(budget::first_notice("downgrade", &s));
+
+    s.since += 86_400;
+    assert!(budget::first_notice("warn", &s));
+}
+
+#[test]
+fn config_parses_scoped_caps() {
+    let config: crate::config::BudgetConfig = toml::from_str(
+        r#"
+        daily_usd = 5.0
+        on_exceed = "downgrade"
+        downgrade_model = "gpt-4o-mini"
+
+        [channels.telegram]
+        daily_usd = 1.0
+
+        [cron."nightly-report"]
+        monthly_usd = 10.0
+        "#,
+    )
+    .unwrap();
+
+    assert!(config.is_enabled());
+    assert_eq!(config.warn_at, 0.8);
+    assert_eq!(config.on_exceed, BudgetAction::Downgrade);
+    assert_eq!(config.channels["telegram"], daily(1.0));
+    assert_eq!(config.cron["nightly-report"].monthly_usd, Some(10.0));
+    assert!(!BudgetConfig::default().is_enabled());
+}
@@ -0,0 +1,209 @@
+//! Tests for `CassetteProvider` record-and-replay.
+//!
+//! A scripted provider is recorded once; replay must reproduce its stream
+//! events, `complete()` replies and errors without calling it, and explain
+//! with a diff when a request doesn't match the cassette.
+
+use crate::brain::provider::{
+    CassetteProvider, ContentBlock, ContentDelta, LLMRequest, LLMResponse, Message, Provider,
+    ProviderError, ProviderStream, Result, Role, StopReason, StreamEvent, StreamMessage,
+    TokenUsage,
+};
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Streams a reply echoing the last user message; fails on "boom".
+#[derive(Default)]
+struct ScriptedProvider {
+    calls: AtomicUsize,
+}
+
+fn last_text(request: &LLMRequest) -> String {
+    request
+        .messages
+        .last()
+        .and_then(|m| {
+            m.content.iter().find_map(|b| match b {
+                ContentBlock::Text { text } => Some(text.clone()),
+                _ => None,
+            })
+        })
+        .unwrap_or_default()
+}
+
+#[async_trait]
+impl Provider for ScriptedProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if last_text(&request) == "boom" {
+            return Err(ProviderError::ApiError {
+                status: 529,
+                message: "overloaded".to_string(),
+                error_type: Some("overloaded_error".to_string()),
+            });
+        }
+        Ok(LLMResponse {
+            id: "resp-1".to_string(),
+            model: request.model.clone(),
+            content: vec![ContentBlock::Text {
+                text: format!("echo: {}", last_text(&request)),
+            }],
+            stop_reason: Some(StopReason::EndTurn),
+            usage: TokenUsage {
+                input_tokens: 7,
+                output_tokens: 3,
+                ..Default::default()
+            },
+        })
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let events = vec![
+            Ok(StreamEvent::MessageStart {
+                message: StreamMessage {
+                    id: "msg-1".to_string(),
+                    model: request.model.clone(),
+                    role: Role::Assistant,
+                    usage: TokenUsage::default(),
+                },
+            }),
+            Ok(StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: ContentDelta::TextDelta {
+                    text: format!("echo: {}", last_text(&request)),
+                },
+            }),
+            Err(ProviderError::RateLimitExceeded("slow down".to_string())),
+            Ok(StreamEvent::MessageStop),
+        ];
+        Ok(Box::pin(futures::stream::iter(events)))
+    }
+
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn default_model(&self) -> &str {
+        "scripted-1"
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        vec!["scripted-1".to_string()]
+    }
+
+    fn context_window(&self, _model: &str) -> Option<u32> {
+        Some(32_000)
+    }
+
+    fn calculate_cost(&self, _model: &str, _input: u32, _output: u32) -> f64 {
+        0.0
+    }
+}
+
+fn request(text: &str) -> LLMRequest {
+    LLMRequest::new("scripted-1", vec![Message::user(text)])
+}
+
+/// Flatten a stream into comparable strings.
+async fn drain(stream: ProviderStream) -> Vec<String> {
+    stream
+        .map(|item| match item {
+            Ok(event) => serde_json::to_string(&event).unwrap(),
+            Err(e) => format!("error: {}", e),
+        })
+        .collect()
+        .await
+}
+
+async fn record(path: &std::path::Path) -> (Vec<String>, String) {
+    let inner = Arc::new(ScriptedProvider::default());
+    let recorder = CassetteProvider::record(inner.clone(), path);
+
+    let streamed = drain(recorder.stream(request("hello")).await.unwrap()).await;
+    let completed = recorder.complete(request("again")).await.unwrap();
+    let err = recorder.complete(request("boom")).await.unwrap_err();
+    assert!(matches!(err, ProviderError::ApiError { status: 529, .. }));
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+
+    (streamed, completed.id)
+}
+
+#[tokio::test]
+async fn replay_reproduces_recorded_calls() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.json");
+    let (streamed, completed_id) = record(&path).await;
+
+    let player = CassetteProvider::replay(&path).unwrap();
+    assert!(player.is_replay());
+    assert_eq!(player.name(), "scripted");
+    assert_eq!(player.default_model(), "scripted-1");
+    assert_eq!(player.context_window("scripted-1"), Some(32_000));
+
+    // Out of order: matching is by request, not position
+    let err = player.complete(request("boom")).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ProviderError::ApiError { status: 529, ref error_type, .. }
+            if error_type.as_deref() == Some("overloaded_error")
+    ));
+    let replayed = drain(player.stream(request("hello")).await.unwrap()).await;
+    assert_eq!(replayed, streamed);
+    assert!(replayed.iter().any(|e| e.contains("Rate limit")));
+    let completed = player.complete(request("again")).await.unwrap();
+    assert_eq!(completed.id, completed_id);
+    assert_eq!(completed.usage.input_tokens, 7);
+
+    assert!(player.unplayed().is_empty());
+}
+
+#[tokio::test]
+async fn mismatch_reports_a_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.json");
+    record(&path).await;
+
+    let player = CassetteProvider::replay(&path).unwrap();
+    let err = player
+        .stream(LLMRequest::new(
+            "other-model",
+            vec![Message::user("goodbye")],
+        ))
+        .await
+        .err()
+        .unwrap();
+    let msg = err.to_string();
+    assert!(msg.contains("no recorded request matches"), "{msg}");
+    assert!(msg.contains("- scripted-1\n  + other-model"), "{msg}");
+    assert!(msg.contains("- hello\n  + goodbye"), "{msg}");
+
+    // Same request, wrong method
+    let err = player.complete(request("hello")).await.unwrap_err();
+    assert!(err.to_string().contains("recorded with stream()"));
+
+    // Nothing left to match once the rest is played
+    player.complete(request("again")).await.unwrap();
+    player.complete(request("boom")).await.unwrap_err();
+    let err = player.complete(request("again")).await.unwrap_err();
+    assert!(err.to_string().contains("already played"), "{err}");
+}
+
+#[tokio::test]
+async fn auto_records_then_replays() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join("auto.json");
+
+    let inner = Arc::new(ScriptedProvider::default());
+    let first = CassetteProvider::auto(inner.clone(), &path).unwrap();
+    assert!(!first.is_replay());
+    first.complete(request("again")).await.unwrap();
+    assert!(path.exists());
+
+    let second = CassetteProvider::auto(inner.clone(), &path).unwrap();
+    assert!(second.is_replay());
+    second.complete(request("again")).await.unwrap();
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+}
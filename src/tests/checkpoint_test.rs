@@ -0,0 +1,216 @@
+//! Tests for file checkpoints (`/undo`, `/rewind`).
+//!
+//! File-changing tool calls snapshot the prior content under the turn's user
+//! message; undo reverts the latest turn, rewind reverts files and drops the
+//! conversation from a message on.
+
+use crate::brain::agent::AgentService;
+use crate::brain::provider::PlaceholderProvider;
+use crate::channels::commands::{ChannelCommand, handle_command};
+use crate::db::Database;
+use crate::services::checkpoint::{self, CheckpointService};
+use crate::services::{MessageService, ServiceContext, SessionService};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+struct Fixture {
+    context: ServiceContext,
+    checkpoints: CheckpointService,
+    messages: MessageService,
+    session_id: Uuid,
+    dir: TempDir,
+}
+
+impl Fixture {
+    async fn new() -> Self {
+        let db = Database::connect_in_memory().await.unwrap();
+        db.run_migrations().await.unwrap();
+        let context = ServiceContext::new(db.pool().clone());
+        let session = SessionService::new(context.clone())
+            .create_session(Some("Checkpoints".to_string()))
+            .await
+            .unwrap();
+        Self {
+            checkpoints: CheckpointService::new(context.clone()),
+            messages: MessageService::new(context.clone()),
+            context,
+            session_id: session.id,
+            dir: TempDir::new().unwrap(),
+        }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    async fn say(&self, role: &str, content: &str) {
+        self.messages
+            .create_message(self.session_id, role.to_string(), content.to_string())
+            .await
+            .unwrap();
+    }
+
+    /// `write_file` as the tool loop runs it: checkpoint, then write
+    async fn write(&self, name: &str, content: &str) {
+        self.checkpoints
+            .before_tool_call(
+                self.session_id,
+                "write_file",
+                &json!({ "path": name, "content": content }),
+                self.dir.path(),
+                false,
+            )
+            .await;
+        std::fs::write(self.path(name), content).unwrap();
+    }
+
+    fn read(&self, name: &str) -> Option<String> {
+        std::fs::read_to_string(self.path(name)).ok()
+    }
+}
+
+#[tokio::test]
+async fn undo_reverts_one_turn_at_a_time() {
+    let f = Fixture::new().await;
+    std::fs::write(f.path("a.txt"), "v1").unwrap();
+
+    f.say("user", "first").await;
+    f.write("a.txt", "v2").await;
+    f.say("user", "second").await;
+    f.write("a.txt", "v3").await;
+    f.write("a.txt", "v4").await;
+    f.write("b.txt", "new").await;
+
+    let report = f.checkpoints.undo(f.session_id).await.unwrap().unwrap();
+    assert_eq!(
+        f.read("a.txt").as_deref(),
+        Some("v2"),
+        "back to before the turn"
+    );
+    assert_eq!(f.read("b.txt"), None, "files the turn created are deleted");
+    assert_eq!(report.restored, vec![f.path("a.txt")]);
+    assert_eq!(report.removed, vec![f.path("b.txt")]);
+
+    f.checkpoints.undo(f.session_id).await.unwrap().unwrap();
+    assert_eq!(f.read("a.txt").as_deref(), Some("v1"));
+    assert!(f.checkpoints.undo(f.session_id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn rewind_restores_files_and_conversation() {
+    let f = Fixture::new().await;
+    f.say("user", "make a").await;
+    f.write("a.txt", "a1").await;
+    f.say("assistant", "made a").await;
+    f.say("user", "change a").await;
+    f.write("a.txt", "a2").await;
+    f.checkpoints
+        .before_tool_call(
+            f.session_id,
+            "bash",
+            &json!({"command": "touch c"}),
+            f.dir.path(),
+            true,
+        )
+        .await;
+    f.say("assistant", "changed a").await;
+    f.say("user", "thanks").await;
+
+    let points = f.checkpoints.rewind_points(f.session_id).await.unwrap();
+    let menu = checkpoint::rewind_menu(&points);
+    assert!(
+        menu.contains("1. thanks") && menu.contains("3. make a"),
+        "{menu}"
+    );
+
+    let target = checkpoint::rewind_point(&points, 2).unwrap();
+    assert_eq!(target.content, "change a");
+    let report = f.checkpoints.rewind(f.session_id, target).await.unwrap();
+
+    assert_eq!(f.read("a.txt").as_deref(), Some("a1"));
+    assert_eq!(report.messages_removed, 3);
+    assert_eq!(
+        report.shell_commands, 1,
+        "shell commands are flagged, not reverted"
+    );
+    assert!(report.summary().contains("not reverted"));
+
+    let left: Vec<String> = f
+        .messages
+        .list_messages_for_session(f.session_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.content)
+        .collect();
+    assert_eq!(left, vec!["make a", "made a"]);
+    assert!(checkpoint::rewind_point(&points, 4).is_none());
+}
+
+#[tokio::test]
+async fn channel_rewind_numbers_skip_slash_commands() {
+    let f = Fixture::new().await;
+    f.say("user", "make a").await;
+    f.write("a.txt", "a1").await;
+    f.say("assistant", "made a").await;
+
+    let agent = AgentService::new_for_test(Arc::new(PlaceholderProvider), f.context.clone()).await;
+    let sessions = SessionService::new(f.context.clone());
+
+    // The channel saves "/rewind" and its reply to the history...
+    let ChannelCommand::Notice(menu) =
+        handle_command("/rewind", f.session_id, &agent, &sessions).await
+    else {
+        panic!("/rewind should reply with a notice");
+    };
+    assert!(menu.contains("1. make a"), "{menu}");
+
+    // ...but #1 is still the last real message, not the command
+    let ChannelCommand::Notice(reply) =
+        handle_command("/rewind 1", f.session_id, &agent, &sessions).await
+    else {
+        panic!("/rewind 1 should reply with a notice");
+    };
+    assert!(reply.contains("> make a"), "{reply}");
+    assert_eq!(f.read("a.txt"), None);
+    let points = f.checkpoints.rewind_points(f.session_id).await.unwrap();
+    assert!(points.is_empty(), "{points:?}");
+}
+
+#[tokio::test]
+async fn only_file_and_shell_tools_are_checkpointed() {
+    let f = Fixture::new().await;
+    f.say("user", "look around").await;
+    f.checkpoints
+        .before_tool_call(
+            f.session_id,
+            "read_file",
+            &json!({"path": "a.txt"}),
+            f.dir.path(),
+            false,
+        )
+        .await;
+    assert!(f.checkpoints.undo(f.session_id).await.unwrap().is_none());
+
+    // Absolute paths are kept as-is, relative ones resolve against the working dir
+    let outside = TempDir::new().unwrap();
+    let absolute = outside.path().join("abs.txt");
+    std::fs::write(&absolute, "before").unwrap();
+    f.checkpoints
+        .before_tool_call(
+            f.session_id,
+            "edit_file",
+            &json!({"path": absolute.to_string_lossy()}),
+            Path::new("/nonexistent"),
+            false,
+        )
+        .await;
+    std::fs::write(&absolute, "after").unwrap();
+
+    let report = f.checkpoints.undo(f.session_id).await.unwrap().unwrap();
+    assert_eq!(report.restored, vec![absolute.clone()]);
+    assert_eq!(std::fs::read_to_string(&absolute).unwrap(), "before");
+}
@@ -253,6 +253,37 @@ mod cli {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_cron_add_delivery_mode() {
+        let parse = |mode: Option<&str>| {
+            let mut args = vec![
+                "opencrabs",
+                "cron",
+                "add",
+                "--name",
+                "Digest",
+                "--cron",
+                "0 7 * * *",
+                "--prompt",
+                "Summarize",
+            ];
+            if let Some(mode) = mode {
+                args.extend(["--delivery-mode", mode]);
+            }
+            Cli::try_parse_from(args)
+        };
+
+        for (mode, expected) in [(None, "immediate"), (Some("batch"), "batch")] {
+            match parse(mode).unwrap().command {
+                Some(Commands::Cron {
+                    operation: CronCommands::Add { delivery_mode, .. },
+                }) => assert_eq!(delivery_mode, expected),
+                _ => panic!("Expected Cron Add command"),
+            }
+        }
+        assert!(parse(Some("later")).is_err());
+    }
+
     #[test]
     fn test_cron_invalid_subcommand() {
         let result = Cli::try_parse_from(["opencrabs", "cron", "invalid"]);
@@ -328,6 +359,38 @@ mod repository {
         assert!(not_found.is_none());
     }
 
+    #[tokio::test]
+    async fn test_tool_choice_round_trips() {
+        let (_db, repo) = setup().await;
+        let mut job = make_job("forced-tool", "0 9 * * *");
+        job.tool_choice = Some("telegram_send".to_string());
+        repo.insert(&job).await.unwrap();
+        repo.insert(&make_job("default-tools", "0 9 * * *"))
+            .await
+            .unwrap();
+
+        let forced = repo.find_by_name("forced-tool").await.unwrap().unwrap();
+        assert_eq!(forced.tool_choice.as_deref(), Some("telegram_send"));
+        let default = repo.find_by_name("default-tools").await.unwrap().unwrap();
+        assert!(default.tool_choice.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_mode_round_trips() {
+        let (_db, repo) = setup().await;
+        let mut job = make_job("nightly-digest", "0 3 * * *");
+        job.delivery_mode = "batch".to_string();
+        repo.insert(&job).await.unwrap();
+        repo.insert(&make_job("interactive", "0 9 * * *"))
+            .await
+            .unwrap();
+
+        let batched = repo.find_by_name("nightly-digest").await.unwrap().unwrap();
+        assert_eq!(batched.delivery_mode, "batch");
+        let default = repo.find_by_name("interactive").await.unwrap().unwrap();
+        assert_eq!(default.delivery_mode, "immediate");
+    }
+
     #[tokio::test]
     async fn test_list_all() {
         let (_db, repo) = setup().await;
@@ -764,6 +827,171 @@ mod tool {
         assert!(result.success);
         assert!(result.output.contains("telegram:123456"));
     }
+
+    #[tokio::test]
+    async fn test_create_with_delivery_mode() {
+        let (_db, tool) = setup().await;
+        let input = serde_json::json!({
+            "action": "create",
+            "name": "Nightly Summary",
+            "cron": "0 2 * * *",
+            "prompt": "Summarize yesterday",
+            "delivery_mode": "batch"
+        });
+        let result = tool.execute(input, &ctx()).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("Mode: batch"));
+
+        let input = serde_json::json!({
+            "action": "create",
+            "name": "Bad Mode",
+            "cron": "0 2 * * *",
+            "prompt": "x",
+            "delivery_mode": "eventually"
+        });
+        let result = tool.execute(input, &ctx()).await.unwrap();
+        assert!(!result.success);
+    }
+}
+
+// --- Batch Tracking Tests ---
+
+mod batches {
+    use crate::db::models::{CronJob, CronJobRun, LlmBatch};
+    use crate::db::{CronJobRepository, CronJobRunRepository, Database, LlmBatchRepository};
+
+    async fn setup() -> (Database, LlmBatchRepository) {
+        let db = Database::connect_in_memory()
+            .await
+            .expect("Failed to create database");
+        db.run_migrations().await.expect("Failed to run migrations");
+        let repo = LlmBatchRepository::new(db.pool().clone());
+        (db, repo)
+    }
+
+    #[tokio::test]
+    async fn test_pending_batches_survive_until_finished() {
+        let (_db, repo) = setup().await;
+        for id in ["msgbatch_a", "msgbatch_b", "msgbatch_c"] {
+            repo.insert(&LlmBatch::new_pending(
+                id.to_string(),
+                "anthropic".to_string(),
+                "cron",
+                Some(format!("run-{id}")),
+                1,
+            ))
+            .await
+            .unwrap();
+        }
+
+        let pending = repo.list_pending().await.unwrap();
+        assert_eq!(pending.len(), 3);
+        assert_eq!(pending[0].provider, "anthropic");
+        assert_eq!(pending[0].reference_id.as_deref(), Some("run-msgbatch_a"));
+
+        repo.mark_completed("msgbatch_a").await.unwrap();
+        repo.mark_failed("msgbatch_b", "batch expired")
+            .await
+            .unwrap();
+
+        let pending = repo.list_pending().await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, "msgbatch_c");
+    }
+
+    #[tokio::test]
+    async fn test_last_submitted_per_purpose() {
+        let (_db, repo) = setup().await;
+        assert!(repo.last_submitted("categorize").await.unwrap().is_none());
+
+        let mut old = LlmBatch::new_pending(
+            "msgbatch_old".to_string(),
+            "anthropic".to_string(),
+            "categorize",
+            None,
+            1,
+        );
+        old.created_at = chrono::Utc::now() - chrono::Duration::days(2);
+        repo.insert(&old).await.unwrap();
+        let new = LlmBatch::new_pending(
+            "msgbatch_new".to_string(),
+            "anthropic".to_string(),
+            "categorize",
+            None,
+            1,
+        );
+        repo.insert(&new).await.unwrap();
+        repo.mark_failed("msgbatch_new", "expired").await.unwrap();
+
+        let last = repo.last_submitted("categorize").await.unwrap().unwrap();
+        assert_eq!(last.timestamp(), new.created_at.timestamp());
+        assert!(repo.last_submitted("rsi_digest").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_categorize_batch_results_are_saved() {
+        use crate::brain::provider::{BatchResult, ContentBlock, LLMResponse, TokenUsage};
+        use crate::services::{ServiceContext, SessionService};
+        use crate::usage::categorizer;
+
+        let (db, _repo) = setup().await;
+        let sessions = SessionService::new(ServiceContext::new(db.pool().clone()));
+        let session = sessions
+            .create_session(Some("Write integration tests".to_string()))
+            .await
+            .unwrap();
+        let id = session.id.to_string();
+
+        let pending = categorizer::fetch_uncategorized(db.pool(), 10)
+            .await
+            .unwrap();
+        assert!(pending.iter().any(|s| s.id == id));
+
+        let reply = LLMResponse {
+            id: "msg_1".into(),
+            model: "m".into(),
+            content: vec![ContentBlock::Text {
+                text: format!("{id}|Testing\nunknown|Features\n{id}|Gardening"),
+            }],
+            stop_reason: None,
+            usage: TokenUsage::default(),
+        };
+        let results = vec![BatchResult::succeeded(categorizer::BATCH_PURPOSE, reply)];
+        let saved = categorizer::apply_categorize_batch(db.pool(), &results)
+            .await
+            .unwrap();
+        assert_eq!(saved, 1);
+        let pending = categorizer::fetch_uncategorized(db.pool(), 10)
+            .await
+            .unwrap();
+        assert!(pending.iter().all(|s| s.id != id));
+    }
+
+    #[tokio::test]
+    async fn test_run_lookup_by_id() {
+        let (db, _repo) = setup().await;
+        let jobs = CronJobRepository::new(db.pool().clone());
+        let runs = CronJobRunRepository::new(db.pool().clone());
+        let job = CronJob::new(
+            "digest".to_string(),
+            "0 3 * * *".to_string(),
+            "UTC".to_string(),
+            "Summarize".to_string(),
+            None,
+            None,
+            "off".to_string(),
+            true,
+            None,
+        );
+        jobs.insert(&job).await.unwrap();
+        let run = CronJobRun::new_running(job.id, job.name.clone(), None, None);
+        runs.insert(&run).await.unwrap();
+
+        let found = runs.find_by_id(&run.id.to_string()).await.unwrap().unwrap();
+        assert_eq!(found.job_id, job.id);
+        assert_eq!(found.status, "running");
+        assert!(runs.find_by_id("missing").await.unwrap().is_none());
+    }
 }
 
 // --- Scheduler Logic Tests ---
@@ -858,6 +1086,29 @@ mod scheduler {
         let enabled = repo.list_enabled().await.unwrap();
         assert!(enabled.is_empty());
     }
+
+    #[test]
+    fn test_tool_choice_setting_parses() {
+        use crate::brain::provider::ToolChoice;
+        assert_eq!(ToolChoice::from_setting("auto"), ToolChoice::Auto);
+        assert_eq!(ToolChoice::from_setting("any"), ToolChoice::Required);
+        assert_eq!(ToolChoice::from_setting("none"), ToolChoice::None);
+        assert_eq!(
+            ToolChoice::from_setting("telegram_send"),
+            ToolChoice::Tool("telegram_send".to_string())
+        );
+    }
+
+    #[test]
+    fn test_thinking_mode_maps_to_budget() {
+        use crate::cron::thinking_budget_for_mode;
+        assert_eq!(thinking_budget_for_mode("off"), None);
+        assert_eq!(thinking_budget_for_mode(""), None);
+        assert_eq!(thinking_budget_for_mode("disabled"), Some(0));
+        assert_eq!(thinking_budget_for_mode("on"), Some(10_000));
+        assert_eq!(thinking_budget_for_mode("budget"), Some(1_024));
+        assert_eq!(thinking_budget_for_mode("sometimes"), None);
+    }
 }
 
 // --- Session Resolution Tests ---
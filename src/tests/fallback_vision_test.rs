@@ -6,7 +6,7 @@
 // --- Fallback chain config ---
 
 mod fallback_chain {
-    use crate::brain::provider::factory::fallback_chain;
+    use crate::brain::provider::factory::{circuit_breaker, fallback_chain};
     use crate::config::FallbackProviderConfig;
 
     #[test]
@@ -21,6 +21,7 @@ mod fallback_chain {
             enabled: true,
             provider: Some("openrouter".into()),
             providers: vec![],
+            ..Default::default()
         };
         assert_eq!(fallback_chain(&cfg), vec!["openrouter"]);
     }
@@ -31,6 +32,7 @@ mod fallback_chain {
             enabled: true,
             provider: None,
             providers: vec!["anthropic".into(), "openai".into()],
+            ..Default::default()
         };
         assert_eq!(fallback_chain(&cfg), vec!["anthropic", "openai"]);
     }
@@ -41,6 +43,7 @@ mod fallback_chain {
             enabled: true,
             provider: Some("gemini".into()),
             providers: vec!["anthropic".into(), "openai".into()],
+            ..Default::default()
         };
         assert_eq!(fallback_chain(&cfg), vec!["anthropic", "openai", "gemini"]);
     }
@@ -51,6 +54,7 @@ mod fallback_chain {
             enabled: true,
             provider: Some("anthropic".into()),
             providers: vec!["anthropic".into(), "openai".into()],
+            ..Default::default()
         };
         // "anthropic" already in array — should NOT be appended again
         assert_eq!(fallback_chain(&cfg), vec!["anthropic", "openai"]);
@@ -62,6 +66,7 @@ mod fallback_chain {
             enabled: true,
             provider: None,
             providers: vec!["minimax".into()],
+            ..Default::default()
         };
         assert_eq!(fallback_chain(&cfg), vec!["minimax"]);
     }
@@ -90,6 +95,24 @@ provider = "openrouter"
         assert!(cfg.providers.is_empty());
     }
 
+    #[test]
+    fn recovery_defaults_and_overrides() {
+        let cfg: FallbackProviderConfig = toml::from_str("enabled = true").unwrap();
+        let breaker = circuit_breaker(&cfg);
+        assert_eq!(breaker.max_failures, 1);
+        assert_eq!(breaker.cooldown, Some(std::time::Duration::from_secs(300)));
+        assert_eq!(breaker.probe_interval, std::time::Duration::from_secs(60));
+
+        let cfg: FallbackProviderConfig = toml::from_str(
+            "enabled = true\nmax_consecutive_failures = 3\ncooldown_secs = 0\nprobe_interval_secs = 10",
+        )
+        .unwrap();
+        let breaker = circuit_breaker(&cfg);
+        assert_eq!(breaker.max_failures, 3);
+        assert_eq!(breaker.cooldown, None);
+        assert_eq!(breaker.probe_interval, std::time::Duration::from_secs(10));
+    }
+
     #[test]
     fn deserialization_from_toml_both() {
         let toml_str = r#"
@@ -107,11 +130,13 @@ providers = ["anthropic", "openai"]
 
 mod fallback_runtime {
     use crate::brain::provider::{
-        FallbackProvider, LLMRequest, LLMResponse, Provider, ProviderError, ProviderStream,
+        CircuitBreaker, FallbackProvider, LLMRequest, LLMResponse, Provider, ProviderError,
+        ProviderStream,
     };
     use async_trait::async_trait;
-    use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
     /// A mock provider that fails N times, then succeeds.
     struct MockProvider {
@@ -136,6 +161,14 @@ mod fallback_runtime {
                 max_failures: 0,
             }
         }
+
+        fn fail_then_succeed(name: &str, failures: usize) -> Self {
+            Self {
+                name: name.to_string(),
+                fail_count: AtomicUsize::new(0),
+                max_failures: failures,
+            }
+        }
     }
 
     #[async_trait]
@@ -214,10 +247,14 @@ mod fallback_runtime {
             max_tokens: None,
             temperature: None,
             tools: None,
+            tool_choice: None,
             stream: false,
             metadata: None,
             working_directory: None,
             session_id: None,
+            thinking_budget: None,
+            response_format: None,
+            cache_ttl: None,
         }
     }
 
@@ -298,6 +335,245 @@ mod fallback_runtime {
         }
     }
 
+    /// Let the background health probe run until the primary has seen `calls`.
+    async fn wait_for_calls(primary: &MockProvider, calls: usize) {
+        for _ in 0..1000 {
+            if primary.fail_count.load(Ordering::SeqCst) >= calls {
+                return;
+            }
+            tokio::task::yield_now().await;
+        }
+        panic!("primary never saw {calls} calls");
+    }
+
+    fn breaker(max_failures: u32, cooldown: Duration, probe_interval: Duration) -> CircuitBreaker {
+        CircuitBreaker {
+            max_failures,
+            cooldown: Some(cooldown),
+            probe_interval,
+        }
+    }
+
+    #[tokio::test]
+    async fn recovers_to_primary_after_cooldown() {
+        let primary = Arc::new(MockProvider::fail_then_succeed(
+            "breaker-recover-primary",
+            1,
+        ));
+        let fb1 = Arc::new(MockProvider::always_succeed("breaker-recover-fallback"));
+        let provider = FallbackProvider::new(primary.clone(), vec![fb1])
+            .with_circuit_breaker(breaker(1, Duration::ZERO, Duration::ZERO));
+
+        let resp = provider.complete(mock_request()).await.unwrap();
+        assert_eq!(resp.id, "breaker-recover-fallback-response");
+        let swap = provider.take_swap_event().unwrap();
+        assert_eq!(swap.to_name, "breaker-recover-fallback");
+
+        // Cool-down elapsed — the next call probes the primary in the
+        // background and is still answered by the fallback
+        let resp = provider.complete(mock_request()).await.unwrap();
+        assert_eq!(resp.id, "breaker-recover-fallback-response");
+        wait_for_calls(&primary, 2).await;
+        assert!(provider.take_swap_event().is_none());
+
+        // The probe succeeded, so the call after it swaps back
+        let resp = provider.complete(mock_request()).await.unwrap();
+        assert_eq!(resp.id, "breaker-recover-primary-response");
+        let swap = provider.take_swap_event().unwrap();
+        assert_eq!(swap.from_name, "breaker-recover-fallback");
+        assert_eq!(swap.to_name, "breaker-recover-primary");
+        assert_eq!(swap.reason, "primary recovered");
+        assert!(provider.active_subprovider_name().is_none());
+    }
+
+    #[tokio::test]
+    async fn stays_on_fallback_during_cooldown() {
+        let primary = Arc::new(MockProvider::fail_then_succeed(
+            "breaker-cooldown-primary",
+            1,
+        ));
+        let fb1 = Arc::new(MockProvider::always_succeed("breaker-cooldown-fallback"));
+        let provider = FallbackProvider::new(primary.clone(), vec![fb1])
+            .with_circuit_breaker(breaker(1, Duration::from_secs(3600), Duration::ZERO));
+
+        provider.complete(mock_request()).await.unwrap();
+        let resp = provider.complete(mock_request()).await.unwrap();
+        assert_eq!(resp.id, "breaker-cooldown-fallback-response");
+        // Primary was only hit by the call that tripped the breaker
+        assert_eq!(primary.fail_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn probe_waits_for_interval_after_primary_failure() {
+        let primary = Arc::new(MockProvider::always_fail("breaker-interval-primary"));
+        let fb1 = Arc::new(MockProvider::always_succeed("breaker-interval-fallback"));
+        let provider = FallbackProvider::new(primary.clone(), vec![fb1])
+            .with_circuit_breaker(breaker(1, Duration::ZERO, Duration::from_secs(3600)));
+
+        provider.complete(mock_request()).await.unwrap();
+        provider.complete(mock_request()).await.unwrap();
+        assert_eq!(primary.fail_count.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            provider.active_subprovider_name().as_deref(),
+            Some("breaker-interval-fallback")
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_probe_stream_stays_on_fallback() {
+        let primary = Arc::new(MockProvider::always_fail("breaker-probe-primary"));
+        let fb1 = Arc::new(MockProvider::always_succeed("breaker-probe-fallback"));
+        let provider = FallbackProvider::new(primary.clone(), vec![fb1])
+            .with_circuit_breaker(breaker(1, Duration::ZERO, Duration::ZERO));
+
+        let _stream = provider.stream(mock_request()).await.unwrap();
+        provider.take_swap_event();
+        let _stream = provider.stream(mock_request()).await.unwrap();
+        wait_for_calls(&primary, 2).await;
+        let _stream = provider.stream(mock_request()).await.unwrap();
+        assert!(provider.take_swap_event().is_none());
+        assert_eq!(
+            provider.active_subprovider_name().as_deref(),
+            Some("breaker-probe-fallback")
+        );
+    }
+
+    /// Primary that fails real requests and answers health probes as told:
+    /// hang forever, or panic. Records every request's `max_tokens`.
+    struct ProbedPrimary {
+        name: &'static str,
+        hang_probes: bool,
+        requests: Mutex<Vec<Option<u32>>>,
+    }
+
+    #[async_trait]
+    impl Provider for ProbedPrimary {
+        async fn complete(
+            &self,
+            request: LLMRequest,
+        ) -> crate::brain::provider::error::Result<LLMResponse> {
+            self.requests.lock().unwrap().push(request.max_tokens);
+            if request.max_tokens != Some(1) {
+                return Err(ProviderError::RateLimitExceeded(self.name.to_string()));
+            }
+            if self.hang_probes {
+                std::future::pending::<()>().await;
+            }
+            panic!("probe blew up");
+        }
+
+        async fn stream(
+            &self,
+            _request: LLMRequest,
+        ) -> crate::brain::provider::error::Result<ProviderStream> {
+            unreachable!("probes use complete")
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn default_model(&self) -> &str {
+            "mock-model"
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            vec!["mock-model".into()]
+        }
+
+        fn context_window(&self, _model: &str) -> Option<u32> {
+            Some(4096)
+        }
+
+        fn calculate_cost(&self, _model: &str, _input: u32, _output: u32) -> f64 {
+            0.0
+        }
+    }
+
+    impl ProbedPrimary {
+        fn new(name: &'static str, hang_probes: bool) -> Self {
+            Self {
+                name,
+                hang_probes,
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+
+        async fn wait_for_requests(&self, n: usize) -> Vec<Option<u32>> {
+            for _ in 0..1000 {
+                let requests = self.requests.lock().unwrap().clone();
+                if requests.len() >= n {
+                    return requests;
+                }
+                tokio::task::yield_now().await;
+            }
+            panic!("primary never saw {n} requests");
+        }
+    }
+
+    #[tokio::test]
+    async fn probe_is_a_health_request_that_does_not_delay_the_call() {
+        let primary = Arc::new(ProbedPrimary::new("breaker-hang-primary", true));
+        let fb1 = Arc::new(MockProvider::always_succeed("breaker-hang-fallback"));
+        let provider = FallbackProvider::new(primary.clone(), vec![fb1])
+            .with_circuit_breaker(breaker(1, Duration::ZERO, Duration::ZERO));
+
+        let mut request = mock_request();
+        request.max_tokens = Some(4096);
+        provider.complete(request.clone()).await.unwrap();
+
+        // The primary never answers the probe; the call doesn't wait for it
+        let resp = tokio::time::timeout(Duration::from_secs(5), provider.complete(request))
+            .await
+            .expect("call waited on the probe")
+            .unwrap();
+        assert_eq!(resp.id, "breaker-hang-fallback-response");
+        assert_eq!(
+            primary.wait_for_requests(2).await,
+            vec![Some(4096), Some(1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn probe_slot_is_released_when_a_probe_dies() {
+        let primary = Arc::new(ProbedPrimary::new("breaker-panic-primary", false));
+        let fb1 = Arc::new(MockProvider::always_succeed("breaker-panic-fallback"));
+        let provider = FallbackProvider::new(primary.clone(), vec![fb1])
+            .with_circuit_breaker(breaker(1, Duration::ZERO, Duration::ZERO));
+
+        provider.complete(mock_request()).await.unwrap();
+        provider.complete(mock_request()).await.unwrap();
+        primary.wait_for_requests(2).await;
+        // Let the panicked probe task finish unwinding
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+
+        // The dead probe handed its slot back, so the next call probes again
+        provider.complete(mock_request()).await.unwrap();
+        assert_eq!(primary.wait_for_requests(3).await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn single_failure_below_threshold_does_not_stick() {
+        let primary = Arc::new(MockProvider::fail_then_succeed(
+            "breaker-threshold-primary",
+            1,
+        ));
+        let fb1 = Arc::new(MockProvider::always_succeed("breaker-threshold-fallback"));
+        let provider = FallbackProvider::new(primary, vec![fb1]).with_circuit_breaker(breaker(
+            2,
+            Duration::from_secs(3600),
+            Duration::ZERO,
+        ));
+
+        let resp = provider.complete(mock_request()).await.unwrap();
+        assert_eq!(resp.id, "breaker-threshold-fallback-response");
+        assert!(provider.take_swap_event().is_none());
+        let resp = provider.complete(mock_request()).await.unwrap();
+        assert_eq!(resp.id, "breaker-threshold-primary-response");
+    }
+
     #[tokio::test]
     async fn delegates_name_to_primary() {
         let primary = Arc::new(MockProvider::always_succeed("my-primary"));
@@ -465,6 +741,7 @@ mod factory_fallback {
                     enabled: false,
                     provider: Some("anthropic".into()),
                     providers: vec![],
+                    ..Default::default()
                 }),
                 ..Default::default()
             },
@@ -500,6 +777,7 @@ mod factory_fallback {
                     enabled: true,
                     provider: None,
                     providers: vec!["anthropic".into(), "openai".into()],
+                    ..Default::default()
                 }),
                 ..Default::default()
             },
@@ -0,0 +1,258 @@
+//! Tests for lifecycle hooks (`brain::hooks`).
+//!
+//! Hooks are real `sh -c` commands run against a temp directory: they get
+//! the event as JSON on stdin and answer with an exit code or a JSON
+//! decision on stdout.
+
+use crate::brain::hooks::{HookDef, HookEvent, HookOutcome, Hooks};
+use serde_json::{Value, json};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+fn hook(event: HookEvent, matcher: Option<&str>, command: &str) -> HookDef {
+    HookDef {
+        event,
+        matcher: matcher.map(str::to_string),
+        command: command.to_string(),
+        timeout_secs: 10,
+        enabled: true,
+    }
+}
+
+#[tokio::test]
+async fn pre_tool_use_denies_matching_tools_only() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(vec![hook(
+        HookEvent::PreToolUse,
+        Some("write_file|edit_file"),
+        "echo 'migrations/ is read-only' >&2; exit 2",
+    )]);
+    let input = json!({"path": "migrations/001.sql"});
+
+    let outcome = hooks
+        .pre_tool_use(Uuid::new_v4(), dir.path(), "write_file", &input)
+        .await;
+    assert_eq!(
+        outcome,
+        HookOutcome::Deny("migrations/ is read-only".to_string())
+    );
+
+    // The matcher is anchored: neither other tools nor prefixes match
+    for tool in ["read_file", "write_file_v2"] {
+        let outcome = hooks
+            .pre_tool_use(Uuid::new_v4(), dir.path(), tool, &input)
+            .await;
+        assert_eq!(outcome, HookOutcome::Allow, "{tool}");
+    }
+}
+
+#[tokio::test]
+async fn pre_tool_use_rewrites_chain_in_order() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(vec![
+        hook(
+            HookEvent::PreToolUse,
+            None,
+            r#"echo '{"tool_input": {"command": "ls -la"}}'"#,
+        ),
+        // Sees the first hook's rewrite on stdin and echoes it back amended
+        hook(
+            HookEvent::PreToolUse,
+            Some("bash"),
+            r#"grep -q '"ls -la"' && echo '{"tool_input": {"command": "ls -la /tmp"}}'"#,
+        ),
+    ]);
+
+    let outcome = hooks
+        .pre_tool_use(
+            Uuid::new_v4(),
+            dir.path(),
+            "bash",
+            &json!({"command": "ls"}),
+        )
+        .await;
+    assert_eq!(
+        outcome,
+        HookOutcome::Rewrite(json!({"command": "ls -la /tmp"}))
+    );
+}
+
+#[tokio::test]
+async fn hooks_receive_the_event_on_stdin() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(vec![hook(
+        HookEvent::PostToolUse,
+        Some("bash"),
+        "cat > audit.json",
+    )]);
+    let session = Uuid::new_v4();
+
+    let denied = hooks
+        .post_tool_use(
+            session,
+            dir.path(),
+            "bash",
+            &json!({"command": "whoami"}),
+            true,
+            "crab",
+        )
+        .await;
+    assert_eq!(denied, None);
+
+    let audit: Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("audit.json")).unwrap())
+            .unwrap();
+    assert_eq!(audit["event"], "PostToolUse");
+    assert_eq!(audit["session_id"], session.to_string());
+    assert_eq!(audit["tool_name"], "bash");
+    assert_eq!(audit["tool_input"]["command"], "whoami");
+    assert_eq!(audit["success"], true);
+    assert_eq!(audit["tool_output"], "crab");
+}
+
+#[tokio::test]
+async fn post_tool_use_can_reject_a_result() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(vec![hook(
+        HookEvent::PostToolUse,
+        None,
+        r#"echo '{"decision": "block", "reason": "run cargo fmt first"}'"#,
+    )]);
+
+    let denied = hooks
+        .post_tool_use(
+            Uuid::new_v4(),
+            dir.path(),
+            "edit_file",
+            &json!({}),
+            true,
+            "",
+        )
+        .await;
+    assert_eq!(denied.as_deref(), Some("run cargo fmt first"));
+}
+
+#[tokio::test]
+async fn user_prompt_submit_rewrites_or_denies() {
+    let dir = TempDir::new().unwrap();
+    let session = Uuid::new_v4();
+
+    let rewrite = Hooks::new(vec![hook(
+        HookEvent::UserPromptSubmit,
+        None,
+        r#"echo '{"prompt": "be brief: hello"}'"#,
+    )]);
+    assert_eq!(
+        rewrite
+            .user_prompt_submit(session, dir.path(), "hello".to_string())
+            .await,
+        Ok("be brief: hello".to_string())
+    );
+
+    let deny = Hooks::new(vec![hook(
+        HookEvent::UserPromptSubmit,
+        None,
+        r#"grep -q secret && echo '{"decision": "deny", "reason": "no secrets"}'; true"#,
+    )]);
+    assert_eq!(
+        deny.user_prompt_submit(session, dir.path(), "my secret key".to_string())
+            .await,
+        Err("no secrets".to_string())
+    );
+    assert_eq!(
+        deny.user_prompt_submit(session, dir.path(), "hello".to_string())
+            .await,
+        Ok("hello".to_string())
+    );
+}
+
+#[tokio::test]
+async fn failing_hooks_let_the_action_through() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(vec![
+        hook(HookEvent::PreToolUse, None, "exit 1"),
+        hook(HookEvent::PreToolUse, None, "echo not json"),
+        HookDef {
+            timeout_secs: 1,
+            ..hook(HookEvent::PreToolUse, None, "sleep 5")
+        },
+    ]);
+
+    let outcome = hooks
+        .pre_tool_use(Uuid::new_v4(), dir.path(), "grep", &json!({}))
+        .await;
+    assert_eq!(outcome, HookOutcome::Allow);
+}
+
+#[tokio::test]
+async fn timeout_covers_a_hook_that_never_reads_stdin() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(vec![HookDef {
+        timeout_secs: 1,
+        ..hook(HookEvent::PreToolUse, None, "sleep 30")
+    }]);
+    // Far more than a pipe buffer holds
+    let input = json!({"content": "x".repeat(1 << 20)});
+
+    let started = std::time::Instant::now();
+    let outcome = hooks
+        .pre_tool_use(Uuid::new_v4(), dir.path(), "write_file", &input)
+        .await;
+    assert_eq!(outcome, HookOutcome::Allow);
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+}
+
+#[tokio::test]
+async fn session_start_fires_once_per_session_and_stop_gets_the_reply() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(vec![
+        hook(HookEvent::SessionStart, None, "echo start >> events.log"),
+        hook(
+            HookEvent::Stop,
+            None,
+            "cat >> events.log; echo >> events.log",
+        ),
+    ]);
+    let session = Uuid::new_v4();
+
+    hooks.session_start(session, dir.path()).await;
+    hooks.session_start(session, dir.path()).await;
+    hooks.stop(session, dir.path(), "all done").await;
+
+    let log = std::fs::read_to_string(dir.path().join("events.log")).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "start");
+    let stop: Value = serde_json::from_str(lines[1]).unwrap();
+    assert_eq!(stop["event"], "Stop");
+    assert_eq!(stop["response"], "all done");
+}
+
+#[test]
+fn hooks_toml_skips_disabled_and_invalid_hooks() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("hooks.toml");
+    std::fs::write(
+        &path,
+        r#"
+        [[hooks]]
+        event = "PostToolUse"
+        matcher = "edit_file"
+        command = "cargo fmt"
+
+        [[hooks]]
+        event = "PreToolUse"
+        command = "audit.sh"
+        enabled = false
+
+        [[hooks]]
+        event = "PreToolUse"
+        matcher = "bash("
+        command = "audit.sh"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(Hooks::load(&path).len(), 1);
+    assert!(Hooks::load(&dir.path().join("missing.toml")).is_empty());
+}
@@ -0,0 +1,280 @@
+//! Tests for API key rotation (`KeyPoolProvider`).
+//!
+//! Each key is a scripted provider that either answers or fails with a
+//! key-level (401/429) or request-level error. The pool must spread
+//! requests, skip quarantined keys, retry on the next key and keep
+//! quarantines in `config::health` across restarts.
+
+use crate::brain::provider::key_pool::{AUTH_QUARANTINE, RATE_LIMIT_QUARANTINE};
+use crate::brain::provider::{
+    ContentBlock, KeyPoolProvider, LLMRequest, LLMResponse, Message, Provider, ProviderError,
+    ProviderStream, Result, StopReason, TokenUsage,
+};
+use crate::config::KeyRotation;
+use crate::config::health;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+
+const OK: u8 = 200;
+
+/// One API key: answers with its index, or fails with the scripted status.
+struct KeyMock {
+    pool: &'static str,
+    index: usize,
+    status: AtomicU8,
+    calls: Arc<Mutex<Vec<usize>>>,
+}
+
+impl KeyMock {
+    fn reply(&self) -> Result<LLMResponse> {
+        self.calls.lock().unwrap().push(self.index);
+        match self.status.load(Ordering::SeqCst) {
+            OK => Ok(LLMResponse {
+                id: format!("resp-{}", self.index),
+                model: "mock-1".to_string(),
+                content: vec![ContentBlock::Text {
+                    text: format!("key {}", self.index),
+                }],
+                stop_reason: Some(StopReason::EndTurn),
+                usage: TokenUsage::default(),
+            }),
+            29 => Err(ProviderError::RateLimitExceeded("slow down".into())),
+            41 => Err(ProviderError::ApiError {
+                status: 401,
+                message: "invalid x-api-key".into(),
+                error_type: Some("authentication_error".into()),
+            }),
+            _ => Err(ProviderError::ApiError {
+                status: 500,
+                message: "overloaded".into(),
+                error_type: None,
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for KeyMock {
+    async fn complete(&self, _request: LLMRequest) -> Result<LLMResponse> {
+        self.reply()
+    }
+
+    async fn stream(&self, _request: LLMRequest) -> Result<ProviderStream> {
+        self.reply()?;
+        Ok(Box::pin(futures::stream::empty()))
+    }
+
+    fn name(&self) -> &str {
+        self.pool
+    }
+
+    fn default_model(&self) -> &str {
+        "mock-1"
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        vec!["mock-1".to_string()]
+    }
+
+    fn context_window(&self, _model: &str) -> Option<u32> {
+        Some(8_000)
+    }
+
+    fn calculate_cost(&self, _model: &str, _input: u32, _output: u32) -> f64 {
+        0.0
+    }
+}
+
+struct Keys {
+    mocks: Vec<Arc<KeyMock>>,
+    calls: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Keys {
+    /// `n` healthy keys recorded under `pool`, with any stale health wiped.
+    fn new(pool: &'static str, n: usize) -> Self {
+        health::forget_key_pool(pool);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mocks = (0..n)
+            .map(|index| {
+                Arc::new(KeyMock {
+                    pool,
+                    index,
+                    status: AtomicU8::new(OK),
+                    calls: calls.clone(),
+                })
+            })
+            .collect();
+        Self { mocks, calls }
+    }
+
+    fn fail(&self, index: usize, status: u8) {
+        self.mocks[index].status.store(status, Ordering::SeqCst);
+    }
+
+    fn pool(&self, rotation: KeyRotation) -> KeyPoolProvider {
+        let providers = self
+            .mocks
+            .iter()
+            .map(|m| m.clone() as Arc<dyn Provider>)
+            .collect();
+        KeyPoolProvider::new(providers, rotation)
+    }
+
+    fn take_calls(&self) -> Vec<usize> {
+        std::mem::take(&mut *self.calls.lock().unwrap())
+    }
+}
+
+fn request() -> LLMRequest {
+    LLMRequest::new("mock-1", vec![Message::user("hi")])
+}
+
+fn text(response: &LLMResponse) -> &str {
+    match &response.content[0] {
+        ContentBlock::Text { text } => text,
+        _ => "",
+    }
+}
+
+#[tokio::test]
+async fn round_robin_spreads_requests() {
+    let keys = Keys::new("keypool-round-robin", 3);
+    let pool = keys.pool(KeyRotation::RoundRobin);
+
+    for _ in 0..6 {
+        pool.complete(request()).await.unwrap();
+    }
+
+    assert_eq!(keys.take_calls(), vec![0, 1, 2, 0, 1, 2]);
+    assert_eq!(pool.key_count(), 3);
+}
+
+#[tokio::test]
+async fn rate_limited_key_is_quarantined_and_request_retried() {
+    let keys = Keys::new("keypool-429", 3);
+    keys.fail(0, 29);
+    let pool = keys.pool(KeyRotation::RoundRobin);
+
+    let response = pool.complete(request()).await.unwrap();
+    assert_eq!(text(&response), "key 1");
+    assert_eq!(keys.take_calls(), vec![0, 1]);
+
+    // Key 0 sits out; the rotation continues over the other two
+    for _ in 0..4 {
+        pool.complete(request()).await.unwrap();
+    }
+    assert!(!keys.take_calls().contains(&0));
+
+    let record = health::get_key_pool("keypool-429").unwrap();
+    let now = health::now_epoch();
+    assert_eq!(record.keys[0].last_status, Some(429));
+    assert!(record.keys[0].quarantine_left(now).unwrap() <= RATE_LIMIT_QUARANTINE.as_secs());
+    assert!(record.keys[1].quarantine_left(now).is_none());
+}
+
+#[tokio::test]
+async fn unauthorized_key_sits_out_longer() {
+    let keys = Keys::new("keypool-401", 2);
+    keys.fail(1, 41);
+    let pool = keys.pool(KeyRotation::LeastLimited);
+
+    // Least-limited stays on key 0 while it's healthy
+    pool.complete(request()).await.unwrap();
+    pool.complete(request()).await.unwrap();
+    assert_eq!(keys.take_calls(), vec![0, 0]);
+    assert_eq!(pool.active_key(), 0);
+
+    keys.fail(0, 29);
+    keys.fail(1, 41);
+    let err = pool.complete(request()).await.unwrap_err();
+    assert!(matches!(err, ProviderError::ApiError { status: 401, .. }));
+
+    let record = health::get_key_pool("keypool-401").unwrap();
+    let left = record.keys[1].quarantine_left(health::now_epoch()).unwrap();
+    assert!(left > RATE_LIMIT_QUARANTINE.as_secs() && left <= AUTH_QUARANTINE.as_secs());
+}
+
+#[tokio::test]
+async fn least_limited_moves_to_the_key_limited_longest_ago() {
+    let keys = Keys::new("keypool-least-limited", 3);
+    keys.fail(0, 29);
+    let pool = keys.pool(KeyRotation::LeastLimited);
+
+    pool.complete(request()).await.unwrap();
+    assert_eq!(keys.take_calls(), vec![0, 1]);
+    // Key 1 was never limited, so it stays in use
+    pool.complete(request()).await.unwrap();
+    assert_eq!(keys.take_calls(), vec![1]);
+    assert_eq!(pool.active_key(), 1);
+    assert_eq!(
+        health::get_key_pool("keypool-least-limited")
+            .unwrap()
+            .active,
+        1
+    );
+}
+
+#[tokio::test]
+async fn other_errors_do_not_rotate() {
+    let keys = Keys::new("keypool-500", 2);
+    keys.fail(0, 50);
+    let pool = keys.pool(KeyRotation::LeastLimited);
+
+    let err = pool.stream(request()).await.err().unwrap();
+    assert!(matches!(err, ProviderError::ApiError { status: 500, .. }));
+    assert_eq!(keys.take_calls(), vec![0]);
+    let record = health::get_key_pool("keypool-500").unwrap();
+    assert!(record.keys.iter().all(|k| k.quarantined_until.is_none()));
+}
+
+#[tokio::test]
+async fn all_keys_quarantined_tries_the_first_released() {
+    let keys = Keys::new("keypool-exhausted", 2);
+    keys.fail(0, 29);
+    keys.fail(1, 41);
+    let pool = keys.pool(KeyRotation::RoundRobin);
+
+    assert!(pool.complete(request()).await.is_err());
+    keys.take_calls();
+
+    // Both quarantined: only key 0 (429, released first) gets a try
+    keys.fail(0, OK);
+    let response = pool.complete(request()).await.unwrap();
+    assert_eq!(text(&response), "key 0");
+    assert_eq!(keys.take_calls(), vec![0]);
+}
+
+#[tokio::test]
+async fn quarantine_survives_a_restart() {
+    let keys = Keys::new("keypool-restart", 2);
+    keys.fail(0, 29);
+    keys.pool(KeyRotation::RoundRobin)
+        .complete(request())
+        .await
+        .unwrap();
+    keys.take_calls();
+
+    keys.fail(0, OK);
+    let restarted = keys.pool(KeyRotation::RoundRobin);
+    for _ in 0..3 {
+        restarted.complete(request()).await.unwrap();
+    }
+    assert_eq!(keys.take_calls(), vec![1, 1, 1]);
+}
+
+#[test]
+fn keys_toml_accepts_a_key_list() {
+    let cfg: crate::config::ProviderConfig = toml::from_str(
+        r#"
+        api_key = "sk-a"
+        api_keys = ["sk-b", "", "sk-a", "sk-c"]
+        key_rotation = "least_limited"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(cfg.all_api_keys(), vec!["sk-a", "sk-b", "sk-c"]);
+    assert_eq!(cfg.key_rotation, Some(KeyRotation::LeastLimited));
+}
@@ -0,0 +1,134 @@
+//! End-to-end tests for `LocalProvider` with a real GGUF model.
+//!
+//! Needs a small chat model on disk, so they only run when
+//! `OPENCRABS_TEST_GGUF` points at one (e.g. a Qwen 0.5B Q4 build in CPU-only
+//! CI); otherwise each test returns early. The backend-sharing test needs no
+//! model.
+
+use crate::brain::provider::local::LocalProvider;
+use crate::brain::provider::{
+    ContentBlock, ContentDelta, LLMRequest, Message, Provider, StopReason, StreamEvent, Tool,
+};
+use futures::StreamExt;
+use llama_cpp_2::LlamaCppError;
+use llama_cpp_2::llama_backend::LlamaBackend;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+fn test_model() -> Option<LocalProvider> {
+    let path = PathBuf::from(std::env::var("OPENCRABS_TEST_GGUF").ok()?);
+    Some(
+        LocalProvider::load(&path, 0)
+            .expect("OPENCRABS_TEST_GGUF should be a loadable GGUF")
+            .with_context_window(4096),
+    )
+}
+
+/// llama.cpp's backend can be claimed once per process, and qmd's embedding
+/// engine claims it itself — whichever of the two starts second must work.
+#[test]
+fn local_provider_and_embedding_engine_share_one_process() {
+    let missing = Path::new("/nonexistent/opencrabs-test.gguf");
+    let load_error = || match LocalProvider::load(missing, 0) {
+        Err(e) => e.to_string(),
+        Ok(_) => panic!("loaded a missing model"),
+    };
+
+    // Embedding engine first: it holds the claim for the life of the process
+    let embedder = LlamaBackend::init().expect("nothing else holds the llama backend");
+    let err = load_error();
+    assert!(err.contains("Failed to load"), "{err}");
+    drop(embedder);
+
+    // Local provider first: the embedding engine can still claim the backend
+    let err = load_error();
+    assert!(err.contains("Failed to load"), "{err}");
+    let err = qmd::EmbeddingEngine::new(missing).err().unwrap();
+    assert!(
+        err.downcast_ref::<LlamaCppError>().is_none(),
+        "embedding engine lost the backend: {err:#}"
+    );
+}
+
+#[tokio::test]
+async fn streams_text_and_reports_usage() {
+    let Some(provider) = test_model() else {
+        return;
+    };
+    assert_eq!(
+        provider.context_window(provider.default_model()),
+        Some(4096)
+    );
+
+    let mut request = LLMRequest::new(
+        provider.default_model(),
+        vec![Message::user("Say hello in one word.")],
+    )
+    .with_streaming();
+    request.max_tokens = Some(16);
+    request.temperature = Some(0.0);
+
+    let mut stream = provider.stream(request).await.unwrap();
+    let mut text = String::new();
+    let mut output_tokens = 0;
+    let mut saw_stop = false;
+    while let Some(event) = stream.next().await {
+        match event.unwrap() {
+            StreamEvent::ContentBlockDelta {
+                delta: ContentDelta::TextDelta { text: t },
+                ..
+            } => text.push_str(&t),
+            StreamEvent::MessageDelta { usage, .. } => {
+                assert!(usage.input_tokens > 0);
+                output_tokens = usage.output_tokens;
+            }
+            StreamEvent::MessageStop => saw_stop = true,
+            _ => {}
+        }
+    }
+
+    assert!(!text.trim().is_empty());
+    assert!(output_tokens > 0 && output_tokens <= 16);
+    assert!(saw_stop);
+}
+
+#[tokio::test]
+async fn grammar_forces_known_tool_call() {
+    let Some(provider) = test_model() else {
+        return;
+    };
+
+    let mut request = LLMRequest::new(
+        provider.default_model(),
+        vec![Message::user(
+            "Read the file notes.txt. Respond with the tool call only.",
+        )],
+    );
+    request.tools = Some(vec![Tool {
+        name: "read_file".to_string(),
+        description: "Read a file from disk".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {"path": {"type": "string"}},
+            "required": ["path"]
+        }),
+    }]);
+    request.temperature = Some(0.0);
+    request.max_tokens = Some(64);
+
+    let response = provider.complete(request).await.unwrap();
+    // Small models may still answer in prose; when they call a tool, the
+    // grammar guarantees it is a known one with object arguments.
+    if response.stop_reason == Some(StopReason::ToolUse) {
+        let call = response
+            .content
+            .iter()
+            .find_map(|b| match b {
+                ContentBlock::ToolUse { name, input, .. } => Some((name, input)),
+                _ => None,
+            })
+            .expect("ToolUse stop reason without a tool call");
+        assert_eq!(call.0, "read_file");
+        assert!(call.1.is_object());
+    }
+}
@@ -0,0 +1,89 @@
+//! Tests for editing an earlier user message (`/edit`).
+//!
+//! Re-sending an edited message first moves the conversation from that
+//! message on into a "(before edit)" branch, so nothing is lost.
+
+use crate::db::Database;
+use crate::services::{CheckpointService, MessageService, ServiceContext, SessionService};
+use uuid::Uuid;
+
+async fn contents(messages: &MessageService, session_id: Uuid) -> Vec<String> {
+    messages
+        .list_messages_for_session(session_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.content)
+        .collect()
+}
+
+#[tokio::test]
+async fn editing_branches_off_the_later_messages() {
+    let db = Database::connect_in_memory().await.unwrap();
+    db.run_migrations().await.unwrap();
+    let context = ServiceContext::new(db.pool().clone());
+    let sessions = SessionService::new(context.clone());
+    let messages = MessageService::new(context.clone());
+    let checkpoints = CheckpointService::new(context);
+
+    let session = sessions
+        .create_session(Some("Typos".to_string()))
+        .await
+        .unwrap();
+    let mut created = Vec::new();
+    for (role, content) in [
+        ("user", "hello"),
+        ("assistant", "hi"),
+        ("user", "fix teh bug"),
+        ("assistant", "which bug?"),
+        ("user", "never mind"),
+    ] {
+        created.push(
+            messages
+                .create_message(session.id, role.to_string(), content.to_string())
+                .await
+                .unwrap(),
+        );
+    }
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join("a.txt");
+    checkpoints
+        .snapshot(session.id, "write_file", &file)
+        .await
+        .unwrap();
+    std::fs::write(&file, "written in a dropped turn").unwrap();
+
+    let branch = sessions
+        .branch_before_edit(session.id, &created[2])
+        .await
+        .unwrap();
+
+    assert_eq!(branch.title.as_deref(), Some("Typos (before edit)"));
+    assert_eq!(branch.parent_session_id, Some(session.id));
+    assert_eq!(
+        contents(&messages, branch.id).await,
+        vec!["hello", "hi", "fix teh bug", "which bug?", "never mind"],
+        "the discarded tail stays recoverable"
+    );
+    assert_eq!(contents(&messages, session.id).await, vec!["hello", "hi"]);
+    assert!(
+        checkpoints.undo(session.id).await.unwrap().is_none(),
+        "checkpoints of the dropped turns leave the session"
+    );
+    let report = checkpoints.undo(branch.id).await.unwrap().unwrap();
+    assert_eq!(
+        report.removed,
+        vec![file],
+        "...and can be undone in the branch"
+    );
+
+    // The edited message continues the trimmed session
+    messages
+        .create_message(session.id, "user".to_string(), "fix the bug".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        contents(&messages, session.id).await,
+        vec!["hello", "hi", "fix the bug"]
+    );
+}
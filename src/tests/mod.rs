@@ -1,4 +1,6 @@
 pub mod altgr_input_test;
+pub mod batch_provider_test;
+pub mod bedrock_provider_test;
 pub mod brain_templates_test;
 pub mod browser_default_linux_test;
 pub mod browser_default_test;
@@ -12,8 +14,11 @@ pub mod browser_profile_wait_test;
 pub mod browser_screenshot_surface_test;
 pub mod browser_session_test;
 pub mod browser_stealth_test;
+pub mod budget_test;
 pub mod candle_whisper_test;
+pub mod cassette_test;
 pub mod channel_search_test;
+pub mod checkpoint_test;
 pub mod claude_cli_model_test;
 pub mod cli_arg_too_long_test;
 pub mod cli_test;
@@ -28,6 +33,7 @@ pub mod evolve_test;
 pub mod exa_search_test;
 pub mod gemini_fetch_test;
 pub mod github_provider_test;
+pub mod hooks_test;
 pub mod html_comment_strip_test;
 pub mod http_request_test;
 pub mod openai_provider_test;
@@ -38,27 +44,41 @@ pub mod fallback_vision_test;
 pub mod file_extract_test;
 pub mod image_util_test;
 //pub mod integration_test;
+pub mod key_pool_test;
 pub mod kimi_reasoning_test;
+pub mod local_gguf_provider_test;
 pub mod local_provider_gate_test;
+pub mod message_edit_test;
 pub mod nonstream_compat_test;
+pub mod ollama_provider_test;
 pub mod onboarding_brain_test;
 pub mod onboarding_field_nav_test;
 pub mod onboarding_keys_test;
 pub mod onboarding_navigation_test;
 pub mod onboarding_types_test;
+pub mod openai_responses_test;
+pub mod permissions_test;
 pub mod plan_document_test;
 pub mod post_evolve_test;
+pub mod project_brain_test;
 pub mod provider_error_proxy_test;
 pub mod provider_sync_test;
 pub mod qr_render_test;
 pub mod queued_message_test;
 pub mod qwen_tool_extractor_test;
 pub mod reasoning_lines_test;
+pub mod response_cache_test;
 //pub mod plan_mode_integration_test;
+pub mod session_fork_test;
 pub mod session_working_dir_test;
+pub mod skills_test;
 pub mod slack_fmt_test;
 pub mod stream_loop_test;
+pub mod structured_output_test;
 pub mod system_continuation_test;
+pub mod tool_choice_test;
+pub mod tool_output_pruning_test;
+pub mod vertex_provider_test;
 pub mod voice_openai_compatible_test;
 pub mod voice_voicebox_test;
 //pub mod streaming_test;
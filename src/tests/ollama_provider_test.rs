@@ -0,0 +1,263 @@
+//! Tests for `OllamaProvider` against a local mock of the Ollama server.
+//!
+//! Covers the native endpoints the OpenAI shim can't reach: per-request
+//! `num_ctx` / `keep_alive`, `/api/tags` for installed models, `/api/show`
+//! for real context windows, and `/api/pull` progress.
+
+use crate::brain::provider::ollama::{OllamaProvider, PullProgress};
+use crate::brain::provider::{
+    ContentBlock, ContentDelta, LLMRequest, Message, Provider, ProviderError, StopReason,
+    StreamEvent,
+};
+use futures::StreamExt;
+use mockito::Matcher;
+use serde_json::json;
+
+const MODEL: &str = "qwen3:8b";
+
+fn show_body() -> String {
+    json!({
+        "parameters": "temperature 0.6",
+        "model_info": {"general.architecture": "qwen3", "qwen3.context_length": 40960}
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn complete_sends_num_ctx_and_keep_alive() {
+    let mut server = mockito::Server::new_async().await;
+    let _show = server
+        .mock("POST", "/api/show")
+        .with_status(200)
+        .with_body(show_body())
+        .create_async()
+        .await;
+    let chat = server
+        .mock("POST", "/api/chat")
+        .match_body(Matcher::PartialJson(json!({
+            "model": MODEL,
+            "stream": false,
+            "options": {"num_ctx": 32768},
+            "keep_alive": "30m",
+            "messages": [{"role": "user", "content": "hello"}]
+        })))
+        .with_status(200)
+        .with_body(
+            json!({
+                "model": MODEL,
+                "message": {"role": "assistant", "content": "hi there"},
+                "done": true,
+                "done_reason": "stop",
+                "prompt_eval_count": 11,
+                "eval_count": 3
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+
+    let provider = OllamaProvider::new(server.url())
+        .with_model(MODEL)
+        .with_context_window(32768)
+        .with_keep_alive("30m");
+    let response = provider
+        .complete(LLMRequest::new(MODEL, vec![Message::user("hello")]))
+        .await
+        .unwrap();
+
+    chat.assert_async().await;
+    assert!(matches!(
+        &response.content[0],
+        ContentBlock::Text { text } if text == "hi there"
+    ));
+    assert_eq!(response.usage.input_tokens, 11);
+    assert_eq!(response.usage.output_tokens, 3);
+    // num_ctx 32768 is within the trained 40960, so it is what's reported
+    assert_eq!(provider.context_window(MODEL), Some(32768));
+}
+
+#[tokio::test]
+async fn context_window_comes_from_api_show() {
+    let mut server = mockito::Server::new_async().await;
+    let show = server
+        .mock("POST", "/api/show")
+        .match_body(Matcher::PartialJson(json!({"model": MODEL})))
+        .with_status(200)
+        .with_body(show_body())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let provider = OllamaProvider::new(server.url()).with_context_window(131072);
+    let info = provider.model_info(MODEL).await.unwrap();
+    assert_eq!(info.trained_context, Some(40960));
+    // Cached — the second lookup doesn't hit the server
+    provider.model_info(MODEL).await.unwrap();
+
+    show.assert_async().await;
+    assert_eq!(provider.context_window(MODEL), Some(40960));
+}
+
+#[tokio::test]
+async fn fetch_models_lists_installed_tags() {
+    let mut server = mockito::Server::new_async().await;
+    let _tags = server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_body(
+            json!({"models": [
+                {"name": "llama3.2:latest", "model": "llama3.2:latest", "size": 2019393189},
+                {"name": "qwen3:8b", "model": "qwen3:8b", "size": 5225388164u64}
+            ]})
+            .to_string(),
+        )
+        .create_async()
+        .await;
+
+    let provider = OllamaProvider::new(format!("{}/v1", server.url()))
+        .with_models(vec!["gpt-oss:20b".to_string()]);
+    assert_eq!(
+        provider.fetch_models().await,
+        vec!["llama3.2:latest".to_string(), "qwen3:8b".to_string()]
+    );
+    assert!(provider.is_installed("llama3.2").await.unwrap());
+    assert!(!provider.is_installed("gpt-oss:20b").await.unwrap());
+}
+
+#[tokio::test]
+async fn pull_reports_progress_until_success() {
+    let body = [
+        json!({"status": "pulling manifest"}),
+        json!({"status": "pulling 3a1b", "digest": "sha256:3a1b", "total": 1000, "completed": 250}),
+        json!({"status": "pulling 3a1b", "digest": "sha256:3a1b", "total": 1000, "completed": 1000}),
+        json!({"status": "verifying sha256 digest"}),
+        json!({"status": "success"}),
+    ]
+    .iter()
+    .map(|l| format!("{}\n", l))
+    .collect::<String>();
+
+    let mut server = mockito::Server::new_async().await;
+    let pull = server
+        .mock("POST", "/api/pull")
+        .match_body(Matcher::PartialJson(json!({"model": "gpt-oss:20b"})))
+        .with_status(200)
+        .with_header("content-type", "application/x-ndjson")
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let mut seen: Vec<PullProgress> = Vec::new();
+    OllamaProvider::new(server.url())
+        .pull_model("gpt-oss:20b", |p| seen.push(p))
+        .await
+        .unwrap();
+
+    pull.assert_async().await;
+    assert_eq!(seen.len(), 5);
+    assert_eq!(seen[1].fraction(), Some(0.25));
+    assert_eq!(seen[0].fraction(), None);
+    assert_eq!(seen[4].status, "success");
+}
+
+#[tokio::test]
+async fn pull_error_line_fails() {
+    let mut server = mockito::Server::new_async().await;
+    let _pull = server
+        .mock("POST", "/api/pull")
+        .with_status(200)
+        .with_body("{\"status\":\"pulling manifest\"}\n{\"error\":\"pull model manifest: file does not exist\"}\n")
+        .create_async()
+        .await;
+
+    let err = OllamaProvider::new(server.url())
+        .pull_model("nope:1b", |_| {})
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("file does not exist"));
+}
+
+#[tokio::test]
+async fn missing_model_maps_to_model_not_found() {
+    let mut server = mockito::Server::new_async().await;
+    let _show = server
+        .mock("POST", "/api/show")
+        .with_status(404)
+        .with_body(r#"{"error":"model 'ghost' not found"}"#)
+        .create_async()
+        .await;
+    let _chat = server
+        .mock("POST", "/api/chat")
+        .with_status(404)
+        .with_body(r#"{"error":"model 'ghost' not found"}"#)
+        .create_async()
+        .await;
+
+    let err = OllamaProvider::new(server.url())
+        .complete(LLMRequest::new("ghost", vec![Message::user("hi")]))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ProviderError::ModelNotFound(msg) if msg.contains("ghost")));
+}
+
+#[tokio::test]
+async fn stream_decodes_ndjson_with_tool_call() {
+    let body = [
+        json!({"model": MODEL, "message": {"role": "assistant", "content": "", "thinking": "need files"}, "done": false}),
+        json!({"model": MODEL, "message": {"role": "assistant", "content": "Listing"}, "done": false}),
+        json!({"model": MODEL, "message": {"role": "assistant", "content": "", "tool_calls": [
+            {"function": {"name": "ls", "arguments": {"path": "src"}}}
+        ]}, "done": false}),
+        json!({"model": MODEL, "message": {"role": "assistant", "content": ""}, "done": true,
+               "done_reason": "stop", "prompt_eval_count": 20, "eval_count": 7}),
+    ]
+    .iter()
+    .map(|l| format!("{}\n", l))
+    .collect::<String>();
+
+    let mut server = mockito::Server::new_async().await;
+    let _show = server
+        .mock("POST", "/api/show")
+        .with_status(200)
+        .with_body(show_body())
+        .create_async()
+        .await;
+    let _chat = server
+        .mock("POST", "/api/chat")
+        .match_body(Matcher::PartialJson(json!({"stream": true})))
+        .with_status(200)
+        .with_header("content-type", "application/x-ndjson")
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let mut stream = OllamaProvider::new(server.url())
+        .stream(LLMRequest::new(MODEL, vec![Message::user("ls src")]).with_streaming())
+        .await
+        .unwrap();
+
+    let mut text = String::new();
+    let mut reasoning = String::new();
+    let mut tool = None;
+    let mut stop_reason = None;
+    while let Some(event) = stream.next().await {
+        match event.unwrap() {
+            StreamEvent::ContentBlockDelta { delta, .. } => match delta {
+                ContentDelta::TextDelta { text: t } => text.push_str(&t),
+                ContentDelta::ReasoningDelta { text: t } => reasoning.push_str(&t),
+                ContentDelta::InputJsonDelta { partial_json } => tool = Some(partial_json),
+                _ => {}
+            },
+            StreamEvent::MessageDelta { delta, usage } => {
+                stop_reason = delta.stop_reason;
+                assert_eq!(usage.output_tokens, 7);
+            }
+            _ => {}
+        }
+    }
+
+    assert_eq!(text, "Listing");
+    assert_eq!(reasoning, "need files");
+    assert_eq!(tool.as_deref(), Some(r#"{"path":"src"}"#));
+    assert!(matches!(stop_reason, Some(StopReason::ToolUse)));
+}
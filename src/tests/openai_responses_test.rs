@@ -0,0 +1,262 @@
+//! Tests for `OpenAIProvider` in Responses API mode (`endpoint_type = "responses"`).
+//!
+//! Covers the `/v1/responses` request shape, `previous_response_id` chaining
+//! across turns of one session, recovery when the stored response is gone,
+//! and translation of the streaming event grammar.
+
+use crate::brain::provider::{
+    ContentBlock, ContentDelta, LLMRequest, Message, OpenAIProvider, Provider, Role, StopReason,
+    StreamEvent,
+};
+use futures::StreamExt;
+use mockito::Matcher;
+use serde_json::json;
+use uuid::Uuid;
+
+fn provider(server: &mockito::Server) -> OpenAIProvider {
+    OpenAIProvider::with_base_url(
+        "sk-test".to_string(),
+        format!("{}/v1/chat/completions", server.url()),
+    )
+    .with_responses_api()
+}
+
+fn tool_call_response() -> String {
+    json!({
+        "id": "resp_1",
+        "model": "gpt-5",
+        "status": "completed",
+        "output": [
+            {"type": "reasoning", "id": "rs_1", "summary": [{"type": "summary_text", "text": "List the dir"}]},
+            {"type": "function_call", "id": "fc_1", "call_id": "call_1", "name": "ls", "arguments": "{\"path\":\".\"}"}
+        ],
+        "usage": {"input_tokens": 50, "output_tokens": 12}
+    })
+    .to_string()
+}
+
+fn final_response(id: &str) -> String {
+    json!({
+        "id": id,
+        "model": "gpt-5",
+        "status": "completed",
+        "output": [{"type": "message", "role": "assistant", "content": [{"type": "output_text", "text": "Two files."}]}],
+        "usage": {"input_tokens": 80, "output_tokens": 4}
+    })
+    .to_string()
+}
+
+/// Conversation after the tool call from `tool_call_response` ran
+fn history_with_tool_result() -> Vec<Message> {
+    vec![
+        Message::user("list files"),
+        Message {
+            role: Role::Assistant,
+            content: vec![ContentBlock::ToolUse {
+                id: "call_1".into(),
+                name: "ls".into(),
+                input: json!({"path": "."}),
+            }],
+        },
+        Message {
+            role: Role::User,
+            content: vec![ContentBlock::ToolResult {
+                tool_use_id: "call_1".into(),
+                content: "a.rs b.rs".into(),
+                is_error: None,
+            }],
+        },
+    ]
+}
+
+fn request(messages: Vec<Message>, session: Uuid) -> LLMRequest {
+    let mut request = LLMRequest::new("gpt-5", messages).with_system("be brief");
+    request.session_id = Some(session);
+    request
+}
+
+#[tokio::test]
+async fn complete_posts_to_responses_endpoint() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/responses")
+        .match_body(Matcher::PartialJson(json!({
+            "model": "gpt-5",
+            "instructions": "be brief",
+            "stream": false,
+            "reasoning": {"summary": "auto"},
+            "input": [{"type": "message", "role": "user", "content": [{"type": "input_text", "text": "list files"}]}]
+        })))
+        .with_status(200)
+        .with_body(tool_call_response())
+        .create_async()
+        .await;
+
+    let response = provider(&server)
+        .complete(request(vec![Message::user("list files")], Uuid::new_v4()))
+        .await
+        .unwrap();
+
+    mock.assert_async().await;
+    assert!(matches!(
+        &response.content[0],
+        ContentBlock::Thinking { thinking, .. } if thinking == "List the dir"
+    ));
+    assert!(matches!(
+        &response.content[1],
+        ContentBlock::ToolUse { id, name, input } if id == "call_1" && name == "ls" && input["path"] == "."
+    ));
+    assert_eq!(response.stop_reason, Some(StopReason::ToolUse));
+    assert_eq!(response.usage.input_tokens, 50);
+}
+
+#[tokio::test]
+async fn follow_up_turn_chains_on_previous_response() {
+    let mut server = mockito::Server::new_async().await;
+    let first = server
+        .mock("POST", "/v1/responses")
+        .match_body(Matcher::Regex("list files".into()))
+        .with_status(200)
+        .with_body(tool_call_response())
+        .create_async()
+        .await;
+    let second = server
+        .mock("POST", "/v1/responses")
+        .match_body(Matcher::PartialJson(json!({
+            "previous_response_id": "resp_1",
+            "input": [{"type": "function_call_output", "call_id": "call_1", "output": "a.rs b.rs"}]
+        })))
+        .with_status(200)
+        .with_body(final_response("resp_2"))
+        .create_async()
+        .await;
+
+    let provider = provider(&server);
+    let session = Uuid::new_v4();
+    provider
+        .complete(request(vec![Message::user("list files")], session))
+        .await
+        .unwrap();
+    let response = provider
+        .complete(request(history_with_tool_result(), session))
+        .await
+        .unwrap();
+
+    first.assert_async().await;
+    second.assert_async().await;
+    assert!(matches!(&response.content[0], ContentBlock::Text { text } if text == "Two files."));
+}
+
+#[tokio::test]
+async fn expired_previous_response_resends_full_history() {
+    let mut server = mockito::Server::new_async().await;
+    let _first = server
+        .mock("POST", "/v1/responses")
+        .match_body(Matcher::Regex("^\\{(?s:.)*list files".into()))
+        .with_status(200)
+        .with_body(tool_call_response())
+        .expect(2)
+        .create_async()
+        .await;
+    let stale = server
+        .mock("POST", "/v1/responses")
+        .match_body(Matcher::Regex("previous_response_id".into()))
+        .with_status(400)
+        .with_body(
+            json!({"error": {
+                "message": "Previous response with id 'resp_1' not found.",
+                "type": "invalid_request_error",
+                "code": "previous_response_not_found"
+            }})
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let provider = provider(&server);
+    let session = Uuid::new_v4();
+    provider
+        .complete(request(vec![Message::user("list files")], session))
+        .await
+        .unwrap();
+    // The retry carries the whole conversation (incl. "list files") and no chain id
+    provider
+        .complete(request(history_with_tool_result(), session))
+        .await
+        .unwrap();
+
+    stale.assert_async().await;
+}
+
+#[tokio::test]
+async fn stream_translates_response_events() {
+    let events = [
+        json!({"type": "response.created", "response": {"id": "resp_9", "model": "gpt-5"}}),
+        json!({"type": "response.output_item.added", "output_index": 0, "item": {"type": "reasoning", "id": "rs_1"}}),
+        json!({"type": "response.reasoning_summary_text.delta", "output_index": 0, "summary_index": 0, "delta": "Check docs"}),
+        json!({"type": "response.output_item.done", "output_index": 0, "item": {"type": "reasoning", "id": "rs_1"}}),
+        json!({"type": "response.output_item.done", "output_index": 1, "item": {"type": "web_search_call", "action": {"type": "search", "query": "tokio 2"}}}),
+        json!({"type": "response.output_item.added", "output_index": 2, "item": {"type": "message", "role": "assistant"}}),
+        json!({"type": "response.output_text.delta", "output_index": 2, "content_index": 0, "delta": "Calling"}),
+        json!({"type": "response.output_item.done", "output_index": 2, "item": {"type": "message", "content": []}}),
+        json!({"type": "response.output_item.added", "output_index": 3, "item": {"type": "function_call", "call_id": "call_7", "name": "grep"}}),
+        json!({"type": "response.function_call_arguments.delta", "output_index": 3, "delta": "{\"q\":"}),
+        json!({"type": "response.function_call_arguments.delta", "output_index": 3, "delta": "\"spawn\"}"}),
+        json!({"type": "response.output_item.done", "output_index": 3, "item": {"type": "function_call", "call_id": "call_7", "name": "grep", "arguments": "{\"q\":\"spawn\"}"}}),
+        json!({"type": "response.completed", "response": {
+            "id": "resp_9", "model": "gpt-5", "status": "completed",
+            "output": [{"type": "function_call", "call_id": "call_7", "name": "grep", "arguments": "{}"}],
+            "usage": {"input_tokens": 30, "output_tokens": 9}
+        }}),
+    ]
+    .iter()
+    .map(|e| format!("event: {}\ndata: {}\n\n", e["type"].as_str().unwrap(), e))
+    .collect::<String>();
+
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("POST", "/v1/responses")
+        .match_body(Matcher::PartialJson(json!({"stream": true})))
+        .with_status(200)
+        .with_header("content-type", "text/event-stream")
+        .with_body(events)
+        .create_async()
+        .await;
+
+    let mut stream = provider(&server)
+        .stream(LLMRequest::new("gpt-5", vec![Message::user("find spawn")]).with_streaming())
+        .await
+        .unwrap();
+
+    let mut text = String::new();
+    let mut reasoning = String::new();
+    let mut args = String::new();
+    let mut tool_name = None;
+    let mut stop_reason = None;
+    while let Some(event) = stream.next().await {
+        match event.unwrap() {
+            StreamEvent::ContentBlockStart {
+                content_block: ContentBlock::ToolUse { name, .. },
+                ..
+            } => tool_name = Some(name),
+            StreamEvent::ContentBlockDelta { delta, .. } => match delta {
+                ContentDelta::TextDelta { text: t } => text.push_str(&t),
+                ContentDelta::ReasoningDelta { text: t } => reasoning.push_str(&t),
+                ContentDelta::InputJsonDelta { partial_json } => args.push_str(&partial_json),
+                _ => {}
+            },
+            StreamEvent::MessageDelta { delta, usage } => {
+                stop_reason = delta.stop_reason;
+                assert_eq!(usage.output_tokens, 9);
+            }
+            _ => {}
+        }
+    }
+
+    assert_eq!(text, "Calling");
+    assert_eq!(reasoning, "Check docsSearched the web: tokio 2\n");
+    assert_eq!(tool_name.as_deref(), Some("grep"));
+    assert_eq!(args, r#"{"q":"spawn"}"#);
+    assert_eq!(stop_reason, Some(StopReason::ToolUse));
+}
@@ -0,0 +1,254 @@
+//! Tests for permission rules (`utils::permissions`).
+//!
+//! Rules are `"<allow|ask|deny> <tool>(<pattern>)"`, checked in order
+//! against a call's command, path or URL; the first match decides.
+
+use crate::utils::permissions::{self, PermissionAction, PermissionRule, Permissions};
+use serde_json::json;
+use std::path::Path;
+
+const CWD: &str = "/work/project";
+
+fn rules(rules: &[&str]) -> Permissions {
+    Permissions::new(&rules.iter().map(|r| r.to_string()).collect::<Vec<_>>())
+}
+
+/// Action of the first rule matching the call, if any
+fn decide(
+    permissions: &Permissions,
+    tool: &str,
+    input: serde_json::Value,
+) -> Option<PermissionAction> {
+    permissions
+        .evaluate(tool, &input, Path::new(CWD))
+        .map(|rule| rule.action)
+}
+
+#[test]
+fn rules_parse_and_print_back() {
+    let rule: PermissionRule = "allow bash(cargo test:*)".parse().unwrap();
+    assert_eq!(rule.action, PermissionAction::Allow);
+    assert_eq!(rule.tool, "bash");
+    assert_eq!(rule.pattern.as_deref(), Some("cargo test:*"));
+    assert_eq!(rule.to_string(), "allow bash(cargo test:*)");
+
+    let rule: PermissionRule = "  deny   read_file ".parse().unwrap();
+    assert_eq!(rule.pattern, None);
+    assert_eq!(rule.to_string(), "deny read_file");
+
+    for bad in [
+        "bash(ls:*)",
+        "permit bash",
+        "allow bash(ls",
+        "allow bash()",
+        "allow",
+    ] {
+        assert!(bad.parse::<PermissionRule>().is_err(), "{bad}");
+    }
+    // Invalid rules are skipped, the rest still apply
+    assert_eq!(rules(&["allow bash(", "deny bash(rm:*)"]).len(), 1);
+}
+
+#[test]
+fn first_matching_rule_wins() {
+    let permissions = rules(&[
+        "deny bash(cargo publish:*)",
+        "allow bash(cargo:*)",
+        "ask bash",
+    ]);
+
+    let bash = |command: &str| decide(&permissions, "bash", json!({ "command": command }));
+    assert_eq!(
+        bash("cargo publish --dry-run"),
+        Some(PermissionAction::Deny)
+    );
+    assert_eq!(bash("cargo test --all"), Some(PermissionAction::Allow));
+    assert_eq!(bash("cargo"), Some(PermissionAction::Allow));
+    assert_eq!(bash("rm -rf target"), Some(PermissionAction::Ask));
+    assert_eq!(decide(&permissions, "read_file", json!({})), None);
+}
+
+#[test]
+fn command_prefixes_match_whole_words() {
+    let permissions = rules(&["allow bash(cargo test:*)", "allow bash(git status)"]);
+    let bash = |command: &str| decide(&permissions, "bash", json!({ "command": command }));
+
+    assert_eq!(bash("cargo test -p core"), Some(PermissionAction::Allow));
+    assert_eq!(bash("cargo testing"), None);
+    assert_eq!(bash("git status"), Some(PermissionAction::Allow));
+    assert_eq!(bash("git status --short"), None);
+}
+
+#[test]
+fn chained_commands_need_every_part_allowed() {
+    let permissions = rules(&["allow bash(cargo:*)", "allow bash(echo:*)"]);
+    let bash = |command: &str| decide(&permissions, "bash", json!({ "command": command }));
+
+    assert_eq!(
+        bash("cargo fmt && cargo test 2>&1 | echo"),
+        Some(PermissionAction::Allow)
+    );
+    assert_eq!(bash("cargo test; rm -rf /"), None);
+    assert_eq!(bash("cargo test $(rm -rf /)"), None);
+    assert_eq!(bash("echo `whoami`"), None);
+
+    // ...while one matching part is enough to deny
+    let permissions = rules(&["deny bash(rm:*)", "allow bash"]);
+    assert_eq!(
+        decide(
+            &permissions,
+            "bash",
+            json!({"command": "cargo build && rm -rf /"})
+        ),
+        Some(PermissionAction::Deny)
+    );
+}
+
+#[test]
+fn path_globs_resolve_against_the_working_directory() {
+    let permissions = rules(&[
+        "deny write_file(src/secrets/**)",
+        "allow write_file(src/**)",
+        "ask edit_file(/etc/*)",
+    ]);
+    let write = |path: &str| decide(&permissions, "write_file", json!({ "path": path }));
+
+    assert_eq!(write("src/main.rs"), Some(PermissionAction::Allow));
+    assert_eq!(
+        write("/work/project/src/a/b/c.rs"),
+        Some(PermissionAction::Allow)
+    );
+    assert_eq!(write("./src/secrets/key.pem"), Some(PermissionAction::Deny));
+    assert_eq!(write("src/../.env"), None, "`..` can't escape the glob");
+    assert_eq!(write("tests/src/main.rs"), None);
+
+    let edit = |path: &str| decide(&permissions, "edit_file", json!({ "file_path": path }));
+    assert_eq!(edit("/etc/hosts"), Some(PermissionAction::Ask));
+    assert_eq!(
+        edit("/etc/ssh/sshd_config"),
+        None,
+        "`*` stays in one directory"
+    );
+}
+
+#[test]
+fn url_patterns_and_tool_wildcards() {
+    let permissions = rules(&["allow http_request(https://api.github.com/*)", "ask *_send"]);
+
+    let fetch = |url: &str| decide(&permissions, "http_request", json!({ "url": url }));
+    assert_eq!(
+        fetch("https://api.github.com/repos/a/b"),
+        Some(PermissionAction::Allow)
+    );
+    assert_eq!(fetch("https://api.github.com.evil.io/x"), None);
+
+    assert_eq!(
+        decide(&permissions, "telegram_send", json!({"message": "hi"})),
+        Some(PermissionAction::Ask)
+    );
+    // A pattern never matches a call without a command, path or URL
+    assert_eq!(
+        decide(
+            &rules(&["allow web_search(*)"]),
+            "web_search",
+            json!({"query": "crabs"})
+        ),
+        None
+    );
+}
+
+#[test]
+fn suggested_rules_cover_the_call() {
+    let cwd = Path::new(CWD);
+    let suggest =
+        |tool: &str, input: serde_json::Value| permissions::suggest_rule(tool, &input, cwd);
+
+    assert_eq!(
+        suggest("bash", json!({"command": "cargo test --all"})),
+        "bash(cargo test:*)"
+    );
+    assert_eq!(
+        suggest("bash", json!({"command": "ls -la && pwd"})),
+        "bash(ls:*)"
+    );
+    assert_eq!(
+        suggest("bash", json!({"command": "python3 run.py"})),
+        "bash(python3:*)"
+    );
+    assert_eq!(
+        suggest("write_file", json!({"path": "src/utils/mod.rs"})),
+        "write_file(src/utils/**)"
+    );
+    assert_eq!(
+        suggest("write_file", json!({"path": "Cargo.toml"})),
+        "write_file(**)"
+    );
+    assert_eq!(
+        suggest("read_file", json!({"path": "/etc/hosts"})),
+        "read_file(/etc/**)"
+    );
+    assert_eq!(
+        suggest(
+            "http_request",
+            json!({"url": "https://api.github.com/repos?page=2"})
+        ),
+        "http_request(https://api.github.com/*)"
+    );
+    assert_eq!(
+        suggest("web_search", json!({"query": "crabs"})),
+        "web_search"
+    );
+
+    // Each suggestion, as an allow rule, matches the call it came from
+    let input = json!({"command": "cargo test --all"});
+    let rule = format!("allow {}", suggest("bash", input.clone()));
+    assert_eq!(
+        rules(&[&rule])
+            .evaluate("bash", &input, cwd)
+            .map(|r| r.action),
+        Some(PermissionAction::Allow)
+    );
+}
+
+#[test]
+fn allow_rules_are_added_where_they_take_effect() {
+    let existing = vec![
+        "deny bash(rm:*)".to_string(),
+        "ask  bash".to_string(),
+        "allow read_file".to_string(),
+    ];
+
+    // Ahead of the ask rule that prompted
+    let updated =
+        permissions::with_allow_rule(&existing, "bash(cargo test:*)", Some("ask bash")).unwrap();
+    assert_eq!(
+        updated,
+        vec![
+            "deny bash(rm:*)",
+            "allow bash(cargo test:*)",
+            "ask  bash",
+            "allow read_file"
+        ]
+    );
+
+    // At the end when no rule matched, and never twice
+    let updated = permissions::with_allow_rule(&existing, "write_file(src/**)", None).unwrap();
+    assert_eq!(updated.last().unwrap(), "allow write_file(src/**)");
+    assert_eq!(
+        permissions::with_allow_rule(&updated, "write_file(src/**)", None),
+        None
+    );
+}
+
+#[test]
+fn config_parses_rules() {
+    let config: crate::config::PermissionsConfig = toml::from_str(
+        r#"
+        rules = ["allow bash(cargo test:*)", "deny write_file(migrations/**)"]
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.rules.len(), 2);
+    assert!(crate::config::PermissionsConfig::default().rules.is_empty());
+}
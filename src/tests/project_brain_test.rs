@@ -0,0 +1,95 @@
+//! Tests for project brain files (`brain::project_brain`).
+//!
+//! `AGENTS.md` / `OPENCRABS.md` in the working directory and its ancestors
+//! are layered after the global brain; those in subdirectories join once a
+//! file under them is touched.
+
+use crate::brain::ProjectBrain;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn write(path: PathBuf, content: &str) -> PathBuf {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+/// Files in scope under `root` (ancestors above the temp dir are the host's)
+fn files_under(brain: &ProjectBrain, root: &Path) -> Vec<PathBuf> {
+    brain
+        .files()
+        .into_iter()
+        .filter(|path| path.starts_with(root))
+        .collect()
+}
+
+#[test]
+fn ancestors_load_outermost_first_and_global_brain_is_skipped() {
+    let root = TempDir::new().unwrap();
+    let root = root.path();
+    let home = root.join(".opencrabs");
+    write(home.join("AGENTS.md"), "global rules");
+    let outer = write(root.join("AGENTS.md"), "workspace rules");
+    let agents = write(root.join("repo/AGENTS.md"), "repo rules");
+    let opencrabs = write(root.join("repo/OPENCRABS.md"), "crab rules");
+    std::fs::create_dir_all(root.join("repo/app")).unwrap();
+
+    let brain = ProjectBrain::new(root.join("repo/app"), home.clone());
+    assert_eq!(files_under(&brain, root), vec![outer, agents, opencrabs]);
+
+    // Working from inside the global brain directory adds nothing twice
+    let brain = ProjectBrain::new(home.clone(), home.clone());
+    assert!(!files_under(&brain, root).contains(&home.join("AGENTS.md")));
+}
+
+#[test]
+fn nested_files_join_once_touched() {
+    let root = TempDir::new().unwrap();
+    let repo = root.path().join("repo");
+    let nested = write(repo.join("crates/core/AGENTS.md"), "core rules");
+    write(repo.join("crates/core/src/lib.rs"), "");
+    let mut brain = ProjectBrain::new(repo.clone(), root.path().join(".opencrabs"));
+    assert!(files_under(&brain, &repo).is_empty());
+
+    assert!(brain.touch(Path::new("crates/core/src/lib.rs")));
+    assert_eq!(files_under(&brain, &repo), vec![nested.clone()]);
+    assert!(
+        !brain.touch(&repo.join("crates/core/src/lib.rs")),
+        "already in scope"
+    );
+
+    // Touching a directory brings it in too; paths outside the project don't
+    std::fs::create_dir_all(repo.join("docs")).unwrap();
+    assert!(brain.touch(Path::new("docs")));
+    assert!(!brain.touch(Path::new("../elsewhere/AGENTS.md")));
+    assert!(!brain.touch(Path::new("/etc/hosts")));
+    assert_eq!(files_under(&brain, &repo), vec![nested]);
+}
+
+#[test]
+fn section_names_each_file_it_came_from() {
+    let root = TempDir::new().unwrap();
+    let repo = root.path().join("repo");
+    let top = write(repo.join("AGENTS.md"), "Run cargo fmt before committing.");
+    write(repo.join("OPENCRABS.md"), "  \n ");
+    let web = write(repo.join("web/AGENTS.md"), "Use pnpm, not npm.");
+    let home = root.path().join(".opencrabs");
+
+    let mut brain = ProjectBrain::new(repo.clone(), home.clone());
+    brain.touch(Path::new("web/index.ts"));
+    let section = brain.section().unwrap();
+
+    let top_header = format!("--- {} (project) ---\nRun cargo fmt", top.display());
+    let web_header = format!("--- {} (project) ---\nUse pnpm", web.display());
+    assert!(section.starts_with("--- Project Brain ---"));
+    assert!(section.contains(&top_header), "{section}");
+    assert!(
+        section.find(&top_header) < section.find(&web_header),
+        "outer files come first"
+    );
+    assert!(!section.contains("OPENCRABS.md"), "empty files are skipped");
+
+    let empty = TempDir::new().unwrap();
+    let brain = ProjectBrain::new(empty.path().to_path_buf(), home);
+    assert!(brain.section().is_none());
+}
@@ -2,13 +2,14 @@
 //!
 //! Verifies that OpenRouter :free pacing is process-wide, not per-instance —
 //! so orchestrator + subagents + team members collectively stay under the
-//! provider's rate limit.
+//! provider's rate limit — and that configured rpm/tpm budgets queue calls
+//! through shared token buckets.
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 
 use crate::brain::provider::rate_limiter::{
-    GlobalRateLimiter, OPENROUTER_FREE_LIMITERS, RateLimiter,
+    GlobalRateLimiter, OPENROUTER_FREE_LIMITERS, RateBudget, RateBudgets, RateLimiter, TokenBucket,
 };
 
 // ── First-call-free ──────────────────────────────────────────────────
@@ -183,3 +184,156 @@ async fn global_limiter_returns_different_limiter_for_different_model() {
     let b = global.get("google/gemma-3-27b-it:free");
     assert!(!Arc::ptr_eq(&a, &b));
 }
+
+// ── Configured budgets ───────────────────────────────────────────────
+// rpm/tpm from `[providers.<name>.rate_limit]`. Reservations return the
+// wait instead of sleeping, so these run without wall-clock delays.
+
+#[test]
+fn token_bucket_waits_for_debt_to_refill() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::per_minute(60);
+    assert_eq!(bucket.reserve(60.0, start), Duration::ZERO);
+    // Empty — one more unit refills at 1/s
+    let wait = bucket.reserve(1.0, start);
+    assert!(
+        (Duration::from_millis(990)..=Duration::from_millis(1010)).contains(&wait),
+        "expected ~1s, got {:?}",
+        wait
+    );
+    // Half a minute later the debt is repaid and 29 units are back
+    assert_eq!(
+        bucket.reserve(29.0, start + Duration::from_secs(30)),
+        Duration::ZERO
+    );
+}
+
+#[test]
+fn token_bucket_caps_oversized_reservations() {
+    let start = Instant::now();
+    let mut bucket = TokenBucket::per_minute(1000);
+    // A prompt bigger than the whole budget waits one minute, not forever
+    bucket.reserve(1000.0, start);
+    let wait = bucket.reserve(50_000.0, start);
+    assert!(wait <= Duration::from_secs(60), "got {:?}", wait);
+}
+
+/// `(provider, model, rpm, tpm)`
+type Entry<'a> = (&'a str, Option<&'a str>, Option<u32>, Option<u32>);
+
+fn budgets(entries: &[Entry]) -> RateBudgets {
+    let budgets = RateBudgets::new();
+    budgets.configure(entries.iter().map(|(provider, model, rpm, tpm)| {
+        (
+            provider.to_string(),
+            model.map(str::to_string),
+            RateBudget {
+                rpm: *rpm,
+                tpm: *tpm,
+            },
+        )
+    }));
+    budgets
+}
+
+#[test]
+fn budgets_queue_requests_past_rpm() {
+    let budgets = budgets(&[("anthropic", None, Some(2), None)]);
+    assert_eq!(
+        budgets.reserve("anthropic", "claude-sonnet-4-6", 100),
+        Duration::ZERO
+    );
+    assert_eq!(
+        budgets.reserve("Anthropic", "claude-opus-4-6", 100),
+        Duration::ZERO
+    );
+    let wait = budgets.reserve("anthropic", "claude-sonnet-4-6", 100);
+    assert!(wait >= Duration::from_secs(25), "got {:?}", wait);
+    // Other providers are untouched
+    assert_eq!(budgets.reserve("openai", "gpt-4o", 100), Duration::ZERO);
+}
+
+#[test]
+fn model_budget_applies_on_top_of_provider_budget() {
+    let budgets = budgets(&[
+        ("anthropic", None, Some(100), None),
+        ("anthropic", Some("claude-opus-4-6"), None, Some(1000)),
+    ]);
+    assert_eq!(
+        budgets.reserve("anthropic", "claude-opus-4-6", 900),
+        Duration::ZERO
+    );
+    assert!(budgets.reserve("anthropic", "claude-opus-4-6", 900) > Duration::ZERO);
+    // Sonnet only answers to the provider-wide rpm
+    assert_eq!(
+        budgets.reserve("anthropic", "claude-sonnet-4-6", 900),
+        Duration::ZERO
+    );
+}
+
+#[test]
+fn settle_refunds_overestimated_tokens() {
+    let budgets = budgets(&[("openai", None, None, Some(1000))]);
+    assert_eq!(budgets.reserve("openai", "gpt-4o", 1000), Duration::ZERO);
+    // The call only used 200 tokens — 800 go back into the bucket
+    budgets.settle("openai", "gpt-4o", 1000, 200);
+    assert_eq!(budgets.reserve("openai", "gpt-4o", 700), Duration::ZERO);
+}
+
+#[test]
+fn reconfigure_keeps_state_of_unchanged_budgets() {
+    let budgets = budgets(&[("openai", None, Some(1), None)]);
+    budgets.reserve("openai", "gpt-4o", 0);
+    budgets.configure([(
+        "openai".to_string(),
+        None,
+        RateBudget {
+            rpm: Some(1),
+            tpm: None,
+        },
+    )]);
+    assert!(budgets.reserve("openai", "gpt-4o", 0) > Duration::ZERO);
+    // Dropping the section lifts the limit
+    budgets.configure(std::iter::empty());
+    assert_eq!(budgets.reserve("openai", "gpt-4o", 0), Duration::ZERO);
+}
+
+#[test]
+fn rate_limit_sections_map_to_runtime_provider_names() {
+    let config: crate::config::Config = toml::from_str(
+        r#"
+[providers.github.rate_limit]
+rpm = 10
+
+[providers.anthropic.rate_limit]
+tpm = 40000
+[providers.anthropic.rate_limit.models."claude-opus-4-6"]
+rpm = 5
+
+[providers.custom.groq]
+base_url = "https://api.groq.com/openai/v1"
+rate_limit = { rpm = 30 }
+"#,
+    )
+    .unwrap();
+    let mut entries = crate::brain::provider::factory::rate_budgets(&config);
+    entries.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+    let entries: Vec<_> = entries
+        .into_iter()
+        .map(|(p, m, b)| (p, m, b.rpm, b.tpm))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("GitHub Copilot".to_string(), None, Some(10), None),
+            ("anthropic".to_string(), None, None, Some(40000)),
+            (
+                "anthropic".to_string(),
+                Some("claude-opus-4-6".to_string()),
+                Some(5),
+                None
+            ),
+            ("groq".to_string(), None, Some(30), None),
+        ]
+    );
+}
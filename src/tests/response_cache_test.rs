@@ -0,0 +1,451 @@
+//! Tests for the exact-match response cache.
+//!
+//! A counting provider sits behind `CachingProvider`; opted-in requests must
+//! reach it once and then be answered (or replayed, for streams) from SQLite
+//! until the TTL runs out, within the configured size limits.
+
+use crate::brain::provider::response_cache::{CacheLimits, cache_key};
+use crate::brain::provider::{
+    CachingProvider, ContentBlock, ContentDelta, LLMRequest, LLMResponse, Message, MessageDelta,
+    OpenAIProvider, Provider, ProviderError, ProviderStream, ResponseCache, Result, Role,
+    StopReason, StreamEvent, StreamMessage, TokenUsage,
+};
+use crate::db::Database;
+use crate::db::repository::UsageLedgerRepository;
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Echoes the last user message; streams fail mid-way on "boom".
+#[derive(Default)]
+struct CountingProvider {
+    calls: AtomicUsize,
+}
+
+fn last_text(request: &LLMRequest) -> String {
+    request
+        .messages
+        .last()
+        .and_then(|m| {
+            m.content.iter().find_map(|b| match b {
+                ContentBlock::Text { text } => Some(text.clone()),
+                _ => None,
+            })
+        })
+        .unwrap_or_default()
+}
+
+#[async_trait]
+impl Provider for CountingProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(LLMResponse {
+            id: "resp-1".to_string(),
+            model: request.model.clone(),
+            content: vec![ContentBlock::Text {
+                text: format!("echo: {}", last_text(&request)),
+            }],
+            stop_reason: Some(StopReason::EndTurn),
+            usage: TokenUsage {
+                input_tokens: 120,
+                output_tokens: 30,
+                ..Default::default()
+            },
+        })
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let text = last_text(&request);
+        let mut events = vec![
+            Ok(StreamEvent::MessageStart {
+                message: StreamMessage {
+                    id: "msg-1".to_string(),
+                    model: request.model.clone(),
+                    role: Role::Assistant,
+                    usage: TokenUsage {
+                        input_tokens: 120,
+                        ..Default::default()
+                    },
+                },
+            }),
+            Ok(StreamEvent::ContentBlockDelta {
+                index: 0,
+                delta: ContentDelta::TextDelta {
+                    text: format!("echo: {}", text),
+                },
+            }),
+        ];
+        if text == "boom" {
+            events.push(Err(ProviderError::StreamError("connection reset".into())));
+        }
+        events.push(Ok(StreamEvent::MessageDelta {
+            delta: MessageDelta {
+                stop_reason: Some(StopReason::EndTurn),
+                stop_sequence: None,
+            },
+            usage: TokenUsage {
+                input_tokens: 120,
+                output_tokens: 30,
+                ..Default::default()
+            },
+        }));
+        events.push(Ok(StreamEvent::MessageStop));
+        Ok(Box::pin(futures::stream::iter(events)))
+    }
+
+    fn name(&self) -> &str {
+        "counting"
+    }
+
+    fn default_model(&self) -> &str {
+        "counting-1"
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        vec!["counting-1".to_string()]
+    }
+
+    fn context_window(&self, _model: &str) -> Option<u32> {
+        Some(32_000)
+    }
+
+    fn calculate_cost(&self, _model: &str, _input: u32, _output: u32) -> f64 {
+        0.0
+    }
+}
+
+async fn setup(limits: CacheLimits) -> (Database, Arc<CountingProvider>, CachingProvider) {
+    let db = Database::connect_in_memory()
+        .await
+        .expect("Failed to create database");
+    db.run_migrations().await.expect("Failed to run migrations");
+    let inner = Arc::new(CountingProvider::default());
+    let cache = Arc::new(ResponseCache::new(db.pool().clone(), limits));
+    let provider = CachingProvider::new(inner.clone(), cache);
+    (db, inner, provider)
+}
+
+fn request(text: &str) -> LLMRequest {
+    LLMRequest::new("counting-1", vec![Message::user(text)])
+}
+
+async fn entries(db: &Database) -> i64 {
+    ResponseCache::new(db.pool().clone(), CacheLimits::default())
+        .stats()
+        .await
+        .unwrap()
+        .0
+}
+
+/// Drain a stream into its text and whether any usage was flagged as cached.
+async fn drain(stream: ProviderStream) -> (String, bool) {
+    let events: Vec<_> = stream.collect().await;
+    let mut text = String::new();
+    let mut hit = false;
+    for event in events {
+        match event.unwrap() {
+            StreamEvent::ContentBlockDelta {
+                delta: ContentDelta::TextDelta { text: t },
+                ..
+            } => text.push_str(&t),
+            StreamEvent::MessageStart { message } => hit |= message.usage.response_cache_hit,
+            StreamEvent::MessageDelta { usage, .. } => hit |= usage.response_cache_hit,
+            _ => {}
+        }
+    }
+    (text, hit)
+}
+
+#[tokio::test]
+async fn complete_is_served_from_cache() {
+    let (_db, inner, provider) = setup(CacheLimits::default()).await;
+
+    let first = provider
+        .complete(request("hello").with_cache_ttl(60))
+        .await
+        .unwrap();
+    let second = provider
+        .complete(request("hello").with_cache_ttl(60))
+        .await
+        .unwrap();
+
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    assert!(!first.usage.response_cache_hit);
+    assert!(second.usage.response_cache_hit);
+    assert_eq!(second.usage.input_tokens, 120);
+    assert!(matches!(
+        &second.content[..],
+        [ContentBlock::Text { text }] if text == "echo: hello"
+    ));
+}
+
+#[tokio::test]
+async fn requests_without_ttl_bypass_cache() {
+    let (db, inner, provider) = setup(CacheLimits::default()).await;
+
+    provider.complete(request("hello")).await.unwrap();
+    provider.complete(request("hello")).await.unwrap();
+
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    assert_eq!(entries(&db).await, 0);
+}
+
+#[tokio::test]
+async fn stream_is_replayed_from_cache() {
+    let (_db, inner, provider) = setup(CacheLimits::default()).await;
+
+    let first = drain(
+        provider
+            .stream(request("hi").with_cache_ttl(60))
+            .await
+            .unwrap(),
+    )
+    .await;
+    let second = drain(
+        provider
+            .stream(request("hi").with_cache_ttl(60))
+            .await
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    assert_eq!(first, ("echo: hi".to_string(), false));
+    assert_eq!(second, ("echo: hi".to_string(), true));
+}
+
+#[tokio::test]
+async fn stream_and_complete_are_cached_separately() {
+    let (_db, inner, provider) = setup(CacheLimits::default()).await;
+
+    provider
+        .complete(request("hi").with_cache_ttl(60))
+        .await
+        .unwrap();
+    let (_, hit) = drain(
+        provider
+            .stream(request("hi").with_cache_ttl(60))
+            .await
+            .unwrap(),
+    )
+    .await;
+
+    assert!(!hit);
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn failed_streams_are_not_stored() {
+    let (db, inner, provider) = setup(CacheLimits::default()).await;
+
+    let stream = provider
+        .stream(request("boom").with_cache_ttl(60))
+        .await
+        .unwrap();
+    let events: Vec<_> = stream.collect().await;
+    assert!(events.iter().any(|e| e.is_err()));
+
+    assert_eq!(entries(&db).await, 0);
+    provider
+        .stream(request("boom").with_cache_ttl(60))
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn any_field_change_misses() {
+    let (_db, inner, provider) = setup(CacheLimits::default()).await;
+
+    provider
+        .complete(request("hello").with_cache_ttl(60))
+        .await
+        .unwrap();
+    provider
+        .complete(request("hello").with_temperature(0.2).with_cache_ttl(60))
+        .await
+        .unwrap();
+    provider
+        .complete(request("hello").with_system("be brief").with_cache_ttl(60))
+        .await
+        .unwrap();
+
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn key_ignores_routing_fields() {
+    let mut a = request("hello");
+    let mut b = request("hello");
+    a.session_id = Some(uuid::Uuid::new_v4());
+    b.working_directory = Some("/tmp".to_string());
+    b.cache_ttl = Some(60);
+    let provider = CountingProvider::default();
+    assert_eq!(
+        cache_key(&provider, &a, "complete"),
+        cache_key(&provider, &b, "complete")
+    );
+    assert_ne!(
+        cache_key(&provider, &a, "complete"),
+        cache_key(&provider, &a, "stream")
+    );
+}
+
+#[test]
+fn key_separates_providers_and_hosts() {
+    let req = request("hello");
+    let local = OpenAIProvider::with_base_url(String::new(), "http://localhost:8080/v1".into());
+    let remote = OpenAIProvider::with_base_url(String::new(), "https://api.example.com/v1".into());
+    let renamed = OpenAIProvider::with_base_url(String::new(), "http://localhost:8080/v1".into())
+        .with_name("lab");
+
+    let key = cache_key(&local, &req, "complete");
+    assert_ne!(key, cache_key(&remote, &req, "complete"));
+    assert_ne!(key, cache_key(&renamed, &req, "complete"));
+    assert_ne!(
+        key,
+        cache_key(&CountingProvider::default(), &req, "complete")
+    );
+}
+
+#[tokio::test]
+async fn expired_entries_are_refetched() {
+    let (db, inner, provider) = setup(CacheLimits::default()).await;
+
+    provider
+        .complete(request("hello").with_cache_ttl(60))
+        .await
+        .unwrap();
+    db.pool()
+        .get()
+        .await
+        .unwrap()
+        .interact(|conn| {
+            conn.execute(
+                "UPDATE response_cache SET created_at = created_at - 120",
+                [],
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let response = provider
+        .complete(request("hello").with_cache_ttl(60))
+        .await
+        .unwrap();
+    assert!(!response.usage.response_cache_hit);
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+
+    // A longer TTL at another call site still accepts the refreshed entry
+    let response = provider
+        .complete(request("hello").with_cache_ttl(3600))
+        .await
+        .unwrap();
+    assert!(response.usage.response_cache_hit);
+}
+
+#[tokio::test]
+async fn oversized_replies_are_not_stored() {
+    let limits = CacheLimits {
+        max_entry_bytes: 64,
+        ..Default::default()
+    };
+    let (db, inner, provider) = setup(limits).await;
+
+    provider
+        .complete(request("hello").with_cache_ttl(60))
+        .await
+        .unwrap();
+    provider
+        .complete(request("hello").with_cache_ttl(60))
+        .await
+        .unwrap();
+
+    assert_eq!(entries(&db).await, 0);
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn least_recently_used_entries_are_evicted() {
+    let limits = CacheLimits {
+        max_entries: 2,
+        ..Default::default()
+    };
+    let (db, inner, provider) = setup(limits).await;
+
+    for text in ["one", "two", "three"] {
+        provider
+            .complete(request(text).with_cache_ttl(60))
+            .await
+            .unwrap();
+    }
+    assert_eq!(entries(&db).await, 2);
+
+    // "one" was evicted, "three" is still there
+    provider
+        .complete(request("three").with_cache_ttl(60))
+        .await
+        .unwrap();
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    provider
+        .complete(request("one").with_cache_ttl(60))
+        .await
+        .unwrap();
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn total_size_limit_evicts() {
+    let (db, _inner, provider) = setup(CacheLimits::default()).await;
+    provider
+        .complete(request("one").with_cache_ttl(60))
+        .await
+        .unwrap();
+    let (_, one_entry) = ResponseCache::new(db.pool().clone(), CacheLimits::default())
+        .stats()
+        .await
+        .unwrap();
+
+    let limits = CacheLimits {
+        max_total_bytes: one_entry as usize + 10,
+        ..Default::default()
+    };
+    let (db, _inner, provider) = setup(limits).await;
+    for text in ["one", "two", "three"] {
+        provider
+            .complete(request(text).with_cache_ttl(60))
+            .await
+            .unwrap();
+    }
+    assert_eq!(entries(&db).await, 1);
+}
+
+#[tokio::test]
+async fn ledger_separates_cache_hits_from_billed_usage() {
+    let db = Database::connect_in_memory()
+        .await
+        .expect("Failed to create database");
+    db.run_migrations().await.expect("Failed to run migrations");
+    let repo = UsageLedgerRepository::new(db.pool().clone());
+
+    repo.record("s1", "sonnet", 100, 0.05).await.unwrap();
+    repo.record_cache_hit("s1", "sonnet", 0.05).await.unwrap();
+    repo.record_cache_hit("s2", "sonnet", 0.02).await.unwrap();
+
+    let (tokens, cost) = repo.totals().await.unwrap();
+    assert_eq!(tokens, 100);
+    assert!((cost - 0.05).abs() < 0.0001);
+
+    let (hits, saved) = repo.cache_savings().await.unwrap();
+    assert_eq!(hits, 2);
+    assert!((saved - 0.07).abs() < 0.0001);
+
+    let stats = repo.stats_by_model().await.unwrap();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].entry_count, 1);
+}
@@ -1304,3 +1304,48 @@ mod feedback_digest {
         assert!(!digest.contains("notable failure rates"));
     }
 }
+
+// --- Digest Batch Tests ---
+
+mod digest_batch {
+    use crate::brain::provider::{BatchResult, ContentBlock, LLMResponse, TokenUsage};
+    use crate::brain::rsi::parse_digest_batch;
+
+    fn reply(text: &str) -> LLMResponse {
+        LLMResponse {
+            id: "msg_1".into(),
+            model: "m".into(),
+            content: vec![ContentBlock::Text { text: text.into() }],
+            stop_reason: None,
+            usage: TokenUsage::default(),
+        }
+    }
+
+    #[test]
+    fn keeps_findings_that_match_the_schema() {
+        let results = vec![BatchResult::succeeded(
+            "rsi_digest",
+            reply(
+                r#"{"findings": [{"target_file": "TOOLS.md", "problem": "edit fails", "improvement": "re-read first"}]}"#,
+            ),
+        )];
+        let findings = parse_digest_batch(&results);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].target_file, "TOOLS.md");
+    }
+
+    #[test]
+    fn drops_replies_that_fail_the_schema() {
+        let results = vec![
+            BatchResult::succeeded(
+                "rsi_digest",
+                reply(
+                    r#"{"findings": [{"target_file": "README.md", "problem": "p", "improvement": "i"}]}"#,
+                ),
+            ),
+            BatchResult::succeeded("rsi_digest", reply("Everything looks fine.")),
+            BatchResult::errored("rsi_digest", "expired"),
+        ];
+        assert!(parse_digest_batch(&results).is_empty());
+    }
+}
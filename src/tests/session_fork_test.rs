@@ -0,0 +1,202 @@
+//! Tests for session forking (`/fork`).
+//!
+//! A fork copies the history up to a message into a new session that
+//! records its parent; the sessions list shows forks under their parent.
+
+use crate::brain::agent::AgentService;
+use crate::brain::provider::PlaceholderProvider;
+use crate::channels::commands::{ChannelCommand, handle_command};
+use crate::db::Database;
+use crate::db::models::Session;
+use crate::services::session::fork_tree;
+use crate::services::{MessageService, ServiceContext, SessionService};
+use std::sync::Arc;
+use uuid::Uuid;
+
+async fn services() -> (SessionService, MessageService) {
+    let db = Database::connect_in_memory().await.unwrap();
+    db.run_migrations().await.unwrap();
+    let context = ServiceContext::new(db.pool().clone());
+    (
+        SessionService::new(context.clone()),
+        MessageService::new(context),
+    )
+}
+
+async fn contents(messages: &MessageService, session_id: Uuid) -> Vec<String> {
+    messages
+        .list_messages_for_session(session_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.content)
+        .collect()
+}
+
+#[tokio::test]
+async fn fork_copies_history_up_to_the_message() {
+    let (sessions, messages) = services().await;
+    let source = sessions
+        .create_session(Some("Refactor".to_string()))
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for (role, content) in [
+        ("user", "plan it"),
+        ("assistant", "plan"),
+        ("user", "do it"),
+        ("assistant", "done"),
+    ] {
+        let message = messages
+            .create_message(source.id, role.to_string(), content.to_string())
+            .await
+            .unwrap();
+        ids.push(message.id);
+    }
+
+    let fork = sessions.fork_session(source.id, ids[1]).await.unwrap();
+    assert_eq!(fork.title.as_deref(), Some("Refactor (fork)"));
+    assert_eq!(fork.parent_session_id, Some(source.id));
+    assert_eq!(fork.forked_from_message_id, Some(ids[1]));
+
+    let stored = sessions.get_session_required(fork.id).await.unwrap();
+    assert_eq!(stored.parent_session_id, Some(source.id));
+    assert_eq!(stored.forked_from_message_id, Some(ids[1]));
+
+    assert_eq!(contents(&messages, fork.id).await, vec!["plan it", "plan"]);
+    assert_eq!(
+        contents(&messages, source.id).await.len(),
+        4,
+        "source untouched"
+    );
+
+    // The fork's history is its own
+    messages
+        .create_message(fork.id, "user".to_string(), "other way".to_string())
+        .await
+        .unwrap();
+    assert_eq!(contents(&messages, source.id).await.len(), 4);
+
+    // A message of another session can't be a fork point
+    assert!(sessions.fork_session(fork.id, ids[3]).await.is_err());
+}
+
+#[tokio::test]
+async fn fork_point_is_the_last_message_or_the_one_before() {
+    let (sessions, messages) = services().await;
+    let session = sessions.create_session(None).await.unwrap();
+    assert!(
+        sessions
+            .fork_point(session.id, None)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let mut created = Vec::new();
+    for content in ["one", "two", "three"] {
+        created.push(
+            messages
+                .create_message(session.id, "user".to_string(), content.to_string())
+                .await
+                .unwrap(),
+        );
+    }
+
+    let last = sessions
+        .fork_point(session.id, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(last.content, "three");
+    let before = sessions
+        .fork_point(session.id, Some(&created[1]))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(before.content, "one");
+    assert!(
+        sessions
+            .fork_point(session.id, Some(&created[0]))
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn channel_fork_numbers_skip_slash_commands() {
+    let db = Database::connect_in_memory().await.unwrap();
+    db.run_migrations().await.unwrap();
+    let context = ServiceContext::new(db.pool().clone());
+    let sessions = SessionService::new(context.clone());
+    let messages = MessageService::new(context.clone());
+    let agent = AgentService::new_for_test(Arc::new(PlaceholderProvider), context).await;
+    let source = sessions.create_session(None).await.unwrap();
+    for (role, content) in [
+        ("user", "plan it"),
+        ("assistant", "plan"),
+        ("user", "do it"),
+        ("assistant", "done"),
+    ] {
+        messages
+            .create_message(source.id, role.to_string(), content.to_string())
+            .await
+            .unwrap();
+    }
+
+    // The channel saves "/help" and its reply to the history, but #1 is
+    // still the last real message
+    handle_command("/help", source.id, &agent, &sessions).await;
+    let ChannelCommand::Sessions(reply) =
+        handle_command("/fork 1", source.id, &agent, &sessions).await
+    else {
+        panic!("/fork 1 should reply with the fork");
+    };
+    assert!(reply.text.contains("> do it"), "{}", reply.text);
+    let fork_id = reply.sessions[0].0;
+    assert_eq!(contents(&messages, fork_id).await, vec!["plan it", "plan"]);
+}
+
+#[test]
+fn fork_tree_puts_forks_under_their_parent() {
+    let session = |title: &str, parent: Option<Uuid>| {
+        let mut s = Session::new(Some(title.to_string()), None, None);
+        s.parent_session_id = parent;
+        s
+    };
+    let a = session("a", None);
+    let b = session("b", None);
+    let a1 = session("a1", Some(a.id));
+    let a1x = session("a1x", Some(a1.id));
+    let a2 = session("a2", Some(a.id));
+    let orphan = session("orphan", Some(Uuid::new_v4()));
+
+    let tree: Vec<(String, usize)> = fork_tree(vec![
+        a1x.clone(),
+        b.clone(),
+        a2.clone(),
+        a.clone(),
+        orphan.clone(),
+        a1.clone(),
+    ])
+    .into_iter()
+    .map(|(s, depth)| (s.title.unwrap(), depth))
+    .collect();
+
+    let expected = [
+        ("b", 0),
+        ("a", 0),
+        ("a2", 1),
+        ("a1", 1),
+        ("a1x", 2),
+        ("orphan", 0),
+    ];
+    assert_eq!(
+        tree,
+        expected
+            .iter()
+            .map(|(t, d)| (t.to_string(), *d))
+            .collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,211 @@
+//! Tests for skills (`brain::skills`) and the `load_skill` tool.
+//!
+//! Skills are `<name>/SKILL.md` directories under `~/.opencrabs/skills/` or a
+//! project's `.opencrabs/skills/`; only their names and descriptions reach
+//! the system brain until `load_skill` fetches the rest.
+
+use crate::brain::skills::{SkillScope, Skills};
+use crate::brain::tools::load_skill::LoadSkillTool;
+use crate::brain::tools::{Tool, ToolExecutionContext};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+fn write(path: PathBuf, content: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+fn skill(dir: &Path, name: &str, skill_md: &str) -> PathBuf {
+    let dir = dir.join(name);
+    write(dir.join("SKILL.md"), skill_md);
+    dir
+}
+
+const PDF_FORMS: &str = "---
+name: pdf-forms
+description: \"Fill in PDF forms from structured data\"
+---
+# PDF forms
+
+Run scripts/fill.py with the field values as JSON.
+";
+
+#[test]
+fn project_skills_layer_over_global_ones() {
+    let root = TempDir::new().unwrap();
+    let global = root.path().join("home/skills");
+    let project = root.path().join("repo");
+    std::fs::create_dir_all(project.join("src")).unwrap();
+
+    skill(&global, "pdf", PDF_FORMS);
+    skill(
+        &global,
+        "changelog",
+        "# Changelog\n\nKeep CHANGELOG.md in Keep a Changelog format.\n",
+    );
+    skill(
+        &project.join(".opencrabs/skills"),
+        "pdf-forms",
+        "---\nname: PDF-Forms\ndescription: This repo's own form filler\n---\nUse make forms.\n",
+    );
+    std::fs::create_dir_all(global.join("empty")).unwrap();
+
+    let skills = Skills::discover(&global, &project.join("src"));
+    assert_eq!(skills.len(), 2, "directories without SKILL.md are skipped");
+
+    let pdf = skills.get("pdf-forms").unwrap();
+    assert_eq!(pdf.scope, SkillScope::Project, "nearest directory wins");
+    assert_eq!(pdf.description, "This repo's own form filler");
+
+    let changelog = skills.get("CHANGELOG").unwrap();
+    assert_eq!(changelog.scope, SkillScope::Global);
+    assert_eq!(
+        changelog.description, "Keep CHANGELOG.md in Keep a Changelog format.",
+        "without frontmatter the name is the directory and the description the first line"
+    );
+
+    let index = skills.index_section().unwrap();
+    assert!(index.contains("`load_skill`"), "{index}");
+    assert!(index.contains("- **changelog**: Keep CHANGELOG.md"));
+    assert!(index.contains("- **PDF-Forms** (project): This repo's own form filler"));
+    assert!(
+        !index.contains("make forms"),
+        "instructions stay out of the index"
+    );
+
+    // Rescanning picks up new skills — no restart needed
+    skill(&global, "deploy", "Ship it.\n");
+    assert_eq!(Skills::discover(&global, &project).len(), 3);
+    assert!(
+        Skills::discover(&global, root.path())
+            .get("pdf-forms")
+            .is_some_and(|s| s.scope == SkillScope::Global)
+    );
+    assert!(
+        Skills::discover(&root.path().join("none"), root.path())
+            .index_section()
+            .is_none()
+    );
+}
+
+#[test]
+fn instructions_list_resources_and_resources_stay_inside() {
+    let root = TempDir::new().unwrap();
+    let dir = skill(root.path(), "pdf", PDF_FORMS);
+    write(dir.join("scripts/fill.py"), "print('filled')\n");
+    write(
+        dir.join("reference.md"),
+        "Field names are case-sensitive.\n",
+    );
+    write(dir.join(".cache/state"), "");
+
+    let skills = Skills::discover(root.path(), Path::new("/nonexistent"));
+    let pdf = skills.get("pdf-forms").unwrap();
+    assert_eq!(
+        pdf.resources(),
+        vec![
+            PathBuf::from("reference.md"),
+            PathBuf::from("scripts/fill.py")
+        ]
+    );
+
+    let instructions = pdf.instructions().unwrap();
+    assert!(instructions.starts_with("--- Skill: pdf-forms (global, "));
+    assert!(instructions.contains("# PDF forms\n\nRun scripts/fill.py"));
+    assert!(
+        !instructions.contains("description:"),
+        "frontmatter is stripped"
+    );
+    assert!(instructions.contains("- scripts/fill.py\n"));
+
+    assert_eq!(
+        pdf.resource("reference.md").unwrap(),
+        "Field names are case-sensitive.\n"
+    );
+    assert!(pdf.resource("../pdf/SKILL.md").is_err());
+    assert!(pdf.resource("/etc/hosts").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_out_of_the_skill_dir_are_neither_listed_nor_read() {
+    use std::os::unix::fs::symlink;
+
+    let root = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    write(outside.path().join("secret.txt"), "api_key=hunter2\n");
+    let dir = skill(root.path(), "pdf", PDF_FORMS);
+    write(dir.join("scripts/fill.py"), "print('filled')\n");
+    symlink(outside.path().join("secret.txt"), dir.join("secret.txt")).unwrap();
+    symlink(
+        outside.path().join("secret.txt"),
+        dir.join("scripts/secret.txt"),
+    )
+    .unwrap();
+    symlink(dir.join("scripts/fill.py"), dir.join("fill.py")).unwrap();
+    symlink(dir.join("missing.txt"), dir.join("dangling.txt")).unwrap();
+
+    let skills = Skills::discover(root.path(), Path::new("/nonexistent"));
+    let pdf = skills.get("pdf-forms").unwrap();
+    assert_eq!(
+        pdf.resources(),
+        vec![PathBuf::from("fill.py"), PathBuf::from("scripts/fill.py")],
+        "only links that stay inside the skill are listed"
+    );
+
+    assert!(pdf.resource("secret.txt").is_err());
+    assert!(pdf.resource("scripts/secret.txt").is_err());
+    assert_eq!(pdf.resource("fill.py").unwrap(), "print('filled')\n");
+}
+
+#[tokio::test]
+async fn load_skill_tool_fetches_global_and_project_skills() {
+    let root = TempDir::new().unwrap();
+    let global = root.path().join("home/skills");
+    skill(
+        &global,
+        "changelog",
+        "# Changelog\n\nKeep CHANGELOG.md in Keep a Changelog format.\n",
+    );
+    let dir = skill(&root.path().join(".opencrabs/skills"), "pdf", PDF_FORMS);
+    write(dir.join("reference.md"), "Dates are YYYY-MM-DD.\n");
+    let ctx =
+        ToolExecutionContext::new(Uuid::new_v4()).with_working_directory(root.path().to_path_buf());
+    let tool = LoadSkillTool::new(global);
+    assert!(!tool.requires_approval() && tool.is_read_only());
+
+    let result = tool
+        .execute(json!({"name": "pdf-forms"}), &ctx)
+        .await
+        .unwrap();
+    assert!(result.success);
+    assert!(result.output.contains("Run scripts/fill.py"));
+    assert!(result.output.contains("- reference.md"));
+
+    let result = tool
+        .execute(json!({"name": "pdf-forms", "file": "reference.md"}), &ctx)
+        .await
+        .unwrap();
+    assert!(result.success);
+    assert!(result.output.ends_with("Dates are YYYY-MM-DD.\n"));
+
+    // Global skills come from the directory the tool was given
+    let result = tool
+        .execute(json!({"name": "changelog"}), &ctx)
+        .await
+        .unwrap();
+    assert!(result.success);
+    assert!(result.output.contains("Keep a Changelog"));
+
+    let result = tool
+        .execute(json!({"name": "spreadsheets"}), &ctx)
+        .await
+        .unwrap();
+    assert!(!result.success);
+    assert!(
+        result.error.unwrap().contains("pdf-forms"),
+        "unknown names list the skills there are"
+    );
+}
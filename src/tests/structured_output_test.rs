@@ -0,0 +1,302 @@
+//! Tests for `LLMRequest::response_format` and `structured::complete_structured`.
+//!
+//! Covers the native OpenAI `json_schema` mapping, validate-and-retry when a
+//! reply doesn't match the schema, dropping to prompt-only JSON when an
+//! endpoint rejects `response_format`, and Anthropic tool-forcing — plus the
+//! repair loop through its callers, debate round analysis and RSI triage.
+
+use crate::brain::provider::structured::complete_structured;
+use crate::brain::provider::{
+    AnthropicProvider, LLMRequest, Message, OpenAIProvider, ProviderError, ResponseFormat,
+};
+use mockito::Matcher;
+use serde_json::json;
+use std::sync::Arc;
+
+fn format() -> ResponseFormat {
+    ResponseFormat::json_schema(
+        "verdict",
+        json!({
+            "type": "object",
+            "properties": {
+                "approved": {"type": "boolean"},
+                "confidence": {"type": "number", "minimum": 0, "maximum": 1}
+            },
+            "required": ["approved", "confidence"]
+        }),
+    )
+}
+
+fn chat_reply(content: &str) -> String {
+    json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "model": "gpt-4o",
+        "choices": [{"index": 0, "message": {"role": "assistant", "content": content}, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 20, "completion_tokens": 8}
+    })
+    .to_string()
+}
+
+fn openai(server: &mockito::Server) -> OpenAIProvider {
+    OpenAIProvider::with_base_url(
+        "sk-test".to_string(),
+        format!("{}/v1/chat/completions", server.url()),
+    )
+}
+
+fn request() -> LLMRequest {
+    LLMRequest::new("gpt-4o", vec![Message::user("Ship it?")])
+}
+
+#[tokio::test]
+async fn openai_sends_json_schema_and_retries_invalid_reply() {
+    let mut server = mockito::Server::new_async().await;
+    let first = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({
+            "response_format": {"type": "json_schema", "json_schema": {"name": "verdict", "strict": false}},
+            "messages": [{"role": "system"}, {"role": "user", "content": "Ship it?"}]
+        })))
+        .with_status(200)
+        .with_body(chat_reply(r#"{"approved": true, "confidence": 7}"#))
+        .expect(1)
+        .create_async()
+        .await;
+    let repair = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex("above the maximum".into()))
+        .with_status(200)
+        .with_body(chat_reply(
+            "```json\n{\"approved\": true, \"confidence\": 0.7}\n```",
+        ))
+        .expect(1)
+        .create_async()
+        .await;
+
+    let value = complete_structured(&openai(&server), request(), format())
+        .await
+        .unwrap();
+
+    first.assert_async().await;
+    repair.assert_async().await;
+    assert_eq!(value, json!({"approved": true, "confidence": 0.7}));
+}
+
+#[tokio::test]
+async fn rejected_response_format_falls_back_to_prompt() {
+    let mut server = mockito::Server::new_async().await;
+    let native = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex("response_format".into()))
+        .with_status(400)
+        .with_body(
+            json!({"error": {"message": "Unknown parameter: 'response_format'.", "type": "invalid_request_error"}})
+                .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let prompted = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex("must match this JSON Schema".into()))
+        .with_status(200)
+        .with_body(chat_reply(
+            r#"Here you go: {"approved": false, "confidence": 0.4}"#,
+        ))
+        .expect(1)
+        .create_async()
+        .await;
+
+    let value = complete_structured(&openai(&server), request(), format())
+        .await
+        .unwrap();
+
+    native.assert_async().await;
+    prompted.assert_async().await;
+    assert_eq!(value["approved"], false);
+}
+
+#[tokio::test]
+async fn gives_up_after_repeated_invalid_replies() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_body(chat_reply("I think so, yes."))
+        .expect(3)
+        .create_async()
+        .await;
+
+    let err = complete_structured(&openai(&server), request(), format())
+        .await
+        .unwrap_err();
+
+    mock.assert_async().await;
+    assert!(matches!(err, ProviderError::StructuredOutput(msg) if msg.contains("not valid JSON")));
+}
+
+#[tokio::test]
+async fn anthropic_forces_response_tool() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock(
+            "POST",
+            "/publishers/anthropic/models/claude-sonnet-4-5:rawPredict",
+        )
+        .match_body(Matcher::PartialJson(json!({
+            "tool_choice": {"type": "tool", "name": "verdict"},
+            "tools": [{"name": "verdict", "input_schema": {"required": ["approved", "confidence"]}}]
+        })))
+        .with_status(200)
+        .with_body(
+            json!({
+                "id": "msg_1",
+                "type": "message",
+                "role": "assistant",
+                "model": "claude-sonnet-4-5",
+                "content": [{"type": "tool_use", "id": "toolu_1", "name": "verdict", "input": {"approved": true, "confidence": 0.9}}],
+                "stop_reason": "tool_use",
+                "usage": {"input_tokens": 40, "output_tokens": 12}
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let provider = AnthropicProvider::new(String::new())
+        .with_thinking_budget(4096)
+        .with_vertex(server.url(), Arc::new(|| "token".to_string()));
+    let value = complete_structured(
+        &provider,
+        LLMRequest::new("claude-sonnet-4-5", vec![Message::user("Ship it?")]),
+        format(),
+    )
+    .await
+    .unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(value, json!({"approved": true, "confidence": 0.9}));
+}
+
+// --- Callers: debate round analysis and RSI triage ---
+
+fn bee(id: &str, content: &str) -> crate::a2a::debate::BeeResponse {
+    crate::a2a::debate::BeeResponse {
+        bee_id: id.to_string(),
+        endpoint: format!("http://{id}/a2a/v1"),
+        content: content.to_string(),
+        confidence: 0.5,
+        position: None,
+        key_points: vec![],
+    }
+}
+
+#[tokio::test]
+async fn debate_round_analysis_retries_invalid_reply() {
+    let mut server = mockito::Server::new_async().await;
+    let first = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({
+            "response_format": {"json_schema": {"name": "debate_round_analysis"}}
+        })))
+        .with_status(200)
+        .with_body(chat_reply(
+            &json!({
+                "bees": [{"bee_id": "bee-0", "position": "pro", "confidence": 9, "key_points": []}],
+                "agreement_points": [], "contention_points": [], "blind_spots": []
+            })
+            .to_string(),
+        ))
+        .expect(1)
+        .create_async()
+        .await;
+    let repair = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex(r"bees\[0\]\.confidence.*above the maximum".into()))
+        .with_status(200)
+        .with_body(chat_reply(
+            &json!({
+                "bees": [
+                    {"bee_id": "bee-0", "position": "pro", "confidence": 0.9, "key_points": ["recall"]},
+                    {"bee_id": "bee-1", "position": "pro", "confidence": 0.8, "key_points": []}
+                ],
+                "agreement_points": ["memory helps"],
+                "contention_points": [],
+                "blind_spots": ["privacy"]
+            })
+            .to_string(),
+        ))
+        .expect(1)
+        .create_async()
+        .await;
+
+    let mut responses = vec![
+        bee("bee-0", "Yes. Confidence: 0.9"),
+        bee("bee-1", "Yes, mostly."),
+    ];
+    let consensus = crate::a2a::debate::analyze_round(
+        &openai(&server),
+        "gpt-4o",
+        "Persistent memory?",
+        &mut responses,
+        0.8,
+    )
+    .await
+    .unwrap();
+
+    first.assert_async().await;
+    repair.assert_async().await;
+    assert_eq!(responses[0].position.as_deref(), Some("pro"));
+    assert_eq!(responses[0].key_points, vec!["recall"]);
+    assert_eq!(responses[1].confidence, 0.8);
+    assert!(consensus.consensus_reached);
+    assert_eq!(consensus.blind_spots, vec!["privacy"]);
+}
+
+#[tokio::test]
+async fn rsi_analysis_retries_invalid_reply() {
+    let mut server = mockito::Server::new_async().await;
+    let first = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({
+            "response_format": {"json_schema": {"name": "rsi_findings"}}
+        })))
+        .with_status(200)
+        .with_body(chat_reply(
+            &json!({"findings": [{"target_file": "README.md", "problem": "p", "improvement": "i"}]})
+                .to_string(),
+        ))
+        .expect(1)
+        .create_async()
+        .await;
+    let repair = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::Regex(r"target_file.*is not one of".into()))
+        .with_status(200)
+        .with_body(chat_reply(
+            &json!({"findings": [{
+                "target_file": "TOOLS.md",
+                "problem": "edit_file fails on stale old_string",
+                "improvement": "Re-read the file before editing it"
+            }]})
+            .to_string(),
+        ))
+        .expect(1)
+        .create_async()
+        .await;
+
+    let findings = crate::brain::rsi::analyze_opportunities(
+        &openai(&server),
+        "gpt-4o",
+        &["Tool 'edit_file' has 40% failure rate".to_string()],
+    )
+    .await
+    .unwrap();
+
+    first.assert_async().await;
+    repair.assert_async().await;
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].target_file, "TOOLS.md");
+}
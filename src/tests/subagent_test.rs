@@ -366,6 +366,7 @@ mod send_input_tool {
             sudo_callback: None,
             shared_working_directory: None,
             service_context: None,
+            pre_tool_use_done: false,
         }
     }
 
@@ -517,6 +518,7 @@ mod close_agent_tool {
             sudo_callback: None,
             shared_working_directory: None,
             service_context: None,
+            pre_tool_use_done: false,
         }
     }
 
@@ -641,6 +643,7 @@ mod wait_agent_tool {
             sudo_callback: None,
             shared_working_directory: None,
             service_context: None,
+            pre_tool_use_done: false,
         }
     }
 
@@ -1255,6 +1258,7 @@ mod team_delete_tool {
             sudo_callback: None,
             shared_working_directory: None,
             service_context: None,
+            pre_tool_use_done: false,
         }
     }
 
@@ -1379,6 +1383,7 @@ mod team_broadcast_tool {
             sudo_callback: None,
             shared_working_directory: None,
             service_context: None,
+            pre_tool_use_done: false,
         }
     }
 
@@ -0,0 +1,184 @@
+//! Tests for `LLMRequest::tool_choice` on the HTTP providers.
+//!
+//! Each provider spells "must call a tool" / "call this tool" / "no tools"
+//! differently; these lock in the wire shape for OpenAI-compatible chat,
+//! the Responses API and Gemini (incl. Vertex).
+
+use crate::brain::provider::{
+    GeminiProvider, LLMRequest, Message, OpenAIProvider, Provider, Tool, ToolChoice,
+};
+use mockito::Matcher;
+use serde_json::json;
+use std::sync::Arc;
+
+fn tools() -> Vec<Tool> {
+    vec![Tool {
+        name: "telegram_send".into(),
+        description: "Send a Telegram message".into(),
+        input_schema: json!({"type": "object", "properties": {"text": {"type": "string"}}}),
+    }]
+}
+
+fn request(model: &str, choice: ToolChoice) -> LLMRequest {
+    LLMRequest::new(model, vec![Message::user("send the report")])
+        .with_tools(tools())
+        .with_tool_choice(choice)
+}
+
+fn chat_reply() -> String {
+    json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "model": "gpt-4o",
+        "choices": [{"index": 0, "message": {"role": "assistant", "content": "ok"}, "finish_reason": "stop"}],
+        "usage": {"prompt_tokens": 5, "completion_tokens": 1}
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn openai_chat_names_the_forced_function() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({
+            "tool_choice": {"type": "function", "function": {"name": "telegram_send"}}
+        })))
+        .with_status(200)
+        .with_body(chat_reply())
+        .create_async()
+        .await;
+
+    OpenAIProvider::with_base_url(
+        "sk-test".to_string(),
+        format!("{}/v1/chat/completions", server.url()),
+    )
+    .complete(request("gpt-4o", ToolChoice::Tool("telegram_send".into())))
+    .await
+    .unwrap();
+
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn openai_chat_required_and_default_auto() {
+    let mut server = mockito::Server::new_async().await;
+    let required = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({"tool_choice": "required"})))
+        .with_status(200)
+        .with_body(chat_reply())
+        .expect(1)
+        .create_async()
+        .await;
+    let auto = server
+        .mock("POST", "/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({"tool_choice": "auto"})))
+        .with_status(200)
+        .with_body(chat_reply())
+        .expect(1)
+        .create_async()
+        .await;
+
+    let provider = OpenAIProvider::with_base_url(
+        "sk-test".to_string(),
+        format!("{}/v1/chat/completions", server.url()),
+    );
+    provider
+        .complete(request("gpt-4o", ToolChoice::Required))
+        .await
+        .unwrap();
+    provider
+        .complete(
+            LLMRequest::new("gpt-4o", vec![Message::user("send the report")]).with_tools(tools()),
+        )
+        .await
+        .unwrap();
+
+    required.assert_async().await;
+    auto.assert_async().await;
+}
+
+#[tokio::test]
+async fn responses_api_uses_flat_function_choice() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("POST", "/v1/responses")
+        .match_body(Matcher::PartialJson(json!({
+            "tool_choice": {"type": "function", "name": "telegram_send"}
+        })))
+        .with_status(200)
+        .with_body(
+            json!({
+                "id": "resp_1",
+                "model": "gpt-4.1",
+                "status": "completed",
+                "output": [{"type": "message", "role": "assistant", "content": [{"type": "output_text", "text": "ok"}]}],
+                "usage": {"input_tokens": 5, "output_tokens": 1}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+
+    OpenAIProvider::with_base_url(
+        "sk-test".to_string(),
+        format!("{}/v1/chat/completions", server.url()),
+    )
+    .with_responses_api()
+    .complete(request("gpt-4.1", ToolChoice::Tool("telegram_send".into())))
+    .await
+    .unwrap();
+
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn gemini_maps_choice_to_function_calling_config() {
+    let mut server = mockito::Server::new_async().await;
+    let reply = json!({
+        "candidates": [{"content": {"role": "model", "parts": [{"text": "ok"}]}, "finishReason": "STOP"}],
+        "usageMetadata": {"promptTokenCount": 5, "candidatesTokenCount": 1}
+    })
+    .to_string();
+    let forced = server
+        .mock("POST", "/models/gemini-2.5-flash:generateContent")
+        .match_body(Matcher::PartialJson(json!({
+            "toolConfig": {"functionCallingConfig": {
+                "mode": "ANY",
+                "allowedFunctionNames": ["telegram_send"]
+            }}
+        })))
+        .with_status(200)
+        .with_body(reply.clone())
+        .expect(1)
+        .create_async()
+        .await;
+    let none = server
+        .mock("POST", "/models/gemini-2.5-flash:generateContent")
+        .match_body(Matcher::PartialJson(json!({
+            "toolConfig": {"functionCallingConfig": {"mode": "NONE"}}
+        })))
+        .with_status(200)
+        .with_body(reply)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let provider = GeminiProvider::new(String::new())
+        .with_vertex(server.url(), Arc::new(|| "token".to_string()));
+    provider
+        .complete(request(
+            "gemini-2.5-flash",
+            ToolChoice::Tool("telegram_send".into()),
+        ))
+        .await
+        .unwrap();
+    provider
+        .complete(request("gemini-2.5-flash", ToolChoice::None))
+        .await
+        .unwrap();
+
+    forced.assert_async().await;
+    none.assert_async().await;
+}
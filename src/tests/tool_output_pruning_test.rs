@@ -0,0 +1,196 @@
+//! Tests for tool-output pruning (`brain::agent::pruning`).
+//!
+//! Before summarizing with an LLM, old tool results are elided to a head
+//! and tail and repeated reads of the same file keep only the newest.
+
+use crate::brain::agent::pruning::{self, PRUNED_PREFIX, PruneReport};
+use crate::brain::provider::{ContentBlock, Message, Role};
+use crate::config::{CompactionConfig, CompactionStrategy};
+use serde_json::{Value, json};
+
+/// A tool call and its result, as the tool loop adds them
+fn call(id: &str, name: &str, input: Value, output: &str) -> [Message; 2] {
+    [
+        Message {
+            role: Role::Assistant,
+            content: vec![ContentBlock::ToolUse {
+                id: id.to_string(),
+                name: name.to_string(),
+                input,
+            }],
+        },
+        Message {
+            role: Role::User,
+            content: vec![ContentBlock::ToolResult {
+                tool_use_id: id.to_string(),
+                content: output.to_string(),
+                is_error: None,
+            }],
+        },
+    ]
+}
+
+fn result(messages: &[Message], id: &str) -> String {
+    messages
+        .iter()
+        .flat_map(|m| &m.content)
+        .find_map(|block| match block {
+            ContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                ..
+            } if tool_use_id == id => Some(content.clone()),
+            _ => None,
+        })
+        .unwrap()
+}
+
+fn numbered_lines(n: usize) -> String {
+    (1..=n)
+        .map(|i| format!("line {i}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn config(keep_recent: usize) -> CompactionConfig {
+    CompactionConfig {
+        keep_recent,
+        elide_over_chars: 100,
+        head_lines: 2,
+        tail_lines: 1,
+        ..CompactionConfig::default()
+    }
+}
+
+#[test]
+fn old_long_results_keep_their_head_and_tail() {
+    let long = numbered_lines(50);
+    let mut messages: Vec<Message> = [
+        call("1", "bash", json!({"command": "cargo test"}), &long),
+        call("2", "bash", json!({"command": "echo hi"}), "hi"),
+        call("3", "edit_file", json!({"path": "a.rs"}), &long),
+        call("4", "bash", json!({"command": "cargo build"}), &long),
+    ]
+    .concat();
+
+    let report = pruning::prune_tool_results(&mut messages, &config(1));
+    assert_eq!(report.elided, 1);
+    assert_eq!(report.deduplicated, 0);
+
+    assert_eq!(
+        result(&messages, "1"),
+        "[pruned: bash `cargo test` output elided, 50 lines / 390 chars]\n\
+         line 1\nline 2\n… 47 lines …\nline 50"
+    );
+    assert_eq!(result(&messages, "2"), "hi", "short output stays");
+    assert_eq!(result(&messages, "3"), long, "unlisted tools stay");
+    assert_eq!(result(&messages, "4"), long, "the newest results stay");
+
+    // Pruning again changes nothing
+    let again = pruning::prune_tool_results(&mut messages, &config(1));
+    assert_eq!(again, PruneReport::default());
+    assert_eq!(pruning::count_pruned(&messages), 1);
+}
+
+#[test]
+fn notice_counts_only_results_pruned_since_the_last_turn() {
+    let long = numbered_lines(50);
+    let bash = |id: &str| call(id, "bash", json!({"command": "cargo test"}), &long);
+
+    // Each turn rebuilds the context and prunes it from scratch
+    let mut first_turn: Vec<Message> = [bash("1"), bash("2"), bash("3")].concat();
+    let mut first = pruning::prune_tool_results(&mut first_turn, &config(1));
+    first.tokens_saved = 800;
+    let mut second_turn: Vec<Message> = [bash("1"), bash("2"), bash("3"), bash("4")].concat();
+    let mut second = pruning::prune_tool_results(&mut second_turn, &config(1));
+    second.tokens_saved = 1_200;
+    assert_eq!((first.elided, second.elided), (2, 3));
+
+    let delta = second.since(&first);
+    assert_eq!(
+        delta,
+        PruneReport {
+            elided: 1,
+            deduplicated: 0,
+            tokens_saved: 400,
+        }
+    );
+    assert_eq!(
+        delta.summary(),
+        "Context pruning: elided 1 old tool output(s) (~400 tokens freed)"
+    );
+    assert_eq!(first + delta, second);
+    assert_eq!(first.since(&second), PruneReport::default());
+}
+
+#[test]
+fn repeated_reads_keep_only_the_newest() {
+    let read = |id: &str, input: Value, output: &str| call(id, "read_file", input, output);
+    let mut messages: Vec<Message> = [
+        read("1", json!({"path": "src/main.rs"}), "fn main() {}"),
+        read(
+            "2",
+            json!({"path": "src/main.rs", "offset": 10}),
+            "fn x() {}",
+        ),
+        read("3", json!({"path": "src/main.rs"}), "fn main() { run() }"),
+        read("4", json!({"path": "src/lib.rs"}), "pub mod a;"),
+    ]
+    .concat();
+    // A failed re-read doesn't make the earlier result stale
+    messages.extend(read("5", json!({"path": "src/lib.rs"}), "denied"));
+    if let ContentBlock::ToolResult { is_error, .. } = &mut messages[9].content[0] {
+        *is_error = Some(true);
+    }
+
+    let report = pruning::prune_tool_results(&mut messages, &config(0));
+    assert_eq!(report.deduplicated, 1);
+    assert!(
+        result(&messages, "1").starts_with(PRUNED_PREFIX),
+        "{}",
+        result(&messages, "1")
+    );
+    assert!(result(&messages, "1").contains("read_file `src/main.rs`"));
+    assert_eq!(result(&messages, "2"), "fn x() {}", "other ranges stay");
+    assert_eq!(result(&messages, "3"), "fn main() { run() }");
+    assert_eq!(result(&messages, "4"), "pub mod a;");
+
+    assert!(report.summary().contains("dropped 1 repeated read(s)"));
+}
+
+#[test]
+fn long_lines_are_clipped() {
+    let wide = format!("{}\n{}", "x".repeat(1000), "y".repeat(1000));
+    let mut messages: Vec<Message> = [call(
+        "1",
+        "browser_content",
+        json!({"url": "https://a.io"}),
+        &wide,
+    )]
+    .concat();
+
+    pruning::prune_tool_results(&mut messages, &config(0));
+    let pruned = result(&messages, "1");
+    assert!(pruned.len() < 500, "{} chars", pruned.len());
+    assert!(pruned.starts_with("[pruned: browser_content `https://a.io` output elided"));
+}
+
+#[test]
+fn config_parses_strategy_and_thresholds() {
+    let config: CompactionConfig = toml::from_str(
+        r#"
+        strategy = "summarize"
+        prune_at = 0.4
+        tools = ["bash"]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.strategy, CompactionStrategy::Summarize);
+    assert_eq!(config.prune_at, 0.4);
+    assert_eq!(config.tools, vec!["bash"]);
+    assert_eq!(config.keep_recent, 6, "unset fields keep their defaults");
+
+    let default = CompactionConfig::default();
+    assert_eq!(default.strategy, CompactionStrategy::PruneThenSummarize);
+    assert!(default.tools.iter().any(|t| t == "read_file"));
+}
@@ -514,7 +514,7 @@ impl App {
                 self.ps.base_url,
             );
 
-            let is_cli_provider = self.ps.is_cli();
+            let is_keyless_provider = self.ps.is_keyless();
 
             if self.ps.focused_field == 0 {
                 // On provider field - save config, DON'T close dialog.
@@ -527,8 +527,8 @@ impl App {
                 {
                     self.push_system_message(format!("Error: {}", e));
                 } else {
-                    // CLI providers have no API key — skip straight to model field
-                    self.ps.focused_field = if is_cli_provider { 2 } else { 1 };
+                    // Keyless providers (CLI, Ollama) — skip straight to model field
+                    self.ps.focused_field = if is_keyless_provider { 2 } else { 1 };
                 }
             } else if self.ps.focused_field == 1 && is_zhipu {
                 // z.ai GLM: field 1 is endpoint type, move to field 2 (api_key)
@@ -623,6 +623,16 @@ impl App {
                     key_changed,
                 )
                 .await?;
+            } else if self.ps.provider_id() == "ollama" {
+                // Ollama: make sure the model is installed first — the save
+                // runs when OllamaPullComplete arrives.
+                if self.ps.ollama_pull.is_none() {
+                    self.ps.ollama_pull = Some("Checking installed models…".to_string());
+                    super::onboarding::spawn_ollama_pull(
+                        self.ps.selected_model_name().to_string(),
+                        self.event_sender(),
+                    );
+                }
             } else {
                 // Non-custom: on model field — save and close
                 let key_changed =
@@ -640,7 +650,7 @@ impl App {
 
     /// Save provider selection to config and reload agent service
     /// If `close_dialog` is false, stays in model selector (for step 1 and 2)
-    async fn save_provider_selection(
+    pub(super) async fn save_provider_selection(
         &mut self,
         provider_idx: usize,
         key_changed: bool,
@@ -701,6 +711,9 @@ impl App {
         if let Some(ref mut p) = config.providers.opencode_cli {
             p.enabled = false;
         }
+        if let Some(ref mut p) = config.providers.ollama {
+            p.enabled = false;
+        }
 
         // Get existing key from config if not changing. Routes by provider
         // id so reordering PROVIDERS doesn't break the match.
@@ -785,7 +798,7 @@ impl App {
         };
         // Indices: 0=Anthropic, 1=OpenAI, 2=GitHub, 3=Gemini, 4=OpenRouter,
         // 5=Minimax, 6=z.ai GLM, 7=Claude CLI, 8=OpenCode CLI, 9=Qwen (native),
-        // 10=Custom (CUSTOM_PROVIDER_IDX). Keep this in sync with PROVIDERS.
+        // 10=Ollama, 11=Custom (CUSTOM_PROVIDER_IDX). Keep this in sync with PROVIDERS.
         match provider_idx {
             0 => {
                 // Anthropic
@@ -915,7 +928,18 @@ impl App {
                     ..merged
                 });
             }
-            10 if !self.ps.custom_name.is_empty() => {
+            10 => {
+                // Ollama — no key. Keep base_url / num_ctx / keep_alive the
+                // user set by hand; only the model and enabled flag change.
+                let merged = config.providers.ollama.clone().unwrap_or_default();
+                config.providers.ollama = Some(ProviderConfig {
+                    enabled: true,
+                    api_key: None,
+                    default_model: Some(default_model.to_string()),
+                    ..merged
+                });
+            }
+            idx if idx == CUSTOM_PROVIDER_IDX && !self.ps.custom_name.is_empty() => {
                 // Edit-in-place semantics: if we're editing an existing
                 // entry (`editing_custom_key`), write back to that key —
                 // even when the user renamed the name field. Rename is a
@@ -976,7 +1000,8 @@ impl App {
             7 => "providers.claude_cli",
             8 => "providers.opencode_cli",
             9 => "providers.qwen",
-            10 => {
+            10 => "providers.ollama",
+            idx if idx == CUSTOM_PROVIDER_IDX => {
                 // Resolve custom provider name: UI field > config active
                 let cname = if !self.ps.custom_name.is_empty() {
                     self.ps.custom_name.clone()
@@ -1014,6 +1039,7 @@ impl App {
             "providers.claude_cli",
             "providers.opencode_cli",
             "providers.qwen",
+            "providers.ollama",
         ] {
             if s != section {
                 try_write(s, "enabled", "false");
@@ -1098,6 +1124,7 @@ impl App {
                 "providers.claude_cli" => cfg.providers.claude_cli.map(|p| p.enabled),
                 "providers.opencode_cli" => cfg.providers.opencode_cli.map(|p| p.enabled),
                 "providers.qwen" => cfg.providers.qwen.map(|p| p.enabled),
+                "providers.ollama" => cfg.providers.ollama.map(|p| p.enabled),
                 s if s.starts_with("providers.custom.") => {
                     let name = s.trim_start_matches("providers.custom.");
                     cfg.providers
@@ -2066,6 +2093,12 @@ impl App {
                         }
                     }
                 }
+                WizardAction::PullOllamaModel => {
+                    if let Some(ref wizard) = self.onboarding {
+                        let model = wizard.ps.selected_model_name().to_string();
+                        super::onboarding::spawn_ollama_pull(model, self.event_sender());
+                    }
+                }
                 WizardAction::None => {
                     // Stay in onboarding
                 }
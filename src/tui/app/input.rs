@@ -774,7 +774,7 @@ impl App {
         }
 
         // Intercept keys when an inline approval is pending
-        // Options: Yes(0), Always(1), No(2)
+        // Options: Yes(0), Always(1), Allow pattern(2), No(3)
         if self.has_pending_approval() {
             if keys::is_left(&event) || keys::is_up(&event) {
                 // Navigate options left
@@ -797,25 +797,38 @@ impl App {
                     .find_map(|m| m.approval.as_mut())
                     .filter(|a| a.state == ApprovalState::Pending)
                 {
-                    approval.selected_option = (approval.selected_option + 1).min(2);
+                    approval.selected_option = (approval.selected_option + 1).min(3);
                 }
                 return Ok(());
             } else if keys::is_enter(&event) || keys::is_submit(&event) {
-                // Confirm: Yes(0)=approve once, Always(1)=approve always, No(2)=deny
+                // Confirm: Yes(0)=approve once, Always(1)=approve always,
+                // Allow pattern(2)=persist an allow rule, No(3)=deny
                 let approval_data: Option<(
                     Uuid,
                     usize,
                     mpsc::UnboundedSender<ToolApprovalResponse>,
+                    String,
+                    Option<String>,
                 )> = self
                     .messages
                     .iter()
                     .rev()
                     .find_map(|m| m.approval.as_ref())
                     .filter(|a| a.state == ApprovalState::Pending)
-                    .map(|a| (a.request_id, a.selected_option, a.response_tx.clone()));
+                    .map(|a| {
+                        (
+                            a.request_id,
+                            a.selected_option,
+                            a.response_tx.clone(),
+                            a.suggested_rule.clone(),
+                            a.ask_rule.clone(),
+                        )
+                    });
 
-                if let Some((request_id, selected, response_tx)) = approval_data {
-                    if selected == 2 {
+                if let Some((request_id, selected, response_tx, suggested_rule, ask_rule)) =
+                    approval_data
+                {
+                    if selected == 3 {
                         // "No" — deny
                         let response = ToolApprovalResponse {
                             request_id,
@@ -832,11 +845,11 @@ impl App {
                             .event_sender()
                             .send(TuiEvent::ToolApprovalResponse(response));
                     } else {
-                        // "Yes" (0) or "Always" (1)
-                        let option = if selected == 1 {
-                            ApprovalOption::AllowAlways
-                        } else {
-                            ApprovalOption::AllowOnce
+                        // "Yes" (0), "Always" (1) or "Allow pattern" (2)
+                        let option = match selected {
+                            1 => ApprovalOption::AllowAlways,
+                            2 => ApprovalOption::AllowPattern,
+                            _ => ApprovalOption::AllowOnce,
                         };
                         if matches!(option, ApprovalOption::AllowAlways) {
                             self.approval_auto_session = true;
@@ -845,6 +858,12 @@ impl App {
                                 "Auto-approve enabled for this session. Use /approve to reset."
                                     .to_string(),
                             );
+                        } else if matches!(option, ApprovalOption::AllowPattern) {
+                            crate::utils::persist_allow_rule(&suggested_rule, ask_rule.as_deref());
+                            self.push_system_message(format!(
+                                "Added permission rule `allow {}` to config.toml.",
+                                suggested_rule
+                            ));
                         }
                         let response = ToolApprovalResponse {
                             request_id,
@@ -1051,8 +1070,11 @@ impl App {
             // Check for slash commands before sending to LLM
             let content = self.input_buffer.clone();
             if self.handle_slash_command(content.trim()).await {
-                self.input_buffer.clear();
-                self.cursor_position = 0;
+                // Keep input a command put there (/rewind, /edit)
+                if self.input_buffer == content {
+                    self.input_buffer.clear();
+                    self.cursor_position = 0;
+                }
                 self.slash_suggestions_active = false;
                 self.dismiss_emoji_picker();
                 return Ok(());
@@ -1152,7 +1174,22 @@ impl App {
                 }
                 msg
             };
+            // Sending an edited earlier message: branch off the old replies first
+            if let Some(message_id) = self.editing_message.take()
+                && !self.branch_for_edit(message_id).await
+            {
+                self.input_buffer = content;
+                self.cursor_position = self.input_buffer.len();
+                self.editing_message = Some(message_id);
+                return Ok(());
+            }
             self.send_message(send_content).await?;
+        } else if keys::is_submit(&event)
+            && self.attachments.is_empty()
+            && let Some(idx) = self.selected_message_idx
+        {
+            // Enter on an empty input with a message selected — edit it
+            self.edit_selected_message(idx).await;
         } else if keys::is_cancel(&event) {
             // When processing, double-Escape aborts the operation
             if self.is_processing {
@@ -1363,7 +1400,8 @@ impl App {
                 self.error_message_shown_at = None;
                 self.escape_pending_at = None;
             } else if self.input_buffer.is_empty() {
-                // Nothing to clear, just dismiss error
+                // Nothing to clear, just dismiss error (and any edit)
+                self.editing_message = None;
                 self.error_message = None;
                 self.error_message_shown_at = None;
                 self.escape_pending_at = None;
@@ -1378,6 +1416,7 @@ impl App {
                     self.cursor_position = 0;
                     self.attachments.clear();
                     self.focused_attachment = None;
+                    self.editing_message = None;
                     self.error_message = None;
                     self.error_message_shown_at = None;
                     self.escape_pending_at = None;
@@ -1809,6 +1848,7 @@ impl App {
                 self.queued_messages.remove(&session_id);
                 self.session_cancel_tokens.remove(&session_id);
                 self.processing_sessions.remove(&session_id);
+                self.agent_service.clear_pruned_results(session_id);
                 if is_current {
                     self.current_session = None;
                     self.messages.clear();
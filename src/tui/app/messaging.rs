@@ -5,6 +5,7 @@ use super::events::{AppMode, ToolApprovalResponse, TuiEvent};
 use super::onboarding::OnboardingWizard;
 use super::*;
 use crate::brain::SelfUpdater;
+use crate::services::{CheckpointService, checkpoint};
 use anyhow::Result;
 use serde_json::Value;
 use tokio_util::sync::CancellationToken;
@@ -111,6 +112,13 @@ impl App {
             self.session_cancel_tokens.insert(old_session.id, old_token);
         }
 
+        // Outgoing session's pruning totals — its next turn starts over
+        if let Some(ref old_session) = self.current_session
+            && old_session.id != session_id
+        {
+            self.agent_service.clear_pruned_results(old_session.id);
+        }
+
         // Cache outgoing session's messages for inactive pane rendering
         if self.pane_manager.is_split()
             && let Some(ref old_session) = self.current_session
@@ -144,6 +152,7 @@ impl App {
         }
 
         self.current_session = Some(session.clone());
+        self.editing_message = None;
         self.set_plan_file_for_session(session.id);
         // Sync is_processing flag with per-session state
         self.is_processing = self.processing_sessions.contains(&session.id);
@@ -442,7 +451,7 @@ impl App {
     pub(crate) async fn load_sessions(&mut self) -> Result<()> {
         use crate::db::repository::{SessionListOptions, UsageLedgerRepository};
 
-        self.sessions = self
+        let sessions = self
             .session_service
             .list_sessions(SessionListOptions {
                 include_archived: false,
@@ -450,6 +459,10 @@ impl App {
                 offset: 0,
             })
             .await?;
+        // Forks are listed under the session they branched from
+        (self.sessions, self.session_fork_depths) = crate::services::session::fork_tree(sessions)
+            .into_iter()
+            .unzip();
 
         // Load all-time usage from the ledger (survives session deletes)
         let ledger = UsageLedgerRepository::new(self.session_service.pool());
@@ -478,6 +491,236 @@ impl App {
         Ok(())
     }
 
+    /// `/undo`: revert the file changes of the last turn that made any
+    pub(crate) async fn undo_file_changes(&mut self) {
+        let Some(session_id) = self.current_session.as_ref().map(|s| s.id) else {
+            return;
+        };
+        if self.is_processing {
+            self.push_system_message("⚠️ Wait for the current response before /undo.".into());
+            return;
+        }
+        let checkpoints = CheckpointService::new(self.agent_service.context().clone());
+        let message = match checkpoints.undo(session_id).await {
+            Ok(Some(report)) => format!(
+                "↩️ Undid the last turn's file changes.\n{}",
+                report.summary()
+            ),
+            Ok(None) => "Nothing to undo — no file changes recorded in this session.".to_string(),
+            Err(e) => format!("⚠️ Undo failed: {}", e),
+        };
+        self.push_system_message(message);
+    }
+
+    /// `/rewind [n]`: without `n`, list recent user messages; with it,
+    /// restore files and conversation to just before the `n`th most recent
+    /// one and put its text back in the input box.
+    pub(crate) async fn rewind_to_message(&mut self, arg: Option<&str>) {
+        let Some(session_id) = self.current_session.as_ref().map(|s| s.id) else {
+            return;
+        };
+        if self.is_processing {
+            self.push_system_message("⚠️ Wait for the current response before /rewind.".into());
+            return;
+        }
+        let checkpoints = CheckpointService::new(self.agent_service.context().clone());
+        let points = match checkpoints.rewind_points(session_id).await {
+            Ok(points) => points,
+            Err(e) => {
+                self.push_system_message(format!("⚠️ Rewind failed: {}", e));
+                return;
+            }
+        };
+
+        let Some(n) = arg.and_then(|a| a.parse::<usize>().ok()) else {
+            self.push_system_message(checkpoint::rewind_menu(&points));
+            return;
+        };
+        let Some(message) = checkpoint::rewind_point(&points, n) else {
+            self.push_system_message(format!("⚠️ No message #{} to rewind to.", n));
+            return;
+        };
+
+        match checkpoints.rewind(session_id, message).await {
+            Ok(report) => {
+                if let Err(e) = self.load_session(session_id).await {
+                    tracing::warn!("Failed to reload session after rewind: {}", e);
+                }
+                self.input_buffer = message.content.clone();
+                self.cursor_position = self.input_buffer.len();
+                self.push_system_message(format!("⏪ Rewound.\n{}", report.summary()));
+            }
+            Err(e) => self.push_system_message(format!("⚠️ Rewind failed: {}", e)),
+        }
+    }
+
+    /// `/edit [n]`: put the `n`th most recent user message (default: the
+    /// last one, numbered as in `/rewind`) in the input box for editing.
+    pub(crate) async fn edit_message(&mut self, arg: Option<&str>) {
+        let Some(session_id) = self.current_session.as_ref().map(|s| s.id) else {
+            return;
+        };
+        let n = match arg.map(str::parse::<usize>) {
+            None => 1,
+            Some(Ok(n)) => n,
+            Some(Err(_)) => {
+                self.push_system_message("Usage: /edit [n] — n as listed by /rewind".into());
+                return;
+            }
+        };
+        let checkpoints = CheckpointService::new(self.agent_service.context().clone());
+        let points = checkpoints
+            .rewind_points(session_id)
+            .await
+            .unwrap_or_default();
+        match checkpoint::rewind_point(&points, n) {
+            Some(message) => self.begin_edit(message.clone()),
+            None => self.push_system_message(format!("⚠️ No message #{} to edit.", n)),
+        }
+    }
+
+    /// Edit the user message at `idx` in the chat (selected with a click,
+    /// then Enter on an empty input).
+    pub(crate) async fn edit_selected_message(&mut self, idx: usize) {
+        let Some(session_id) = self.current_session.as_ref().map(|s| s.id) else {
+            return;
+        };
+        let Some(selected) = self.messages.get(idx).filter(|m| m.role == "user") else {
+            self.push_system_message("Only your own messages can be edited.".into());
+            return;
+        };
+
+        // Messages loaded from the database keep their id; ones sent since
+        // are found by their position among the user messages instead.
+        let message = match self.message_service.get_message(selected.id).await {
+            Ok(Some(message)) if message.session_id == session_id => Some(message),
+            _ => {
+                let n = self.messages[idx..]
+                    .iter()
+                    .filter(|m| m.role == "user")
+                    .count();
+                let checkpoints = CheckpointService::new(self.agent_service.context().clone());
+                let points = checkpoints
+                    .rewind_points(session_id)
+                    .await
+                    .unwrap_or_default();
+                checkpoint::rewind_point(&points, n).cloned()
+            }
+        };
+        self.selected_message_idx = None;
+        match message {
+            Some(message) => self.begin_edit(message),
+            None => self.push_system_message("⚠️ Couldn't find that message to edit.".into()),
+        }
+    }
+
+    fn begin_edit(&mut self, message: crate::db::models::Message) {
+        if !self.input_buffer.trim().is_empty() {
+            self.input_history_stash = std::mem::take(&mut self.input_buffer);
+        }
+        self.input_buffer = message.content;
+        self.cursor_position = self.input_buffer.len();
+        self.editing_message = Some(message.id);
+    }
+
+    /// Before an edited message is sent: move the conversation from the
+    /// original message on into a "(before edit)" branch session, so the
+    /// regenerated reply starts from clean history. False if it failed.
+    pub(crate) async fn branch_for_edit(&mut self, message_id: Uuid) -> bool {
+        let Some(session_id) = self.current_session.as_ref().map(|s| s.id) else {
+            return false;
+        };
+        if self.is_processing {
+            self.push_system_message("⚠️ Wait for the current response before editing.".into());
+            return false;
+        }
+        let branch = match self.message_service.get_message_required(message_id).await {
+            Ok(message) => {
+                self.session_service
+                    .branch_before_edit(session_id, &message)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        match branch {
+            Ok(branch) => {
+                if let Err(e) = self.load_session(session_id).await {
+                    tracing::warn!("Failed to reload session after edit: {}", e);
+                }
+                self.push_system_message(format!(
+                    "✏️ Regenerating from the edited message. The previous replies are kept in \"{}\" (/sessions).",
+                    branch.title.unwrap_or_default()
+                ));
+                true
+            }
+            Err(e) => {
+                self.push_system_message(format!("⚠️ Edit failed: {}", e));
+                false
+            }
+        }
+    }
+
+    /// `/fork [n]`: branch the conversation into a new session and switch
+    /// to it — the whole history, or with `n` only what came before the
+    /// `n`th most recent message (numbered as in `/rewind`), whose text goes
+    /// back in the input box to try differently.
+    pub(crate) async fn fork_current_session(&mut self, arg: Option<&str>) {
+        let Some(session_id) = self.current_session.as_ref().map(|s| s.id) else {
+            return;
+        };
+        let before = match arg {
+            None => None,
+            Some(arg) => {
+                let checkpoints = CheckpointService::new(self.agent_service.context().clone());
+                let points = checkpoints
+                    .rewind_points(session_id)
+                    .await
+                    .unwrap_or_default();
+                match arg
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| checkpoint::rewind_point(&points, n))
+                {
+                    Some(message) => Some(message.clone()),
+                    None => {
+                        self.push_system_message(format!("⚠️ No message #{} to fork at.", arg));
+                        return;
+                    }
+                }
+            }
+        };
+
+        let fork = match self
+            .session_service
+            .fork_point(session_id, before.as_ref())
+            .await
+        {
+            Ok(Some(at)) => self.session_service.fork_session(session_id, at.id).await,
+            Ok(None) => {
+                self.push_system_message("Nothing to fork yet — use /new to start over.".into());
+                return;
+            }
+            Err(e) => Err(e),
+        };
+        match fork {
+            Ok(fork) => {
+                if let Err(e) = self.load_session(fork.id).await {
+                    self.push_system_message(format!("⚠️ Failed to open fork: {}", e));
+                    return;
+                }
+                if let Some(message) = before {
+                    self.input_buffer = message.content;
+                    self.cursor_position = self.input_buffer.len();
+                }
+                self.push_system_message(format!(
+                    "🌿 Forked into \"{}\". The original session is unchanged.",
+                    fork.title.unwrap_or_default()
+                ));
+            }
+            Err(e) => self.push_system_message(format!("⚠️ Fork failed: {}", e)),
+        }
+    }
+
     /// Handle slash commands locally (returns true if handled)
     pub(crate) async fn handle_slash_command(&mut self, input: &str) -> bool {
         let cmd = input.split_whitespace().next().unwrap_or("");
@@ -585,6 +828,24 @@ impl App {
                 ));
                 true
             }
+            "/undo" => {
+                self.undo_file_changes().await;
+                true
+            }
+            "/rewind" => {
+                self.rewind_to_message(input.split_whitespace().nth(1))
+                    .await;
+                true
+            }
+            "/edit" => {
+                self.edit_message(input.split_whitespace().nth(1)).await;
+                true
+            }
+            "/fork" => {
+                self.fork_current_session(input.split_whitespace().nth(1))
+                    .await;
+                true
+            }
             "/rebuild" => {
                 self.push_system_message(
                     "🔨 Building from source... (streaming output below)".to_string(),
@@ -93,6 +93,22 @@ pub const SLASH_COMMANDS: &[SlashCommand] = &[
         name: "/compact",
         description: "Compact context now",
     },
+    SlashCommand {
+        name: "/undo",
+        description: "Revert the last turn's file changes",
+    },
+    SlashCommand {
+        name: "/rewind",
+        description: "Restore files & chat to an earlier message",
+    },
+    SlashCommand {
+        name: "/edit",
+        description: "Edit an earlier message & regenerate",
+    },
+    SlashCommand {
+        name: "/fork",
+        description: "Branch this chat into a new session",
+    },
     SlashCommand {
         name: "/rebuild",
         description: "Build & restart from source",
@@ -117,6 +133,8 @@ pub enum ApprovalOption {
     AllowOnce,
     AllowForSession,
     AllowAlways,
+    /// Persist an `allow` permission rule for the suggested pattern
+    AllowPattern,
 }
 
 /// State of an inline approval request
@@ -134,11 +152,15 @@ pub struct ApprovalData {
     pub tool_description: String,
     pub tool_input: Value,
     pub capabilities: Vec<String>,
+    /// `ask` permission rule that asked for this prompt
+    pub ask_rule: Option<String>,
+    /// Rule offered as "always allow this pattern"
+    pub suggested_rule: String,
     pub request_id: Uuid,
     pub response_tx: mpsc::UnboundedSender<ToolApprovalResponse>,
     pub requested_at: std::time::Instant,
     pub state: ApprovalState,
-    /// 0-2, arrow key navigation
+    /// 0-3, arrow key navigation
     pub selected_option: usize,
     /// V key toggle
     pub show_details: bool,
@@ -241,6 +263,8 @@ pub struct App {
     pub current_session: Option<Session>,
     pub messages: Vec<DisplayMessage>,
     pub sessions: Vec<Session>,
+    /// Fork depth of each entry in `sessions` (0 = not a listed fork)
+    pub session_fork_depths: Vec<usize>,
     /// All-time usage stats from the ledger (survives session deletes)
     pub usage_ledger_stats: Vec<crate::db::repository::usage_ledger::ModelUsageStats>,
     /// Usage dashboard state (populated when /usage is opened)
@@ -272,6 +296,9 @@ pub struct App {
     /// Streaming state
     pub is_processing: bool,
     pub processing_started_at: Option<std::time::Instant>,
+    /// Session, `provider/model` label and deadline of a pending rate-limit
+    /// wait — the spinner shows it until the deadline passes.
+    pub rate_limit_wait: Option<(Uuid, String, std::time::Instant)>,
     pub streaming_response: Option<String>,
     /// Cached parsed markdown lines for the streaming response — avoids
     /// re-parsing the entire growing buffer on every render frame.
@@ -287,6 +314,9 @@ pub struct App {
     pub notification_shown_at: Option<std::time::Instant>,
     /// Currently selected message index (left-click to select, right-click to copy)
     pub selected_message_idx: Option<usize>,
+    /// Earlier user message being edited in the input box — sending it
+    /// branches off everything from that message on and regenerates
+    pub editing_message: Option<Uuid>,
     /// Set to true when IntermediateText arrives during the current response cycle.
     /// Reset to false at the start of each new send_message call.
     /// Used in complete_response to avoid double-adding the assistant message.
@@ -528,6 +558,7 @@ impl App {
             current_session: None,
             messages: Vec::new(),
             sessions: Vec::new(),
+            session_fork_depths: Vec::new(),
             usage_ledger_stats: Vec::new(),
             dashboard_state: None,
             mode: AppMode::Chat,
@@ -543,6 +574,7 @@ impl App {
             pending_resize: None,
             is_processing: false,
             processing_started_at: None,
+            rate_limit_wait: None,
             streaming_response: None,
             streaming_render_cache: None,
             streaming_reasoning: None,
@@ -551,6 +583,7 @@ impl App {
             notification: None,
             notification_shown_at: None,
             selected_message_idx: None,
+            editing_message: None,
             intermediate_text_received: false,
             build_lines: Vec::new(),
             build_msg_idx: None,
@@ -1032,6 +1065,8 @@ impl App {
                     tool_description: tool_info.tool_description,
                     tool_input: tool_info.tool_input,
                     capabilities: tool_info.capabilities,
+                    ask_rule: tool_info.ask_rule,
+                    suggested_rule: tool_info.suggested_rule,
                     response_tx,
                     requested_at: std::time::Instant::now(),
                 };
@@ -1943,6 +1978,18 @@ impl App {
                     self.last_input_tokens = Some(count as u32);
                 }
             }
+            TuiEvent::RateLimitWait {
+                session_id,
+                provider,
+                model,
+                wait_secs,
+            } => {
+                self.rate_limit_wait = Some((
+                    session_id,
+                    format!("{}/{}", provider, model),
+                    std::time::Instant::now() + std::time::Duration::from_secs(wait_secs),
+                ));
+            }
             TuiEvent::StreamingOutputTokens { session_id, tokens }
                 if self.is_current_session(session_id) =>
             {
@@ -2157,6 +2204,44 @@ impl App {
                     self.push_system_message(format!("Brain generation: {}", e));
                 }
             },
+            TuiEvent::OllamaPullProgress { status, fraction } => {
+                let line = match fraction {
+                    Some(f) => format!("{} — {:.0}%", status, f * 100.0),
+                    None => status,
+                };
+                match self.onboarding {
+                    Some(ref mut wizard) if self.mode == AppMode::Onboarding => {
+                        wizard.ps.ollama_pull = Some(line);
+                    }
+                    _ => self.ps.ollama_pull = Some(line),
+                }
+            }
+            TuiEvent::OllamaPullComplete(result) => {
+                if self.mode == AppMode::Onboarding
+                    && let Some(ref mut wizard) = self.onboarding
+                {
+                    wizard.ps.ollama_pull = None;
+                    match result {
+                        Ok(_) => wizard.next_step(),
+                        Err(e) => wizard.error_message = Some(format!("Ollama: {}", e)),
+                    }
+                } else {
+                    self.ps.ollama_pull = None;
+                    match result {
+                        Ok(model) if self.mode == AppMode::ModelSelector => {
+                            tracing::info!("Ollama model {} ready — saving selection", model);
+                            // Ollama has no key to persist
+                            self.save_provider_selection(self.ps.selected_provider, false)
+                                .await?;
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            self.error_message = Some(format!("Ollama: {}", e));
+                            self.error_message_shown_at = Some(std::time::Instant::now());
+                        }
+                    }
+                }
+            }
             TuiEvent::WhisperDownloadProgress(progress) => {
                 if let Some(ref mut wizard) = self.onboarding {
                     wizard.stt_model_download_progress = Some(progress);
@@ -2682,8 +2767,8 @@ impl App {
             self.approval_auto_always
         );
 
-        // Auto-approve silently if policy allows
-        if self.approval_auto_always || self.approval_auto_session {
+        // Auto-approve silently if policy allows — unless a permission rule asks
+        if (self.approval_auto_always || self.approval_auto_session) && request.ask_rule.is_none() {
             let response = ToolApprovalResponse {
                 request_id: request.request_id,
                 approved: true,
@@ -2697,7 +2782,23 @@ impl App {
         }
 
         // Background session approval — auto-approve (user can't interact with it)
-        // They'll see the results when they switch to that session
+        // They'll see the results when they switch to that session. Calls an
+        // `ask` rule insists on are denied instead.
+        if !is_current && let Some(rule) = &request.ask_rule {
+            let response = ToolApprovalResponse {
+                request_id: request.request_id,
+                approved: false,
+                reason: Some(format!(
+                    "Permission rule `{}` asks, but the session is in the background",
+                    rule
+                )),
+            };
+            let _ = request.response_tx.send(response.clone());
+            let _ = self
+                .event_sender()
+                .send(TuiEvent::ToolApprovalResponse(response));
+            return;
+        }
         if !is_current {
             tracing::info!(
                 "[APPROVAL] Auto-approving background session {} tool '{}'",
@@ -2764,6 +2865,8 @@ impl App {
                 tool_description: request.tool_description,
                 tool_input: request.tool_input,
                 capabilities: request.capabilities,
+                ask_rule: request.ask_rule,
+                suggested_rule: request.suggested_rule,
                 request_id: request.request_id,
                 response_tx: request.response_tx,
                 requested_at: request.requested_at,
@@ -155,6 +155,15 @@ pub enum TuiEvent {
     /// A system message to display in chat
     SystemMessage(String),
 
+    /// An LLM call is queued behind a configured rate budget and will be
+    /// sent in `wait_secs` — the spinner says so instead of "thinking".
+    RateLimitWait {
+        session_id: Uuid,
+        provider: String,
+        model: String,
+        wait_secs: u64,
+    },
+
     /// Sticky fallback just swapped the active provider/model.
     /// The TUI should update the session + footer to reflect the new choice.
     ProviderSwitched {
@@ -191,6 +200,14 @@ pub enum TuiEvent {
     /// Whisper model download completed (Ok or Err message)
     WhisperDownloadComplete(Result<(), String>),
 
+    /// Ollama model pull progress: status line and layer fraction (0.0–1.0) if known
+    OllamaPullProgress {
+        status: String,
+        fraction: Option<f64>,
+    },
+    /// Ollama model is installed (Ok(model) — pulled or already present) or the pull failed
+    OllamaPullComplete(Result<String, String>),
+
     /// Piper voice download progress (0.0–1.0)
     PiperDownloadProgress(f64),
     /// Piper voice download completed (Ok(voice_id) or Err message)
@@ -264,6 +281,13 @@ pub struct ToolApprovalRequest {
     /// Tool capabilities
     pub capabilities: Vec<String>,
 
+    /// `ask` permission rule that asked for this prompt (shown even under
+    /// an auto-approve policy)
+    pub ask_rule: Option<String>,
+
+    /// Rule offered as "always allow this pattern"
+    pub suggested_rule: String,
+
     /// Channel to send response back
     pub response_tx: mpsc::UnboundedSender<ToolApprovalResponse>,
 
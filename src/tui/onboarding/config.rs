@@ -105,6 +105,8 @@ impl OnboardingWizard {
 
         let auth_label = if self.ps.is_cli() {
             "CLI Binary Found"
+        } else if self.ps.provider_id() == "ollama" {
+            "Ollama Selected"
         } else {
             "API Key Present"
         };
@@ -168,6 +170,7 @@ impl OnboardingWizard {
                 HealthStatus::Fail(format!("'{}' CLI not found in PATH", binary))
             }
         } else if !self.ps.api_key_input.is_empty()
+            || self.ps.provider_id() == "ollama"
             || (self.ps.is_custom() && !self.ps.base_url.is_empty())
         {
             HealthStatus::Pass
@@ -147,6 +147,7 @@ pub fn is_first_time() -> bool {
             .as_ref()
             .is_some_and(|p| p.enabled)
         || config.providers.qwen.as_ref().is_some_and(|p| p.enabled)
+        || config.providers.ollama.as_ref().is_some_and(|p| p.enabled)
         || config.providers.active_custom().is_some();
 
     tracing::debug!(
@@ -198,6 +199,12 @@ pub async fn fetch_provider_models(
         return fetch_opencode_models().await;
     }
 
+    // Ollama: installed models from /api/tags, then config suggestions that
+    // can be pulled on selection
+    if provider_id == "ollama" {
+        return fetch_ollama_models().await;
+    }
+
     // Qwen (DashScope): no /v1/models endpoint on the OpenAI-compat path,
     // so we read the curated list from config.toml.example. Users can
     // override via `models = [...]` in their own config.toml.
@@ -416,6 +423,78 @@ pub async fn fetch_provider_models(
     }
 }
 
+/// Ollama client for the configured server (`[providers.ollama] base_url`,
+/// default `localhost:11434`).
+fn ollama_from_config() -> crate::brain::provider::OllamaProvider {
+    let base_url = crate::config::Config::load()
+        .ok()
+        .and_then(|c| c.providers.ollama)
+        .and_then(|p| p.base_url)
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| crate::brain::provider::ollama::DEFAULT_BASE_URL.to_string());
+    crate::brain::provider::OllamaProvider::new(base_url)
+}
+
+/// Installed Ollama models first, then not-yet-installed suggestions from
+/// `models = [...]` (or config.toml.example) that get pulled when picked.
+async fn fetch_ollama_models() -> Vec<String> {
+    use crate::brain::provider::ollama::with_default_tag;
+
+    let mut models = match ollama_from_config().installed_models().await {
+        Ok(installed) => installed,
+        Err(e) => {
+            tracing::warn!("[fetch_provider_models] Ollama /api/tags failed: {}", e);
+            Vec::new()
+        }
+    };
+    let suggested = crate::config::Config::load()
+        .ok()
+        .and_then(|c| c.providers.ollama)
+        .map(|p| p.models)
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| crate::tui::provider_selector::load_default_models("ollama"));
+    for model in suggested {
+        let tagged = with_default_tag(&model);
+        if !models.iter().any(|m| with_default_tag(m) == tagged) {
+            models.push(model);
+        }
+    }
+    models
+}
+
+/// Make sure `model` is installed on the Ollama server, pulling it first if
+/// needed. Progress arrives as `OllamaPullProgress`, the outcome as
+/// `OllamaPullComplete`.
+pub fn spawn_ollama_pull(
+    model: String,
+    sender: tokio::sync::mpsc::UnboundedSender<crate::tui::events::TuiEvent>,
+) {
+    use crate::tui::events::TuiEvent;
+
+    tokio::spawn(async move {
+        let ollama = ollama_from_config();
+        let result = match ollama.is_installed(&model).await {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                let progress_sender = sender.clone();
+                ollama
+                    .pull_model(&model, move |p| {
+                        let fraction = p.fraction();
+                        let _ = progress_sender.send(TuiEvent::OllamaPullProgress {
+                            status: p.status,
+                            fraction,
+                        });
+                    })
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        let _ = sender.send(TuiEvent::OllamaPullComplete(
+            result.map(|_| model).map_err(|e| e.to_string()),
+        ));
+    });
+}
+
 /// Fetch available models from the opencode CLI binary.
 async fn fetch_opencode_models() -> Vec<String> {
     // Resolve binary path
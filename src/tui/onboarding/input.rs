@@ -455,8 +455,8 @@ impl OnboardingWizard {
                         return WizardAction::GitHubDeviceFlow;
                     } else if self.ps.is_custom() {
                         self.auth_field = AuthField::CustomName;
-                    } else if self.ps.is_cli() {
-                        // CLI providers: no API key — skip to model
+                    } else if self.ps.is_keyless() {
+                        // CLI providers and Ollama: no API key — skip to model
                         self.auth_field = AuthField::Model;
                         self.ps.models.clear();
                         self.ps.selected_model = 0;
@@ -542,8 +542,8 @@ impl OnboardingWizard {
                 }
                 KeyCode::Backspace => {
                     if self.ps.model_filter.is_empty() {
-                        // CLI providers have no API key — go back to Provider
-                        if self.ps.is_cli() {
+                        // Keyless providers have no API key — go back to Provider
+                        if self.ps.is_keyless() {
                             self.auth_field = AuthField::Provider;
                         } else {
                             self.auth_field = AuthField::ApiKey;
@@ -553,11 +553,19 @@ impl OnboardingWizard {
                         self.ps.selected_model = 0;
                     }
                 }
+                KeyCode::Enter | KeyCode::Tab if self.ps.provider_id() == "ollama" => {
+                    // Pull the model first if it isn't installed; the
+                    // wizard advances once OllamaPullComplete arrives.
+                    if self.ps.ollama_pull.is_none() {
+                        self.ps.ollama_pull = Some("Checking installed models…".to_string());
+                        return WizardAction::PullOllamaModel;
+                    }
+                }
                 KeyCode::Enter => {
                     self.next_step();
                 }
                 KeyCode::BackTab => {
-                    if self.ps.is_cli() {
+                    if self.ps.is_keyless() {
                         self.auth_field = AuthField::Provider;
                     } else {
                         self.auth_field = AuthField::ApiKey;
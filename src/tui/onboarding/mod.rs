@@ -31,4 +31,4 @@ pub use types::{
 pub use wizard::OnboardingWizard;
 
 pub(crate) use brain::parse_brain_sections;
-pub use fetch::{fetch_provider_models, is_first_time};
+pub use fetch::{fetch_provider_models, is_first_time, spawn_ollama_pull};
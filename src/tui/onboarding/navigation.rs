@@ -29,8 +29,9 @@ impl OnboardingWizard {
                 self.ps.detect_existing_key();
             }
             OnboardingStep::ProviderAuth => {
-                // CLI providers (Claude CLI, OpenCode CLI) have no API key
-                if self.ps.api_key_input.is_empty() && !self.ps.is_custom() && !self.ps.is_cli() {
+                // Keyless providers (Claude CLI, OpenCode CLI, Ollama) have no API key
+                if self.ps.api_key_input.is_empty() && !self.ps.is_custom() && !self.ps.is_keyless()
+                {
                     self.error_message = Some("API key is required".to_string());
                     return;
                 }
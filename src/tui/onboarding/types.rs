@@ -235,6 +235,16 @@ pub const PROVIDERS: &[ProviderInfo] = &[
             "Get key from bailian.console.aliyun.com or qwen.ai/apiplatform",
         ],
     },
+    ProviderInfo {
+        id: "ollama",
+        name: "Ollama",
+        models: &[], // Installed models fetched from /api/tags
+        key_label: "",
+        help_lines: &[
+            "Native Ollama API — no API key needed (server at localhost:11434)",
+            "Models not yet installed are pulled when you select them",
+        ],
+    },
     ProviderInfo {
         id: "", // dynamic — custom providers use runtime names
         name: "Custom OpenAI-Compatible",
@@ -561,6 +571,8 @@ pub enum WizardAction {
     DownloadWhisperModel,
     /// Trigger async Piper voice model download
     DownloadPiperVoice,
+    /// Pull the selected Ollama model if it isn't installed yet
+    PullOllamaModel,
     /// Trigger GitHub Copilot OAuth device flow
     GitHubDeviceFlow,
     /// Quick-jump step completed — save config and close wizard
@@ -270,6 +270,13 @@ impl OnboardingWizard {
                         String::new(),
                         String::new(),
                     )
+                } else if config.providers.ollama.as_ref().is_some_and(|p| p.enabled) {
+                    (
+                        crate::tui::provider_selector::index_of_provider("ollama").unwrap_or(0),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                    )
                 } else if let Some((name, c)) = config.providers.active_custom().or_else(|| {
                     config
                         .providers
@@ -576,6 +583,16 @@ impl OnboardingWizard {
             {
                 wizard.ps.custom_model = model.clone();
             }
+        } else if config.providers.ollama.as_ref().is_some_and(|p| p.enabled) {
+            wizard.ps.selected_provider = 10; // Ollama
+            if let Some(model) = &config
+                .providers
+                .ollama
+                .as_ref()
+                .and_then(|p| p.default_model.clone())
+            {
+                wizard.ps.custom_model = model.clone();
+            }
         }
 
         // Detect if we have an existing API key for the selected provider
@@ -860,8 +860,8 @@ fn render_provider_auth(lines: &mut Vec<Line<'static>>, wizard: &OnboardingWizar
             lines.push(Line::from(""));
         }
 
-        // CLI providers have no API key — skip the field
-        if !wizard.ps.is_cli() {
+        // Keyless providers (CLI, Ollama) — skip the field
+        if !wizard.ps.is_keyless() {
             let key_focused = wizard.auth_field == AuthField::ApiKey;
             let key_label = provider.key_label;
             let (masked_key, key_hint) = if wizard.ps.has_existing_key_sentinel() {
@@ -1027,6 +1027,12 @@ fn render_provider_auth(lines: &mut Vec<Line<'static>>, wizard: &OnboardingWizar
                     render_model_window(lines, &filtered, wizard.ps.selected_model, model_focused);
                 }
             }
+            if let Some(ref pull) = wizard.ps.ollama_pull {
+                lines.push(Line::from(Span::styled(
+                    format!("  ⟳ {}", pull),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
         }
     }
     focused_line
@@ -65,6 +65,8 @@ pub struct ProviderSelectorState {
     pub focused_field: usize,
     /// Whether the provider list is expanded/visible
     pub showing_providers: bool,
+    /// Status line of an in-flight Ollama model pull (`None` when idle)
+    pub ollama_pull: Option<String>,
 }
 
 impl ProviderSelectorState {
@@ -87,6 +89,11 @@ impl ProviderSelectorState {
         id == "claude-cli" || id == "opencode-cli"
     }
 
+    /// Providers that never take an API key (CLI subprocesses, Ollama).
+    pub fn is_keyless(&self) -> bool {
+        self.is_cli() || self.provider_id() == "ollama"
+    }
+
     pub fn is_zhipu(&self) -> bool {
         self.provider_id() == "zhipu"
     }
@@ -113,7 +120,14 @@ impl ProviderSelectorState {
     pub fn supports_model_fetch(&self) -> bool {
         matches!(
             self.provider_id(),
-            "anthropic" | "openai" | "github" | "gemini" | "openrouter" | "zhipu" | "opencode-cli"
+            "anthropic"
+                | "openai"
+                | "github"
+                | "gemini"
+                | "openrouter"
+                | "zhipu"
+                | "opencode-cli"
+                | "ollama"
         )
     }
 
@@ -168,6 +182,8 @@ impl ProviderSelectorState {
                     };
                     which::which(bin).is_ok()
                 }
+                // Ollama — keyless; the server is checked when models load
+                "ollama" => true,
                 // OAuth providers — check for token/accounts
                 "github" => config
                     .providers
@@ -477,6 +477,20 @@ pub(super) fn render_chat(f: &mut Frame, app: &mut App, area: Rect) {
             .processing_started_at
             .map(|t| t.elapsed().as_secs())
             .unwrap_or(0);
+        // Queued behind a configured rate budget — say so rather than "thinking"
+        let status = match &app.rate_limit_wait {
+            Some((session_id, label, until))
+                if app.is_current_session(*session_id) && *until > std::time::Instant::now() =>
+            {
+                let remaining = until.saturating_duration_since(std::time::Instant::now());
+                format!(
+                    "Waiting for rate limit on {} ({}s)...",
+                    label,
+                    remaining.as_secs() + 1
+                )
+            }
+            _ => "OpenCrabs is thinking...".to_string(),
+        };
         let mut spans = vec![
             Span::styled(
                 format!("  {} ", frame),
@@ -484,10 +498,7 @@ pub(super) fn render_chat(f: &mut Frame, app: &mut App, area: Rect) {
                     .fg(Color::Rgb(120, 120, 120))
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::styled(
-                "OpenCrabs is thinking...",
-                Style::default().fg(Color::Rgb(215, 100, 20)),
-            ),
+            Span::styled(status, Style::default().fg(Color::Rgb(215, 100, 20))),
         ];
         if elapsed > 0 || app.streaming_output_tokens > 0 {
             let mut meta = String::new();
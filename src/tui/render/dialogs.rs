@@ -502,9 +502,8 @@ pub(super) fn render_model_selector(f: &mut Frame, app: &App, area: Rect) {
     }
 
     // API Key field (field 1 for non-Custom, field 2 for Custom; field 2 for zhipu since field 1 = endpoint type)
-    // CLI providers have no API key — skip entirely.
-    let is_cli_provider = app.ps.is_cli();
-    if !is_cli_provider {
+    // Keyless providers (CLI, Ollama) — skip entirely.
+    if !app.ps.is_keyless() {
         let is_zhipu = selected_provider.id == "zhipu";
         let key_focused = (focused_field == 1 && !is_custom && !is_zhipu)
             || (focused_field == 2 && (is_custom || is_zhipu));
@@ -560,7 +559,15 @@ pub(super) fn render_model_selector(f: &mut Frame, app: &App, area: Rect) {
         }
 
         lines.push(Line::from(""));
-    } else if is_cli_provider {
+    } else if app.ps.provider_id() == "ollama" {
+        lines.push(Line::from(Span::styled(
+            "  No API key needed — models not installed yet are pulled on select",
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+        )));
+        lines.push(Line::from(""));
+    } else {
         // CLI provider: show "no API key needed" hint
         let cli_name = if app.ps.provider_id() == "opencode-cli" {
             "opencode"
@@ -689,6 +696,13 @@ pub(super) fn render_model_selector(f: &mut Frame, app: &App, area: Rect) {
                 Style::default().fg(Color::DarkGray),
             )));
         }
+
+        if let Some(ref pull) = app.ps.ollama_pull {
+            lines.push(Line::from(Span::styled(
+                format!("  ⟳ {}", pull),
+                Style::default().fg(Color::Yellow),
+            )));
+        }
     }
 
     // Custom provider: name identifier field (field 4 — last before save)
@@ -91,6 +91,10 @@ pub(super) fn render_help(f: &mut Frame, app: &App, area: Rect) {
         kv("/sessions", "Session manager", cyan),
         kv("/approve", "Tool approval policy", cyan),
         kv("/compact", "Compact context now", cyan),
+        kv("/undo", "Revert last turn's file changes", cyan),
+        kv("/rewind", "Restore files & chat to a message", cyan),
+        kv("/edit", "Edit an earlier message & regenerate", cyan),
+        kv("/fork", "Branch chat into a new session", cyan),
         kv("/rebuild", "Build & restart from source", cyan),
         kv("/evolve", "Download latest release & restart", cyan),
         kv("/cd", "Change working directory", cyan),
@@ -478,6 +478,17 @@ pub(super) fn render_input(f: &mut Frame, app: &App, area: Rect) {
     if !app.attachments.is_empty() {
         block = block.title(attach_title);
     }
+    if app.editing_message.is_some() {
+        block = block.title(
+            Line::from(Span::styled(
+                " ✏️ Editing earlier message — Enter re-sends from here, Esc×2 cancels ",
+                Style::default()
+                    .fg(Color::Rgb(215, 100, 20))
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .alignment(Alignment::Left),
+        );
+    }
 
     // Compute a vertical scroll so the cursor row is always inside the
     // visible viewport (area.height - 2 for top/bottom borders).
@@ -98,6 +98,12 @@ pub(super) fn render_sessions(f: &mut Frame, app: &App, area: Rect) {
 
         let prefix = if is_selected { "  > " } else { "    " };
 
+        // Forks sit under the session they branched from
+        let branch = match app.session_fork_depths.get(idx).copied().unwrap_or(0) {
+            0 => String::new(),
+            depth => format!("{}└─ ", "   ".repeat(depth - 1)),
+        };
+
         let name = session.title.as_deref().unwrap_or("Untitled");
         let created = session.created_at.format("%Y-%m-%d %H:%M");
 
@@ -147,7 +153,9 @@ pub(super) fn render_sessions(f: &mut Frame, app: &App, area: Rect) {
             };
 
             let mut spans = vec![
-                Span::styled(format!("{}{}", prefix, name), name_style),
+                Span::styled(prefix, name_style),
+                Span::styled(branch, Style::default().fg(Color::Rgb(80, 200, 120))),
+                Span::styled(name, name_style),
                 Span::styled(
                     format!(" - {} ", created),
                     Style::default().fg(Color::DarkGray),
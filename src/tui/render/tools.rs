@@ -324,15 +324,26 @@ pub(super) fn render_inline_approval<'a>(
                 lines.push(Line::from(""));
             }
 
+            if let Some(rule) = &approval.ask_rule {
+                lines.push(Line::from(vec![Span::styled(
+                    format!("  Asked by permission rule `{}`", rule),
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                )]));
+            }
+
             // "Do you approve?" + vertical option list with ❯ selector
-            // Order: Yes(0), Always(1), No(2)
+            // Order: Yes(0), Always(1), Allow pattern(2), No(3)
             lines.push(Line::from(vec![Span::styled(
                 "  Do you approve?",
                 Style::default().fg(Color::DarkGray),
             )]));
+            let allow_pattern = format!("Always allow {}", approval.suggested_rule);
             let options = [
                 ("Yes", Color::Cyan),
                 ("Always", Color::Rgb(215, 100, 20)),
+                (allow_pattern.as_str(), Color::Rgb(215, 100, 20)),
                 ("No", Color::Red),
             ];
             for (i, (label, color)) in options.iter().enumerate() {
@@ -0,0 +1,312 @@
+//! Spending Budgets
+//!
+//! `[budget]` in config.toml caps spend per calendar day and month, for the
+//! whole profile, per channel and per cron job. The agent service checks
+//! [`check`] before every LLM call: past `warn_at` of a cap the user gets
+//! one warning per cap and period, and at the cap the call is refused or
+//! sent to the configured cheaper model.
+//!
+//! Spend comes from the usage ledger, which a turn only writes once it
+//! finishes. Calls made earlier in a still-running turn are tracked here
+//! via [`note_call_cost`] until the turn is recorded, so a long tool loop
+//! can't run past the cap.
+
+use crate::config::{BudgetAction, BudgetConfig, BudgetLimits};
+use crate::db::{Pool, UsageLedgerRepository};
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{LazyLock, Mutex, RwLock};
+use uuid::Uuid;
+
+/// Scope name for the profile-wide caps.
+pub const PROFILE_SCOPE: &str = "profile";
+
+/// Budgets in effect for the process.
+static CONFIG: LazyLock<RwLock<BudgetConfig>> =
+    LazyLock::new(|| RwLock::new(BudgetConfig::default()));
+
+/// Cost of calls in turns the ledger hasn't recorded yet, per session,
+/// with the channel they're budgeted under.
+static UNRECORDED: LazyLock<Mutex<HashMap<Uuid, (String, f64)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Warnings and downgrade notices already shown, so each fires once per
+/// cap and period.
+static NOTIFIED: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Apply `[budget]` from config.toml. Called whenever providers are
+/// (re)built so config reloads take effect.
+pub fn configure(config: &BudgetConfig) {
+    *CONFIG.write().unwrap_or_else(|e| e.into_inner()) = config.clone();
+}
+
+/// Budgets currently in effect.
+pub fn current() -> BudgetConfig {
+    CONFIG.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Budget window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Period {
+    Daily,
+    Monthly,
+}
+
+impl Period {
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::Daily => "daily",
+            Period::Monthly => "monthly",
+        }
+    }
+
+    /// Unix time the window containing `now` started (local midnight).
+    pub fn start(self, now: DateTime<Local>) -> i64 {
+        let date = match self {
+            Period::Daily => now.date_naive(),
+            Period::Monthly => NaiveDate::from_ymd_opt(now.year(), now.month(), 1)
+                .unwrap_or_else(|| now.date_naive()),
+        };
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+            .map(|start| start.timestamp())
+            .unwrap_or_else(|| now.timestamp())
+    }
+}
+
+/// Spend against one cap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetStatus {
+    /// `profile`, a channel name, or `cron:<job>`
+    pub scope: String,
+    pub period: Period,
+    pub spent: f64,
+    pub limit: f64,
+    /// Start of the period (unix seconds)
+    pub since: i64,
+}
+
+impl BudgetStatus {
+    pub fn remaining(&self) -> f64 {
+        (self.limit - self.spent).max(0.0)
+    }
+
+    /// Share of the cap spent (1.0 = exhausted)
+    pub fn used(&self) -> f64 {
+        if self.limit <= 0.0 {
+            1.0
+        } else {
+            self.spent / self.limit
+        }
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.spent >= self.limit
+    }
+}
+
+impl fmt::Display for BudgetStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} budget", self.period.label())?;
+        if self.scope != PROFILE_SCOPE {
+            write!(f, " for {}", self.scope)?;
+        }
+        write!(
+            f,
+            ": ${:.2} of ${:.2} spent, ${:.2} left",
+            self.spent,
+            self.limit,
+            self.remaining()
+        )
+    }
+}
+
+/// What to do with the next LLM call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Allow,
+    /// Send it, but a cap is past `warn_at`
+    Warn(BudgetStatus),
+    /// A cap is spent; send it to `model` instead
+    Downgrade {
+        status: BudgetStatus,
+        model: String,
+    },
+    /// A cap is spent; don't send it
+    Refuse(BudgetStatus),
+}
+
+/// Channel a budget applies to: `telegram` for itself, `cron` for every
+/// cron job.
+fn base_channel(channel: &str) -> &str {
+    channel.split(':').next().unwrap_or(channel)
+}
+
+/// Caps that apply to calls on `channel`, as `(scope, ledger filter, limits)`.
+fn applicable<'a>(
+    config: &'a BudgetConfig,
+    channel: &'a str,
+) -> Vec<(String, Option<&'a str>, BudgetLimits)> {
+    let mut caps = vec![(
+        PROFILE_SCOPE.to_string(),
+        None,
+        BudgetLimits {
+            daily_usd: config.daily_usd,
+            monthly_usd: config.monthly_usd,
+        },
+    )];
+    let base = base_channel(channel);
+    if let Some(limits) = config.channels.get(base) {
+        caps.push((base.to_string(), Some(base), limits.clone()));
+    }
+    if let Some(job) = channel.strip_prefix("cron:")
+        && let Some(limits) = config.cron.get(job)
+    {
+        caps.push((channel.to_string(), Some(channel), limits.clone()));
+    }
+    caps
+}
+
+/// Spend against every cap that applies to `channel` (`""` for profile
+/// caps only).
+pub async fn statuses(
+    pool: &Pool,
+    config: &BudgetConfig,
+    channel: &str,
+    now: DateTime<Local>,
+) -> anyhow::Result<Vec<BudgetStatus>> {
+    let ledger = UsageLedgerRepository::new(pool.clone());
+    let mut out = Vec::new();
+    for (scope, filter, limits) in applicable(config, channel) {
+        for (period, limit) in [
+            (Period::Daily, limits.daily_usd),
+            (Period::Monthly, limits.monthly_usd),
+        ] {
+            let Some(limit) = limit else { continue };
+            let since = period.start(now);
+            let spent = ledger.spent_since(since, filter).await? + unrecorded(filter);
+            out.push(BudgetStatus {
+                scope: scope.clone(),
+                period,
+                spent,
+                limit,
+                since,
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// Spend against every configured cap — profile, each channel, each cron
+/// job — for `opencrabs status` and `/usage`.
+pub async fn all_statuses(pool: &Pool, config: &BudgetConfig) -> anyhow::Result<Vec<BudgetStatus>> {
+    let now = Local::now();
+    let mut out = statuses(pool, config, "", now).await?;
+    for name in config.channels.keys() {
+        let scoped = statuses(pool, config, name, now).await?;
+        out.extend(scoped.into_iter().filter(|s| &s.scope == name));
+    }
+    for job in config.cron.keys() {
+        let channel = format!("cron:{job}");
+        let scoped = statuses(pool, config, &channel, now).await?;
+        out.extend(scoped.into_iter().filter(|s| s.scope == channel));
+    }
+    Ok(out)
+}
+
+/// Decide on a call given the spend against its caps.
+pub fn evaluate(config: &BudgetConfig, statuses: &[BudgetStatus]) -> Verdict {
+    if let Some(status) = statuses.iter().find(|s| s.is_exceeded()) {
+        return match (config.on_exceed, &config.downgrade_model) {
+            (BudgetAction::Downgrade, Some(model)) => Verdict::Downgrade {
+                status: status.clone(),
+                model: model.clone(),
+            },
+            (BudgetAction::Downgrade, None) => {
+                tracing::warn!(
+                    "budget.on_exceed = \"downgrade\" without downgrade_model — refusing"
+                );
+                Verdict::Refuse(status.clone())
+            }
+            (BudgetAction::Refuse, _) => Verdict::Refuse(status.clone()),
+        };
+    }
+    statuses
+        .iter()
+        .filter(|s| s.used() >= config.warn_at)
+        .max_by(|a, b| a.used().total_cmp(&b.used()))
+        .map(|s| Verdict::Warn(s.clone()))
+        .unwrap_or(Verdict::Allow)
+}
+
+/// Check the budgets in effect for the next call on `channel`. Ledger
+/// errors are logged and let the call through.
+pub async fn check(pool: &Pool, channel: &str) -> Verdict {
+    let config = current();
+    if !config.is_enabled() {
+        return Verdict::Allow;
+    }
+    match statuses(pool, &config, channel, Local::now()).await {
+        Ok(statuses) => evaluate(&config, &statuses),
+        Err(e) => {
+            tracing::warn!("Budget check failed, allowing call: {e}");
+            Verdict::Allow
+        }
+    }
+}
+
+/// True the first time `kind` is reported for this cap in this period.
+pub fn first_notice(kind: &str, status: &BudgetStatus) -> bool {
+    let key = format!(
+        "{kind}/{}/{}/{}",
+        status.scope,
+        status.period.label(),
+        status.since
+    );
+    NOTIFIED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key)
+}
+
+/// Add the cost of a call in `session_id`'s running turn.
+pub fn note_call_cost(session_id: Uuid, channel: &str, cost: f64) {
+    if cost <= 0.0 {
+        return;
+    }
+    let mut pending = UNRECORDED.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = pending
+        .entry(session_id)
+        .or_insert_with(|| (channel.to_string(), 0.0));
+    entry.0 = channel.to_string();
+    entry.1 += cost;
+}
+
+/// Forget `session_id`'s running-turn cost once the turn is over (and
+/// recorded in the ledger, if it got that far).
+pub fn clear_unrecorded(session_id: Uuid) {
+    UNRECORDED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&session_id);
+}
+
+/// Running-turn cost on `channel` (every channel when `None`), matched the
+/// same way as [`UsageLedgerRepository::spent_since`].
+fn unrecorded(channel: Option<&str>) -> f64 {
+    let pending = UNRECORDED.lock().unwrap_or_else(|e| e.into_inner());
+    pending
+        .values()
+        .filter(|(chan, _)| match channel {
+            None => true,
+            Some(filter) => {
+                chan == filter
+                    || chan
+                        .strip_prefix(filter)
+                        .is_some_and(|rest| rest.starts_with(':'))
+            }
+        })
+        .map(|(_, cost)| cost)
+        .sum()
+}
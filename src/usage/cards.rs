@@ -39,7 +39,7 @@ fn card_block(title: &str, focused: bool) -> Block<'_> {
 pub fn render_summary(f: &mut Frame, data: &DashboardData, area: Rect, period_label: &str) {
     let s = &data.summary;
     let version = crate::VERSION;
-    let line = Line::from(vec![
+    let mut spans = vec![
         Span::styled(format!("v{version}  "), ACCENT),
         Span::styled("Tokens: ", LABEL),
         Span::styled(fmt_tokens(s.total_tokens), BOLD),
@@ -49,8 +49,29 @@ pub fn render_summary(f: &mut Frame, data: &DashboardData, area: Rect, period_la
         Span::styled(format!("{}", s.session_count), BOLD),
         Span::styled("  Calls: ", LABEL),
         Span::styled(format!("{}", s.call_count), BOLD),
-        Span::styled(format!("  [{}]", period_label), ACCENT),
-    ]);
+    ];
+    if s.cache_hits > 0 {
+        spans.push(Span::styled("  Saved: ", LABEL));
+        spans.push(Span::styled(fmt_cost(s.saved_cost), BOLD));
+        spans.push(Span::styled(format!(" ({} cached)", s.cache_hits), DIM));
+    }
+    // The cap closest to running out
+    if let Some(b) = data
+        .budgets
+        .iter()
+        .max_by(|a, b| a.used().total_cmp(&b.used()))
+    {
+        spans.push(Span::styled("  Budget left: ", LABEL));
+        spans.push(Span::styled(fmt_cost(b.remaining()), BOLD));
+        let scope = if b.scope == super::budget::PROFILE_SCOPE {
+            b.period.label().to_string()
+        } else {
+            format!("{} {}", b.scope, b.period.label())
+        };
+        spans.push(Span::styled(format!(" ({scope})"), DIM));
+    }
+    spans.push(Span::styled(format!("  [{}]", period_label), ACCENT));
+    let line = Line::from(spans);
     let block = Block::default()
         .borders(Borders::BOTTOM)
         .border_style(Style::default().fg(Color::DarkGray));
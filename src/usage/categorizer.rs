@@ -3,8 +3,15 @@
 //! Runs on startup (or periodically) to classify uncategorized sessions.
 //! Uses the LLM to batch-classify session titles into activity categories,
 //! then persists the result to `sessions.category`.
+//!
+//! Nothing here is interactive, so on providers with a batch API the cron
+//! scheduler classifies sessions created since startup through
+//! [`submit_categorize_batch`] once a day, at half price; its batch poller
+//! hands the results to [`apply_categorize_batch`] once the batch ends.
 
-use crate::db::{Pool, interact_err};
+use crate::brain::provider::{BatchRequest, BatchResult, LLMRequest, Message, Provider};
+use crate::db::models::LlmBatch;
+use crate::db::{LlmBatchRepository, Pool, interact_err};
 use anyhow::{Context, Result};
 
 /// Valid categories the LLM should pick from.
@@ -127,6 +134,57 @@ pub async fn categorize_with_heuristic(pool: &Pool) -> Result<usize> {
     save_categories(pool, &pairs).await
 }
 
+/// `llm_batches.purpose` for classification batches.
+pub const BATCH_PURPOSE: &str = "categorize";
+
+/// Submit classification of uncategorized sessions as a provider batch and
+/// record it in `llm_batches`. Uses the line-based prompt so the reply is
+/// plain text on every batch backend. Returns the batch id, or `None` when
+/// there is nothing to classify.
+pub async fn submit_categorize_batch(
+    pool: &Pool,
+    provider: &dyn Provider,
+    model: &str,
+) -> Result<Option<String>> {
+    let uncategorized = fetch_uncategorized(pool, 100).await?;
+    if uncategorized.is_empty() {
+        return Ok(None);
+    }
+
+    let prompt = build_classification_prompt(&uncategorized);
+    let request = LLMRequest::new(model, vec![Message::user(prompt)]).with_max_tokens(4096);
+    let batch_id = provider
+        .submit_batch(vec![BatchRequest::new(BATCH_PURPOSE, request)])
+        .await
+        .context("Failed to submit classification batch")?;
+
+    LlmBatchRepository::new(pool.clone())
+        .insert(&LlmBatch::new_pending(
+            batch_id.clone(),
+            provider.name().to_string(),
+            BATCH_PURPOSE,
+            None,
+            1,
+        ))
+        .await?;
+    Ok(Some(batch_id))
+}
+
+/// Persist the categories from a finished classification batch.
+/// Returns count of sessions categorized.
+pub async fn apply_categorize_batch(pool: &Pool, results: &[BatchResult]) -> Result<usize> {
+    let mut categories = Vec::new();
+    for result in results {
+        match &result.outcome {
+            Ok(_) => categories.extend(parse_classification_response(
+                &result.text().unwrap_or_default(),
+            )),
+            Err(e) => tracing::warn!("Classification batch request failed: {e}"),
+        }
+    }
+    save_categories(pool, &categories).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,6 +60,10 @@ pub struct SummaryStats {
     pub total_cost: f64,
     pub session_count: i64,
     pub call_count: i64,
+    /// Calls served from the response cache (not included in `call_count`)
+    pub cache_hits: i64,
+    /// What the cached calls would have cost
+    pub saved_cost: f64,
 }
 
 /// Daily usage for the sparkline / bar chart
@@ -115,6 +119,9 @@ pub struct DashboardData {
     pub models: Vec<ModelStats>,
     pub tools: Vec<ToolStats>,
     pub activities: Vec<ActivityStats>,
+    /// Spend against each configured `[budget]` cap (current period, not
+    /// the dashboard's)
+    pub budgets: Vec<super::budget::BudgetStatus>,
 }
 
 // ── Activity classifier ──────────────────────────────────────────────────────
@@ -169,6 +176,15 @@ impl DashboardData {
         // fetch_models recalculates using current TOML pricing, so we trust that.
         summary.total_cost = models.iter().map(|m| m.cost).sum();
 
+        let budget = super::budget::current();
+        let budgets = if budget.is_enabled() {
+            super::budget::all_statuses(pool, &budget)
+                .await
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
         Ok(Self {
             summary,
             daily,
@@ -176,6 +192,7 @@ impl DashboardData {
             models,
             tools,
             activities,
+            budgets,
         })
     }
 }
@@ -186,14 +203,16 @@ async fn fetch_summary(pool: &Pool, since: Option<i64>) -> Result<SummaryStats>
         let (query, param): (&str, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(s) = since {
             (
                 "SELECT COALESCE(SUM(token_count), 0), COALESCE(SUM(cost), 0.0), \
-                 COUNT(DISTINCT session_id), COUNT(*) \
+                 COUNT(DISTINCT session_id), COALESCE(SUM(cache_hit = 0), 0), \
+                 COALESCE(SUM(cache_hit), 0), COALESCE(SUM(saved_cost), 0.0) \
                  FROM usage_ledger WHERE created_at >= ?1",
                 vec![Box::new(s)],
             )
         } else {
             (
                 "SELECT COALESCE(SUM(token_count), 0), COALESCE(SUM(cost), 0.0), \
-                 COUNT(DISTINCT session_id), COUNT(*) \
+                 COUNT(DISTINCT session_id), COALESCE(SUM(cache_hit = 0), 0), \
+                 COALESCE(SUM(cache_hit), 0), COALESCE(SUM(saved_cost), 0.0) \
                  FROM usage_ledger",
                 vec![],
             )
@@ -205,6 +224,8 @@ async fn fetch_summary(pool: &Pool, since: Option<i64>) -> Result<SummaryStats>
                 total_cost: row.get(1)?,
                 session_count: row.get(2)?,
                 call_count: row.get(3)?,
+                cache_hits: row.get(4)?,
+                saved_cost: row.get(5)?,
             })
         })
     })
@@ -219,7 +240,8 @@ async fn fetch_daily(pool: &Pool, since: Option<i64>) -> Result<Vec<DailyStats>>
         let (query, param): (&str, Vec<Box<dyn rusqlite::types::ToSql>>) = if let Some(s) = since {
             (
                 "SELECT date(created_at, 'unixepoch') AS day, \
-                 COALESCE(SUM(token_count), 0), COALESCE(SUM(cost), 0.0), COUNT(*) \
+                 COALESCE(SUM(token_count), 0), COALESCE(SUM(cost), 0.0), \
+                 COALESCE(SUM(cache_hit = 0), 0) \
                  FROM usage_ledger WHERE created_at >= ?1 \
                  GROUP BY day ORDER BY day ASC",
                 vec![Box::new(s)],
@@ -227,7 +249,8 @@ async fn fetch_daily(pool: &Pool, since: Option<i64>) -> Result<Vec<DailyStats>>
         } else {
             (
                 "SELECT date(created_at, 'unixepoch') AS day, \
-                 COALESCE(SUM(token_count), 0), COALESCE(SUM(cost), 0.0), COUNT(*) \
+                 COALESCE(SUM(token_count), 0), COALESCE(SUM(cost), 0.0), \
+                 COALESCE(SUM(cache_hit = 0), 0) \
                  FROM usage_ledger GROUP BY day ORDER BY day ASC",
                 vec![],
             )
@@ -339,9 +362,9 @@ async fn fetch_models(pool: &Pool, since: Option<i64>) -> Result<Vec<ModelStats>
     let models = conn.interact(move |conn| {
         // Reuse the same SQL normalization as usage_ledger.rs stats_by_model
         let base_where = if since.is_some() {
-            "WHERE model != '' AND created_at >= ?1"
+            "WHERE model != '' AND cache_hit = 0 AND created_at >= ?1"
         } else {
-            "WHERE model != ''"
+            "WHERE model != '' AND cache_hit = 0"
         };
         let query = format!(
             "WITH stripped AS ( \
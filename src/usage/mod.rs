@@ -3,6 +3,7 @@
 //! Full-screen usage analytics dashboard with 6 cards:
 //! Summary Bar, Daily Activity, By Project, By Model, Core Tools, By Activity.
 
+pub mod budget;
 pub mod cards;
 pub mod categorizer;
 pub mod dashboard;
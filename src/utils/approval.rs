@@ -1,11 +1,21 @@
 //! Shared approval utilities used across all channel integrations.
 //!
 //! Centralises the config-level approval policy check and the
-//! "always approve" persistence so every channel behaves identically.
+//! "always approve" / "always allow this pattern" persistence so every
+//! channel behaves identically.
+
+use crate::brain::agent::ToolApprovalInfo;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
 
 /// Check config-level approval policy.
 /// Returns `Some((true, true))` when the policy auto-approves, `None` otherwise.
-pub fn check_approval_policy() -> Option<(bool, bool)> {
+/// Prompts requested by an `ask` permission rule always get `None`.
+pub fn check_approval_policy(info: &ToolApprovalInfo) -> Option<(bool, bool)> {
+    if let Some(rule) = &info.ask_rule {
+        tracing::debug!("Permission rule '{}' asks — prompting", rule);
+        return None;
+    }
     match crate::config::Config::load() {
         Ok(cfg) => match cfg.agent.approval_policy.as_str() {
             "auto-always" | "auto-session" => {
@@ -39,3 +49,59 @@ pub fn persist_auto_always_policy() {
         Err(e) => tracing::error!("Failed to persist approval_policy to config.toml: {}", e),
     }
 }
+
+/// Persist `"allow <spec>"` to `[permissions]` in config.toml — ahead of
+/// the `ask` rule that prompted (`before`), if any — and apply it right away.
+pub fn persist_allow_rule(spec: &str, before: Option<&str>) {
+    let mut config = match crate::config::Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("Failed to load config to add permission rule: {}", e);
+            return;
+        }
+    };
+    let Some(rules) =
+        crate::utils::permissions::with_allow_rule(&config.permissions.rules, spec, before)
+    else {
+        return;
+    };
+    match crate::config::Config::write_array("permissions", "rules", &rules) {
+        Ok(_) => tracing::info!("Persisted permission rule 'allow {}' to config.toml", spec),
+        Err(e) => tracing::error!("Failed to persist permission rule to config.toml: {}", e),
+    }
+    config.permissions.rules = rules;
+    crate::utils::permissions::configure(&config.permissions);
+}
+
+/// "Always allow this pattern" offers on prompts still waiting for an
+/// answer, as `(suggested_rule, ask_rule)` by approval id — for channels
+/// whose button handlers only see the id.
+static OFFERED: LazyLock<Mutex<HashMap<String, (String, Option<String>)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Remember the rule behind a prompt's "always allow this pattern" button.
+pub fn offer_allow_rule(approval_id: &str, info: &ToolApprovalInfo) {
+    OFFERED.lock().unwrap_or_else(|e| e.into_inner()).insert(
+        approval_id.to_string(),
+        (info.suggested_rule.clone(), info.ask_rule.clone()),
+    );
+}
+
+/// The "always allow this pattern" button was pressed: persist the rule
+/// and return it for the confirmation message.
+pub fn accept_allow_rule(approval_id: &str) -> Option<String> {
+    let (spec, before) = OFFERED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(approval_id)?;
+    persist_allow_rule(&spec, before.as_deref());
+    Some(spec)
+}
+
+/// Forget an offered rule once its prompt is answered or timed out.
+pub fn withdraw_allow_rule(approval_id: &str) {
+    OFFERED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(approval_id);
+}
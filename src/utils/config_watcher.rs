@@ -2,6 +2,7 @@
 //!
 //! Watches `~/.opencrabs/config.toml` and `~/.opencrabs/keys.toml` for changes.
 //! On any modification, re-loads the full `Config` and fires all registered callbacks.
+//! hooks.toml is watched too and re-read on every change.
 //!
 //! Designed to be extended: register any channel state update or command reload
 //! by pushing a `ReloadCallback` via `spawn()`.
@@ -35,6 +36,7 @@ pub fn spawn(callbacks: Vec<ReloadCallback>) -> tokio::task::JoinHandle<()> {
         let config_path = base.join("config.toml");
         let keys_path = base.join("keys.toml");
         let commands_path = base.join("commands.toml");
+        let hooks_path = base.join("hooks.toml");
 
         let (tx, rx) = std::sync::mpsc::channel();
 
@@ -50,7 +52,7 @@ pub fn spawn(callbacks: Vec<ReloadCallback>) -> tokio::task::JoinHandle<()> {
             }
         };
 
-        for path in [&config_path, &keys_path, &commands_path] {
+        for path in [&config_path, &keys_path, &commands_path, &hooks_path] {
             if path.exists()
                 && let Err(e) = watcher.watch(path, RecursiveMode::NonRecursive)
             {
@@ -59,7 +61,7 @@ pub fn spawn(callbacks: Vec<ReloadCallback>) -> tokio::task::JoinHandle<()> {
         }
 
         tracing::info!(
-            "ConfigWatcher: watching config.toml, keys.toml and commands.toml in {:?}",
+            "ConfigWatcher: watching config.toml, keys.toml, commands.toml and hooks.toml in {:?}",
             base
         );
 
@@ -79,6 +81,8 @@ pub fn spawn(callbacks: Vec<ReloadCallback>) -> tokio::task::JoinHandle<()> {
                 }
             }
 
+            crate::brain::hooks::reload();
+
             match Config::load() {
                 Ok(new_config) => {
                     tracing::info!(
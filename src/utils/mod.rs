@@ -7,6 +7,7 @@ pub mod file_extract;
 pub mod image;
 pub mod install;
 pub mod pdf_vision;
+pub mod permissions;
 pub mod providers;
 pub mod retry;
 pub mod sanitize;
@@ -15,7 +16,8 @@ pub mod string;
 mod tool_context;
 
 pub use approval::{
-    check_approval_policy, persist_auto_always_policy, persist_auto_session_policy,
+    accept_allow_rule, check_approval_policy, offer_allow_rule, persist_allow_rule,
+    persist_auto_always_policy, persist_auto_session_policy, withdraw_allow_rule,
 };
 pub use file_extract::{FileContent, classify_file, inject_file_content, process_file_with_vision};
 pub use image::extract_img_markers;
@@ -0,0 +1,388 @@
+//! Permission Rules
+//!
+//! `[permissions] rules` in config.toml is an ordered list of
+//! `"<action> <tool>(<pattern>)"` rules such as `"allow bash(cargo test:*)"`,
+//! `"deny write_file(migrations/**)"` or
+//! `"ask http_request(https://api.github.com/*)"`. The tool loop checks them
+//! before every approval prompt and the first matching rule decides: `allow`
+//! runs the call without asking, `ask` prompts even when the approval policy
+//! would auto-approve, and `deny` blocks the call. Calls no rule matches fall
+//! back to `agent.approval_policy`.
+//!
+//! The pattern is matched against the call's main argument:
+//! - `command`: `prefix:*` matches commands starting with `prefix`, anything
+//!   else is a `*` wildcard over the command. Chains (`&&`, `;`, `|`, ...)
+//!   are decided command by command — `deny` or `ask` for any command
+//!   decides the chain, `allow` needs every command allowed.
+//! - `path` / `file_path` / `notebook_path`: a glob (`*` within a directory,
+//!   `**` across directories), relative to the working directory unless
+//!   absolute.
+//! - `url`: a `*` wildcard over the whole URL.
+//!
+//! A rule without a pattern (`"allow read_file"`) matches every call to the
+//! tool, and the tool name itself may use `*` (`"ask *_send"`).
+
+use crate::config::PermissionsConfig;
+use serde_json::Value;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{LazyLock, RwLock};
+
+/// Rules in effect for the process.
+static PERMISSIONS: LazyLock<RwLock<Permissions>> =
+    LazyLock::new(|| RwLock::new(Permissions::default()));
+
+/// Apply `[permissions]` from config.toml. Called whenever providers are
+/// (re)built so config reloads take effect.
+pub fn configure(config: &PermissionsConfig) {
+    *PERMISSIONS.write().unwrap_or_else(|e| e.into_inner()) = Permissions::new(&config.rules);
+}
+
+/// Rules currently in effect.
+pub fn current() -> Permissions {
+    PERMISSIONS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// What a matching rule does with a tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionAction {
+    /// Run without asking
+    Allow,
+    /// Always prompt, whatever the approval policy
+    Ask,
+    /// Block the call
+    Deny,
+}
+
+impl PermissionAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PermissionAction::Allow => "allow",
+            PermissionAction::Ask => "ask",
+            PermissionAction::Deny => "deny",
+        }
+    }
+}
+
+/// One `"<action> <tool>(<pattern>)"` rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionRule {
+    pub action: PermissionAction,
+    /// Tool name, `*` wildcards allowed
+    pub tool: String,
+    /// Pattern for the call's main argument; `None` matches every call
+    pub pattern: Option<String>,
+}
+
+impl FromStr for PermissionRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (action, spec) = s
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("expected \"<allow|ask|deny> <tool>(<pattern>)\", got {s:?}"))?;
+        let action = match action {
+            "allow" => PermissionAction::Allow,
+            "ask" => PermissionAction::Ask,
+            "deny" => PermissionAction::Deny,
+            other => return Err(format!("unknown action {other:?} in {s:?}")),
+        };
+        let spec = spec.trim();
+        let (tool, pattern) = match spec.split_once('(') {
+            Some((tool, rest)) => {
+                let pattern = rest
+                    .strip_suffix(')')
+                    .ok_or_else(|| format!("missing ')' in {s:?}"))?;
+                if pattern.is_empty() {
+                    return Err(format!("empty pattern in {s:?}"));
+                }
+                (tool.trim(), Some(pattern.to_string()))
+            }
+            None => (spec, None),
+        };
+        if tool.is_empty() || tool.contains(char::is_whitespace) {
+            return Err(format!("invalid tool name in {s:?}"));
+        }
+        Ok(Self {
+            action,
+            tool: tool.to_string(),
+            pattern,
+        })
+    }
+}
+
+impl fmt::Display for PermissionRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.action.as_str(), self.tool)?;
+        if let Some(pattern) = &self.pattern {
+            write!(f, "({pattern})")?;
+        }
+        Ok(())
+    }
+}
+
+impl PermissionRule {
+    /// Whether the rule covers a call to `tool_name` whose main argument
+    /// is `argument` (for a shell chain, one command of it).
+    fn covers(&self, tool_name: &str, argument: Option<&Argument>, cwd: &Path) -> bool {
+        if !wildcard_match(&self.tool, tool_name) {
+            return false;
+        }
+        let Some(pattern) = &self.pattern else {
+            return true;
+        };
+        match argument {
+            Some(Argument::Command(command)) => match pattern.strip_suffix(":*") {
+                Some(prefix) => {
+                    *command == prefix
+                        || command
+                            .strip_prefix(prefix)
+                            .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+                }
+                None => wildcard_match(pattern, command),
+            },
+            Some(Argument::Path(path)) => path_match(pattern, path, cwd),
+            Some(Argument::Url(url)) => wildcard_match(pattern, url),
+            None => false,
+        }
+    }
+}
+
+/// Ordered permission rules.
+#[derive(Debug, Clone, Default)]
+pub struct Permissions {
+    rules: Vec<PermissionRule>,
+}
+
+impl Permissions {
+    /// Parse rules in order. Invalid rules are logged and skipped.
+    pub fn new(rules: &[String]) -> Self {
+        let rules = rules
+            .iter()
+            .filter_map(|rule| {
+                rule.parse()
+                    .map_err(|e| tracing::warn!("Ignoring permission rule: {e}"))
+                    .ok()
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Rule deciding a call, if any: the first rule matching it.
+    ///
+    /// Each command of a shell chain is decided on its own. A `deny` or
+    /// `ask` for any of them decides the chain; `allow` needs every command
+    /// allowed, and no command substitution hiding from the patterns.
+    pub fn evaluate(&self, tool_name: &str, input: &Value, cwd: &Path) -> Option<&PermissionRule> {
+        let first = |argument: Option<&Argument>| {
+            self.rules
+                .iter()
+                .find(|rule| rule.covers(tool_name, argument, cwd))
+        };
+        let argument = Argument::of(input);
+        let Some(Argument::Command(command)) = argument else {
+            return first(argument.as_ref());
+        };
+
+        let mut parts = command_parts(command);
+        if parts.is_empty() {
+            parts.push("");
+        }
+        let decisions: Vec<Option<&PermissionRule>> = parts
+            .into_iter()
+            .map(|part| first(Some(&Argument::Command(part))))
+            .collect();
+        for action in [PermissionAction::Deny, PermissionAction::Ask] {
+            if let Some(rule) = decisions.iter().flatten().find(|r| r.action == action) {
+                return Some(rule);
+            }
+        }
+        let substitution = command.contains("$(") || command.contains('`');
+        let allowed = |rule: &Option<&PermissionRule>| {
+            rule.is_some_and(|r| !(substitution && r.pattern.is_some()))
+        };
+        if decisions.iter().all(allowed) {
+            decisions[0]
+        } else {
+            None
+        }
+    }
+}
+
+/// `rules` with `"allow <spec>"` added where it takes effect: just before
+/// `before` (the `ask` rule that prompted), or at the end when no rule
+/// matched. `None` if the rule is already there or `spec` doesn't parse.
+pub fn with_allow_rule(rules: &[String], spec: &str, before: Option<&str>) -> Option<Vec<String>> {
+    let rule: PermissionRule = format!("allow {spec}").parse().ok()?;
+    let same = |a: &str, b: &PermissionRule| a.parse::<PermissionRule>().is_ok_and(|a| &a == b);
+    if rules.iter().any(|r| same(r, &rule)) {
+        return None;
+    }
+    let position = before
+        .and_then(|before| before.parse::<PermissionRule>().ok())
+        .and_then(|before| rules.iter().position(|r| same(r, &before)))
+        .unwrap_or(rules.len());
+    let mut rules = rules.to_vec();
+    rules.insert(position, rule.to_string());
+    Some(rules)
+}
+
+/// Rule spec (`tool(pattern)`, no action) offered as "always allow this
+/// pattern" for a call: the command's leading words, the file's directory,
+/// or the URL's origin.
+pub fn suggest_rule(tool_name: &str, input: &Value, cwd: &Path) -> String {
+    match Argument::of(input) {
+        Some(Argument::Command(command)) => match command_prefix(command) {
+            Some(prefix) => format!("{tool_name}({prefix}:*)"),
+            None => tool_name.to_string(),
+        },
+        Some(Argument::Path(path)) => {
+            let cwd = normalize(cwd);
+            let dir = resolve(path, &cwd)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| cwd.clone());
+            match dir.strip_prefix(&cwd) {
+                Ok(rel) if rel.as_os_str().is_empty() => format!("{tool_name}(**)"),
+                Ok(rel) => format!("{tool_name}({}/**)", rel.display()),
+                Err(_) => format!("{tool_name}({}/**)", dir.display()),
+            }
+        }
+        Some(Argument::Url(url)) => match url.split_once("://") {
+            Some((scheme, rest)) => {
+                let host = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+                format!("{tool_name}({scheme}://{host}/*)")
+            }
+            None => format!("{tool_name}({url})"),
+        },
+        None => tool_name.to_string(),
+    }
+}
+
+/// The argument of a tool call that rule patterns apply to.
+enum Argument<'a> {
+    Command(&'a str),
+    Path(&'a str),
+    Url(&'a str),
+}
+
+impl<'a> Argument<'a> {
+    fn of(input: &'a Value) -> Option<Self> {
+        let field = |key: &str| input.get(key).and_then(Value::as_str);
+        if let Some(command) = field("command") {
+            return Some(Argument::Command(command));
+        }
+        if let Some(url) = field("url") {
+            return Some(Argument::Url(url));
+        }
+        ["path", "file_path", "notebook_path"]
+            .into_iter()
+            .find_map(field)
+            .map(Argument::Path)
+    }
+}
+
+/// Commands in a shell chain, split at `;`, `|`, `&` and newlines (but not
+/// redirections like `2>&1`). Quoting is ignored, which only ever makes a
+/// chain look longer — safe for both `allow` and `deny`.
+fn command_parts(command: &str) -> Vec<&str> {
+    let bytes = command.as_bytes();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        let redirect = b == b'&' && i > 0 && matches!(bytes[i - 1], b'>' | b'<');
+        if matches!(b, b';' | b'|' | b'&' | b'\n') && !redirect {
+            parts.push(&command[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&command[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Program plus subcommand (`cargo test`, `git status`) of the first
+/// command in a chain; flags and file arguments are left to the wildcard.
+fn command_prefix(command: &str) -> Option<String> {
+    let first = command_parts(command).into_iter().next()?;
+    let mut words = first.split_whitespace();
+    let mut prefix = words.next()?.to_string();
+    if let Some(sub) = words.next()
+        && !sub.starts_with('-')
+        && sub
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        prefix.push(' ');
+        prefix.push_str(sub);
+    }
+    Some(prefix)
+}
+
+/// `*` matches any run of characters; everything else is literal.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut pieces = pattern.split('*');
+    let first = pieces.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut pieces: Vec<&str> = pieces.collect();
+    let Some(last) = pieces.pop() else {
+        // No `*` at all
+        return rest.is_empty();
+    };
+    for piece in pieces {
+        match rest.find(piece) {
+            Some(i) => rest = &rest[i + piece.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+fn path_match(pattern: &str, path: &str, cwd: &Path) -> bool {
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    let pattern = resolve(pattern, cwd);
+    glob::Pattern::new(&pattern.to_string_lossy())
+        .is_ok_and(|p| p.matches_path_with(&resolve(path, cwd), options))
+}
+
+/// Absolute, `~`-expanded and `..`-free form of `path` relative to `cwd`,
+/// so `src/../.env` can't slip past a `src/**` rule.
+fn resolve(path: &str, cwd: &Path) -> PathBuf {
+    normalize(&cwd.join(crate::brain::tools::error::expand_tilde(path)))
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
@@ -13,7 +13,7 @@ pub struct ProviderMeta {
     pub display_name: &'static str,
     /// Config section path (e.g. "providers.anthropic")
     pub config_section: &'static str,
-    /// Whether this provider needs an API key (false for CLI providers)
+    /// Whether this provider needs an API key (false for CLI providers and Ollama)
     pub needs_api_key: bool,
 }
 
@@ -49,6 +49,12 @@ pub const KNOWN_PROVIDERS: &[ProviderMeta] = &[
         config_section: "providers.minimax",
         needs_api_key: true,
     },
+    ProviderMeta {
+        id: "ollama",
+        display_name: "Ollama",
+        config_section: "providers.ollama",
+        needs_api_key: false,
+    },
     ProviderMeta {
         id: "openai",
         display_name: "OpenAI",
@@ -158,6 +164,7 @@ pub fn config_for<'a>(providers: &'a ProviderConfigs, name: &str) -> Option<&'a
         Some("claude-cli") => providers.claude_cli.as_ref(),
         Some("opencode-cli") => providers.opencode_cli.as_ref(),
         Some("qwen") => providers.qwen.as_ref(),
+        Some("ollama") => providers.ollama.as_ref(),
         _ => {
             let custom_name = name.strip_prefix("custom:")?;
             providers.custom.as_ref()?.get(custom_name)
@@ -174,8 +181,8 @@ pub fn configured_providers(providers: &ProviderConfigs) -> Vec<(String, String)
         let is_configured = if meta.needs_api_key {
             cfg.is_some_and(|c| c.api_key.is_some())
         } else {
-            // CLI providers (no API key needed) — always show them.
-            // If the binary isn't installed, the error surfaces when the user selects it.
+            // Keyless providers (CLI, Ollama) — always show them.
+            // If the binary or server isn't there, the error surfaces when the user selects it.
             // This matches TUI behaviour where CLI providers are always listed.
             true
         };